@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand, ValueEnum};
+use savings_tracker_core::models::asset::AssetType;
+use savings_tracker_core::models::event::EventType;
+
+/// Inspect and script encrypted Savings Tracker portfolios.
+#[derive(Debug, Parser)]
+#[command(name = "savings-tracker", version, about)]
+pub struct Cli {
+    /// Portfolio file (.svtk)
+    pub path: PathBuf,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create a new, empty portfolio file
+    Create {
+        /// Default currency (3-letter code)
+        #[arg(long, default_value = "USD")]
+        currency: String,
+
+        /// Overwrite the file if it exists
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Show the file header and what the portfolio holds
+    Info,
+
+    /// Add an event
+    AddEvent {
+        #[arg(value_enum)]
+        event_type: EventTypeArg,
+
+        /// Asset symbol (e.g., BTC, EUR, AAPL)
+        symbol: String,
+
+        /// Units bought, sold, deposited, withdrawn or received
+        amount: f64,
+
+        #[arg(long, value_enum)]
+        asset_type: AssetTypeArg,
+
+        /// Event date (YYYY-MM-DD)
+        #[arg(long)]
+        date: NaiveDate,
+
+        /// Display name (defaults to the symbol)
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// List events, newest first
+    ListEvents {
+        /// Only this asset symbol
+        #[arg(long)]
+        symbol: Option<String>,
+
+        #[arg(long, value_enum)]
+        asset_type: Option<AssetTypeArg>,
+
+        #[arg(long = "type", value_enum)]
+        event_type: Option<EventTypeArg>,
+
+        /// First date included (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last date included (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Also list planned events
+        #[arg(long)]
+        planned: bool,
+    },
+
+    /// Total portfolio value in the default currency
+    Value {
+        /// Valuation date (default: today)
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+
+    /// Gain/loss, returns and holdings
+    Summary {
+        /// Summary date (default: today)
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+
+    /// Daily portfolio values over a date range
+    Chart {
+        /// First day (YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+
+        /// Last day (YYYY-MM-DD)
+        #[arg(long)]
+        to: NaiveDate,
+
+        /// Print CSV (date,value,currency) instead of a table
+        #[arg(long)]
+        csv: bool,
+    },
+
+    /// Export events to stdout or a file
+    ExportEvents {
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Import events from a JSON or CSV export
+    ImportEvents {
+        file: PathBuf,
+
+        /// Input format (default: from the file extension, else JSON)
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+
+    /// Re-encrypt the file with a new password
+    ChangePassword,
+
+    /// Remove cached prices
+    PruneCache {
+        /// Remove prices dated before this day (YYYY-MM-DD)
+        #[arg(long, conflicts_with = "all", required_unless_present = "all")]
+        before: Option<NaiveDate>,
+
+        /// Remove every cached price
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum EventTypeArg {
+    Buy,
+    Sell,
+    Deposit,
+    Withdrawal,
+    Interest,
+}
+
+impl From<EventTypeArg> for EventType {
+    fn from(arg: EventTypeArg) -> Self {
+        match arg {
+            EventTypeArg::Buy => EventType::Buy,
+            EventTypeArg::Sell => EventType::Sell,
+            EventTypeArg::Deposit => EventType::Deposit,
+            EventTypeArg::Withdrawal => EventType::Withdrawal,
+            EventTypeArg::Interest => EventType::Interest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum AssetTypeArg {
+    Crypto,
+    Fiat,
+    Metal,
+    Stock,
+}
+
+impl From<AssetTypeArg> for AssetType {
+    fn from(arg: AssetTypeArg) -> Self {
+        match arg {
+            AssetTypeArg::Crypto => AssetType::Crypto,
+            AssetTypeArg::Fiat => AssetType::Fiat,
+            AssetTypeArg::Metal => AssetType::Metal,
+            AssetTypeArg::Stock => AssetType::Stock,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Csv,
+}
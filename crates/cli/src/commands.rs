@@ -0,0 +1,230 @@
+use std::path::Path;
+
+use chrono::NaiveDate;
+use savings_tracker_core::models::asset::Asset;
+use savings_tracker_core::models::event::{EventFilter, EventType};
+use savings_tracker_core::storage::csv::CsvOptions;
+use savings_tracker_core::storage::format;
+use savings_tracker_core::storage::manager::StorageManager;
+use savings_tracker_core::SavingsTracker;
+
+use crate::args::{Cli, Command, Format};
+use crate::error::CliError;
+
+/// Password for scripts; prompted for when unset.
+const PASSWORD_VAR: &str = "SAVINGS_TRACKER_PASSWORD";
+/// New password for `change-password`; prompted for (twice) when unset.
+const NEW_PASSWORD_VAR: &str = "SAVINGS_TRACKER_NEW_PASSWORD";
+
+/// Run one command against the file at `cli.path`.
+pub fn run(cli: Cli) -> Result<(), CliError> {
+    let path = path_str(&cli.path)?;
+    match cli.command {
+        Command::Create { currency, force } => create(path, currency, force),
+        Command::Info => info(path),
+        Command::AddEvent { event_type, symbol, amount, asset_type, date, name } => {
+            let (mut tracker, password) = open(path)?;
+            let name = name.unwrap_or_else(|| symbol.clone());
+            let asset = Asset::new(symbol, name, asset_type.into());
+            let id = tracker.add_event(event_type.into(), asset, amount, date)?;
+            tracker.save_to_file(path, &password)?;
+            println!("{id}");
+            Ok(())
+        }
+        Command::ListEvents { symbol, asset_type, event_type, from, to, planned } => {
+            let (tracker, _) = open(path)?;
+            let filter = EventFilter {
+                symbol,
+                asset_type: asset_type.map(Into::into),
+                event_type: event_type.map(Into::into),
+                from,
+                to,
+                include_planned: planned,
+                include_deleted: false,
+            };
+            for event in tracker.get_events_matching(&filter) {
+                let planned = if event.is_planned { "\tplanned" } else { "" };
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}{planned}",
+                    event.date,
+                    type_name(&event.event_type),
+                    event.amount,
+                    event.asset.symbol,
+                    event.asset.asset_type,
+                    event.id,
+                );
+            }
+            Ok(())
+        }
+        Command::Value { date } => {
+            let (mut tracker, _) = open(path)?;
+            let value = tracker.get_portfolio_value_blocking(date.unwrap_or_else(today))?;
+            println!("{:.2} {}", value.amount, value.currency);
+            Ok(())
+        }
+        Command::Summary { date } => {
+            let (mut tracker, _) = open(path)?;
+            let summary = tracker.get_portfolio_summary_blocking(date.unwrap_or_else(today))?;
+            let currency = &summary.currency;
+            println!("As of:     {}", summary.as_of_date);
+            println!("Value:     {:.2} {currency}", summary.total_value);
+            println!("Invested:  {:.2} {currency}", summary.total_invested);
+            println!("Returned:  {:.2} {currency}", summary.total_returned);
+            println!("Gain/loss: {:.2} {currency} ({:.2}%)", summary.total_gain_loss, summary.total_return_pct);
+            for holding in &summary.holdings {
+                println!(
+                    "{}\t{}\t{:.2} {currency}\t{:.1}%",
+                    holding.asset.symbol, holding.amount, holding.current_value, holding.allocation_pct,
+                );
+            }
+            Ok(())
+        }
+        Command::Chart { from, to, csv } => {
+            let (mut tracker, _) = open(path)?;
+            let points = tracker.generate_portfolio_chart_blocking(from, to)?;
+            if csv {
+                println!("date,value,currency");
+            }
+            for point in points {
+                if csv {
+                    println!("{},{:.2},{}", point.date, point.portfolio_value, point.currency);
+                } else {
+                    println!("{}\t{:.2} {}", point.date, point.portfolio_value, point.currency);
+                }
+            }
+            Ok(())
+        }
+        Command::ExportEvents { format, output } => {
+            let (tracker, _) = open(path)?;
+            let data = match format {
+                Format::Json => tracker.export_events_to_json()?,
+                Format::Csv => tracker.export_events_to_csv(),
+            };
+            match output {
+                Some(output) => std::fs::write(output, data)?,
+                None => print!("{data}"),
+            }
+            Ok(())
+        }
+        Command::ImportEvents { file, format } => {
+            let format = format.unwrap_or_else(|| match file.extension() {
+                Some(ext) if ext.eq_ignore_ascii_case("csv") => Format::Csv,
+                _ => Format::Json,
+            });
+            let data = std::fs::read_to_string(&file)?;
+            let (mut tracker, password) = open(path)?;
+            let imported = match format {
+                Format::Json => tracker.import_events_from_json(&data)?,
+                Format::Csv => tracker.import_events_from_csv(&data, &CsvOptions::default())?,
+            };
+            tracker.save_to_file(path, &password)?;
+            println!("Imported {imported} events");
+            Ok(())
+        }
+        Command::ChangePassword => {
+            let bytes = std::fs::read(path)?;
+            let password = password("Password: ")?;
+            let mut tracker = SavingsTracker::load_from_bytes(&bytes, &password)?;
+            let new_password = new_password()?;
+            let new_bytes = tracker.change_password(&bytes, &password, &new_password)?;
+            StorageManager::write_atomic(path, &new_bytes)?;
+            println!("Password changed");
+            Ok(())
+        }
+        Command::PruneCache { before, all } => {
+            let (mut tracker, password) = open(path)?;
+            let removed = if all {
+                let entries = tracker.cache_total_entries();
+                tracker.cache_clear();
+                entries
+            } else {
+                // clap requires `--before` without `--all`
+                tracker.cache_prune_before(before.unwrap_or_else(today))
+            };
+            tracker.save_to_file(path, &password)?;
+            println!("Removed {removed} cached prices");
+            Ok(())
+        }
+    }
+}
+
+/// Write a new empty portfolio with `currency` as its default currency.
+fn create(path: &str, currency: String, force: bool) -> Result<(), CliError> {
+    if !force && Path::new(path).exists() {
+        return Err(CliError::Usage(format!(
+            "{path} already exists (use --force to overwrite it)"
+        )));
+    }
+    let password = new_password()?;
+    let mut tracker = SavingsTracker::create_new();
+    tracker.set_default_currency(currency)?;
+    tracker.save_to_file(path, &password)?;
+    println!("Created {path}");
+    Ok(())
+}
+
+/// Print the unencrypted header, then the contents once decrypted.
+fn info(path: &str) -> Result<(), CliError> {
+    let bytes = std::fs::read(path)?;
+    let (header, _) = format::read_file(&bytes)?;
+    println!("Format version: {}", header.version);
+    println!(
+        "KDF:            Argon2id, {} KiB, {} iterations, {} lanes",
+        header.kdf_params.memory_cost, header.kdf_params.time_cost, header.kdf_params.parallelism,
+    );
+    println!("Size:           {} bytes", bytes.len());
+
+    let password = password("Password: ")?;
+    let tracker = SavingsTracker::load_from_bytes(&bytes, &password)?;
+    let today = today();
+    println!("Currency:       {}", tracker.get_settings().default_currency);
+    println!("Events:         {}", tracker.get_events().len());
+    println!("Holdings:       {}", tracker.get_holdings(today).len());
+    println!("Watchlist:      {}", tracker.get_watchlist().len());
+    println!("In trash:       {}", tracker.get_trash().len());
+    println!("Cached prices:  {}", tracker.cache_total_entries());
+    Ok(())
+}
+
+/// Load the portfolio at `path`, returning it with the password to save it.
+fn open(path: &str) -> Result<(SavingsTracker, String), CliError> {
+    let password = password("Password: ")?;
+    let tracker = SavingsTracker::load_from_file(path, &password)?;
+    Ok((tracker, password))
+}
+
+/// The password from the environment, or else prompted for.
+fn password(prompt: &str) -> Result<String, CliError> {
+    match std::env::var(PASSWORD_VAR) {
+        Ok(password) => Ok(password),
+        Err(_) => Ok(rpassword::prompt_password(prompt)?),
+    }
+}
+
+/// A new password from the environment, or else prompted for and confirmed.
+fn new_password() -> Result<String, CliError> {
+    let var = std::env::var(NEW_PASSWORD_VAR).or_else(|_| std::env::var(PASSWORD_VAR));
+    if let Ok(password) = var {
+        return Ok(password);
+    }
+    let password = rpassword::prompt_password("New password: ")?;
+    if rpassword::prompt_password("Repeat new password: ")? != password {
+        return Err(CliError::Usage("Passwords don't match".into()));
+    }
+    Ok(password)
+}
+
+fn path_str(path: &Path) -> Result<&str, CliError> {
+    path.to_str().ok_or_else(|| {
+        CliError::Usage(format!("{} is not valid UTF-8", path.display()))
+    })
+}
+
+/// Lowercase event type, as accepted by `add-event` and `--type`.
+fn type_name(event_type: &EventType) -> String {
+    event_type.to_string().to_lowercase()
+}
+
+fn today() -> NaiveDate {
+    chrono::Utc::now().date_naive()
+}
@@ -0,0 +1,16 @@
+use savings_tracker_core::errors::CoreError;
+use thiserror::Error;
+
+/// Why a command failed.
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error(transparent)]
+    Core(#[from] CoreError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// Arguments or input clap can't reject on its own
+    #[error("{0}")]
+    Usage(String),
+}
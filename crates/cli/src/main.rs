@@ -0,0 +1,27 @@
+//! `savings-tracker` — command-line companion for `.svtk` portfolio files.
+//!
+//! Every command works on one encrypted file and goes through the public
+//! `SavingsTracker` API only. The password is prompted for on the terminal,
+//! or read from `SAVINGS_TRACKER_PASSWORD` (and `SAVINGS_TRACKER_NEW_PASSWORD`
+//! for `change-password`) when set, for scripts.
+
+mod args;
+mod commands;
+mod error;
+
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use args::Cli;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match commands::run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,158 @@
+// ═══════════════════════════════════════════════════════════════════
+// CLI Tests — the savings-tracker binary against temp .svtk files
+// ═══════════════════════════════════════════════════════════════════
+//
+// No network: portfolios only hold USD in a USD portfolio, which needs
+// no prices.
+
+use std::path::{Path, PathBuf};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const PASSWORD: &str = "test-password-123!";
+
+/// The binary with the password set, run on `path`.
+fn cli(path: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("savings-tracker").unwrap();
+    cmd.env("SAVINGS_TRACKER_PASSWORD", PASSWORD)
+        .env_remove("SAVINGS_TRACKER_NEW_PASSWORD")
+        .arg(path);
+    cmd
+}
+
+/// A new portfolio in a temp dir, with a 100 USD deposit on Jan 10 and a
+/// 40 USD withdrawal on Jan 12 2025.
+fn portfolio() -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("portfolio.svtk");
+    cli(&path).args(["create", "--currency", "usd"]).assert().success();
+    for (event_type, amount, date) in [("deposit", "100", "2025-01-10"), ("withdrawal", "40", "2025-01-12")] {
+        cli(&path)
+            .args(["add-event", event_type, "USD", amount, "--asset-type", "fiat", "--date", date])
+            .assert()
+            .success();
+    }
+    (dir, path)
+}
+
+#[test]
+fn test_create_and_info() {
+    let (_dir, path) = portfolio();
+
+    cli(&path)
+        .arg("info")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Format version: "))
+        .stdout(predicate::str::contains("Currency:       USD"))
+        .stdout(predicate::str::contains("Events:         2"));
+
+    // An existing file is only replaced with --force
+    cli(&path)
+        .arg("create")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+}
+
+#[test]
+fn test_list_events_with_filters() {
+    let (_dir, path) = portfolio();
+
+    let output = cli(&path).arg("list-events").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("2025-01-12\twithdrawal\t40\tUSD\tFiat\t"));
+    assert!(lines[1].starts_with("2025-01-10\tdeposit\t100\tUSD\tFiat\t"));
+
+    cli(&path)
+        .args(["list-events", "--type", "deposit", "--from", "2025-01-01"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("deposit").and(predicate::str::contains("withdrawal").not()));
+    cli(&path)
+        .args(["list-events", "--symbol", "BTC"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn test_value_and_chart_csv() {
+    let (_dir, path) = portfolio();
+
+    cli(&path)
+        .args(["value", "--date", "2025-01-11"])
+        .assert()
+        .success()
+        .stdout("100.00 USD\n");
+    cli(&path)
+        .args(["chart", "--from", "2025-01-11", "--to", "2025-01-12", "--csv"])
+        .assert()
+        .success()
+        .stdout("date,value,currency\n2025-01-11,100.00,USD\n2025-01-12,60.00,USD\n");
+}
+
+#[test]
+fn test_export_import_round_trip() {
+    let (dir, path) = portfolio();
+    let export = dir.path().join("events.csv");
+    cli(&path)
+        .args(["export-events", "--format", "csv", "--output"])
+        .arg(&export)
+        .assert()
+        .success();
+
+    let copy = dir.path().join("copy.svtk");
+    cli(&copy).arg("create").assert().success();
+    cli(&copy)
+        .arg("import-events")
+        .arg(&export)
+        .assert()
+        .success()
+        .stdout("Imported 2 events\n");
+    cli(&copy)
+        .args(["value", "--date", "2025-01-12"])
+        .assert()
+        .success()
+        .stdout("60.00 USD\n");
+}
+
+#[test]
+fn test_change_password() {
+    let (dir, path) = portfolio();
+
+    cli(&path)
+        .arg("change-password")
+        .env("SAVINGS_TRACKER_NEW_PASSWORD", "new-password")
+        .assert()
+        .success();
+    // Replaced through a temporary file, which is gone
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    cli(&path)
+        .arg("list-events")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("wrong password"));
+    cli(&path)
+        .arg("list-events")
+        .env("SAVINGS_TRACKER_PASSWORD", "new-password")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_prune_cache_requires_a_cutoff() {
+    let (_dir, path) = portfolio();
+
+    cli(&path).arg("prune-cache").assert().failure();
+    cli(&path)
+        .args(["prune-cache", "--all"])
+        .assert()
+        .success()
+        .stdout("Removed 0 cached prices\n");
+}
@@ -0,0 +1,27 @@
+//! Parse arbitrary bytes as an .svtk file: `format::read_file` must reject
+//! anything malformed with an error, never panic or allocate by a length
+//! read from the header.
+//!
+//! Run with `cargo +nightly fuzz run read_file` from `crates/core`; the
+//! seed corpus in `fuzz/corpus/read_file` holds minimal valid and invalid
+//! files.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use savings_tracker_core::storage::format;
+
+fuzz_target!(|data: &[u8]| {
+    // A limit below most inputs, so both sides of the size check are hit
+    let _ = format::read_file_with_limit(data, 256);
+
+    if let Ok((header, ciphertext)) = format::read_file(data) {
+        assert_eq!(ciphertext.len() as u64, header.ciphertext_len);
+        assert!((1..=format::CURRENT_VERSION).contains(&header.version));
+        // Ciphertext standing in for a decrypted payload
+        if let Ok((version, payload)) = format::read_payload(header.version, ciphertext) {
+            assert!((1..=format::PAYLOAD_VERSION).contains(&version));
+            assert!(payload.len() <= ciphertext.len());
+        }
+    }
+});
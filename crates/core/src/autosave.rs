@@ -0,0 +1,116 @@
+//! Debounced background saving for `SavingsTracker::set_autosave`.
+//!
+//! Each mutation lends the portfolio to a worker thread (see
+//! `SharedPortfolio`) and signals it; the worker saves the portfolio last
+//! lent once no signal has arrived for the debounce window, so a burst of
+//! edits is written once and nothing is copied per edit. Failures can't be
+//! returned to anyone and are recorded as `AutosaveWarning`s instead.
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::warning::AutosaveWarning;
+use crate::services::price_warnings::WarningLog;
+use crate::shared_portfolio::Published;
+use crate::storage::manager::StorageManager;
+use crate::storage::secret::SecretString;
+
+enum Message {
+    /// The portfolio changed and was lent again
+    Changed,
+    /// Save what is pending and exit
+    Stop,
+}
+
+/// Time and tracker revision of the last successful autosave.
+pub(crate) type LastAutosave = Arc<Mutex<Option<(DateTime<Utc>, u64)>>>;
+
+/// Called on the worker after each autosave, successful or not (see
+/// `SavingsTracker::set_autosave_hook`).
+pub(crate) type AutosaveHook = Arc<Mutex<Option<Box<dyn Fn() + Send>>>>;
+
+pub(crate) struct Autosaver {
+    sender: Sender<Message>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Autosaver {
+    /// Start a worker saving the portfolio lent in `published` to `path`
+    /// `debounce` after the last change, recording successes in `saved` and
+    /// failures in `warnings`, then calling `hook`.
+    pub(crate) fn start(
+        path: String,
+        password: SecretString,
+        debounce: Duration,
+        published: Published,
+        saved: LastAutosave,
+        warnings: Arc<WarningLog<AutosaveWarning>>,
+        hook: AutosaveHook,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let worker = std::thread::spawn(move || {
+            // Taking the portfolio lets the tracker change it in place again
+            // once the save is done. Returns whether a save is still pending:
+            // the tracker took the portfolio back and will lend it again.
+            let save = || {
+                let lent = {
+                    let mut lent = published.lock().unwrap_or_else(|e| e.into_inner());
+                    if lent.taken_back {
+                        return true;
+                    }
+                    lent.portfolio.take()
+                };
+                let Some((portfolio, revision)) = lent else {
+                    return false;
+                };
+                let result = StorageManager::save_to_file(&portfolio, &path, password.expose());
+                let now = Utc::now();
+                match result {
+                    Ok(()) => *saved.lock().unwrap_or_else(|e| e.into_inner()) = Some((now, revision)),
+                    Err(error) => warnings.push(AutosaveWarning { at: now, path: path.clone(), error }),
+                }
+                if let Some(hook) = &*hook.lock().unwrap_or_else(|e| e.into_inner()) {
+                    hook();
+                }
+                false
+            };
+            let mut pending = false;
+            loop {
+                let message = match pending {
+                    true => receiver.recv_timeout(debounce),
+                    false => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match message {
+                    // A newer change restarts the window
+                    Ok(Message::Changed) => pending = true,
+                    Err(RecvTimeoutError::Timeout) => pending = save(),
+                    Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => {
+                        save();
+                        break;
+                    }
+                }
+            }
+        });
+        Self { sender, worker: Some(worker) }
+    }
+
+    /// Save the portfolio lent last once the window passes.
+    pub(crate) fn schedule(&self) {
+        // The worker only exits when stopped
+        let _ = self.sender.send(Message::Changed);
+    }
+}
+
+impl Drop for Autosaver {
+    /// Save any pending change, then stop the worker.
+    fn drop(&mut self) {
+        let _ = self.sender.send(Message::Stop);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
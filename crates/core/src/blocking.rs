@@ -0,0 +1,78 @@
+//! Blocking wrappers of the async `SavingsTracker` methods, for callers
+//! without an async runtime of their own (CLI tools, scripts).
+//!
+//! Each wrapper runs its future to completion on a current-thread tokio
+//! runtime kept per calling thread (reqwest needs tokio's I/O and timer
+//! drivers on native builds). They must not be called from inside an async
+//! runtime: await the async method there instead.
+
+use std::future::Future;
+
+use chrono::NaiveDate;
+
+use crate::errors::CoreError;
+use crate::models::analytics::PortfolioSummary;
+use crate::models::asset::Asset;
+use crate::models::chart::ChartDataPoint;
+use crate::models::progress::RefreshReport;
+use crate::models::valuation::Money;
+use crate::SavingsTracker;
+
+thread_local! {
+    static RUNTIME: tokio::runtime::Runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build the blocking runtime");
+}
+
+/// Run `future` to completion on this thread's runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    RUNTIME.with(|runtime| runtime.block_on(future))
+}
+
+impl SavingsTracker {
+    /// Blocking `get_portfolio_value`.
+    pub fn get_portfolio_value_blocking(&mut self, date: NaiveDate) -> Result<Money, CoreError> {
+        block_on(self.get_portfolio_value(date))
+    }
+
+    /// Blocking `get_portfolio_summary`.
+    pub fn get_portfolio_summary_blocking(
+        &mut self,
+        date: NaiveDate,
+    ) -> Result<PortfolioSummary, CoreError> {
+        block_on(self.get_portfolio_summary(date))
+    }
+
+    /// Blocking `get_asset_price`.
+    pub fn get_asset_price_blocking(
+        &mut self,
+        asset: &Asset,
+        date: NaiveDate,
+    ) -> Result<f64, CoreError> {
+        block_on(self.get_asset_price(asset, date))
+    }
+
+    /// Blocking `generate_portfolio_chart`.
+    pub fn generate_portfolio_chart_blocking(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        block_on(self.generate_portfolio_chart(from, to))
+    }
+
+    /// Blocking `refresh_prices`.
+    pub fn refresh_prices_blocking(&mut self, force: bool) -> Result<RefreshReport, CoreError> {
+        block_on(self.refresh_prices(force))
+    }
+
+    /// Blocking `prefetch_prices`.
+    pub fn prefetch_prices_blocking(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<(), CoreError> {
+        block_on(self.prefetch_prices(from, to))
+    }
+}
@@ -2,14 +2,21 @@ use thiserror::Error;
 
 /// Unified error type for the entire savings-tracker-core library.
 /// Every public function returns `Result<T, CoreError>`.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum CoreError {
     // ── Storage / File ──────────────────────────────────────────────
     #[error("Invalid file format: {0}")]
     InvalidFileFormat(String),
 
-    #[error("Unsupported file version: {0}")]
-    UnsupportedVersion(u16),
+    /// `found` is newer than the `supported` version this library reads
+    /// (a file saved by a newer version of the app).
+    #[error("Unsupported file version: {found} (supported up to {supported})")]
+    UnsupportedVersion { found: u16, supported: u16 },
+
+    /// The file is `size` bytes, more than the `limit` it may have (see
+    /// `LoadOptions::max_file_size`).
+    #[error("File too large: {size} bytes (limit {limit} bytes)")]
+    FileTooLarge { size: u64, limit: u64 },
 
     #[error("Encryption failed: {0}")]
     Encryption(String),
@@ -31,15 +38,24 @@ pub enum CoreError {
     #[error("API error ({provider}): {message}")]
     Api {
         provider: String,
+        /// HTTP status code of the failed response, if one was received;
+        /// 408 for requests that timed out (see `CoreError::timed_out`).
+        status: Option<u16>,
         message: String,
     },
 
+    #[error("Invalid or missing API key for {provider}")]
+    InvalidApiKey { provider: String },
+
     #[error("Network error: {0}")]
     Network(String),
 
     #[error("No provider available for asset type: {0}")]
     NoProvider(String),
 
+    #[error("Could not map symbol {symbol} to a {provider} asset id (set one with set_symbol_override)")]
+    SymbolNotMapped { provider: String, symbol: String },
+
     // ── Business Logic ──────────────────────────────────────────────
     #[error("Event validation failed: {0}")]
     ValidationError(String),
@@ -47,12 +63,79 @@ pub enum CoreError {
     #[error("Event not found: {0}")]
     EventNotFound(String),
 
+    #[error("Alert not found: {0}")]
+    AlertNotFound(String),
+
+    #[error("Corporate action not found: {0}")]
+    CorporateActionNotFound(String),
+
+    #[error("Accrual rule not found: {0}")]
+    AccrualRuleNotFound(String),
+
+    #[error("Reminder not found for event: {0}")]
+    ReminderNotFound(String),
+
     #[error("Price not available for {symbol} in {currency} on {date}")]
     PriceNotAvailable {
         symbol: String,
         currency: String,
         date: String,
     },
+
+    #[error("Inflation data not available for {country} on {date}")]
+    InflationDataUnavailable { country: String, date: String },
+
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+impl CoreError {
+    /// Returns `true` if the error is likely temporary and the operation
+    /// may succeed when retried (network failures, timeouts, HTTP 429 / 5xx).
+    ///
+    /// Validation, decryption, not-found and invalid-key errors are permanent.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            CoreError::Network(_) => true,
+            CoreError::Api {
+                status: Some(status),
+                ..
+            } => *status == 408 || *status == 429 || (500..600).contains(status),
+            _ => false,
+        }
+    }
+
+    /// A `provider` request that got no response within its timeout:
+    /// `CoreError::Api` with status 408 (Request Timeout), a transient error.
+    pub fn timed_out(provider: &str) -> Self {
+        CoreError::Api {
+            provider: provider.to_string(),
+            status: Some(408),
+            message: "Request timed out".to_string(),
+        }
+    }
+
+    /// Returns `true` if a provider request timed out (see `timed_out`).
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, CoreError::Api { status: Some(408), .. })
+    }
+
+    /// A `provider` request refused because the provider's daily request
+    /// limit is used up: `CoreError::Api` with status 429 (Too Many
+    /// Requests), a transient error.
+    pub fn rate_limited(provider: &str) -> Self {
+        CoreError::Api {
+            provider: provider.to_string(),
+            status: Some(429),
+            message: "Daily request limit reached".to_string(),
+        }
+    }
+
+    /// Returns `true` if a provider refused a request as rate limited, or
+    /// its daily limit was reached (see `rate_limited`).
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, CoreError::Api { status: Some(429), .. })
+    }
 }
 
 // ── Conversion helpers (From impls) ─────────────────────────────────
@@ -0,0 +1,188 @@
+//! Locale-aware display formatting of amounts, percentages and dates.
+//!
+//! Shared by the frontends so every screen renders values the same way.
+//! The conventions of the supported locales are hand-written tables rather
+//! than ICU data, which keeps the WASM build small.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Non-breaking space, used between a number and its unit and as the
+/// Polish thousands separator so values never wrap mid-number.
+const NBSP: char = '\u{a0}';
+
+/// Shown for NaN and infinite values.
+const NOT_AVAILABLE: &str = "n/a";
+
+/// Display conventions for numbers and dates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    /// English (US): 1,234.56 / $1,234.56 / 01/15/2025
+    #[default]
+    En,
+    /// Polish: 1234,56 / 12 345,67 zł / 15.01.2025
+    Pl,
+    /// German: 1.234,56 / 1.234,56 € / 15.01.2025
+    De,
+}
+
+impl Locale {
+    fn decimal_separator(&self) -> char {
+        match self {
+            Locale::En => '.',
+            Locale::Pl | Locale::De => ',',
+        }
+    }
+
+    fn group_separator(&self) -> char {
+        match self {
+            Locale::En => ',',
+            Locale::Pl => NBSP,
+            Locale::De => '.',
+        }
+    }
+
+    /// Smallest integer part that gets grouped: Polish leaves four-digit
+    /// numbers ungrouped (1234 but 12 345).
+    fn min_grouped_digits(&self) -> usize {
+        match self {
+            Locale::En | Locale::De => 4,
+            Locale::Pl => 5,
+        }
+    }
+
+    /// Symbol of `currency` in this locale, if it has one; other currencies
+    /// are shown by their code.
+    fn currency_symbol(&self, currency: &str) -> Option<&'static str> {
+        match (currency, self) {
+            ("USD", Locale::En | Locale::De) => Some("$"),
+            ("PLN", Locale::Pl) => Some("zł"),
+            ("EUR", _) => Some("€"),
+            ("GBP", _) => Some("£"),
+            ("JPY", _) => Some("¥"),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Locale::En => write!(f, "en"),
+            Locale::Pl => write!(f, "pl"),
+            Locale::De => write!(f, "de"),
+        }
+    }
+}
+
+/// Decimal places shown for amounts in `currency`: `(min, max)`.
+///
+/// Currencies without minor units show none, cryptocurrencies up to 8 and
+/// everything else 2. Case-insensitive.
+pub fn currency_decimals(currency: &str) -> (usize, usize) {
+    match currency.to_ascii_uppercase().as_str() {
+        "JPY" | "KRW" | "VND" | "CLP" | "ISK" => (0, 0),
+        "BTC" | "ETH" | "LTC" | "XMR" | "SOL" | "DOGE" => (2, 8),
+        _ => (2, 2),
+    }
+}
+
+/// `value` with thousands separators and between `min_decimals` and
+/// `max_decimals` decimal places (trailing zeros beyond the minimum are
+/// dropped), e.g. "1,234.5" or "1.234,50".
+pub fn format_number(
+    value: f64,
+    min_decimals: usize,
+    max_decimals: usize,
+    locale: Locale,
+) -> String {
+    if !value.is_finite() {
+        return NOT_AVAILABLE.to_string();
+    }
+    let max_decimals = max_decimals.max(min_decimals);
+    let digits = format!("{:.*}", max_decimals, value.abs());
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((&digits, ""));
+    let keep = frac_part.trim_end_matches('0').len().max(min_decimals);
+    let frac_part = &frac_part[..keep];
+
+    let mut out = String::new();
+    // Rounded to zero: no "-0.00"
+    if value < 0.0 && digits.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+        out.push('-');
+    }
+    let group = int_part.len() >= locale.min_grouped_digits();
+    for (i, digit) in int_part.chars().enumerate() {
+        if group && i > 0 && (int_part.len() - i) % 3 == 0 {
+            out.push(locale.group_separator());
+        }
+        out.push(digit);
+    }
+    if !frac_part.is_empty() {
+        out.push(locale.decimal_separator());
+        out.push_str(frac_part);
+    }
+    out
+}
+
+/// `value` rounded half away from zero to `decimals` decimal places.
+/// Values too large to scale are returned unchanged.
+pub fn round_to(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    let rounded = (value * factor).round() / factor;
+    if rounded.is_finite() {
+        rounded
+    } else {
+        value
+    }
+}
+
+/// Amount of an asset with up to `decimals` decimal places and no trailing
+/// zeros (e.g. "0.5" or "1,234.5678"); see `AssetType::default_precision`.
+pub fn format_amount(value: f64, decimals: u32, locale: Locale) -> String {
+    format_number(value, 0, decimals as usize, locale)
+}
+
+/// Amount in `currency` (an ISO code or crypto ticker, e.g. "USD", "BTC"):
+/// "$1,234.56" and "PLN 1,234.56" in English, "1 234,56 zł" in Polish,
+/// "1.234,56 €" in German. Decimal places follow `currency_decimals`.
+pub fn format_money(value: f64, currency: &str, locale: Locale) -> String {
+    let currency = currency.trim().to_ascii_uppercase();
+    let (min_decimals, max_decimals) = currency_decimals(&currency);
+    let number = format_number(value, min_decimals, max_decimals, locale);
+    if !value.is_finite() {
+        return number;
+    }
+    match (locale, locale.currency_symbol(&currency)) {
+        (Locale::En, Some(symbol)) => match number.strip_prefix('-') {
+            Some(abs) => format!("-{symbol}{abs}"),
+            None => format!("{symbol}{number}"),
+        },
+        (Locale::En, None) => match number.strip_prefix('-') {
+            Some(abs) => format!("-{currency}{NBSP}{abs}"),
+            None => format!("{currency}{NBSP}{number}"),
+        },
+        (_, symbol) => format!("{number}{NBSP}{}", symbol.unwrap_or(&currency)),
+    }
+}
+
+/// Percentage with 2 decimals, `value` in percent (12.5 → "12.50%";
+/// "12,50 %" in German).
+pub fn format_pct(value: f64, locale: Locale) -> String {
+    let number = format_number(value, 2, 2, locale);
+    if !value.is_finite() {
+        return number;
+    }
+    match locale {
+        Locale::En | Locale::Pl => format!("{number}%"),
+        Locale::De => format!("{number}{NBSP}%"),
+    }
+}
+
+/// Date in the locale's numeric form: "01/15/2025" in English,
+/// "15.01.2025" in Polish and German.
+pub fn format_date(date: NaiveDate, locale: Locale) -> String {
+    match locale {
+        Locale::En => date.format("%m/%d/%Y").to_string(),
+        Locale::Pl | Locale::De => date.format("%d.%m.%Y").to_string(),
+    }
+}
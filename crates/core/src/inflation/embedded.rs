@@ -0,0 +1,127 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::errors::CoreError;
+use crate::models::inflation::CpiPoint;
+
+use super::traits::InflationProvider;
+
+/// CPI table compiled into the library (see `data/cpi.csv`).
+const EMBEDDED_CPI_CSV: &str = include_str!("data/cpi.csv");
+
+/// Built-in CPI source backed by a small embedded table for US, EU and PL.
+///
+/// - No network access: works offline and on every platform.
+/// - The table can be replaced with a newer data file (`from_csv`) or
+///   extended month by month (`set_override`).
+#[derive(Debug, Clone)]
+pub struct EmbeddedCpiProvider {
+    /// country → (year, month) → index
+    table: HashMap<String, BTreeMap<(i32, u32), f64>>,
+}
+
+impl EmbeddedCpiProvider {
+    /// Create a provider with the embedded table.
+    pub fn new() -> Self {
+        Self::from_csv(EMBEDDED_CPI_CSV).expect("embedded CPI table is valid")
+    }
+
+    /// Create a provider with no data (e.g., to hold manual values only).
+    pub fn empty() -> Self {
+        Self {
+            table: HashMap::new(),
+        }
+    }
+
+    /// Create a provider from a CPI data file in the embedded format:
+    /// `country,YYYY-MM,index` per line; blank lines and `#` comments are ignored.
+    pub fn from_csv(data: &str) -> Result<Self, CoreError> {
+        let mut provider = Self::empty();
+        provider.load_csv(data)?;
+        Ok(provider)
+    }
+
+    /// Merge a CPI data file into the table; its values replace existing months.
+    pub fn load_csv(&mut self, data: &str) -> Result<(), CoreError> {
+        for (line_no, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (country, year, month, index) = Self::parse_line(line)
+                .ok_or_else(|| {
+                    CoreError::ValidationError(format!(
+                        "Invalid CPI data on line {}: '{line}' (expected country,YYYY-MM,index)",
+                        line_no + 1
+                    ))
+                })?;
+            self.set_override(country, year, month, index)?;
+        }
+        Ok(())
+    }
+
+    /// Set (or replace) the CPI value for one month of a country.
+    pub fn set_override(
+        &mut self,
+        country: &str,
+        year: i32,
+        month: u32,
+        index: f64,
+    ) -> Result<(), CoreError> {
+        if !(1..=12).contains(&month) {
+            return Err(CoreError::ValidationError(format!(
+                "Invalid CPI month {month}: must be between 1 and 12"
+            )));
+        }
+        if !index.is_finite() || index <= 0.0 {
+            return Err(CoreError::ValidationError(format!(
+                "Invalid CPI index {index}: must be a positive number"
+            )));
+        }
+        self.table
+            .entry(country.trim().to_uppercase())
+            .or_default()
+            .insert((year, month), index);
+        Ok(())
+    }
+
+    fn parse_line(line: &str) -> Option<(&str, i32, u32, f64)> {
+        let mut fields = line.split(',').map(str::trim);
+        let country = fields.next().filter(|c| !c.is_empty())?;
+        let (year, month) = fields.next()?.split_once('-')?;
+        let index = fields.next()?.parse().ok()?;
+        if fields.next().is_some() {
+            return None;
+        }
+        Some((country, year.parse().ok()?, month.parse().ok()?, index))
+    }
+}
+
+impl Default for EmbeddedCpiProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InflationProvider for EmbeddedCpiProvider {
+    fn name(&self) -> &str {
+        "Embedded CPI"
+    }
+
+    fn countries(&self) -> Vec<String> {
+        let mut countries: Vec<String> = self.table.keys().cloned().collect();
+        countries.sort();
+        countries
+    }
+
+    fn monthly_cpi(&self, country: &str) -> Vec<CpiPoint> {
+        self.table
+            .get(&country.to_uppercase())
+            .map(|months| {
+                months
+                    .iter()
+                    .map(|(&(year, month), &index)| CpiPoint { year, month, index })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,19 @@
+use crate::models::inflation::CpiPoint;
+
+/// Trait abstraction for consumer price index sources.
+///
+/// CPI data is published monthly and changes rarely, so providers are
+/// synchronous: an implementation backed by a remote API should fetch
+/// its series up front and serve them from memory.
+pub trait InflationProvider: Send + Sync {
+    /// Human-readable name of this provider (for logs/errors).
+    fn name(&self) -> &str;
+
+    /// Country codes this provider has data for (e.g., "US", "EU", "PL").
+    fn countries(&self) -> Vec<String>;
+
+    /// Known monthly CPI values for a country, sorted by (year, month).
+    /// Months may be missing; callers interpolate between known values.
+    /// Returns an empty Vec for unknown countries.
+    fn monthly_cpi(&self, country: &str) -> Vec<CpiPoint>;
+}
@@ -1,50 +1,147 @@
+#[cfg(all(feature = "blocking", target_arch = "wasm32"))]
+compile_error!("the `blocking` feature is not available on wasm32; use the async methods");
+
+#[cfg(not(target_arch = "wasm32"))]
+mod autosave;
+#[cfg(feature = "blocking")]
+mod blocking;
 pub mod errors;
+pub mod formatting;
+pub mod inflation;
 pub mod models;
 pub mod providers;
 pub mod services;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod session;
+mod shared_portfolio;
 pub mod storage;
+#[cfg(feature = "test-utils")]
+pub mod testing;
 
 use chrono::NaiveDate;
+use formatting::Locale;
+use inflation::traits::InflationProvider;
 use models::{
-    analytics::PortfolioSummary,
-    asset::{Asset, AssetType},
-    chart::ChartDataPoint,
-    event::{Event, EventSortOrder, EventType},
-    portfolio::Portfolio,
-    settings::Settings,
+    accrual::AccrualRule,
+    alert::{Alert, AlertCondition},
+    analytics::{
+        AnnualReport, ChangePeriod, IncomeProjection, PortfolioSummary, PriceStatus,
+        SinceSummary, Statistics, SummaryOptions, ValueChange,
+    },
+    asset::{Asset, AssetMetadata, AssetType, MetalUnit},
+    chart::{AssetChart, AssetChartOptions, ChartDataPoint, GroupChart, PortfolioChartOptions},
+    corporate_action::{CorporateAction, CorporateActionType, KnownSplit},
+    coverage::CacheCoverage,
+    diagnostics::PriceDiagnostic,
+    diff::PortfolioDiff,
+    event::{
+        newest_first, Event, EventChanges, EventFilter, EventGroup, EventSortOrder, EventType,
+        EventsIter, GroupGranularity, RecordedValue,
+    },
+    holdings::HoldingsOptions,
+    integrity::{IntegrityReport, RepairOptions},
+    portfolio::{ImportIdPolicy, ImportReport, MergeOptions, Portfolio},
+    preview::{EventImpact, EventPreview},
+    price::{CachedPrice, PriceCache, PriceSource, MANUAL_SOURCE},
+    progress::{CancellationToken, Progress, RefreshReport},
+    reminder::Reminder,
+    search::SearchOptions,
+    settings::{Settings, TrashPolicy, UnpriceableAssetPolicy, ValidationPolicy, WeekStart},
+    simulation::SimulationResult,
+    snapshot::{AssetValue, ValueSnapshot},
+    sync::{Resolution, SyncConflict, SyncReport, ThreeWayMerge},
+    tax::{CostBasisMethod, TaxLot},
+    usage::ProviderUsage,
+    warning::PriceWarning,
+    valuation::{CachedHoldingValue, CachedValuation, FailedValuation, Money, PortfolioValuation},
+    watchlist::WatchlistQuote,
 };
-use providers::registry::PriceProviderRegistry;
+use providers::http;
+use providers::registry::{PriceProviderRegistry, ProviderInfo, BUILTIN_PROVIDER_IDS};
+use providers::traits::PriceProvider;
 use services::{
-    analytics_service::AnalyticsService,
+    alert_service::AlertService, analytics_service::AnalyticsService,
+    api_usage::ApiUsageTracker,
     chart_service::ChartService, currency_service::CurrencyService,
-    portfolio_service::PortfolioService, price_service::PriceService,
+    inflation_service::InflationService,
+    portfolio_service::PortfolioService,
+    price_service::{resolve_cached, PriceFallback, PriceService},
+    price_diagnostics::PriceDiagnosticLog,
+    price_warnings::PriceWarningLog,
+    response_cache::{MemoryResponseCache, ResponseCache},
+    simulation_service::SimulationService,
 };
-use std::collections::HashMap;
-use storage::manager::StorageManager;
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+use storage::csv::CsvOptions;
+use storage::manager::{LoadOptions, StorageManager};
 
 use errors::CoreError;
 
-/// Maximum chart date range in days (10 years).
-const MAX_CHART_RANGE_DAYS: i64 = 3650;
-
 /// Main entry point for the Savings Tracker core library.
 /// Holds the portfolio state and all services needed to operate on it.
 #[must_use]
 pub struct SavingsTracker {
-    portfolio: Portfolio,
+    /// Shared with the autosave worker, which saves it without a copy
+    portfolio: shared_portfolio::SharedPortfolio,
     portfolio_service: PortfolioService,
     price_service: PriceService,
     chart_service: ChartService,
     currency_service: CurrencyService,
     analytics_service: AnalyticsService,
+    alert_service: AlertService,
+    inflation_service: InflationService,
+    simulation_service: SimulationService,
+    /// Providers registered by the embedding app; re-added on every registry rebuild.
+    custom_providers: PriceProviderRegistry,
+    /// Whether the built-in providers are added after the custom ones.
+    use_default_providers: bool,
+    /// Short-lived memo of provider responses; survives registry rebuilds.
+    response_cache: Arc<dyn ResponseCache>,
+    /// Requests sent to each provider; survives registry rebuilds and is
+    /// copied to `Portfolio::api_usage` when saving.
+    api_usage: Arc<ApiUsageTracker>,
+    /// Provider quotes rejected by the price sanity checks; survives registry
+    /// rebuilds. Not saved.
+    price_warnings: Arc<PriceWarningLog>,
+    /// Recent provider attempts, fallbacks and cache hits of the price
+    /// service; survives registry rebuilds. Not saved.
+    price_diagnostics: Arc<PriceDiagnosticLog>,
+    /// Event conflicts of the last `three_way_merge` not yet resolved. Not saved.
+    sync_conflicts: Vec<SyncConflict>,
+    /// Events check of the loaded file (see `load_integrity_report`). Not saved.
+    load_report: IntegrityReport,
     /// Tracks whether any mutation has occurred since the last save/load.
     dirty: bool,
+    /// Bumped by every mutation (and price refresh); results memoized at an
+    /// older revision are stale. Not saved.
+    revision: u64,
+    /// Last few portfolio summaries by (date, currency, revision), oldest
+    /// first. Not saved.
+    summary_memo: Vec<(NaiveDate, String, u64, PortfolioSummary)>,
+    /// Background saver set by `set_autosave`. Not saved.
+    #[cfg(not(target_arch = "wasm32"))]
+    autosave: Option<autosave::Autosaver>,
+    /// Time and revision of the last autosave; survives `disable_autosave`.
+    #[cfg(not(target_arch = "wasm32"))]
+    last_autosave: autosave::LastAutosave,
+    /// Autosaves that failed. Not saved.
+    #[cfg(not(target_arch = "wasm32"))]
+    autosave_warnings: Arc<services::price_warnings::WarningLog<models::warning::AutosaveWarning>>,
+    /// Set by `set_autosave_hook`; survives `disable_autosave`. Not saved.
+    #[cfg(not(target_arch = "wasm32"))]
+    autosave_hook: autosave::AutosaveHook,
 }
 
+/// How many portfolio summaries `SavingsTracker` memoizes.
+const SUMMARY_MEMO_CAPACITY: usize = 4;
+
 impl std::fmt::Debug for SavingsTracker {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SavingsTracker")
-            .field("events", &self.portfolio.events.len())
+            .field("events", &self.portfolio.active_events().count())
             .field("settings", &self.portfolio.settings)
             .field("cached_prices", &self.portfolio.price_cache.total_entries())
             .field("dirty", &self.dirty)
@@ -59,17 +156,39 @@ impl SavingsTracker {
         Self::build(portfolio)
     }
 
+    /// Create a new empty portfolio that fetches prices through the given registry
+    /// instead of the default providers (e.g., custom or test providers).
+    pub fn create_with_registry(registry: PriceProviderRegistry) -> Self {
+        Self::build_with_registry(Portfolio::default(), registry)
+    }
+
     /// Load an existing portfolio from encrypted bytes (password required).
     /// Use this for WASM / Tauri where the frontend handles file I/O.
+    ///
+    /// The events are checked on load; problems don't fail the load but are
+    /// reported by `load_integrity_report`.
     pub fn load_from_bytes(encrypted: &[u8], password: &str) -> Result<Self, CoreError> {
         let portfolio = StorageManager::load_from_bytes(encrypted, password)?;
-        Ok(Self::build(portfolio))
+        Ok(Self::build_loaded(portfolio))
+    }
+
+    /// Like `load_from_bytes`, within the limits of `options` (e.g., a
+    /// smaller `max_file_size` for files picked by the user).
+    pub fn load_from_bytes_with(
+        encrypted: &[u8],
+        password: &str,
+        options: &LoadOptions,
+    ) -> Result<Self, CoreError> {
+        let portfolio = StorageManager::load_from_bytes_with(encrypted, password, options)?;
+        Ok(Self::build_loaded(portfolio))
     }
 
     /// Save the current portfolio to encrypted bytes.
     /// Returns raw bytes that the frontend can write to a file.
     /// Clears the unsaved-changes flag on success.
     pub fn save_to_bytes(&mut self, password: &str) -> Result<Vec<u8>, CoreError> {
+        self.sync_symbol_overrides();
+        self.sync_api_usage();
         let bytes = StorageManager::save_to_bytes(&self.portfolio, password)?;
         self.dirty = false;
         Ok(bytes)
@@ -79,13 +198,27 @@ impl SavingsTracker {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn load_from_file(path: &str, password: &str) -> Result<Self, CoreError> {
         let portfolio = StorageManager::load_from_file(path, password)?;
-        Ok(Self::build(portfolio))
+        Ok(Self::build_loaded(portfolio))
+    }
+
+    /// Like `load_from_file`, within the limits of `options`; the file size
+    /// is checked before it is read (native only, not WASM).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_from_file_with(
+        path: &str,
+        password: &str,
+        options: &LoadOptions,
+    ) -> Result<Self, CoreError> {
+        let portfolio = StorageManager::load_from_file_with(path, password, options)?;
+        Ok(Self::build_loaded(portfolio))
     }
 
     /// Save to an encrypted file on disk (native only, not WASM).
     /// Clears the unsaved-changes flag on success.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn save_to_file(&mut self, path: &str, password: &str) -> Result<(), CoreError> {
+        self.sync_symbol_overrides();
+        self.sync_api_usage();
         StorageManager::save_to_file(&self.portfolio, path, password)?;
         self.dirty = false;
         Ok(())
@@ -104,12 +237,13 @@ impl SavingsTracker {
         let event = Event::new(event_type, asset, amount, date);
         let id = event.id;
         self.portfolio_service
-            .add_event(&mut self.portfolio, event)?;
-        self.dirty = true;
+            .add_event(&mut self.portfolio.edit(), event)?;
+        self.mark_dirty();
         Ok(id)
     }
 
-    /// Add a buy/sell event with notes attached.
+    /// Add a buy/sell event with notes attached. Trailing whitespace is
+    /// trimmed from the notes; see `ValidationPolicy::max_notes_len`.
     pub fn add_event_with_notes(
         &mut self,
         event_type: EventType,
@@ -121,17 +255,171 @@ impl SavingsTracker {
         let event = Event::with_notes(event_type, asset, amount, date, notes);
         let id = event.id;
         self.portfolio_service
-            .add_event(&mut self.portfolio, event)?;
-        self.dirty = true;
+            .add_event(&mut self.portfolio.edit(), event)?;
+        self.mark_dirty();
+        Ok(id)
+    }
+
+    /// Add a planned buy/sell event, e.g. next month's contribution.
+    ///
+    /// Planned events may be dated in the future; they are left out of
+    /// holdings, analytics and event listings unless asked for (see
+    /// `get_holdings_with`, `get_planned_events`).
+    pub fn add_planned_event(
+        &mut self,
+        event_type: EventType,
+        asset: Asset,
+        amount: f64,
+        date: NaiveDate,
+    ) -> Result<uuid::Uuid, CoreError> {
+        let event = Event::planned(event_type, asset, amount, date);
+        let id = event.id;
+        self.portfolio_service
+            .add_event(&mut self.portfolio.edit(), event)?;
+        self.mark_dirty();
+        Ok(id)
+    }
+
+    /// Finalize a planned event: it becomes an actual event on `actual_date`
+    /// with `actual_amount`, validated like any other actual event.
+    pub fn convert_planned_to_actual(
+        &mut self,
+        event_id: uuid::Uuid,
+        actual_date: NaiveDate,
+        actual_amount: f64,
+    ) -> Result<(), CoreError> {
+        self.portfolio_service.convert_planned_to_actual(
+            &mut self.portfolio.edit(),
+            event_id,
+            actual_date,
+            actual_amount,
+        )?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Add a buy/sell event, recording the asset's unit price on `date` in both
+    /// its native quote currency and the default currency (`Event::recorded_value`).
+    ///
+    /// Requires price data (online or cached). If the price lookup fails,
+    /// the event is not added.
+    pub async fn add_event_with_auto_price(
+        &mut self,
+        event_type: EventType,
+        asset: Asset,
+        amount: f64,
+        date: NaiveDate,
+    ) -> Result<uuid::Uuid, CoreError> {
+        let currency = self.portfolio.settings.default_currency.clone();
+        let quote_currency = CurrencyService::quote_currency(&asset);
+        let quote_unit_price = self.asset_price(&asset, date, &quote_currency).await?;
+        let unit_price = self.asset_price(&asset, date, &currency).await?;
+
+        let mut event = Event::new(event_type, asset, amount, date);
+        event.recorded_value = Some(RecordedValue {
+            quote_currency,
+            quote_unit_price,
+            currency,
+            unit_price,
+        });
+        let id = event.id;
+        self.portfolio_service
+            .add_event(&mut self.portfolio.edit(), event)?;
+        self.mark_dirty();
         Ok(id)
     }
 
+    /// What adding a buy/sell event would do to the holdings on `date`,
+    /// without adding it. Fails with the same errors as `add_event`, so it
+    /// doubles as pre-validation for an event form.
+    pub fn preview_event(
+        &self,
+        event_type: EventType,
+        asset: Asset,
+        amount: f64,
+        date: NaiveDate,
+    ) -> Result<EventPreview, CoreError> {
+        let event = Event::new(event_type, asset, amount, date);
+        let (_, preview) = self.portfolio_service.preview_event(&self.portfolio, event)?;
+        Ok(preview)
+    }
+
+    /// `preview_event` plus the portfolio summary on `date` in the default
+    /// currency with and without the event, e.g. "this sell realizes a gain
+    /// of $X and drops BTC to 12%". Requires price data (online or cached).
+    pub async fn preview_event_impact(
+        &mut self,
+        event_type: EventType,
+        asset: Asset,
+        amount: f64,
+        date: NaiveDate,
+    ) -> Result<EventImpact, CoreError> {
+        let event = Event::new(event_type, asset, amount, date);
+        let (after_portfolio, preview) =
+            self.portfolio_service.preview_event(&self.portfolio, event)?;
+        let currency = self.portfolio.settings.default_currency.clone();
+        let inflation = InflationService::country_for_currency(&currency)
+            .map(|country| (&self.inflation_service, country));
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+
+        let result = match self
+            .analytics_service
+            .get_portfolio_summary_with_inflation(
+                portfolio,
+                &self.price_service,
+                price_cache,
+                date,
+                &currency,
+                inflation,
+            )
+            .await
+        {
+            Ok(before) => self
+                .analytics_service
+                .get_portfolio_summary_with_inflation(
+                    &after_portfolio,
+                    &self.price_service,
+                    price_cache,
+                    date,
+                    &currency,
+                    inflation,
+                )
+                .await
+                .map(|after| (before, after)),
+            Err(e) => Err(e),
+        };
+
+        drop(loan);
+        let (before, after) = result?;
+
+        let event = &preview.event;
+        let held_before = before.holdings.iter().find(|h| h.asset == event.asset);
+        let held_after = after.holdings.iter().find(|h| h.asset == event.asset);
+        let realized_gain = match held_before {
+            Some(held) if event.event_type == EventType::Sell && held.amount > 0.0 => {
+                event.amount * (held.current_value / held.amount - held.cost_basis_per_unit)
+            }
+            _ => 0.0,
+        };
+
+        Ok(EventImpact {
+            value_change: after.total_value - before.total_value,
+            realized_gain,
+            allocation_before_pct: held_before.map_or(0.0, |h| h.allocation_pct),
+            allocation_after_pct: held_after.map_or(0.0, |h| h.allocation_pct),
+            preview,
+            before,
+            after,
+        })
+    }
+
     /// Remove an event by its ID.
     /// Validates that removal doesn't create inconsistent sell events.
     pub fn remove_event(&mut self, event_id: uuid::Uuid) -> Result<(), CoreError> {
         self.portfolio_service
-            .remove_event(&mut self.portfolio, event_id)?;
-        self.dirty = true;
+            .remove_event(&mut self.portfolio.edit(), event_id)?;
+        self.mark_dirty();
         Ok(())
     }
 
@@ -146,240 +434,842 @@ impl SavingsTracker {
         date: NaiveDate,
     ) -> Result<(), CoreError> {
         self.portfolio_service.update_event(
-            &mut self.portfolio,
+            &mut self.portfolio.edit(),
             event_id,
             event_type,
             asset,
             amount,
             date,
         )?;
-        self.dirty = true;
+        self.mark_dirty();
         Ok(())
     }
 
-    /// Set or clear notes on an existing event.
+    /// Set or clear notes on an existing event. Trailing whitespace is
+    /// trimmed; NUL characters and notes longer than
+    /// `ValidationPolicy::max_notes_len` are rejected.
     pub fn set_event_notes(
         &mut self,
         event_id: uuid::Uuid,
         notes: Option<String>,
     ) -> Result<(), CoreError> {
         self.portfolio_service
-            .set_notes(&mut self.portfolio, event_id, notes)?;
-        self.dirty = true;
+            .set_notes(&mut self.portfolio.edit(), event_id, notes)?;
+        self.mark_dirty();
         Ok(())
     }
 
-    /// Get a single event by its ID.
+    /// Get a single event (actual or planned) by its ID. Deleted events are
+    /// returned too, so their ids stay referenceable (see `Event::deleted_at`).
     #[must_use]
     pub fn get_event(&self, event_id: uuid::Uuid) -> Option<&Event> {
         self.portfolio.events.iter().find(|e| e.id == event_id)
     }
 
-    /// Get all events, ordered by date.
+    /// Get all actual events, newest first. Planned and deleted events are
+    /// left out of this and the other event listings (see
+    /// `get_events_matching` to include them).
     #[must_use]
     pub fn get_events(&self) -> Vec<&Event> {
         self.portfolio_service.get_events(&self.portfolio)
     }
 
+    /// Get planned events, newest first (the furthest in the future first).
+    #[must_use]
+    pub fn get_planned_events(&self) -> Vec<&Event> {
+        self.portfolio_service.get_planned_events(&self.portfolio)
+    }
+
+    /// Get the events matching `filter`, newest first.
+    #[must_use]
+    pub fn get_events_matching(&self, filter: &EventFilter) -> Vec<&Event> {
+        newest_first(self.portfolio.events.iter().filter(|e| filter.matches(e)))
+    }
+
     /// Get events filtered by asset symbol (case-insensitive).
     /// Returns newest-first, consistent with `get_events()`.
     #[must_use]
     pub fn get_events_for_asset(&self, asset_symbol: &str) -> Vec<&Event> {
         let upper = asset_symbol.to_uppercase();
-        let mut events: Vec<&Event> = self.portfolio
-            .events
-            .iter()
-            .filter(|e| e.asset.symbol == upper)
-            .collect();
-        events.reverse(); // internal storage is oldest-first; reverse for newest-first
-        events
+        newest_first(self.portfolio.actual_events().filter(|e| e.asset.symbol == upper))
     }
 
     /// Get events filtered by event type (Buy or Sell).
     /// Returns newest-first, consistent with `get_events()`.
     #[must_use]
     pub fn get_events_by_type(&self, event_type: &EventType) -> Vec<&Event> {
-        let mut events: Vec<&Event> = self.portfolio
-            .events
-            .iter()
-            .filter(|e| &e.event_type == event_type)
-            .collect();
-        events.reverse();
-        events
+        newest_first(self.portfolio.actual_events().filter(|e| &e.event_type == event_type))
     }
 
     /// Get events within a date range (inclusive).
     /// Returns newest-first, consistent with `get_events()`.
     #[must_use]
     pub fn get_events_in_range(&self, from: NaiveDate, to: NaiveDate) -> Vec<&Event> {
-        let mut events: Vec<&Event> = self.portfolio
-            .events
-            .iter()
-            .filter(|e| e.date >= from && e.date <= to)
-            .collect();
-        events.reverse();
-        events
+        newest_first(self.portfolio.actual_events().filter(|e| e.date >= from && e.date <= to))
     }
 
     // ── Holdings & Value ────────────────────────────────────────────
 
     /// Calculate current holdings (how much of each asset you own) at a given date.
+    /// Planned events are not counted.
     #[must_use]
     pub fn get_holdings(&self, date: NaiveDate) -> HashMap<Asset, f64> {
         self.portfolio_service
             .get_holdings(&self.portfolio, date)
     }
 
-    /// Get the total portfolio value in the default currency.
-    /// Requires price data (online or cached).
-    pub async fn get_portfolio_value(
+    /// Every asset transacted up to `date` with the amount held then, 0 for
+    /// assets since fully sold or withdrawn (e.g., to show "previously
+    /// held"). Assets never held by `date` are absent. Planned events are
+    /// not counted.
+    #[must_use]
+    pub fn get_all_asset_positions(&self, date: NaiveDate) -> HashMap<Asset, f64> {
+        self.portfolio_service
+            .get_all_positions(&self.portfolio, date)
+    }
+
+    /// `get_holdings` with `options`, e.g. counting planned events up to
+    /// `date` as if they had happened.
+    #[must_use]
+    pub fn get_holdings_with(
+        &self,
+        date: NaiveDate,
+        options: &HoldingsOptions,
+    ) -> HashMap<Asset, f64> {
+        self.portfolio_service
+            .get_holdings_with(&self.portfolio, date, options)
+    }
+
+    /// `get_holdings` for direct display: each asset with its unrounded
+    /// amount and the amount formatted with the asset's display precision
+    /// and the portfolio's locale, sorted by symbol.
+    #[must_use]
+    pub fn get_holdings_display(&self, date: NaiveDate) -> Vec<(Asset, f64, String)> {
+        let locale = self.portfolio.settings.locale;
+        let mut holdings: Vec<(Asset, f64, String)> = self
+            .get_holdings(date)
+            .into_iter()
+            .map(|(asset, amount)| {
+                let precision = self.get_display_precision(&asset);
+                let formatted = formatting::format_amount(amount, precision, locale);
+                (asset, amount, formatted)
+            })
+            .collect();
+        holdings.sort_by(|a, b| a.0.symbol.cmp(&b.0.symbol));
+        holdings
+    }
+
+    /// Amount of one asset held from `from` to `to`, for sparklines: a point
+    /// at `from` with the amount held that day, one per date its events
+    /// change the amount, and one at `to`. Computed from the asset's events
+    /// alone, without prices. The symbol is case-insensitive; fails if the
+    /// asset has no events.
+    pub fn get_asset_holdings_series(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, f64)>, CoreError> {
+        self.portfolio_service
+            .asset_holdings_series(&self.portfolio, symbol, from, to)
+    }
+
+    /// Get the total portfolio value in the default currency, with that
+    /// currency. Requires price data (online or cached).
+    ///
+    /// Holdings that can't be priced are left out of the total; fails only
+    /// if none of them could be priced. Use `get_portfolio_value_detailed`
+    /// to see which holdings were left out.
+    pub async fn get_portfolio_value(&mut self, date: NaiveDate) -> Result<Money, CoreError> {
+        let currency = self.portfolio.settings.default_currency.clone();
+        let amount = self.portfolio_value(date, &currency).await?;
+        Ok(Money { amount, currency })
+    }
+
+    /// `get_portfolio_value` as a plain number, without its currency.
+    #[deprecated(note = "use get_portfolio_value, which returns the currency with the value")]
+    pub async fn get_portfolio_value_f64(&mut self, date: NaiveDate) -> Result<f64, CoreError> {
+        Ok(self.get_portfolio_value(date).await?.amount)
+    }
+
+    /// Get the total portfolio value in `currency` (e.g., "EUR"), with that
+    /// currency, without changing the default currency. Currency code must
+    /// be 3 ASCII letters.
+    pub async fn get_portfolio_value_in_currency(
+        &mut self,
+        date: NaiveDate,
+        currency: &str,
+    ) -> Result<Money, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        let amount = self.portfolio_value(date, &currency).await?;
+        Ok(Money { amount, currency })
+    }
+
+    /// `get_portfolio_value_in_currency` as a plain number, without its currency.
+    #[deprecated(
+        note = "use get_portfolio_value_in_currency, which returns the currency with the value"
+    )]
+    pub async fn get_portfolio_value_in_currency_f64(
         &mut self,
         date: NaiveDate,
+        currency: &str,
     ) -> Result<f64, CoreError> {
-        let holdings = self.get_holdings(date);
+        Ok(self.get_portfolio_value_in_currency(date, currency).await?.amount)
+    }
+
+    /// Portfolio value on `date` in the default currency from cached prices
+    /// only, synchronously and without touching the network, so a UI can
+    /// render at once and refresh with `get_portfolio_value` afterwards.
+    ///
+    /// Holdings without a cached price on `date` are valued like in
+    /// `get_portfolio_summary_cached` and flagged by their `price_status`.
+    pub fn get_portfolio_value_cached(&mut self, date: NaiveDate) -> CachedValuation {
+        let currency = self.portfolio.settings.default_currency.clone();
+        let cached_prices = self.price_service.cache_only();
+        let policy =
+            AnalyticsService::cached_policy(self.portfolio.settings.unpriceable_asset_policy);
+        let mut holdings = Vec::new();
+
+        for (asset, amount) in self.get_holdings(date) {
+            let manual_price = AnalyticsService::manual_price(&self.portfolio, &asset);
+            let (value, price_status) = resolve_cached(self.currency_service.value_holding(
+                &cached_prices,
+                self.portfolio.price_cache_mut(),
+                &asset,
+                amount,
+                &currency,
+                date,
+                policy,
+                manual_price,
+            ))
+            .unwrap_or((0.0, PriceStatus::Unavailable));
+            holdings.push(CachedHoldingValue { asset, amount, value, price_status });
+        }
+        holdings.sort_by(|a, b| a.asset.symbol.cmp(&b.asset.symbol));
+
+        CachedValuation {
+            date,
+            value: Money {
+                amount: holdings.iter().map(|h| h.value).sum(),
+                currency,
+            },
+            holdings,
+        }
+    }
+
+    /// Value every holding in the default currency, reporting per-asset
+    /// values and the holdings whose price lookup failed.
+    pub async fn get_portfolio_value_detailed(&mut self, date: NaiveDate) -> PortfolioValuation {
         let default_currency = self.portfolio.settings.default_currency.clone();
-        let mut total = 0.0;
+        self.portfolio_valuation(date, &default_currency).await
+    }
 
-        for (asset, amount) in &holdings {
-            let value = self
+    /// `get_portfolio_value_detailed` in `currency` instead of the default currency.
+    pub async fn get_portfolio_value_detailed_in_currency(
+        &mut self,
+        date: NaiveDate,
+        currency: &str,
+    ) -> Result<PortfolioValuation, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        Ok(self.portfolio_valuation(date, &currency).await)
+    }
+
+    async fn portfolio_value(&mut self, date: NaiveDate, currency: &str) -> Result<f64, CoreError> {
+        let mut valuation = self.portfolio_valuation(date, currency).await;
+        if valuation.assets.is_empty() && !valuation.failed.is_empty() {
+            return Err(valuation.failed.swap_remove(0).error);
+        }
+        Ok(valuation.total_value)
+    }
+
+    async fn portfolio_valuation(&mut self, date: NaiveDate, currency: &str) -> PortfolioValuation {
+        let mut assets = Vec::new();
+        let mut failed = Vec::new();
+
+        for (asset, amount) in self.get_holdings(date) {
+            match self
                 .currency_service
                 .convert_asset_to_currency(
                     &self.price_service,
-                    &mut self.portfolio.price_cache,
-                    asset,
-                    *amount,
-                    &default_currency,
+                    self.portfolio.price_cache_mut(),
+                    &asset,
+                    amount,
+                    currency,
                     date,
                 )
-                .await?;
-            total += value;
+                .await
+            {
+                Ok(value) => assets.push(AssetValue { asset, amount, value }),
+                Err(error) => failed.push(FailedValuation { asset, amount, error }),
+            }
         }
+        assets.sort_by(|a, b| a.asset.symbol.cmp(&b.asset.symbol));
+        failed.sort_by(|a, b| a.asset.symbol.cmp(&b.asset.symbol));
 
-        Ok(total)
+        PortfolioValuation {
+            date,
+            currency: currency.to_string(),
+            total_value: assets.iter().map(|a| a.value).sum(),
+            assets,
+            failed,
+        }
     }
 
     // ── Charts ──────────────────────────────────────────────────────
 
     /// Generate chart data for the whole portfolio over a date range.
+    ///
+    /// One point per day; ranges over 10 years get a point per week and
+    /// over 20 years a point per month (see `ChartResolution`). Ranges
+    /// longer than `max_chart_range_days` (10 years by default) fail.
     pub async fn generate_portfolio_chart(
         &mut self,
         from: NaiveDate,
         to: NaiveDate,
     ) -> Result<Vec<ChartDataPoint>, CoreError> {
-        if from > to {
-            return Err(CoreError::ValidationError(
-                format!("'from' date ({from}) must not be after 'to' date ({to})"),
-            ));
-        }
-        let range_days = (to - from).num_days();
-        if range_days > MAX_CHART_RANGE_DAYS {
-            return Err(CoreError::ValidationError(
-                format!("Chart range of {range_days} days exceeds maximum of {MAX_CHART_RANGE_DAYS} days (10 years)"),
-            ));
-        }
+        self.generate_portfolio_chart_with(from, to, &PortfolioChartOptions::default())
+            .await
+    }
+
+    /// `generate_portfolio_chart` with `options`, e.g. applying planned events
+    /// as if they happened. `to` may then be in the future: days after today
+    /// are valued at today's prices, projecting the value line forward.
+    pub async fn generate_portfolio_chart_with(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        options: &PortfolioChartOptions,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        let currency = self.portfolio.settings.default_currency.clone();
+        self.portfolio_chart(from, to, &currency, options, |_| {}, None).await
+    }
+
+    /// Generate portfolio chart data in `currency` (e.g., "EUR") without
+    /// changing the default currency. Currency code must be 3 ASCII letters.
+    pub async fn generate_portfolio_chart_in_currency(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: &str,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        let options = PortfolioChartOptions::default();
+        self.portfolio_chart(from, to, &currency, &options, |_| {}, None)
+            .await
+    }
+
+    /// Generate portfolio chart data, reporting progress through `on_progress`.
+    ///
+    /// The callback runs on the calling task. If `cancel` (or else the token
+    /// set with `set_cancellation_token`) is triggered, returns
+    /// `CoreError::Cancelled` and aborts the provider requests in flight;
+    /// prices fetched up to that point stay cached.
+    pub async fn generate_portfolio_chart_with_progress(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        on_progress: impl FnMut(Progress),
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        let currency = self.portfolio.settings.default_currency.clone();
+        let options = PortfolioChartOptions::default();
+        self.portfolio_chart(from, to, &currency, &options, on_progress, cancel)
+            .await
+    }
 
+    /// Compute portfolio chart points and pass each one to `sink` as soon as it
+    /// is ready, in date order (e.g., to stream points to the UI).
+    ///
+    /// Fetched prices go straight into the portfolio's cache, so dropping the
+    /// future part-way (e.g., aborting the task) keeps everything fetched so far.
+    pub async fn generate_portfolio_chart_streaming(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        mut sink: impl FnMut(ChartDataPoint),
+    ) -> Result<(), CoreError> {
+        self.validate_chart_range(from, to)?;
         let currency = self.portfolio.settings.default_currency.clone();
 
-        // Temporarily take price_cache out of portfolio to satisfy the borrow checker:
-        // generate_portfolio_chart needs &Portfolio (immutable) and &mut PriceCache (mutable).
-        // Since PriceCache lives inside Portfolio, we can't borrow both simultaneously.
-        let mut price_cache = std::mem::take(&mut self.portfolio.price_cache);
+        let mut iter = self
+            .chart_service
+            .portfolio_chart_iter(&self.portfolio, from, to, &currency);
+        while let Some(point) = iter
+            .next_point(&self.price_service, self.portfolio.price_cache_mut())
+            .await
+        {
+            sink(point?);
+        }
+
+        Ok(())
+    }
+
+    async fn portfolio_chart(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: &str,
+        options: &PortfolioChartOptions,
+        on_progress: impl FnMut(Progress),
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        self.validate_chart_range(from, to)?;
+
+        // The iterator owns its copy of the events, so the cache can stay inside
+        // the portfolio and is updated in place even if this future is dropped.
+        let iter = self
+            .chart_service
+            .portfolio_chart_iter_with(&self.portfolio, from, to, currency, options);
+        let price_service = self.scoped_price_service(cancel);
+        self.chart_service
+            .collect_chart(
+                iter,
+                &price_service,
+                self.portfolio.price_cache_mut(),
+                on_progress,
+                None,
+            )
+            .await
+    }
+
+    /// Generate portfolio chart data in real terms: values in the default currency
+    /// deflated with `country`'s CPI (e.g., "US", "EU", "PL") and expressed in
+    /// `base_date` money (`to`-date money when `None`).
+    ///
+    /// Fails with `CoreError::InflationDataUnavailable` if any day in the range
+    /// or the base date has no CPI data (unknown country or before the first
+    /// known month).
+    pub async fn generate_portfolio_chart_real(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        country: &str,
+        base_date: Option<NaiveDate>,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        let base_date = base_date.unwrap_or(to);
+        // Check CPI coverage before fetching any prices
+        self.inflation_service.deflator(country, from, base_date)?;
+        let chart = self.generate_portfolio_chart(from, to).await?;
+        self.chart_service
+            .deflate_chart(chart, &self.inflation_service, country, base_date)
+    }
 
-        let result = self
+    /// Generate chart data for only the assets with the given symbols
+    /// (case-insensitive), e.g. the "retirement" assets, in the default
+    /// currency. Symbols without events are ignored and listed in the
+    /// result; if none is left, the chart is all zeros.
+    pub async fn generate_chart_for_assets(
+        &mut self,
+        symbols: &[String],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<GroupChart, CoreError> {
+        self.validate_chart_range(from, to)?;
+        let currency = self.portfolio.settings.default_currency.clone();
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+        self
             .chart_service
-            .generate_portfolio_chart(
-                &self.portfolio,
+            .generate_group_chart(
+                portfolio,
                 &mut self.price_service,
-                &mut price_cache,
+                price_cache,
+                symbols,
                 from,
                 to,
                 &currency,
             )
-            .await;
-
-        // Put the (now updated) cache back
-        self.portfolio.price_cache = price_cache;
+            .await
+    }
 
-        result
+    /// `generate_chart_for_assets` for the assets of the events `filter`
+    /// matches (e.g. every crypto asset). All events of those assets are
+    /// charted, not only the matching ones.
+    pub async fn generate_chart_for_filter(
+        &mut self,
+        filter: &EventFilter,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<GroupChart, CoreError> {
+        let symbols: Vec<String> = self
+            .portfolio
+            .events
+            .iter()
+            .filter(|e| filter.matches(e))
+            .map(|e| e.asset.symbol.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        self.generate_chart_for_assets(&symbols, from, to).await
     }
 
-    /// Generate chart data for a single asset over a date range.
+    /// Generate chart data for a single asset over a date range, with
+    /// points and range limit like `generate_portfolio_chart`.
     pub async fn generate_asset_chart(
         &mut self,
         asset_symbol: &str,
         from: NaiveDate,
         to: NaiveDate,
     ) -> Result<Vec<ChartDataPoint>, CoreError> {
-        if from > to {
-            return Err(CoreError::ValidationError(
-                format!("'from' date ({from}) must not be after 'to' date ({to})"),
-            ));
-        }
-        let range_days = (to - from).num_days();
-        if range_days > MAX_CHART_RANGE_DAYS {
-            return Err(CoreError::ValidationError(
-                format!("Chart range of {range_days} days exceeds maximum of {MAX_CHART_RANGE_DAYS} days (10 years)"),
-            ));
-        }
+        let chart = self
+            .generate_asset_chart_with(asset_symbol, from, to, &AssetChartOptions::default())
+            .await?;
+        Ok(chart.points)
+    }
+
+    /// `generate_asset_chart` with `options`, e.g. to also return the asset's
+    /// price per unit on every date for a price line.
+    pub async fn generate_asset_chart_with(
+        &mut self,
+        asset_symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        options: &AssetChartOptions,
+    ) -> Result<AssetChart, CoreError> {
+        self.validate_chart_range(from, to)?;
 
         let currency = self.portfolio.settings.default_currency.clone();
 
-        let mut price_cache = std::mem::take(&mut self.portfolio.price_cache);
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
 
-        let result = self
+        self
             .chart_service
-            .generate_asset_chart(
-                &self.portfolio,
+            .generate_asset_chart_with(
+                portfolio,
                 &mut self.price_service,
-                &mut price_cache,
+                price_cache,
                 asset_symbol,
                 from,
                 to,
                 &currency,
+                options,
             )
-            .await;
-
-        self.portfolio.price_cache = price_cache;
-
-        result
+            .await
     }
 
     // ── Analytics ───────────────────────────────────────────────────
 
+    /// Event counts, traded amounts and first/last dates, overall and per
+    /// asset. Synchronous and price-free, e.g. for a dashboard header.
+    #[must_use]
+    pub fn get_statistics(&self) -> Statistics {
+        self.analytics_service.get_statistics(&self.portfolio)
+    }
+
     /// Get a full portfolio summary with gain/loss, returns, and allocation breakdown.
+    ///
+    /// Monetary values are rounded to the currency's minor unit and
+    /// percentages to 4 decimal places; allocation percentages sum to
+    /// exactly 100 (see `AnalyticsService`).
+    ///
+    /// The last few summaries are memoized until the next mutation or price
+    /// refresh, so asking again for the same date is free.
     pub async fn get_portfolio_summary(
         &mut self,
         date: NaiveDate,
+    ) -> Result<PortfolioSummary, CoreError> {
+        self.get_portfolio_summary_with(date, &SummaryOptions::default())
+            .await
+    }
+
+    /// `get_portfolio_summary` with options: another currency,
+    /// `force_refresh` to bypass the memo (e.g., after prices changed
+    /// upstream), `raw` for unrounded values, or
+    /// `include_closed_positions` to list fully sold assets too.
+    pub async fn get_portfolio_summary_with(
+        &mut self,
+        date: NaiveDate,
+        options: &SummaryOptions,
+    ) -> Result<PortfolioSummary, CoreError> {
+        let currency = match &options.currency {
+            Some(currency) => Self::normalize_currency_code(currency)?,
+            None => self.portfolio.settings.default_currency.clone(),
+        };
+        if options.raw || options.include_closed_positions {
+            return self.portfolio_summary(date, &currency, options).await;
+        }
+        if !options.force_refresh {
+            if let Some(summary) = self.memoized_summary(date, &currency) {
+                return Ok(summary);
+            }
+        }
+        let summary = self.portfolio_summary(date, &currency, options).await?;
+        self.memoize_summary(date, currency, summary.clone());
+        Ok(summary)
+    }
+
+    /// Get a portfolio summary in `currency` (e.g., "EUR") without changing
+    /// the default currency. `PortfolioSummary::currency` reports the currency used.
+    pub async fn get_portfolio_summary_in_currency(
+        &mut self,
+        date: NaiveDate,
+        currency: &str,
+    ) -> Result<PortfolioSummary, CoreError> {
+        let options = SummaryOptions {
+            currency: Some(currency.to_string()),
+            ..Default::default()
+        };
+        self.get_portfolio_summary_with(date, &options).await
+    }
+
+    /// Portfolio summary on `date` in the default currency from cached prices
+    /// only, synchronously and without touching the network, so a UI can
+    /// render at once and refresh with `get_portfolio_summary` afterwards.
+    ///
+    /// Holdings without a cached price on `date` are valued under the
+    /// unpriceable asset policy, with `Fail` treated as `LastCachedPrice`;
+    /// `HoldingSummary::price_status` flags them as stale, manual or
+    /// unavailable. Events and exchange rates without a cached price still
+    /// fail the summary. Not memoized.
+    pub fn get_portfolio_summary_cached(
+        &mut self,
+        date: NaiveDate,
     ) -> Result<PortfolioSummary, CoreError> {
         let currency = self.portfolio.settings.default_currency.clone();
+        let inflation = InflationService::country_for_currency(&currency)
+            .map(|country| (&self.inflation_service, country));
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+        self.analytics_service.get_portfolio_summary_cached(
+            portfolio,
+            &self.price_service,
+            price_cache,
+            date,
+            &currency,
+            inflation,
+        )
+    }
 
-        let mut price_cache = std::mem::take(&mut self.portfolio.price_cache);
+    fn memoized_summary(&self, date: NaiveDate, currency: &str) -> Option<PortfolioSummary> {
+        self.summary_memo
+            .iter()
+            .find(|(d, c, revision, _)| *d == date && c == currency && *revision == self.revision)
+            .map(|(_, _, _, summary)| summary.clone())
+    }
 
-        let result = self
+    fn memoize_summary(&mut self, date: NaiveDate, currency: String, summary: PortfolioSummary) {
+        let revision = self.revision;
+        self.summary_memo
+            .retain(|(d, c, r, _)| *r == revision && !(*d == date && *c == currency));
+        if self.summary_memo.len() >= SUMMARY_MEMO_CAPACITY {
+            self.summary_memo.remove(0);
+        }
+        self.summary_memo.push((date, currency, revision, summary));
+    }
+
+    async fn portfolio_summary(
+        &mut self,
+        date: NaiveDate,
+        currency: &str,
+        options: &SummaryOptions,
+    ) -> Result<PortfolioSummary, CoreError> {
+        let inflation = InflationService::country_for_currency(currency)
+            .map(|country| (&self.inflation_service, country));
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+
+        self
             .analytics_service
-            .get_portfolio_summary(
-                &self.portfolio,
+            .get_portfolio_summary_with_options(
+                portfolio,
                 &self.price_service,
-                &mut price_cache,
+                price_cache,
+                date,
+                currency,
+                inflation,
+                options,
+            )
+            .await
+    }
+
+    /// Projected annual income of the holdings on `date` in the default currency:
+    /// holding value × annual yield, for assets with a yield set via `set_asset_yield`.
+    pub async fn get_income_projection(
+        &mut self,
+        date: NaiveDate,
+    ) -> Result<IncomeProjection, CoreError> {
+        let currency = self.portfolio.settings.default_currency.clone();
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+
+        self
+            .analytics_service
+            .get_income_projection(
+                portfolio,
+                &self.price_service,
+                price_cache,
                 date,
                 &currency,
             )
-            .await;
+            .await
+    }
 
-        self.portfolio.price_cache = price_cache;
+    /// What changed since the app was last opened on `last_opened`: portfolio
+    /// value then vs. now (default currency), per-asset price changes, the
+    /// biggest mover, and events dated after `last_opened`.
+    ///
+    /// Falls back to the inception date (and sets `from_is_inception`) when
+    /// `last_opened` predates the first event.
+    pub async fn get_since_summary(
+        &mut self,
+        last_opened: NaiveDate,
+    ) -> Result<SinceSummary, CoreError> {
+        let today = chrono::Utc::now().date_naive();
+        let currency = self.portfolio.settings.default_currency.clone();
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
 
-        result
+        self
+            .analytics_service
+            .get_since_summary(
+                portfolio,
+                &self.price_service,
+                price_cache,
+                last_opened,
+                today,
+                &currency,
+            )
+            .await
+    }
+
+    /// Change of the portfolio value (default currency) over `period`, ending
+    /// today, e.g. for a "▲ 2.3% this week" tile: the values on the period's
+    /// start and today, and the absolute and percentage change. Only those
+    /// two days are valued, no chart is computed.
+    ///
+    /// Starts on the inception date (and sets `from_is_inception`) when the
+    /// period starts before the first event. Holdings with no price on the
+    /// start date are valued with the first cached price after it within the
+    /// period and flagged by `value_then_estimated`. Fails if a custom start is after today.
+    pub async fn get_value_change(
+        &mut self,
+        period: ChangePeriod,
+    ) -> Result<ValueChange, CoreError> {
+        let today = chrono::Utc::now().date_naive();
+        let currency = self.portfolio.settings.default_currency.clone();
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+        // Estimates are flagged, so the configured fallback is left out
+        let price_service = self.price_service.with_fallback(PriceFallback::default());
+
+        self
+            .analytics_service
+            .get_value_change(
+                portfolio,
+                &price_service,
+                price_cache,
+                period,
+                today,
+                &currency,
+            )
+            .await
+    }
+
+    /// Yearly statement for fiscal year `year` (see `set_fiscal_year_start_month`)
+    /// in the default currency (e.g., for an accountant): opening and closing
+    /// value, the year's events valued at their dates, realized gains and
+    /// estimated income. Missing prices are listed in
+    /// `AnnualReport::missing_prices`, never counted as zero.
+    pub async fn get_annual_report(&mut self, year: i32) -> Result<AnnualReport, CoreError> {
+        let currency = self.portfolio.settings.default_currency.clone();
+        self.annual_report(year, &currency).await
+    }
+
+    /// Same as `get_annual_report`, in `currency` (e.g., "EUR").
+    pub async fn get_annual_report_in_currency(
+        &mut self,
+        year: i32,
+        currency: &str,
+    ) -> Result<AnnualReport, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        self.annual_report(year, &currency).await
     }
 
-    /// Get a list of all unique assets that appear in portfolio events.
+    /// The annual report for `year` in the default currency, rendered as
+    /// Markdown (see `AnnualReport::to_markdown`).
+    pub async fn render_annual_report_markdown(&mut self, year: i32) -> Result<String, CoreError> {
+        Ok(self.get_annual_report(year).await?.to_markdown())
+    }
+
+    async fn annual_report(
+        &mut self,
+        year: i32,
+        currency: &str,
+    ) -> Result<AnnualReport, CoreError> {
+        let today = chrono::Utc::now().date_naive();
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+
+        self
+            .analytics_service
+            .get_annual_report(
+                portfolio,
+                &self.price_service,
+                price_cache,
+                year,
+                currency,
+                today,
+            )
+            .await
+    }
+
+    /// Sales in `year` matched against the buys they sold with `method`,
+    /// one `TaxLot` per buy lot, valued in `currency` (e.g., "USD").
+    ///
+    /// Lots held longer than `Settings::long_term_holding_days` are
+    /// long-term. Fails if a needed price cannot be found or a sale exceeds
+    /// the units bought before it.
+    pub async fn get_tax_lots(
+        &mut self,
+        year: i32,
+        method: CostBasisMethod,
+        currency: &str,
+    ) -> Result<Vec<TaxLot>, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        let long_term_days = self.portfolio.settings.long_term_holding_days;
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+
+        self
+            .analytics_service
+            .get_tax_lots(
+                portfolio,
+                &self.price_service,
+                price_cache,
+                year,
+                method,
+                &currency,
+                long_term_days,
+            )
+            .await
+    }
+
+    /// `get_tax_lots` as a Form 8949-style CSV string.
+    /// Columns: asset, acquired_date, sold_date, quantity, proceeds, cost_basis, gain, term
+    pub async fn export_tax_lots_csv(
+        &mut self,
+        year: i32,
+        method: CostBasisMethod,
+        currency: &str,
+    ) -> Result<String, CoreError> {
+        let lots = self.get_tax_lots(year, method, currency).await?;
+        let mut csv = format!("{}\n", TaxLot::CSV_HEADER);
+        for lot in &lots {
+            csv.push_str(&lot.to_csv_row());
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+
+    /// Get a list of all unique assets that appear in actual (non-planned) portfolio events.
     /// Returns deterministic order (sorted by symbol).
     #[must_use]
     pub fn get_unique_assets(&self) -> Vec<&Asset> {
         let mut seen = std::collections::HashSet::new();
         let mut assets: Vec<&Asset> = self
             .portfolio
-            .events
-            .iter()
+            .actual_events()
             .filter_map(|e| {
                 if seen.insert((&e.asset.symbol, &e.asset.asset_type)) {
                     Some(&e.asset)
@@ -392,49 +1282,774 @@ impl SavingsTracker {
         assets
     }
 
+    // ── Simulation ──────────────────────────────────────────────────
+
+    /// What `asset` would be worth had `monthly_amount` of `currency` been
+    /// invested in it on the 1st of every month from `from` to `to`, valued
+    /// with real prices. A month without a price is bought at the last known
+    /// price, or skipped if there is none yet. The portfolio is not changed
+    /// (fetched prices are cached). The range is capped like charts
+    /// (`set_max_chart_range_days`).
+    pub async fn simulate_dca(
+        &mut self,
+        asset: Asset,
+        monthly_amount: f64,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<SimulationResult, CoreError> {
+        let contributions: Vec<(NaiveDate, f64)> = SimulationService::monthly_dates(from, to)
+            .into_iter()
+            .map(|date| (date, monthly_amount))
+            .collect();
+        self.simulate(asset, monthly_amount, &contributions, currency, from, to).await
+    }
+
+    /// Same as `simulate_dca` with all of `amount` invested on `from`, for
+    /// comparison.
+    pub async fn simulate_lump_sum(
+        &mut self,
+        asset: Asset,
+        amount: f64,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<SimulationResult, CoreError> {
+        self.simulate(asset, amount, &[(from, amount)], currency, from, to).await
+    }
+
+    async fn simulate(
+        &mut self,
+        asset: Asset,
+        amount: f64,
+        contributions: &[(NaiveDate, f64)],
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<SimulationResult, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        self.validate_chart_range(from, to)?;
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(CoreError::ValidationError(format!(
+                "Invalid amount {amount}: must be a positive number"
+            )));
+        }
+
+        let mut loan = self.portfolio.lend_price_cache();
+        let (portfolio, price_cache) = loan.split();
+        self
+            .simulation_service
+            .simulate(
+                &portfolio.settings,
+                &self.price_service,
+                price_cache,
+                &asset,
+                contributions,
+                &currency,
+                from,
+                to,
+            )
+            .await
+    }
+
+    // ── Snapshots ───────────────────────────────────────────────────
+
+    /// Compute the portfolio value on `date` in the default currency and store
+    /// it as a snapshot in the portfolio file, replacing any snapshot for that date.
+    /// Charts use stored snapshots instead of recomputing those days.
+    ///
+    /// Also prunes old snapshots (weekly after one year, see `prune_snapshots`).
+    /// Requires price data (online or cached) for every holding.
+    pub async fn record_snapshot(&mut self, date: NaiveDate) -> Result<ValueSnapshot, CoreError> {
+        let currency = self.portfolio.settings.default_currency.clone();
+        let mut assets = Vec::new();
+        let mut total_value = 0.0;
+
+        for (asset, amount) in self.get_holdings(date) {
+            let value = self
+                .currency_service
+                .convert_asset_to_currency(
+                    &self.price_service,
+                    self.portfolio.price_cache_mut(),
+                    &asset,
+                    amount,
+                    &currency,
+                    date,
+                )
+                .await?;
+            total_value += value;
+            assets.push(AssetValue { asset, amount, value });
+        }
+        assets.sort_by(|a, b| a.asset.symbol.cmp(&b.asset.symbol));
+
+        let snapshot = ValueSnapshot {
+            date,
+            total_value,
+            currency,
+            assets,
+        };
+        self.portfolio_service
+            .upsert_snapshot(&mut self.portfolio.edit(), snapshot.clone());
+        self.prune_snapshots();
+        self.mark_dirty();
+        Ok(snapshot)
+    }
+
+    /// All stored snapshots, sorted by date.
+    #[must_use]
+    pub fn get_snapshots(&self) -> &[ValueSnapshot] {
+        &self.portfolio.snapshots
+    }
+
+    /// Thin out snapshots older than one year: keep only the latest one per ISO week.
+    /// Returns the number of snapshots removed.
+    pub fn prune_snapshots(&mut self) -> usize {
+        let today = chrono::Utc::now().date_naive();
+        let removed = self
+            .portfolio_service
+            .prune_snapshots(&mut self.portfolio.edit(), today);
+        if removed > 0 {
+            self.mark_dirty();
+        }
+        removed
+    }
+
+    // ── Alerts ──────────────────────────────────────────────────────
+
+    /// Add a price alert on `asset`. `threshold` is a price in `currency` for
+    /// `Above`/`Below`, or a percentage for `PctChangeDay`.
+    /// Returns the UUID of the new alert.
+    pub fn add_alert(
+        &mut self,
+        asset: Asset,
+        condition: AlertCondition,
+        threshold: f64,
+        currency: &str,
+    ) -> Result<uuid::Uuid, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        let alert = Alert::new(asset, condition, threshold, currency);
+        let id = alert.id;
+        self.portfolio_service.add_alert(&mut self.portfolio.edit(), alert)?;
+        self.mark_dirty();
+        Ok(id)
+    }
+
+    /// Change an alert's condition, threshold and currency.
+    /// Resets `last_triggered`, so the updated alert can fire today.
+    pub fn update_alert(
+        &mut self,
+        alert_id: uuid::Uuid,
+        condition: AlertCondition,
+        threshold: f64,
+        currency: &str,
+    ) -> Result<(), CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        self.portfolio_service
+            .update_alert(&mut self.portfolio.edit(), alert_id, condition, threshold, currency)?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Enable or disable an alert. Disabled alerts are skipped by `check_alerts()`.
+    pub fn set_alert_enabled(&mut self, alert_id: uuid::Uuid, enabled: bool) -> Result<(), CoreError> {
+        self.portfolio_service
+            .set_alert_enabled(&mut self.portfolio.edit(), alert_id, enabled)?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Remove an alert by its UUID.
+    pub fn remove_alert(&mut self, alert_id: uuid::Uuid) -> Result<(), CoreError> {
+        self.portfolio_service
+            .remove_alert(&mut self.portfolio.edit(), alert_id)?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Get a single alert by its UUID.
+    #[must_use]
+    pub fn get_alert(&self, alert_id: uuid::Uuid) -> Option<&Alert> {
+        self.portfolio.alerts.iter().find(|a| a.id == alert_id)
+    }
+
+    /// All alerts, in the order they were added.
+    #[must_use]
+    pub fn get_alerts(&self) -> &[Alert] {
+        &self.portfolio.alerts
+    }
+
+    /// Fetch current prices for the assets of all enabled alerts and return
+    /// the alerts that fired. A fired alert records today's date in
+    /// `last_triggered` and does not fire again on the same day.
+    pub async fn check_alerts(&mut self) -> Result<Vec<Alert>, CoreError> {
+        let today = chrono::Utc::now().date_naive();
+        // Alerts need fresh quotes, not memoized ones
+        let price_service = self.price_service.bypassing_response_cache();
+        // Not through `edit`, so the portfolio stays lent to autosave
+        let (alerts, price_cache) = self.portfolio.alerts_and_price_cache_mut();
+        let triggered = self
+            .alert_service
+            .check_alerts(alerts, &price_service, price_cache, today)
+            .await?;
+        if !triggered.is_empty() {
+            self.mark_dirty();
+        }
+        Ok(triggered)
+    }
+
+    // ── Reminders ───────────────────────────────────────────────────
+
+    /// Remind the user to review an event on `date` (e.g., "in 6 months"),
+    /// or remove its reminder with `None`. Replaces an earlier reminder of
+    /// the event, acknowledged or not.
+    pub fn set_event_reminder(
+        &mut self,
+        event_id: uuid::Uuid,
+        date: Option<NaiveDate>,
+    ) -> Result<(), CoreError> {
+        self.portfolio_service
+            .set_event_reminder(&mut self.portfolio.edit(), event_id, date)?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// The reminder of an event, if it has one.
+    #[must_use]
+    pub fn get_event_reminder(&self, event_id: uuid::Uuid) -> Option<&Reminder> {
+        self.portfolio.reminders.iter().find(|r| r.event_id == event_id)
+    }
+
+    /// Events whose reminder is due on `as_of` (dated on or before it and
+    /// not acknowledged), newest event first like every event listing.
+    /// Deleted events are left out.
+    #[must_use]
+    pub fn get_due_reminders(&self, as_of: NaiveDate) -> Vec<&Event> {
+        let due: HashSet<uuid::Uuid> = self
+            .portfolio
+            .reminders
+            .iter()
+            .filter(|r| r.is_due(as_of))
+            .map(|r| r.event_id)
+            .collect();
+        newest_first(self.portfolio.active_events().filter(|e| due.contains(&e.id)))
+    }
+
+    /// Dismiss the reminder of an event; it won't be due again unless set anew.
+    pub fn acknowledge_reminder(&mut self, event_id: uuid::Uuid) -> Result<(), CoreError> {
+        self.portfolio_service
+            .acknowledge_reminder(&mut self.portfolio.edit(), event_id)?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    // ── Watchlist ───────────────────────────────────────────────────
+
+    /// Track the price of `asset` without holding it. Watched assets are
+    /// refreshed by `refresh_prices()` and don't affect holdings or analytics.
+    /// Returns `false` if the asset is already on the watchlist.
+    pub fn add_to_watchlist(&mut self, asset: Asset) -> Result<bool, CoreError> {
+        let added = self
+            .portfolio_service
+            .add_to_watchlist(&mut self.portfolio.edit(), asset)?;
+        if added {
+            self.mark_dirty();
+        }
+        Ok(added)
+    }
+
+    /// Stop tracking `asset`. Returns `false` if it was not on the watchlist.
+    pub fn remove_from_watchlist(&mut self, asset: &Asset) -> bool {
+        let removed = self
+            .portfolio_service
+            .remove_from_watchlist(&mut self.portfolio.edit(), asset);
+        if removed {
+            self.mark_dirty();
+        }
+        removed
+    }
+
+    /// Watched assets, in the order they were added.
+    #[must_use]
+    pub fn get_watchlist(&self) -> &[Asset] {
+        &self.portfolio.watchlist
+    }
+
+    /// Price of every watched asset on `date` in the default currency, with
+    /// the change since the previous day and the previous week.
+    ///
+    /// Prices on `date` are fetched if needed; the earlier prices are taken
+    /// from the cache only, leaving a change `None` when they are missing.
+    /// A failed lookup is reported in the quote's `error`.
+    pub async fn get_watchlist_quotes(&mut self, date: NaiveDate) -> Vec<WatchlistQuote> {
+        let currency = self.portfolio.settings.default_currency.clone();
+        let cached_prices = self.price_service.cache_only();
+        let mut quotes = Vec::with_capacity(self.portfolio.watchlist.len());
+
+        for asset in self.portfolio.watchlist.clone() {
+            let (price, error) = match self.asset_price(&asset, date, &currency).await {
+                Ok(price) => (Some(price), None),
+                Err(error) => (None, Some(error)),
+            };
+            // Change since 1 and 7 days before `date`
+            let mut changes = [None, None];
+            for (change, days) in changes.iter_mut().zip([1, 7]) {
+                let Some(earlier) = date.checked_sub_days(chrono::Days::new(days)) else {
+                    continue;
+                };
+                let earlier_price = self
+                    .currency_service
+                    .convert_asset_to_currency(
+                        &cached_prices,
+                        self.portfolio.price_cache_mut(),
+                        &asset,
+                        1.0,
+                        &currency,
+                        earlier,
+                    )
+                    .await;
+                if let (Some(price), Ok(earlier_price)) = (price, earlier_price) {
+                    if earlier_price > 0.0 {
+                        *change = Some((price - earlier_price) / earlier_price * 100.0);
+                    }
+                }
+            }
+            let [change_24h_pct, change_7d_pct] = changes;
+            quotes.push(WatchlistQuote {
+                asset,
+                date,
+                currency: currency.clone(),
+                price,
+                change_24h_pct,
+                change_7d_pct,
+                error,
+            });
+        }
+        quotes
+    }
+
+    // ── Corporate Actions ───────────────────────────────────────────
+
+    /// Record a stock split or symbol change (see `CorporateAction`).
+    /// Holdings, analytics and charts count the events with it applied;
+    /// every sell is revalidated and on failure nothing changes.
+    /// Returns the UUID of the action.
+    pub fn add_corporate_action(
+        &mut self,
+        action: CorporateAction,
+    ) -> Result<uuid::Uuid, CoreError> {
+        let id = action.id;
+        self.portfolio_service
+            .add_corporate_action(&mut self.portfolio.edit(), action)?;
+        self.mark_dirty();
+        Ok(id)
+    }
+
+    /// Change what a corporate action does and when it takes effect.
+    pub fn update_corporate_action(
+        &mut self,
+        action_id: uuid::Uuid,
+        action: CorporateActionType,
+        effective_date: NaiveDate,
+    ) -> Result<(), CoreError> {
+        self.portfolio_service.update_corporate_action(
+            &mut self.portfolio.edit(),
+            action_id,
+            action,
+            effective_date,
+        )?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Remove a corporate action by its UUID. Returns the removed action.
+    pub fn remove_corporate_action(
+        &mut self,
+        action_id: uuid::Uuid,
+    ) -> Result<CorporateAction, CoreError> {
+        let removed = self
+            .portfolio_service
+            .remove_corporate_action(&mut self.portfolio.edit(), action_id)?;
+        self.mark_dirty();
+        Ok(removed)
+    }
+
+    /// All corporate actions, by effective date.
+    #[must_use]
+    pub fn get_corporate_actions(&self) -> &[CorporateAction] {
+        &self.portfolio.corporate_actions
+    }
+
+    /// Record the splits in `table` (e.g., `KNOWN_SPLITS`) of the stocks
+    /// the portfolio has events of, skipping splits already recorded. All
+    /// are validated together; if any fails, none is added (all-or-nothing).
+    /// Returns the number of splits added.
+    pub fn apply_known_splits(&mut self, table: &[KnownSplit]) -> Result<usize, CoreError> {
+        let stocks: HashSet<&str> = self
+            .portfolio
+            .active_events()
+            .filter(|e| e.asset.asset_type == AssetType::Stock)
+            .map(|e| e.asset.symbol.as_str())
+            .collect();
+        let recorded = |split: &CorporateAction| {
+            self.portfolio.corporate_actions.iter().any(|a| {
+                a.symbol == split.symbol
+                    && a.effective_date == split.effective_date
+                    && matches!(a.action, CorporateActionType::Split { .. })
+            })
+        };
+        let splits: Vec<CorporateAction> = table
+            .iter()
+            .filter(|known| stocks.contains(known.symbol))
+            .filter_map(KnownSplit::to_action)
+            .filter(|split| !recorded(split))
+            .collect();
+        if splits.is_empty() {
+            return Ok(0);
+        }
+
+        let mut temp_portfolio = self.portfolio.clone();
+        for split in &splits {
+            self.portfolio_service
+                .add_corporate_action(&mut temp_portfolio, split.clone())?;
+        }
+        *self.portfolio.edit() = temp_portfolio;
+        self.mark_dirty();
+        Ok(splits.len())
+    }
+
+    // ── Accruals ────────────────────────────────────────────────────
+
+    /// Add a rule growing an asset's balance by a yearly percentage (e.g.,
+    /// staking rewards) without an event per payout; see `AccrualRule`.
+    /// Holdings, values and charts include the growth, and summaries count
+    /// it as income. Rules of one asset must not overlap.
+    /// Returns the UUID of the rule.
+    pub fn add_accrual_rule(&mut self, rule: AccrualRule) -> Result<uuid::Uuid, CoreError> {
+        let id = rule.id;
+        self.portfolio_service.add_accrual_rule(&mut self.portfolio.edit(), rule)?;
+        self.mark_dirty();
+        Ok(id)
+    }
+
+    /// Remove an accrual rule by its UUID. Fails if a sell relies on the
+    /// units it accrued. Returns the removed rule.
+    pub fn remove_accrual_rule(&mut self, rule_id: uuid::Uuid) -> Result<AccrualRule, CoreError> {
+        let removed = self
+            .portfolio_service
+            .remove_accrual_rule(&mut self.portfolio.edit(), rule_id)?;
+        self.mark_dirty();
+        Ok(removed)
+    }
+
+    /// All accrual rules, in the order they were added.
+    #[must_use]
+    pub fn get_accrual_rules(&self) -> &[AccrualRule] {
+        &self.portfolio.accrual_rules
+    }
+
+    /// Record what the accrual rules accrued up to `up_to` as `Interest`
+    /// events, one per rule and month (dated at the month end, or at a
+    /// rule's end date), for an auditable history. Holdings don't change.
+    /// All-or-nothing; returns the IDs of the new events.
+    pub fn materialize_accruals(&mut self, up_to: NaiveDate) -> Result<Vec<uuid::Uuid>, CoreError> {
+        let ids = self
+            .portfolio_service
+            .materialize_accruals(&mut self.portfolio.edit(), up_to)?;
+        self.mark_dirty();
+        Ok(ids)
+    }
+
+    // ── Asset Metadata ──────────────────────────────────────────────
+
+    /// Set the expected annual yield (dividend/interest, in percent) of an asset,
+    /// or clear it with `None`. Used by income projections.
+    pub fn set_asset_yield(
+        &mut self,
+        symbol: &str,
+        asset_type: &AssetType,
+        annual_yield_pct: Option<f64>,
+    ) -> Result<(), CoreError> {
+        self.portfolio_service.set_asset_yield(
+            &mut self.portfolio.edit(),
+            symbol,
+            asset_type,
+            annual_yield_pct,
+        )?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Replace all metadata of an asset (notes, display color, icon, custom
+    /// fields, yield). Empty metadata removes the entry. `color_hex` must be
+    /// "#RRGGBB" and custom field names must not be empty. Assets of
+    /// different types with the same symbol have their own metadata.
+    pub fn set_asset_metadata(
+        &mut self,
+        symbol: &str,
+        asset_type: &AssetType,
+        metadata: AssetMetadata,
+    ) -> Result<(), CoreError> {
+        self.portfolio_service
+            .set_asset_metadata(&mut self.portfolio.edit(), symbol, asset_type, metadata)?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Metadata stored for an asset symbol of `asset_type`, if any.
+    #[must_use]
+    pub fn get_asset_metadata(
+        &self,
+        symbol: &str,
+        asset_type: &AssetType,
+    ) -> Option<&AssetMetadata> {
+        self.portfolio_service
+            .get_asset_metadata(&self.portfolio, symbol, asset_type)
+    }
+
+    /// `get_unique_assets()` joined with their metadata, so the UI can render
+    /// each asset with its stored color, icon and notes.
+    #[must_use]
+    pub fn get_assets_with_metadata(&self) -> Vec<(&Asset, Option<&AssetMetadata>)> {
+        self.get_unique_assets()
+            .into_iter()
+            .map(|asset| (asset, self.get_asset_metadata(&asset.symbol, &asset.asset_type)))
+            .collect()
+    }
+
+    /// Display color of every asset in `get_unique_assets()`: the user's
+    /// `color_hex` if set, otherwise a stable palette color.
+    #[must_use]
+    pub fn get_asset_colors(&self) -> Vec<(Asset, String)> {
+        let assets: Vec<Asset> = self.get_unique_assets().into_iter().cloned().collect();
+        ChartService::assign_colors(&assets, &self.portfolio.asset_metadata)
+    }
+
+    /// Decimal places amounts of `asset` are shown and exported with: the
+    /// `display_precision` in its metadata, or `AssetType::default_precision`
+    /// (8 for crypto, 4 for stocks and metals, 2 for fiat).
+    #[must_use]
+    pub fn get_display_precision(&self, asset: &Asset) -> u32 {
+        self.portfolio_service
+            .display_precision(&self.portfolio, asset)
+    }
+
     // ── Prices ──────────────────────────────────────────────────────
 
     /// Get the price of a specific asset in the default currency on a given date.
-    /// Uses cache first, falls back to API providers.
+    /// Uses cache first, falls back to API providers. A renamed symbol (see
+    /// `add_corporate_action`) is priced under its current name.
     pub async fn get_asset_price(
         &mut self,
         asset: &Asset,
         date: NaiveDate,
     ) -> Result<f64, CoreError> {
         let currency = self.portfolio.settings.default_currency.clone();
+        self.asset_price(asset, date, &currency).await
+    }
+
+    /// Get the price of a specific asset in `currency` (e.g., "EUR") on a given
+    /// date without changing the default currency.
+    pub async fn get_asset_price_in_currency(
+        &mut self,
+        asset: &Asset,
+        date: NaiveDate,
+        currency: &str,
+    ) -> Result<f64, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        self.asset_price(asset, date, &currency).await
+    }
+
+    async fn asset_price(
+        &mut self,
+        asset: &Asset,
+        date: NaiveDate,
+        currency: &str,
+    ) -> Result<f64, CoreError> {
+        // A renamed symbol is quoted under its new name
+        let asset = Asset {
+            symbol: self.portfolio.current_symbol(&asset.symbol),
+            ..asset.clone()
+        };
         self.currency_service
             .convert_asset_to_currency(
                 &self.price_service,
-                &mut self.portfolio.price_cache,
-                asset,
+                self.portfolio.price_cache_mut(),
+                &asset,
                 1.0,
-                &currency,
+                currency,
                 date,
             )
             .await
     }
 
-    /// Refresh current prices for all held assets from APIs.
-    pub async fn refresh_prices(&mut self) -> Result<(), CoreError> {
-        let today = chrono::Utc::now().date_naive();
-        let holdings = self.get_holdings(today);
-        let currency = self.portfolio.settings.default_currency.clone();
+    /// Refresh today's prices of all held and watched assets from APIs.
+    /// Assets already refreshed today are skipped unless `force` is set.
+    pub async fn refresh_prices(&mut self, force: bool) -> Result<RefreshReport, CoreError> {
+        self.refresh_prices_with_progress(force, |_| {}, None).await
+    }
 
-        for asset in holdings.keys() {
-            self.price_service
-                .get_price(
-                    &mut self.portfolio.price_cache,
-                    &asset.symbol,
-                    &currency,
-                    today,
-                    &asset.asset_type,
-                )
-                .await?;
+    /// Refresh today's prices, reporting progress once per asset.
+    /// Memoized provider responses are bypassed, so every fetch is fresh.
+    /// An asset that can't be fetched is reported in `RefreshReport::failed`
+    /// and the rest are still refreshed.
+    ///
+    /// If `cancel` (or else the token set with `set_cancellation_token`) is
+    /// triggered, returns `CoreError::Cancelled` and aborts the request in
+    /// flight; prices refreshed before that point stay cached.
+    pub async fn refresh_prices_with_progress(
+        &mut self,
+        force: bool,
+        on_progress: impl FnMut(Progress),
+        cancel: Option<&CancellationToken>,
+    ) -> Result<RefreshReport, CoreError> {
+        let price_service = self.scoped_price_service(cancel).bypassing_response_cache();
+        let result = self.refresh_held_prices(&price_service, force, on_progress).await;
+        self.invalidate_memos();
+        result
+    }
+
+    /// Fetch and cache price ranges for every asset in the portfolio over
+    /// `from..=to`, so later chart/summary calls can run from cache.
+    /// Only the gaps reported by `get_cache_coverage` are fetched, and days
+    /// old enough for month closes (see `set_monthly_price_age`) only get
+    /// those.
+    pub async fn prefetch_prices(&mut self, from: NaiveDate, to: NaiveDate) -> Result<(), CoreError> {
+        self.prefetch_prices_with_progress(from, to, |_| {}, None).await
+    }
+
+    /// Prefetch price ranges, reporting progress once per (symbol, currency) fetch.
+    ///
+    /// If `cancel` (or else the token set with `set_cancellation_token`) is
+    /// triggered, returns `CoreError::Cancelled` and aborts the request in
+    /// flight; ranges fetched before that point stay cached.
+    pub async fn prefetch_prices_with_progress(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        on_progress: impl FnMut(Progress),
+        cancel: Option<&CancellationToken>,
+    ) -> Result<(), CoreError> {
+        self.validate_chart_range(from, to)?;
+        let price_service = self.scoped_price_service(cancel);
+        self.prefetch_ranges(&price_service, from, to, on_progress).await
+    }
+
+    /// Fetch the price range of every asset (see `prefetch_prices_with_progress`).
+    async fn prefetch_ranges(
+        &mut self,
+        price_service: &PriceService,
+        from: NaiveDate,
+        to: NaiveDate,
+        mut on_progress: impl FnMut(Progress),
+    ) -> Result<(), CoreError> {
+        let fetches = self.range_fetches();
+        // No prices exist for future days (an empty range has no gaps)
+        let today = chrono::Utc::now().date_naive();
+        let to = to.min(today);
+        let first_daily = price_service.monthly_before(today).map_or(from, |d| d.max(from));
+        let total = fetches.len();
+        for (completed, (symbol, target, asset_type)) in fetches.iter().enumerate() {
+            if Self::is_cancelled(price_service) {
+                return Err(CoreError::Cancelled);
+            }
+            on_progress(Progress {
+                completed,
+                total,
+                current_symbol: Some(symbol.clone()),
+            });
+            if from < first_daily {
+                let last_monthly = to.min(first_daily - chrono::Duration::days(1));
+                price_service
+                    .get_monthly_prices(
+                        self.portfolio.price_cache_mut(),
+                        symbol,
+                        target,
+                        from,
+                        last_monthly,
+                        asset_type,
+                    )
+                    .await?;
+                self.mark_dirty();
+            }
+            let coverage =
+                self.portfolio.price_cache.coverage(symbol, target, first_daily, to, asset_type);
+            for gap in &coverage.gaps {
+                price_service
+                    .get_price_range(
+                        self.portfolio.price_cache_mut(),
+                        symbol,
+                        target,
+                        gap.from,
+                        gap.to,
+                        asset_type,
+                    )
+                    .await?;
+                self.mark_dirty();
+            }
         }
 
+        on_progress(Progress {
+            completed: total,
+            total,
+            current_symbol: None,
+        });
         Ok(())
     }
 
+    /// Which days of `from..=to` (up to today) are cached for every asset
+    /// held and the exchange rates valuing them, in the pairs and order
+    /// `prefetch_prices` fetches. Only reads the cache; nothing is fetched.
+    ///
+    /// Summing `missing_days` tells how many prices a prefetch would
+    /// download. Days priced with month closes (see `set_monthly_price_age`)
+    /// are left out.
+    pub fn get_cache_coverage(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<CacheCoverage>, CoreError> {
+        self.validate_chart_range(from, to)?;
+        let today = chrono::Utc::now().date_naive();
+        let to = to.min(today);
+        let from = self.price_service.monthly_before(today).map_or(from, |d| d.max(from));
+        if to < from {
+            return Ok(Vec::new());
+        }
+        let cache = &self.portfolio.price_cache;
+        Ok(self
+            .range_fetches()
+            .iter()
+            .map(|(symbol, target, asset_type)| {
+                cache.coverage(symbol, target, from, to, asset_type)
+            })
+            .collect())
+    }
+
+    /// The (symbol, currency, type) price ranges valuing the portfolio.
+    fn range_fetches(&self) -> Vec<(String, String, AssetType)> {
+        let currency = self.portfolio.settings.default_currency.clone();
+
+        // Mirror CurrencyService: non-fiat assets are priced in USD, then USD → target.
+        let mut fetches: Vec<(String, String, AssetType)> = Vec::new();
+        for asset in self.get_unique_assets() {
+            let fetch = match asset.asset_type {
+                AssetType::Fiat if asset.symbol == currency => continue,
+                AssetType::Fiat => (asset.symbol.clone(), currency.clone(), AssetType::Fiat),
+                _ => (asset.symbol.clone(), "USD".to_string(), asset.asset_type.clone()),
+            };
+            if currency != "USD" && fetch.1 == "USD" {
+                let fx = ("USD".to_string(), currency.clone(), AssetType::Fiat);
+                if !fetches.contains(&fx) {
+                    fetches.push(fx);
+                }
+            }
+            if !fetches.contains(&fetch) {
+                fetches.push(fetch);
+            }
+        }
+        fetches
+    }
+
     // ── Cache Management ────────────────────────────────────────────
 
     /// Get the total number of cached price points.
@@ -452,17 +2067,100 @@ impl SavingsTracker {
     /// Remove all cached price points older than `before` date.
     /// Returns the number of entries removed.
     pub fn cache_prune_before(&mut self, before: NaiveDate) -> usize {
-        let removed = self.portfolio.price_cache.prune_before(before);
+        let removed = self.portfolio.edit().price_cache.prune_before(before);
+        if removed > 0 {
+            self.mark_dirty();
+        }
+        removed
+    }
+
+    /// Remove the cached prices of (symbol, currency) dated within `range`
+    /// (inclusive; `None`: all of them), e.g. a bad quote a provider returned.
+    /// Later lookups fetch them again. Memoized provider responses are dropped.
+    /// Returns the number of prices removed (0 if none are cached).
+    pub fn cache_remove(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        range: Option<(NaiveDate, NaiveDate)>,
+        asset_type: &AssetType,
+    ) -> usize {
+        let removed = self
+            .portfolio
+            .edit()
+            .price_cache
+            .remove(symbol, currency, range, asset_type);
         if removed > 0 {
-            self.dirty = true;
+            self.response_cache.clear();
+            self.mark_dirty();
         }
         removed
     }
 
-    /// Clear all cached price data.
+    /// Replace the cached prices of (symbol, currency) from `from` to `to`
+    /// (inclusive) with a fresh fetch from the providers, bypassing memoized
+    /// responses. The old prices are removed only once the fetch succeeds.
+    /// Returns the number of prices now cached for the range.
+    pub async fn cache_refetch(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Result<usize, CoreError> {
+        self.validate_chart_range(from, to)?;
+        let mut fresh = PriceCache::new();
+        let points = self
+            .price_service
+            .bypassing_response_cache()
+            .get_price_range(&mut fresh, symbol, currency, from, to, asset_type)
+            .await?;
+
+        let cache = self.portfolio.price_cache_mut();
+        cache.remove(symbol, currency, Some((from, to)), asset_type);
+        cache.merge(fresh);
+        self.mark_dirty();
+        Ok(points.len())
+    }
+
+    /// Clear all cached price data, including memoized provider responses.
     pub fn cache_clear(&mut self) {
-        self.portfolio.price_cache.clear();
-        self.dirty = true;
+        self.portfolio.edit().price_cache.clear();
+        self.response_cache.clear();
+        self.mark_dirty();
+    }
+
+    /// Set how long provider responses are memoized within the session
+    /// (default: 5 minutes). `Duration::ZERO` disables memoization.
+    /// Previously memoized responses are dropped.
+    pub fn set_response_cache_ttl(&mut self, ttl: Duration) {
+        self.set_response_cache(Arc::new(MemoryResponseCache::with_ttl(ttl)));
+    }
+
+    /// Replace the memo of provider responses (e.g., with one that records
+    /// hits and misses).
+    pub fn set_response_cache(&mut self, cache: Arc<dyn ResponseCache>) {
+        self.response_cache = cache;
+        self.price_service
+            .set_response_cache(Some(Arc::clone(&self.response_cache)));
+    }
+
+    /// Cancel every provider request with `token` (`None`: never), e.g. one
+    /// cancelled when the user leaves the chart view. Requests in flight are
+    /// aborted and lookups fail with `CoreError::Cancelled`; cached prices
+    /// are still used. The `cancel` argument of the `*_with_progress` methods
+    /// takes precedence for their duration. Not saved.
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.price_service.set_cancellation(token);
+    }
+
+    /// Let price lookups for past dates that no provider can answer return
+    /// the nearest cached price instead of `CoreError::PriceNotAvailable`
+    /// (off by default). Applies to valuations and charts alike; not saved.
+    pub fn set_price_fallback(&mut self, fallback: PriceFallback) {
+        self.price_service.set_fallback(fallback);
+        self.invalidate_memos();
     }
 
     // ── Settings ────────────────────────────────────────────────────
@@ -470,14 +2168,173 @@ impl SavingsTracker {
     /// Set the default display currency (e.g., "PLN", "USD", "EUR").
     /// Currency code must be a 3-letter alphabetic string.
     pub fn set_default_currency(&mut self, currency: String) -> Result<(), CoreError> {
-        let trimmed = currency.trim().to_uppercase();
-        if trimmed.len() != 3 || !trimmed.chars().all(|c| c.is_ascii_alphabetic()) {
-            return Err(CoreError::ValidationError(
-                format!("Invalid currency code '{currency}': must be exactly 3 ASCII letters (e.g., USD, EUR, PLN)"),
-            ));
+        self.portfolio.edit().settings.default_currency = Self::normalize_currency_code(&currency)?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Set the pivot currencies used, in order, to triangulate a fiat rate
+    /// when no provider quotes the pair directly (default: USD, then EUR).
+    /// Each code must be a 3-letter alphabetic string; duplicates are dropped.
+    pub fn set_pivot_currencies(&mut self, currencies: Vec<String>) -> Result<(), CoreError> {
+        let mut pivots: Vec<String> = Vec::with_capacity(currencies.len());
+        for currency in &currencies {
+            let code = Self::normalize_currency_code(currency)?;
+            if !pivots.contains(&code) {
+                pivots.push(code);
+            }
         }
-        self.portfolio.settings.default_currency = trimmed;
-        self.dirty = true;
+        self.portfolio.edit().settings.pivot_currencies = pivots;
+        self.rebuild_currency_services();
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Set the unit of metal holdings and metal prices (default: troy ounce).
+    /// Metal event amounts (and snapshot amounts) are converted to the new
+    /// unit, so holdings keep their value; cached metal prices are converted
+    /// when next used.
+    pub fn set_metal_unit(&mut self, unit: MetalUnit) {
+        let previous = self.portfolio.settings.metal_unit;
+        if previous == unit {
+            return;
+        }
+        let amount_factor = unit.price_factor(previous);
+        let price_factor = previous.price_factor(unit);
+        let mut edit = self.portfolio.edit();
+        let portfolio = &mut *edit;
+        for event in portfolio.events.iter_mut().chain(portfolio.trash.iter_mut()) {
+            if event.asset.asset_type != AssetType::Metal {
+                continue;
+            }
+            event.amount *= amount_factor;
+            if let Some(recorded) = &mut event.recorded_value {
+                recorded.quote_unit_price *= price_factor;
+                recorded.unit_price *= price_factor;
+            }
+        }
+        for snapshot in &mut portfolio.snapshots {
+            for holding in &mut snapshot.assets {
+                if holding.asset.asset_type == AssetType::Metal {
+                    holding.amount *= amount_factor;
+                }
+            }
+        }
+        portfolio.mark_events_changed();
+        portfolio.settings.metal_unit = unit;
+        drop(edit);
+        self.rebuild_price_service();
+        self.mark_dirty();
+    }
+
+    /// Set the rules events are validated against when they are added,
+    /// updated or removed. Existing events are not revalidated.
+    /// `max_amount`, if set, must be a positive number.
+    pub fn set_validation_policy(&mut self, policy: ValidationPolicy) -> Result<(), CoreError> {
+        if let Some(max_amount) = policy.max_amount {
+            if !max_amount.is_finite() || max_amount <= 0.0 {
+                return Err(CoreError::ValidationError(format!(
+                    "Invalid maximum amount {max_amount}: must be a positive number"
+                )));
+            }
+        }
+        self.portfolio.edit().settings.validation_policy = policy;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Set how many days a lot must be held for its sale to be long-term
+    /// in tax lot reports (default: 365).
+    pub fn set_long_term_holding_days(&mut self, days: u32) {
+        if self.portfolio.settings.long_term_holding_days == days {
+            return;
+        }
+        self.portfolio.edit().settings.long_term_holding_days = days;
+        self.mark_dirty();
+    }
+
+    /// Set how sales are matched against buy lots for the average buy
+    /// price of summary holdings (default: FIFO).
+    pub fn set_cost_basis_method(&mut self, method: CostBasisMethod) {
+        if self.portfolio.settings.cost_basis_method == method {
+            return;
+        }
+        self.portfolio.edit().settings.cost_basis_method = method;
+        self.mark_dirty();
+    }
+
+    /// Set the month (1-12) fiscal years start in, used by annual reports
+    /// and yearly event groups (default: 1, the calendar year).
+    pub fn set_fiscal_year_start_month(&mut self, month: u32) -> Result<(), CoreError> {
+        if !(1..=12).contains(&month) {
+            return Err(CoreError::ValidationError(format!(
+                "Invalid fiscal year start month {month}: must be between 1 and 12"
+            )));
+        }
+        if self.portfolio.settings.fiscal_year_start_month == month {
+            return Ok(());
+        }
+        self.portfolio.edit().settings.fiscal_year_start_month = month;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Set the day weekly event groups start on (default: Monday).
+    pub fn set_week_start(&mut self, week_start: WeekStart) {
+        if self.portfolio.settings.week_start == week_start {
+            return;
+        }
+        self.portfolio.edit().settings.week_start = week_start;
+        self.mark_dirty();
+    }
+
+    /// Set the number and date conventions used by `format_value`
+    /// (default: English).
+    pub fn set_locale(&mut self, locale: Locale) {
+        if self.portfolio.settings.locale == locale {
+            return;
+        }
+        self.portfolio.edit().settings.locale = locale;
+        self.mark_dirty();
+    }
+
+    /// `value` as an amount in the default currency, formatted for the
+    /// portfolio's locale (e.g., "$1,234.56" or "1 234,56 zł").
+    pub fn format_value(&self, value: f64) -> String {
+        let settings = &self.portfolio.settings;
+        formatting::format_money(value, &settings.default_currency, settings.locale)
+    }
+
+    /// Set how holdings no price can be found for (e.g., a delisted token)
+    /// are valued in summaries and charts: fail (the default), the latest
+    /// cached price, the asset's `AssetMetadata::manual_price`, or zero.
+    /// `HoldingSummary::price_status` reports how each holding was priced.
+    pub fn set_unpriceable_asset_policy(&mut self, policy: UnpriceableAssetPolicy) {
+        if self.portfolio.settings.unpriceable_asset_policy == policy {
+            return;
+        }
+        self.portfolio.edit().settings.unpriceable_asset_policy = policy;
+        self.mark_dirty();
+    }
+
+    /// Replace the CPI source used for real values (default: the embedded table).
+    /// Manual CPI overrides are kept.
+    pub fn set_inflation_provider(&mut self, provider: Box<dyn InflationProvider>) {
+        self.inflation_service.set_provider(provider);
+        self.invalidate_memos();
+    }
+
+    /// Set the CPI value for one month of a country (e.g., a newly published
+    /// month), taking precedence over the provider. Not saved in the portfolio file.
+    pub fn set_cpi_override(
+        &mut self,
+        country: &str,
+        year: i32,
+        month: u32,
+        index: f64,
+    ) -> Result<(), CoreError> {
+        self.inflation_service.set_override(country, year, month, index)?;
+        self.invalidate_memos();
         Ok(())
     }
 
@@ -491,25 +2348,241 @@ impl SavingsTracker {
     /// Rebuilds the provider registry so the new key takes effect immediately.
     pub fn set_api_key(&mut self, provider: String, key: String) {
         self.portfolio
+            .edit()
             .settings
             .api_keys
             .insert(provider, key);
 
         // Rebuild registry with updated API keys
-        let registry = PriceProviderRegistry::new_with_defaults(&self.portfolio.settings.api_keys);
-        self.price_service = PriceService::new(registry);
-        self.dirty = true;
+        self.rebuild_price_service();
+        self.mark_dirty();
+    }
+
+    /// Set how many seconds a provider request may take before it fails as
+    /// timed out (default: 10). Rebuilds the provider registry.
+    pub fn set_provider_timeout(&mut self, secs: u64) -> Result<(), CoreError> {
+        if secs == 0 {
+            return Err(CoreError::ValidationError(
+                "Provider timeout must be at least 1 second".into(),
+            ));
+        }
+        if self.portfolio.settings.provider_timeout_secs == secs {
+            return Ok(());
+        }
+        self.portfolio.edit().settings.provider_timeout_secs = secs;
+        self.rebuild_price_service();
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Set the request timeout of one provider by id (e.g., "alphavantage",
+    /// see `PriceProvider::id`), overriding `set_provider_timeout`; `None`
+    /// removes the override. Rebuilds the provider registry.
+    pub fn set_provider_timeout_for(
+        &mut self,
+        provider: &str,
+        secs: Option<u64>,
+    ) -> Result<(), CoreError> {
+        let removing = secs.is_none();
+        let provider = self.provider_setting_key(provider, removing, |s| &s.provider_timeouts)?;
+        if secs == Some(0) {
+            return Err(CoreError::ValidationError(
+                "Provider timeout must be at least 1 second".into(),
+            ));
+        }
+        let previous = {
+            let timeouts = &mut self.portfolio.edit().settings.provider_timeouts;
+            match secs {
+                Some(secs) => timeouts.insert(provider, secs),
+                None => timeouts.remove(&provider),
+            }
+        };
+        if previous == secs {
+            return Ok(());
+        }
+        self.rebuild_price_service();
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Set the factor a provider quote may differ by from a cached price of
+    /// the same or a nearby day before another provider has to confirm it
+    /// (default: 10); `None` disables the check.
+    pub fn set_max_price_change_factor(&mut self, factor: Option<f64>) -> Result<(), CoreError> {
+        if !Settings::is_valid_max_price_change_factor(factor) {
+            return Err(CoreError::ValidationError(
+                "Price change factor must be a finite number above 1".into(),
+            ));
+        }
+        if self.portfolio.settings.max_price_change_factor == factor {
+            return Ok(());
+        }
+        self.portfolio.edit().settings.max_price_change_factor = factor;
+        self.price_service.set_max_price_change(factor);
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Allow charts, simulations and price prefetches over at most `days`
+    /// days (default: 3650, about 10 years); `None` allows any range. Long
+    /// charts get weekly or monthly points (see `ChartResolution`), so even
+    /// decades stay a few hundred points.
+    pub fn set_max_chart_range_days(&mut self, days: Option<u32>) -> Result<(), CoreError> {
+        if days == Some(0) {
+            return Err(CoreError::ValidationError(
+                "Maximum chart range must be at least 1 day".into(),
+            ));
+        }
+        if self.portfolio.settings.max_chart_range_days == days {
+            return Ok(());
+        }
+        self.portfolio.edit().settings.max_chart_range_days = days;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Price dates more than `days` days in the past with the close of their
+    /// month when no daily price is cached (e.g., 365 for events from years
+    /// ago); `None` prices every date daily (the default). Charts and
+    /// `prefetch_prices` then fetch one price a month for those dates.
+    pub fn set_monthly_price_age(&mut self, days: Option<u32>) -> Result<(), CoreError> {
+        if days == Some(0) {
+            return Err(CoreError::ValidationError(
+                "Monthly price age must be at least 1 day".into(),
+            ));
+        }
+        if self.portfolio.settings.monthly_price_age_days == days {
+            return Ok(());
+        }
+        self.portfolio.edit().settings.monthly_price_age_days = days;
+        self.price_service.set_monthly_price_age(days);
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Allow at most `limit` requests a day (UTC) to a provider by id (e.g.,
+    /// "alphavantage", whose free tier allows 25; see `PriceProvider::id`);
+    /// `None` removes the limit. Once it is reached the provider is skipped
+    /// with a rate-limited error (see `CoreError::rate_limited`) and the next
+    /// provider is tried.
+    pub fn set_provider_daily_limit(
+        &mut self,
+        provider: &str,
+        limit: Option<u32>,
+    ) -> Result<(), CoreError> {
+        let removing = limit.is_none();
+        let provider =
+            self.provider_setting_key(provider, removing, |s| &s.provider_daily_limits)?;
+        if limit == Some(0) {
+            return Err(CoreError::ValidationError(
+                "Daily request limit must be at least 1".into(),
+            ));
+        }
+        let previous = {
+            let limits = &mut self.portfolio.edit().settings.provider_daily_limits;
+            match limit {
+                Some(limit) => limits.insert(provider, limit),
+                None => limits.remove(&provider),
+            }
+        };
+        if previous == limit {
+            return Ok(());
+        }
+        let limits = self.portfolio.settings.provider_daily_limits.clone();
+        self.price_service.set_daily_limits(limits);
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Send every provider request through the HTTP(S) proxy at `url`
+    /// (e.g., "http://proxy.corp:3128"); `None` connects directly.
+    /// Rebuilds the provider registry. Native only; browsers use the
+    /// system proxy.
+    pub fn set_proxy(&mut self, url: Option<&str>) -> Result<(), CoreError> {
+        let url = url.map(str::trim);
+        if let Some(url) = url {
+            http::validate_proxy_url(url)?;
+        }
+        if self.portfolio.settings.proxy_url.as_deref() == url {
+            return Ok(());
+        }
+        self.portfolio.edit().settings.proxy_url = url.map(str::to_string);
+        self.rebuild_price_service();
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Add `email` to the `User-Agent` of provider requests (e.g.,
+    /// "savings-tracker/0.1.0 (+mailto:me@example.com)"), as CoinCap and
+    /// Frankfurter ask of their users; `None` removes it. Rebuilds the
+    /// provider registry.
+    pub fn set_contact_email(&mut self, email: Option<&str>) -> Result<(), CoreError> {
+        let email = email.map(str::trim);
+        if let Some(email) = email {
+            http::validate_contact_email(email)?;
+        }
+        if self.portfolio.settings.contact_email.as_deref() == email {
+            return Ok(());
+        }
+        self.portfolio.edit().settings.contact_email = email.map(str::to_string);
+        self.rebuild_price_service();
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Set the hosts reached directly rather than through the proxy
+    /// (e.g., "localhost", ".corp.example", "10.0.0.0/8").
+    /// Rebuilds the provider registry.
+    pub fn set_no_proxy(&mut self, hosts: Vec<String>) {
+        let hosts: Vec<String> = hosts
+            .iter()
+            .map(|host| host.trim().to_string())
+            .filter(|host| !host.is_empty())
+            .collect();
+        if self.portfolio.settings.no_proxy == hosts {
+            return;
+        }
+        self.portfolio.edit().settings.no_proxy = hosts;
+        self.rebuild_price_service();
+        self.mark_dirty();
+    }
+
+    /// Map `symbol` to a provider-specific asset id (e.g., "coincap", "PEPE", "pepe"),
+    /// correcting or pre-empting the provider's own symbol resolution.
+    /// Saved in the settings; rebuilds the provider registry so it takes effect.
+    pub fn set_symbol_override(
+        &mut self,
+        provider: &str,
+        symbol: &str,
+        id: &str,
+    ) -> Result<(), CoreError> {
+        let provider = provider.trim().to_lowercase();
+        let symbol = symbol.trim().to_uppercase();
+        let id = id.trim();
+        if provider.is_empty() || symbol.is_empty() || id.is_empty() {
+            return Err(CoreError::ValidationError(
+                "Provider, symbol and id of a symbol override must not be empty".into(),
+            ));
+        }
+        self.portfolio
+            .edit()
+            .settings
+            .symbol_overrides
+            .entry(provider)
+            .or_default()
+            .insert(symbol, id.to_string());
+        self.rebuild_price_service();
+        self.mark_dirty();
+        Ok(())
     }
 
     /// Remove an API key for a provider.
     /// Rebuilds the provider registry so the removal takes effect immediately.
     pub fn remove_api_key(&mut self, provider: &str) -> bool {
-        let removed = self.portfolio.settings.api_keys.remove(provider).is_some();
+        let removed = self.portfolio.edit().settings.api_keys.remove(provider).is_some();
         if removed {
-            let registry =
-                PriceProviderRegistry::new_with_defaults(&self.portfolio.settings.api_keys);
-            self.price_service = PriceService::new(registry);
-            self.dirty = true;
+            self.rebuild_price_service();
+            self.mark_dirty();
         }
         removed
     }
@@ -533,63 +2606,281 @@ impl SavingsTracker {
         StorageManager::load_from_bytes(last_saved_bytes, current_password)?;
 
         // Re-encrypt with the new password
+        self.sync_symbol_overrides();
+        self.sync_api_usage();
         let new_bytes = StorageManager::save_to_bytes(&self.portfolio, new_password)?;
         self.dirty = false;
         Ok(new_bytes)
     }
 
-    /// Returns `true` if the portfolio has been modified since the last save or load.
+    /// Returns `true` if the portfolio has been modified since the last save
+    /// or load, and autosave hasn't saved it since.
     #[must_use]
     pub fn has_unsaved_changes(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some((_, revision)) = *self.last_autosave.lock().unwrap_or_else(|e| e.into_inner()) {
+            if revision == self.revision {
+                return false;
+            }
+        }
         self.dirty
     }
 
-    // ── Search & Sorting ────────────────────────────────────────────
+    /// Hash of the portfolio's logical content (events, settings, alerts,
+    /// ...; not the price cache), see `Portfolio::content_hash`. Equal to
+    /// `StorageManager::file_content_hash` of the bytes last saved or loaded
+    /// until the portfolio changes, so a file changed elsewhere (e.g., by
+    /// cloud sync) can be told apart from the open one.
+    #[must_use]
+    pub fn content_hash(&self) -> u64 {
+        self.portfolio.content_hash()
+    }
+
+    // ── Autosave ────────────────────────────────────────────────────
+
+    /// Save to the file at `path` in the background after every change
+    /// (native only, not WASM). Saving waits until no change has been made
+    /// for `debounce_secs`, so a burst of edits is written once, and
+    /// replaces the file atomically. Unsaved changes are saved right away
+    /// (after the window); an earlier autosave setup is replaced.
+    ///
+    /// Failed autosaves don't interrupt anything; they are reported by
+    /// `get_autosave_warnings`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_autosave(
+        &mut self,
+        path: &str,
+        password: storage::secret::SecretString,
+        debounce_secs: u64,
+    ) {
+        // Stop the previous worker first, saving what it has pending
+        self.autosave = None;
+        self.autosave = Some(autosave::Autosaver::start(
+            path.to_string(),
+            password,
+            Duration::from_secs(debounce_secs),
+            self.portfolio.published(),
+            Arc::clone(&self.last_autosave),
+            Arc::clone(&self.autosave_warnings),
+            Arc::clone(&self.autosave_hook),
+        ));
+        if self.has_unsaved_changes() {
+            self.schedule_autosave();
+        }
+    }
+
+    /// Stop autosaving. A pending save is written first, so this waits for it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn disable_autosave(&mut self) {
+        self.autosave = None;
+    }
+
+    /// Whether autosave is on (see `set_autosave`).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn is_autosave_enabled(&self) -> bool {
+        self.autosave.is_some()
+    }
+
+    /// Call `hook` on the autosave worker after each autosave, successful or
+    /// not (see `last_autosave_time` and `get_autosave_warnings`), e.g. to
+    /// update a "saved" indicator. Replaces an earlier hook.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_autosave_hook(&mut self, hook: impl Fn() + Send + 'static) {
+        *self.autosave_hook.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(hook));
+    }
+
+    /// When autosave last saved the portfolio, if it has.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn last_autosave_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.last_autosave
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map(|(at, _)| at)
+    }
+
+    /// Autosaves that failed (e.g., the disk was full), oldest first. The
+    /// most recent `price_warnings::DEFAULT_MAX_WARNINGS` are kept.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn get_autosave_warnings(&self) -> Vec<models::warning::AutosaveWarning> {
+        self.autosave_warnings.recent()
+    }
+
+    /// Forget the warnings returned by `get_autosave_warnings`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn clear_autosave_warnings(&mut self) {
+        self.autosave_warnings.clear();
+    }
+
+    /// What changed from `other` (e.g., the last saved version) to the current
+    /// portfolio: added, removed and modified events, changed settings and the
+    /// change in cached prices. Useful to review changes before saving.
+    pub fn diff(&self, other: &Portfolio) -> PortfolioDiff {
+        PortfolioDiff::between(other, &self.portfolio)
+    }
+
+    /// Like `diff`, against an encrypted portfolio (e.g., the file about to
+    /// be overwritten).
+    pub fn diff_bytes(&self, encrypted: &[u8], password: &str) -> Result<PortfolioDiff, CoreError> {
+        let other = StorageManager::load_from_bytes(encrypted, password)?;
+        Ok(self.diff(&other))
+    }
+
+    // ── Search & Sorting ────────────────────────────────────────────
+
+    /// Search events by matching query against symbol, name, and notes (case-insensitive).
+    /// Returns newest-first, consistent with `get_events()`.
+    #[must_use]
+    pub fn search_events(&self, query: &str) -> Vec<&Event> {
+        self.search_events_with(query, &SearchOptions::default())
+    }
+
+    /// Search events like `search_events`, optionally matching the query
+    /// fuzzily (see `SearchOptions`). Uses the portfolio's search index,
+    /// which is updated incrementally after events change. Returns
+    /// newest-first, consistent with `get_events()`.
+    #[must_use]
+    pub fn search_events_with(&self, query: &str, options: &SearchOptions) -> Vec<&Event> {
+        let positions = self.portfolio.search_index().search(query, options);
+        newest_first(
+            positions
+                .into_iter()
+                .map(|pos| &self.portfolio.events[pos])
+                .filter(|e| !e.is_deleted() && !e.is_planned),
+        )
+    }
+
+    /// Get events sorted by a specific order. Events the order ranks equal
+    /// are sorted newest first, then by id.
+    #[must_use]
+    pub fn get_events_sorted(&self, order: &EventSortOrder) -> Vec<&Event> {
+        self.get_events_sorted_multi(std::slice::from_ref(order))
+    }
+
+    /// Get events sorted by several orders: by the first, events it ranks
+    /// equal by the second, and so on. Ties left by all of them are sorted
+    /// newest first, then by id, so the result never depends on insertion
+    /// order (see `EventSortOrder::compare_by`).
+    #[must_use]
+    pub fn get_events_sorted_multi(&self, orders: &[EventSortOrder]) -> Vec<&Event> {
+        let mut events: Vec<&Event> = self.portfolio.actual_events().collect();
+        events.sort_by(|a, b| EventSortOrder::compare_by(orders, a, b));
+        events
+    }
+
+    /// Iterate the actual events in `order` (e.g., for virtual scrolling),
+    /// in the order of `get_events_sorted`. The date orders read the stored
+    /// events directly, without collecting them; other orders sort all
+    /// events first.
+    pub fn events_iter(
+        &self,
+        order: &EventSortOrder,
+    ) -> impl DoubleEndedIterator<Item = &Event> + '_ {
+        EventsIter::new(&self.portfolio.events, order)
+    }
 
-    /// Search events by matching query against symbol, name, and notes (case-insensitive).
+    /// The actual event at `index` in `order`, if there are that many.
     #[must_use]
-    pub fn search_events(&self, query: &str) -> Vec<&Event> {
-        let q = query.to_lowercase();
-        self.portfolio
-            .events
-            .iter()
-            .filter(|e| {
-                e.asset.symbol.to_lowercase().contains(&q)
-                    || e.asset.name.to_lowercase().contains(&q)
-                    || e.notes.as_deref().unwrap_or("").to_lowercase().contains(&q)
-            })
-            .collect()
+    pub fn get_event_at(&self, index: usize, order: &EventSortOrder) -> Option<&Event> {
+        self.events_iter(order).nth(index)
     }
 
-    /// Get events sorted by a specific order.
+    /// The actual events at positions `range` in `order`, e.g. the visible
+    /// rows of a list. Positions past the last event are left out. For the
+    /// date orders only the returned events are collected.
     #[must_use]
-    pub fn get_events_sorted(&self, order: &EventSortOrder) -> Vec<&Event> {
-        let mut events: Vec<&Event> = self.portfolio.events.iter().collect();
-        match order {
-            EventSortOrder::DateDesc => events.sort_by(|a, b| b.date.cmp(&a.date)),
-            EventSortOrder::DateAsc => events.sort_by(|a, b| a.date.cmp(&b.date)),
-            EventSortOrder::AmountDesc => events.sort_by(|a, b| b.amount.partial_cmp(&a.amount).unwrap_or(std::cmp::Ordering::Equal)),
-            EventSortOrder::AmountAsc => events.sort_by(|a, b| a.amount.partial_cmp(&b.amount).unwrap_or(std::cmp::Ordering::Equal)),
-            EventSortOrder::AssetAsc => events.sort_by(|a, b| a.asset.symbol.cmp(&b.asset.symbol)),
-            EventSortOrder::AssetDesc => events.sort_by(|a, b| b.asset.symbol.cmp(&a.asset.symbol)),
-        }
-        events
+    pub fn get_events_slice(&self, range: Range<usize>, order: &EventSortOrder) -> Vec<&Event> {
+        let len = range.len();
+        self.events_iter(order).skip(range.start).take(len).collect()
     }
 
     /// Get events filtered by asset type (e.g., show all Crypto events).
+    /// Returns newest-first, consistent with `get_events()`.
     #[must_use]
     pub fn get_events_for_asset_type(&self, asset_type: &AssetType) -> Vec<&Event> {
-        self.portfolio
+        newest_first(self.portfolio.actual_events().filter(|e| &e.asset.asset_type == asset_type))
+    }
+
+    /// Events matching `filter` (all actual events if `None`), grouped by
+    /// week, month or fiscal year (see `Settings::week_start` and
+    /// `Settings::fiscal_year_start_month`). Groups and the events within them are newest-first.
+    /// `buy_total` is left `None`: see `get_events_grouped_with_totals`.
+    #[must_use]
+    pub fn get_events_grouped(
+        &self,
+        granularity: GroupGranularity,
+        filter: Option<EventFilter>,
+    ) -> Vec<EventGroup<'_>> {
+        let filter = filter.unwrap_or_default();
+        let mut groups: Vec<EventGroup> = Vec::new();
+        for event in self.portfolio.events.iter().rev().filter(|e| filter.matches(e)) {
+            let period_label = granularity.period_label(event.date, &self.portfolio.settings);
+            match groups.last_mut() {
+                Some(group) if group.period_label == period_label => {
+                    group.events.push(event);
+                    group.count += 1;
+                }
+                _ => groups.push(EventGroup {
+                    period_label,
+                    events: vec![event],
+                    buy_total: None,
+                    count: 1,
+                }),
+            }
+        }
+        groups
+    }
+
+    /// Same as `get_events_grouped`, with each group's `buy_total` in
+    /// `currency` (e.g., "EUR"), valued at the event dates. Prices are
+    /// fetched if needed; a group with a buy that cannot be priced keeps
+    /// `buy_total: None`.
+    pub async fn get_events_grouped_with_totals(
+        &mut self,
+        granularity: GroupGranularity,
+        filter: Option<EventFilter>,
+        currency: &str,
+    ) -> Result<Vec<EventGroup<'_>>, CoreError> {
+        let currency = Self::normalize_currency_code(currency)?;
+        let filter = filter.unwrap_or_default();
+        let buys: Vec<Event> = self
+            .portfolio
             .events
             .iter()
-            .filter(|e| &e.asset.asset_type == asset_type)
-            .collect()
+            .filter(|e| e.event_type == EventType::Buy && filter.matches(e))
+            .cloned()
+            .collect();
+
+        let price_cache = self.portfolio.price_cache_mut();
+        let mut values = HashMap::new();
+        for event in &buys {
+            let value = self
+                .analytics_service
+                .event_value(&self.price_service, price_cache, event, &currency)
+                .await;
+            if let Ok(value) = value {
+                values.insert(event.id, value);
+            }
+        }
+
+        let mut groups = self.get_events_grouped(granularity, Some(filter));
+        for group in &mut groups {
+            group.buy_total = group
+                .events
+                .iter()
+                .filter(|e| e.event_type == EventType::Buy)
+                .map(|e| values.get(&e.id).copied())
+                .sum();
+        }
+        Ok(groups)
     }
 
-    /// Get the total number of events without materializing a sorted vector.
+    /// Get the number of actual events without materializing a sorted vector.
     #[must_use]
     pub fn event_count(&self) -> usize {
-        self.portfolio.events.len()
+        self.portfolio.actual_events().count()
     }
 
     // ── Convenience Helpers ─────────────────────────────────────────
@@ -604,13 +2895,13 @@ impl SavingsTracker {
     /// Get the date of the earliest event in the portfolio.
     #[must_use]
     pub fn earliest_event_date(&self) -> Option<NaiveDate> {
-        self.portfolio.events.first().map(|e| e.date)
+        self.portfolio.actual_events().next().map(|e| e.date)
     }
 
     /// Get the date of the most recent event in the portfolio.
     #[must_use]
     pub fn latest_event_date(&self) -> Option<NaiveDate> {
-        self.portfolio.events.last().map(|e| e.date)
+        self.portfolio.actual_events().next_back().map(|e| e.date)
     }
 
     /// Get the number of days since the first event (portfolio age).
@@ -636,11 +2927,50 @@ impl SavingsTracker {
         }
 
         // Phase 2: All valid — apply to real portfolio
-        self.portfolio = temp_portfolio;
-        self.dirty = true;
+        *self.portfolio.edit() = temp_portfolio;
+        self.mark_dirty();
         Ok(ids)
     }
 
+    /// Replace an event with one or more others in one step, e.g. to change
+    /// its asset type or split a buy into lots. The removal and all
+    /// additions are validated together; if anything fails, nothing changes
+    /// (all-or-nothing). The replaced event goes to the trash like with
+    /// `remove_event_to_trash` (restoring it does not remove the
+    /// replacements). Returns the IDs of the replacements, in order.
+    pub fn replace_event(
+        &mut self,
+        event_id: uuid::Uuid,
+        replacements: Vec<Event>,
+    ) -> Result<Vec<uuid::Uuid>, CoreError> {
+        let ids = self.portfolio_service.replace_with_events(
+            &mut self.portfolio.edit(),
+            event_id,
+            replacements,
+            chrono::Utc::now(),
+        )?;
+        self.evict_trash();
+        self.mark_dirty();
+        Ok(ids)
+    }
+
+    /// Split an event into parts with the given amounts (e.g., a 1.0 BTC buy
+    /// into 0.4 and 0.6 lots), which must add up to its amount. The parts
+    /// keep its date, notes and recorded unit price; they replace it like
+    /// `replace_event`. Returns the IDs of the parts, in order.
+    pub fn split_event(
+        &mut self,
+        event_id: uuid::Uuid,
+        parts: &[f64],
+    ) -> Result<Vec<uuid::Uuid>, CoreError> {
+        let event = self
+            .get_event(event_id)
+            .filter(|e| !e.is_deleted())
+            .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))?;
+        let parts = event.split(parts)?;
+        self.replace_event(event_id, parts)
+    }
+
     /// Remove multiple events at once. All removals are validated first;
     /// if any removal fails, none are removed (all-or-nothing).
     pub fn remove_events(&mut self, event_ids: &[uuid::Uuid]) -> Result<(), CoreError> {
@@ -650,134 +2980,603 @@ impl SavingsTracker {
             self.portfolio_service.remove_event(&mut temp_portfolio, *id)?;
         }
 
-        self.portfolio = temp_portfolio;
-        self.dirty = true;
+        *self.portfolio.edit() = temp_portfolio;
+        self.mark_dirty();
         Ok(())
     }
 
+    /// Apply `changes` to every event matching `filter` (e.g., rename "GOLD"
+    /// to "XAU" across history). The changed history is fully revalidated;
+    /// if anything fails, no event is changed (all-or-nothing).
+    /// Returns the number of events changed.
+    pub fn bulk_update_events(
+        &mut self,
+        filter: EventFilter,
+        changes: EventChanges,
+    ) -> Result<usize, CoreError> {
+        let mut temp_portfolio = self.portfolio.clone();
+        let count = self
+            .portfolio_service
+            .bulk_update_events(&mut temp_portfolio, &filter, &changes)?;
+
+        if count > 0 {
+            *self.portfolio.edit() = temp_portfolio;
+            self.mark_dirty();
+        }
+        Ok(count)
+    }
+
+    /// Turn the buys and sells of fiat assets into deposits and withdrawals
+    /// (e.g., cash recorded as buys of "USD"), so they stop counting as
+    /// invested and returned. Planned and deleted events are converted too;
+    /// holdings don't change. Returns the number of events converted.
+    pub fn convert_fiat_trades_to_cash_flows(&mut self) -> usize {
+        let mut count = 0;
+        for event in &mut self.portfolio.edit().events {
+            if event.asset.asset_type != AssetType::Fiat {
+                continue;
+            }
+            event.event_type = match event.event_type {
+                EventType::Buy => EventType::Deposit,
+                EventType::Sell => EventType::Withdrawal,
+                EventType::Deposit | EventType::Withdrawal | EventType::Interest => continue,
+            };
+            count += 1;
+        }
+        if count > 0 {
+            self.portfolio.edit().mark_events_changed();
+            self.mark_dirty();
+        }
+        count
+    }
+
     // ── Undo (Trash) ────────────────────────────────────────────────
 
-    /// Remove an event and keep it in the trash for potential undo.
-    /// Returns the removed event.
+    /// Delete an event, keeping it in the history (and the trash) for
+    /// potential undo: it gets `deleted_at` set and is left out of holdings,
+    /// analytics, charts and event listings. Returns the event as it was.
+    /// The oldest deleted events are purged as the trash policy requires.
     pub fn remove_event_to_trash(&mut self, event_id: uuid::Uuid) -> Result<Event, CoreError> {
-        let event = self.portfolio.events.iter().find(|e| e.id == event_id)
-            .cloned()
-            .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))?;
-
-        self.portfolio_service.remove_event(&mut self.portfolio, event_id)?;
-        self.portfolio.trash.push(event.clone());
-        self.dirty = true;
+        let event = self
+            .portfolio_service
+            .delete_event(&mut self.portfolio.edit(), event_id, chrono::Utc::now())?;
+        self.evict_trash();
+        self.mark_dirty();
         Ok(event)
     }
 
-    /// Restore the most recently trashed event back into the portfolio.
+    /// Restore the most recently deleted event back into the portfolio.
     /// Returns the restored event, or `None` if trash is empty.
     pub fn undo_last_removal(&mut self) -> Result<Option<Event>, CoreError> {
-        let event = match self.portfolio.trash.pop() {
-            Some(e) => e,
-            None => return Ok(None),
-        };
+        match self.get_trash().first() {
+            Some(e) => self.restore_from_trash(e.id).map(Some),
+            None => Ok(None),
+        }
+    }
 
-        self.portfolio_service.add_event(&mut self.portfolio, event.clone())?;
-        self.dirty = true;
-        Ok(Some(event))
+    /// Restore a specific deleted event back into the portfolio.
+    ///
+    /// The event is validated again like a newly added one; if it no longer
+    /// fits the history (e.g., a later sell now needs the units it sold),
+    /// the error is returned and the event stays deleted.
+    pub fn restore_from_trash(&mut self, event_id: uuid::Uuid) -> Result<Event, CoreError> {
+        let event = self.portfolio_service.restore_event(&mut self.portfolio.edit(), event_id)?;
+        self.mark_dirty();
+        Ok(event)
+    }
+
+    /// Permanently remove a specific deleted event. Returns the removed event.
+    pub fn delete_from_trash(&mut self, event_id: uuid::Uuid) -> Result<Event, CoreError> {
+        let event = self
+            .get_event(event_id)
+            .filter(|e| e.is_deleted())
+            .cloned()
+            .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))?;
+        self.portfolio_service.remove_event(&mut self.portfolio.edit(), event_id)?;
+        self.mark_dirty();
+        Ok(event)
     }
 
-    /// Get events currently in the trash.
+    /// Get the deleted events, most recently deleted first.
     #[must_use]
-    pub fn get_trash(&self) -> &[Event] {
-        &self.portfolio.trash
+    pub fn get_trash(&self) -> Vec<&Event> {
+        let mut trash: Vec<&Event> = self.portfolio.deleted_events().collect();
+        trash.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+        trash
     }
 
-    /// Clear all trashed events permanently.
+    /// Permanently remove all deleted events.
     pub fn clear_trash(&mut self) {
-        if !self.portfolio.trash.is_empty() {
-            self.portfolio.trash.clear();
-            self.dirty = true;
+        let count = self.portfolio.events.len();
+        self.portfolio.edit().events.retain(|e| !e.is_deleted());
+        if self.portfolio.events.len() != count {
+            self.drop_orphaned_reminders();
+            self.portfolio.edit().mark_events_changed();
+            self.mark_dirty();
+        }
+    }
+
+    /// Set how many deleted events are kept and for how long. Events beyond
+    /// the new limits are removed right away. `max_entries`, if set, must
+    /// be at least 1.
+    pub fn set_trash_policy(&mut self, policy: TrashPolicy) -> Result<(), CoreError> {
+        if policy.max_entries == Some(0) {
+            return Err(CoreError::ValidationError(
+                "Trash size limit must be at least 1 (clear the trash instead)".to_string(),
+            ));
+        }
+        if self.portfolio.settings.trash_policy == policy {
+            return Ok(());
+        }
+        self.portfolio.edit().settings.trash_policy = policy;
+        self.evict_trash();
+        self.mark_dirty();
+        Ok(())
+    }
+
+    // ── Integrity ───────────────────────────────────────────────────
+
+    /// Check the portfolio for problems event validation normally prevents
+    /// (e.g., after importing a hand-edited file): duplicate event ids,
+    /// deleted events reusing ids in use, NaN/infinite or non-positive
+    /// amounts, holdings going negative (unless oversells are allowed) and
+    /// non-finite cached prices.
+    #[must_use]
+    pub fn check_integrity(&self) -> IntegrityReport {
+        self.portfolio_service.check_integrity(&self.portfolio, true)
+    }
+
+    /// Problems found in the events when the file was loaded (the price
+    /// cache is not scanned). Empty for new portfolios and after
+    /// `repair_integrity`.
+    #[must_use]
+    pub fn load_integrity_report(&self) -> &IntegrityReport {
+        &self.load_report
+    }
+
+    /// Fix what `check_integrity` finds: duplicate ids are replaced, events
+    /// with invalid amounts are moved to the trash, overdrawing sells and
+    /// withdrawals are moved to the trash or clamped to the amount held (see
+    /// `RepairOptions`), and non-finite cached prices are removed.
+    /// Returns the issues that were fixed.
+    pub fn repair_integrity(&mut self, options: RepairOptions) -> IntegrityReport {
+        let report = self.portfolio_service.repair_integrity(
+            &mut self.portfolio.edit(),
+            options,
+            chrono::Utc::now(),
+        );
+        self.load_report = IntegrityReport::default();
+        if !report.is_clean() {
+            self.evict_trash();
+            self.mark_dirty();
+        }
+        report
+    }
+
+    // ── Sync ────────────────────────────────────────────────────────
+
+    /// Merge the changes another copy of this portfolio made (e.g., the file
+    /// a sync service updated from another device) since `base_bytes`, the
+    /// version both copies started from.
+    ///
+    /// Event additions, removals and edits are matched by id; changes made
+    /// on one side only are applied. Events changed differently on both
+    /// sides keep the local version and are returned as conflicts to settle
+    /// with `resolve_conflict`. Settings are merged field by field; where both
+    /// sides changed a field, the local value wins (it is the last writer) and
+    /// the field is reported. Corporate actions, accrual rules and reminders
+    /// are merged by id the same way; merged rules of one asset must not
+    /// overlap. Price caches are merged. The merged history is revalidated; if
+    /// it is inconsistent nothing changes.
+    pub fn three_way_merge(
+        &mut self,
+        base_bytes: &[u8],
+        theirs_bytes: &[u8],
+        password: &str,
+    ) -> Result<SyncReport, CoreError> {
+        let base = StorageManager::load_from_bytes(base_bytes, password)?;
+        let theirs = StorageManager::load_from_bytes(theirs_bytes, password)?;
+        let merge = ThreeWayMerge::compute(&base, &self.portfolio, &theirs);
+
+        let mut temp_portfolio = self.portfolio.clone();
+        temp_portfolio.settings = merge.settings;
+        temp_portfolio.corporate_actions = merge.corporate_actions;
+        temp_portfolio.accrual_rules.clear();
+        for rule in merge.accrual_rules {
+            self.portfolio_service.add_accrual_rule(&mut temp_portfolio, rule)?;
         }
+        self.portfolio_service
+            .replace_events(&mut temp_portfolio, merge.events)?;
+        temp_portfolio.reminders = merge.reminders;
+        temp_portfolio.price_cache.merge(theirs.price_cache);
+
+        *self.portfolio.edit() = temp_portfolio;
+        self.drop_orphaned_reminders();
+        self.sync_conflicts = merge.report.conflicts.clone();
+        self.rebuild_price_service();
+        self.rebuild_currency_services();
+        self.mark_dirty();
+        Ok(merge.report)
+    }
+
+    /// Event conflicts of the last `three_way_merge` that are not resolved yet.
+    pub fn get_sync_conflicts(&self) -> &[SyncConflict] {
+        &self.sync_conflicts
+    }
+
+    /// Settle a conflict of the last `three_way_merge`: keep the local
+    /// version, take theirs (which may remove the event), or use a custom
+    /// event with the same id. The chosen version is validated.
+    pub fn resolve_conflict(
+        &mut self,
+        event_id: uuid::Uuid,
+        resolution: Resolution,
+    ) -> Result<(), CoreError> {
+        let idx = self
+            .sync_conflicts
+            .iter()
+            .position(|c| c.event_id == event_id)
+            .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))?;
+        let conflict = &self.sync_conflicts[idx];
+        let version = match resolution {
+            Resolution::Mine => conflict.mine.clone(),
+            Resolution::Theirs => conflict.theirs.clone(),
+            Resolution::Custom(event) => Some(event),
+        };
+
+        self.portfolio_service
+            .set_event_version(&mut self.portfolio.edit(), event_id, version)?;
+        self.sync_conflicts.remove(idx);
+        self.mark_dirty();
+        Ok(())
     }
 
     // ── Export / Import ─────────────────────────────────────────────
 
-    /// Export all events as a JSON string.
+    /// Export all events (except deleted ones) as a JSON string: an object
+    /// with the `schema_version` (see `JSON_SCHEMA_VERSION`), the events and
+    /// their reminders.
     pub fn export_events_to_json(&self) -> Result<String, CoreError> {
-        serde_json::to_string_pretty(&self.portfolio.events)
-            .map_err(|e| CoreError::Serialization(format!("Failed to serialize events to JSON: {e}")))
+        let active: HashSet<uuid::Uuid> = self.portfolio.active_events().map(|e| e.id).collect();
+        let reminders = self.portfolio.reminders.iter().filter(|r| active.contains(&r.event_id));
+        storage::json::events_to_json(self.portfolio.active_events(), reminders)
+    }
+
+    /// Export the metadata of all assets as a JSON object keyed by symbol,
+    /// then by asset type (both sorted).
+    pub fn export_asset_metadata_to_json(&self) -> Result<String, CoreError> {
+        let mut metadata: BTreeMap<&str, BTreeMap<&AssetType, &AssetMetadata>> = BTreeMap::new();
+        for ((symbol, asset_type), entry) in &self.portfolio.asset_metadata {
+            metadata.entry(symbol).or_default().insert(asset_type, entry);
+        }
+        serde_json::to_string_pretty(&metadata).map_err(|e| {
+            CoreError::Serialization(format!("Failed to serialize asset metadata to JSON: {e}"))
+        })
     }
 
-    /// Export all events as a CSV string.
-    /// Columns: id, event_type, symbol, name, asset_type, amount, date, notes
+    /// Export all events (except deleted ones) as a CSV string with the
+    /// default `CsvOptions`: comma-separated, formula-like text defused, and
+    /// amounts rounded to each asset's display precision (`get_display_precision`).
+    /// Columns: id, event_type, symbol, name, asset_type, amount, date, notes, planned
     #[must_use]
     pub fn export_events_to_csv(&self) -> String {
-        let mut csv = String::from("id,event_type,symbol,name,asset_type,amount,date,notes\n");
-        for event in &self.portfolio.events {
-            let notes = event.notes.as_deref().unwrap_or("");
-            // Escape CSV: quote fields containing commas, quotes, or newlines
-            let escaped_notes = if notes.contains(',') || notes.contains('"') || notes.contains('\n') {
-                format!("\"{}\"", notes.replace('"', "\"\""))
-            } else {
-                notes.to_string()
-            };
-            let escaped_name = if event.asset.name.contains(',') || event.asset.name.contains('"') {
-                format!("\"{}\"", event.asset.name.replace('"', "\"\""))
-            } else {
-                event.asset.name.clone()
-            };
-            csv.push_str(&format!(
-                "{},{},{},{},{},{},{},{}\n",
-                event.id,
-                event.event_type,
-                event.asset.symbol,
-                escaped_name,
-                event.asset.asset_type,
-                event.amount,
-                event.date,
-                escaped_notes,
-            ));
+        self.export_events_to_csv_with(&CsvOptions::default())
+    }
+
+    /// Export all events (except deleted ones) as a CSV string with the
+    /// given delimiter, formula defusing and columns.
+    #[must_use]
+    pub fn export_events_to_csv_with(&self, options: &CsvOptions) -> String {
+        storage::csv::events_to_csv(
+            self.portfolio.active_events(),
+            |asset| self.get_display_precision(asset),
+            options,
+        )
+    }
+
+    /// Export all cached prices as a CSV string, sorted by series and date.
+    /// Columns: symbol, currency, asset_type, date, price, source, fetched_at
+    /// (source and fetched_at are empty where unknown).
+    #[must_use]
+    pub fn export_price_cache_csv(&self) -> String {
+        let cache = &self.portfolio.price_cache;
+        let mut series: Vec<_> = cache.entries.iter().collect();
+        series.sort_by(|(a, _), (b, _)| {
+            (&a.0, &a.1, a.2.to_string()).cmp(&(&b.0, &b.1, b.2.to_string()))
+        });
+        let mut csv = String::from("symbol,currency,asset_type,date,price,source,fetched_at\n");
+        for ((symbol, currency, asset_type), points) in series {
+            for point in points {
+                let source = cache.source(symbol, currency, point.date, asset_type);
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    symbol,
+                    currency,
+                    asset_type,
+                    point.date,
+                    point.price,
+                    source.map_or("", |s| s.source.as_str()),
+                    source.map(|s| s.fetched_at.to_rfc3339()).unwrap_or_default(),
+                ));
+            }
         }
         csv
     }
 
-    /// Import events from a JSON string. Validates each event.
-    /// Returns the number of events imported.
+    /// Import events from a JSON string: an export of `export_events_to_json`
+    /// or a bare array of events (exports before `schema_version`). Ids
+    /// already in use are replaced (see `ImportIdPolicy::KeepIfUnique`).
+    /// Validates each event and imports all or none. Returns the number of
+    /// events imported.
     pub fn import_events_from_json(&mut self, json: &str) -> Result<usize, CoreError> {
-        let events: Vec<Event> = serde_json::from_str(json)?;
-        let count = events.len();
+        let report = self.import_events_from_json_with(json, ImportIdPolicy::default())?;
+        Ok(report.imported)
+    }
+
+    /// Import events from a JSON string like `import_events_from_json`,
+    /// choosing what happens to their ids. Reminders of the imported events
+    /// come along. Returns how many events were imported and which ids were
+    /// changed.
+    pub fn import_events_from_json_with(
+        &mut self,
+        json: &str,
+        policy: ImportIdPolicy,
+    ) -> Result<ImportReport, CoreError> {
+        let export = storage::json::events_from_json(json)?;
+        let incoming: HashSet<uuid::Uuid> = export.events.iter().map(|e| e.id).collect();
+        let report = self.import_events(export.events, policy)?;
+        let remapped: HashMap<uuid::Uuid, uuid::Uuid> = report.remapped_ids.iter().copied().collect();
+        for mut reminder in export.reminders {
+            if !incoming.contains(&reminder.event_id) {
+                continue;
+            }
+            reminder.event_id = remapped.get(&reminder.event_id).copied().unwrap_or(reminder.event_id);
+            self.portfolio.edit().reminders.retain(|r| r.event_id != reminder.event_id);
+            self.portfolio.edit().reminders.push(reminder);
+        }
+        Ok(report)
+    }
+
+    /// Assign ids to incoming `events` by `policy`, then add them all or none.
+    fn import_events(
+        &mut self,
+        mut events: Vec<Event>,
+        policy: ImportIdPolicy,
+    ) -> Result<ImportReport, CoreError> {
+        let mut taken: HashSet<uuid::Uuid> =
+            self.portfolio.events.iter().chain(&self.portfolio.trash).map(|e| e.id).collect();
+        let mut remapped_ids = Vec::new();
+        for event in &mut events {
+            let keep = match policy {
+                ImportIdPolicy::KeepIfUnique => !taken.contains(&event.id),
+                ImportIdPolicy::AlwaysRegenerate => false,
+                ImportIdPolicy::FailOnCollision if taken.contains(&event.id) => {
+                    return Err(CoreError::ValidationError(format!(
+                        "Event id {} is already in use",
+                        event.id
+                    )));
+                }
+                ImportIdPolicy::FailOnCollision => true,
+            };
+            if !keep {
+                let new_id = uuid::Uuid::new_v4();
+                remapped_ids.push((event.id, new_id));
+                event.id = new_id;
+            }
+            taken.insert(event.id);
+        }
+        let imported = events.len();
         self.add_events(events)?;
-        Ok(count)
+        Ok(ImportReport { imported, remapped_ids })
+    }
+
+    /// Import events from CSV, e.g. an export of `export_events_to_csv_with`
+    /// read with the same delimiter and formula defusing. Columns are found
+    /// by the header row. Ids already in use are replaced (see
+    /// `ImportIdPolicy::KeepIfUnique`). Validates each event and imports all
+    /// or none. Returns the number of events imported.
+    pub fn import_events_from_csv(
+        &mut self,
+        csv: &str,
+        options: &CsvOptions,
+    ) -> Result<usize, CoreError> {
+        let events = storage::csv::events_from_csv(csv, options)?;
+        let report = self.import_events(events, ImportIdPolicy::default())?;
+        Ok(report.imported)
+    }
+
+    /// Merge another encrypted portfolio (e.g., a partner's file) into this one.
+    ///
+    /// Incoming events are revalidated against the combined timeline and
+    /// added all-or-nothing (deleted ones are skipped); an incoming event
+    /// whose id is already taken gets a new id. Metal amounts are converted
+    /// to this portfolio's metal unit.
+    /// Settings stay this portfolio's (currency, API keys, policy); only API
+    /// keys, symbol overrides and asset metadata it lacks are taken over.
+    /// Corporate actions it lacks (by id, or the same action on the same
+    /// day) and accrual rules it lacks (by id, or the same rule) are added
+    /// before the events. Added events bring their reminders along. Price
+    /// caches are merged (see `PriceCache::merge`). Snapshots from the
+    /// earliest merged event on no longer describe the portfolio and are dropped.
+    /// Returns the number of events added.
+    pub fn merge_from_bytes(
+        &mut self,
+        encrypted: &[u8],
+        password: &str,
+        options: MergeOptions,
+    ) -> Result<usize, CoreError> {
+        let other = StorageManager::load_from_bytes(encrypted, password)?;
+        let mut temp_portfolio = self.portfolio.clone();
+
+        // Known assets and prices first, so the incoming events validate against them
+        let settings = &mut temp_portfolio.settings;
+        for (provider, key) in other.settings.api_keys {
+            settings.api_keys.entry(provider).or_insert(key);
+        }
+        for (provider, ids) in other.settings.symbol_overrides {
+            let known = settings.symbol_overrides.entry(provider).or_default();
+            for (symbol, id) in ids {
+                known.entry(symbol).or_insert(id);
+            }
+        }
+        for (symbol, metadata) in other.asset_metadata {
+            temp_portfolio.asset_metadata.entry(symbol).or_insert(metadata);
+        }
+        for asset in other.watchlist {
+            if !temp_portfolio.watchlist.contains(&asset) {
+                temp_portfolio.watchlist.push(asset);
+            }
+        }
+        for action in other.corporate_actions {
+            let known = temp_portfolio.corporate_actions.iter().any(|a| {
+                a.id == action.id
+                    || (a.symbol == action.symbol
+                        && a.action == action.action
+                        && a.effective_date == action.effective_date)
+            });
+            if !known {
+                self.portfolio_service
+                    .add_corporate_action(&mut temp_portfolio, action)?;
+            }
+        }
+        for rule in other.accrual_rules {
+            let known = temp_portfolio
+                .accrual_rules
+                .iter()
+                .any(|r| r.id == rule.id || *r == AccrualRule { id: r.id, ..rule.clone() });
+            if !known {
+                self.portfolio_service.add_accrual_rule(&mut temp_portfolio, rule)?;
+            }
+        }
+        temp_portfolio.price_cache.merge(other.price_cache);
+
+        let metal_factor = temp_portfolio
+            .settings
+            .metal_unit
+            .price_factor(other.settings.metal_unit);
+        let mut existing: HashMap<_, usize> = HashMap::new();
+        if options.dedupe {
+            for event in self.portfolio.active_events() {
+                *existing.entry(Self::merge_key(event)).or_default() += 1;
+            }
+        }
+        let mut taken_ids: HashSet<uuid::Uuid> =
+            self.portfolio.events.iter().map(|e| e.id).collect();
+        let prefix = options.prefix_notes.as_deref().map(str::trim).filter(|p| !p.is_empty());
+        let mut reminders: HashMap<uuid::Uuid, Reminder> =
+            other.reminders.into_iter().map(|r| (r.event_id, r)).collect();
+
+        let mut added = 0;
+        let mut earliest: Option<NaiveDate> = None;
+        for mut event in other.events.into_iter().filter(|e| !e.is_deleted()) {
+            if event.asset.asset_type == AssetType::Metal && metal_factor != 1.0 {
+                event.amount *= metal_factor;
+                // Unit prices were recorded per the other portfolio's unit
+                event.recorded_value = None;
+            }
+            if let Some(count) = existing.get_mut(&Self::merge_key(&event)) {
+                if *count > 0 {
+                    *count -= 1;
+                    continue;
+                }
+            }
+            let reminder = reminders.remove(&event.id);
+            if !taken_ids.insert(event.id) {
+                event.id = uuid::Uuid::new_v4();
+                taken_ids.insert(event.id);
+            }
+            if let Some(prefix) = prefix {
+                event.notes = Some(match event.notes.take() {
+                    Some(notes) => format!("{prefix} {notes}"),
+                    None => prefix.to_string(),
+                });
+            }
+            earliest = Some(earliest.map_or(event.date, |d| d.min(event.date)));
+            let event_id = event.id;
+            self.portfolio_service.add_event(&mut temp_portfolio, event)?;
+            if let Some(reminder) = reminder {
+                temp_portfolio.reminders.push(Reminder { event_id, ..reminder });
+            }
+            added += 1;
+        }
+
+        if let Some(earliest) = earliest {
+            temp_portfolio.snapshots.retain(|s| s.date < earliest);
+        }
+        *self.portfolio.edit() = temp_portfolio;
+        self.rebuild_price_service();
+        self.mark_dirty();
+        Ok(added)
     }
 
     /// Export the full portfolio summary as JSON (unencrypted snapshot for debugging/display).
+    /// Maps are written in sorted order, so the same portfolio always gives
+    /// the same string; the object includes a `schema_version`.
     pub fn to_json(&self) -> Result<String, CoreError> {
-        serde_json::to_string_pretty(&self.portfolio)
-            .map_err(|e| CoreError::Serialization(format!("Failed to serialize portfolio: {e}")))
+        storage::json::portfolio_to_json(&self.portfolio)
     }
 
     // ── Cache Inspection ────────────────────────────────────────────
 
-    /// Get a specific cached price.
+    /// Get a specific cached price of an asset of type `asset_type`.
+    #[must_use]
+    pub fn get_cached_price(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Option<f64> {
+        self.portfolio.price_cache.get_price(symbol, currency, date, asset_type)
+    }
+
+    /// Get a specific cached price with its source, fetch time and flags.
     #[must_use]
-    pub fn get_cached_price(&self, symbol: &str, currency: &str, date: NaiveDate) -> Option<f64> {
-        self.portfolio.price_cache.get_price(symbol, currency, date)
+    pub fn get_cached_price_detailed(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Option<CachedPrice> {
+        self.portfolio.price_cache.get_entry(symbol, currency, date, asset_type)
     }
 
-    /// Get all cached (symbol, currency) pairs.
+    /// Get all cached (symbol, currency, asset type) series.
     #[must_use]
-    pub fn get_cached_pairs(&self) -> Vec<(String, String)> {
+    pub fn get_cached_pairs(&self) -> Vec<(String, String, AssetType)> {
         self.portfolio.price_cache.entries.keys().cloned().collect()
     }
 
-    /// Get the date when a (symbol, currency) pair was last refreshed.
+    /// Get the date when a (symbol, currency) series of an asset type was last refreshed.
     #[must_use]
-    pub fn get_last_refreshed(&self, symbol: &str, currency: &str) -> Option<NaiveDate> {
-        let key = (symbol.to_uppercase(), currency.to_uppercase());
+    pub fn get_last_refreshed(
+        &self,
+        symbol: &str,
+        currency: &str,
+        asset_type: &AssetType,
+    ) -> Option<NaiveDate> {
+        let key = (symbol.to_uppercase(), currency.to_uppercase(), asset_type.clone());
         self.portfolio.price_cache.last_updated.get(&key).copied()
     }
 
     /// Manually insert a price into the cache (useful for testing, offline, or historical import).
-    pub fn set_cached_price(&mut self, symbol: &str, currency: &str, date: NaiveDate, price: f64) {
-        self.portfolio.price_cache.set_price(symbol, currency, date, price);
-        self.dirty = true;
+    /// Its source is recorded as "manual". NaN and infinite prices are ignored.
+    pub fn set_cached_price(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        price: f64,
+        asset_type: &AssetType,
+    ) {
+        if !price.is_finite() {
+            return;
+        }
+        let cache = self.portfolio.price_cache_mut();
+        cache.set_price(symbol, currency, date, price, asset_type);
+        cache.set_source(symbol, currency, date, PriceSource::now(MANUAL_SOURCE), asset_type);
+        self.mark_dirty();
     }
 
     // ── Provider Availability ───────────────────────────────────────
@@ -794,25 +3593,370 @@ impl SavingsTracker {
         self.price_service.get_provider_names(asset_type)
     }
 
+    /// Requests sent to each provider (every registered one, plus any called
+    /// earlier), sorted by name. Daily counts restart at UTC midnight;
+    /// answers from the price cache don't count. Saved with the portfolio.
+    #[must_use]
+    pub fn get_api_usage(&self) -> Vec<ProviderUsage> {
+        self.price_service.api_usage()
+    }
+
+    /// Provider quotes rejected by the price sanity checks this session
+    /// (at most the last 100), oldest first: prices that were not positive,
+    /// or too far from a cached price without another provider confirming
+    /// them. Rejected quotes are never cached; the lookup uses another
+    /// provider's quote or fails as if no provider had a price.
+    #[must_use]
+    pub fn get_recent_warnings(&self) -> Vec<PriceWarning> {
+        self.price_warnings.recent()
+    }
+
+    /// Forget the warnings returned by `get_recent_warnings`.
+    pub fn clear_recent_warnings(&mut self) {
+        self.price_warnings.clear();
+    }
+
+    /// The steps of the latest price lookups this session (at most the last
+    /// 200), oldest first: cache hits, and each provider asked with its
+    /// outcome, so a failed provider followed by another one's success
+    /// shows a fallback. Not saved.
+    #[must_use]
+    pub fn get_price_diagnostics(&self) -> Vec<PriceDiagnostic> {
+        self.price_diagnostics.recent()
+    }
+
+    /// Forget the events returned by `get_price_diagnostics`.
+    pub fn clear_price_diagnostics(&mut self) {
+        self.price_diagnostics.clear();
+    }
+
+    /// Register a custom price provider (e.g., a broker-specific source).
+    ///
+    /// Custom providers take priority over the built-in ones and survive
+    /// registry rebuilds triggered by `set_api_key` / `remove_api_key`.
+    pub fn register_provider(&mut self, provider: Box<dyn PriceProvider>) {
+        self.custom_providers.register(provider);
+        self.rebuild_price_service();
+        self.invalidate_memos();
+    }
+
+    /// Name and supported asset types of every registered provider, in priority order.
+    #[must_use]
+    pub fn list_registered_providers(&self) -> Vec<ProviderInfo> {
+        self.price_service.list_providers()
+    }
+
     // ── Internal ────────────────────────────────────────────────────
 
+    /// Validate a chart/prefetch date range: `from <= to` and within the
+    /// maximum span set by `set_max_chart_range_days`.
+    fn validate_chart_range(&self, from: NaiveDate, to: NaiveDate) -> Result<(), CoreError> {
+        if from > to {
+            return Err(CoreError::ValidationError(
+                format!("'from' date ({from}) must not be after 'to' date ({to})"),
+            ));
+        }
+        let range_days = (to - from).num_days();
+        if let Some(max) = self.portfolio.settings.max_chart_range_days {
+            if range_days > i64::from(max) {
+                return Err(CoreError::ValidationError(format!(
+                    "Chart range of {range_days} days exceeds maximum of {max} days \
+                     (the max_chart_range_days setting)"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Permanently remove deleted events beyond the trash policy, the
+    /// earliest deleted first.
+    fn evict_trash(&mut self) {
+        let policy = &self.portfolio.settings.trash_policy;
+        let cutoff = policy
+            .retention_days
+            .map(|days| chrono::Utc::now() - chrono::Duration::days(i64::from(days)));
+        let mut evicted: HashSet<uuid::Uuid> = HashSet::new();
+        for (rank, event) in self.get_trash().into_iter().enumerate() {
+            let expired = cutoff.is_some_and(|cutoff| event.deleted_at < Some(cutoff));
+            if expired || policy.max_entries.is_some_and(|max| rank >= max) {
+                evicted.insert(event.id);
+            }
+        }
+        if !evicted.is_empty() {
+            self.portfolio.edit().events.retain(|e| !evicted.contains(&e.id));
+            self.portfolio.edit().mark_events_changed();
+            self.drop_orphaned_reminders();
+        }
+    }
+
+    /// Remove the reminders of events that no longer exist.
+    fn drop_orphaned_reminders(&mut self) {
+        let ids: HashSet<uuid::Uuid> = self.portfolio.events.iter().map(|e| e.id).collect();
+        self.portfolio.edit().reminders.retain(|r| ids.contains(&r.event_id));
+    }
+
+    /// What makes two events duplicates when merging portfolios (the id aside).
+    fn merge_key(event: &Event) -> (EventType, Asset, NaiveDate, u64, bool) {
+        (
+            event.event_type.clone(),
+            event.asset.clone(),
+            event.date,
+            event.amount.to_bits(),
+            event.is_planned,
+        )
+    }
+
+    fn normalize_currency_code(currency: &str) -> Result<String, CoreError> {
+        let trimmed = currency.trim().to_uppercase();
+        if trimmed.len() != 3 || !trimmed.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(CoreError::ValidationError(
+                format!("Invalid currency code '{currency}': must be exactly 3 ASCII letters (e.g., USD, EUR, PLN)"),
+            ));
+        }
+        Ok(trimmed)
+    }
+
+    /// Fetch today's price of every held and watched asset not refreshed
+    /// today, or of all of them if `force` (see `refresh_prices_with_progress`).
+    async fn refresh_held_prices(
+        &mut self,
+        price_service: &PriceService,
+        force: bool,
+        mut on_progress: impl FnMut(Progress),
+    ) -> Result<RefreshReport, CoreError> {
+        let today = chrono::Utc::now().date_naive();
+        let mut assets: Vec<Asset> = self.get_holdings(today).into_keys().collect();
+        for asset in &self.portfolio.watchlist {
+            if !assets.contains(asset) {
+                assets.push(asset.clone());
+            }
+        }
+        let currency = self.portfolio.settings.default_currency.clone();
+        let total = assets.len();
+        let mut report = RefreshReport::default();
+
+        for (completed, asset) in assets.iter().enumerate() {
+            if Self::is_cancelled(price_service) {
+                return Err(CoreError::Cancelled);
+            }
+            on_progress(Progress {
+                completed,
+                total,
+                current_symbol: Some(asset.symbol.clone()),
+            });
+            let cache = self.portfolio.price_cache_mut();
+            if cache.is_today_fresh(&asset.symbol, &currency, today, &asset.asset_type) {
+                if !force {
+                    report.skipped_fresh.push(asset.symbol.clone());
+                    continue;
+                }
+                cache.mark_stale(&asset.symbol, &currency, &asset.asset_type);
+            }
+            let result = price_service
+                .get_price(cache, &asset.symbol, &currency, today, &asset.asset_type)
+                .await;
+            match result {
+                Ok(_) => report.refreshed.push(asset.symbol.clone()),
+                Err(CoreError::Cancelled) => return Err(CoreError::Cancelled),
+                Err(e) => report.failed.push((asset.symbol.clone(), e)),
+            }
+        }
+
+        on_progress(Progress {
+            completed: total,
+            total,
+            current_symbol: None,
+        });
+        Ok(report)
+    }
+
+    /// `provider` trimmed and lowercased, as the key of a per-provider
+    /// setting: the id of a built-in or registered provider, or (when
+    /// `removing`) a key already in the setting.
+    fn provider_setting_key<T>(
+        &self,
+        provider: &str,
+        removing: bool,
+        setting: impl Fn(&Settings) -> &HashMap<String, T>,
+    ) -> Result<String, CoreError> {
+        let provider = provider.trim().to_lowercase();
+        if provider.is_empty() {
+            return Err(CoreError::ValidationError("Provider must not be empty".into()));
+        }
+        let known = BUILTIN_PROVIDER_IDS.contains(&provider.as_str())
+            || self.custom_providers.has_provider(&provider)
+            || (removing && setting(&self.portfolio.settings).contains_key(&provider));
+        if !known {
+            return Err(CoreError::ValidationError(format!(
+                "Unknown provider '{provider}' (built-in: {})",
+                BUILTIN_PROVIDER_IDS.join(", "),
+            )));
+        }
+        Ok(provider)
+    }
+
+    /// The price service of one operation: its provider requests are
+    /// cancelled with `cancel`, if given, instead of the tracker-wide token.
+    fn scoped_price_service(&self, cancel: Option<&CancellationToken>) -> PriceService {
+        match cancel {
+            Some(cancel) => self.price_service.with_cancellation(cancel.clone()),
+            None => self.price_service.clone(),
+        }
+    }
+
+    /// Whether the token of the running operation has been cancelled.
+    fn is_cancelled(price_service: &PriceService) -> bool {
+        price_service.cancellation().is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Record a mutation: unsaved changes, and memoized results are stale.
+    fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.invalidate_memos();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.schedule_autosave();
+    }
+
+    /// Lend the current portfolio to the autosave worker, if autosave is on;
+    /// it is saved (not copied) once the debounce window passes.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn schedule_autosave(&mut self) {
+        if self.autosave.is_none() {
+            return;
+        }
+        self.sync_symbol_overrides();
+        self.sync_api_usage();
+        self.portfolio.publish(self.revision);
+        if let Some(autosave) = &self.autosave {
+            autosave.schedule();
+        }
+    }
+
+    /// Make memoized results stale without marking unsaved changes (e.g.,
+    /// after a price refresh).
+    fn invalidate_memos(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+        self.summary_memo.clear();
+    }
+
     fn build(portfolio: Portfolio) -> Self {
-        let api_keys = portfolio.settings.api_keys.clone();
-        let registry = PriceProviderRegistry::new_with_defaults(&api_keys);
-        let price_service = PriceService::new(registry);
-        let portfolio_service = PortfolioService::new();
-        let chart_service = ChartService::new();
-        let currency_service = CurrencyService::new();
-        let analytics_service = AnalyticsService::new();
-
-        Self {
-            portfolio,
-            portfolio_service,
-            price_service,
-            chart_service,
-            currency_service,
-            analytics_service,
+        Self::build_with_providers(portfolio, PriceProviderRegistry::new(), true)
+    }
+
+    /// `build` for a loaded file, with its events checked (prices are not
+    /// scanned, to keep loading fast).
+    fn build_loaded(portfolio: Portfolio) -> Self {
+        let mut tracker = Self::build(portfolio);
+        tracker.load_report = tracker.portfolio_service.check_integrity(&tracker.portfolio, false);
+        tracker
+    }
+
+    pub(crate) fn build_with_registry(portfolio: Portfolio, registry: PriceProviderRegistry) -> Self {
+        Self::build_with_providers(portfolio, registry, false)
+    }
+
+    fn build_with_providers(
+        portfolio: Portfolio,
+        custom_providers: PriceProviderRegistry,
+        use_default_providers: bool,
+    ) -> Self {
+        let api_usage = Arc::new(ApiUsageTracker::with_usage(portfolio.api_usage.clone()));
+        let mut tracker = Self {
+            portfolio: shared_portfolio::SharedPortfolio::new(portfolio),
+            portfolio_service: PortfolioService::new(),
+            price_service: PriceService::new(PriceProviderRegistry::new()),
+            chart_service: ChartService::new(),
+            currency_service: CurrencyService::new(),
+            analytics_service: AnalyticsService::new(),
+            alert_service: AlertService::new(),
+            inflation_service: InflationService::new(),
+            simulation_service: SimulationService::new(),
+            custom_providers,
+            use_default_providers,
+            response_cache: Arc::new(MemoryResponseCache::new()),
+            api_usage,
+            price_warnings: Arc::new(PriceWarningLog::new()),
+            price_diagnostics: Arc::new(PriceDiagnosticLog::new()),
+            sync_conflicts: Vec::new(),
+            load_report: IntegrityReport::default(),
             dirty: false,
+            revision: 0,
+            summary_memo: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            autosave: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            last_autosave: Arc::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            autosave_warnings: Arc::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            autosave_hook: Arc::default(),
+        };
+        tracker.rebuild_price_service();
+        tracker.rebuild_currency_services();
+        tracker
+    }
+
+    /// Rebuild the services that convert currencies so they use the
+    /// pivot currencies from the current settings.
+    fn rebuild_currency_services(&mut self) {
+        let currency_service = CurrencyService::with_pivot_currencies(
+            self.portfolio.settings.pivot_currencies.clone(),
+        );
+        self.chart_service = ChartService::with_currency_service(currency_service.clone());
+        self.analytics_service = AnalyticsService::with_currency_service(currency_service.clone());
+        self.alert_service = AlertService::with_currency_service(currency_service.clone());
+        self.simulation_service =
+            SimulationService::with_currency_service(currency_service.clone());
+        self.currency_service = currency_service;
+    }
+
+    /// Rebuild the provider registry: custom providers first (highest priority),
+    /// then the defaults configured from the current settings (API keys, symbol overrides).
+    fn rebuild_price_service(&mut self) {
+        self.sync_symbol_overrides();
+        let mut registry = self.custom_providers.clone();
+        if self.use_default_providers {
+            registry.extend(&PriceProviderRegistry::new_with_settings(&self.portfolio.settings));
+        }
+        let fallback = self.price_service.fallback();
+        let cancellation = self.price_service.cancellation().cloned();
+        self.price_service =
+            PriceService::with_metal_unit(registry, self.portfolio.settings.metal_unit);
+        self.price_service.set_fallback(fallback);
+        self.price_service.set_cancellation(cancellation);
+        self.price_service.set_usage_tracker(Arc::clone(&self.api_usage));
+        self.price_service
+            .set_daily_limits(self.portfolio.settings.provider_daily_limits.clone());
+        self.price_service
+            .set_max_price_change(self.portfolio.settings.max_price_change_factor);
+        self.price_service
+            .set_monthly_price_age(self.portfolio.settings.monthly_price_age_days);
+        self.price_service.set_warning_log(Arc::clone(&self.price_warnings));
+        let diagnostics = Arc::clone(&self.price_diagnostics);
+        self.price_service.set_event_sink(move |event| diagnostics.push(event));
+        // Responses of the old providers may no longer apply (e.g., a new API key)
+        self.response_cache.clear();
+        self.price_service
+            .set_response_cache(Some(Arc::clone(&self.response_cache)));
+    }
+
+    /// Copy symbol ids the providers resolved at runtime into the settings,
+    /// so they persist across sessions. Existing entries (manual overrides) win.
+    fn sync_symbol_overrides(&mut self) {
+        for (provider, ids) in self.price_service.learned_symbol_ids() {
+            let mut edit = self.portfolio.edit();
+            let known = edit.settings.symbol_overrides.entry(provider).or_default();
+            for (symbol, id) in ids {
+                if let Entry::Vacant(entry) = known.entry(symbol) {
+                    entry.insert(id);
+                    self.dirty = true;
+                }
+            }
         }
     }
+
+    /// Copy the provider request counts into the portfolio, so they are saved.
+    fn sync_api_usage(&mut self) {
+        self.portfolio.edit().api_usage = self.api_usage.snapshot();
+    }
 }
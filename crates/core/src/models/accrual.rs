@@ -0,0 +1,183 @@
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::asset::Asset;
+use crate::errors::CoreError;
+
+/// How often an accrual rule adds its interest to the balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Compounding {
+    /// Every day, at `annual_rate_pct / 365` percent
+    Daily,
+    /// On the last day of every month, at `annual_rate_pct / 12` percent
+    Monthly,
+}
+
+impl std::fmt::Display for Compounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compounding::Daily => write!(f, "daily"),
+            Compounding::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+/// Percentage growth of an asset's balance over a date window, e.g. staking
+/// rewards or interest on a savings account, counted without an event per
+/// payout.
+///
+/// Holdings grow multiplicatively inside the window: each period's interest
+/// is earned on the balance at the end of the previous day, events included.
+/// `SavingsTracker::materialize_accruals` turns the accrued amounts into
+/// `Interest` events; the periods it covered then stop accruing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccrualRule {
+    /// Unique identifier
+    pub id: Uuid,
+
+    /// The asset whose balance grows
+    pub asset: Asset,
+
+    /// Yearly rate in percent (e.g., 3.65 for 0.01% a day)
+    pub annual_rate_pct: f64,
+
+    pub compounding: Compounding,
+
+    /// First day that accrues
+    pub start_date: NaiveDate,
+
+    /// Last day that accrues; `None` for no end
+    pub end_date: Option<NaiveDate>,
+
+    /// Last day whose accrual was turned into `Interest` events, if any
+    #[serde(default)]
+    pub materialized_until: Option<NaiveDate>,
+}
+
+impl AccrualRule {
+    /// A rule accruing from `start_date` with no end.
+    pub fn new(
+        asset: Asset,
+        annual_rate_pct: f64,
+        compounding: Compounding,
+        start_date: NaiveDate,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            asset,
+            annual_rate_pct,
+            compounding,
+            start_date,
+            end_date: None,
+            materialized_until: None,
+        }
+    }
+
+    /// Stop accruing after `end_date`.
+    pub fn with_end_date(mut self, end_date: NaiveDate) -> Self {
+        self.end_date = Some(end_date);
+        self
+    }
+
+    /// Check the rule on its own: a positive finite rate and an end date
+    /// not before the start date.
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if !self.annual_rate_pct.is_finite() || self.annual_rate_pct <= 0.0 {
+            return Err(CoreError::ValidationError(format!(
+                "Invalid accrual rate {}%: must be a positive number",
+                self.annual_rate_pct
+            )));
+        }
+        if self.end_date.is_some_and(|end| end < self.start_date) {
+            return Err(CoreError::ValidationError(format!(
+                "Accrual of {} ends before it starts on {}",
+                self.asset.symbol, self.start_date
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether both rules accrue the same asset on some day.
+    pub fn overlaps(&self, other: &AccrualRule) -> bool {
+        self.asset == other.asset
+            && self.start_date <= other.end_date.unwrap_or(NaiveDate::MAX)
+            && other.start_date <= self.end_date.unwrap_or(NaiveDate::MAX)
+    }
+
+    /// First day still accruing: the start date, or the day after the last
+    /// materialized one.
+    pub fn accrues_from(&self) -> NaiveDate {
+        self.materialized_until
+            .and_then(|until| until.succ_opt())
+            .map_or(self.start_date, |next| next.max(self.start_date))
+    }
+
+    /// Factor the rule grows a balance held at the end of `from` by, until
+    /// the end of `to` (1.0 outside the window).
+    pub fn growth(&self, from: NaiveDate, to: NaiveDate) -> f64 {
+        let Some(after) = from.succ_opt() else {
+            return 1.0;
+        };
+        let first = self.accrues_from().max(after);
+        let last = self.end_date.map_or(to, |end| end.min(to));
+        if first > last {
+            return 1.0;
+        }
+        let periods = match self.compounding {
+            Compounding::Daily => (last - first).num_days() + 1,
+            Compounding::Monthly => month_ends_between(first, last),
+        };
+        (1.0 + self.period_rate()).powi(i32::try_from(periods).unwrap_or(i32::MAX))
+    }
+
+    /// Ends of the periods accrued from `accrues_from` to `up_to`: every
+    /// month end, and the end date if it falls mid-month.
+    pub fn period_ends(&self, up_to: NaiveDate) -> Vec<NaiveDate> {
+        let last = self.end_date.map_or(up_to, |end| end.min(up_to));
+        let mut ends = Vec::new();
+        let mut month_end = last_of_month(self.accrues_from());
+        while month_end <= last {
+            ends.push(month_end);
+            month_end = match month_end.succ_opt() {
+                Some(next) => last_of_month(next),
+                None => break,
+            };
+        }
+        if let Some(end) = self.end_date.filter(|&end| end <= up_to) {
+            if ends.last() != Some(&end) && end >= self.accrues_from() {
+                ends.push(end);
+            }
+        }
+        ends
+    }
+
+    fn period_rate(&self) -> f64 {
+        let periods_per_year = match self.compounding {
+            Compounding::Daily => 365.0,
+            Compounding::Monthly => 12.0,
+        };
+        self.annual_rate_pct / 100.0 / periods_per_year
+    }
+}
+
+/// Factor `rules` (all of one asset) grow a balance held at the end of
+/// `from` by, until the end of `to`.
+pub fn growth(rules: &[AccrualRule], from: NaiveDate, to: NaiveDate) -> f64 {
+    rules.iter().map(|rule| rule.growth(from, to)).product()
+}
+
+/// Last day of `date`'s month.
+fn last_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1)
+        .and_then(|start| start.checked_add_months(Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(NaiveDate::MAX)
+}
+
+/// Number of month ends from `first` to `last`, both included.
+fn month_ends_between(first: NaiveDate, last: NaiveDate) -> i64 {
+    let month = |date: NaiveDate| i64::from(date.year()) * 12 + i64::from(date.month0());
+    let last_included = i64::from(last == last_of_month(last));
+    month(last) - month(first) + last_included
+}
@@ -0,0 +1,74 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::asset::Asset;
+
+/// What an alert watches for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertCondition {
+    /// Current price is at or above the threshold
+    Above,
+    /// Current price is at or below the threshold
+    Below,
+    /// Price moved by at least `threshold` percent (either direction)
+    /// compared to the previous day's price
+    PctChangeDay,
+}
+
+impl std::fmt::Display for AlertCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertCondition::Above => write!(f, "Above"),
+            AlertCondition::Below => write!(f, "Below"),
+            AlertCondition::PctChangeDay => write!(f, "Daily % change"),
+        }
+    }
+}
+
+/// A price alert stored in the portfolio file, so it syncs with the portfolio.
+///
+/// Alerts are evaluated by `SavingsTracker::check_alerts()`; an alert fires
+/// at most once per day (tracked by `last_triggered`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Unique identifier
+    pub id: Uuid,
+
+    /// The asset whose price is watched
+    pub asset: Asset,
+
+    pub condition: AlertCondition,
+
+    /// Price in `currency` for `Above`/`Below`, percent for `PctChangeDay`
+    pub threshold: f64,
+
+    /// Currency the price is compared in (e.g., "PLN")
+    pub currency: String,
+
+    /// Disabled alerts are kept but never evaluated
+    pub enabled: bool,
+
+    /// Date the alert last fired, if ever
+    pub last_triggered: Option<NaiveDate>,
+}
+
+impl Alert {
+    /// Create an enabled alert that has never fired.
+    pub fn new(
+        asset: Asset,
+        condition: AlertCondition,
+        threshold: f64,
+        currency: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            asset,
+            condition,
+            threshold,
+            currency: currency.into(),
+            enabled: true,
+            last_triggered: None,
+        }
+    }
+}
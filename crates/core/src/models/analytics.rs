@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
-use super::asset::Asset;
+use super::asset::{Asset, AssetType};
+use super::event::{Event, EventType};
 
 /// Summary of the entire portfolio at a point in time.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,10 +12,10 @@ pub struct PortfolioSummary {
     /// Currency used for all monetary values
     pub currency: String,
 
-    /// Total number of events in the portfolio
+    /// Number of events in the portfolio up to `as_of_date`
     pub total_events: usize,
 
-    /// Date of the earliest event, if any
+    /// Date of the earliest event, if any is dated up to `as_of_date`
     pub inception_date: Option<chrono::NaiveDate>,
 
     /// Total portfolio value in the default display currency
@@ -26,14 +27,68 @@ pub struct PortfolioSummary {
     /// Total returned from sells (sum of all sell events' values in display currency)
     pub total_returned: f64,
 
-    /// Absolute gain/loss: total_value + total_returned - total_invested
+    /// Cash deposited minus cash withdrawn (deposit and withdrawal events'
+    /// values in display currency); not part of invested or returned
+    #[serde(default)]
+    pub net_cash_flow: f64,
+
+    /// Absolute gain/loss: total_value + total_returned - total_invested - net_cash_flow
     pub total_gain_loss: f64,
 
+    /// Part of `total_gain_loss` from price moves in each asset's native
+    /// quote currency, converted at the as-of date's FX rate
+    #[serde(default)]
+    pub asset_gain: f64,
+
+    /// Part of `total_gain_loss` from exchange-rate moves:
+    /// total_gain_loss - asset_gain - income
+    #[serde(default)]
+    pub fx_gain: f64,
+
+    /// Part of `total_gain_loss` received as interest or staking rewards
+    /// (see `HoldingSummary::income`)
+    #[serde(default)]
+    pub income: f64,
+
     /// Percentage return: (total_gain_loss / total_invested) * 100
     pub total_return_pct: f64,
 
+    /// Inflation-adjusted return: like `total_return_pct`, with every buy/sell
+    /// value restated in as-of-date money. `None` when no CPI data is available.
+    #[serde(default)]
+    pub real_return_pct: Option<f64>,
+
     /// Per-asset breakdown
     pub holdings: Vec<HoldingSummary>,
+
+    /// Totals per asset type, largest value first
+    #[serde(default)]
+    pub by_asset_type: Vec<AssetTypeAllocation>,
+
+    /// Projected annual income from holdings with a yield set;
+    /// `None` if no held asset has one
+    #[serde(default)]
+    pub income_projection: Option<IncomeProjection>,
+}
+
+/// How `SavingsTracker::get_portfolio_summary_with` computes the summary.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummaryOptions {
+    /// Currency of the summary (e.g., "EUR"); `None` for the default currency
+    pub currency: Option<String>,
+
+    /// Recompute the summary even if an identical one is memoized
+    pub force_refresh: bool,
+
+    /// Leave values unrounded (see `AnalyticsService`), e.g. to
+    /// check calculations in tests. Raw summaries are not memoized
+    pub raw: bool,
+
+    /// Also list assets held before but sold out by the date, with amount,
+    /// value and allocation 0 and their realized gain in `gain_loss`.
+    /// Such summaries are not memoized
+    pub include_closed_positions: bool,
 }
 
 /// Summary of a single held asset.
@@ -51,15 +106,459 @@ pub struct HoldingSummary {
     /// Total invested in this asset (sum of buy amounts × price at buy date)
     pub total_invested: f64,
 
-    /// Average cost per unit (total_invested / total_units_bought)
+    /// Average cost per unit: (total_invested + deposited value + income) /
+    /// (units bought, deposited and received as income)
     pub cost_basis_per_unit: f64,
 
+    /// Average unit cost of the units still held: the lots left after
+    /// sales were matched under `Settings::cost_basis_method`
+    #[serde(default)]
+    pub avg_buy_price: f64,
+
+    /// Unit price at which selling the holding recovers the net amount put
+    /// in: (total_invested + net cash flow - sell proceeds) / amount, at
+    /// least 0. Selling at it would make `gain_loss` 0
+    #[serde(default)]
+    pub break_even_price: f64,
+
+    /// Whether sell proceeds already cover everything put in, so
+    /// `break_even_price` was clamped to 0
+    #[serde(default)]
+    pub break_even_recouped: bool,
+
+    /// Price of one unit on the as-of date: current_value / amount
+    #[serde(default)]
+    pub current_price: f64,
+
     /// Absolute gain/loss for this asset
     pub gain_loss: f64,
 
+    /// Part of `gain_loss` from price moves in the asset's native quote currency
+    #[serde(default)]
+    pub asset_gain: f64,
+
+    /// Part of `gain_loss` from exchange-rate moves: gain_loss - asset_gain - income
+    #[serde(default)]
+    pub fx_gain: f64,
+
+    /// Part of `gain_loss` received as income: `Interest` events at their
+    /// values when received, plus units accrued under accrual rules (and
+    /// not yet materialized) at the as-of price
+    #[serde(default)]
+    pub income: f64,
+
     /// Percentage return for this asset
     pub return_pct: f64,
 
     /// Allocation percentage (this asset's value / total portfolio value × 100)
     pub allocation_pct: f64,
+
+    /// Display color for allocation charts, "#RRGGBB"
+    /// (see `ChartService::assign_colors`)
+    #[serde(default)]
+    pub color: String,
+
+    /// Where `current_value`'s price came from, for badging holdings
+    /// valued under `Settings::unpriceable_asset_policy`
+    #[serde(default)]
+    pub price_status: PriceStatus,
+}
+
+/// How a holding's value was priced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceStatus {
+    /// A price for the valuation date
+    #[default]
+    Fresh,
+    /// No price for the valuation date; the latest cached one, from `as_of`
+    Stale { as_of: chrono::NaiveDate },
+    /// No price found; the asset's manual price
+    Manual,
+    /// No price found; valued at zero
+    Unavailable,
+}
+
+/// Value and gain of all assets of one type (e.g., all crypto).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetTypeAllocation {
+    pub asset_type: AssetType,
+
+    /// Current value of all holdings of this type in the display currency
+    pub value: f64,
+
+    /// This type's value / total portfolio value × 100
+    pub allocation_pct: f64,
+
+    /// Gain/loss of all assets of this type, including fully sold ones:
+    /// value + sell proceeds - total invested
+    pub gain_loss: f64,
+}
+
+/// Estimated annual income (dividends, interest) of the current holdings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncomeProjection {
+    /// Date the holding values are taken from
+    pub as_of_date: chrono::NaiveDate,
+
+    /// Currency used for all monetary values
+    pub currency: String,
+
+    /// Sum of `annual_income` over all holdings with a yield
+    pub total_annual_income: f64,
+
+    /// Holdings with a yield set, largest income first
+    pub holdings: Vec<HoldingIncome>,
+}
+
+/// Projected income of one holding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoldingIncome {
+    pub asset: Asset,
+
+    /// Value of the holding in the projection's currency
+    pub value: f64,
+
+    /// Annual yield used for the projection, in percent
+    pub annual_yield_pct: f64,
+
+    /// value × annual_yield_pct / 100
+    pub annual_income: f64,
+}
+
+/// What changed between the last time the app was opened and now
+/// (data for a "welcome back" card).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinceSummary {
+    /// The last-opened date that was requested
+    pub last_opened: chrono::NaiveDate,
+
+    /// Start date actually compared against: `last_opened`, or the portfolio
+    /// inception date when `last_opened` predates it
+    pub from: chrono::NaiveDate,
+
+    /// `true` if `from` fell back to the inception date
+    pub from_is_inception: bool,
+
+    /// End date (today)
+    pub to: chrono::NaiveDate,
+
+    /// Currency used for all monetary values
+    pub currency: String,
+
+    /// Portfolio value on `from`
+    pub value_then: f64,
+
+    /// Portfolio value on `to`
+    pub value_now: f64,
+
+    /// value_now - value_then (includes the effect of new buys/sells)
+    pub value_change: f64,
+
+    /// Percentage change: (value_change / value_then) * 100, or 0 if value_then is 0
+    pub value_change_pct: f64,
+
+    /// Unit price change of every asset held on `from` or `to`,
+    /// largest absolute move first
+    pub asset_changes: Vec<AssetPriceChange>,
+
+    /// The asset with the largest absolute price move, if any
+    pub biggest_mover: Option<AssetPriceChange>,
+
+    /// Events dated after `last_opened`, oldest first
+    pub new_events: Vec<Event>,
+}
+
+/// Unit price of one asset at the start and end of a `SinceSummary` period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetPriceChange {
+    pub asset: Asset,
+
+    /// Price of one unit on the start date
+    pub price_then: f64,
+
+    /// Price of one unit on the end date
+    pub price_now: f64,
+
+    /// Percentage change: (price_now - price_then) / price_then * 100
+    pub change_pct: f64,
+}
+
+/// Period a `ValueChange` covers, ending today.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangePeriod {
+    /// Since the same day yesterday
+    Day,
+    /// Since 7 days ago
+    Week,
+    /// Since the same day last month (its last day if shorter)
+    Month,
+    /// Since the same day last year
+    Year,
+    /// Since the given date
+    Custom(chrono::NaiveDate),
+}
+
+impl ChangePeriod {
+    /// First day of the period ending on `today`.
+    pub fn start(self, today: chrono::NaiveDate) -> chrono::NaiveDate {
+        let start = match self {
+            ChangePeriod::Day => today.checked_sub_days(chrono::Days::new(1)),
+            ChangePeriod::Week => today.checked_sub_days(chrono::Days::new(7)),
+            ChangePeriod::Month => today.checked_sub_months(chrono::Months::new(1)),
+            ChangePeriod::Year => today.checked_sub_months(chrono::Months::new(12)),
+            ChangePeriod::Custom(from) => Some(from),
+        };
+        start.unwrap_or(chrono::NaiveDate::MIN)
+    }
+}
+
+/// Change of the portfolio value over a `ChangePeriod` (data for a
+/// dashboard tile such as "▲ 2.3% this week"), from two valuations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange {
+    /// The period that was requested
+    pub period: ChangePeriod,
+
+    /// Start date actually valued: the period's start, or the portfolio
+    /// inception date when the period starts before it
+    pub from: chrono::NaiveDate,
+
+    /// `true` if `from` fell back to the inception date
+    pub from_is_inception: bool,
+
+    /// End date (today)
+    pub to: chrono::NaiveDate,
+
+    /// Currency used for all monetary values
+    pub currency: String,
+
+    /// Portfolio value on `from`
+    pub value_then: f64,
+
+    /// Portfolio value on `to`
+    pub value_now: f64,
+
+    /// value_now - value_then (includes the effect of buys/sells in between)
+    pub change: f64,
+
+    /// Percentage change: (change / value_then) * 100, or 0 if value_then is 0
+    pub change_pct: f64,
+
+    /// `true` if some holding on `from` had no price for that day and was
+    /// valued with the first cached price within the period instead
+    pub value_then_estimated: bool,
+}
+
+/// Yearly statement of the portfolio (e.g., for an accountant), in one currency.
+///
+/// Event values are taken at each event's own date. Values that need a
+/// price which could not be found are `None`, and the price is listed in
+/// `missing_prices`; they are never counted as zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnualReport {
+    /// Fiscal year, named after the calendar year it starts in
+    pub year: i32,
+
+    /// Currency used for all monetary values
+    pub currency: String,
+
+    /// First day of the (fiscal) year
+    pub from: chrono::NaiveDate,
+
+    /// Last day of the year, or today for the current year
+    pub to: chrono::NaiveDate,
+
+    /// Value of the holdings at the end of the previous year
+    pub opening_value: Option<f64>,
+
+    /// Value of the holdings on `to`
+    pub closing_value: Option<f64>,
+
+    /// Actual events of the year, oldest first
+    pub events: Vec<ReportEvent>,
+
+    /// Sum of the year's buy values
+    pub total_bought: Option<f64>,
+
+    /// Sum of the year's sell values
+    pub total_sold: Option<f64>,
+
+    /// Gain of the year's sells over their average cost basis
+    pub realized_gain: Option<f64>,
+
+    /// Fees paid; events don't record fees, so this is always `None`
+    pub fees: Option<f64>,
+
+    /// Estimated income from asset yields on the closing holdings, if any
+    /// holding has a yield set (actual payouts are not tracked)
+    pub income: Option<IncomeProjection>,
+
+    /// Prices that could not be found, oldest first
+    pub missing_prices: Vec<MissingPrice>,
+}
+
+/// One event of an `AnnualReport`, valued at its date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportEvent {
+    pub event: Event,
+
+    /// Price of one unit on the event date
+    pub unit_price: Option<f64>,
+
+    /// amount × unit_price
+    pub value: Option<f64>,
+
+    /// For sells: value minus the average cost of the units sold
+    pub realized_gain: Option<f64>,
+}
+
+/// A price an `AnnualReport` needed but could not get.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingPrice {
+    pub asset: Asset,
+    pub date: chrono::NaiveDate,
+
+    /// Why the lookup failed
+    pub reason: String,
+}
+
+impl AnnualReport {
+    /// Whether every value could be computed.
+    pub fn is_complete(&self) -> bool {
+        self.missing_prices.is_empty()
+    }
+
+    /// Plain Markdown rendering of the report: a totals table, the events
+    /// table and the list of missing prices. Unknown values show as "n/a".
+    pub fn to_markdown(&self) -> String {
+        let amount = |value: Option<f64>| match value {
+            Some(value) => format!("{value:.2} {}", self.currency),
+            None => "n/a".to_string(),
+        };
+        let opening_date = self.from.pred_opt().unwrap_or(self.from);
+
+        let mut md = format!("# Annual report {}\n\n", self.year);
+        md.push_str(&format!(
+            "Period: {} to {} · Currency: {}\n\n",
+            self.from, self.to, self.currency
+        ));
+        md.push_str("| | |\n|---|---:|\n");
+        md.push_str(&format!(
+            "| Opening value ({opening_date}) | {} |\n",
+            amount(self.opening_value)
+        ));
+        md.push_str(&format!(
+            "| Closing value ({}) | {} |\n",
+            self.to,
+            amount(self.closing_value)
+        ));
+        md.push_str(&format!("| Bought | {} |\n", amount(self.total_bought)));
+        md.push_str(&format!("| Sold | {} |\n", amount(self.total_sold)));
+        md.push_str(&format!("| Realized gain | {} |\n", amount(self.realized_gain)));
+        let fees = match self.fees {
+            Some(fees) => amount(Some(fees)),
+            None => "not tracked".to_string(),
+        };
+        md.push_str(&format!("| Fees | {fees} |\n"));
+        if let Some(income) = &self.income {
+            md.push_str(&format!(
+                "| Estimated annual income | {} |\n",
+                amount(Some(income.total_annual_income))
+            ));
+        }
+
+        md.push_str("\n## Events\n\n");
+        if self.events.is_empty() {
+            md.push_str("No events in this year.\n");
+        } else {
+            md.push_str("| Date | Type | Asset | Amount | Unit price | Value | Realized gain |\n");
+            md.push_str("|---|---|---|---:|---:|---:|---:|\n");
+            for row in &self.events {
+                let event = &row.event;
+                let realized = match event.event_type {
+                    EventType::Sell => amount(row.realized_gain),
+                    _ => String::new(),
+                };
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} | {} |\n",
+                    event.date,
+                    event.event_type,
+                    event.asset.symbol,
+                    event.amount,
+                    amount(row.unit_price),
+                    amount(row.value),
+                    realized,
+                ));
+            }
+        }
+
+        if !self.missing_prices.is_empty() {
+            md.push_str("\n## Missing prices\n\n");
+            for missing in &self.missing_prices {
+                md.push_str(&format!(
+                    "- {} on {}: {}\n",
+                    missing.asset.symbol, missing.date, missing.reason
+                ));
+            }
+        }
+        md
+    }
+}
+
+/// Event counts and traded amounts, computed without any price data
+/// (see `SavingsTracker::get_statistics`). Planned events are not counted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Statistics {
+    /// Number of actual events
+    pub total_events: usize,
+
+    /// Events per type: always Buy, then Sell, then Deposit, Withdrawal and
+    /// Interest if there are any
+    pub by_event_type: Vec<EventTypeCount>,
+
+    /// Events per asset type, for types that have events
+    /// (in the order Crypto, Fiat, Metal, Stock)
+    pub by_asset_type: Vec<AssetTypeCount>,
+
+    /// Per-asset statistics, sorted by symbol
+    pub assets: Vec<AssetStatistics>,
+
+    /// Date of the earliest event
+    pub first_event_date: Option<chrono::NaiveDate>,
+
+    /// Date of the latest event
+    pub last_event_date: Option<chrono::NaiveDate>,
+}
+
+/// Number of events of one type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventTypeCount {
+    pub event_type: EventType,
+    pub count: usize,
+}
+
+/// Number of events and distinct assets of one asset type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetTypeCount {
+    pub asset_type: AssetType,
+    pub event_count: usize,
+    pub asset_count: usize,
+}
+
+/// Event counts and traded amounts of one asset (in units of the asset).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetStatistics {
+    pub asset: Asset,
+    pub event_count: usize,
+    pub buy_count: usize,
+    pub sell_count: usize,
+
+    /// Sum of the amounts bought
+    pub total_bought: f64,
+
+    /// Sum of the amounts sold
+    pub total_sold: f64,
+
+    pub first_event_date: chrono::NaiveDate,
+    pub last_event_date: chrono::NaiveDate,
 }
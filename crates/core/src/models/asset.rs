@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// The type/category of a tracked asset.
 /// Determines which price provider to use for fetching market data.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AssetType {
     /// Cryptocurrencies (BTC, ETH, etc.) — uses CoinCap API
     Crypto,
@@ -25,6 +26,67 @@ impl std::fmt::Display for AssetType {
     }
 }
 
+impl AssetType {
+    /// Decimal places amounts of this type are shown with, unless the
+    /// asset's metadata overrides it: 8 for crypto, 4 for metals and
+    /// stocks, 2 for fiat.
+    pub fn default_precision(&self) -> u32 {
+        match self {
+            AssetType::Crypto => 8,
+            AssetType::Metal | AssetType::Stock => 4,
+            AssetType::Fiat => 2,
+        }
+    }
+}
+
+/// Most decimal places an asset's amounts can be shown with
+/// (`AssetMetadata::display_precision`).
+pub const MAX_DISPLAY_PRECISION: u32 = 12;
+
+/// Grams in one troy ounce.
+pub const GRAMS_PER_TROY_OUNCE: f64 = 31.1034768;
+
+/// Unit that one "1.0" of a metal holding, and each metal price, refers to.
+///
+/// Providers quote metals per troy ounce; `PriceService` converts their
+/// prices to the configured unit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MetalUnit {
+    #[default]
+    TroyOunce,
+    Gram,
+    Kilogram,
+}
+
+impl MetalUnit {
+    /// Number of troy ounces in one unit (a per-ounce price times this is the unit price).
+    pub fn troy_ounces(&self) -> f64 {
+        match self {
+            MetalUnit::TroyOunce => 1.0,
+            MetalUnit::Gram => 1.0 / GRAMS_PER_TROY_OUNCE,
+            MetalUnit::Kilogram => 1000.0 / GRAMS_PER_TROY_OUNCE,
+        }
+    }
+
+    /// Factor that converts a price per `self` into a price per `to`.
+    pub fn price_factor(&self, to: MetalUnit) -> f64 {
+        if *self == to {
+            return 1.0;
+        }
+        to.troy_ounces() / self.troy_ounces()
+    }
+}
+
+impl std::fmt::Display for MetalUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetalUnit::TroyOunce => write!(f, "troy ounce"),
+            MetalUnit::Gram => write!(f, "gram"),
+            MetalUnit::Kilogram => write!(f, "kilogram"),
+        }
+    }
+}
+
 /// Represents a trackable asset (currency, crypto, metal, stock).
 ///
 /// **Equality and hashing** are based solely on `(symbol, asset_type)`,
@@ -83,3 +145,58 @@ impl Asset {
         Self::new(symbol, name, AssetType::Stock)
     }
 }
+
+/// Key of `Portfolio::asset_metadata`: uppercase symbol and asset type, so
+/// a stock and a crypto with the same ticker keep separate metadata.
+pub type AssetMetadataKey = (String, AssetType);
+
+/// User-maintained information about an asset, stored per symbol and type
+/// in `Portfolio::asset_metadata`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetMetadata {
+    /// Expected annual income as a percentage of the holding's value
+    /// (dividend or interest yield), used for income projections
+    #[serde(default)]
+    pub annual_yield_pct: Option<f64>,
+
+    /// Free-form notes about the asset (e.g., where it is kept)
+    #[serde(default)]
+    pub notes: Option<String>,
+
+    /// Display color as "#RRGGBB", e.g. for allocation charts
+    #[serde(default)]
+    pub color_hex: Option<String>,
+
+    /// Icon identifier chosen by the frontend
+    #[serde(default)]
+    pub icon: Option<String>,
+
+    /// User-defined fields, by name
+    #[serde(default, serialize_with = "super::sorted::map")]
+    pub custom_fields: HashMap<String, String>,
+
+    /// Decimal places the asset's amounts are shown with, overriding
+    /// `AssetType::default_precision`
+    #[serde(default)]
+    pub display_precision: Option<u32>,
+
+    /// Unit price in the asset's quote currency (USD for crypto, metals and
+    /// stocks) for when no price can be found, used under
+    /// `UnpriceableAssetPolicy::ManualPrice`
+    #[serde(default)]
+    pub manual_price: Option<f64>,
+}
+
+impl AssetMetadata {
+    /// `true` if no field is set (the entry can be dropped).
+    pub fn is_empty(&self) -> bool {
+        self.annual_yield_pct.is_none()
+            && self.notes.is_none()
+            && self.color_hex.is_none()
+            && self.icon.is_none()
+            && self.custom_fields.is_empty()
+            && self.display_precision.is_none()
+            && self.manual_price.is_none()
+    }
+}
@@ -1,28 +1,44 @@
-use chrono::NaiveDate;
+use chrono::{Datelike, Days, Months, NaiveDate};
 use serde::{Deserialize, Serialize};
 
 use super::event::EventType;
 
+/// Charts spanning more days than this get weekly points.
+pub const WEEKLY_CHART_THRESHOLD_DAYS: i64 = 3650;
+
+/// Charts spanning more days than this get monthly points.
+pub const MONTHLY_CHART_THRESHOLD_DAYS: i64 = 7300;
+
 /// A single data point for portfolio chart rendering.
 ///
 /// The core generates these — the frontend just renders them.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChartDataPoint {
     /// The date for this data point
     pub date: NaiveDate,
 
-    /// Total portfolio value in the default display currency at this date
+    /// Total portfolio value in `currency` at this date
     pub portfolio_value: f64,
 
+    /// The currency `portfolio_value` and event values are expressed in (e.g., "PLN")
+    #[serde(default)]
+    pub currency: String,
+
     /// Any buy/sell events that happened on this date
     pub events: Vec<ChartEvent>,
+
+    /// Valued with a provisional price (see `PriceCache::is_provisional`): a
+    /// current quote standing in for a close not known yet, so the value may
+    /// still change
+    #[serde(default)]
+    pub provisional: bool,
 }
 
 /// An event annotation on a chart data point.
 ///
 /// Tells the frontend "on this date, the user bought/sold X amount of Y,
 /// worth Z in the display currency".
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChartEvent {
     /// Buy or Sell
     pub event_type: EventType,
@@ -33,6 +49,130 @@ pub struct ChartEvent {
     /// Amount of the asset
     pub amount: f64,
 
-    /// Value of this event in the default display currency
+    /// Value of this event in the chart's display currency
+    /// (the default currency unless another one was requested)
     pub value_in_default_currency: f64,
+
+    /// Price of one unit of the asset in the chart's display currency on
+    /// the event date (from the price cache or a provider), e.g. to draw a
+    /// marker against the asset's price line. `None` if no price was found.
+    #[serde(default)]
+    pub unit_price: Option<f64>,
+}
+
+/// Chart of a group of assets (e.g. the "retirement" ones), valued as if
+/// the portfolio held nothing else.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroupChart {
+    /// Value of the group's holdings on every date
+    pub points: Vec<ChartDataPoint>,
+
+    /// Requested symbols (uppercase, sorted) that no event has; left out
+    /// of the chart
+    pub ignored_symbols: Vec<String>,
+}
+
+/// Options of `SavingsTracker::generate_asset_chart_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetChartOptions {
+    /// Also return the asset's price per unit on every date
+    /// (`AssetChart::unit_prices`)
+    pub include_unit_prices: bool,
+}
+
+/// Options of `SavingsTracker::generate_portfolio_chart_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortfolioChartOptions {
+    /// Apply planned events as if they happened. `to` may then be in the
+    /// future: days after today are valued at today's prices, projecting
+    /// the value line forward
+    pub include_planned: bool,
+}
+
+/// Chart of a single asset: the value of its holdings per date and,
+/// optionally, its price per unit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetChart {
+    /// Value of the holdings on every date, as `generate_asset_chart` returns
+    pub points: Vec<ChartDataPoint>,
+
+    /// Price of one unit in the chart's currency per date. Carried forward
+    /// over dates without a price (weekends, holidays); dates before the
+    /// first known price are left out. Empty unless `include_unit_prices`.
+    pub unit_prices: Vec<(NaiveDate, f64)>,
+}
+
+/// Spacing of chart points, picked from the length of the range so that
+/// long charts stay quick to compute and to draw.
+///
+/// Points start at the range's first day, step by a day, a week or a month
+/// and always end with a point on its last day. The holdings change with
+/// every event in between, and a point lists the events since the previous
+/// one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChartResolution {
+    /// A point per day (ranges up to `WEEKLY_CHART_THRESHOLD_DAYS`)
+    Daily,
+    /// A point every 7 days (ranges up to `MONTHLY_CHART_THRESHOLD_DAYS`)
+    Weekly,
+    /// A point per month, on the first day's day of the month (or the
+    /// month's last day if it is shorter)
+    Monthly,
+}
+
+impl ChartResolution {
+    /// Resolution of a chart from `from` to `to`.
+    pub fn for_range(from: NaiveDate, to: NaiveDate) -> Self {
+        match (to - from).num_days() {
+            days if days > MONTHLY_CHART_THRESHOLD_DAYS => Self::Monthly,
+            days if days > WEEKLY_CHART_THRESHOLD_DAYS => Self::Weekly,
+            _ => Self::Daily,
+        }
+    }
+
+    /// Approximate days between points.
+    pub fn step_days(self) -> u64 {
+        match self {
+            Self::Daily => 1,
+            Self::Weekly => 7,
+            Self::Monthly => 31,
+        }
+    }
+
+    /// Date of the point after the one on `date` in a chart from `from` to
+    /// `to`, or `None` if `date` is the last one.
+    pub fn next_date(self, from: NaiveDate, date: NaiveDate, to: NaiveDate) -> Option<NaiveDate> {
+        if date >= to {
+            return None;
+        }
+        let next = match self {
+            Self::Daily => date.succ_opt(),
+            Self::Weekly => date.checked_add_days(Days::new(7)),
+            // Counted from `from`, so a short month doesn't shift later points
+            Self::Monthly => {
+                let months = (date.year() - from.year()) * 12 + date.month() as i32
+                    - from.month() as i32;
+                u32::try_from(months + 1)
+                    .ok()
+                    .and_then(|months| from.checked_add_months(Months::new(months)))
+            }
+        };
+        Some(next.map_or(to, |next| next.min(to)))
+    }
+
+    /// Number of points of a chart from `from` to `to`.
+    pub fn point_count(self, from: NaiveDate, to: NaiveDate) -> usize {
+        if from > to {
+            return 0;
+        }
+        let mut count = 1;
+        let mut date = from;
+        while let Some(next) = self.next_date(from, date, to) {
+            count += 1;
+            date = next;
+        }
+        count
+    }
 }
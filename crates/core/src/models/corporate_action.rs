@@ -0,0 +1,247 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use super::event::Event;
+use crate::errors::CoreError;
+
+/// What a corporate action does to an asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CorporateActionType {
+    /// Every unit becomes `ratio` units (4.0 for a 4:1 split, 0.1 for a
+    /// 1:10 reverse split)
+    Split { ratio: f64 },
+
+    /// The asset is quoted as `new_symbol` from now on (e.g., "FB" → "META")
+    Rename { new_symbol: String },
+}
+
+/// A stock split or symbol change, stored in the portfolio.
+///
+/// Holdings, analytics and charts count every unit in today's terms:
+/// amounts of events dated before a split are multiplied by its ratio (and
+/// recorded unit prices divided by it), matching the split-adjusted price
+/// history providers return. Events of a renamed symbol are valued as the
+/// new symbol at all dates, so prices are fetched under the name providers
+/// know. The events themselves keep the amounts and symbols entered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorporateAction {
+    /// Unique identifier
+    pub id: Uuid,
+
+    /// Uppercase symbol of the asset the action applies to
+    pub symbol: String,
+
+    pub action: CorporateActionType,
+
+    /// First day the action is in effect (e.g., the first day of trading
+    /// at the split-adjusted price)
+    pub effective_date: NaiveDate,
+}
+
+impl CorporateAction {
+    pub fn new(symbol: &str, action: CorporateActionType, effective_date: NaiveDate) -> Self {
+        let action = match action {
+            CorporateActionType::Rename { new_symbol } => CorporateActionType::Rename {
+                new_symbol: new_symbol.trim().to_uppercase(),
+            },
+            split => split,
+        };
+        Self {
+            id: Uuid::new_v4(),
+            symbol: symbol.trim().to_uppercase(),
+            action,
+            effective_date,
+        }
+    }
+
+    /// A split of `ratio` new units per old unit.
+    pub fn split(symbol: &str, ratio: f64, effective_date: NaiveDate) -> Self {
+        Self::new(symbol, CorporateActionType::Split { ratio }, effective_date)
+    }
+
+    /// A change of `symbol` to `new_symbol`.
+    pub fn rename(symbol: &str, new_symbol: &str, effective_date: NaiveDate) -> Self {
+        let new_symbol = new_symbol.to_string();
+        Self::new(symbol, CorporateActionType::Rename { new_symbol }, effective_date)
+    }
+
+    /// Check the action on its own: a symbol, a positive finite split ratio
+    /// other than 1, and a new symbol different from the old one.
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if self.symbol.is_empty() {
+            return Err(CoreError::ValidationError(
+                "A corporate action needs a symbol".into(),
+            ));
+        }
+        match &self.action {
+            CorporateActionType::Split { ratio } if !ratio.is_finite() || *ratio <= 0.0 => Err(
+                CoreError::ValidationError(format!(
+                    "Invalid split ratio {ratio}: must be a positive number"
+                )),
+            ),
+            CorporateActionType::Split { ratio } if *ratio == 1.0 => Err(
+                CoreError::ValidationError("A split ratio of 1 changes nothing".into()),
+            ),
+            CorporateActionType::Rename { new_symbol } if new_symbol.is_empty() => Err(
+                CoreError::ValidationError("A rename needs the new symbol".into()),
+            ),
+            CorporateActionType::Rename { new_symbol } if *new_symbol == self.symbol => Err(
+                CoreError::ValidationError(format!("{new_symbol} is already the symbol")),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A split in the table `SavingsTracker::apply_known_splits` reads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KnownSplit {
+    pub symbol: &'static str,
+    pub ratio: f64,
+
+    /// First trading day at the split-adjusted price, as (year, month, day)
+    pub effective: (i32, u32, u32),
+}
+
+impl KnownSplit {
+    /// The split as a corporate action (`None` if the date is invalid).
+    pub fn to_action(&self) -> Option<CorporateAction> {
+        let (year, month, day) = self.effective;
+        let date = NaiveDate::from_ymd_opt(year, month, day)?;
+        Some(CorporateAction::split(self.symbol, self.ratio, date))
+    }
+}
+
+/// Splits of widely held stocks, for `SavingsTracker::apply_known_splits`.
+pub const KNOWN_SPLITS: &[KnownSplit] = &[
+    KnownSplit { symbol: "AAPL", ratio: 7.0, effective: (2014, 6, 9) },
+    KnownSplit { symbol: "AAPL", ratio: 4.0, effective: (2020, 8, 31) },
+    KnownSplit { symbol: "TSLA", ratio: 5.0, effective: (2020, 8, 31) },
+    KnownSplit { symbol: "TSLA", ratio: 3.0, effective: (2022, 8, 25) },
+    KnownSplit { symbol: "NVDA", ratio: 4.0, effective: (2021, 7, 20) },
+    KnownSplit { symbol: "NVDA", ratio: 10.0, effective: (2024, 6, 10) },
+    KnownSplit { symbol: "AMZN", ratio: 20.0, effective: (2022, 6, 6) },
+    KnownSplit { symbol: "GOOGL", ratio: 20.0, effective: (2022, 7, 18) },
+    KnownSplit { symbol: "GOOG", ratio: 20.0, effective: (2022, 7, 18) },
+    KnownSplit { symbol: "SHOP", ratio: 10.0, effective: (2022, 6, 29) },
+    KnownSplit { symbol: "WMT", ratio: 3.0, effective: (2024, 2, 26) },
+    KnownSplit { symbol: "AVGO", ratio: 10.0, effective: (2024, 7, 15) },
+];
+
+/// `event` as holdings and valuation count it under `actions` (sorted by
+/// effective date): renamed to the current symbol, and with the amount and
+/// recorded unit price of every later split applied. Borrowed when no
+/// action applies.
+pub fn adjust_event<'a>(actions: &[CorporateAction], event: &'a Event) -> Cow<'a, Event> {
+    let mut adjusted = Cow::Borrowed(event);
+    for action in actions {
+        if action.symbol != adjusted.asset.symbol {
+            continue;
+        }
+        match &action.action {
+            CorporateActionType::Split { ratio } => {
+                if event.date < action.effective_date {
+                    let event = adjusted.to_mut();
+                    event.amount *= ratio;
+                    if let Some(recorded) = &mut event.recorded_value {
+                        recorded.quote_unit_price /= ratio;
+                        recorded.unit_price /= ratio;
+                    }
+                }
+            }
+            CorporateActionType::Rename { new_symbol } => {
+                adjusted.to_mut().asset.symbol = new_symbol.clone();
+            }
+        }
+    }
+    adjusted
+}
+
+/// The symbol `symbol` (uppercase) is quoted under after the renames in
+/// `actions` (sorted by effective date).
+pub fn current_symbol(actions: &[CorporateAction], symbol: &str) -> String {
+    let mut current = symbol.to_string();
+    for action in actions {
+        if let CorporateActionType::Rename { new_symbol } = &action.action {
+            if action.symbol == current {
+                current = new_symbol.clone();
+            }
+        }
+    }
+    current
+}
+
+/// The events of a portfolio as holdings and valuation count them (see
+/// `CorporateAction`), in storage order.
+pub enum ValuedEvents<'a> {
+    /// No corporate action applies: the stored events
+    Stored(&'a [Event]),
+
+    /// Adjusted copies of the stored events
+    Adjusted(Arc<Vec<Event>>),
+}
+
+impl ValuedEvents<'_> {
+    /// Every event, deleted and planned ones included.
+    pub fn all(&self) -> &[Event] {
+        match self {
+            Self::Stored(events) => events,
+            Self::Adjusted(events) => events,
+        }
+    }
+
+    /// Events that are not deleted (actual and planned), in date order.
+    pub fn active(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.all().iter().filter(|e| !e.is_deleted())
+    }
+
+    /// Events that actually happened (not planned, not deleted), in date order.
+    pub fn actual(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.active().filter(|e| !e.is_planned)
+    }
+}
+
+/// Adjusted events with the events generation and count they were built from.
+type BuiltEvents = (u64, usize, Arc<Vec<Event>>);
+
+/// Adjusted events of a portfolio, built once per events generation.
+///
+/// Not serialized; a clone starts from the same events, which stay valid
+/// as long as the clone's events are unchanged.
+#[derive(Debug, Default)]
+pub struct ValuedEventsCell(Mutex<Option<BuiltEvents>>);
+
+impl ValuedEventsCell {
+    /// The cached adjusted events if they are current, otherwise freshly
+    /// adjusted ones.
+    pub fn get_or_build(
+        &self,
+        events: &[Event],
+        actions: &[CorporateAction],
+        generation: u64,
+    ) -> Arc<Vec<Event>> {
+        let mut cached = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match cached.as_ref() {
+            Some((built, count, adjusted)) if *built == generation && *count == events.len() => {
+                Arc::clone(adjusted)
+            }
+            _ => {
+                let adjusted: Arc<Vec<Event>> = Arc::new(
+                    events.iter().map(|e| adjust_event(actions, e).into_owned()).collect(),
+                );
+                *cached = Some((generation, events.len(), Arc::clone(&adjusted)));
+                adjusted
+            }
+        }
+    }
+}
+
+impl Clone for ValuedEventsCell {
+    fn clone(&self) -> Self {
+        let cached = self.0.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        Self(Mutex::new(cached))
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use super::asset::AssetType;
+
+/// Days from `from` to `to`, both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateSpan {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateSpan {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Self {
+        Self { from, to }
+    }
+
+    /// Number of days in the span.
+    pub fn days(&self) -> usize {
+        ((self.to - self.from).num_days() + 1).max(0) as usize
+    }
+}
+
+/// Which days of a price series are cached within a date range, without
+/// asking any provider (see `SavingsTracker::get_cache_coverage`).
+///
+/// Stock markets don't trade on weekends, so for stocks weekend days are
+/// never gaps, and neither are up to two weekdays between cached quotes
+/// (market holidays).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheCoverage {
+    pub symbol: String,
+
+    /// Currency the series is quoted in (e.g., "USD")
+    pub currency: String,
+
+    pub asset_type: AssetType,
+
+    /// Runs of cached days, in date order
+    pub covered: Vec<DateSpan>,
+
+    /// Runs of days without a cached price, in date order
+    pub gaps: Vec<DateSpan>,
+
+    /// Days in `gaps` a price could be fetched for (weekdays only for stocks)
+    pub missing_days: usize,
+}
+
+impl CacheCoverage {
+    /// Whether every day of the range is cached.
+    pub fn is_complete(&self) -> bool {
+        self.gaps.is_empty()
+    }
+}
+
+/// Whether `date` falls on a day stock markets are closed every week.
+pub(crate) fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
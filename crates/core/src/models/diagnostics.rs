@@ -0,0 +1,113 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fmt;
+
+use crate::errors::CoreError;
+use crate::models::asset::AssetType;
+
+/// Prices a `PriceService` was asked for: one day (`from == to`) or a range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceLookup {
+    /// Uppercase symbol and quote currency
+    pub symbol: String,
+    pub currency: String,
+    pub asset_type: AssetType,
+
+    /// First and last day, inclusive
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl PriceLookup {
+    pub fn new(
+        symbol: &str,
+        currency: &str,
+        asset_type: &AssetType,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            currency: currency.to_uppercase(),
+            asset_type: asset_type.clone(),
+            from,
+            to,
+        }
+    }
+}
+
+/// A step of a price lookup, reported to the sink set with
+/// `PriceService::set_event_sink`.
+///
+/// A lookup answered from `PriceCache` reports `CacheHit`. Otherwise each
+/// provider tried reports `ProviderAttempt`, then `ProviderSucceeded` or
+/// `ProviderFailed`; a failure followed by another provider's attempt is a
+/// fallback.
+#[derive(Debug, Clone)]
+pub enum PriceServiceEvent {
+    /// The lookup was answered from the price cache
+    CacheHit { lookup: PriceLookup },
+
+    /// `provider` is asked (its answer may come from the response memo)
+    ProviderAttempt { provider: String, lookup: PriceLookup },
+
+    /// `provider` failed, or its quote was rejected by the sanity checks
+    ProviderFailed { provider: String, lookup: PriceLookup, error: CoreError },
+
+    /// `provider`'s answer is used
+    ProviderSucceeded { provider: String, lookup: PriceLookup },
+}
+
+impl PriceServiceEvent {
+    /// The lookup the event belongs to.
+    pub fn lookup(&self) -> &PriceLookup {
+        match self {
+            Self::CacheHit { lookup }
+            | Self::ProviderAttempt { lookup, .. }
+            | Self::ProviderFailed { lookup, .. }
+            | Self::ProviderSucceeded { lookup, .. } => lookup,
+        }
+    }
+
+    /// The provider the event is about (`None` for cache hits).
+    pub fn provider(&self) -> Option<&str> {
+        match self {
+            Self::CacheHit { .. } => None,
+            Self::ProviderAttempt { provider, .. }
+            | Self::ProviderFailed { provider, .. }
+            | Self::ProviderSucceeded { provider, .. } => Some(provider),
+        }
+    }
+}
+
+impl fmt::Display for PriceLookup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { symbol, currency, from, to, .. } = self;
+        write!(f, "{symbol}/{currency} on {from}")?;
+        if to != from {
+            write!(f, " to {to}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PriceServiceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CacheHit { lookup } => write!(f, "{lookup}: cached"),
+            Self::ProviderAttempt { provider, lookup } => write!(f, "{lookup}: asking {provider}"),
+            Self::ProviderFailed { provider, lookup, error } => {
+                write!(f, "{lookup}: {provider} failed ({error})")
+            }
+            Self::ProviderSucceeded { provider, lookup } => write!(f, "{lookup}: used {provider}"),
+        }
+    }
+}
+
+/// A `PriceServiceEvent` recorded by `SavingsTracker::get_price_diagnostics`.
+#[derive(Debug, Clone)]
+pub struct PriceDiagnostic {
+    /// When the event happened
+    pub at: DateTime<Utc>,
+
+    pub event: PriceServiceEvent,
+}
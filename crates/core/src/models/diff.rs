@@ -0,0 +1,425 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+use super::accrual::AccrualRule;
+use super::corporate_action::CorporateAction;
+use super::event::{Event, RecordedValue};
+use super::portfolio::Portfolio;
+use super::reminder::Reminder;
+use super::settings::Settings;
+
+/// Shown instead of API keys and proxy passwords, which must not end up in a diff view.
+const HIDDEN_VALUE: &str = "***";
+
+/// What changed between two versions of a portfolio (e.g., the saved file
+/// and the one about to overwrite it).
+///
+/// Events are matched by id. Values are rendered as display strings so the
+/// diff serializes as-is for the frontend.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioDiff {
+    /// Events only in the new version, in date order
+    pub added_events: Vec<Event>,
+
+    /// Events only in the old version, in date order
+    pub removed_events: Vec<Event>,
+
+    /// Events in both versions whose fields differ, in the new version's order
+    pub modified_events: Vec<EventDiff>,
+
+    /// Changed settings, sorted by field (API key values are hidden)
+    pub settings_changes: Vec<FieldChange>,
+
+    /// Added, removed and changed corporate actions
+    pub corporate_actions: RecordChanges<CorporateAction>,
+
+    /// Added, removed and changed accrual rules
+    pub accrual_rules: RecordChanges<AccrualRule>,
+
+    /// Added, removed and changed reminders, matched by event
+    pub reminders: RecordChanges<Reminder>,
+
+    /// Cached price points in the new version minus those in the old one
+    pub cache_entry_delta: i64,
+}
+
+/// Records of one kind matched by id (e.g., corporate actions) that only
+/// one version has or that differ between them, each list in the order of
+/// the version it comes from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordChanges<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+    /// (old, new) versions of records in both that differ
+    pub modified: Vec<(T, T)>,
+}
+
+impl<T> Default for RecordChanges<T> {
+    fn default() -> Self {
+        Self { added: Vec::new(), removed: Vec::new(), modified: Vec::new() }
+    }
+}
+
+impl<T: Clone + PartialEq> RecordChanges<T> {
+    /// The changes that turn the records `old` into `new`, matched by `id`.
+    pub fn between(old: &[T], new: &[T], id: impl Fn(&T) -> Uuid) -> Self {
+        let old_records: HashMap<Uuid, &T> = old.iter().map(|r| (id(r), r)).collect();
+        let new_ids: BTreeSet<Uuid> = new.iter().map(&id).collect();
+        let mut changes = Self::default();
+        for record in new {
+            match old_records.get(&id(record)) {
+                None => changes.added.push(record.clone()),
+                Some(&previous) if previous != record => {
+                    changes.modified.push((previous.clone(), record.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        changes.removed = old.iter().filter(|r| !new_ids.contains(&id(r))).cloned().collect();
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Field-level changes of one event present in both versions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventDiff {
+    pub id: Uuid,
+    pub changes: Vec<FieldChange>,
+}
+
+/// One changed field: its name and the old and new values
+/// (`None` where the field is unset or absent in that version).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Field name, dotted for nested entries (e.g., "api_keys.finnhub")
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl FieldChange {
+    fn new(field: impl Into<String>, old: Option<String>, new: Option<String>) -> Self {
+        Self { field: field.into(), old, new }
+    }
+}
+
+impl PortfolioDiff {
+    /// Compute the changes that turn `old` into `new`.
+    pub fn between(old: &Portfolio, new: &Portfolio) -> Self {
+        let old_events: HashMap<Uuid, &Event> = old.events.iter().map(|e| (e.id, e)).collect();
+        let new_ids: BTreeSet<Uuid> = new.events.iter().map(|e| e.id).collect();
+
+        let mut added_events = Vec::new();
+        let mut modified_events = Vec::new();
+        for event in &new.events {
+            match old_events.get(&event.id) {
+                None => added_events.push(event.clone()),
+                Some(previous) => {
+                    let changes = Self::event_changes(previous, event);
+                    if !changes.is_empty() {
+                        modified_events.push(EventDiff { id: event.id, changes });
+                    }
+                }
+            }
+        }
+        let removed_events = old
+            .events
+            .iter()
+            .filter(|e| !new_ids.contains(&e.id))
+            .cloned()
+            .collect();
+
+        Self {
+            added_events,
+            removed_events,
+            modified_events,
+            settings_changes: Self::settings_changes(&old.settings, &new.settings),
+            corporate_actions: RecordChanges::between(
+                &old.corporate_actions,
+                &new.corporate_actions,
+                |action| action.id,
+            ),
+            accrual_rules: RecordChanges::between(&old.accrual_rules, &new.accrual_rules, |rule| {
+                rule.id
+            }),
+            reminders: RecordChanges::between(&old.reminders, &new.reminders, |reminder| {
+                reminder.event_id
+            }),
+            cache_entry_delta: new.price_cache.total_entries() as i64
+                - old.price_cache.total_entries() as i64,
+        }
+    }
+
+    /// Whether the two versions have the same events, settings, corporate
+    /// actions, accrual rules and reminders (the price cache is not
+    /// considered).
+    pub fn is_empty(&self) -> bool {
+        self.added_events.is_empty()
+            && self.removed_events.is_empty()
+            && self.modified_events.is_empty()
+            && self.settings_changes.is_empty()
+            && self.corporate_actions.is_empty()
+            && self.accrual_rules.is_empty()
+            && self.reminders.is_empty()
+    }
+
+    fn event_changes(old: &Event, new: &Event) -> Vec<FieldChange> {
+        let fields = [
+            ("event_type", old.event_type.to_string(), new.event_type.to_string()),
+            ("symbol", old.asset.symbol.clone(), new.asset.symbol.clone()),
+            ("name", old.asset.name.clone(), new.asset.name.clone()),
+            ("asset_type", old.asset.asset_type.to_string(), new.asset.asset_type.to_string()),
+            ("amount", old.amount.to_string(), new.amount.to_string()),
+            ("date", old.date.to_string(), new.date.to_string()),
+            ("is_planned", old.is_planned.to_string(), new.is_planned.to_string()),
+        ];
+        let mut changes: Vec<FieldChange> = fields
+            .into_iter()
+            .filter(|(_, old, new)| old != new)
+            .map(|(field, old, new)| FieldChange::new(field, Some(old), Some(new)))
+            .collect();
+        if old.notes != new.notes {
+            changes.push(FieldChange::new("notes", old.notes.clone(), new.notes.clone()));
+        }
+        if old.deleted_at != new.deleted_at {
+            changes.push(FieldChange::new(
+                "deleted_at",
+                old.deleted_at.map(|at| at.to_rfc3339()),
+                new.deleted_at.map(|at| at.to_rfc3339()),
+            ));
+        }
+        if old.recorded_value != new.recorded_value {
+            let render = |value: &Option<RecordedValue>| {
+                value.as_ref().map(|v| format!("{} {}", v.unit_price, v.currency))
+            };
+            changes.push(FieldChange::new(
+                "recorded_value",
+                render(&old.recorded_value),
+                render(&new.recorded_value),
+            ));
+        }
+        changes
+    }
+
+    pub(crate) fn settings_changes(old: &Settings, new: &Settings) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        push_change(
+            &mut changes,
+            "default_currency".into(),
+            Some(old.default_currency.clone()),
+            Some(new.default_currency.clone()),
+        );
+        let providers: BTreeSet<&String> = old.api_keys.keys().chain(new.api_keys.keys()).collect();
+        for provider in providers {
+            let (before, after) = (old.api_keys.get(provider), new.api_keys.get(provider));
+            if before != after {
+                // Report that a key changed without revealing it
+                let hide = |key: Option<&String>| key.map(|_| HIDDEN_VALUE.to_string());
+                changes.push(FieldChange::new(
+                    format!("api_keys.{provider}"),
+                    hide(before),
+                    hide(after),
+                ));
+            }
+        }
+        push_change(
+            &mut changes,
+            "pivot_currencies".into(),
+            Some(old.pivot_currencies.join(", ")),
+            Some(new.pivot_currencies.join(", ")),
+        );
+        push_change(
+            &mut changes,
+            "metal_unit".into(),
+            Some(old.metal_unit.to_string()),
+            Some(new.metal_unit.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "long_term_holding_days".into(),
+            Some(old.long_term_holding_days.to_string()),
+            Some(new.long_term_holding_days.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "cost_basis_method".into(),
+            Some(old.cost_basis_method.to_string()),
+            Some(new.cost_basis_method.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "fiscal_year_start_month".into(),
+            Some(old.fiscal_year_start_month.to_string()),
+            Some(new.fiscal_year_start_month.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "week_start".into(),
+            Some(old.week_start.to_string()),
+            Some(new.week_start.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "locale".into(),
+            Some(old.locale.to_string()),
+            Some(new.locale.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "unpriceable_asset_policy".into(),
+            Some(old.unpriceable_asset_policy.to_string()),
+            Some(new.unpriceable_asset_policy.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "provider_timeout_secs".into(),
+            Some(old.provider_timeout_secs.to_string()),
+            Some(new.provider_timeout_secs.to_string()),
+        );
+        let providers: BTreeSet<&String> =
+            old.provider_timeouts.keys().chain(new.provider_timeouts.keys()).collect();
+        for provider in providers {
+            let secs = |s: &Settings| s.provider_timeouts.get(provider).map(|n| n.to_string());
+            let field = format!("provider_timeouts.{provider}");
+            push_change(&mut changes, field, secs(old), secs(new));
+        }
+        if old.proxy_url != new.proxy_url {
+            changes.push(FieldChange::new(
+                "proxy_url",
+                old.proxy_url.as_deref().map(redact_credentials),
+                new.proxy_url.as_deref().map(redact_credentials),
+            ));
+        }
+        push_change(
+            &mut changes,
+            "no_proxy".into(),
+            Some(old.no_proxy.join(", ")),
+            Some(new.no_proxy.join(", ")),
+        );
+        push_change(
+            &mut changes,
+            "contact_email".into(),
+            old.contact_email.clone(),
+            new.contact_email.clone(),
+        );
+        push_change(
+            &mut changes,
+            "max_price_change_factor".into(),
+            old.max_price_change_factor.map(|n| n.to_string()),
+            new.max_price_change_factor.map(|n| n.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "monthly_price_age_days".into(),
+            old.monthly_price_age_days.map(|n| n.to_string()),
+            new.monthly_price_age_days.map(|n| n.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "max_chart_range_days".into(),
+            old.max_chart_range_days.map(|n| n.to_string()),
+            new.max_chart_range_days.map(|n| n.to_string()),
+        );
+        let providers: BTreeSet<&String> = old
+            .provider_daily_limits
+            .keys()
+            .chain(new.provider_daily_limits.keys())
+            .collect();
+        for provider in providers {
+            let limit = |s: &Settings| s.provider_daily_limits.get(provider).map(|n| n.to_string());
+            let field = format!("provider_daily_limits.{provider}");
+            push_change(&mut changes, field, limit(old), limit(new));
+        }
+        let (old_trash, new_trash) = (&old.trash_policy, &new.trash_policy);
+        push_change(
+            &mut changes,
+            "trash_policy.max_entries".into(),
+            old_trash.max_entries.map(|n| n.to_string()),
+            new_trash.max_entries.map(|n| n.to_string()),
+        );
+        push_change(
+            &mut changes,
+            "trash_policy.retention_days".into(),
+            old_trash.retention_days.map(|d| d.to_string()),
+            new_trash.retention_days.map(|d| d.to_string()),
+        );
+        let overrides: BTreeSet<(&String, &String)> = [old, new]
+            .into_iter()
+            .flat_map(|s| &s.symbol_overrides)
+            .flat_map(|(provider, ids)| ids.keys().map(move |symbol| (provider, symbol)))
+            .collect();
+        for (provider, symbol) in overrides {
+            let id = |settings: &Settings| {
+                settings.symbol_overrides.get(provider).and_then(|ids| ids.get(symbol)).cloned()
+            };
+            let field = format!("symbol_overrides.{provider}.{symbol}");
+            push_change(&mut changes, field, id(old), id(new));
+        }
+
+        let (old, new) = (&old.validation_policy, &new.validation_policy);
+        let policy_fields = [
+            (
+                "allow_future_days",
+                Some(old.allow_future_days.to_string()),
+                Some(new.allow_future_days.to_string()),
+            ),
+            (
+                "allow_oversell",
+                Some(old.allow_oversell.to_string()),
+                Some(new.allow_oversell.to_string()),
+            ),
+            (
+                "max_amount",
+                old.max_amount.map(|a| a.to_string()),
+                new.max_amount.map(|a| a.to_string()),
+            ),
+            (
+                "require_known_asset",
+                Some(old.require_known_asset.to_string()),
+                Some(new.require_known_asset.to_string()),
+            ),
+            (
+                "max_notes_len",
+                old.max_notes_len.map(|n| n.to_string()),
+                new.max_notes_len.map(|n| n.to_string()),
+            ),
+            (
+                "max_events",
+                old.max_events.map(|n| n.to_string()),
+                new.max_events.map(|n| n.to_string()),
+            ),
+        ];
+        for (field, before, after) in policy_fields {
+            push_change(&mut changes, format!("validation_policy.{field}"), before, after);
+        }
+        changes.sort_by(|a, b| a.field.cmp(&b.field));
+        changes
+    }
+}
+
+/// `url` with the password of its credentials, if any, replaced by `HIDDEN_VALUE`.
+fn redact_credentials(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some(HIDDEN_VALUE));
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+fn push_change(
+    changes: &mut Vec<FieldChange>,
+    field: String,
+    old: Option<String>,
+    new: Option<String>,
+) {
+    if old != new {
+        changes.push(FieldChange::new(field, old, new));
+    }
+}
@@ -1,16 +1,44 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use uuid::Uuid;
 
-use super::asset::Asset;
+use super::asset::{Asset, AssetType};
+use super::settings::Settings;
+use crate::errors::CoreError;
 
-/// Type of portfolio event.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Relative difference the parts of `Event::split` may add up to besides
+/// the event amount, for amounts that can't be written exactly.
+pub const SPLIT_TOLERANCE: f64 = 1e-9;
+
+/// Type of portfolio event. Ordered as declared (buys first).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum EventType {
     /// Buying / acquiring an asset
     Buy,
     /// Selling / disposing of an asset
     Sell,
+    /// Adding cash (fiat assets only); not counted as invested
+    Deposit,
+    /// Taking cash out (fiat assets only); not counted as returned
+    Withdrawal,
+    /// Units received as interest or staking rewards; counted as income,
+    /// not as invested
+    Interest,
+}
+
+impl EventType {
+    /// Whether the event adds units to the holding (buys, deposits and
+    /// interest).
+    pub fn increases_holding(&self) -> bool {
+        matches!(self, EventType::Buy | EventType::Deposit | EventType::Interest)
+    }
+
+    /// Whether the event moves cash in or out rather than trading
+    /// (deposits and withdrawals).
+    pub fn is_cash_flow(&self) -> bool {
+        matches!(self, EventType::Deposit | EventType::Withdrawal)
+    }
 }
 
 impl std::fmt::Display for EventType {
@@ -18,11 +46,17 @@ impl std::fmt::Display for EventType {
         match self {
             EventType::Buy => write!(f, "Buy"),
             EventType::Sell => write!(f, "Sell"),
+            EventType::Deposit => write!(f, "Deposit"),
+            EventType::Withdrawal => write!(f, "Withdrawal"),
+            EventType::Interest => write!(f, "Interest"),
         }
     }
 }
 
 /// Sort order for event listings.
+///
+/// Events an order ranks equal are sorted newest first, then by id, so
+/// every listing has one deterministic order (see `compare_by`).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EventSortOrder {
     /// Newest date first (default for display)
@@ -37,12 +71,313 @@ pub enum EventSortOrder {
     AssetAsc,
     /// Reverse alphabetical by asset symbol
     AssetDesc,
+    /// By event type: buys, sells, deposits, withdrawals, then interest
+    TypeAsc,
+    /// By event type in reverse: interest first, buys last
+    TypeDesc,
+    /// Events with notes first
+    NotesFirst,
+    /// Events without notes first
+    NotesLast,
+}
+
+impl EventSortOrder {
+    /// Compare two events by this order alone.
+    pub fn compare(&self, a: &Event, b: &Event) -> Ordering {
+        match self {
+            EventSortOrder::DateDesc => b.date.cmp(&a.date),
+            EventSortOrder::DateAsc => a.date.cmp(&b.date),
+            EventSortOrder::AmountDesc => b.amount.total_cmp(&a.amount),
+            EventSortOrder::AmountAsc => a.amount.total_cmp(&b.amount),
+            EventSortOrder::AssetAsc => a.asset.symbol.cmp(&b.asset.symbol),
+            EventSortOrder::AssetDesc => b.asset.symbol.cmp(&a.asset.symbol),
+            EventSortOrder::TypeAsc => a.event_type.cmp(&b.event_type),
+            EventSortOrder::TypeDesc => b.event_type.cmp(&a.event_type),
+            EventSortOrder::NotesFirst => b.notes.is_some().cmp(&a.notes.is_some()),
+            EventSortOrder::NotesLast => a.notes.is_some().cmp(&b.notes.is_some()),
+        }
+    }
+
+    /// Compare two events by `orders` in turn; events all of them rank
+    /// equal are compared by date (newest first), then by id.
+    pub fn compare_by(orders: &[EventSortOrder], a: &Event, b: &Event) -> Ordering {
+        orders
+            .iter()
+            .map(|order| order.compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.date.cmp(&a.date))
+            .then_with(|| a.id.cmp(&b.id))
+    }
+}
+
+/// `events` newest first, the order every event listing returns. Events of
+/// the same day are ordered by id (see `EventSortOrder::compare_by`), as
+/// their storage order depends on how they were added.
+pub(crate) fn newest_first<'a>(events: impl Iterator<Item = &'a Event>) -> Vec<&'a Event> {
+    let mut events: Vec<&Event> = events.collect();
+    events.sort_by(|a, b| EventSortOrder::compare_by(&[], a, b));
+    events
+}
+
+/// Actual events in an `EventSortOrder`, created by `SavingsTracker::events_iter`.
+///
+/// For the date orders the events are read from the (date-sorted) storage
+/// one day at a time, sorting only the events of a day by id, so iterating
+/// or skipping never collects all events. Other orders sort all events up
+/// front.
+pub(crate) struct EventsIter<'a> {
+    /// Stored events not yet buffered, in date order
+    rest: &'a [Event],
+    /// Events of the day at the front of the order, by id
+    front: std::vec::IntoIter<&'a Event>,
+    /// Events of the day at the back of the order, by id
+    back: std::vec::IntoIter<&'a Event>,
+    /// The front of the order is the end of `rest` (newest first)
+    descending: bool,
+}
+
+impl<'a> EventsIter<'a> {
+    /// Iterate the actual events of date-sorted `events` in `order`.
+    pub(crate) fn new(events: &'a [Event], order: &EventSortOrder) -> Self {
+        let descending = match order {
+            EventSortOrder::DateAsc => false,
+            EventSortOrder::DateDesc => true,
+            _ => {
+                let mut sorted: Vec<&Event> = events.iter().filter(|e| is_actual(e)).collect();
+                sorted.sort_by(|a, b| {
+                    EventSortOrder::compare_by(std::slice::from_ref(order), a, b)
+                });
+                return Self {
+                    rest: &[],
+                    front: sorted.into_iter(),
+                    back: Vec::new().into_iter(),
+                    descending: false,
+                };
+            }
+        };
+        Self {
+            rest: events,
+            front: Vec::new().into_iter(),
+            back: Vec::new().into_iter(),
+            descending,
+        }
+    }
+
+    /// Stored events of the first (or last) day left in `rest`, taken out of it.
+    fn take_day(&mut self, last: bool) -> &'a [Event] {
+        let rest = self.rest;
+        let (day, left) = if last {
+            let date = rest[rest.len() - 1].date;
+            let (left, day) = rest.split_at(rest.partition_point(|e| e.date < date));
+            (day, left)
+        } else {
+            let date = rest[0].date;
+            rest.split_at(rest.partition_point(|e| e.date <= date))
+        };
+        self.rest = left;
+        day
+    }
+
+    /// Actual events of `day`, by id (their order within a date order).
+    fn buffer(day: &'a [Event]) -> std::vec::IntoIter<&'a Event> {
+        let mut events: Vec<&Event> = day.iter().filter(|e| is_actual(e)).collect();
+        events.sort_by_key(|e| e.id);
+        events.into_iter()
+    }
+}
+
+fn is_actual(event: &Event) -> bool {
+    !event.is_deleted() && !event.is_planned
+}
+
+impl<'a> Iterator for EventsIter<'a> {
+    type Item = &'a Event;
+
+    fn next(&mut self) -> Option<&'a Event> {
+        loop {
+            if let Some(event) = self.front.next() {
+                return Some(event);
+            }
+            if self.rest.is_empty() {
+                return self.back.next();
+            }
+            let day = self.take_day(self.descending);
+            self.front = Self::buffer(day);
+        }
+    }
+
+    /// Skips whole days by counting their events, without buffering them.
+    fn nth(&mut self, mut n: usize) -> Option<&'a Event> {
+        loop {
+            let buffered = self.front.len();
+            if n < buffered {
+                return self.front.nth(n);
+            }
+            n -= buffered;
+            self.front = Vec::new().into_iter();
+            if self.rest.is_empty() {
+                return self.back.nth(n);
+            }
+            let day = self.take_day(self.descending);
+            let count = day.iter().filter(|e| is_actual(e)).count();
+            if n < count {
+                self.front = Self::buffer(day);
+                return self.front.nth(n);
+            }
+            n -= count;
+        }
+    }
+}
+
+impl DoubleEndedIterator for EventsIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.back.next_back() {
+                return Some(event);
+            }
+            if self.rest.is_empty() {
+                return self.front.next_back();
+            }
+            let day = self.take_day(!self.descending);
+            self.back = Self::buffer(day);
+        }
+    }
+}
+
+/// Period length of `SavingsTracker::get_events_grouped`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroupGranularity {
+    /// Weeks starting on `Settings::week_start`
+    Week,
+    Month,
+    /// Fiscal years starting in `Settings::fiscal_year_start_month`
+    Year,
+}
+
+impl GroupGranularity {
+    /// Label of the period containing `date`: the week's first day
+    /// ("2025-01-06"), "2025-01", or the fiscal year ("2025", named after
+    /// the calendar year it starts in).
+    pub fn period_label(&self, date: NaiveDate, settings: &Settings) -> String {
+        match self {
+            GroupGranularity::Week => settings.week_start_date(date).to_string(),
+            GroupGranularity::Month => date.format("%Y-%m").to_string(),
+            GroupGranularity::Year => settings.fiscal_year(date).to_string(),
+        }
+    }
+}
+
+/// Events of one week, month or year, for timeline listings.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventGroup<'a> {
+    /// "2025-01-06" for weeks, "2025-01" for months, "2025" for years
+    pub period_label: String,
+
+    /// Events of the period, newest first
+    pub events: Vec<&'a Event>,
+
+    /// Value of the period's buys in the requested currency
+    /// (`None` without a currency or when a price is missing)
+    pub buy_total: Option<f64>,
+
+    /// Number of events in the period
+    pub count: usize,
+}
+
+/// Criteria selecting events, e.g. for `SavingsTracker::bulk_update_events`.
+///
+/// Every criterion that is set must match; the default matches all actual
+/// (non-planned, non-deleted) events.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    /// Asset symbol (case-insensitive)
+    pub symbol: Option<String>,
+    pub asset_type: Option<AssetType>,
+    pub event_type: Option<EventType>,
+    /// First date included
+    pub from: Option<NaiveDate>,
+    /// Last date included
+    pub to: Option<NaiveDate>,
+    /// Also match planned events
+    pub include_planned: bool,
+    /// Also match deleted events
+    pub include_deleted: bool,
+}
+
+impl EventFilter {
+    /// Match the events of one asset symbol (case-insensitive).
+    pub fn for_symbol(symbol: &str) -> Self {
+        Self {
+            symbol: Some(symbol.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Whether `event` meets every criterion.
+    pub fn matches(&self, event: &Event) -> bool {
+        (self.include_planned || !event.is_planned)
+            && (self.include_deleted || !event.is_deleted())
+            && self
+                .symbol
+                .as_ref()
+                .is_none_or(|symbol| event.asset.symbol.eq_ignore_ascii_case(symbol.trim()))
+            && self.asset_type.as_ref().is_none_or(|t| &event.asset.asset_type == t)
+            && self.event_type.as_ref().is_none_or(|t| &event.event_type == t)
+            && self.from.is_none_or(|from| event.date >= from)
+            && self.to.is_none_or(|to| event.date <= to)
+    }
+}
+
+/// Changes applied to every event selected by an `EventFilter`.
+/// Fields left `None` are not changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventChanges {
+    /// Replace the asset (e.g., rename "GOLD" to "XAU")
+    pub new_asset: Option<Asset>,
+    /// Move the date by this many days (negative moves back)
+    pub shift_date_days: Option<i64>,
+    /// Multiply the amount by this positive factor
+    pub scale_amount: Option<f64>,
+    /// Set (`Some(Some(..))`) or clear (`Some(None)`) the notes
+    pub set_notes: Option<Option<String>>,
+}
+
+impl EventChanges {
+    /// Whether no change is set.
+    pub fn is_empty(&self) -> bool {
+        self.new_asset.is_none()
+            && self.shift_date_days.is_none()
+            && self.scale_amount.is_none()
+            && self.set_notes.is_none()
+    }
+}
+
+/// Unit price of an event's asset captured when the event was added.
+///
+/// Stored in both the asset's native quote currency (USD for crypto, metals
+/// and stocks; the currency itself for fiat) and the default currency at the
+/// time, so cost basis and FX gain don't depend on later price lookups.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedValue {
+    /// Native quote currency of the asset (e.g., "USD" for AAPL)
+    pub quote_currency: String,
+
+    /// Price of one unit in `quote_currency` on the event date
+    pub quote_unit_price: f64,
+
+    /// Default currency when the event was recorded (e.g., "PLN")
+    pub currency: String,
+
+    /// Price of one unit in `currency` on the event date
+    pub unit_price: f64,
 }
 
 /// A single buy/sell event in the portfolio.
 ///
-/// **Important**: Events do NOT store price. Price is fetched from APIs
-/// based on the event date, and cached locally for offline access.
+/// **Important**: Events do NOT require a price. Price is fetched from APIs
+/// based on the event date, and cached locally for offline access. Events
+/// added with `add_event_with_auto_price` also carry a `RecordedValue`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Event {
     /// Unique identifier
@@ -63,6 +398,21 @@ pub struct Event {
     /// Optional free-text notes (e.g., reason, exchange, memo)
     #[serde(default)]
     pub notes: Option<String>,
+
+    /// Unit price captured at insert time, if recorded
+    #[serde(default)]
+    pub recorded_value: Option<RecordedValue>,
+
+    /// A planned (future) event rather than an actual one. Planned events
+    /// are left out of holdings and analytics unless asked for.
+    #[serde(default)]
+    pub is_planned: bool,
+
+    /// When the event was deleted (moved to the trash), if it was. Deleted
+    /// events keep their place in the history but are left out of
+    /// holdings, analytics, charts and event listings.
+    #[serde(default, alias = "trashed_at")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl Event {
@@ -74,6 +424,97 @@ impl Event {
             amount,
             date,
             notes: None,
+            recorded_value: None,
+            is_planned: false,
+            deleted_at: None,
+        }
+    }
+
+    /// Check the fields that are valid or not on their own: the amount must
+    /// be a positive finite number and recorded unit prices finite and not
+    /// negative. Checks against the portfolio (oversells, future dates, the
+    /// validation policy) are done by `PortfolioService` when adding.
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if !self.amount.is_finite() {
+            return Err(CoreError::ValidationError(format!(
+                "Event amount {} is not a finite number",
+                self.amount
+            )));
+        }
+        if self.amount <= 0.0 {
+            return Err(CoreError::ValidationError(
+                "Event amount must be positive".into(),
+            ));
+        }
+        if let Some(recorded) = &self.recorded_value {
+            for price in [recorded.quote_unit_price, recorded.unit_price] {
+                if !price.is_finite() || price < 0.0 {
+                    return Err(CoreError::ValidationError(format!(
+                        "Invalid recorded unit price {price}: must be a non-negative number"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies of the event, with new ids, for each amount of `parts` (e.g.
+    /// lots of a buy). Everything else is kept: the recorded unit price
+    /// applies to every part, and each part gets the notes. The parts must
+    /// be positive and add up to `amount` (within `SPLIT_TOLERANCE` of it;
+    /// the last part absorbs the rounding difference).
+    pub fn split(&self, parts: &[f64]) -> Result<Vec<Event>, CoreError> {
+        if parts.len() < 2 {
+            return Err(CoreError::ValidationError(
+                "Splitting an event needs at least two parts".into(),
+            ));
+        }
+        if let Some(part) = parts.iter().find(|p| !p.is_finite() || **p <= 0.0) {
+            return Err(CoreError::ValidationError(format!(
+                "Invalid part {part}: every part must be a positive number"
+            )));
+        }
+        let total: f64 = parts.iter().sum();
+        if (total - self.amount).abs() > self.amount * SPLIT_TOLERANCE {
+            return Err(CoreError::ValidationError(format!(
+                "The parts add up to {total}, not the event amount {}",
+                self.amount
+            )));
+        }
+
+        let rest = &parts[..parts.len() - 1];
+        let last = self.amount - rest.iter().sum::<f64>();
+        Ok(rest
+            .iter()
+            .chain(std::iter::once(&last))
+            .map(|&amount| Event {
+                id: Uuid::new_v4(),
+                amount,
+                ..self.clone()
+            })
+            .collect())
+    }
+
+    /// Whether the event was deleted (see `deleted_at`).
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Change in units held caused by the event: `amount` for buys,
+    /// deposits and interest, `-amount` for sells and withdrawals.
+    pub fn holding_delta(&self) -> f64 {
+        if self.event_type.increases_holding() {
+            self.amount
+        } else {
+            -self.amount
+        }
+    }
+
+    /// Create a planned event, e.g. a contribution scheduled for next month.
+    pub fn planned(event_type: EventType, asset: Asset, amount: f64, date: NaiveDate) -> Self {
+        Self {
+            is_planned: true,
+            ..Self::new(event_type, asset, amount, date)
         }
     }
 
@@ -92,6 +533,30 @@ impl Event {
             amount,
             date,
             notes: Some(notes.into()),
+            recorded_value: None,
+            is_planned: false,
+            deleted_at: None,
+        }
+    }
+}
+
+/// `Event` as saved by the first release (file version 1).
+#[derive(Debug, Deserialize)]
+pub(crate) struct EventV1 {
+    pub id: Uuid,
+    pub event_type: EventType,
+    pub asset: Asset,
+    pub amount: f64,
+    pub date: NaiveDate,
+    pub notes: Option<String>,
+}
+
+impl From<EventV1> for Event {
+    fn from(event: EventV1) -> Self {
+        Event {
+            id: event.id,
+            notes: event.notes,
+            ..Event::new(event.event_type, event.asset, event.amount, event.date)
         }
     }
 }
@@ -0,0 +1,181 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::accrual::{self, AccrualRule};
+use super::asset::Asset;
+use super::event::Event;
+
+/// Options of `SavingsTracker::get_holdings_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HoldingsOptions {
+    /// Count planned events up to the date as if they had happened
+    pub include_planned: bool,
+}
+
+/// Running totals of every asset's holdings, for as-of lookups.
+///
+/// For each asset the index stores one entry per event date with the amount
+/// held after all events of that date (prefix sums in event order), so the
+/// holdings on any date are a binary search per asset away:
+/// O(assets × log events) instead of a pass over every event.
+///
+/// Planned events are only counted by an index built with `include_planned`;
+/// deleted events are never counted.
+///
+/// Assets with accrual rules grow between entries: an entry's amount
+/// includes the accrual up to its date, and a lookup applies the growth
+/// from the entry to the requested date.
+#[derive(Debug, Clone, Default)]
+pub struct HoldingsIndex {
+    /// `Portfolio` events generation the index was built for
+    generation: u64,
+    /// Number of events the index was built from (guards direct `events` edits)
+    event_count: usize,
+    totals: HashMap<Asset, Vec<(NaiveDate, f64)>>,
+    /// Accrual rules of the assets that have any
+    accruals: HashMap<Asset, Vec<AccrualRule>>,
+}
+
+impl HoldingsIndex {
+    /// Build the index from date-sorted events.
+    pub fn build(events: &[Event], generation: u64, include_planned: bool) -> Self {
+        Self::build_accruing(events, &[], generation, include_planned)
+    }
+
+    /// Build the index from date-sorted events, growing balances under
+    /// `accruals`.
+    pub fn build_accruing(
+        events: &[Event],
+        accruals: &[AccrualRule],
+        generation: u64,
+        include_planned: bool,
+    ) -> Self {
+        let mut rules: HashMap<Asset, Vec<AccrualRule>> = HashMap::new();
+        for rule in accruals {
+            rules.entry(rule.asset.clone()).or_default().push(rule.clone());
+        }
+        let mut totals: HashMap<Asset, Vec<(NaiveDate, f64)>> = HashMap::new();
+        let counted = |e: &&Event| !e.is_deleted() && (include_planned || !e.is_planned);
+        for event in events.iter().filter(counted) {
+            let series = totals.entry(event.asset.clone()).or_default();
+            let asset_rules = rules.get(&event.asset).map_or(&[][..], Vec::as_slice);
+            let held = Self::held_on(series, asset_rules, event.date);
+            let held = held + event.holding_delta();
+            match series.last_mut() {
+                Some(last) if last.0 == event.date => last.1 = held,
+                _ => series.push((event.date, held)),
+            }
+        }
+        Self {
+            generation,
+            event_count: events.len(),
+            totals,
+            accruals: rules,
+        }
+    }
+
+    /// Whether the index still describes events of this generation and count.
+    pub fn is_current(&self, generation: u64, event_count: usize) -> bool {
+        self.generation == generation && self.event_count == event_count
+    }
+
+    /// Amount of `asset` held on `date` (including that day's events).
+    pub fn amount_at(&self, asset: &Asset, date: NaiveDate) -> f64 {
+        self.totals
+            .get(asset)
+            .map_or(0.0, |series| Self::held_on(series, self.rules(asset), date))
+    }
+
+    /// Every asset held on `date` with a positive amount.
+    pub fn holdings_at(&self, date: NaiveDate) -> HashMap<Asset, f64> {
+        self.totals
+            .iter()
+            .map(|(asset, series)| (asset, Self::held_on(series, self.rules(asset), date)))
+            .filter(|(_, amount)| *amount > f64::EPSILON)
+            .map(|(asset, amount)| (asset.clone(), amount))
+            .collect()
+    }
+
+    /// Every asset with events on or before `date`, with the amount held
+    /// then: zero (never a float residue like 1e-17) once sold out. Assets
+    /// first transacted after `date` are absent.
+    pub fn positions_at(&self, date: NaiveDate) -> HashMap<Asset, f64> {
+        self.totals
+            .iter()
+            .filter(|(_, series)| series.first().is_some_and(|&(day, _)| day <= date))
+            .map(|(asset, series)| {
+                let amount = Self::held_on(series, self.rules(asset), date);
+                (asset.clone(), if amount > f64::EPSILON { amount } else { 0.0 })
+            })
+            .collect()
+    }
+
+    fn rules(&self, asset: &Asset) -> &[AccrualRule] {
+        self.accruals.get(asset).map_or(&[], Vec::as_slice)
+    }
+
+    /// Amount held at the end of `date`: the last entry up to then, grown
+    /// under `rules` (the asset's accrual rules) since its date.
+    fn held_on(series: &[(NaiveDate, f64)], rules: &[AccrualRule], date: NaiveDate) -> f64 {
+        match series.partition_point(|&(day, _)| day <= date) {
+            0 => 0.0,
+            idx => {
+                let (day, amount) = series[idx - 1];
+                match rules {
+                    [] => amount,
+                    rules => amount * accrual::growth(rules, day, date),
+                }
+            }
+        }
+    }
+}
+
+/// Lazily built `HoldingsIndex` kept next to the events it indexes.
+///
+/// Not serialized; a clone starts from the same index, which stays valid
+/// as long as the clone's events are unchanged.
+#[derive(Debug, Default)]
+pub struct HoldingsIndexCell(Mutex<Option<Arc<HoldingsIndex>>>);
+
+impl HoldingsIndexCell {
+    /// The cached index if it is current, otherwise a freshly built one.
+    ///
+    /// A cell always caches one kind of index: pass the same `include_planned`
+    /// on every call. Code changing the accrual rules must bump the events
+    /// generation.
+    pub fn get_or_build(
+        &self,
+        events: &[Event],
+        accruals: &[AccrualRule],
+        generation: u64,
+        include_planned: bool,
+    ) -> Arc<HoldingsIndex> {
+        let mut cached = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match cached.as_ref() {
+            Some(index) if index.is_current(generation, events.len()) => Arc::clone(index),
+            _ => {
+                let index = Arc::new(HoldingsIndex::build_accruing(
+                    events,
+                    accruals,
+                    generation,
+                    include_planned,
+                ));
+                *cached = Some(Arc::clone(&index));
+                index
+            }
+        }
+    }
+
+    fn cached(&self) -> Option<Arc<HoldingsIndex>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Clone for HoldingsIndexCell {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.cached()))
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// A monthly consumer price index value (e.g., 2024-01 → 308.417).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CpiPoint {
+    pub year: i32,
+
+    /// Month of the year (1–12)
+    pub month: u32,
+
+    /// Index value; only ratios between months are meaningful
+    pub index: f64,
+}
@@ -0,0 +1,84 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::asset::{Asset, AssetType};
+
+/// A problem `SavingsTracker::check_integrity` found, e.g. in a hand-edited
+/// file that bypassed event validation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IntegrityIssue {
+    /// Replaying the events in date order, the holding of `asset` drops to
+    /// `held` (below zero) after the sell or withdrawal `event_id`
+    NegativeHolding {
+        event_id: Uuid,
+        asset: Asset,
+        date: NaiveDate,
+        held: f64,
+    },
+    /// `count` events that are not deleted share one id
+    DuplicateEventId { event_id: Uuid, count: usize },
+    /// A deleted event (in the trash) has the id of an event that is not
+    DeletedIdCollision { event_id: Uuid },
+    /// An event's amount is NaN, infinite, zero or negative
+    InvalidAmount { event_id: Uuid, amount: f64 },
+    /// A cached price is NaN or infinite
+    NonFinitePrice {
+        symbol: String,
+        currency: String,
+        asset_type: AssetType,
+        date: NaiveDate,
+    },
+}
+
+impl std::fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityIssue::NegativeHolding { event_id, asset, date, held } => write!(
+                f,
+                "Event {event_id} leaves {held} {} held on {date}",
+                asset.symbol
+            ),
+            IntegrityIssue::DuplicateEventId { event_id, count } => {
+                write!(f, "{count} events share the id {event_id}")
+            }
+            IntegrityIssue::DeletedIdCollision { event_id } => {
+                write!(f, "A deleted event reuses the id {event_id} of an event in use")
+            }
+            IntegrityIssue::InvalidAmount { event_id, amount } => {
+                write!(f, "Event {event_id} has an invalid amount {amount}")
+            }
+            IntegrityIssue::NonFinitePrice { symbol, currency, asset_type, date } => write!(
+                f,
+                "The cached {asset_type} price of {symbol} in {currency} on {date} is not a number"
+            ),
+        }
+    }
+}
+
+/// Result of an integrity check: every problem found, in the order checked
+/// (ids, amounts, holdings, prices).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Whether no problem was found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// How `SavingsTracker::repair_integrity` fixes what it finds.
+///
+/// Duplicate ids are always replaced with new ones (the first event keeps
+/// its id), events with invalid amounts are deleted and non-finite cached
+/// prices are removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepairOptions {
+    /// Reduce sells and withdrawals that exceed the holding to the amount
+    /// held, instead of deleting them
+    pub clamp_overdraws: bool,
+}
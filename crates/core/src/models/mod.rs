@@ -1,7 +1,29 @@
+pub mod accrual;
+pub mod alert;
 pub mod analytics;
 pub mod asset;
 pub mod chart;
+pub mod corporate_action;
+pub mod coverage;
+pub mod diagnostics;
+pub mod diff;
 pub mod event;
+pub mod holdings;
+pub mod inflation;
+pub mod integrity;
 pub mod portfolio;
+pub mod preview;
 pub mod price;
+pub mod progress;
+pub mod reminder;
+pub mod search;
 pub mod settings;
+pub mod simulation;
+pub mod snapshot;
+pub(crate) mod sorted;
+pub mod sync;
+pub mod tax;
+pub mod usage;
+pub mod warning;
+pub mod valuation;
+pub mod watchlist;
@@ -1,17 +1,37 @@
+use chrono::{Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
 
-use super::event::Event;
-use super::price::PriceCache;
-use super::settings::Settings;
+use super::accrual::AccrualRule;
+use super::alert::Alert;
+use super::asset::{Asset, AssetMetadata, AssetMetadataKey, AssetType};
+use super::corporate_action::{self, CorporateAction, ValuedEvents, ValuedEventsCell};
+use super::event::{Event, EventV1};
+use super::holdings::{HoldingsIndex, HoldingsIndexCell};
+use super::price::{PriceCache, PriceCacheV1};
+use super::reminder::Reminder;
+use super::search::{SearchIndex, SearchIndexCell};
+use super::settings::{Settings, SettingsV1};
+use super::snapshot::ValueSnapshot;
+use super::usage::ProviderUsage;
 
 /// The main data container. Everything in here gets serialized,
 /// encrypted, and saved to the portable .svtk file.
 ///
 /// Contains: events (buy/sell history), user settings, and the price cache
 /// (so historical prices are available offline).
+///
+/// Saved as MessagePack with named fields, so a missing field takes its
+/// default: new fields of the persisted models need `#[serde(default)]`
+/// (or a container default) to keep older files loading.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Portfolio {
-    /// All buy/sell events in the portfolio
+    /// All buy/sell events in the portfolio, in date order, including
+    /// deleted ones (see `Event::deleted_at`)
     pub events: Vec<Event>,
 
     /// User settings (default currency, API keys, etc.)
@@ -21,9 +41,349 @@ pub struct Portfolio {
     /// Once a historical price is fetched, it's stored here permanently.
     pub price_cache: PriceCache,
 
-    /// Events that have been removed but can be restored (undo support).
+    /// Trash of files saved before deleted events were kept in `events`.
+    /// Moved there by `migrate_legacy_trash` on load; otherwise empty.
     #[serde(default)]
     pub trash: Vec<Event>,
+
+    /// Recorded portfolio values, sorted by date (at most one per date).
+    #[serde(default)]
+    pub snapshots: Vec<ValueSnapshot>,
+
+    /// Price alert definitions, evaluated by `check_alerts()`.
+    #[serde(default)]
+    pub alerts: Vec<Alert>,
+
+    /// Per-asset metadata (e.g., annual yield), keyed by uppercase symbol
+    /// and asset type.
+    #[serde(default, rename = "typed_asset_metadata", with = "super::sorted::keyed")]
+    pub asset_metadata: HashMap<AssetMetadataKey, AssetMetadata>,
+
+    /// Metadata of files saved before it was keyed by asset type, by
+    /// uppercase symbol. Moved to `asset_metadata` by
+    /// `migrate_legacy_asset_metadata` on load; otherwise empty.
+    #[serde(default, rename = "asset_metadata")]
+    pub legacy_asset_metadata: HashMap<String, AssetMetadata>,
+
+    /// Assets whose prices are tracked without being held. They don't
+    /// affect holdings or analytics.
+    #[serde(default)]
+    pub watchlist: Vec<Asset>,
+
+    /// Requests sent to each price provider, keyed by lowercase provider
+    /// name. Kept up to date by `SavingsTracker` when saving.
+    #[serde(default, serialize_with = "super::sorted::map")]
+    pub api_usage: HashMap<String, ProviderUsage>,
+
+    /// Stock splits and symbol changes, sorted by effective date. Applied to
+    /// the events by holdings and valuation (see `valued_events`).
+    #[serde(default)]
+    pub corporate_actions: Vec<CorporateAction>,
+
+    /// Percentage growth of asset balances (e.g., staking), applied by the
+    /// holdings. Rules of one asset never overlap.
+    #[serde(default)]
+    pub accrual_rules: Vec<AccrualRule>,
+
+    /// Review reminders of events, at most one per event.
+    #[serde(default)]
+    pub reminders: Vec<Reminder>,
+
+    /// Bumped on every change to `events` (see `mark_events_changed`). Not saved.
+    #[serde(skip)]
+    events_generation: u64,
+
+    /// Holdings index of `events`, rebuilt on first use after a change. Not saved.
+    #[serde(skip)]
+    holdings_index: HoldingsIndexCell,
+
+    /// Same as `holdings_index`, counting planned events too. Not saved.
+    #[serde(skip)]
+    planned_holdings_index: HoldingsIndexCell,
+
+    /// Same as `holdings_index`, without the accrual rules' growth. Not saved.
+    #[serde(skip)]
+    unaccrued_holdings_index: HoldingsIndexCell,
+
+    /// Search index of `events`, updated on first use after a change. Not saved.
+    #[serde(skip)]
+    search_index: SearchIndexCell,
+
+    /// `events` adjusted for `corporate_actions`, rebuilt on first use after
+    /// a change. Not saved.
+    #[serde(skip)]
+    valued_events: ValuedEventsCell,
+}
+
+impl Portfolio {
+    /// Record that `events` changed, so data derived from them (the holdings
+    /// and search indexes) is rebuilt. `PortfolioService` calls this after every mutation;
+    /// code editing `events` directly must call it too (appending and
+    /// removing events is also detected by the event count).
+    pub fn mark_events_changed(&mut self) {
+        self.events_generation = self.events_generation.wrapping_add(1);
+    }
+
+    /// Events that are not deleted (actual and planned), in date order.
+    pub fn active_events(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.events.iter().filter(|e| !e.is_deleted())
+    }
+
+    /// Events that actually happened (not planned, not deleted), in date order.
+    pub fn actual_events(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.active_events().filter(|e| !e.is_planned)
+    }
+
+    /// Planned events (not deleted), in date order.
+    pub fn planned_events(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.active_events().filter(|e| e.is_planned)
+    }
+
+    /// Deleted events, in date order.
+    pub fn deleted_events(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.events.iter().filter(|e| e.is_deleted())
+    }
+
+    /// Move the entries of a legacy `trash` into `events` as deleted events.
+    /// Entries without a trash time are marked deleted now, keeping their
+    /// trash order. Returns the number of events moved.
+    pub fn migrate_legacy_trash(&mut self) -> usize {
+        let trash = std::mem::take(&mut self.trash);
+        let count = trash.len();
+        let now = Utc::now();
+        for (i, mut event) in trash.into_iter().enumerate() {
+            // Later trash entries were trashed later
+            let offset = Duration::microseconds((count - i) as i64);
+            event.deleted_at = event.deleted_at.or(Some(now - offset));
+            let pos = self.events.partition_point(|e| e.date <= event.date);
+            self.events.insert(pos, event);
+        }
+        if count > 0 {
+            self.mark_events_changed();
+        }
+        count
+    }
+
+    /// Move the entries of `legacy_asset_metadata` into `asset_metadata`,
+    /// under each asset type the symbol has in the events (deleted ones
+    /// included), the watchlist or the price cache. Entries of symbols found
+    /// nowhere are dropped. Returns the number of entries dropped.
+    pub fn migrate_legacy_asset_metadata(&mut self) -> usize {
+        let legacy = std::mem::take(&mut self.legacy_asset_metadata);
+        let mut dropped = 0;
+        for (symbol, metadata) in legacy {
+            let types: HashSet<&AssetType> = self
+                .events
+                .iter()
+                .map(|e| &e.asset)
+                .chain(&self.watchlist)
+                .filter(|asset| asset.symbol.eq_ignore_ascii_case(&symbol))
+                .map(|asset| &asset.asset_type)
+                .chain(
+                    self.price_cache
+                        .entries
+                        .keys()
+                        .filter(|(cached, _, _)| *cached == symbol)
+                        .map(|(_, _, asset_type)| asset_type),
+                )
+                .collect();
+            if types.is_empty() {
+                dropped += 1;
+            }
+            let keys: Vec<AssetMetadataKey> =
+                types.into_iter().map(|asset_type| (symbol.clone(), asset_type.clone())).collect();
+            for key in keys {
+                self.asset_metadata.entry(key).or_insert_with(|| metadata.clone());
+            }
+        }
+        dropped
+    }
+
+    /// Counter bumped by `mark_events_changed`.
+    pub fn events_generation(&self) -> u64 {
+        self.events_generation
+    }
+
+    /// Holdings index of the current actual (non-planned) events, built once
+    /// per events generation. Amounts and assets are adjusted for corporate
+    /// actions (see `valued_events`), and balances grow under the accrual
+    /// rules. Code changing `accrual_rules` must call `mark_events_changed`.
+    pub fn holdings_index(&self) -> Arc<HoldingsIndex> {
+        self.holdings_index.get_or_build(
+            self.valued_events().all(),
+            &self.accrual_rules,
+            self.events_generation,
+            false,
+        )
+    }
+
+    /// Holdings index of all current events, planned ones included.
+    pub fn planned_holdings_index(&self) -> Arc<HoldingsIndex> {
+        self.planned_holdings_index.get_or_build(
+            self.valued_events().all(),
+            &self.accrual_rules,
+            self.events_generation,
+            true,
+        )
+    }
+
+    /// Factor the accrual rules of `asset` grow a balance held at the end of
+    /// `from` by, until the end of `to` (1.0 without rules).
+    pub fn accrual_growth(&self, asset: &Asset, from: NaiveDate, to: NaiveDate) -> f64 {
+        self.accrual_rules
+            .iter()
+            .filter(|rule| rule.asset == *asset)
+            .map(|rule| rule.growth(from, to))
+            .product()
+    }
+
+    /// Units of each asset accrued under the accrual rules up to `date`
+    /// and not yet turned into `Interest` events (actual events only).
+    pub fn accrued_amounts(&self, date: NaiveDate) -> HashMap<Asset, f64> {
+        if self.accrual_rules.is_empty() {
+            return HashMap::new();
+        }
+        let accruing = self.holdings_index();
+        let plain = self.unaccrued_holdings_index.get_or_build(
+            self.valued_events().all(),
+            &[],
+            self.events_generation,
+            false,
+        );
+        let assets: HashSet<&Asset> = self.accrual_rules.iter().map(|rule| &rule.asset).collect();
+        assets
+            .into_iter()
+            .map(|asset| {
+                let accrued = accruing.amount_at(asset, date) - plain.amount_at(asset, date);
+                (asset.clone(), accrued)
+            })
+            .filter(|(_, accrued)| *accrued > f64::EPSILON)
+            .collect()
+    }
+
+    /// `events` as holdings and valuation count them: with the corporate
+    /// actions applied (see `CorporateAction`), in the same order. The stored
+    /// events themselves when there are no corporate actions; otherwise
+    /// adjusted copies, made once per events generation. Code changing
+    /// `corporate_actions` must call `mark_events_changed`.
+    pub fn valued_events(&self) -> ValuedEvents<'_> {
+        if self.corporate_actions.is_empty() {
+            return ValuedEvents::Stored(&self.events);
+        }
+        ValuedEvents::Adjusted(self.valued_events.get_or_build(
+            &self.events,
+            &self.corporate_actions,
+            self.events_generation,
+        ))
+    }
+
+    /// `event` as holdings and valuation count it (see `valued_events`).
+    pub fn valued_event<'a>(&self, event: &'a Event) -> Cow<'a, Event> {
+        corporate_action::adjust_event(&self.corporate_actions, event)
+    }
+
+    /// The symbol `symbol` is quoted under today, after the recorded renames.
+    pub fn current_symbol(&self, symbol: &str) -> String {
+        corporate_action::current_symbol(&self.corporate_actions, &symbol.to_uppercase())
+    }
+
+    /// Search index of all events (positions in `events` order), updated
+    /// once per events generation.
+    pub fn search_index(&self) -> Arc<SearchIndex> {
+        self.search_index
+            .get_or_update(&self.events, self.events_generation)
+    }
+
+    /// A copy with an empty price cache, for what-if calculations on the
+    /// events that don't need the (possibly large) cache or bring their own.
+    pub fn clone_without_prices(&self) -> Self {
+        Self {
+            events: self.events.clone(),
+            settings: self.settings.clone(),
+            price_cache: PriceCache::new(),
+            trash: self.trash.clone(),
+            snapshots: self.snapshots.clone(),
+            alerts: self.alerts.clone(),
+            asset_metadata: self.asset_metadata.clone(),
+            legacy_asset_metadata: self.legacy_asset_metadata.clone(),
+            watchlist: self.watchlist.clone(),
+            api_usage: self.api_usage.clone(),
+            corporate_actions: self.corporate_actions.clone(),
+            accrual_rules: self.accrual_rules.clone(),
+            reminders: self.reminders.clone(),
+            events_generation: self.events_generation,
+            holdings_index: self.holdings_index.clone(),
+            planned_holdings_index: self.planned_holdings_index.clone(),
+            unaccrued_holdings_index: self.unaccrued_holdings_index.clone(),
+            search_index: self.search_index.clone(),
+            valued_events: self.valued_events.clone(),
+        }
+    }
+
+    /// Hash of the logical content: events, settings, snapshots, alerts,
+    /// asset metadata, watchlist, corporate actions, accrual rules and
+    /// reminders. The price cache and provider request counts are left
+    /// out, so refreshing prices doesn't change it.
+    ///
+    /// Stable across runs, platforms and map insertion order (maps are
+    /// hashed with their keys in order), so it can be compared with the
+    /// hash of a file saved elsewhere.
+    #[must_use]
+    pub fn content_hash(&self) -> u64 {
+        let content = PortfolioContent {
+            events: &self.events,
+            settings: &self.settings,
+            snapshots: &self.snapshots,
+            alerts: &self.alerts,
+            asset_metadata: self.asset_metadata.iter().collect(),
+            watchlist: &self.watchlist,
+            corporate_actions: &self.corporate_actions,
+            accrual_rules: &self.accrual_rules,
+            reminders: &self.reminders,
+        };
+        let mut hasher = Fnv1a::default();
+        // Writing to the hasher can't fail, and neither can serializing the models
+        let _ = bincode::serialize_into(&mut hasher, &content);
+        hasher.0
+    }
+}
+
+/// What `Portfolio::content_hash` covers.
+#[derive(Serialize)]
+struct PortfolioContent<'a> {
+    events: &'a [Event],
+    settings: &'a Settings,
+    snapshots: &'a [ValueSnapshot],
+    alerts: &'a [Alert],
+    /// In key order
+    asset_metadata: BTreeMap<&'a AssetMetadataKey, &'a AssetMetadata>,
+    watchlist: &'a [Asset],
+    corporate_actions: &'a [CorporateAction],
+    accrual_rules: &'a [AccrualRule],
+    reminders: &'a [Reminder],
+}
+
+/// FNV-1a over the bytes written: stable across runs, platforms and Rust
+/// versions (unlike `DefaultHasher`).
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl std::io::Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &byte in buf {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 impl Default for Portfolio {
@@ -33,6 +393,119 @@ impl Default for Portfolio {
             settings: Settings::default(),
             price_cache: PriceCache::new(),
             trash: Vec::new(),
+            snapshots: Vec::new(),
+            alerts: Vec::new(),
+            asset_metadata: HashMap::new(),
+            legacy_asset_metadata: HashMap::new(),
+            watchlist: Vec::new(),
+            api_usage: HashMap::new(),
+            corporate_actions: Vec::new(),
+            accrual_rules: Vec::new(),
+            reminders: Vec::new(),
+            events_generation: 0,
+            holdings_index: HoldingsIndexCell::default(),
+            planned_holdings_index: HoldingsIndexCell::default(),
+            unaccrued_holdings_index: HoldingsIndexCell::default(),
+            search_index: SearchIndexCell::default(),
+            valued_events: ValuedEventsCell::default(),
+        }
+    }
+}
+
+/// `Portfolio` as saved by the first release (file version 1).
+///
+/// Bincode has no field names, so the layout is frozen here, down to the
+/// events, settings and cached prices, whatever is added to the current
+/// structs. Read by `StorageManager` and converted with `migrate`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct PortfolioV1 {
+    pub events: Vec<EventV1>,
+    pub settings: SettingsV1,
+    pub price_cache: PriceCacheV1,
+    pub trash: Vec<EventV1>,
+}
+
+impl PortfolioV1 {
+    /// Convert to the current `Portfolio`; everything added since the first
+    /// release takes its default.
+    ///
+    /// Each cached series gets the asset type of the portfolio's assets
+    /// (events and trash) with its symbol, or `Fiat` for a currency the
+    /// cache quotes in (or the default and pivot currencies). Series whose
+    /// symbol matches none, or several types, are dropped. Returns the
+    /// portfolio and the number of dropped series.
+    pub fn migrate(self) -> (Portfolio, usize) {
+        let events: Vec<Event> = self.events.into_iter().map(Event::from).collect();
+        let trash: Vec<Event> = self.trash.into_iter().map(Event::from).collect();
+        let settings = Settings::from(self.settings);
+
+        let mut types: HashMap<String, HashSet<AssetType>> = HashMap::new();
+        for asset in events.iter().chain(&trash).map(|e| &e.asset) {
+            let symbol = asset.symbol.to_uppercase();
+            types.entry(symbol).or_default().insert(asset.asset_type.clone());
+        }
+        let currencies = self
+            .price_cache
+            .entries
+            .keys()
+            .map(|(_, currency)| currency)
+            .chain(std::iter::once(&settings.default_currency))
+            .chain(&settings.pivot_currencies);
+        for currency in currencies {
+            types.entry(currency.to_uppercase()).or_default().insert(AssetType::Fiat);
         }
+        let (price_cache, dropped) = self.price_cache.migrate(|symbol| {
+            let candidates = types.get(symbol)?;
+            match candidates.len() {
+                1 => candidates.iter().next().cloned(),
+                _ => None,
+            }
+        });
+
+        let portfolio = Portfolio {
+            events,
+            settings,
+            price_cache,
+            trash,
+            ..Portfolio::default()
+        };
+        (portfolio, dropped)
     }
 }
+
+/// How `SavingsTracker::merge_from_bytes` combines another portfolio into this one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeOptions {
+    /// Skip incoming events identical to an existing one (same type, asset,
+    /// amount, date and planned flag), e.g. when both files share history.
+    pub dedupe: bool,
+
+    /// Text put in front of the notes of every incoming event
+    /// (e.g., "Anna:"), so merged events stay recognizable.
+    pub prefix_notes: Option<String>,
+}
+
+/// What happens to the ids of imported events
+/// (`SavingsTracker::import_events_from_json_with`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportIdPolicy {
+    /// Keep an incoming id unless an event (including deleted ones) or an
+    /// event imported before it already has it; then generate a new one
+    #[default]
+    KeepIfUnique,
+    /// Give every imported event a new id
+    AlwaysRegenerate,
+    /// Fail the import if any incoming id is already taken
+    FailOnCollision,
+}
+
+/// Outcome of an event import.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Number of events imported
+    pub imported: usize,
+
+    /// `(incoming id, new id)` of every event whose id was changed, in
+    /// import order, so references to the incoming ids can be updated
+    pub remapped_ids: Vec<(Uuid, Uuid)>,
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use super::analytics::PortfolioSummary;
+use super::asset::Asset;
+use super::event::Event;
+
+/// Amount of one asset held before and after a previewed event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoldingChange {
+    pub asset: Asset,
+
+    /// Amount held on the event date without the event
+    pub before: f64,
+
+    /// Amount held on the event date with the event
+    pub after: f64,
+}
+
+impl HoldingChange {
+    /// `after - before`
+    pub fn change(&self) -> f64 {
+        self.after - self.before
+    }
+}
+
+/// What adding an event would do to the holdings
+/// (see `SavingsTracker::preview_event`). The portfolio is not changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPreview {
+    /// The event as it would be added
+    pub event: Event,
+
+    /// Every asset held on the event date before or after the event,
+    /// sorted by symbol
+    pub holdings: Vec<HoldingChange>,
+}
+
+impl EventPreview {
+    /// Holding change of the event's asset.
+    pub fn asset_change(&self) -> Option<&HoldingChange> {
+        self.holdings.iter().find(|h| h.asset == self.event.asset)
+    }
+}
+
+/// What adding an event would do to the portfolio summary on the event date
+/// (see `SavingsTracker::preview_event_impact`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventImpact {
+    pub preview: EventPreview,
+
+    /// Summary without the event
+    pub before: PortfolioSummary,
+
+    /// Summary with the event
+    pub after: PortfolioSummary,
+
+    /// after.total_value - before.total_value
+    pub value_change: f64,
+
+    /// Gain realized by a sell at the average cost basis:
+    /// amount × (unit price - `cost_basis_per_unit`). 0 for other events.
+    pub realized_gain: f64,
+
+    /// Allocation of the event's asset without the event, in percent
+    pub allocation_before_pct: f64,
+
+    /// Allocation of the event's asset with the event, in percent
+    pub allocation_after_pct: f64,
+}
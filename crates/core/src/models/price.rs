@@ -1,6 +1,13 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use super::asset::{AssetType, MetalUnit};
+use super::coverage::{is_weekend, CacheCoverage, DateSpan};
+
+/// Weekdays between two cached stock quotes taken for market holidays
+/// rather than missing prices.
+const MARKET_HOLIDAY_DAYS: usize = 2;
 
 /// A single price data point (date → price).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -9,8 +16,79 @@ pub struct PricePoint {
     pub price: f64,
 }
 
-/// Cache key: (asset_symbol, target_currency) e.g., ("BTC", "USD")
-pub type PriceCacheKey = (String, String);
+/// What stretch of time a cached price stands for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PriceResolution {
+    /// The price of its own day
+    #[default]
+    Daily,
+
+    /// The close of its month, which also stands in for the days of that
+    /// month without a price (see `PriceCache::get_month_price`)
+    Monthly,
+}
+
+/// `PriceSource::source` of prices entered by hand.
+pub const MANUAL_SOURCE: &str = "manual";
+
+/// Where a cached price came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceSource {
+    /// Name of the provider that quoted the price, or "manual" for prices
+    /// entered with `SavingsTracker::set_cached_price`
+    pub source: String,
+
+    /// When the price was fetched (or entered)
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl PriceSource {
+    /// Source `source`, fetched now.
+    pub fn now(source: impl Into<String>) -> Self {
+        Self { source: source.into(), fetched_at: Utc::now() }
+    }
+}
+
+/// A cached price with everything the cache knows about it
+/// (see `PriceCache::get_entry`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedPrice {
+    pub date: NaiveDate,
+    pub price: f64,
+
+    /// Provider name or "manual"; `None` for derived and carried prices and
+    /// for prices cached before sources were recorded
+    pub source: Option<String>,
+
+    /// When the price was fetched; `None` where `source` is
+    pub fetched_at: Option<DateTime<Utc>>,
+
+    /// Triangulated through a pivot currency (see `PriceCache::is_derived`)
+    pub derived: bool,
+
+    /// Carried forward from an earlier day (see `PriceCache::is_carried`)
+    pub carried: bool,
+
+    /// An intraday quote taken before the day's close was known (see
+    /// `PriceCache::is_provisional`)
+    #[serde(default)]
+    pub provisional: bool,
+
+    /// Whether the price is also its month's close
+    #[serde(default)]
+    pub resolution: PriceResolution,
+}
+
+/// Cache key: (asset_symbol, target_currency, asset_type) e.g., ("BTC", "USD", Crypto).
+///
+/// The asset type keeps assets sharing a ticker (e.g., a stock and a crypto
+/// both called "GLD") apart.
+pub type PriceCacheKey = (String, String, AssetType);
+
+/// Key of `symbol` in `currency`, normalized to uppercase.
+fn cache_key(symbol: &str, currency: &str, asset_type: &AssetType) -> PriceCacheKey {
+    (symbol.to_uppercase(), currency.to_uppercase(), asset_type.clone())
+}
 
 /// Local cache of historical and current price data.
 ///
@@ -19,13 +97,50 @@ pub type PriceCacheKey = (String, String);
 /// - The app works fully offline with cached data.
 /// - Today's price can be refreshed when online.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PriceCache {
-    /// Historical price entries: (symbol, currency) → sorted Vec of PricePoints
+    /// Historical price entries: (symbol, currency, type) → sorted Vec of PricePoints
+    #[serde(with = "super::sorted::keyed")]
     pub entries: HashMap<PriceCacheKey, Vec<PricePoint>>,
 
-    /// Tracks when we last refreshed "today's" price for each (symbol, currency).
+    /// Tracks when we last refreshed "today's" price for each (symbol, currency, type).
     /// Used to avoid redundant API calls within the same day.
+    #[serde(with = "super::sorted::keyed")]
     pub last_updated: HashMap<PriceCacheKey, NaiveDate>,
+
+    /// Prices derived by triangulating through a pivot currency rather than
+    /// quoted by a provider: (key, date). A provider quote for the same
+    /// point replaces the derived value and clears the flag.
+    #[serde(default, serialize_with = "super::sorted::set")]
+    pub derived: HashSet<(PriceCacheKey, NaiveDate)>,
+
+    /// Prices carried forward from the previous quote over days without one
+    /// (weekends, holidays): (key, date). Unlike derived prices they are
+    /// used as-is; a provider quote for the same day clears the flag.
+    #[serde(default, serialize_with = "super::sorted::set")]
+    pub carried: HashSet<(PriceCacheKey, NaiveDate)>,
+
+    /// Unit of each cached metal price series. Series without an entry
+    /// are per troy ounce (the unit providers quote in).
+    #[serde(default, with = "super::sorted::keyed")]
+    pub metal_units: HashMap<PriceCacheKey, MetalUnit>,
+
+    /// Source of each quoted price: (key, date). Replacing a price drops
+    /// its source; derived and carried prices have none.
+    #[serde(default, with = "super::sorted::keyed")]
+    pub sources: HashMap<(PriceCacheKey, NaiveDate), PriceSource>,
+
+    /// Month closes, each standing in for the days of its month without a
+    /// price (e.g., for old history): (key, date). Other prices are daily.
+    /// Replacing a close with a daily price clears the flag.
+    #[serde(default, serialize_with = "super::sorted::set")]
+    pub monthly: HashSet<(PriceCacheKey, NaiveDate)>,
+
+    /// Current quotes cached for a day whose close was not known yet:
+    /// (key, date) → the last day the close was asked for and none came.
+    /// Replacing the price clears the entry.
+    #[serde(default, with = "super::sorted::keyed")]
+    pub provisional: HashMap<(PriceCacheKey, NaiveDate), Option<NaiveDate>>,
 }
 
 impl PriceCache {
@@ -33,11 +148,16 @@ impl PriceCache {
         Self::default()
     }
 
-    /// Get a cached price for a specific (symbol, currency, date).
+    /// Get a cached price for a specific (symbol, currency, date) of an asset type.
     /// Returns None if not cached. Uses binary search (O(log n)).
-    pub fn get_price(&self, symbol: &str, currency: &str, date: NaiveDate) -> Option<f64> {
-        let key = (symbol.to_uppercase(), currency.to_uppercase());
-        let entries = self.entries.get(&key)?;
+    pub fn get_price(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Option<f64> {
+        let entries = self.entries.get(&cache_key(symbol, currency, asset_type))?;
         entries
             .binary_search_by_key(&date, |p| p.date)
             .ok()
@@ -46,9 +166,33 @@ impl PriceCache {
 
     /// Insert or update a price point in the cache.
     /// Maintains sorted order by date using binary search (O(log n) insertion).
-    pub fn set_price(&mut self, symbol: &str, currency: &str, date: NaiveDate, price: f64) {
-        let key = (symbol.to_uppercase(), currency.to_uppercase());
-        let entries = self.entries.entry(key.clone()).or_default();
+    /// NaN and infinite prices are ignored.
+    pub fn set_price(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        price: f64,
+        asset_type: &AssetType,
+    ) {
+        if !price.is_finite() {
+            return;
+        }
+        let key = cache_key(symbol, currency, asset_type);
+        if !self.derived.is_empty()
+            || !self.carried.is_empty()
+            || !self.sources.is_empty()
+            || !self.monthly.is_empty()
+            || !self.provisional.is_empty()
+        {
+            let point = (key.clone(), date);
+            self.derived.remove(&point);
+            self.carried.remove(&point);
+            self.sources.remove(&point);
+            self.monthly.remove(&point);
+            self.provisional.remove(&point);
+        }
+        let entries = self.entries.entry(key).or_default();
 
         // Binary search for existing entry or insertion point
         match entries.binary_search_by_key(&date, |p| p.date) {
@@ -63,23 +207,364 @@ impl PriceCache {
         }
     }
 
+    /// Record where the cached price for (symbol, currency, date) came from.
+    /// Call after `set_price`, which drops the previous source.
+    pub fn set_source(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        source: PriceSource,
+        asset_type: &AssetType,
+    ) {
+        self.sources.insert((cache_key(symbol, currency, asset_type), date), source);
+    }
+
+    /// Where the cached price for (symbol, currency, date) came from, if recorded.
+    pub fn source(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Option<&PriceSource> {
+        if self.sources.is_empty() {
+            return None;
+        }
+        self.sources.get(&(cache_key(symbol, currency, asset_type), date))
+    }
+
+    /// The cached price for (symbol, currency, date) with its source and flags.
+    pub fn get_entry(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Option<CachedPrice> {
+        let price = self.get_price(symbol, currency, date, asset_type)?;
+        let source = self.source(symbol, currency, date, asset_type);
+        Some(CachedPrice {
+            date,
+            price,
+            source: source.map(|s| s.source.clone()),
+            fetched_at: source.map(|s| s.fetched_at),
+            derived: self.is_derived(symbol, currency, date, asset_type),
+            carried: self.is_carried(symbol, currency, date, asset_type),
+            provisional: self.is_provisional(symbol, currency, date, asset_type),
+            resolution: self.resolution(symbol, currency, date, asset_type),
+        })
+    }
+
+    /// Whether the cached price for (symbol, currency, date) is a provider's
+    /// current quote, fetched before the day's close was known. Once the day
+    /// is over, `PriceService` asks for the close and only falls back to
+    /// this price when none comes.
+    pub fn is_provisional(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> bool {
+        !self.provisional.is_empty()
+            && self.provisional.contains_key(&(cache_key(symbol, currency, asset_type), date))
+    }
+
+    /// Flag the cached price for (symbol, currency, date) as provisional.
+    /// Call after `set_price`, which clears the flag.
+    pub fn mark_provisional(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) {
+        self.provisional.insert((cache_key(symbol, currency, asset_type), date), None);
+    }
+
+    /// Record that the close replacing the provisional price for
+    /// (symbol, currency, date) was asked for on `today` and none came.
+    /// Does nothing if the price is not provisional.
+    pub fn record_missing_close(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+        today: NaiveDate,
+    ) {
+        let point = (cache_key(symbol, currency, asset_type), date);
+        if let Some(missing) = self.provisional.get_mut(&point) {
+            *missing = Some(today);
+        }
+    }
+
+    /// Whether the close replacing the provisional price for
+    /// (symbol, currency, date) was already found missing on `today`.
+    pub fn is_close_missing(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+        today: NaiveDate,
+    ) -> bool {
+        !self.provisional.is_empty()
+            && self
+                .provisional
+                .get(&(cache_key(symbol, currency, asset_type), date))
+                .is_some_and(|missing| *missing == Some(today))
+    }
+
+    /// Insert a price derived from other cached rates (e.g., a cross rate).
+    /// Derived prices are not authoritative: `PriceService` still asks providers
+    /// for a direct quote and only falls back to this value when none exists.
+    pub fn set_derived_price(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        price: f64,
+        asset_type: &AssetType,
+    ) {
+        if !price.is_finite() {
+            return;
+        }
+        self.set_price(symbol, currency, date, price, asset_type);
+        self.derived.insert((cache_key(symbol, currency, asset_type), date));
+    }
+
+    /// Whether the cached price for (symbol, currency, date) was derived rather than quoted.
+    pub fn is_derived(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> bool {
+        !self.derived.is_empty()
+            && self.derived.contains(&(cache_key(symbol, currency, asset_type), date))
+    }
+
+    /// Insert a price carried forward from an earlier quote, unless a quote
+    /// for that day is already cached or the price is not finite. Returns
+    /// `true` if it was inserted.
+    pub fn set_carried_price(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        price: f64,
+        asset_type: &AssetType,
+    ) -> bool {
+        if !price.is_finite()
+            || (self.get_price(symbol, currency, date, asset_type).is_some()
+                && !self.is_carried(symbol, currency, date, asset_type))
+        {
+            return false;
+        }
+        self.set_price(symbol, currency, date, price, asset_type);
+        self.carried.insert((cache_key(symbol, currency, asset_type), date));
+        true
+    }
+
+    /// Whether the cached price for (symbol, currency, date) was carried forward
+    /// from an earlier day rather than quoted for that day.
+    pub fn is_carried(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> bool {
+        !self.carried.is_empty()
+            && self.carried.contains(&(cache_key(symbol, currency, asset_type), date))
+    }
+
+    /// Insert the close of `date`'s month: the price of `date` (its last
+    /// trading day), which also stands in for the days of the month without
+    /// a price. NaN and infinite prices are ignored.
+    pub fn set_monthly_price(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        price: f64,
+        asset_type: &AssetType,
+    ) {
+        if !price.is_finite() {
+            return;
+        }
+        self.set_price(symbol, currency, date, price, asset_type);
+        self.monthly.insert((cache_key(symbol, currency, asset_type), date));
+    }
+
+    /// Whether the cached price for (symbol, currency, date) is a month close.
+    pub fn resolution(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> PriceResolution {
+        if !self.monthly.is_empty()
+            && self.monthly.contains(&(cache_key(symbol, currency, asset_type), date))
+        {
+            PriceResolution::Monthly
+        } else {
+            PriceResolution::Daily
+        }
+    }
+
+    /// The cached close of the month `date` falls in, if any (the latest
+    /// one should the month have several). Uses binary search.
+    pub fn get_month_price(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Option<PricePoint> {
+        if self.monthly.is_empty() {
+            return None;
+        }
+        let key = cache_key(symbol, currency, asset_type);
+        let entries = self.entries.get(&key)?;
+        let month = (date.year(), date.month());
+        let start = entries.partition_point(|p| (p.date.year(), p.date.month()) < month);
+        entries[start..]
+            .iter()
+            .take_while(|p| (p.date.year(), p.date.month()) == month)
+            .filter(|p| self.monthly.contains(&(key.clone(), p.date)))
+            .last()
+            .cloned()
+    }
+
     /// Insert multiple price points at once (e.g., from a historical range API call).
-    pub fn set_prices(&mut self, symbol: &str, currency: &str, points: &[PricePoint]) {
+    pub fn set_prices(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        points: &[PricePoint],
+        asset_type: &AssetType,
+    ) {
         for point in points {
-            self.set_price(symbol, currency, point.date, point.price);
+            self.set_price(symbol, currency, point.date, point.price, asset_type);
         }
     }
 
     /// Check if today's price was already fetched today (avoid redundant API calls).
-    pub fn is_today_fresh(&self, symbol: &str, currency: &str, today: NaiveDate) -> bool {
-        let key = (symbol.to_uppercase(), currency.to_uppercase());
+    pub fn is_today_fresh(
+        &self,
+        symbol: &str,
+        currency: &str,
+        today: NaiveDate,
+        asset_type: &AssetType,
+    ) -> bool {
+        let key = cache_key(symbol, currency, asset_type);
         self.last_updated.get(&key).is_some_and(|&d| d == today)
     }
 
     /// Mark that we've refreshed the current price for this asset today.
-    pub fn mark_updated_today(&mut self, symbol: &str, currency: &str, today: NaiveDate) {
-        let key = (symbol.to_uppercase(), currency.to_uppercase());
-        self.last_updated.insert(key, today);
+    pub fn mark_updated_today(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        today: NaiveDate,
+        asset_type: &AssetType,
+    ) {
+        self.last_updated.insert(cache_key(symbol, currency, asset_type), today);
+    }
+
+    /// Forget that today's price was refreshed, so the next lookup for today
+    /// fetches a fresh quote. Cached values are kept as an offline fallback.
+    pub fn mark_stale(&mut self, symbol: &str, currency: &str, asset_type: &AssetType) {
+        self.last_updated.remove(&cache_key(symbol, currency, asset_type));
+    }
+
+    /// Unit the cached metal prices of (symbol, currency) are stored in.
+    pub fn metal_unit(&self, symbol: &str, currency: &str) -> MetalUnit {
+        let key = cache_key(symbol, currency, &AssetType::Metal);
+        self.metal_units.get(&key).copied().unwrap_or_default()
+    }
+
+    /// Convert the cached metal prices of (symbol, currency) to `unit` and
+    /// record it, so a unit change never reinterprets old entries.
+    /// Does nothing if the series is already in `unit`.
+    pub fn convert_metal_unit(&mut self, symbol: &str, currency: &str, unit: MetalUnit) {
+        let current = self.metal_unit(symbol, currency);
+        let key = cache_key(symbol, currency, &AssetType::Metal);
+        if current != unit {
+            let factor = current.price_factor(unit);
+            if let Some(entries) = self.entries.get_mut(&key) {
+                for point in entries {
+                    point.price *= factor;
+                }
+            }
+        }
+        if unit == MetalUnit::default() {
+            self.metal_units.remove(&key);
+        } else {
+            self.metal_units.insert(key, unit);
+        }
+    }
+
+    /// Add the prices of another cache (e.g., of a merged portfolio).
+    ///
+    /// Where both caches hold a price for the same day, the cache whose
+    /// series was refreshed more recently (`last_updated`) wins; quotes
+    /// always replace derived or carried prices. Incoming metal series are
+    /// converted to the unit of the existing series first.
+    pub fn merge(&mut self, mut other: PriceCache) {
+        for (key, points) in std::mem::take(&mut other.entries) {
+            let (symbol, currency, asset_type) = (key.0.as_str(), key.1.as_str(), &key.2);
+            other.entries.insert(key.clone(), points);
+            if *asset_type == AssetType::Metal {
+                if self.entries.contains_key(&key) {
+                    other.convert_metal_unit(symbol, currency, self.metal_unit(symbol, currency));
+                } else {
+                    self.convert_metal_unit(symbol, currency, other.metal_unit(symbol, currency));
+                }
+            }
+            let prefer_other = other.last_updated.get(&key) > self.last_updated.get(&key);
+            for point in other.entries.remove(&key).unwrap_or_default() {
+                if !point.price.is_finite() {
+                    continue;
+                }
+                let date = point.date;
+                let other_quoted = !other.is_derived(symbol, currency, date, asset_type)
+                    && !other.is_carried(symbol, currency, date, asset_type);
+                if self.get_price(symbol, currency, date, asset_type).is_some() {
+                    let own_quoted = !self.is_derived(symbol, currency, date, asset_type)
+                        && !self.is_carried(symbol, currency, date, asset_type);
+                    if (own_quoted, !prefer_other) >= (other_quoted, true) {
+                        continue;
+                    }
+                }
+                if other_quoted {
+                    self.set_price(symbol, currency, date, point.price, asset_type);
+                    if let Some(source) = other.sources.remove(&(key.clone(), date)) {
+                        self.sources.insert((key.clone(), date), source);
+                    }
+                    if other.monthly.contains(&(key.clone(), date)) {
+                        self.monthly.insert((key.clone(), date));
+                    }
+                    if let Some(missing) = other.provisional.remove(&(key.clone(), date)) {
+                        self.provisional.insert((key.clone(), date), missing);
+                    }
+                } else if other.is_derived(symbol, currency, date, asset_type) {
+                    self.set_derived_price(symbol, currency, date, point.price, asset_type);
+                } else {
+                    self.set_price(symbol, currency, date, point.price, asset_type);
+                    self.carried.insert((key.clone(), date));
+                }
+            }
+            if let Some(&updated) = other.last_updated.get(&key) {
+                let own = self.last_updated.entry(key).or_insert(updated);
+                *own = (*own).max(updated);
+            }
+        }
     }
 
     /// Get the total number of cached price points across all assets.
@@ -87,7 +572,7 @@ impl PriceCache {
         self.entries.values().map(|v| v.len()).sum()
     }
 
-    /// Get the number of distinct (symbol, currency) pairs cached.
+    /// Get the number of distinct (symbol, currency, type) series cached.
     pub fn asset_count(&self) -> usize {
         self.entries.len()
     }
@@ -114,16 +599,151 @@ impl PriceCache {
         self.last_updated.retain(|key, updated| {
             self.entries.contains_key(key) && *updated >= before
         });
+        self.derived.retain(|(_, date)| *date >= before);
+        self.carried.retain(|(_, date)| *date >= before);
+        self.sources.retain(|(_, date), _| *date >= before);
+        self.monthly.retain(|(_, date)| *date >= before);
+        self.provisional.retain(|(_, date), _| *date >= before);
+        self.metal_units.retain(|key, _| self.entries.contains_key(key));
         removed
     }
 
+    /// Remove the cached prices of (symbol, currency, type) dated within
+    /// `range` (inclusive; `None`: all of them), with their flags and
+    /// sources. If the range covers the day today's price was refreshed, the
+    /// next lookup for today fetches a fresh quote.
+    /// Returns the number of prices removed (0 for an unknown series).
+    pub fn remove(
+        &mut self,
+        symbol: &str,
+        currency: &str,
+        range: Option<(NaiveDate, NaiveDate)>,
+        asset_type: &AssetType,
+    ) -> usize {
+        let key = cache_key(symbol, currency, asset_type);
+        let Some(entries) = self.entries.get_mut(&key) else {
+            return 0;
+        };
+        let in_range = |date: NaiveDate| range.is_none_or(|(from, to)| from <= date && date <= to);
+        let old_len = entries.len();
+        let mut removed = Vec::new();
+        entries.retain(|p| {
+            let keep = !in_range(p.date);
+            if !keep {
+                removed.push(p.date);
+            }
+            keep
+        });
+        for date in removed {
+            let point = (key.clone(), date);
+            self.derived.remove(&point);
+            self.carried.remove(&point);
+            self.sources.remove(&point);
+            self.monthly.remove(&point);
+            self.provisional.remove(&point);
+        }
+        let remaining = entries.len();
+        if remaining == 0 {
+            self.entries.remove(&key);
+            self.metal_units.remove(&key);
+        }
+        if remaining == 0 || self.last_updated.get(&key).is_some_and(|d| in_range(*d)) {
+            self.last_updated.remove(&key);
+        }
+        old_len - remaining
+    }
+
+    /// Every cached price that is NaN or infinite, sorted by series and date.
+    pub fn non_finite_prices(&self) -> Vec<(PriceCacheKey, NaiveDate)> {
+        let mut found: Vec<(PriceCacheKey, NaiveDate)> = self
+            .entries
+            .iter()
+            .flat_map(|(key, entries)| {
+                entries
+                    .iter()
+                    .filter(|p| !p.price.is_finite())
+                    .map(move |p| (key.clone(), p.date))
+            })
+            .collect();
+        found.sort_by(|(a, a_date), (b, b_date)| {
+            (&a.0, &a.1, a.2.to_string(), a_date).cmp(&(&b.0, &b.1, b.2.to_string(), b_date))
+        });
+        found
+    }
+
+    /// Remove every NaN or infinite price, with its flags and source.
+    /// Returns the number of prices removed.
+    pub fn remove_non_finite(&mut self) -> usize {
+        let removed = self.non_finite_prices();
+        for (key, date) in &removed {
+            let point = (key.clone(), *date);
+            if let Some(entries) = self.entries.get_mut(key) {
+                entries.retain(|p| p.date != *date);
+            }
+            self.derived.remove(&point);
+            self.carried.remove(&point);
+            self.sources.remove(&point);
+            self.monthly.remove(&point);
+            self.provisional.remove(&point);
+        }
+        self.entries.retain(|_, v| !v.is_empty());
+        self.last_updated.retain(|key, _| self.entries.contains_key(key));
+        self.metal_units.retain(|key, _| self.entries.contains_key(key));
+        removed.len()
+    }
+
     /// Clear all cached data.
     pub fn clear(&mut self) {
         self.entries.clear();
         self.last_updated.clear();
+        self.derived.clear();
+        self.carried.clear();
+        self.metal_units.clear();
+        self.sources.clear();
+        self.monthly.clear();
+        self.provisional.clear();
     }
 
-    /// Get all cached price points for a (symbol, currency) pair in a date range.
+    /// The cached price closest to `date` at most `max_distance_days` away
+    /// (the earlier one on a tie), e.g. to estimate a day without a quote.
+    pub fn get_nearest_price(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        max_distance_days: u32,
+        asset_type: &AssetType,
+    ) -> Option<PricePoint> {
+        let entries = self.entries.get(&cache_key(symbol, currency, asset_type))?;
+        let idx = match entries.binary_search_by_key(&date, |p| p.date) {
+            Ok(idx) => return Some(entries[idx].clone()),
+            Err(idx) => idx,
+        };
+        let distance = |p: &PricePoint| (p.date - date).num_days().unsigned_abs();
+        let before = idx.checked_sub(1).map(|i| &entries[i]);
+        let after = entries.get(idx);
+        let nearest = match (before, after) {
+            (Some(b), Some(a)) => Some(if distance(a) < distance(b) { a } else { b }),
+            (b, a) => b.or(a),
+        }?;
+        (distance(nearest) <= u64::from(max_distance_days)).then(|| nearest.clone())
+    }
+
+    /// The latest cached price on or before `date`, however old, e.g. the
+    /// last quote of a delisted asset.
+    pub fn get_latest_price(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Option<PricePoint> {
+        let entries = self.entries.get(&cache_key(symbol, currency, asset_type))?;
+        let idx = entries.partition_point(|p| p.date <= date);
+        idx.checked_sub(1).map(|i| entries[i].clone())
+    }
+
+    /// Get all cached price points for a (symbol, currency) series in a date range.
     /// Uses binary search to efficiently find the range boundaries.
     pub fn get_price_range(
         &self,
@@ -131,10 +751,10 @@ impl PriceCache {
         currency: &str,
         from: NaiveDate,
         to: NaiveDate,
+        asset_type: &AssetType,
     ) -> Vec<PricePoint> {
-        let key = (symbol.to_uppercase(), currency.to_uppercase());
         self.entries
-            .get(&key)
+            .get(&cache_key(symbol, currency, asset_type))
             .map(|entries| {
                 // Binary search for start index (first entry >= from)
                 let start = entries
@@ -149,4 +769,113 @@ impl PriceCache {
             })
             .unwrap_or_default()
     }
+
+    /// Which days of (symbol, currency, type) from `from` to `to` are cached
+    /// and which are missing, in one pass over the cached points of the range.
+    /// See `CacheCoverage` for the days stocks are not expected to have.
+    pub fn coverage(
+        &self,
+        symbol: &str,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        asset_type: &AssetType,
+    ) -> CacheCoverage {
+        let stock = *asset_type == AssetType::Stock;
+        let points = self
+            .entries
+            .get(&cache_key(symbol, currency, asset_type))
+            .map_or(&[][..], |entries| {
+                let start = entries.partition_point(|p| p.date < from);
+                let end = entries.partition_point(|p| p.date <= to);
+                &entries[start..end.max(start)]
+            });
+
+        let mut covered: Vec<DateSpan> = Vec::new();
+        let mut gaps = Vec::new();
+        let mut missing_days = 0;
+        let mut next = from; // first day not looked at yet
+        for point in points {
+            let holiday = |days: usize| stock && !covered.is_empty() && days <= MARKET_HOLIDAY_DAYS;
+            let gap = open_days(next, point.date - chrono::Duration::days(1), stock)
+                .filter(|(_, days)| !holiday(*days));
+            match (gap, covered.last_mut()) {
+                (None, Some(run)) => run.to = point.date,
+                (gap, _) => {
+                    if let Some((gap, days)) = gap {
+                        gaps.push(gap);
+                        missing_days += days;
+                    }
+                    covered.push(DateSpan::new(point.date, point.date));
+                }
+            }
+            next = point.date + chrono::Duration::days(1);
+        }
+        if let Some((gap, days)) = open_days(next, to, stock) {
+            gaps.push(gap);
+            missing_days += days;
+        }
+
+        CacheCoverage {
+            symbol: symbol.to_uppercase(),
+            currency: currency.to_uppercase(),
+            asset_type: asset_type.clone(),
+            covered,
+            gaps,
+            missing_days,
+        }
+    }
+}
+
+/// The days from `from` to `to` a price can be expected for (weekdays only
+/// for stocks), trimmed to the first and last such day, with their count.
+/// `None` if there are none.
+fn open_days(from: NaiveDate, to: NaiveDate, stock: bool) -> Option<(DateSpan, usize)> {
+    if from > to {
+        return None;
+    }
+    if !stock {
+        let span = DateSpan::new(from, to);
+        return Some((span, span.days()));
+    }
+    let mut weekdays = from.iter_days().take_while(|d| *d <= to).filter(|d| !is_weekend(*d));
+    let first = weekdays.next()?;
+    let (last, count) = weekdays.fold((first, 1), |(_, count), day| (day, count + 1));
+    Some((DateSpan::new(first, last), count))
+}
+
+/// `PriceCache` as saved by the first release (file version 1), keyed by
+/// (symbol, currency) without the asset type.
+#[derive(Debug, Deserialize)]
+pub(crate) struct PriceCacheV1 {
+    pub entries: HashMap<(String, String), Vec<PricePoint>>,
+    pub last_updated: HashMap<(String, String), NaiveDate>,
+}
+
+impl PriceCacheV1 {
+    /// Key every series by the asset type `guess` returns for its symbol.
+    /// Series without a guess are dropped. Returns the migrated cache and
+    /// the number of dropped series.
+    pub fn migrate(self, guess: impl Fn(&str) -> Option<AssetType>) -> (PriceCache, usize) {
+        let key = |(symbol, currency): (String, String)| {
+            guess(&symbol).map(|asset_type| (symbol, currency, asset_type))
+        };
+        let mut cache = PriceCache::new();
+        let mut dropped = 0;
+        for (legacy, points) in self.entries {
+            match key(legacy) {
+                Some(key) => {
+                    cache.entries.insert(key, points);
+                }
+                None => dropped += 1,
+            }
+        }
+        cache.last_updated = self
+            .last_updated
+            .into_iter()
+            .filter_map(|(legacy, date)| key(legacy).map(|key| (key, date)))
+            .filter(|(key, _)| cache.entries.contains_key(key))
+            .collect();
+        (cache, dropped)
+    }
 }
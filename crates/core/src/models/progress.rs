@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use crate::errors::CoreError;
+
+/// Progress of a long-running operation (chart generation, price refresh/prefetch).
+///
+/// Passed to the `on_progress` callback of the `*_with_progress` methods.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Number of completed steps (days for charts, fetches for refresh/prefetch)
+    pub completed: usize,
+
+    /// Total number of steps
+    pub total: usize,
+
+    /// Symbol currently being fetched, if any
+    pub current_symbol: Option<String>,
+}
+
+/// Outcome of `SavingsTracker::refresh_prices`, by asset symbol.
+///
+/// A failed asset doesn't stop the others; its cached price (if any) is kept.
+#[derive(Debug, Clone, Default)]
+pub struct RefreshReport {
+    /// Assets whose price for today was fetched
+    pub refreshed: Vec<String>,
+
+    /// Assets already refreshed today, left alone (unless forced)
+    pub skipped_fresh: Vec<String>,
+
+    /// Assets whose fetch failed, with the error
+    pub failed: Vec<(String, CoreError)>,
+}
+
+/// Cooperative cancellation flag shared between the caller and a running operation.
+///
+/// Cloning yields a handle to the same flag. Operations check it between
+/// steps and return `CoreError::Cancelled`; prices fetched so far stay cached.
+/// Provider requests in flight wait on `cancelled()` and are dropped (aborting
+/// their HTTP request) as soon as it fires.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    /// Tasks waiting in `WaitForCancellation`, by waiter id
+    waiters: Mutex<HashMap<u64, Waker>>,
+    next_waiter: AtomicU64,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation. Takes effect at the next checkpoint, and wakes
+    /// every task waiting on `cancelled()`.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        let waiters = std::mem::take(&mut *self.state.lock_waiters());
+        for waker in waiters.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` once `cancel()` has been called on any clone.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// A future that completes once `cancel()` has been called on any clone
+    /// (at once if it already was), e.g. to race a request against.
+    pub fn cancelled(&self) -> WaitForCancellation<'_> {
+        WaitForCancellation {
+            state: &self.state,
+            id: self.state.next_waiter.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl CancellationState {
+    fn lock_waiters(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Waker>> {
+        self.waiters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Future returned by `CancellationToken::cancelled`.
+#[derive(Debug)]
+pub struct WaitForCancellation<'a> {
+    state: &'a CancellationState,
+    id: u64,
+}
+
+impl Future for WaitForCancellation<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.state.cancelled.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+        self.state.lock_waiters().insert(self.id, cx.waker().clone());
+        // `cancel()` may have run between the check and the registration
+        if self.state.cancelled.load(Ordering::SeqCst) {
+            self.state.lock_waiters().remove(&self.id);
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for WaitForCancellation<'_> {
+    fn drop(&mut self) {
+        self.state.lock_waiters().remove(&self.id);
+    }
+}
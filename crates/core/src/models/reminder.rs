@@ -0,0 +1,38 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A date to come back to an event (e.g., "review this position in 6
+/// months"), stored in the portfolio file so the app needs no scheduling
+/// state of its own.
+///
+/// An event has at most one reminder. It is due from its date on until
+/// acknowledged (see `SavingsTracker::get_due_reminders`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reminder {
+    /// The event to review
+    pub event_id: Uuid,
+
+    /// First day the reminder is due
+    pub date: NaiveDate,
+
+    /// Dismissed by the user; acknowledged reminders are never due again
+    pub acknowledged: bool,
+}
+
+impl Reminder {
+    /// An unacknowledged reminder for `event_id` due on `date`.
+    pub fn new(event_id: Uuid, date: NaiveDate) -> Self {
+        Self {
+            event_id,
+            date,
+            acknowledged: false,
+        }
+    }
+
+    /// Whether the reminder is due on `as_of`: its date has come and it
+    /// hasn't been acknowledged.
+    pub fn is_due(&self, as_of: NaiveDate) -> bool {
+        !self.acknowledged && self.date <= as_of
+    }
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use super::event::Event;
+
+/// How `SavingsTracker::search_events_with` matches the query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Match the query's characters in order but not necessarily adjacent
+    /// (e.g. "btcn" matches "bitcoin"), within the symbol, name or notes
+    pub fuzzy: bool,
+}
+
+/// Separates the symbol, name and notes in an entry's text. Notes can't
+/// contain it (see `ValidationPolicy`), so exact matches never span fields.
+const FIELD_SEPARATOR: char = '\0';
+
+/// Three consecutive characters of lowercased text.
+type Trigram = [char; 3];
+
+/// Lowercased searchable text of one event.
+#[derive(Debug, Clone)]
+struct SearchEntry {
+    /// Hash of the symbol, name and notes the text was built from
+    fingerprint: u64,
+    /// Lowercased symbol, name and notes, separated by `FIELD_SEPARATOR`
+    text: String,
+}
+
+/// Case-insensitive search index of events' symbols, names and notes.
+///
+/// Keeps each event's lowercased text and a map from every trigram to the
+/// events containing it, so a query of three or more characters only checks
+/// the events that have all of its trigrams. Shorter and fuzzy queries scan
+/// the lowercased texts.
+///
+/// `update` brings the index up to date with changed events incrementally:
+/// only events whose text changed (or that were added or removed) are
+/// reindexed. Covers all events, deleted and planned ones included.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    /// `Portfolio` events generation the index was built for
+    generation: u64,
+    /// Slot of the entry of the event at each position of the events
+    positions: Vec<usize>,
+    /// Entries by slot; `None` for free slots
+    entries: Vec<Option<SearchEntry>>,
+    /// Free slots, reused before `entries` grows
+    free: Vec<usize>,
+    /// Slot of every event by id and occurrence (events sharing an id, see
+    /// `check_integrity`, are told apart by their order)
+    slots: HashMap<(Uuid, usize), usize>,
+    /// Slots of the entries containing each trigram
+    trigrams: HashMap<Trigram, HashSet<usize>>,
+}
+
+impl SearchIndex {
+    /// Build the index from `events`.
+    pub fn build(events: &[Event], generation: u64) -> Self {
+        let mut index = Self::default();
+        index.update(events, generation);
+        index
+    }
+
+    /// Whether the index still describes events of this generation and count.
+    pub fn is_current(&self, generation: u64, event_count: usize) -> bool {
+        self.generation == generation && self.positions.len() == event_count
+    }
+
+    /// Reindex the events whose text changed since the last update (by id),
+    /// index new events and drop removed ones.
+    pub fn update(&mut self, events: &[Event], generation: u64) {
+        let mut previous = std::mem::take(&mut self.slots);
+        let mut slots = HashMap::with_capacity(events.len());
+        let mut positions = Vec::with_capacity(events.len());
+        for event in events {
+            let occurrence = (0..)
+                .find(|n| !slots.contains_key(&(event.id, *n)))
+                .unwrap_or_default();
+            let key = (event.id, occurrence);
+            let fingerprint = Self::fingerprint(event);
+            let slot = match previous.remove(&key) {
+                Some(slot) if self.entry(slot).fingerprint == fingerprint => slot,
+                Some(slot) => {
+                    self.remove_entry(slot);
+                    self.insert_entry(event, fingerprint)
+                }
+                None => self.insert_entry(event, fingerprint),
+            };
+            slots.insert(key, slot);
+            positions.push(slot);
+        }
+        for slot in previous.into_values() {
+            self.remove_entry(slot);
+        }
+        self.slots = slots;
+        self.positions = positions;
+        self.generation = generation;
+    }
+
+    /// Positions (in the indexed events, ascending) of the events whose
+    /// symbol, name or notes match `query`, compared case-insensitively.
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Vec<usize> {
+        let query = query.to_lowercase();
+        if options.fuzzy {
+            return self.positions_where(|entry| {
+                entry
+                    .text
+                    .split(FIELD_SEPARATOR)
+                    .any(|field| is_subsequence(&query, field))
+            });
+        }
+        let query_trigrams: HashSet<Trigram> = trigrams(&query).collect();
+        if query_trigrams.is_empty() {
+            return self.positions_where(|entry| entry.text.contains(&query));
+        }
+
+        let mut postings = Vec::with_capacity(query_trigrams.len());
+        for trigram in &query_trigrams {
+            match self.trigrams.get(trigram) {
+                Some(slots) => postings.push(slots),
+                None => return Vec::new(),
+            }
+        }
+        let Some(rarest) = postings.iter().min_by_key(|slots| slots.len()) else {
+            return Vec::new();
+        };
+        let mut hits = vec![false; self.entries.len()];
+        let mut any = false;
+        for &slot in rarest.iter() {
+            if self.entry(slot).text.contains(&query) {
+                hits[slot] = true;
+                any = true;
+            }
+        }
+        if !any {
+            return Vec::new();
+        }
+        (0..self.positions.len())
+            .filter(|&pos| hits[self.positions[pos]])
+            .collect()
+    }
+
+    fn positions_where(&self, matches: impl Fn(&SearchEntry) -> bool) -> Vec<usize> {
+        (0..self.positions.len())
+            .filter(|&pos| matches(self.entry(self.positions[pos])))
+            .collect()
+    }
+
+    fn entry(&self, slot: usize) -> &SearchEntry {
+        self.entries[slot]
+            .as_ref()
+            .expect("indexed events point to used slots")
+    }
+
+    fn fingerprint(event: &Event) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        event.asset.symbol.hash(&mut hasher);
+        event.asset.name.hash(&mut hasher);
+        event.notes.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn insert_entry(&mut self, event: &Event, fingerprint: u64) -> usize {
+        let text = format!(
+            "{}{FIELD_SEPARATOR}{}{FIELD_SEPARATOR}{}",
+            event.asset.symbol.to_lowercase(),
+            event.asset.name.to_lowercase(),
+            event.notes.as_deref().unwrap_or("").to_lowercase()
+        );
+        let slot = self.free.pop().unwrap_or(self.entries.len());
+        for trigram in trigrams(&text).collect::<HashSet<_>>() {
+            self.trigrams.entry(trigram).or_default().insert(slot);
+        }
+        let entry = Some(SearchEntry { fingerprint, text });
+        match self.entries.get_mut(slot) {
+            Some(free) => *free = entry,
+            None => self.entries.push(entry),
+        }
+        slot
+    }
+
+    fn remove_entry(&mut self, slot: usize) {
+        let Some(entry) = self.entries[slot].take() else {
+            return;
+        };
+        for trigram in trigrams(&entry.text) {
+            if let Some(slots) = self.trigrams.get_mut(&trigram) {
+                slots.remove(&slot);
+                if slots.is_empty() {
+                    self.trigrams.remove(&trigram);
+                }
+            }
+        }
+        self.free.push(slot);
+    }
+}
+
+/// Trigrams of `text` that don't span fields.
+fn trigrams(text: &str) -> impl Iterator<Item = Trigram> + '_ {
+    let chars: Vec<char> = text.chars().collect();
+    (0..chars.len().saturating_sub(2))
+        .map(move |i| [chars[i], chars[i + 1], chars[i + 2]])
+        .filter(|trigram| !trigram.contains(&FIELD_SEPARATOR))
+}
+
+/// Whether the characters of `query` appear in `text` in order.
+fn is_subsequence(query: &str, text: &str) -> bool {
+    let mut text = text.chars();
+    query.chars().all(|c| text.any(|t| t == c))
+}
+
+/// Lazily built `SearchIndex` kept next to the events it indexes.
+///
+/// Not serialized; a clone starts from the same index, which stays valid
+/// as long as the clone's events are unchanged.
+#[derive(Debug, Default)]
+pub struct SearchIndexCell(Mutex<Option<Arc<SearchIndex>>>);
+
+impl SearchIndexCell {
+    /// The cached index if it is current, otherwise the cached one updated
+    /// (or a freshly built one).
+    pub fn get_or_update(&self, events: &[Event], generation: u64) -> Arc<SearchIndex> {
+        let mut cached = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match cached.take() {
+            Some(index) if index.is_current(generation, events.len()) => {
+                *cached = Some(Arc::clone(&index));
+                index
+            }
+            stale => {
+                // Shared with a clone of the portfolio: update a copy
+                let mut index = stale
+                    .map(|index| Arc::try_unwrap(index).unwrap_or_else(|shared| (*shared).clone()))
+                    .unwrap_or_default();
+                index.update(events, generation);
+                let index = Arc::new(index);
+                *cached = Some(Arc::clone(&index));
+                index
+            }
+        }
+    }
+
+    fn cached(&self) -> Option<Arc<SearchIndex>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Clone for SearchIndexCell {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.cached()))
+    }
+}
@@ -1,8 +1,15 @@
+use chrono::{Datelike, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+
+use super::asset::MetalUnit;
+use super::tax::CostBasisMethod;
+use crate::formatting::Locale;
 
 /// User-configurable settings, stored inside the encrypted portfolio file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     /// The currency in which all portfolio values are displayed (e.g., "PLN", "USD", "EUR").
     pub default_currency: String,
@@ -10,7 +17,137 @@ pub struct Settings {
     /// Optional API keys for providers that require them.
     /// Keys: provider name (e.g., "metals_dev", "alphavantage").
     /// Values: the API key string.
+    #[serde(serialize_with = "super::sorted::map")]
     pub api_keys: HashMap<String, String>,
+
+    /// Currencies tried, in order, to triangulate a fiat rate when a provider
+    /// has no direct quote for the pair (e.g., EUR → USD → PLN).
+    #[serde(default = "default_pivot_currencies")]
+    pub pivot_currencies: Vec<String>,
+
+    /// Unit of metal holdings and metal prices (troy ounce by default).
+    #[serde(default)]
+    pub metal_unit: MetalUnit,
+
+    /// Provider-specific asset ids for symbols the built-in mapping doesn't know.
+    /// Keys: lowercase provider name (e.g., "coincap") → uppercase symbol → id.
+    /// Filled by manual overrides and by ids providers resolved at runtime.
+    #[serde(default, serialize_with = "super::sorted::nested_map")]
+    pub symbol_overrides: HashMap<String, HashMap<String, String>>,
+
+    /// Rules events must satisfy when they are added, updated or removed.
+    #[serde(default)]
+    pub validation_policy: ValidationPolicy,
+
+    /// Sales of lots held longer than this many days are long-term in tax
+    /// lot reports (365 by default).
+    #[serde(default = "default_long_term_holding_days")]
+    pub long_term_holding_days: u32,
+
+    /// How sales are matched against buy lots for the average buy price of
+    /// summary holdings (FIFO by default).
+    #[serde(default)]
+    pub cost_basis_method: CostBasisMethod,
+
+    /// Limits on the trash, beyond which the earliest deleted events are
+    /// removed permanently (unlimited by default).
+    #[serde(default)]
+    pub trash_policy: TrashPolicy,
+
+    /// Month (1-12) the fiscal year starts in; annual reports and yearly
+    /// event groups cover fiscal years (1, the calendar year, by default).
+    #[serde(default = "default_fiscal_year_start_month")]
+    pub fiscal_year_start_month: u32,
+
+    /// Day weekly event groups start on (Monday by default).
+    #[serde(default)]
+    pub week_start: WeekStart,
+
+    /// Number and date conventions of `SavingsTracker::format_value`
+    /// (English by default).
+    #[serde(default)]
+    pub locale: Locale,
+
+    /// How holdings no price can be found for (e.g., a delisted token) are
+    /// valued in summaries and charts (failing the summary by default).
+    #[serde(default)]
+    pub unpriceable_asset_policy: UnpriceableAssetPolicy,
+
+    /// Seconds a provider request may take before it fails as timed out
+    /// (10 by default).
+    #[serde(default = "default_provider_timeout_secs")]
+    pub provider_timeout_secs: u64,
+
+    /// Per-provider request timeouts in seconds, replacing
+    /// `provider_timeout_secs` for that provider.
+    /// Keys: lowercase provider id (see `PriceProvider::id`), e.g., "coincap",
+    /// "frankfurter", "metals_dev", "yahoo" or "alphavantage".
+    #[serde(default, serialize_with = "super::sorted::map")]
+    pub provider_timeouts: HashMap<String, u64>,
+
+    /// HTTP(S) proxy every provider request goes through (e.g.,
+    /// "http://proxy.corp:3128"), if any. Native only; browsers use the
+    /// system proxy.
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+
+    /// Hosts reached directly rather than through `proxy_url` (e.g.,
+    /// "localhost", ".corp.example", "10.0.0.0/8").
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+
+    /// Email address added to the `User-Agent` of provider requests, so API
+    /// operators can reach the user instead of blocking them. Native only.
+    #[serde(default)]
+    pub contact_email: Option<String>,
+
+    /// Requests a provider may be sent per UTC day; once they are used up it
+    /// is skipped (the next provider answers). Keys: lowercase provider id,
+    /// as in `provider_timeouts`. No limits by default.
+    #[serde(default, serialize_with = "super::sorted::map")]
+    pub provider_daily_limits: HashMap<String, u32>,
+
+    /// Factor a provider quote may differ by from a cached price of the same
+    /// or a nearby day before another provider has to confirm it (10 by
+    /// default, i.e. a tenfold rise or fall). `None` disables the check.
+    #[serde(default = "default_max_price_change_factor")]
+    pub max_price_change_factor: Option<f64>,
+
+    /// Dates more than this many days in the past are priced with the close
+    /// of their month when no daily price is cached, which keeps the cache
+    /// small for old history. `None` (the default) prices every date daily.
+    #[serde(default)]
+    pub monthly_price_age_days: Option<u32>,
+
+    /// Longest date range, in days, a chart or price prefetch may span
+    /// (3650, about 10 years, by default). `None` allows any range; long
+    /// charts get weekly or monthly points (see `ChartResolution`).
+    #[serde(default = "default_max_chart_range_days")]
+    pub max_chart_range_days: Option<u32>,
+}
+
+fn default_pivot_currencies() -> Vec<String> {
+    vec!["USD".to_string(), "EUR".to_string()]
+}
+
+fn default_long_term_holding_days() -> u32 {
+    365
+}
+
+fn default_fiscal_year_start_month() -> u32 {
+    1
+}
+
+fn default_provider_timeout_secs() -> u64 {
+    10
+}
+
+fn default_max_price_change_factor() -> Option<f64> {
+    Some(10.0)
+}
+
+fn default_max_chart_range_days() -> Option<u32> {
+    Some(3650)
 }
 
 impl Default for Settings {
@@ -18,6 +155,214 @@ impl Default for Settings {
         Self {
             default_currency: "USD".to_string(),
             api_keys: HashMap::new(),
+            pivot_currencies: default_pivot_currencies(),
+            metal_unit: MetalUnit::default(),
+            symbol_overrides: HashMap::new(),
+            validation_policy: ValidationPolicy::default(),
+            long_term_holding_days: default_long_term_holding_days(),
+            cost_basis_method: CostBasisMethod::default(),
+            trash_policy: TrashPolicy::default(),
+            fiscal_year_start_month: default_fiscal_year_start_month(),
+            week_start: WeekStart::default(),
+            locale: Locale::default(),
+            unpriceable_asset_policy: UnpriceableAssetPolicy::default(),
+            provider_timeout_secs: default_provider_timeout_secs(),
+            provider_timeouts: HashMap::new(),
+            proxy_url: None,
+            no_proxy: Vec::new(),
+            contact_email: None,
+            provider_daily_limits: HashMap::new(),
+            max_price_change_factor: default_max_price_change_factor(),
+            monthly_price_age_days: None,
+            max_chart_range_days: default_max_chart_range_days(),
+        }
+    }
+}
+
+impl Settings {
+    /// First day of fiscal year `year`, which is named after the calendar
+    /// year it starts in (with an April start, fiscal 2025 is April 2025 to
+    /// March 2026). `None` if the date is out of range.
+    pub fn fiscal_year_start(&self, year: i32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, self.fiscal_year_start_month.clamp(1, 12), 1)
+    }
+
+    /// Last day of fiscal year `year`.
+    pub fn fiscal_year_end(&self, year: i32) -> Option<NaiveDate> {
+        self.fiscal_year_start(year.checked_add(1)?)?.pred_opt()
+    }
+
+    /// Fiscal year `date` falls in.
+    pub fn fiscal_year(&self, date: NaiveDate) -> i32 {
+        if date.month() < self.fiscal_year_start_month {
+            date.year() - 1
+        } else {
+            date.year()
+        }
+    }
+
+    /// First day of the week containing `date`.
+    pub fn week_start_date(&self, date: NaiveDate) -> NaiveDate {
+        date.week(self.week_start.weekday()).first_day()
+    }
+
+    /// Request timeout of `provider`: its override in `provider_timeouts`,
+    /// else `provider_timeout_secs`, and at least one second.
+    pub fn provider_timeout(&self, provider: &str) -> Duration {
+        let secs = self
+            .provider_timeouts
+            .get(provider)
+            .copied()
+            .unwrap_or(self.provider_timeout_secs);
+        Duration::from_secs(secs.max(1))
+    }
+
+    /// Whether `factor` may be `max_price_change_factor`: `None`, or a
+    /// finite number above 1.
+    pub fn is_valid_max_price_change_factor(factor: Option<f64>) -> bool {
+        factor.is_none_or(|f| f.is_finite() && f > 1.0)
+    }
+
+    /// Reset settings the setters would have rejected (e.g., in a file
+    /// edited by hand) to their defaults: a `max_price_change_factor` of 0
+    /// or NaN would reject or accept every quote. Returns whether any was.
+    pub fn reset_invalid(&mut self) -> bool {
+        if Self::is_valid_max_price_change_factor(self.max_price_change_factor) {
+            return false;
+        }
+        self.max_price_change_factor = default_max_price_change_factor();
+        true
+    }
+}
+
+/// Day a week starts on, for weekly groupings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStart {
+    pub fn weekday(&self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+impl std::fmt::Display for WeekStart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeekStart::Monday => write!(f, "Monday"),
+            WeekStart::Sunday => write!(f, "Sunday"),
+        }
+    }
+}
+
+/// How a holding is valued when its price lookup fails, e.g. because the
+/// asset was delisted or its provider dropped it.
+///
+/// Every policy but `Fail` values such holdings somehow, so totals still
+/// compute; `HoldingSummary::price_status` tells how each one was priced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnpriceableAssetPolicy {
+    /// Fail with the lookup error (summaries) or leave the holding out (charts)
+    #[default]
+    Fail,
+    /// Use the latest cached price before the date, or value at zero if
+    /// none is cached
+    LastCachedPrice,
+    /// Use the asset's `AssetMetadata::manual_price`, or value at zero if
+    /// it has none
+    ManualPrice,
+    /// Value at zero
+    Zero,
+}
+
+impl std::fmt::Display for UnpriceableAssetPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnpriceableAssetPolicy::Fail => write!(f, "fail"),
+            UnpriceableAssetPolicy::LastCachedPrice => write!(f, "last cached price"),
+            UnpriceableAssetPolicy::ManualPrice => write!(f, "manual price"),
+            UnpriceableAssetPolicy::Zero => write!(f, "zero"),
+        }
+    }
+}
+
+/// Configurable rules for validating portfolio events.
+///
+/// Amounts must always be positive and notes free of NUL characters;
+/// everything else is tunable. The defaults reject dates more than one day
+/// ahead (tolerance for time zones), sells of more than is held on that
+/// date and notes longer than 10,000 characters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationPolicy {
+    /// How many days after today an event may be dated (e.g., planned buys).
+    pub allow_future_days: u32,
+
+    /// Accept sells of more than is held on their date.
+    pub allow_oversell: bool,
+
+    /// Largest accepted event amount, if any.
+    pub max_amount: Option<f64>,
+
+    /// Reject assets the portfolio has never seen: no other event, no stored
+    /// metadata and no cached price. Guards against mistyped symbols.
+    pub require_known_asset: bool,
+
+    /// Longest accepted event notes in characters (after trimming trailing
+    /// whitespace), if limited.
+    pub max_notes_len: Option<usize>,
+
+    /// Most events (actual and planned, not deleted) the portfolio may
+    /// hold, if limited. Checked when events are added or imported.
+    pub max_events: Option<usize>,
+}
+
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        Self {
+            allow_future_days: 1,
+            allow_oversell: false,
+            max_amount: None,
+            require_known_asset: false,
+            max_notes_len: Some(10_000),
+            max_events: None,
+        }
+    }
+}
+
+/// How many deleted events are kept, and for how long.
+///
+/// Enforced whenever an event is deleted or the policy changes; the events
+/// deleted first are removed first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashPolicy {
+    /// Most deleted events kept, if limited.
+    pub max_entries: Option<usize>,
+
+    /// Days after which a deleted event is removed, if limited.
+    pub retention_days: Option<u32>,
+}
+
+/// `Settings` as saved by the first release (file version 1).
+#[derive(Debug, Deserialize)]
+pub(crate) struct SettingsV1 {
+    pub default_currency: String,
+    pub api_keys: HashMap<String, String>,
+}
+
+impl From<SettingsV1> for Settings {
+    fn from(settings: SettingsV1) -> Self {
+        Self {
+            default_currency: settings.default_currency,
+            api_keys: settings.api_keys,
+            ..Self::default()
         }
     }
 }
@@ -0,0 +1,43 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::analytics::PortfolioSummary;
+use super::asset::Asset;
+use super::chart::ChartDataPoint;
+use super::event::Event;
+
+/// Outcome of a hypothetical investment in one asset
+/// (see `SavingsTracker::simulate_dca` and `simulate_lump_sum`).
+///
+/// Computed from real prices on a throwaway portfolio; the user's
+/// portfolio is not changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationResult {
+    pub asset: Asset,
+
+    /// Currency used for all monetary values
+    pub currency: String,
+
+    /// The hypothetical buys, oldest first, with their unit prices recorded
+    pub events: Vec<Event>,
+
+    /// Buy dates without a price of their own, bought at the last known
+    /// price instead (like chart gaps on weekends and holidays)
+    pub gap_filled_dates: Vec<NaiveDate>,
+
+    /// Planned buy dates without a buy: no price was found and none was
+    /// known from an earlier date
+    pub skipped_dates: Vec<NaiveDate>,
+
+    /// Money put in: the sum of the buys' values
+    pub total_contributed: f64,
+
+    /// Value of the holding at the end of the range
+    pub final_value: f64,
+
+    /// Daily value of the holding over the range
+    pub value_series: Vec<ChartDataPoint>,
+
+    /// Summary of the hypothetical portfolio at the end of the range
+    pub summary: PortfolioSummary,
+}
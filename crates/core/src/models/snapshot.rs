@@ -0,0 +1,46 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::asset::Asset;
+
+/// Portfolio value recorded on a given date and stored in the portfolio file.
+///
+/// Snapshots keep the value history available even when historical prices
+/// can no longer be fetched (e.g., delisted tokens). Charts prefer a snapshot
+/// over recomputation for dates that have one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueSnapshot {
+    /// Date the value was recorded for
+    pub date: NaiveDate,
+
+    /// Total portfolio value in `currency`
+    pub total_value: f64,
+
+    /// Currency of all values in this snapshot (e.g., "PLN")
+    pub currency: String,
+
+    /// Per-asset breakdown, sorted by symbol
+    pub assets: Vec<AssetValue>,
+}
+
+/// Value of one holding inside a `ValueSnapshot`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetValue {
+    pub asset: Asset,
+
+    /// Amount held on the snapshot date
+    pub amount: f64,
+
+    /// Value of the holding in the snapshot's currency
+    pub value: f64,
+}
+
+impl ValueSnapshot {
+    /// Value of one unit of `asset` on the snapshot date, if it was held.
+    pub fn unit_value(&self, asset: &Asset) -> Option<f64> {
+        self.assets
+            .iter()
+            .find(|a| &a.asset == asset && a.amount > 0.0)
+            .map(|a| a.value / a.amount)
+    }
+}
@@ -0,0 +1,82 @@
+//! Serde helpers that write hash maps and sets in sorted order, so the same
+//! data always serializes to the same bytes (diffable JSON exports).
+//!
+//! Maps with non-string keys are written as sorted `[key, value]` pairs in
+//! human-readable formats, since JSON object keys must be strings, and as
+//! maps otherwise, which keeps the saved file format unchanged.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+
+/// Serialize a map with its keys in order.
+pub fn map<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Serialize a map of maps with the keys of both levels in order.
+pub fn nested_map<K, V, S>(
+    map: &HashMap<K, HashMap<K, V>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    map.iter()
+        .map(|(key, inner)| (key, inner.iter().collect::<BTreeMap<_, _>>()))
+        .collect::<BTreeMap<_, _>>()
+        .serialize(serializer)
+}
+
+/// Serialize a set with its items in order.
+pub fn set<T, S>(set: &HashSet<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Ord + Serialize,
+    S: Serializer,
+{
+    let mut items: Vec<&T> = set.iter().collect();
+    items.sort();
+    items.serialize(serializer)
+}
+
+/// A map with non-string keys, e.g. the price cache keyed by
+/// (symbol, currency, asset type).
+pub mod keyed {
+    use super::*;
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Ord + Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            let mut pairs: Vec<(&K, &V)> = map.iter().collect();
+            pairs.sort_by(|a, b| a.0.cmp(b.0));
+            pairs.serialize(serializer)
+        } else {
+            super::map(map, serializer)
+        }
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let pairs = Vec::<(K, V)>::deserialize(deserializer)?;
+            Ok(pairs.into_iter().collect())
+        } else {
+            HashMap::deserialize(deserializer)
+        }
+    }
+}
@@ -0,0 +1,401 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use uuid::Uuid;
+
+use super::accrual::AccrualRule;
+use super::corporate_action::CorporateAction;
+use super::diff::{FieldChange, PortfolioDiff};
+use super::event::Event;
+use super::portfolio::Portfolio;
+use super::reminder::Reminder;
+use super::settings::Settings;
+
+/// An event both sides changed differently since their common base
+/// (edited differently, or edited on one side and removed on the other).
+///
+/// `None` means the event does not exist in that version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub event_id: Uuid,
+    pub base: Option<Event>,
+    pub mine: Option<Event>,
+    pub theirs: Option<Event>,
+}
+
+/// How to settle a `SyncConflict`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Resolution {
+    /// Keep the local version (what the merge keeps until resolved)
+    Mine,
+    /// Take the other side's version
+    Theirs,
+    /// Use this event instead (its id must be the conflict's event id)
+    Custom(Event),
+}
+
+/// Outcome of `SavingsTracker::three_way_merge`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// Event additions, removals and edits taken over from their side
+    pub events_from_theirs: usize,
+
+    /// Events changed differently on both sides; the local version is kept
+    /// until `resolve_conflict` is called
+    pub conflicts: Vec<SyncConflict>,
+
+    /// How the local settings changed (API key values are hidden)
+    pub settings_changes: Vec<FieldChange>,
+
+    /// Settings fields both sides changed differently; the local value won
+    pub settings_conflicts: Vec<String>,
+
+    /// Corporate actions, accrual rules and reminders both sides changed
+    /// differently, as "corporate_actions.<id>", "accrual_rules.<id>" or
+    /// "reminders.<event id>"; the local version won
+    pub record_conflicts: Vec<String>,
+}
+
+/// Result of merging two versions of a portfolio with their common base.
+#[derive(Debug, Clone)]
+pub struct ThreeWayMerge {
+    /// Merged events (unsorted), local versions for conflicts
+    pub events: Vec<Event>,
+    /// Merged settings
+    pub settings: Settings,
+    /// Merged corporate actions, by effective date
+    pub corporate_actions: Vec<CorporateAction>,
+    /// Merged accrual rules (not checked for overlaps)
+    pub accrual_rules: Vec<AccrualRule>,
+    /// Merged reminders, matched by event (may include reminders of
+    /// events the merge removed)
+    pub reminders: Vec<Reminder>,
+    pub report: SyncReport,
+}
+
+impl ThreeWayMerge {
+    /// Merge `mine` and `theirs`, both derived from `base`.
+    ///
+    /// Events are matched by id: a change made on one side only is taken
+    /// over, the same change on both sides is kept once, and different
+    /// changes on both sides are conflicts. Settings are merged field by
+    /// field, and corporate actions, accrual rules and reminders by id the
+    /// same way, except that
+    /// conflicting ones keep the local value: the local version is the last
+    /// writer.
+    pub fn compute(base: &Portfolio, mine: &Portfolio, theirs: &Portfolio) -> Self {
+        let by_id = |p: &Portfolio| -> HashMap<Uuid, Event> {
+            p.events.iter().map(|e| (e.id, e.clone())).collect()
+        };
+        let (base_events, mut mine_events, mut theirs_events) =
+            (by_id(base), by_id(mine), by_id(theirs));
+        let ids: BTreeSet<Uuid> = mine_events.keys().chain(theirs_events.keys()).copied().collect();
+
+        let mut events = Vec::with_capacity(ids.len());
+        let mut report = SyncReport::default();
+        for id in ids {
+            let (base, mine, theirs) =
+                (base_events.get(&id), mine_events.remove(&id), theirs_events.remove(&id));
+            if mine == theirs || theirs.as_ref() == base {
+                events.extend(mine);
+            } else if mine.as_ref() == base {
+                report.events_from_theirs += 1;
+                events.extend(theirs);
+            } else {
+                events.extend(mine.clone());
+                report.conflicts.push(SyncConflict {
+                    event_id: id,
+                    base: base.cloned(),
+                    mine,
+                    theirs,
+                });
+            }
+        }
+
+        let settings = Self::merge_settings(
+            &base.settings,
+            &mine.settings,
+            &theirs.settings,
+            &mut report.settings_conflicts,
+        );
+        report.settings_changes = PortfolioDiff::settings_changes(&mine.settings, &settings);
+
+        let mut corporate_actions = merge_by_id(
+            "corporate_actions",
+            &base.corporate_actions,
+            &mine.corporate_actions,
+            &theirs.corporate_actions,
+            |action| action.id,
+            &mut report.record_conflicts,
+        );
+        corporate_actions.sort_by_key(|action| action.effective_date);
+        let accrual_rules = merge_by_id(
+            "accrual_rules",
+            &base.accrual_rules,
+            &mine.accrual_rules,
+            &theirs.accrual_rules,
+            |rule| rule.id,
+            &mut report.record_conflicts,
+        );
+        let reminders = merge_by_id(
+            "reminders",
+            &base.reminders,
+            &mine.reminders,
+            &theirs.reminders,
+            |reminder| reminder.event_id,
+            &mut report.record_conflicts,
+        );
+        Self { events, settings, corporate_actions, accrual_rules, reminders, report }
+    }
+
+    fn merge_settings(
+        base: &Settings,
+        mine: &Settings,
+        theirs: &Settings,
+        conflicts: &mut Vec<String>,
+    ) -> Settings {
+        let mut merged = mine.clone();
+        merged.default_currency = pick(
+            "default_currency",
+            &base.default_currency,
+            &mine.default_currency,
+            &theirs.default_currency,
+            conflicts,
+        );
+        merged.pivot_currencies = pick(
+            "pivot_currencies",
+            &base.pivot_currencies,
+            &mine.pivot_currencies,
+            &theirs.pivot_currencies,
+            conflicts,
+        );
+        merged.metal_unit = pick(
+            "metal_unit",
+            &base.metal_unit,
+            &mine.metal_unit,
+            &theirs.metal_unit,
+            conflicts,
+        );
+        merged.validation_policy = pick(
+            "validation_policy",
+            &base.validation_policy,
+            &mine.validation_policy,
+            &theirs.validation_policy,
+            conflicts,
+        );
+        merged.long_term_holding_days = pick(
+            "long_term_holding_days",
+            &base.long_term_holding_days,
+            &mine.long_term_holding_days,
+            &theirs.long_term_holding_days,
+            conflicts,
+        );
+        merged.cost_basis_method = pick(
+            "cost_basis_method",
+            &base.cost_basis_method,
+            &mine.cost_basis_method,
+            &theirs.cost_basis_method,
+            conflicts,
+        );
+        merged.trash_policy = pick(
+            "trash_policy",
+            &base.trash_policy,
+            &mine.trash_policy,
+            &theirs.trash_policy,
+            conflicts,
+        );
+        merged.fiscal_year_start_month = pick(
+            "fiscal_year_start_month",
+            &base.fiscal_year_start_month,
+            &mine.fiscal_year_start_month,
+            &theirs.fiscal_year_start_month,
+            conflicts,
+        );
+        merged.week_start = pick(
+            "week_start",
+            &base.week_start,
+            &mine.week_start,
+            &theirs.week_start,
+            conflicts,
+        );
+        merged.locale = pick("locale", &base.locale, &mine.locale, &theirs.locale, conflicts);
+        merged.unpriceable_asset_policy = pick(
+            "unpriceable_asset_policy",
+            &base.unpriceable_asset_policy,
+            &mine.unpriceable_asset_policy,
+            &theirs.unpriceable_asset_policy,
+            conflicts,
+        );
+        merged.provider_timeout_secs = pick(
+            "provider_timeout_secs",
+            &base.provider_timeout_secs,
+            &mine.provider_timeout_secs,
+            &theirs.provider_timeout_secs,
+            conflicts,
+        );
+        merged.provider_timeouts = merge_map(
+            "provider_timeouts",
+            &base.provider_timeouts,
+            &mine.provider_timeouts,
+            &theirs.provider_timeouts,
+            conflicts,
+        );
+        merged.proxy_url = pick(
+            "proxy_url",
+            &base.proxy_url,
+            &mine.proxy_url,
+            &theirs.proxy_url,
+            conflicts,
+        );
+        merged.no_proxy = pick(
+            "no_proxy",
+            &base.no_proxy,
+            &mine.no_proxy,
+            &theirs.no_proxy,
+            conflicts,
+        );
+        merged.contact_email = pick(
+            "contact_email",
+            &base.contact_email,
+            &mine.contact_email,
+            &theirs.contact_email,
+            conflicts,
+        );
+        merged.max_price_change_factor = pick(
+            "max_price_change_factor",
+            &base.max_price_change_factor,
+            &mine.max_price_change_factor,
+            &theirs.max_price_change_factor,
+            conflicts,
+        );
+        merged.monthly_price_age_days = pick(
+            "monthly_price_age_days",
+            &base.monthly_price_age_days,
+            &mine.monthly_price_age_days,
+            &theirs.monthly_price_age_days,
+            conflicts,
+        );
+        merged.max_chart_range_days = pick(
+            "max_chart_range_days",
+            &base.max_chart_range_days,
+            &mine.max_chart_range_days,
+            &theirs.max_chart_range_days,
+            conflicts,
+        );
+        merged.provider_daily_limits = merge_map(
+            "provider_daily_limits",
+            &base.provider_daily_limits,
+            &mine.provider_daily_limits,
+            &theirs.provider_daily_limits,
+            conflicts,
+        );
+        merged.api_keys = merge_map(
+            "api_keys",
+            &base.api_keys,
+            &mine.api_keys,
+            &theirs.api_keys,
+            conflicts,
+        );
+        let providers: BTreeSet<&String> = [base, mine, theirs]
+            .into_iter()
+            .flat_map(|s| s.symbol_overrides.keys())
+            .collect();
+        let empty = HashMap::new();
+        merged.symbol_overrides = providers
+            .into_iter()
+            .map(|provider| {
+                let ids = |s: &Settings| {
+                    s.symbol_overrides.get(provider).unwrap_or(&empty).clone()
+                };
+                let merged = merge_map(
+                    &format!("symbol_overrides.{provider}"),
+                    &ids(base),
+                    &ids(mine),
+                    &ids(theirs),
+                    conflicts,
+                );
+                (provider.clone(), merged)
+            })
+            .filter(|(_, ids)| !ids.is_empty())
+            .collect();
+        conflicts.sort();
+        merged
+    }
+}
+
+/// Three-way pick of one value: a change on one side wins; when both sides
+/// changed it differently, `mine` wins and `field` is recorded as a conflict.
+fn pick<T: PartialEq + Clone>(
+    field: &str,
+    base: &T,
+    mine: &T,
+    theirs: &T,
+    conflicts: &mut Vec<String>,
+) -> T {
+    if mine == base {
+        theirs.clone()
+    } else {
+        if theirs != base && theirs != mine {
+            conflicts.push(field.to_string());
+        }
+        mine.clone()
+    }
+}
+
+/// `pick` for every record of a list, matched by `id` (a missing record is
+/// a removed one). Local records keep their order; the ones only the other
+/// side has follow in its order. Conflicts are recorded as "<field>.<id>".
+fn merge_by_id<T: PartialEq + Clone>(
+    field: &str,
+    base: &[T],
+    mine: &[T],
+    theirs: &[T],
+    id: impl Fn(&T) -> Uuid,
+    conflicts: &mut Vec<String>,
+) -> Vec<T> {
+    let by_id = |records: &'_ [T]| -> HashMap<Uuid, T> {
+        records.iter().map(|r| (id(r), r.clone())).collect()
+    };
+    let (base_records, mine_records, theirs_records) = (by_id(base), by_id(mine), by_id(theirs));
+    let mut ids: Vec<Uuid> = mine.iter().map(&id).collect();
+    ids.extend(theirs.iter().map(&id).filter(|id| !mine_records.contains_key(id)));
+    ids.into_iter()
+        .filter_map(|id| {
+            pick(
+                &format!("{field}.{id}"),
+                &base_records.get(&id),
+                &mine_records.get(&id),
+                &theirs_records.get(&id),
+                conflicts,
+            )
+            .cloned()
+        })
+        .collect()
+}
+
+/// `pick` for every key of a map (a missing key is a removed entry).
+fn merge_map<K, V>(
+    field: &str,
+    base: &HashMap<K, V>,
+    mine: &HashMap<K, V>,
+    theirs: &HashMap<K, V>,
+    conflicts: &mut Vec<String>,
+) -> HashMap<K, V>
+where
+    K: Eq + Hash + Ord + Clone + std::fmt::Display,
+    V: PartialEq + Clone,
+{
+    let keys: BTreeSet<&K> = [base, mine, theirs].into_iter().flat_map(|m| m.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let value = pick(
+                &format!("{field}.{key}"),
+                &base.get(key),
+                &mine.get(key),
+                &theirs.get(key),
+                conflicts,
+            );
+            value.map(|v| (key.clone(), v.clone()))
+        })
+        .collect()
+}
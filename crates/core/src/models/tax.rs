@@ -0,0 +1,96 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::asset::Asset;
+
+/// Which buy lots a sale is matched against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CostBasisMethod {
+    /// First in, first out: the oldest lots are sold first
+    #[default]
+    Fifo,
+    /// Last in, first out: the newest lots are sold first
+    Lifo,
+    /// Highest in, first out: the lots with the highest unit cost are sold first
+    Hifo,
+}
+
+impl std::fmt::Display for CostBasisMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CostBasisMethod::Fifo => write!(f, "FIFO"),
+            CostBasisMethod::Lifo => write!(f, "LIFO"),
+            CostBasisMethod::Hifo => write!(f, "HIFO"),
+        }
+    }
+}
+
+/// Holding period of a sold lot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HoldingTerm {
+    /// Held for at most `Settings::long_term_holding_days`
+    Short,
+    /// Held for longer than `Settings::long_term_holding_days`
+    Long,
+}
+
+impl std::fmt::Display for HoldingTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HoldingTerm::Short => write!(f, "short"),
+            HoldingTerm::Long => write!(f, "long"),
+        }
+    }
+}
+
+/// One row of a tax lot report: the part of a sale matched against a
+/// single buy.
+///
+/// A sale covering several buys is split into one lot per buy. Amounts are
+/// in the report currency, rounded to 2 decimals; `gain` is the difference
+/// of the rounded amounts so every row adds up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxLot {
+    pub asset: Asset,
+
+    /// Date of the buy this lot comes from
+    pub acquired_date: NaiveDate,
+
+    /// Date of the sale
+    pub sold_date: NaiveDate,
+
+    /// Units sold from this lot
+    pub quantity: f64,
+
+    /// Sale value of `quantity` units
+    pub proceeds: f64,
+
+    /// Purchase value of `quantity` units
+    pub cost_basis: f64,
+
+    /// `proceeds - cost_basis`
+    pub gain: f64,
+
+    pub term: HoldingTerm,
+}
+
+impl TaxLot {
+    /// Header row of `to_csv_row`.
+    pub const CSV_HEADER: &'static str =
+        "asset,acquired_date,sold_date,quantity,proceeds,cost_basis,gain,term";
+
+    /// The lot as a CSV row (without a line break), columns as in `CSV_HEADER`.
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{:.2},{:.2},{:.2},{}",
+            self.asset.symbol,
+            self.acquired_date,
+            self.sold_date,
+            self.quantity,
+            self.proceeds,
+            self.cost_basis,
+            self.gain,
+            self.term,
+        )
+    }
+}
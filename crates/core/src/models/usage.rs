@@ -0,0 +1,58 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Requests sent to one price provider, to keep an eye on API quotas
+/// (e.g., Alpha Vantage's free tier allows 25 calls a day).
+///
+/// Only requests actually sent count; answers from the price cache or the
+/// response memo don't. The daily count restarts at UTC midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderUsage {
+    /// Provider name, as reported by `PriceProvider::name` (e.g., "CoinCap")
+    pub name: String,
+
+    /// Requests sent since the last UTC midnight
+    pub calls_today: u32,
+
+    /// Requests sent since usage was first recorded
+    pub calls_total: u64,
+
+    /// When the last request was sent
+    pub last_call: Option<DateTime<Utc>>,
+}
+
+impl ProviderUsage {
+    /// Usage of a provider that was never called.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            calls_today: 0,
+            calls_total: 0,
+            last_call: None,
+        }
+    }
+
+    /// Requests sent on the UTC day `today` (0 if the last one was sent earlier).
+    pub fn calls_on(&self, today: NaiveDate) -> u32 {
+        match self.last_call {
+            Some(last) if last.date_naive() == today => self.calls_today,
+            _ => 0,
+        }
+    }
+
+    /// Count a request sent at `now`, restarting the daily count on a new day.
+    pub fn record_call(&mut self, now: DateTime<Utc>) {
+        self.calls_today = self.calls_on(now.date_naive()).saturating_add(1);
+        self.calls_total = self.calls_total.saturating_add(1);
+        self.last_call = Some(now);
+    }
+
+    /// This usage as of `today`, with the daily count reset if no request
+    /// was sent that day.
+    pub fn as_of(&self, today: NaiveDate) -> Self {
+        Self {
+            calls_today: self.calls_on(today),
+            ..self.clone()
+        }
+    }
+}
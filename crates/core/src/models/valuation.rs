@@ -0,0 +1,99 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::analytics::PriceStatus;
+use super::asset::Asset;
+use super::snapshot::AssetValue;
+use crate::errors::CoreError;
+
+/// Portfolio value on a date, computed from every holding that could be priced.
+///
+/// Holdings whose price lookup failed are listed in `failed` instead of
+/// failing the whole valuation, so one unpriceable token doesn't hide the rest.
+#[derive(Debug, Clone)]
+pub struct PortfolioValuation {
+    /// Date the holdings were valued on
+    pub date: NaiveDate,
+
+    /// Currency of all values (e.g., "PLN")
+    pub currency: String,
+
+    /// Sum of the values in `assets` (failed holdings are not included)
+    pub total_value: f64,
+
+    /// Value of every priced holding, sorted by symbol
+    pub assets: Vec<AssetValue>,
+
+    /// Holdings that could not be valued, sorted by symbol
+    pub failed: Vec<FailedValuation>,
+}
+
+/// A holding `PortfolioValuation` could not price, with the reason.
+#[derive(Debug, Clone)]
+pub struct FailedValuation {
+    pub asset: Asset,
+
+    /// Amount held on the valuation date
+    pub amount: f64,
+
+    /// Why the holding could not be valued
+    pub error: CoreError,
+}
+
+impl PortfolioValuation {
+    /// Whether every holding was valued.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// An amount of money with its currency, so a value cached by the frontend
+/// can't be mistaken for one in another currency after the default
+/// currency changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Money {
+    pub amount: f64,
+
+    /// Currency of `amount` (e.g., "PLN")
+    pub currency: String,
+}
+
+/// Portfolio value from cached prices only (see
+/// `SavingsTracker::get_portfolio_value_cached`), flagging the holdings
+/// without a cached price on the valuation date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedValuation {
+    /// Date the holdings were valued on
+    pub date: NaiveDate,
+
+    /// Sum of the holdings' values
+    pub value: Money,
+
+    /// Value of every holding, sorted by symbol
+    pub holdings: Vec<CachedHoldingValue>,
+}
+
+/// One holding of a `CachedValuation`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedHoldingValue {
+    pub asset: Asset,
+
+    /// Amount held on the valuation date
+    pub amount: f64,
+
+    /// Value of the holding in the valuation's currency
+    pub value: f64,
+
+    /// Whether the price was cached for the date, or is an older, manual or
+    /// missing one
+    pub price_status: PriceStatus,
+}
+
+impl CachedValuation {
+    /// Whether every holding was valued with a price cached for the date.
+    pub fn is_fresh(&self) -> bool {
+        self.holdings
+            .iter()
+            .all(|h| h.price_status == PriceStatus::Fresh)
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fmt;
+
+use crate::errors::CoreError;
+
+/// A provider quote `PriceService` refused to use or cache.
+///
+/// Rejected quotes are not errors: the lookup goes on with the next
+/// provider, and the warning is kept for `SavingsTracker::get_recent_warnings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceWarning {
+    /// When the quote was rejected
+    pub at: DateTime<Utc>,
+
+    /// Provider that returned the quote (e.g., "CoinCap")
+    pub provider: String,
+
+    /// Uppercase symbol and quote currency of the price
+    pub symbol: String,
+    pub currency: String,
+
+    /// Date the price was for
+    pub date: NaiveDate,
+
+    /// The rejected price, in the unit prices are cached in
+    pub price: f64,
+
+    pub reason: PriceWarningReason,
+}
+
+/// Why a provider quote was rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceWarningReason {
+    /// Zero, negative or not a finite number
+    NotPositive,
+
+    /// More than `Settings::max_price_change_factor` times above or below
+    /// `reference`, a cached price for the same or a nearby day, and not
+    /// confirmed by another provider
+    SuspiciousChange { reference: f64 },
+}
+
+impl fmt::Display for PriceWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { provider, symbol, currency, date, price, .. } = self;
+        write!(f, "{provider} quoted {symbol}/{currency} at {price} on {date}: ")?;
+        match self.reason {
+            PriceWarningReason::NotPositive => write!(f, "not a positive price"),
+            PriceWarningReason::SuspiciousChange { reference } => {
+                write!(f, "too far from the cached {reference}, not confirmed")
+            }
+        }
+    }
+}
+
+/// An autosave that failed (see `SavingsTracker::set_autosave`).
+///
+/// The changes stay unsaved until the next autosave or an explicit save;
+/// the warning is kept for `SavingsTracker::get_autosave_warnings`.
+#[derive(Debug, Clone)]
+pub struct AutosaveWarning {
+    /// When the save was attempted
+    pub at: DateTime<Utc>,
+
+    /// File that couldn't be written
+    pub path: String,
+
+    pub error: CoreError,
+}
+
+impl fmt::Display for AutosaveWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Autosave to {} failed: {}", self.path, self.error)
+    }
+}
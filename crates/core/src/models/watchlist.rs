@@ -0,0 +1,31 @@
+use chrono::NaiveDate;
+
+use super::asset::Asset;
+use crate::errors::CoreError;
+
+/// Price of a watchlist asset on a date, with its recent change.
+///
+/// The changes are computed from cached prices only, so they are `None`
+/// until the earlier prices have been cached (e.g., by `prefetch_prices`).
+#[derive(Debug, Clone)]
+pub struct WatchlistQuote {
+    pub asset: Asset,
+
+    /// Date the asset was quoted on
+    pub date: NaiveDate,
+
+    /// Currency of the price (e.g., "PLN")
+    pub currency: String,
+
+    /// Price of one unit on `date`, `None` if the lookup failed
+    pub price: Option<f64>,
+
+    /// Change since the day before `date`, in percent
+    pub change_24h_pct: Option<f64>,
+
+    /// Change since a week before `date`, in percent
+    pub change_7d_pct: Option<f64>,
+
+    /// Why `price` could not be found
+    pub error: Option<CoreError>,
+}
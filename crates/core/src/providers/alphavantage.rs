@@ -1,42 +1,144 @@
 use async_trait::async_trait;
 use chrono::NaiveDate;
+use futures_util::lock::Mutex;
 use reqwest::Client;
 use serde::Deserialize;
-use std::collections::HashMap;
-#[cfg(not(target_arch = "wasm32"))]
+use serde_json::Value;
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::errors::CoreError;
 use crate::models::asset::AssetType;
 use crate::models::price::PricePoint;
+use super::http::{self, HttpClient, ProviderClient};
 use super::traits::PriceProvider;
 
 const BASE_URL: &str = "https://www.alphavantage.co/query";
 
-/// Alpha Vantage API provider for stock/equity prices.
+/// Alpha Vantage API provider for stock, FX and crypto prices.
 ///
 /// - **Free tier**: 25 requests/day (across ALL endpoints).
 /// - **Requires**: API key (set via settings as "alphavantage").
-/// - **Coverage**: 100k+ global equity symbols.
+/// - **Coverage**: 100k+ global equity symbols, physical currencies
+///   (`FX_DAILY`) and digital currencies (`DIGITAL_CURRENCY_DAILY`).
 /// - **Strategy**: Cache aggressively (24h), fetch daily data only.
+///   Requests are serialized through the provider, since the rate limit
+///   is shared by every endpoint of one key.
 ///
-/// Note: Returns prices in the stock's native currency (typically USD).
-/// Cross-currency conversion handled by CurrencyService.
+/// Registered after Frankfurter and CoinCap, so it serves as a fallback
+/// for fiat and crypto. Stock prices are in the stock's native currency
+/// (typically USD); cross-currency conversion handled by CurrencyService.
 pub struct AlphaVantageProvider {
-    client: Client,
+    client: ProviderClient,
     api_key: String,
+    /// Held for the duration of each request.
+    request_lock: Mutex<()>,
 }
 
 impl AlphaVantageProvider {
     pub fn new(api_key: String) -> Self {
-        let builder = Client::builder();
-        #[cfg(not(target_arch = "wasm32"))]
-        let builder = builder.timeout(Duration::from_secs(30));
         Self {
-            client: builder.build().unwrap_or_else(|_| Client::new()),
+            client: ProviderClient::new(),
             api_key,
+            request_lock: Mutex::new(()),
         }
     }
+
+    /// Fail requests that get no response within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_timeout(timeout);
+        self
+    }
+
+    /// Send requests through `client`, e.g. one shared by every provider
+    /// and configured with a proxy.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = self.client.with_client(client);
+        self
+    }
+
+    /// Send requests through `http` instead of reqwest, e.g. a stub
+    /// answering with recorded responses.
+    pub fn with_http_client(mut self, http: Arc<dyn HttpClient>) -> Self {
+        self.client = self.client.with_transport(http);
+        self
+    }
+
+    /// Parse a `GLOBAL_QUOTE` response into the latest stock price.
+    pub fn parse_global_quote(body: &str, symbol: &str) -> Result<f64, CoreError> {
+        let resp: GlobalQuoteResponse = parse_json(body, symbol)?;
+        check_information(resp.information.as_deref().or(resp.note.as_deref()))?;
+
+        let price_str = resp
+            .global_quote
+            .and_then(|q| q.price)
+            .ok_or_else(|| {
+                api_error(format!("No quote data for {symbol}. API limit may be exceeded."))
+            })?;
+        parse_price(&price_str, symbol)
+    }
+
+    /// Parse a `CURRENCY_EXCHANGE_RATE` response (fiat or crypto) into the rate.
+    pub fn parse_exchange_rate(body: &str, symbol: &str) -> Result<f64, CoreError> {
+        let resp: ExchangeRateResponse = parse_json(body, symbol)?;
+        check_information(resp.information.as_deref().or(resp.note.as_deref()))?;
+
+        let rate_str = resp
+            .rate
+            .and_then(|r| r.rate)
+            .ok_or_else(|| {
+                api_error(format!("No exchange rate for {symbol}. API limit may be exceeded."))
+            })?;
+        parse_price(&rate_str, symbol)
+    }
+
+    /// Parse a daily time series response into price points sorted by date.
+    ///
+    /// Handles all three endpoint families: `TIME_SERIES_DAILY`
+    /// ("Time Series (Daily)"), `FX_DAILY` ("Time Series FX (Daily)") and
+    /// `DIGITAL_CURRENCY_DAILY` ("Time Series (Digital Currency Daily)"),
+    /// whose close is "4. close" or, in older responses, "4a. close (`market`)".
+    pub fn parse_daily_series(
+        body: &str,
+        symbol: &str,
+        market: &str,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        let resp: Value = parse_json(body, symbol)?;
+        check_information(
+            resp.get("Information")
+                .or_else(|| resp.get("Note"))
+                .and_then(Value::as_str),
+        )?;
+
+        let series = resp
+            .as_object()
+            .and_then(|fields| {
+                fields
+                    .iter()
+                    .find(|(key, _)| key.starts_with("Time Series"))
+                    .and_then(|(_, series)| series.as_object())
+            })
+            .ok_or_else(|| {
+                api_error(format!("No time series data for {symbol}. API limit may be exceeded."))
+            })?;
+
+        let market_close = format!("4a. close ({})", market.to_uppercase());
+        let mut points: Vec<PricePoint> = series
+            .iter()
+            .filter_map(|(date_str, day)| {
+                let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()?;
+                let close = day
+                    .get("4. close")
+                    .or_else(|| day.get(&market_close))?
+                    .as_str()?;
+                let price: f64 = close.trim().parse().ok()?;
+                Some(PricePoint { date, price })
+            })
+            .collect();
+
+        points.sort_by_key(|p| p.date);
+        Ok(points)
+    }
 }
 
 // ── Alpha Vantage API response types ────────────────────────────────
@@ -45,6 +147,10 @@ impl AlphaVantageProvider {
 struct GlobalQuoteResponse {
     #[serde(rename = "Global Quote")]
     global_quote: Option<GlobalQuote>,
+    #[serde(rename = "Information")]
+    information: Option<String>,
+    #[serde(rename = "Note")]
+    note: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -54,15 +160,19 @@ struct GlobalQuote {
 }
 
 #[derive(Deserialize)]
-struct TimeSeriesResponse {
-    #[serde(rename = "Time Series (Daily)")]
-    time_series: Option<HashMap<String, DailyData>>,
+struct ExchangeRateResponse {
+    #[serde(rename = "Realtime Currency Exchange Rate")]
+    rate: Option<ExchangeRate>,
+    #[serde(rename = "Information")]
+    information: Option<String>,
+    #[serde(rename = "Note")]
+    note: Option<String>,
 }
 
 #[derive(Deserialize)]
-struct DailyData {
-    #[serde(rename = "4. close")]
-    close: String,
+struct ExchangeRate {
+    #[serde(rename = "5. Exchange Rate")]
+    rate: Option<String>,
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -72,121 +182,198 @@ impl PriceProvider for AlphaVantageProvider {
         "Alpha Vantage"
     }
 
+    fn id(&self) -> &str {
+        "alphavantage"
+    }
+
     fn supported_asset_types(&self) -> Vec<AssetType> {
-        vec![AssetType::Stock]
+        vec![AssetType::Stock, AssetType::Fiat, AssetType::Crypto]
     }
 
+    // Without an asset type, symbols are treated as stocks.
+
     async fn get_current_price(
         &self,
         symbol: &str,
-        _currency: &str,
+        currency: &str,
     ) -> Result<f64, CoreError> {
-        let resp: GlobalQuoteResponse = self
-            .client
-            .get(BASE_URL)
-            .query(&[
-                ("function", "GLOBAL_QUOTE"),
-                ("symbol", &symbol.to_uppercase()),
-                ("apikey", &self.api_key),
-            ])
-            .send()
-            .await?
-            .json()
+        self.get_current_price_for_asset_type(&AssetType::Stock, symbol, currency)
             .await
-            .map_err(|e| CoreError::Api {
-                provider: "Alpha Vantage".into(),
-                message: format!("Failed to parse quote for {symbol}: {e}"),
-            })?;
+    }
 
-        let price_str = resp
-            .global_quote
-            .and_then(|q| q.price)
-            .ok_or_else(|| CoreError::Api {
-                provider: "Alpha Vantage".into(),
-                message: format!("No quote data for {symbol}. API limit may be exceeded."),
-            })?;
+    async fn get_historical_price(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<f64, CoreError> {
+        self.get_historical_price_for_asset_type(&AssetType::Stock, symbol, currency, date)
+            .await
+    }
 
-        price_str.parse().map_err(|e| CoreError::Api {
-            provider: "Alpha Vantage".into(),
-            message: format!("Invalid price format for {symbol}: {e}"),
-        })
+    async fn get_price_range(
+        &self,
+        symbol: &str,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        self.get_price_range_for_asset_type(&AssetType::Stock, symbol, currency, from, to)
+            .await
     }
 
-    async fn get_historical_price(
+    async fn get_current_price_for_asset_type(
         &self,
+        asset_type: &AssetType,
         symbol: &str,
-        _currency: &str,
+        currency: &str,
+    ) -> Result<f64, CoreError> {
+        let symbol = symbol.to_uppercase();
+        match asset_type {
+            AssetType::Stock => {
+                let body = self
+                    .fetch(&[("function", "GLOBAL_QUOTE"), ("symbol", &symbol)])
+                    .await?;
+                Self::parse_global_quote(&body, &symbol)
+            }
+            AssetType::Fiat | AssetType::Crypto => {
+                let body = self
+                    .fetch(&[
+                        ("function", "CURRENCY_EXCHANGE_RATE"),
+                        ("from_currency", &symbol),
+                        ("to_currency", &currency.to_uppercase()),
+                    ])
+                    .await?;
+                Self::parse_exchange_rate(&body, &symbol)
+            }
+            AssetType::Metal => Err(CoreError::NoProvider(asset_type.to_string())),
+        }
+    }
+
+    async fn get_historical_price_for_asset_type(
+        &self,
+        asset_type: &AssetType,
+        symbol: &str,
+        currency: &str,
         date: NaiveDate,
     ) -> Result<f64, CoreError> {
         // Fetch daily time series and find the specific date
-        let time_series = self.fetch_daily_series(symbol).await?;
+        let points = self.fetch_daily_series(asset_type, symbol, currency).await?;
 
-        let date_str = date.format("%Y-%m-%d").to_string();
-        time_series
-            .get(&date_str)
-            .and_then(|d| d.close.parse().ok())
+        points
+            .iter()
+            .find(|p| p.date == date)
+            .map(|p| p.price)
             .ok_or_else(|| CoreError::PriceNotAvailable {
                 symbol: symbol.to_string(),
-                currency: "USD".to_string(),
+                currency: currency.to_uppercase(),
                 date: date.to_string(),
             })
     }
 
-    async fn get_price_range(
+    async fn get_price_range_for_asset_type(
         &self,
+        asset_type: &AssetType,
         symbol: &str,
-        _currency: &str,
+        currency: &str,
         from: NaiveDate,
         to: NaiveDate,
     ) -> Result<Vec<PricePoint>, CoreError> {
-        let time_series = self.fetch_daily_series(symbol).await?;
-
-        let mut points: Vec<PricePoint> = time_series
-            .iter()
-            .filter_map(|(date_str, data)| {
-                let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()?;
-                if date >= from && date <= to {
-                    let price: f64 = data.close.parse().ok()?;
-                    Some(PricePoint { date, price })
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        points.sort_by_key(|p| p.date);
+        let mut points = self.fetch_daily_series(asset_type, symbol, currency).await?;
+        points.retain(|p| p.date >= from && p.date <= to);
         Ok(points)
     }
 }
 
 impl AlphaVantageProvider {
-    /// Fetch the daily time series for a stock symbol.
-    /// Returns compact data (last 100 trading days).
+    /// Fetch the daily series of the endpoint matching `asset_type`.
+    /// Returns compact data (last 100 days) where the endpoint supports it.
     async fn fetch_daily_series(
         &self,
+        asset_type: &AssetType,
         symbol: &str,
-    ) -> Result<HashMap<String, DailyData>, CoreError> {
-        let resp: TimeSeriesResponse = self
-            .client
-            .get(BASE_URL)
-            .query(&[
-                ("function", "TIME_SERIES_DAILY"),
-                ("symbol", &symbol.to_uppercase()),
-                ("outputsize", "compact"),
-                ("apikey", &self.api_key),
-            ])
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "Alpha Vantage".into(),
-                message: format!("Failed to parse time series for {symbol}: {e}"),
-            })?;
+        currency: &str,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        let symbol = symbol.to_uppercase();
+        let currency = currency.to_uppercase();
+        let body = match asset_type {
+            AssetType::Stock => {
+                self.fetch(&[
+                    ("function", "TIME_SERIES_DAILY"),
+                    ("symbol", &symbol),
+                    ("outputsize", "compact"),
+                ])
+                .await?
+            }
+            AssetType::Fiat => {
+                self.fetch(&[
+                    ("function", "FX_DAILY"),
+                    ("from_symbol", &symbol),
+                    ("to_symbol", &currency),
+                    ("outputsize", "compact"),
+                ])
+                .await?
+            }
+            AssetType::Crypto => {
+                self.fetch(&[
+                    ("function", "DIGITAL_CURRENCY_DAILY"),
+                    ("symbol", &symbol),
+                    ("market", &currency),
+                ])
+                .await?
+            }
+            AssetType::Metal => return Err(CoreError::NoProvider(asset_type.to_string())),
+        };
+        Self::parse_daily_series(&body, &symbol, &currency)
+    }
+
+    /// Send one request (serialized with all other requests of this provider)
+    /// and return the response body.
+    async fn fetch(&self, query: &[(&str, &str)]) -> Result<String, CoreError> {
+        let _guard = self.request_lock.lock().await;
+        let query: Vec<(&str, &str)> =
+            query.iter().copied().chain([("apikey", self.api_key.as_str())]).collect();
+        let url = http::url(BASE_URL, &query)?;
+        self.client.get_text_with_key("Alpha Vantage", &url, &[]).await
+    }
+}
+
+fn api_error(message: String) -> CoreError {
+    CoreError::Api {
+        provider: "Alpha Vantage".into(),
+        status: None,
+        message,
+    }
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(body: &str, symbol: &str) -> Result<T, CoreError> {
+    serde_json::from_str(body)
+        .map_err(|e| api_error(format!("Failed to parse response for {symbol}: {e}")))
+}
+
+fn parse_price(price: &str, symbol: &str) -> Result<f64, CoreError> {
+    price
+        .trim()
+        .parse()
+        .map_err(|e| api_error(format!("Invalid price format for {symbol}: {e}")))
+}
 
-        resp.time_series.ok_or_else(|| CoreError::Api {
+/// Alpha Vantage reports key and quota problems as HTTP 200 with an
+/// "Information" (or older "Note") message instead of an error status.
+fn check_information(information: Option<&str>) -> Result<(), CoreError> {
+    let Some(message) = information else {
+        return Ok(());
+    };
+    let lower = message.to_lowercase();
+    if lower.contains("apikey is invalid") || lower.contains("invalid api key") {
+        return Err(CoreError::InvalidApiKey {
             provider: "Alpha Vantage".into(),
-            message: format!("No time series data for {symbol}. API limit may be exceeded."),
-        })
+        });
     }
+    // Anything else in this field is the daily/minute rate-limit notice.
+    Err(CoreError::Api {
+        provider: "Alpha Vantage".into(),
+        status: Some(429),
+        message: message.to_string(),
+    })
 }
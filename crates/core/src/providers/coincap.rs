@@ -4,12 +4,13 @@ use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::sync::Mutex;
-#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::errors::CoreError;
 use crate::models::asset::AssetType;
 use crate::models::price::PricePoint;
+use super::http::{self, HttpClient, ProviderClient};
 use super::traits::PriceProvider;
 
 const BASE_URL: &str = "https://api.coincap.io/v2";
@@ -21,16 +22,26 @@ const BASE_URL: &str = "https://api.coincap.io/v2";
 /// - **Endpoints**: `/assets/{id}`, `/assets/{id}/history`, `/assets?search={symbol}`
 ///
 /// Note: CoinCap uses lowercase ids like "bitcoin", "ethereum".
-/// We map common symbols (BTC → bitcoin) and dynamically resolve unknown ones.
+/// We map common symbols (BTC → bitcoin) and dynamically resolve unknown ones;
+/// resolved ids are reported via `learned_symbol_ids` so they can be persisted.
 pub struct CoinCapProvider {
-    client: Client,
+    client: ProviderClient,
     /// Map from uppercase symbol (BTC) to CoinCap asset id (bitcoin).
-    /// Seeded with common mappings, extended at runtime via dynamic search.
+    /// Seeded with common mappings and overrides, extended at runtime via dynamic search.
     symbol_map: Mutex<HashMap<String, String>>,
+    /// Mappings found via dynamic search, to be persisted by the caller.
+    learned: Mutex<HashMap<String, String>>,
 }
 
 impl CoinCapProvider {
     pub fn new() -> Self {
+        Self::with_symbol_overrides(&HashMap::new())
+    }
+
+    /// Create a provider with extra symbol → CoinCap id mappings
+    /// (e.g., from `Settings::symbol_overrides`), which take precedence
+    /// over the built-in ones.
+    pub fn with_symbol_overrides(overrides: &HashMap<String, String>) -> Self {
         let mut symbol_map = HashMap::new();
         // Pre-populate common mappings
         let common = vec![
@@ -78,16 +89,37 @@ impl CoinCapProvider {
         for (sym, id) in common {
             symbol_map.insert(sym.to_string(), id.to_string());
         }
+        for (sym, id) in overrides {
+            symbol_map.insert(sym.to_uppercase(), id.clone());
+        }
 
-        let builder = Client::builder();
-        #[cfg(not(target_arch = "wasm32"))]
-        let builder = builder.timeout(Duration::from_secs(30));
         Self {
-            client: builder.build().unwrap_or_else(|_| Client::new()),
+            client: ProviderClient::new(),
             symbol_map: Mutex::new(symbol_map),
+            learned: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Fail requests that get no response within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_timeout(timeout);
+        self
+    }
+
+    /// Send requests through `client`, e.g. one shared by every provider
+    /// and configured with a proxy.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = self.client.with_client(client);
+        self
+    }
+
+    /// Send requests through `http` instead of reqwest, e.g. a stub
+    /// answering with recorded responses.
+    pub fn with_http_client(mut self, http: Arc<dyn HttpClient>) -> Self {
+        self.client = self.client.with_transport(http);
+        self
+    }
+
     /// Resolve a symbol like "BTC" to a CoinCap ID like "bitcoin".
     /// Checks the static map first.
     pub fn resolve_id(&self, symbol: &str) -> String {
@@ -99,7 +131,7 @@ impl CoinCapProvider {
     }
 
     /// Dynamically resolve a symbol by searching the CoinCap API.
-    /// Caches the result for future lookups.
+    /// Caches the result for future lookups and records it in `learned_symbol_ids`.
     async fn resolve_id_dynamic(&self, symbol: &str) -> Result<String, CoreError> {
         let upper = symbol.to_uppercase();
 
@@ -111,40 +143,55 @@ impl CoinCapProvider {
             }
         }
 
-        // Search CoinCap API: /assets?search={symbol}&limit=5
-        let url = format!("{BASE_URL}/assets?search={upper}&limit=5");
-        let resp: AssetsSearchResponse = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "CoinCap".into(),
-                message: format!("Failed to search for {upper}: {e}"),
-            })?;
-
-        // Find the asset whose symbol matches (case-insensitive)
-        let matched = resp
-            .data
-            .iter()
-            .find(|a| a.symbol.to_uppercase() == upper)
-            .ok_or_else(|| CoreError::Api {
-                provider: "CoinCap".into(),
-                message: format!("No CoinCap asset found for symbol {upper}"),
-            })?;
-
-        let id = matched.id.clone();
+        // Search CoinCap API: /assets?search={symbol}&limit=10
+        let url = http::url(
+            &format!("{BASE_URL}/assets"),
+            &[("search", upper.as_str()), ("limit", "10")],
+        )?;
+        let body = self.client.get_text("CoinCap", &url, &[]).await?;
+        let id = Self::parse_search_response(&body, &upper)?;
 
         // Cache for next time
         {
             let mut map = self.symbol_map.lock().unwrap_or_else(|e| e.into_inner());
-            map.insert(upper, id.clone());
+            map.insert(upper.clone(), id.clone());
+        }
+        {
+            let mut learned = self.learned.lock().unwrap_or_else(|e| e.into_inner());
+            learned.insert(upper, id.clone());
         }
 
         Ok(id)
     }
+
+    /// Pick the CoinCap id for `symbol` from an `/assets?search=` response:
+    /// the best-ranked asset whose symbol matches exactly (case-insensitive).
+    /// Name-only matches are ignored, so an unmatched symbol is an error
+    /// rather than a guess.
+    pub fn parse_search_response(body: &str, symbol: &str) -> Result<String, CoreError> {
+        let resp: AssetsSearchResponse = serde_json::from_str(body)
+            .map_err(|e| CoreError::Api {
+                provider: "CoinCap".into(),
+                status: None,
+                message: format!("Failed to search for {symbol}: {e}"),
+            })?;
+
+        let upper = symbol.to_uppercase();
+        resp.data
+            .into_iter()
+            .filter(|a| a.symbol.to_uppercase() == upper)
+            .min_by_key(|a| {
+                a.rank
+                    .as_deref()
+                    .and_then(|r| r.parse::<u32>().ok())
+                    .unwrap_or(u32::MAX)
+            })
+            .map(|a| a.id)
+            .ok_or_else(|| CoreError::SymbolNotMapped {
+                provider: "CoinCap".into(),
+                symbol: upper,
+            })
+    }
 }
 
 impl Default for CoinCapProvider {
@@ -187,6 +234,7 @@ struct AssetsSearchResponse {
 struct AssetSearchEntry {
     id: String,
     symbol: String,
+    rank: Option<String>,
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -196,10 +244,18 @@ impl PriceProvider for CoinCapProvider {
         "CoinCap"
     }
 
+    fn id(&self) -> &str {
+        "coincap"
+    }
+
     fn supported_asset_types(&self) -> Vec<AssetType> {
         vec![AssetType::Crypto]
     }
 
+    fn learned_symbol_ids(&self) -> HashMap<String, String> {
+        self.learned.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
     async fn get_current_price(
         &self,
         symbol: &str,
@@ -208,28 +264,22 @@ impl PriceProvider for CoinCapProvider {
         let id = self.resolve_id_dynamic(symbol).await?;
         let url = format!("{BASE_URL}/assets/{id}");
 
-        let resp: AssetResponse = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "CoinCap".into(),
-                message: format!("Failed to parse response for {symbol}: {e}"),
-            })?;
+        let body = self.client.get_text("CoinCap", &url, &[]).await?;
+        let resp: AssetResponse =
+            http::parse_json("CoinCap", &body, &format!("response for {symbol}"))?;
 
         let price_usd: f64 = resp
             .data
             .price_usd
             .ok_or_else(|| CoreError::Api {
                 provider: "CoinCap".into(),
+                status: None,
                 message: format!("No price data for {symbol}"),
             })?
             .parse()
             .map_err(|e| CoreError::Api {
                 provider: "CoinCap".into(),
+                status: None,
                 message: format!("Invalid price format for {symbol}: {e}"),
             })?;
 
@@ -261,17 +311,9 @@ impl PriceProvider for CoinCapProvider {
             "{BASE_URL}/assets/{id}/history?interval=d1&start={start}&end={end}"
         );
 
-        let resp: HistoryResponse = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "CoinCap".into(),
-                message: format!("Failed to parse history for {symbol}: {e}"),
-            })?;
+        let body = self.client.get_text("CoinCap", &url, &[]).await?;
+        let resp: HistoryResponse =
+            http::parse_json("CoinCap", &body, &format!("history for {symbol}"))?;
 
         let price_usd: f64 = resp
             .data
@@ -285,6 +327,7 @@ impl PriceProvider for CoinCapProvider {
             .parse()
             .map_err(|e| CoreError::Api {
                 provider: "CoinCap".into(),
+                status: None,
                 message: format!("Invalid price format: {e}"),
             })?;
 
@@ -314,17 +357,9 @@ impl PriceProvider for CoinCapProvider {
             "{BASE_URL}/assets/{id}/history?interval=d1&start={start}&end={end}"
         );
 
-        let resp: HistoryResponse = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "CoinCap".into(),
-                message: format!("Failed to parse history range for {symbol}: {e}"),
-            })?;
+        let body = self.client.get_text("CoinCap", &url, &[]).await?;
+        let resp: HistoryResponse =
+            http::parse_json("CoinCap", &body, &format!("history range for {symbol}"))?;
 
         let points: Vec<PricePoint> = resp
             .data
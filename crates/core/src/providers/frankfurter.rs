@@ -3,38 +3,102 @@ use chrono::NaiveDate;
 use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
-#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::errors::CoreError;
 use crate::models::asset::AssetType;
 use crate::models::price::PricePoint;
+use super::http::{self, HttpClient, ProviderClient};
 use super::traits::PriceProvider;
 
 const BASE_URL: &str = "https://api.frankfurter.dev/v1";
 
+/// Longest date range (inclusive, in days) requested from `/{start}..{end}` at once.
+pub const MAX_TIMESERIES_DAYS: i64 = 365;
+
+/// Days fetched before a range's start to find the rate in effect on its first day.
+const LOOKBACK_DAYS: i64 = 7;
+
 /// Frankfurter API provider for fiat currency exchange rates.
 ///
 /// - **Free**: No API key, no rate limits, open-source.
 /// - **Source**: European Central Bank (ECB) data.
 /// - **Coverage**: ~30+ currencies (EUR, USD, PLN, GBP, JPY, etc.)
 /// - **Endpoints**: `/latest`, `/{date}`, `/{start}..{end}`
+/// - **Ranges**: one `/{start}..{end}` request per `MAX_TIMESERIES_DAYS`.
+///   Rates are published on ECB working days only; the range result starts
+///   with the last quote at or before `from`, and `PriceService` carries it
+///   forward over weekends and holidays.
 ///
 /// Note: Frankfurter uses EUR as the base by default.
 /// All rates are relative to the specified base currency.
 pub struct FrankfurterProvider {
-    client: Client,
+    client: ProviderClient,
 }
 
 impl FrankfurterProvider {
     pub fn new() -> Self {
-        let builder = Client::builder();
-        #[cfg(not(target_arch = "wasm32"))]
-        let builder = builder.timeout(Duration::from_secs(30));
         Self {
-            client: builder.build().unwrap_or_else(|_| Client::new()),
+            client: ProviderClient::new(),
         }
     }
+
+    /// Fail requests that get no response within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_timeout(timeout);
+        self
+    }
+
+    /// Send requests through `client`, e.g. one shared by every provider
+    /// and configured with a proxy.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = self.client.with_client(client);
+        self
+    }
+
+    /// Send requests through `http` instead of reqwest, e.g. a stub
+    /// answering with recorded responses.
+    pub fn with_http_client(mut self, http: Arc<dyn HttpClient>) -> Self {
+        self.client = self.client.with_transport(http);
+        self
+    }
+}
+
+impl FrankfurterProvider {
+    /// Split `from..=to` into consecutive windows of at most `MAX_TIMESERIES_DAYS` days.
+    pub fn timeseries_windows(from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+        http::date_windows(from, to, MAX_TIMESERIES_DAYS)
+    }
+
+    /// Parse a `/{start}..{end}` response (`{"rates": {date: {currency: rate}}}`)
+    /// into the `base` → `target` rates, sorted by date.
+    pub fn parse_timeseries(
+        body: &str,
+        base: &str,
+        target: &str,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        let target = target.to_uppercase();
+        let resp: TimeSeriesResponse = serde_json::from_str(body)
+            .map_err(|e| CoreError::Api {
+                provider: "Frankfurter".into(),
+                status: None,
+                message: format!("Failed to parse time series for {base}/{target}: {e}"),
+            })?;
+
+        let mut points: Vec<PricePoint> = resp
+            .rates
+            .iter()
+            .filter_map(|(date_str, rates)| {
+                let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()?;
+                let price = rates.get(&target)?;
+                Some(PricePoint { date, price: *price })
+            })
+            .collect();
+
+        points.sort_by_key(|p| p.date);
+        Ok(points)
+    }
 }
 
 impl Default for FrankfurterProvider {
@@ -62,6 +126,10 @@ impl PriceProvider for FrankfurterProvider {
         "Frankfurter"
     }
 
+    fn id(&self) -> &str {
+        "frankfurter"
+    }
+
     fn supported_asset_types(&self) -> Vec<AssetType> {
         vec![AssetType::Fiat]
     }
@@ -81,20 +149,13 @@ impl PriceProvider for FrankfurterProvider {
 
         let url = format!("{BASE_URL}/latest?base={base}&symbols={target}");
 
-        let resp: RatesResponse = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "Frankfurter".into(),
-                message: format!("Failed to parse response for {base}/{target}: {e}"),
-            })?;
+        let body = self.client.get_text("Frankfurter", &url, &[]).await?;
+        let resp: RatesResponse =
+            http::parse_json("Frankfurter", &body, &format!("response for {base}/{target}"))?;
 
         resp.rates.get(&target).copied().ok_or_else(|| CoreError::Api {
             provider: "Frankfurter".into(),
+            status: None,
             message: format!("No rate found for {base} → {target}"),
         })
     }
@@ -115,17 +176,9 @@ impl PriceProvider for FrankfurterProvider {
         let date_str = date.format("%Y-%m-%d");
         let url = format!("{BASE_URL}/{date_str}?base={base}&symbols={target}");
 
-        let resp: RatesResponse = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "Frankfurter".into(),
-                message: format!("Failed to parse historical rate for {base}/{target} on {date}: {e}"),
-            })?;
+        let body = self.client.get_text("Frankfurter", &url, &[]).await?;
+        let what = format!("historical rate for {base}/{target} on {date}");
+        let resp: RatesResponse = http::parse_json("Frankfurter", &body, &what)?;
 
         resp.rates.get(&target).copied().ok_or_else(|| CoreError::PriceNotAvailable {
             symbol: symbol.to_string(),
@@ -158,33 +211,26 @@ impl PriceProvider for FrankfurterProvider {
             return Ok(points);
         }
 
-        let from_str = from.format("%Y-%m-%d");
-        let to_str = to.format("%Y-%m-%d");
-        let url = format!("{BASE_URL}/{from_str}..{to_str}?base={base}&symbols={target}");
-
-        let resp: TimeSeriesResponse = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "Frankfurter".into(),
-                message: format!("Failed to parse time series for {base}/{target}: {e}"),
-            })?;
-
-        let mut points: Vec<PricePoint> = resp
-            .rates
-            .iter()
-            .filter_map(|(date_str, rates)| {
-                let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()?;
-                let price = rates.get(&target)?;
-                Some(PricePoint { date, price: *price })
-            })
-            .collect();
+        // Start a week early so the rate in effect on `from` (e.g., the
+        // Friday before a weekend) is included
+        let start = from - chrono::Duration::days(LOOKBACK_DAYS);
+        let mut points = Vec::new();
+        for (window_from, window_to) in Self::timeseries_windows(start, to) {
+            let url = format!(
+                "{BASE_URL}/{}..{}?base={base}&symbols={target}",
+                window_from.format("%Y-%m-%d"),
+                window_to.format("%Y-%m-%d"),
+            );
+            let body = self.client.get_text("Frankfurter", &url, &[]).await?;
+            points.extend(Self::parse_timeseries(&body, &base, &target)?);
+        }
 
         points.sort_by_key(|p| p.date);
+        points.dedup_by_key(|p| p.date);
+        // Keep the last quote at or before `from`, then everything in range
+        let first = points.partition_point(|p| p.date <= from).saturating_sub(1);
+        points.drain(..first);
+        points.retain(|p| p.date <= to);
         Ok(points)
     }
 }
@@ -0,0 +1,365 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT};
+use reqwest::{Client, RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::errors::CoreError;
+use crate::models::settings::Settings;
+
+/// Timeout of provider requests unless configured otherwise
+/// (see `Settings::provider_timeout_secs`).
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Product token of the `User-Agent` of provider requests.
+const USER_AGENT: &str = concat!("savings-tracker/", env!("CARGO_PKG_VERSION"));
+
+/// `User-Agent` of provider requests: the crate name and version, plus
+/// `Settings::contact_email` so API operators can reach the user
+/// (e.g., `savings-tracker/0.1.0 (+mailto:me@example.com)`).
+pub fn user_agent(settings: &Settings) -> String {
+    match settings.contact_email.as_deref() {
+        Some(email) => format!("{USER_AGENT} (+mailto:{email})"),
+        None => USER_AGENT.to_string(),
+    }
+}
+
+/// Check that `email` can serve as `Settings::contact_email`: an address
+/// of printable ASCII (it is sent in a header) with a local part and a domain.
+pub(crate) fn validate_contact_email(email: &str) -> Result<(), CoreError> {
+    let valid = email.bytes().all(|b| b.is_ascii_graphic())
+        && email
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(CoreError::ValidationError(format!("Invalid contact email {email:?}")))
+    }
+}
+
+/// Check that `url` can serve as `Settings::proxy_url`: an absolute
+/// `http` or `https` URL with a host.
+pub(crate) fn validate_proxy_url(url: &str) -> Result<(), CoreError> {
+    let invalid = |reason: String| {
+        CoreError::ValidationError(format!("Invalid proxy URL {url:?}: {reason}"))
+    };
+    let parsed = Url::parse(url.trim()).map_err(|e| invalid(e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(invalid(format!("unsupported scheme {:?}", parsed.scheme())));
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(invalid("missing host".into()));
+    }
+    Ok(())
+}
+
+/// The proxy of `settings`, if one is set, bypassed for `Settings::no_proxy`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn proxy(settings: &Settings) -> Result<Option<reqwest::Proxy>, CoreError> {
+    let Some(url) = settings.proxy_url.as_deref() else {
+        return Ok(None);
+    };
+    validate_proxy_url(url)?;
+    let proxy = reqwest::Proxy::all(url.trim())
+        .map_err(|e| CoreError::ValidationError(format!("Invalid proxy URL {url:?}: {e}")))?;
+    let no_proxy = reqwest::NoProxy::from_string(&settings.no_proxy.join(","));
+    Ok(Some(proxy.no_proxy(no_proxy)))
+}
+
+/// Client shared by the providers of a registry, also usable by custom
+/// providers. Requests ask for JSON (`Accept: application/json`), identify
+/// themselves with `user_agent`, go through `Settings::proxy_url` and time
+/// out after `Settings::provider_timeout_secs` unless a provider overrides it.
+/// Browsers send their own `User-Agent` and use the system proxy, so on WASM
+/// only the `Accept` header applies.
+pub fn shared_client(settings: &Settings) -> Result<Client, CoreError> {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    let builder = Client::builder().default_headers(headers);
+    #[cfg(not(target_arch = "wasm32"))]
+    let builder = {
+        if let Some(email) = settings.contact_email.as_deref() {
+            validate_contact_email(email)?;
+        }
+        let mut builder = builder
+            .user_agent(user_agent(settings))
+            .timeout(Duration::from_secs(settings.provider_timeout_secs.max(1)));
+        if let Some(proxy) = proxy(settings)? {
+            builder = builder.proxy(proxy);
+        }
+        builder
+    };
+    #[cfg(target_arch = "wasm32")]
+    let _ = settings;
+    builder
+        .build()
+        .map_err(|e| CoreError::Network(format!("Failed to build HTTP client: {e}")))
+}
+
+/// Response to a GET request of an `HttpClient`, whatever its status.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Header names (lowercase) and values, in the order received.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// A response with `status` and `body` and no headers.
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self { status, headers: Vec::new(), body: body.into() }
+    }
+
+    /// Add a header, e.g. `("set-cookie", "A3=...")`.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_lowercase(), value.to_string()));
+        self
+    }
+
+    /// Values of every header named `name` (case-insensitive).
+    pub fn header_values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Transport of the reqwest-based providers, swappable so their parsing
+/// can be tested against canned responses (see `testing::StubHttpClient`).
+///
+/// Implemented for `reqwest::Client`; providers take one through
+/// `with_http_client`. `provider` names the provider in errors.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait HttpClient: Send + Sync {
+    /// Send a GET request to `url` (query string included) with extra
+    /// `headers` and return the response, whatever its status.
+    ///
+    /// Fails only if no response arrives: `CoreError::timed_out` on
+    /// timeout, `CoreError::Network` otherwise.
+    async fn get(
+        &self,
+        provider: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, CoreError>;
+
+    /// GET `url` and return the body of a successful response.
+    ///
+    /// Non-2xx statuses become `CoreError::Api` with `status` set, so
+    /// 429/5xx are classified as transient and a 401/403 of a provider
+    /// without an API key (e.g., a firewall rejecting the request) lets the
+    /// next provider answer.
+    async fn get_text(
+        &self,
+        provider: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<String, CoreError> {
+        let response = self.get(provider, url, headers).await?;
+        check_status(provider, response.status, false)?;
+        Ok(response.body)
+    }
+
+    /// `get_text` for a request authenticated with the provider's API key:
+    /// 401/403 become `CoreError::InvalidApiKey`, which stops the fallback
+    /// to other providers.
+    async fn get_text_with_key(
+        &self,
+        provider: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<String, CoreError> {
+        let response = self.get(provider, url, headers).await?;
+        check_status(provider, response.status, true)?;
+        Ok(response.body)
+    }
+
+    /// GET `url` and parse the body of a successful response as JSON; a
+    /// malformed body becomes `CoreError::Api` without status.
+    async fn get_json(
+        &self,
+        provider: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<serde_json::Value, CoreError> {
+        let body = self.get_text(provider, url, headers).await?;
+        parse_json(provider, &body, "response")
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpClient for Client {
+    async fn get(
+        &self,
+        provider: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, CoreError> {
+        send(self.get(url), provider, headers).await
+    }
+}
+
+/// Parse `body` into `T`, reporting a malformed body (`what`, e.g.
+/// "history for BTC") as `CoreError::Api` of `provider`.
+pub(crate) fn parse_json<T: DeserializeOwned>(
+    provider: &str,
+    body: &str,
+    what: &str,
+) -> Result<T, CoreError> {
+    serde_json::from_str(body).map_err(|e| CoreError::Api {
+        provider: provider.to_string(),
+        status: None,
+        message: format!("Failed to parse {what}: {e}"),
+    })
+}
+
+/// `base` with `query` appended, percent-encoded.
+pub(crate) fn url(base: &str, query: &[(&str, &str)]) -> Result<String, CoreError> {
+    Url::parse_with_params(base, query)
+        .map(String::from)
+        .map_err(|e| CoreError::Network(format!("Invalid URL {base}: {e}")))
+}
+
+/// Split `from..=to` into consecutive windows of at most `max_days` days,
+/// for APIs that limit the range one request may span.
+pub(crate) fn date_windows(
+    from: NaiveDate,
+    to: NaiveDate,
+    max_days: i64,
+) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut windows = Vec::new();
+    let mut start = from;
+    while start <= to {
+        let end = (start + chrono::Duration::days(max_days - 1)).min(to);
+        windows.push((start, end));
+        start = end + chrono::Duration::days(1);
+    }
+    windows
+}
+
+/// Turn a non-2xx `status` into a `CoreError` (see `HttpClient::get_text`);
+/// 401/403 mean a bad key only if the request sent one (`keyed`).
+fn check_status(provider: &str, status: u16, keyed: bool) -> Result<(), CoreError> {
+    match status {
+        200..=299 => Ok(()),
+        401 | 403 if keyed => Err(CoreError::InvalidApiKey {
+            provider: provider.to_string(),
+        }),
+        code => Err(CoreError::Api {
+            provider: provider.to_string(),
+            status: Some(code),
+            message: format!("HTTP {code}"),
+        }),
+    }
+}
+
+/// Send `request` with extra `headers`, mapping a timeout to
+/// `CoreError::timed_out`.
+async fn send(
+    mut request: RequestBuilder,
+    provider: &str,
+    headers: &[(&str, &str)],
+) -> Result<HttpResponse, CoreError> {
+    #[cfg(feature = "tracing")]
+    let started = std::time::Instant::now();
+
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = request.send().await.map_err(|e| {
+        if e.is_timeout() {
+            CoreError::timed_out(provider)
+        } else {
+            CoreError::from(e)
+        }
+    })?;
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        provider,
+        url = %redacted_url(response.url()),
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        "provider HTTP response"
+    );
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let body = response.text().await.map_err(|e| CoreError::Api {
+        provider: provider.to_string(),
+        status: Some(status),
+        message: format!("Failed to read response: {e}"),
+    })?;
+    Ok(HttpResponse { status, headers, body })
+}
+
+/// Transport of a provider: a reqwest client — its own, or one shared with
+/// other providers — with the timeout of each request, unless replaced by
+/// another `HttpClient`.
+#[derive(Clone)]
+pub(crate) struct ProviderClient {
+    client: Client,
+    timeout: Duration,
+    transport: Option<Arc<dyn HttpClient>>,
+}
+
+impl ProviderClient {
+    /// A client without proxy and with `DEFAULT_TIMEOUT`.
+    pub(crate) fn new() -> Self {
+        Self {
+            client: shared_client(&Settings::default()).unwrap_or_else(|_| Client::new()),
+            timeout: DEFAULT_TIMEOUT,
+            transport: None,
+        }
+    }
+
+    /// Send requests through `client` instead.
+    pub(crate) fn with_client(self, client: Client) -> Self {
+        Self { client, transport: None, ..self }
+    }
+
+    /// Fail requests that get no response within `timeout`.
+    pub(crate) fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Send requests through `transport` instead of reqwest.
+    pub(crate) fn with_transport(self, transport: Arc<dyn HttpClient>) -> Self {
+        Self { transport: Some(transport), ..self }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpClient for ProviderClient {
+    async fn get(
+        &self,
+        provider: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, CoreError> {
+        match &self.transport {
+            Some(transport) => transport.get(provider, url, headers).await,
+            None => send(self.client.get(url).timeout(self.timeout), provider, headers).await,
+        }
+    }
+}
+
+/// URL with the query string removed — query parameters may carry API keys.
+#[cfg(feature = "tracing")]
+fn redacted_url(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    url.set_query(None);
+    url.to_string()
+}
@@ -3,27 +3,33 @@ use chrono::NaiveDate;
 use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
-#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::errors::CoreError;
 use crate::models::asset::AssetType;
 use crate::models::price::PricePoint;
+use super::http::{self, HttpClient, ProviderClient};
 use super::traits::PriceProvider;
 
 const BASE_URL: &str = "https://api.metals.dev/v1";
 
+/// Longest date range (inclusive, in days) one `/timeseries` request may span.
+pub const MAX_TIMESERIES_DAYS: i64 = 30;
+
 /// metals.dev API provider for precious metals prices.
 ///
 /// - **Free tier**: 100 requests/month (no credit card required).
 /// - **Requires**: API key (set via settings as "metals_dev").
 /// - **Coverage**: Gold (XAU), Silver (XAG), Platinum (XPT), Palladium (XPD), etc.
 /// - **Strategy**: Cache aggressively (24h+), only fetch when truly needed.
+///   Ranges use `/timeseries`, split into windows of `MAX_TIMESERIES_DAYS`.
 ///
-/// Note: metals.dev returns prices in USD. Cross-currency conversion
-/// is handled by CurrencyService using Frankfurter.
+/// Note: metals.dev returns prices in USD per troy ounce (requested explicitly
+/// with `unit=toz`). Unit conversion is handled by PriceService, cross-currency
+/// conversion by CurrencyService using Frankfurter.
 pub struct MetalsDevProvider {
-    client: Client,
+    client: ProviderClient,
     api_key: String,
     /// Map from our symbol (XAU) to metals.dev metal name (gold)
     symbol_map: HashMap<String, String>,
@@ -37,16 +43,33 @@ impl MetalsDevProvider {
         symbol_map.insert("XPT".to_string(), "platinum".to_string());
         symbol_map.insert("XPD".to_string(), "palladium".to_string());
 
-        let builder = Client::builder();
-        #[cfg(not(target_arch = "wasm32"))]
-        let builder = builder.timeout(Duration::from_secs(30));
         Self {
-            client: builder.build().unwrap_or_else(|_| Client::new()),
+            client: ProviderClient::new(),
             api_key,
             symbol_map,
         }
     }
 
+    /// Fail requests that get no response within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_timeout(timeout);
+        self
+    }
+
+    /// Send requests through `client`, e.g. one shared by every provider
+    /// and configured with a proxy.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = self.client.with_client(client);
+        self
+    }
+
+    /// Send requests through `http` instead of reqwest, e.g. a stub
+    /// answering with recorded responses.
+    pub fn with_http_client(mut self, http: Arc<dyn HttpClient>) -> Self {
+        self.client = self.client.with_transport(http);
+        self
+    }
+
     pub fn resolve_metal_name(&self, symbol: &str) -> Result<String, CoreError> {
         let upper = symbol.to_uppercase();
         self.symbol_map
@@ -54,9 +77,69 @@ impl MetalsDevProvider {
             .cloned()
             .ok_or_else(|| CoreError::Api {
                 provider: "metals.dev".into(),
+                status: None,
                 message: format!("Unknown metal symbol: {symbol}. Supported: XAU, XAG, XPT, XPD"),
             })
     }
+
+    /// Split `from..=to` into consecutive windows of at most `MAX_TIMESERIES_DAYS` days.
+    pub fn timeseries_windows(from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+        http::date_windows(from, to, MAX_TIMESERIES_DAYS)
+    }
+
+    /// Parse a `/timeseries` response into the prices of `metal_name`, sorted by date.
+    ///
+    /// The documented shape is `{"rates": {"YYYY-MM-DD": {"metals": {"gold": ..}}}}`;
+    /// the older `{"gold": [{"date": .., "price": ..}]}` shape is accepted too.
+    pub fn parse_timeseries(body: &str, metal_name: &str) -> Result<Vec<PricePoint>, CoreError> {
+        let parsed: serde_json::Value = serde_json::from_str(body)
+            .map_err(|e| CoreError::Api {
+                provider: "metals.dev".into(),
+                status: None,
+                message: format!("Failed to parse timeseries: {e}"),
+            })?;
+
+        if parsed.get("status").and_then(|v| v.as_str()) == Some("failure") {
+            let message = parsed
+                .get("error_message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("request failed");
+            return Err(CoreError::Api {
+                provider: "metals.dev".into(),
+                status: None,
+                message: message.to_string(),
+            });
+        }
+
+        let mut points = Vec::new();
+        if let Some(rates) = parsed.get("rates").and_then(|v| v.as_object()) {
+            for (date_str, day) in rates {
+                let price = day
+                    .get("metals")
+                    .and_then(|m| m.get(metal_name))
+                    .and_then(|v| v.as_f64());
+                if let (Ok(date), Some(price)) =
+                    (NaiveDate::parse_from_str(date_str, "%Y-%m-%d"), price)
+                {
+                    points.push(PricePoint { date, price });
+                }
+            }
+        } else if let Some(arr) = parsed.get(metal_name).and_then(|v| v.as_array()) {
+            for item in arr {
+                if let (Some(date_str), Some(price)) = (
+                    item.get("date").and_then(|v| v.as_str()),
+                    item.get("price").and_then(|v| v.as_f64()),
+                ) {
+                    if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+                        points.push(PricePoint { date, price });
+                    }
+                }
+            }
+        }
+
+        points.sort_by_key(|p| p.date);
+        Ok(points)
+    }
 }
 
 // ── metals.dev API response types ───────────────────────────────────
@@ -67,7 +150,7 @@ struct LatestResponse {
 }
 
 // Note: metals.dev timeseries responses are parsed dynamically via serde_json::Value
-// because the response structure varies by metal name key.
+// because the response structure varies by date and metal name keys.
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -76,6 +159,10 @@ impl PriceProvider for MetalsDevProvider {
         "metals.dev"
     }
 
+    fn id(&self) -> &str {
+        "metals_dev"
+    }
+
     fn supported_asset_types(&self) -> Vec<AssetType> {
         vec![AssetType::Metal]
     }
@@ -86,20 +173,13 @@ impl PriceProvider for MetalsDevProvider {
         _currency: &str,
     ) -> Result<f64, CoreError> {
         let metal_name = self.resolve_metal_name(symbol)?;
-        let url = format!("{BASE_URL}/latest");
-
-        let resp: LatestResponse = self
-            .client
-            .get(&url)
-            .query(&[("api_key", &self.api_key), ("currency", &"USD".to_string())])
-            .send()
-            .await?
-            .json()
-            .await
-            .map_err(|e| CoreError::Api {
-                provider: "metals.dev".into(),
-                message: format!("Failed to parse latest prices: {e}"),
-            })?;
+        let url = http::url(
+            &format!("{BASE_URL}/latest"),
+            &[("api_key", self.api_key.as_str()), ("currency", "USD"), ("unit", "toz")],
+        )?;
+
+        let body = self.client.get_text_with_key("metals.dev", &url, &[]).await?;
+        let resp: LatestResponse = http::parse_json("metals.dev", &body, "latest prices")?;
 
         resp.metals
             .get(&metal_name)
@@ -117,47 +197,18 @@ impl PriceProvider for MetalsDevProvider {
         _currency: &str,
         date: NaiveDate,
     ) -> Result<f64, CoreError> {
-        // metals.dev has a /timeseries endpoint for historical data
         let metal_name = self.resolve_metal_name(symbol)?;
-        let date_str = date.format("%Y-%m-%d").to_string();
-        let url = format!("{BASE_URL}/timeseries");
+        let points = self.fetch_timeseries(&metal_name, date, date).await?;
 
-        let resp_text = self
-            .client
-            .get(&url)
-            .query(&[
-                ("api_key", self.api_key.as_str()),
-                ("currency", "USD"),
-                ("metal", &metal_name),
-                ("start_date", &date_str),
-                ("end_date", &date_str),
-            ])
-            .send()
-            .await?
-            .text()
-            .await?;
-
-        // metals.dev timeseries returns: { "metal_name": [{"date": "...", "price": ...}] }
-        let parsed: serde_json::Value = serde_json::from_str(&resp_text)
-            .map_err(|e| CoreError::Api {
-                provider: "metals.dev".into(),
-                message: format!("Failed to parse timeseries response: {e}"),
-            })?;
-
-        // Try to extract the price from the response
-        if let Some(arr) = parsed.get(&metal_name).and_then(|v| v.as_array()) {
-            if let Some(point) = arr.first() {
-                if let Some(price) = point.get("price").and_then(|v| v.as_f64()) {
-                    return Ok(price);
-                }
-            }
-        }
-
-        Err(CoreError::PriceNotAvailable {
-            symbol: symbol.to_string(),
-            currency: "USD".to_string(),
-            date: date.to_string(),
-        })
+        points
+            .iter()
+            .find(|p| p.date == date)
+            .map(|p| p.price)
+            .ok_or_else(|| CoreError::PriceNotAvailable {
+                symbol: symbol.to_string(),
+                currency: "USD".to_string(),
+                date: date.to_string(),
+            })
     }
 
     async fn get_price_range(
@@ -168,48 +219,39 @@ impl PriceProvider for MetalsDevProvider {
         to: NaiveDate,
     ) -> Result<Vec<PricePoint>, CoreError> {
         let metal_name = self.resolve_metal_name(symbol)?;
-        let from_str = from.format("%Y-%m-%d").to_string();
-        let to_str = to.format("%Y-%m-%d").to_string();
-
-        let url = format!("{BASE_URL}/timeseries");
-
-        let resp_text = self
-            .client
-            .get(&url)
-            .query(&[
-                ("api_key", self.api_key.as_str()),
-                ("currency", "USD"),
-                ("metal", &metal_name),
-                ("start_date", &from_str),
-                ("end_date", &to_str),
-            ])
-            .send()
-            .await?
-            .text()
-            .await?;
-
-        let parsed: serde_json::Value = serde_json::from_str(&resp_text)
-            .map_err(|e| CoreError::Api {
-                provider: "metals.dev".into(),
-                message: format!("Failed to parse timeseries: {e}"),
-            })?;
 
         let mut points = Vec::new();
-
-        if let Some(arr) = parsed.get(&metal_name).and_then(|v| v.as_array()) {
-            for item in arr {
-                if let (Some(date_str), Some(price)) = (
-                    item.get("date").and_then(|v| v.as_str()),
-                    item.get("price").and_then(|v| v.as_f64()),
-                ) {
-                    if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                        points.push(PricePoint { date, price });
-                    }
-                }
-            }
+        for (start, end) in Self::timeseries_windows(from, to) {
+            points.extend(self.fetch_timeseries(&metal_name, start, end).await?);
         }
-
+        points.retain(|p| p.date >= from && p.date <= to);
         points.sort_by_key(|p| p.date);
+        points.dedup_by_key(|p| p.date);
         Ok(points)
     }
 }
+
+impl MetalsDevProvider {
+    /// Fetch one `/timeseries` window (at most `MAX_TIMESERIES_DAYS` days).
+    async fn fetch_timeseries(
+        &self,
+        metal_name: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        let url = http::url(
+            &format!("{BASE_URL}/timeseries"),
+            &[
+                ("api_key", self.api_key.as_str()),
+                ("currency", "USD"),
+                ("unit", "toz"),
+                ("metal", metal_name),
+                ("start_date", &from.format("%Y-%m-%d").to_string()),
+                ("end_date", &to.format("%Y-%m-%d").to_string()),
+            ],
+        )?;
+        let resp_text = self.client.get_text_with_key("metals.dev", &url, &[]).await?;
+
+        Self::parse_timeseries(&resp_text, metal_name)
+    }
+}
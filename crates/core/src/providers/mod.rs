@@ -1,3 +1,4 @@
+pub mod http;
 pub mod registry;
 pub mod traits;
 
@@ -6,5 +7,6 @@ pub mod alphavantage;
 pub mod coincap;
 pub mod frankfurter;
 pub mod metals_dev;
+pub mod yahoo_chart;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod yahoo_finance;
@@ -1,21 +1,43 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::models::asset::AssetType;
+use crate::models::settings::Settings;
 
 use super::alphavantage::AlphaVantageProvider;
 use super::coincap::CoinCapProvider;
 use super::frankfurter::FrankfurterProvider;
+use super::http;
 use super::metals_dev::MetalsDevProvider;
+#[cfg(target_arch = "wasm32")]
+use super::yahoo_chart::YahooChartProvider;
 #[cfg(not(target_arch = "wasm32"))]
 use super::yahoo_finance::YahooFinanceProvider;
 use super::traits::PriceProvider;
 
+/// Ids of the built-in providers (see `PriceProvider::id`), registered or
+/// not (e.g., Alpha Vantage before its API key is set).
+pub const BUILTIN_PROVIDER_IDS: [&str; 5] =
+    ["coincap", "frankfurter", "metals_dev", "yahoo", "alphavantage"];
+
+/// Name and supported asset types of a registered provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderInfo {
+    pub name: String,
+    pub supported_asset_types: Vec<AssetType>,
+}
+
 /// Registry of all available price providers.
 ///
 /// Routes requests to the correct provider based on `AssetType`.
 /// New providers can be added without modifying existing code (Open/Closed Principle).
+///
+/// Providers are shared (`Arc`), so cloning a registry is cheap and lets the
+/// facade re-register custom providers when it rebuilds the defaults.
+#[derive(Clone)]
 pub struct PriceProviderRegistry {
-    providers: Vec<Box<dyn PriceProvider>>,
+    providers: Vec<Arc<dyn PriceProvider>>,
 }
 
 impl PriceProviderRegistry {
@@ -28,31 +50,79 @@ impl PriceProviderRegistry {
 
     /// Create a registry with all default providers pre-configured.
     pub fn new_with_defaults(api_keys: &HashMap<String, String>) -> Self {
+        Self::new_with_settings(&Settings {
+            api_keys: api_keys.clone(),
+            ..Settings::default()
+        })
+    }
+
+    /// Create a registry with all default providers, configured from the
+    /// API keys, symbol overrides, request timeouts and proxy in `settings`.
+    ///
+    /// The providers share one HTTP client (see `http::shared_client`); if
+    /// the stored proxy or contact email is invalid, they do without it.
+    pub fn new_with_settings(settings: &Settings) -> Self {
+        let api_keys = &settings.api_keys;
+        let client = http::shared_client(settings)
+            .or_else(|_| {
+                let direct = Settings { proxy_url: None, contact_email: None, ..settings.clone() };
+                http::shared_client(&direct)
+            })
+            .unwrap_or_default();
         let mut registry = Self::new();
 
         // CoinCap — crypto, no API key needed
-        registry.register(Box::new(CoinCapProvider::new()));
+        let coincap_overrides =
+            settings.symbol_overrides.get("coincap").cloned().unwrap_or_default();
+        registry.register(Box::new(
+            CoinCapProvider::with_symbol_overrides(&coincap_overrides)
+                .with_client(client.clone())
+                .with_timeout(settings.provider_timeout("coincap")),
+        ));
 
         // Frankfurter — forex, no API key needed
-        registry.register(Box::new(FrankfurterProvider::new()));
+        registry.register(Box::new(
+            FrankfurterProvider::new()
+                .with_client(client.clone())
+                .with_timeout(settings.provider_timeout("frankfurter")),
+        ));
 
         // metals.dev — precious metals, requires API key
         if let Some(key) = api_keys.get("metals_dev") {
-            registry.register(Box::new(MetalsDevProvider::new(key.clone())));
+            registry.register(Box::new(
+                MetalsDevProvider::new(key.clone())
+                    .with_client(client.clone())
+                    .with_timeout(settings.provider_timeout("metals_dev")),
+            ));
         }
 
         // Yahoo Finance — stocks, NO API key needed (primary)
-        // Not available on WASM (uses native reqwest/tokio connectors)
         #[cfg(not(target_arch = "wasm32"))]
         {
-            if let Ok(yahoo) = YahooFinanceProvider::new() {
+            let timeout = settings.provider_timeout("yahoo");
+            let proxy = http::proxy(settings).unwrap_or_default();
+            if let Ok(yahoo) = YahooFinanceProvider::with_connection(timeout, proxy) {
                 registry.register(Box::new(yahoo));
             }
         }
+        // On WASM the chart API is called through fetch, optionally via a CORS proxy
+        #[cfg(target_arch = "wasm32")]
+        {
+            let yahoo = match api_keys.get("yahoo_proxy") {
+                Some(proxy) => YahooChartProvider::with_proxy(proxy),
+                None => YahooChartProvider::new(),
+            };
+            let yahoo = yahoo.with_client(client.clone());
+            registry.register(Box::new(yahoo.with_timeout(settings.provider_timeout("yahoo"))));
+        }
 
         // Alpha Vantage — stocks, requires API key (fallback)
         if let Some(key) = api_keys.get("alphavantage") {
-            registry.register(Box::new(AlphaVantageProvider::new(key.clone())));
+            registry.register(Box::new(
+                AlphaVantageProvider::new(key.clone())
+                    .with_client(client)
+                    .with_timeout(settings.provider_timeout("alphavantage")),
+            ));
         }
 
         registry
@@ -60,7 +130,39 @@ impl PriceProviderRegistry {
 
     /// Register a new price provider.
     pub fn register(&mut self, provider: Box<dyn PriceProvider>) {
-        self.providers.push(provider);
+        self.providers.push(Arc::from(provider));
+    }
+
+    /// Append all providers of `other` (shared, not re-created), keeping their order.
+    pub fn extend(&mut self, other: &PriceProviderRegistry) {
+        self.providers.extend(other.providers.iter().cloned());
+    }
+
+    /// Number of registered providers.
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// Returns `true` if no providers are registered.
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Name and supported asset types of every provider, in priority order.
+    pub fn list_providers(&self) -> Vec<ProviderInfo> {
+        self.providers
+            .iter()
+            .map(|p| ProviderInfo {
+                name: p.name().to_string(),
+                supported_asset_types: p.supported_asset_types(),
+            })
+            .collect()
+    }
+
+    /// Whether a provider with id `id` (see `PriceProvider::id`, any case)
+    /// is registered.
+    pub fn has_provider(&self, id: &str) -> bool {
+        self.providers.iter().any(|p| p.id().eq_ignore_ascii_case(id))
     }
 
     /// Find the first provider that supports the given asset type.
@@ -71,6 +173,23 @@ impl PriceProviderRegistry {
             .map(|p| p.as_ref())
     }
 
+    /// Symbol → id mappings learned at runtime by each provider, keyed by
+    /// lowercase provider name (the key used in `Settings::symbol_overrides`).
+    /// Providers that learned nothing are omitted.
+    pub fn learned_symbol_ids(&self) -> HashMap<String, HashMap<String, String>> {
+        let mut learned: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for provider in &self.providers {
+            let ids = provider.learned_symbol_ids();
+            if !ids.is_empty() {
+                learned
+                    .entry(provider.name().to_lowercase())
+                    .or_default()
+                    .extend(ids);
+            }
+        }
+        learned
+    }
+
     /// Return ALL providers that support the given asset type, ordered by registration priority.
     /// Used for fallback: if the first provider fails, try the next one.
     pub fn get_providers_for(&self, asset_type: &AssetType) -> Vec<&dyn PriceProvider> {
@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use chrono::NaiveDate;
+use std::collections::HashMap;
 
 use crate::errors::CoreError;
 use crate::models::asset::AssetType;
@@ -16,9 +17,23 @@ pub trait PriceProvider: Send + Sync {
     /// Human-readable name of this provider (for logs/errors).
     fn name(&self) -> &str;
 
+    /// Key of this provider in per-provider settings (compared lowercase),
+    /// e.g. `Settings::provider_timeouts` and `provider_daily_limits`.
+    /// Defaults to `name`.
+    fn id(&self) -> &str {
+        self.name()
+    }
+
     /// Which asset types this provider can handle.
     fn supported_asset_types(&self) -> Vec<AssetType>;
 
+    /// Symbol → provider id mappings this provider resolved at runtime
+    /// (e.g., by searching its API), for the caller to persist in
+    /// `Settings::symbol_overrides`. Empty by default.
+    fn learned_symbol_ids(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+
     /// Get the current (latest) price of an asset in a given currency.
     async fn get_current_price(
         &self,
@@ -43,4 +58,44 @@ pub trait PriceProvider: Send + Sync {
         from: NaiveDate,
         to: NaiveDate,
     ) -> Result<Vec<PricePoint>, CoreError>;
+
+    // ── Asset-type aware variants ───────────────────────────────────
+    //
+    // `PriceService` calls these with the asset type it is resolving.
+    // Providers that serve several asset types from different endpoints
+    // (e.g., Alpha Vantage: stocks, FX, crypto) override them; the
+    // defaults forward to the methods above.
+
+    /// Same as `get_current_price`, with the asset type being priced.
+    async fn get_current_price_for_asset_type(
+        &self,
+        _asset_type: &AssetType,
+        symbol: &str,
+        currency: &str,
+    ) -> Result<f64, CoreError> {
+        self.get_current_price(symbol, currency).await
+    }
+
+    /// Same as `get_historical_price`, with the asset type being priced.
+    async fn get_historical_price_for_asset_type(
+        &self,
+        _asset_type: &AssetType,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<f64, CoreError> {
+        self.get_historical_price(symbol, currency, date).await
+    }
+
+    /// Same as `get_price_range`, with the asset type being priced.
+    async fn get_price_range_for_asset_type(
+        &self,
+        _asset_type: &AssetType,
+        symbol: &str,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        self.get_price_range(symbol, currency, from, to).await
+    }
 }
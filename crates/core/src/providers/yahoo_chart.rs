@@ -0,0 +1,392 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use futures_util::lock::Mutex;
+use reqwest::Client;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::errors::CoreError;
+use crate::models::asset::AssetType;
+use crate::models::price::PricePoint;
+use super::http::{self, HttpClient, HttpResponse, ProviderClient};
+use super::traits::PriceProvider;
+
+const BASE_URL: &str = "https://query1.finance.yahoo.com";
+
+/// Host that sets the session cookie a crumb is tied to.
+const COOKIE_URL: &str = "https://fc.yahoo.com";
+
+/// `User-Agent` of a desktop browser: Yahoo answers 403 to clients that
+/// identify as libraries. Browsers send their own.
+#[cfg(not(target_arch = "wasm32"))]
+const BROWSER_HEADERS: &[(&str, &str)] = &[(
+    "user-agent",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
+     AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36",
+)];
+#[cfg(target_arch = "wasm32")]
+const BROWSER_HEADERS: &[(&str, &str)] = &[];
+
+/// Yahoo Finance provider talking to the public chart API over plain reqwest.
+///
+/// - **Free**: No API key required.
+/// - **Endpoint**: `/v8/finance/chart/{symbol}` (daily closes).
+/// - **Platforms**: All, including WASM (reqwest uses the browser's `fetch`).
+///
+/// This is the stock provider of the WASM build; native builds keep using
+/// `YahooFinanceProvider`. Yahoo does not send CORS headers, so browsers
+/// usually need a proxy: `with_proxy(url)` sends requests to
+/// `{url}/v8/finance/chart/...` instead of Yahoo's own host. The default
+/// registry reads the proxy from `api_keys["yahoo_proxy"]`.
+///
+/// Requests are anonymous until Yahoo rejects one (401/403); the provider
+/// then starts a session — a cookie from `fc.yahoo.com` and a crumb from
+/// `/v1/test/getcrumb` — and retries with it. Through a proxy, the cookie
+/// is requested from the proxy's root.
+pub struct YahooChartProvider {
+    client: ProviderClient,
+    base_url: String,
+    cookie_url: String,
+    session: Mutex<Option<Session>>,
+}
+
+/// Cookie and crumb of a Yahoo session.
+#[derive(Clone)]
+struct Session {
+    /// `None` in browsers, which keep cookies to themselves.
+    cookie: Option<String>,
+    crumb: String,
+}
+
+impl YahooChartProvider {
+    /// Create a provider that calls Yahoo directly.
+    pub fn new() -> Self {
+        Self::with_base_url(BASE_URL, COOKIE_URL)
+    }
+
+    /// Create a provider that calls Yahoo through a proxy mirroring its paths.
+    pub fn with_proxy(proxy_url: &str) -> Self {
+        Self::with_base_url(proxy_url, proxy_url)
+    }
+
+    /// Fail requests that get no response within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_timeout(timeout);
+        self
+    }
+
+    /// Send requests through `client`, e.g. one shared by every provider
+    /// and configured with a proxy.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = self.client.with_client(client);
+        self
+    }
+
+    /// Send requests through `http` instead of reqwest, e.g. a stub
+    /// answering with recorded responses.
+    pub fn with_http_client(mut self, http: Arc<dyn HttpClient>) -> Self {
+        self.client = self.client.with_transport(http);
+        self
+    }
+
+    fn with_base_url(base_url: &str, cookie_url: &str) -> Self {
+        let trim = |url: &str| url.trim().trim_end_matches('/').to_string();
+        Self {
+            client: ProviderClient::new(),
+            base_url: trim(base_url),
+            cookie_url: trim(cookie_url),
+            session: Mutex::new(None),
+        }
+    }
+
+    /// Base URL requests are sent to (Yahoo's host or the proxy).
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Parse a chart response into daily closes sorted by date.
+    ///
+    /// Days without a close (`null`, e.g. the still-open trading day) are skipped.
+    pub fn parse_chart(body: &str, symbol: &str) -> Result<Vec<PricePoint>, CoreError> {
+        let result = Self::parse_result(body, symbol)?;
+        let closes = result
+            .indicators
+            .quote
+            .into_iter()
+            .next()
+            .map(|q| q.close)
+            .unwrap_or_default();
+
+        let mut points: Vec<PricePoint> = result
+            .timestamp
+            .iter()
+            .zip(closes)
+            .filter_map(|(&ts, close)| {
+                let date = chrono::DateTime::from_timestamp(ts, 0)?.date_naive();
+                Some(PricePoint { date, price: close? })
+            })
+            .collect();
+
+        points.sort_by_key(|p| p.date);
+        points.dedup_by_key(|p| p.date);
+        Ok(points)
+    }
+
+    /// Parse a chart response into the latest price: the regular market price
+    /// if present, otherwise the last daily close.
+    pub fn parse_latest_price(body: &str, symbol: &str) -> Result<f64, CoreError> {
+        let result = Self::parse_result(body, symbol)?;
+        if let Some(price) = result.meta.and_then(|m| m.regular_market_price) {
+            return Ok(price);
+        }
+        result
+            .indicators
+            .quote
+            .into_iter()
+            .next()
+            .and_then(|q| q.close.into_iter().rev().flatten().next())
+            .ok_or_else(|| api_error(format!("No quote data for {symbol}")))
+    }
+
+    fn parse_result(body: &str, symbol: &str) -> Result<ChartResult, CoreError> {
+        let resp: ChartResponse = serde_json::from_str(body)
+            .map_err(|e| api_error(format!("Failed to parse response for {symbol}: {e}")))?;
+
+        if let Some(error) = resp.chart.error {
+            return Err(api_error(format!(
+                "{symbol}: {}",
+                error.description.or(error.code).unwrap_or_else(|| "unknown error".into())
+            )));
+        }
+        resp.chart
+            .result
+            .and_then(|results| results.into_iter().next())
+            .ok_or_else(|| api_error(format!("No chart data for {symbol}")))
+    }
+}
+
+impl Default for YahooChartProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ── Yahoo chart API response types ──────────────────────────────────
+
+#[derive(Deserialize)]
+struct ChartResponse {
+    chart: Chart,
+}
+
+#[derive(Deserialize)]
+struct Chart {
+    result: Option<Vec<ChartResult>>,
+    error: Option<ChartError>,
+}
+
+#[derive(Deserialize)]
+struct ChartError {
+    code: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChartResult {
+    meta: Option<ChartMeta>,
+    #[serde(default)]
+    timestamp: Vec<i64>,
+    indicators: Indicators,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartMeta {
+    regular_market_price: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct Indicators {
+    #[serde(default)]
+    quote: Vec<Quote>,
+}
+
+#[derive(Deserialize)]
+struct Quote {
+    #[serde(default)]
+    close: Vec<Option<f64>>,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl PriceProvider for YahooChartProvider {
+    fn name(&self) -> &str {
+        "Yahoo Finance"
+    }
+
+    fn id(&self) -> &str {
+        "yahoo"
+    }
+
+    fn supported_asset_types(&self) -> Vec<AssetType> {
+        vec![AssetType::Stock]
+    }
+
+    async fn get_current_price(
+        &self,
+        symbol: &str,
+        _currency: &str,
+    ) -> Result<f64, CoreError> {
+        let body = self
+            .fetch(symbol, &[("range", "1d"), ("interval", "1d")])
+            .await?;
+        Self::parse_latest_price(&body, symbol)
+    }
+
+    async fn get_historical_price(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<f64, CoreError> {
+        // Fetch a 3-day window to handle weekends/holidays
+        let points = self
+            .get_price_range(symbol, currency, date, date + chrono::Duration::days(3))
+            .await?;
+
+        points
+            .first()
+            .map(|p| p.price)
+            .ok_or_else(|| CoreError::PriceNotAvailable {
+                symbol: symbol.to_string(),
+                currency: currency.to_uppercase(),
+                date: date.to_string(),
+            })
+    }
+
+    async fn get_price_range(
+        &self,
+        symbol: &str,
+        _currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        let period1 = unix_midnight(from).to_string();
+        let period2 = unix_midnight(to + chrono::Duration::days(1)).to_string(); // inclusive end
+        let body = self
+            .fetch(
+                symbol,
+                &[("period1", &period1), ("period2", &period2), ("interval", "1d")],
+            )
+            .await?;
+
+        let mut points = Self::parse_chart(&body, symbol)?;
+        points.retain(|p| p.date >= from && p.date <= to);
+        Ok(points)
+    }
+}
+
+impl YahooChartProvider {
+    /// Request the chart of `symbol` and return the response body, starting
+    /// a session and retrying once if Yahoo rejects the request.
+    async fn fetch(&self, symbol: &str, query: &[(&str, &str)]) -> Result<String, CoreError> {
+        let session = self.session.lock().await.clone();
+        match self.fetch_chart(symbol, query, session.as_ref()).await {
+            Err(CoreError::Api { status: Some(401 | 403), .. }) => {
+                let session = self.start_session().await?;
+                self.fetch_chart(symbol, query, Some(&session)).await
+            }
+            result => result,
+        }
+    }
+
+    async fn fetch_chart(
+        &self,
+        symbol: &str,
+        query: &[(&str, &str)],
+        session: Option<&Session>,
+    ) -> Result<String, CoreError> {
+        let base = format!("{}/v8/finance/chart/{}", self.base_url, symbol.to_uppercase());
+        let mut query = query.to_vec();
+        let mut headers = BROWSER_HEADERS.to_vec();
+        if let Some(session) = session {
+            query.push(("crumb", &session.crumb));
+            if let Some(cookie) = &session.cookie {
+                headers.push(("cookie", cookie));
+            }
+        }
+        let url = http::url(&base, &query)?;
+        self.client
+            .get_text("Yahoo Finance", &url, &headers)
+            .await
+            .map_err(|e| self.fetch_error(e))
+    }
+
+    /// Fetch a session cookie and the crumb tied to it, and keep them for
+    /// later requests.
+    async fn start_session(&self) -> Result<Session, CoreError> {
+        // The cookie host answers 404, but sets the cookie anyway
+        let response = self.client.get("Yahoo Finance", &self.cookie_url, BROWSER_HEADERS).await?;
+        let cookie = session_cookie(&response);
+
+        let mut headers = BROWSER_HEADERS.to_vec();
+        headers.push(("accept", "*/*"));
+        if let Some(cookie) = &cookie {
+            headers.push(("cookie", cookie));
+        }
+        let url = format!("{}/v1/test/getcrumb", self.base_url);
+        let crumb = self.client.get_text("Yahoo Finance", &url, &headers).await?;
+        let crumb = crumb.trim();
+        if crumb.is_empty() || crumb.contains(['<', '{', ' ']) {
+            return Err(api_error("Yahoo returned no session crumb".into()));
+        }
+
+        let session = Session { cookie, crumb: crumb.to_string() };
+        *self.session.lock().await = Some(session.clone());
+        Ok(session)
+    }
+
+    /// In browsers a request rejected by CORS fails with an opaque network
+    /// error, so point the user at the proxy setting.
+    #[cfg(target_arch = "wasm32")]
+    fn fetch_error(&self, error: CoreError) -> CoreError {
+        let CoreError::Network(message) = error else {
+            return error;
+        };
+        let hint = if self.base_url == BASE_URL {
+            "Yahoo blocks cross-origin requests; set a proxy with the \"yahoo_proxy\" API key"
+        } else {
+            "check that the \"yahoo_proxy\" URL is reachable and allows cross-origin requests"
+        };
+        CoreError::Network(format!("Yahoo Finance fetch failed ({hint}): {message}"))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn fetch_error(&self, error: CoreError) -> CoreError {
+        error
+    }
+}
+
+/// Unix timestamp of midnight UTC at the start of `date`.
+fn unix_midnight(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp()
+}
+
+/// `Cookie` header value of the cookies set by a response, if any.
+fn session_cookie(response: &HttpResponse) -> Option<String> {
+    let cookies: Vec<&str> = response
+        .header_values("set-cookie")
+        .filter_map(|value| value.split(';').next())
+        .map(str::trim)
+        .filter(|pair| pair.contains('='))
+        .collect();
+    (!cookies.is_empty()).then(|| cookies.join("; "))
+}
+
+fn api_error(message: String) -> CoreError {
+    CoreError::Api {
+        provider: "Yahoo Finance".into(),
+        status: None,
+        message,
+    }
+}
@@ -1,10 +1,13 @@
 use async_trait::async_trait;
 use chrono::{Datelike, NaiveDate};
+use std::time::Duration;
 use time::OffsetDateTime;
+use yahoo_finance_api::YahooError;
 
 use crate::errors::CoreError;
 use crate::models::asset::AssetType;
 use crate::models::price::PricePoint;
+use super::http;
 use super::traits::PriceProvider;
 
 /// Yahoo Finance API provider for stock/equity prices.
@@ -26,9 +29,30 @@ pub struct YahooFinanceProvider {
 
 impl YahooFinanceProvider {
     pub fn new() -> Result<Self, CoreError> {
-        let connector = yahoo_finance_api::YahooConnector::new()
+        Self::with_timeout(http::DEFAULT_TIMEOUT)
+    }
+
+    /// Create a provider whose requests fail after `timeout` without a response.
+    pub fn with_timeout(timeout: Duration) -> Result<Self, CoreError> {
+        Self::with_connection(timeout, None)
+    }
+
+    /// Create a provider whose requests fail after `timeout` and, if set,
+    /// go through `proxy`. The connector builds its own client, since Yahoo
+    /// expects the browser-like `User-Agent` it sends.
+    pub fn with_connection(
+        timeout: Duration,
+        proxy: Option<reqwest::Proxy>,
+    ) -> Result<Self, CoreError> {
+        let mut builder = yahoo_finance_api::YahooConnector::builder().timeout(timeout);
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy);
+        }
+        let connector = builder
+            .build()
             .map_err(|e| CoreError::Api {
                 provider: "Yahoo Finance".into(),
+                status: None,
                 message: format!("Failed to create connector: {e}"),
             })?;
         Ok(Self { connector })
@@ -55,17 +79,48 @@ impl YahooFinanceProvider {
         let odt = time::Date::from_calendar_date(date.year(), month, date.day() as u8)
             .map_err(|e| CoreError::Api {
                 provider: "Yahoo Finance".into(),
+                status: None,
                 message: format!("Invalid date {date}: {e}"),
             })?
             .with_hms(0, 0, 0)
             .map_err(|e| CoreError::Api {
                 provider: "Yahoo Finance".into(),
+                status: None,
                 message: format!("Invalid time for {date}: {e}"),
             })?
             .assume_utc();
         Ok(odt)
     }
 
+    /// Map a failed request to `CoreError`, keeping the HTTP status Yahoo
+    /// answered with, so rate limits and server errors count as transient
+    /// (see `CoreError::is_transient`). Timeouts become
+    /// `CoreError::timed_out` and requests without a response
+    /// `CoreError::Network`.
+    pub fn fetch_error(e: YahooError, message: String) -> CoreError {
+        let status = match &e {
+            YahooError::ConnectionFailed(e) if e.is_timeout() => {
+                return CoreError::timed_out("Yahoo Finance");
+            }
+            YahooError::ConnectionFailed(e) => match e.status() {
+                Some(status) => Some(status.as_u16()),
+                None => return CoreError::Network(format!("{message}: {e}")),
+            },
+            YahooError::TooManyRequests(_) => Some(429),
+            YahooError::Unauthorized => Some(401),
+            // e.g. "HTTP error: 503 Service Unavailable"
+            YahooError::FetchFailed(text) => text
+                .strip_prefix("HTTP error: ")
+                .and_then(|rest| rest.split_whitespace().next()?.parse().ok()),
+            _ => None,
+        };
+        CoreError::Api {
+            provider: "Yahoo Finance".into(),
+            status,
+            message: format!("{message}: {e}"),
+        }
+    }
+
     /// Convert a unix timestamp (seconds) to `chrono::NaiveDate`.
     fn timestamp_to_naive_date(ts: i64) -> Option<NaiveDate> {
         chrono::DateTime::from_timestamp(ts, 0).map(|dt| dt.date_naive())
@@ -80,6 +135,10 @@ impl PriceProvider for YahooFinanceProvider {
         "Yahoo Finance"
     }
 
+    fn id(&self) -> &str {
+        "yahoo"
+    }
+
     fn supported_asset_types(&self) -> Vec<AssetType> {
         vec![AssetType::Stock]
     }
@@ -93,13 +152,13 @@ impl PriceProvider for YahooFinanceProvider {
             .connector
             .get_latest_quotes(symbol, "1d")
             .await
-            .map_err(|e| CoreError::Api {
-                provider: "Yahoo Finance".into(),
-                message: format!("Failed to fetch latest quote for {symbol}: {e}"),
+            .map_err(|e| {
+                Self::fetch_error(e, format!("Failed to fetch latest quote for {symbol}"))
             })?;
 
         let quote = resp.last_quote().map_err(|e| CoreError::Api {
             provider: "Yahoo Finance".into(),
+            status: None,
             message: format!("No quote data for {symbol}: {e}"),
         })?;
 
@@ -121,13 +180,13 @@ impl PriceProvider for YahooFinanceProvider {
             .connector
             .get_quote_history(symbol, start, end)
             .await
-            .map_err(|e| CoreError::Api {
-                provider: "Yahoo Finance".into(),
-                message: format!("Failed to fetch history for {symbol} on {date}: {e}"),
+            .map_err(|e| {
+                Self::fetch_error(e, format!("Failed to fetch history for {symbol} on {date}"))
             })?;
 
         let quotes = resp.quotes().map_err(|e| CoreError::Api {
             provider: "Yahoo Finance".into(),
+            status: None,
             message: format!("Failed to parse quotes for {symbol}: {e}"),
         })?;
 
@@ -165,13 +224,13 @@ impl PriceProvider for YahooFinanceProvider {
             .connector
             .get_quote_history(symbol, start, end)
             .await
-            .map_err(|e| CoreError::Api {
-                provider: "Yahoo Finance".into(),
-                message: format!("Failed to fetch history range for {symbol}: {e}"),
+            .map_err(|e| {
+                Self::fetch_error(e, format!("Failed to fetch history range for {symbol}"))
             })?;
 
         let quotes = resp.quotes().map_err(|e| CoreError::Api {
             provider: "Yahoo Finance".into(),
+            status: None,
             message: format!("Failed to parse quotes for {symbol}: {e}"),
         })?;
 
@@ -0,0 +1,133 @@
+use chrono::NaiveDate;
+use std::collections::HashSet;
+
+use crate::errors::CoreError;
+use crate::models::alert::{Alert, AlertCondition};
+use crate::models::asset::AssetType;
+use crate::models::price::PriceCache;
+use crate::services::currency_service::CurrencyService;
+use crate::services::price_service::PriceService;
+
+/// Evaluates price alerts against current prices.
+///
+/// - Each watched asset's current price is re-fetched once per check
+///   (today's cached price is treated as stale).
+/// - Disabled alerts and alerts that already fired today are skipped.
+pub struct AlertService {
+    currency_service: CurrencyService,
+}
+
+impl AlertService {
+    pub fn new() -> Self {
+        Self {
+            currency_service: CurrencyService::new(),
+        }
+    }
+
+    /// Use a configured `CurrencyService` (e.g., custom pivot currencies) for conversions.
+    pub fn with_currency_service(currency_service: CurrencyService) -> Self {
+        Self { currency_service }
+    }
+
+    /// Evaluate `alerts` on `today` and return the ones that newly fired.
+    ///
+    /// Fired alerts get `last_triggered = today` (both in `alerts` and in the
+    /// returned copies), so they don't fire again until the next day.
+    /// On error, no alert is marked as fired.
+    pub async fn check_alerts(
+        &self,
+        alerts: &mut [Alert],
+        price_service: &PriceService,
+        cache: &mut PriceCache,
+        today: NaiveDate,
+    ) -> Result<Vec<Alert>, CoreError> {
+        let pending: Vec<usize> = alerts
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.enabled && a.last_triggered != Some(today))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        // Force one fresh quote per watched asset
+        let mut stale: HashSet<(String, String)> = HashSet::new();
+        for &idx in &pending {
+            let alert = &alerts[idx];
+            let quote = match alert.asset.asset_type {
+                AssetType::Fiat => alert.currency.clone(),
+                _ => CurrencyService::quote_currency(&alert.asset),
+            };
+            if stale.insert((alert.asset.symbol.clone(), quote.clone())) {
+                cache.mark_stale(&alert.asset.symbol, &quote, &alert.asset.asset_type);
+            }
+        }
+
+        // Evaluate everything before marking alerts as fired, so a failed
+        // lookup doesn't swallow notifications for alerts checked earlier
+        let mut fired = Vec::new();
+        for idx in pending {
+            let alert = &alerts[idx];
+            let price = self
+                .currency_service
+                .convert_asset_to_currency(
+                    price_service,
+                    cache,
+                    &alert.asset,
+                    1.0,
+                    &alert.currency,
+                    today,
+                )
+                .await?;
+            let previous = match alert.condition {
+                AlertCondition::PctChangeDay => {
+                    let yesterday = today.pred_opt().unwrap_or(today);
+                    Some(
+                        self.currency_service
+                            .convert_asset_to_currency(
+                                price_service,
+                                cache,
+                                &alert.asset,
+                                1.0,
+                                &alert.currency,
+                                yesterday,
+                            )
+                            .await?,
+                    )
+                }
+                AlertCondition::Above | AlertCondition::Below => None,
+            };
+
+            if Self::is_triggered(alert, price, previous) {
+                fired.push(idx);
+            }
+        }
+
+        Ok(fired
+            .into_iter()
+            .map(|idx| {
+                alerts[idx].last_triggered = Some(today);
+                alerts[idx].clone()
+            })
+            .collect())
+    }
+
+    /// Whether `alert` fires for the current `price`.
+    /// `previous` is the previous day's price, used by `PctChangeDay`.
+    pub fn is_triggered(alert: &Alert, price: f64, previous: Option<f64>) -> bool {
+        match alert.condition {
+            AlertCondition::Above => price >= alert.threshold,
+            AlertCondition::Below => price <= alert.threshold,
+            AlertCondition::PctChangeDay => match previous {
+                Some(previous) if previous > 0.0 => {
+                    ((price - previous) / previous * 100.0).abs() >= alert.threshold
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+impl Default for AlertService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
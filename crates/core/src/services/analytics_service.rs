@@ -1,18 +1,41 @@
 use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
 
 use crate::errors::CoreError;
-use crate::models::analytics::{HoldingSummary, PortfolioSummary};
-use crate::models::event::EventType;
+use crate::formatting::{currency_decimals, round_to};
+use crate::models::analytics::{
+    AnnualReport, AssetPriceChange, AssetStatistics, AssetTypeAllocation, AssetTypeCount,
+    ChangePeriod, EventTypeCount, HoldingIncome, HoldingSummary, IncomeProjection,
+    MissingPrice, PortfolioSummary, PriceStatus, ReportEvent, SinceSummary, Statistics,
+    SummaryOptions, ValueChange,
+};
+use crate::models::asset::{Asset, AssetType};
+use crate::models::event::{Event, EventType};
 use crate::models::portfolio::Portfolio;
+use crate::models::settings::UnpriceableAssetPolicy;
 use crate::models::price::PriceCache;
+use crate::models::tax::{CostBasisMethod, HoldingTerm, TaxLot};
+use crate::services::chart_service::ChartService;
 use crate::services::currency_service::CurrencyService;
+use crate::services::inflation_service::InflationService;
 use crate::services::portfolio_service::PortfolioService;
-use crate::services::price_service::PriceService;
+use crate::services::price_service::{resolve_cached, PriceService};
+
+/// Decimal places of the percentages in summaries and income projections.
+pub const PERCENT_DECIMALS: u32 = 4;
 
 /// Computes portfolio analytics: gain/loss, returns, allocation breakdown.
 ///
 /// All calculations use market prices from APIs (current or cached).
-/// Cost basis is determined by the market price on the event date.
+/// Cost basis is determined by the market price on the event date, or by the
+/// event's `RecordedValue` when it was captured in the requested currency.
+///
+/// Summaries and income projections are rounded on the way out, so float
+/// noise like 9999.999999999998 never reaches the UI: monetary values to the
+/// currency's minor unit (see `formatting::currency_decimals`) and
+/// percentages to `PERCENT_DECIMALS` places, with allocation percentages
+/// adjusted to sum to exactly 100. Amounts held and per-unit prices are
+/// never rounded. `SummaryOptions::raw` leaves a summary unrounded.
 pub struct AnalyticsService {
     portfolio_service: PortfolioService,
     currency_service: CurrencyService,
@@ -26,13 +49,28 @@ impl AnalyticsService {
         }
     }
 
+    /// Use a configured `CurrencyService` (e.g., custom pivot currencies) for conversions.
+    pub fn with_currency_service(currency_service: CurrencyService) -> Self {
+        Self {
+            portfolio_service: PortfolioService::new(),
+            currency_service,
+        }
+    }
+
     /// Generate a full portfolio summary at a given date.
     ///
+    /// Everything is as of `date`: holdings come from
+    /// `PortfolioService::get_holdings`, which only counts events up to that
+    /// date, and so do the invested, returned and cash flow totals, the event
+    /// count and the inception date. Later events are ignored.
+    ///
     /// Computes:
     /// - Total current value
     /// - Total invested (sum of buy event values at their dates)
     /// - Total returned (sum of sell event values at their dates)
     /// - Gain/loss and % return (overall and per-asset)
+    /// - Gain/loss split into asset gain (price moves in the asset's quote
+    ///   currency, at today's FX rate) and FX gain (the remainder)
     /// - Allocation percentages
     pub async fn get_portfolio_summary(
         &self,
@@ -42,42 +80,220 @@ impl AnalyticsService {
         date: NaiveDate,
         currency: &str,
     ) -> Result<PortfolioSummary, CoreError> {
-        let holdings = self.portfolio_service.get_holdings(portfolio, date);
+        self.get_portfolio_summary_with_inflation(
+            portfolio,
+            price_service,
+            price_cache,
+            date,
+            currency,
+            None,
+        )
+        .await
+    }
+
+    /// Same as `get_portfolio_summary`, additionally computing `real_return_pct`
+    /// from `inflation` = (service, country) when CPI data covers every event date.
+    ///
+    /// Each buy/sell value is restated in `date` money before computing the return.
+    pub async fn get_portfolio_summary_with_inflation(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        date: NaiveDate,
+        currency: &str,
+        inflation: Option<(&InflationService, &str)>,
+    ) -> Result<PortfolioSummary, CoreError> {
+        self.get_portfolio_summary_with_options(
+            portfolio,
+            price_service,
+            price_cache,
+            date,
+            currency,
+            inflation,
+            &SummaryOptions::default(),
+        )
+        .await
+    }
+
+    /// Same as `get_portfolio_summary_with_inflation`, unrounded if
+    /// `options.raw` is set and listing assets sold out by `date` too if
+    /// `options.include_closed_positions` is: amount, value, price and
+    /// allocation 0, with the realized gain in `gain_loss`. `currency` and
+    /// `force_refresh` are the caller's to apply.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_portfolio_summary_with_options(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        date: NaiveDate,
+        currency: &str,
+        inflation: Option<(&InflationService, &str)>,
+        options: &SummaryOptions,
+    ) -> Result<PortfolioSummary, CoreError> {
+        let policy = portfolio.settings.unpriceable_asset_policy;
+        let summary = self
+            .portfolio_summary(
+                portfolio,
+                price_service,
+                price_cache,
+                date,
+                currency,
+                inflation,
+                policy,
+                options.include_closed_positions,
+            )
+            .await?;
+        Ok(if options.raw { summary } else { Self::rounded_summary(summary) })
+    }
+
+    /// Same as `get_portfolio_summary_with_inflation`, synchronously and with
+    /// cached prices only: `price_service`'s providers are never called.
+    ///
+    /// Holdings without a cached price are valued under the unpriceable asset
+    /// policy, with `Fail` treated as `LastCachedPrice`, so their
+    /// `price_status` flags them instead of failing the summary. Events and
+    /// exchange rates without a cached price still fail it.
+    pub fn get_portfolio_summary_cached(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        date: NaiveDate,
+        currency: &str,
+        inflation: Option<(&InflationService, &str)>,
+    ) -> Result<PortfolioSummary, CoreError> {
+        let cached_prices = price_service.cache_only();
+        let policy = Self::cached_policy(portfolio.settings.unpriceable_asset_policy);
+        let summary = resolve_cached(self.portfolio_summary(
+            portfolio,
+            &cached_prices,
+            price_cache,
+            date,
+            currency,
+            inflation,
+            policy,
+            false,
+        ))?;
+        Ok(Self::rounded_summary(summary))
+    }
+
+    /// The unpriceable asset policy for valuations from cached prices only,
+    /// where a missing price is expected: `Fail` becomes `LastCachedPrice`.
+    pub fn cached_policy(policy: UnpriceableAssetPolicy) -> UnpriceableAssetPolicy {
+        match policy {
+            UnpriceableAssetPolicy::Fail => UnpriceableAssetPolicy::LastCachedPrice,
+            policy => policy,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn portfolio_summary(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        date: NaiveDate,
+        currency: &str,
+        inflation: Option<(&InflationService, &str)>,
+        policy: UnpriceableAssetPolicy,
+        include_closed_positions: bool,
+    ) -> Result<PortfolioSummary, CoreError> {
+        let holdings = if include_closed_positions {
+            self.portfolio_service.get_all_positions(portfolio, date)
+        } else {
+            self.portfolio_service.get_holdings(portfolio, date)
+        };
 
-        // 1. Calculate current value per asset
+        // 1. Calculate current value per asset (display and native quote currency)
         let mut holding_summaries = Vec::new();
         let mut total_value = 0.0;
+        let mut asset_value_native: HashMap<Asset, f64> = HashMap::new();
 
         for (asset, amount) in &holdings {
-            let current_value = self
+            // Sold out: nothing to price
+            if *amount == 0.0 {
+                holding_summaries.push(Self::closed_position(portfolio, asset));
+                continue;
+            }
+            let manual_price = Self::manual_price(portfolio, asset);
+            let (current_value, price_status) = self
                 .currency_service
-                .convert_asset_to_currency(
+                .value_holding(
                     price_service,
                     price_cache,
                     asset,
                     *amount,
                     currency,
                     date,
+                    policy,
+                    manual_price,
                 )
                 .await?;
 
             total_value += current_value;
 
+            let quote = CurrencyService::quote_currency(asset);
+            let current_value_native = match price_status {
+                PriceStatus::Fresh => {
+                    self.currency_service
+                        .convert_asset_to_currency(
+                            price_service,
+                            price_cache,
+                            asset,
+                            *amount,
+                            &quote,
+                            date,
+                        )
+                        .await?
+                }
+                // Priced without a lookup: restate the fallback value
+                PriceStatus::Stale { .. } | PriceStatus::Manual | PriceStatus::Unavailable => {
+                    let value = current_value;
+                    self.currency_service
+                        .convert_fiat(price_service, price_cache, value, currency, &quote, date)
+                        .await
+                        .unwrap_or(0.0)
+                }
+            };
+            asset_value_native.insert(asset.clone(), current_value_native);
+
             holding_summaries.push(HoldingSummary {
                 asset: asset.clone(),
                 amount: *amount,
                 current_value,
                 total_invested: 0.0,      // filled below
                 cost_basis_per_unit: 0.0,  // filled below
+                avg_buy_price: 0.0,       // filled below
+                break_even_price: 0.0,    // filled below
+                break_even_recouped: false, // filled below
+                current_price: current_value / amount,
                 gain_loss: 0.0,           // filled below
+                asset_gain: 0.0,          // filled below
+                fx_gain: 0.0,             // filled below
+                income: 0.0,              // filled below
                 return_pct: 0.0,          // filled below
                 allocation_pct: 0.0,      // filled below
+                color: ChartService::asset_color(asset, &portfolio.asset_metadata),
+                price_status,
             });
         }
 
         // 2. Calculate total invested and returned from events
         let mut total_invested = 0.0;
         let mut total_returned = 0.0;
+        // Deposits minus withdrawals: cash moved in and out, not invested
+        let mut net_cash_flow = 0.0;
+        let mut asset_cash_flow: HashMap<Asset, f64> = HashMap::new();
+        // Deposited value per asset, part of the cost basis like bought units
+        let mut asset_deposited: HashMap<Asset, f64> = HashMap::new();
+        // Interest received and accrued (see `HoldingSummary::income`)
+        let mut total_income = 0.0;
+        let mut asset_income: HashMap<Asset, f64> = HashMap::new();
+        // Lots still held per asset: (acquired date, units left, unit cost)
+        let method = portfolio.settings.cost_basis_method;
+        let mut asset_lots: HashMap<Asset, Vec<(NaiveDate, f64, f64)>> = HashMap::new();
 
         // Track per-asset invested and returned amounts, and total units bought
         let mut asset_invested: std::collections::HashMap<
@@ -92,35 +308,120 @@ impl AnalyticsService {
             crate::models::asset::Asset,
             f64,
         > = std::collections::HashMap::new();
+        // Net invested in the asset's native quote currency (buys positive, sells negative)
+        let mut asset_net_invested_native: HashMap<Asset, f64> = HashMap::new();
+        // Invested, returned and net cash flow restated in `date` money;
+        // None once CPI data is missing
+        let mut real_flows: Option<(f64, f64, f64)> = inflation.map(|_| (0.0, 0.0, 0.0));
 
-        for event in &portfolio.events {
+        // Amounts in today's units, like the holdings (see `Portfolio::valued_events`)
+        let valued = portfolio.valued_events();
+        for event in valued.actual() {
             if event.date > date {
                 continue;
             }
 
-            let event_value = self
-                .currency_service
-                .convert_asset_to_currency(
-                    price_service,
-                    price_cache,
-                    &event.asset,
-                    event.amount,
-                    currency,
-                    event.date,
-                )
+            let (event_value, event_value_native) = self
+                .event_values(price_service, price_cache, event, currency)
                 .await?;
 
+            if let (Some((service, country)), Some((real_invested, real_returned, real_cash))) =
+                (inflation, real_flows.as_mut())
+            {
+                match service.deflator(country, event.date, date) {
+                    Ok(factor) => match event.event_type {
+                        EventType::Buy => *real_invested += event_value * factor,
+                        EventType::Sell => *real_returned += event_value * factor,
+                        EventType::Deposit => *real_cash += event_value * factor,
+                        EventType::Withdrawal => *real_cash -= event_value * factor,
+                        EventType::Interest => {}
+                    },
+                    Err(_) => real_flows = None,
+                }
+            }
+
             match event.event_type {
                 EventType::Buy => {
                     total_invested += event_value;
                     *asset_invested.entry(event.asset.clone()).or_insert(0.0) += event_value;
                     *asset_units_bought.entry(event.asset.clone()).or_insert(0.0) += event.amount;
+                    *asset_net_invested_native.entry(event.asset.clone()).or_insert(0.0) +=
+                        event_value_native;
                 }
                 EventType::Sell => {
                     total_returned += event_value;
                     *asset_returned.entry(event.asset.clone()).or_insert(0.0) += event_value;
+                    *asset_net_invested_native.entry(event.asset.clone()).or_insert(0.0) -=
+                        event_value_native;
+                }
+                EventType::Deposit => {
+                    net_cash_flow += event_value;
+                    *asset_cash_flow.entry(event.asset.clone()).or_insert(0.0) += event_value;
+                    *asset_deposited.entry(event.asset.clone()).or_insert(0.0) += event_value;
+                    *asset_units_bought.entry(event.asset.clone()).or_insert(0.0) += event.amount;
+                    *asset_net_invested_native.entry(event.asset.clone()).or_insert(0.0) +=
+                        event_value_native;
+                }
+                EventType::Withdrawal => {
+                    net_cash_flow -= event_value;
+                    *asset_cash_flow.entry(event.asset.clone()).or_insert(0.0) -= event_value;
+                    *asset_net_invested_native.entry(event.asset.clone()).or_insert(0.0) -=
+                        event_value_native;
+                }
+                // Income: part of the gain, not of the asset gain
+                EventType::Interest => {
+                    total_income += event_value;
+                    *asset_income.entry(event.asset.clone()).or_insert(0.0) += event_value;
+                    *asset_units_bought.entry(event.asset.clone()).or_insert(0.0) += event.amount;
+                    *asset_net_invested_native.entry(event.asset.clone()).or_insert(0.0) +=
+                        event_value_native;
                 }
             }
+
+            let lots = asset_lots.entry(event.asset.clone()).or_default();
+            if event.event_type.increases_holding() {
+                lots.push((event.date, event.amount, event_value / event.amount));
+            } else {
+                Self::consume_lots(lots, event.amount, method);
+            }
+        }
+
+        // Units accrued under accrual rules and not yet materialized are
+        // income too, valued at the as-of price
+        let accrued = portfolio.accrued_amounts(date);
+        for holding in &holding_summaries {
+            let Some(units) = accrued.get(&holding.asset).filter(|_| holding.amount > 0.0) else {
+                continue;
+            };
+            let share = units / holding.amount;
+            let value = holding.current_value * share;
+            total_income += value;
+            *asset_income.entry(holding.asset.clone()).or_insert(0.0) += value;
+            *asset_units_bought.entry(holding.asset.clone()).or_insert(0.0) += units;
+            let lots = asset_lots.entry(holding.asset.clone()).or_default();
+            lots.push((date, *units, value / units));
+            let value_native = asset_value_native.get(&holding.asset).copied().unwrap_or(0.0);
+            *asset_net_invested_native.entry(holding.asset.clone()).or_insert(0.0) +=
+                value_native * share;
+        }
+
+        // Asset gain per asset: gain in the native quote currency at today's FX rate
+        let mut asset_gains: HashMap<Asset, f64> = HashMap::new();
+        for (asset, net_invested_native) in &asset_net_invested_native {
+            let value_native = asset_value_native.get(asset).copied().unwrap_or(0.0);
+            let gain_native = value_native - net_invested_native;
+            let fx_rate = self
+                .currency_service
+                .convert_fiat(
+                    price_service,
+                    price_cache,
+                    1.0,
+                    &CurrencyService::quote_currency(asset),
+                    currency,
+                    date,
+                )
+                .await?;
+            asset_gains.insert(asset.clone(), gain_native * fx_rate);
         }
 
         // 3. Fill in per-asset details
@@ -128,24 +429,34 @@ impl AnalyticsService {
             let invested = asset_invested.get(&holding.asset).copied().unwrap_or(0.0);
             let returned = asset_returned.get(&holding.asset).copied().unwrap_or(0.0);
             let units_bought = asset_units_bought.get(&holding.asset).copied().unwrap_or(0.0);
+            let deposited = asset_deposited.get(&holding.asset).copied().unwrap_or(0.0);
+            let cash_flow = asset_cash_flow.get(&holding.asset).copied().unwrap_or(0.0);
+            let income = asset_income.get(&holding.asset).copied().unwrap_or(0.0);
             holding.total_invested = invested;
             holding.cost_basis_per_unit = if units_bought > 0.0 {
-                invested / units_bought
-            } else {
-                0.0
-            };
-            // I5: gain/loss = current_value + sell_proceeds - total_invested
-            holding.gain_loss = holding.current_value + returned - invested;
-            holding.return_pct = if invested > 0.0 {
-                (holding.gain_loss / invested) * 100.0
+                (invested + deposited + income) / units_bought
             } else {
                 0.0
             };
-            holding.allocation_pct = if total_value > 0.0 {
-                (holding.current_value / total_value) * 100.0
+            let lots = asset_lots.get(&holding.asset).map_or(&[][..], Vec::as_slice);
+            let (lot_units, lot_cost) = lots
+                .iter()
+                .fold((0.0, 0.0), |(units, cost), lot| (units + lot.1, cost + lot.1 * lot.2));
+            holding.avg_buy_price = if lot_units > 0.0 { lot_cost / lot_units } else { 0.0 };
+            let unrecouped = invested + cash_flow - returned;
+            holding.break_even_recouped = unrecouped <= 0.0;
+            holding.break_even_price = if holding.amount > 0.0 {
+                (unrecouped / holding.amount).max(0.0)
             } else {
                 0.0
             };
+            // I5: gain/loss = current_value + sell_proceeds - total_invested - net cash flow
+            holding.gain_loss = holding.current_value + returned - invested - cash_flow;
+            holding.asset_gain = asset_gains.get(&holding.asset).copied().unwrap_or(0.0);
+            holding.income = income;
+            holding.fx_gain = holding.gain_loss - holding.asset_gain - income;
+            holding.return_pct = percent_of(holding.gain_loss, invested);
+            holding.allocation_pct = percent_of(holding.current_value, total_value);
         }
 
         // Sort by allocation (largest first)
@@ -156,29 +467,993 @@ impl AnalyticsService {
         });
 
         // 4. Overall gain/loss
-        let total_gain_loss = total_value + total_returned - total_invested;
-        let total_return_pct = if total_invested > 0.0 {
-            (total_gain_loss / total_invested) * 100.0
-        } else {
-            0.0
-        };
+        let total_gain_loss = total_value + total_returned - total_invested - net_cash_flow;
+        let asset_gain: f64 = asset_gains.values().sum();
+        let fx_gain = total_gain_loss - asset_gain - total_income;
+        let total_return_pct = percent_of(total_gain_loss, total_invested);
+
+        let real_return_pct = real_flows.map(|(real_invested, real_returned, real_cash)| {
+            let real_gain = total_value + real_returned - real_invested - real_cash;
+            percent_of(real_gain, real_invested)
+        });
 
-        // G3: Compute context fields
-        let inception_date = portfolio.events.iter().map(|e| e.date).min();
+        let by_asset_type = Self::asset_type_breakdown(
+            &holding_summaries,
+            &asset_invested,
+            &asset_returned,
+            &asset_cash_flow,
+            total_value,
+        );
+
+        let income_projection = Self::build_income_projection(
+            portfolio,
+            holding_summaries
+                .iter()
+                .map(|h| (h.asset.clone(), h.current_value)),
+            date,
+            currency,
+        );
+        let income_projection = (!income_projection.holdings.is_empty()).then_some(income_projection);
+
+        // G3: Compute context fields, from the events up to `date` like the totals
+        let events_so_far = || portfolio.actual_events().filter(|e| e.date <= date);
+        let inception_date = events_so_far().map(|e| e.date).min();
 
         Ok(PortfolioSummary {
             as_of_date: date,
             currency: currency.to_string(),
-            total_events: portfolio.events.len(),
+            total_events: events_so_far().count(),
             inception_date,
             total_value,
             total_invested,
             total_returned,
+            net_cash_flow,
             total_gain_loss,
+            asset_gain,
+            fx_gain,
+            income: total_income,
             total_return_pct,
+            real_return_pct,
             holdings: holding_summaries,
+            by_asset_type,
+            income_projection,
+        })
+    }
+
+    /// Estimate annual income per holding on `date`: holding value × the
+    /// asset's `annual_yield_pct` from the portfolio's asset metadata.
+    /// Holdings without a yield are skipped (and need no price lookup).
+    pub async fn get_income_projection(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        date: NaiveDate,
+        currency: &str,
+    ) -> Result<IncomeProjection, CoreError> {
+        let holdings = self.portfolio_service.get_holdings(portfolio, date);
+        let mut values = Vec::new();
+
+        for (asset, amount) in holdings {
+            if Self::annual_yield_pct(portfolio, &asset).is_none() {
+                continue;
+            }
+            let value = self
+                .currency_service
+                .convert_asset_to_currency(
+                    price_service,
+                    price_cache,
+                    &asset,
+                    amount,
+                    currency,
+                    date,
+                )
+                .await?;
+            values.push((asset, value));
+        }
+
+        let mut projection = Self::build_income_projection(portfolio, values, date, currency);
+        Self::round_income_projection(&mut projection);
+        Ok(projection)
+    }
+
+    /// `summary` rounded for display. Gains are derived from the rounded
+    /// values they are made of, so e.g. `gain_loss` stays exactly
+    /// `current_value - total_invested` for a holding only ever bought.
+    fn rounded_summary(mut summary: PortfolioSummary) -> PortfolioSummary {
+        let money = |value: f64| round_to(value, currency_decimals(&summary.currency).1 as u32);
+        let pct = |value: f64| round_to(value, PERCENT_DECIMALS);
+
+        summary.total_value = money(summary.total_value);
+        summary.total_invested = money(summary.total_invested);
+        summary.total_returned = money(summary.total_returned);
+        summary.net_cash_flow = money(summary.net_cash_flow);
+        summary.total_gain_loss = money(
+            summary.total_value + summary.total_returned
+                - summary.total_invested
+                - summary.net_cash_flow,
+        );
+        summary.asset_gain = money(summary.asset_gain);
+        summary.income = money(summary.income);
+        summary.fx_gain = money(summary.total_gain_loss - summary.asset_gain - summary.income);
+        summary.total_return_pct = pct(summary.total_return_pct);
+        summary.real_return_pct = summary.real_return_pct.map(pct);
+
+        for holding in &mut summary.holdings {
+            // Sell proceeds minus net cash flow, which the holding doesn't list
+            let flows = holding.gain_loss - holding.current_value + holding.total_invested;
+            holding.current_value = money(holding.current_value);
+            holding.total_invested = money(holding.total_invested);
+            holding.gain_loss =
+                money(holding.current_value - holding.total_invested + money(flows));
+            holding.asset_gain = money(holding.asset_gain);
+            holding.income = money(holding.income);
+            holding.fx_gain = money(holding.gain_loss - holding.asset_gain - holding.income);
+            holding.return_pct = pct(holding.return_pct);
+        }
+        for allocation in &mut summary.by_asset_type {
+            allocation.value = money(allocation.value);
+            allocation.gain_loss = money(allocation.gain_loss);
+        }
+
+        let whole = summary.total_value != 0.0;
+        round_allocations(summary.holdings.iter_mut().map(|h| &mut h.allocation_pct).collect(), whole);
+        round_allocations(
+            summary.by_asset_type.iter_mut().map(|a| &mut a.allocation_pct).collect(),
+            whole,
+        );
+        if let Some(projection) = &mut summary.income_projection {
+            Self::round_income_projection(projection);
+        }
+        summary
+    }
+
+    fn round_income_projection(projection: &mut IncomeProjection) {
+        let decimals = currency_decimals(&projection.currency).1 as u32;
+        projection.total_annual_income = round_to(projection.total_annual_income, decimals);
+        for holding in &mut projection.holdings {
+            holding.value = round_to(holding.value, decimals);
+            holding.annual_income = round_to(holding.annual_income, decimals);
+            holding.annual_yield_pct = round_to(holding.annual_yield_pct, PERCENT_DECIMALS);
+        }
+    }
+
+    /// Compare the portfolio on `last_opened` with `today`: total value then and
+    /// now, unit price change per asset, the biggest mover, and events dated
+    /// after `last_opened`.
+    ///
+    /// Only prices on the two endpoint dates are needed (cached ones are reused).
+    /// If `last_opened` predates the first event, the inception date is used
+    /// instead and `from_is_inception` is set.
+    pub async fn get_since_summary(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        last_opened: NaiveDate,
+        today: NaiveDate,
+        currency: &str,
+    ) -> Result<SinceSummary, CoreError> {
+        if last_opened > today {
+            return Err(CoreError::ValidationError(format!(
+                "Last opened date {last_opened} must not be after {today}"
+            )));
+        }
+
+        let inception = portfolio.actual_events().map(|e| e.date).min();
+        let (from, from_is_inception) = match inception {
+            Some(inception) if last_opened < inception && inception <= today => (inception, true),
+            _ => (last_opened, false),
+        };
+
+        let holdings_then = self.portfolio_service.get_holdings(portfolio, from);
+        let holdings_now = self.portfolio_service.get_holdings(portfolio, today);
+
+        let mut assets: Vec<&Asset> = holdings_then.keys().chain(holdings_now.keys()).collect();
+        assets.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        assets.dedup();
+
+        let mut value_then = 0.0;
+        let mut value_now = 0.0;
+        let mut asset_changes = Vec::new();
+
+        for asset in assets {
+            let price_then = self
+                .currency_service
+                .convert_asset_to_currency(price_service, price_cache, asset, 1.0, currency, from)
+                .await?;
+            let price_now = self
+                .currency_service
+                .convert_asset_to_currency(price_service, price_cache, asset, 1.0, currency, today)
+                .await?;
+
+            value_then += holdings_then.get(asset).copied().unwrap_or(0.0) * price_then;
+            value_now += holdings_now.get(asset).copied().unwrap_or(0.0) * price_now;
+
+            let change_pct = percent_of(price_now - price_then, price_then);
+            asset_changes.push(AssetPriceChange {
+                asset: asset.clone(),
+                price_then,
+                price_now,
+                change_pct,
+            });
+        }
+
+        // Largest absolute move first
+        asset_changes.sort_by(|a, b| {
+            b.change_pct
+                .abs()
+                .partial_cmp(&a.change_pct.abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let value_change = value_now - value_then;
+        let value_change_pct = percent_of(value_change, value_then);
+
+        // Events are kept sorted by date
+        let new_events = portfolio
+            .actual_events()
+            .filter(|e| e.date > last_opened && e.date <= today)
+            .cloned()
+            .collect();
+
+        Ok(SinceSummary {
+            last_opened,
+            from,
+            from_is_inception,
+            to: today,
+            currency: currency.to_string(),
+            value_then,
+            value_now,
+            value_change,
+            value_change_pct,
+            biggest_mover: asset_changes.first().cloned(),
+            asset_changes,
+            new_events,
         })
     }
+
+    /// Change of the portfolio value in `currency` over `period`, ending on
+    /// `today`, from two valuations: on the period's start (or the inception
+    /// date, when the period starts before it) and on `today`.
+    ///
+    /// Holdings with no price on the start date are valued with the first
+    /// cached price within the period, setting `value_then_estimated`; prices
+    /// from before the period are not used. The values on `today` are never
+    /// estimated. `price_service` is expected to have no `PriceFallback` of
+    /// its own, or its estimates go unflagged.
+    pub async fn get_value_change(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        period: ChangePeriod,
+        today: NaiveDate,
+        currency: &str,
+    ) -> Result<ValueChange, CoreError> {
+        let start = period.start(today);
+        if start > today {
+            return Err(CoreError::ValidationError(format!(
+                "Period start {start} must not be after {today}"
+            )));
+        }
+
+        let inception = portfolio.actual_events().map(|e| e.date).min();
+        let (from, from_is_inception) = match inception {
+            Some(inception) if start < inception && inception <= today => (inception, true),
+            _ => (start, false),
+        };
+
+        // A start price may come from within the period, not from before it
+        let period_days = u32::try_from((today - from).num_days()).unwrap_or(u32::MAX);
+        let period_prices = price_service.cache_only().with_next_price_fallback(period_days);
+        let mut value_then = 0.0;
+        let mut value_then_estimated = false;
+        for (asset, amount) in self.sorted_holdings(portfolio, from) {
+            let value = match self
+                .currency_service
+                .convert_asset_to_currency(price_service, price_cache, &asset, amount, currency, from)
+                .await
+            {
+                Err(CoreError::Cancelled) => return Err(CoreError::Cancelled),
+                Err(error) => {
+                    value_then_estimated = true;
+                    self.currency_service
+                        .convert_asset_to_currency(
+                            &period_prices,
+                            price_cache,
+                            &asset,
+                            amount,
+                            currency,
+                            from,
+                        )
+                        .await
+                        .map_err(|_| error)?
+                }
+                Ok(value) => value,
+            };
+            value_then += value;
+        }
+
+        let mut value_now = 0.0;
+        for (asset, amount) in self.sorted_holdings(portfolio, today) {
+            value_now += self
+                .currency_service
+                .convert_asset_to_currency(price_service, price_cache, &asset, amount, currency, today)
+                .await?;
+        }
+
+        let change = value_now - value_then;
+        Ok(ValueChange {
+            period,
+            from,
+            from_is_inception,
+            to: today,
+            currency: currency.to_string(),
+            value_then,
+            value_now,
+            change,
+            change_pct: percent_of(change, value_then),
+            value_then_estimated,
+        })
+    }
+
+    /// Holdings on `date` in symbol order, so values are summed in a
+    /// stable order.
+    fn sorted_holdings(&self, portfolio: &Portfolio, date: NaiveDate) -> Vec<(Asset, f64)> {
+        let mut holdings: Vec<(Asset, f64)> =
+            self.portfolio_service.get_holdings(portfolio, date).into_iter().collect();
+        holdings.sort_by(|a, b| a.0.symbol.cmp(&b.0.symbol));
+        holdings
+    }
+
+    /// Yearly statement for fiscal year `year` in `currency` (see
+    /// `Settings::fiscal_year_start_month`): opening and closing value, the
+    /// year's events valued at their dates, totals, realized gains on an
+    /// average cost basis, and estimated income from asset yields.
+    ///
+    /// The current year ends on `today`; a future year is an error. Prices
+    /// that cannot be found are listed in `missing_prices` and the values
+    /// depending on them are left `None`. Cost bases need the buy prices of
+    /// earlier years only for assets sold during `year`.
+    pub async fn get_annual_report(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        year: i32,
+        currency: &str,
+        today: NaiveDate,
+    ) -> Result<AnnualReport, CoreError> {
+        let settings = &portfolio.settings;
+        let (from, end) = match (settings.fiscal_year_start(year), settings.fiscal_year_end(year)) {
+            (Some(from), Some(end)) if from <= today => (from, end),
+            _ => {
+                return Err(CoreError::ValidationError(format!(
+                    "No annual report for {year}: the year has not started yet"
+                )))
+            }
+        };
+        let to = end.min(today);
+        let mut missing_prices = Vec::new();
+
+        let opening_value = match from.pred_opt() {
+            Some(opening_date) => {
+                self.holdings_values(
+                    portfolio,
+                    price_service,
+                    price_cache,
+                    opening_date,
+                    currency,
+                    &mut missing_prices,
+                )
+                .await?
+                .0
+            }
+            None => Some(0.0),
+        };
+
+        // Average cost basis per asset: (units held, cost of those units)
+        let valued = portfolio.valued_events();
+        let sold_in_year: HashSet<&Asset> = valued
+            .actual()
+            .filter(|e| e.event_type == EventType::Sell && e.date >= from && e.date <= to)
+            .map(|e| &e.asset)
+            .collect();
+        let mut basis: HashMap<&Asset, (f64, Option<f64>)> = HashMap::new();
+        let mut events = Vec::new();
+        let (mut total_bought, mut total_sold, mut realized_gain) =
+            (Some(0.0), Some(0.0), Some(0.0));
+
+        for event in valued.actual().take_while(|e| e.date <= to) {
+            let in_year = event.date >= from;
+            let needs_value = in_year
+                || (event.event_type.increases_holding() && sold_in_year.contains(&event.asset));
+            let value = if needs_value {
+                match self
+                    .event_value(price_service, price_cache, event, currency)
+                    .await
+                {
+                    Ok(value) => Some(value),
+                    Err(CoreError::Cancelled) => return Err(CoreError::Cancelled),
+                    Err(e) => {
+                        missing_prices.push(MissingPrice {
+                            asset: event.asset.clone(),
+                            date: event.date,
+                            reason: e.to_string(),
+                        });
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            let (units, cost) = basis.entry(&event.asset).or_insert((0.0, Some(0.0)));
+            // Deposits, interest and withdrawals move the cost basis like
+            // buys and sells (interest at its value when received), but a
+            // withdrawal realizes no gain
+            let event_gain = match event.event_type {
+                EventType::Buy | EventType::Deposit | EventType::Interest => {
+                    *units += event.amount;
+                    *cost = cost.zip(value).map(|(cost, value)| cost + value);
+                    None
+                }
+                EventType::Sell | EventType::Withdrawal => {
+                    let share = if *units > 0.0 { (event.amount / *units).min(1.0) } else { 1.0 };
+                    let sold_cost = cost.map(|cost| cost * share);
+                    *units -= event.amount;
+                    *cost = cost.zip(sold_cost).map(|(cost, sold)| cost - sold);
+                    if *units <= f64::EPSILON {
+                        // Sold out: the next buy starts a fresh, known basis
+                        *units = 0.0;
+                        *cost = Some(0.0);
+                    }
+                    let gain = value.zip(sold_cost).map(|(value, sold)| value - sold);
+                    gain.filter(|_| event.event_type == EventType::Sell)
+                }
+            };
+
+            if in_year {
+                match event.event_type {
+                    EventType::Buy => total_bought = total_bought.zip(value).map(|(t, v)| t + v),
+                    EventType::Sell => {
+                        total_sold = total_sold.zip(value).map(|(t, v)| t + v);
+                        realized_gain = realized_gain.zip(event_gain).map(|(t, g)| t + g);
+                    }
+                    EventType::Deposit | EventType::Withdrawal | EventType::Interest => {}
+                }
+                events.push(ReportEvent {
+                    event: event.clone(),
+                    unit_price: value.map(|value| value / event.amount),
+                    value,
+                    realized_gain: event_gain,
+                });
+            }
+        }
+
+        let (closing_value, closing_values) = self
+            .holdings_values(
+                portfolio,
+                price_service,
+                price_cache,
+                to,
+                currency,
+                &mut missing_prices,
+            )
+            .await?;
+        let income = Self::build_income_projection(portfolio, closing_values, to, currency);
+        missing_prices.sort_by(|a, b| {
+            a.date.cmp(&b.date).then_with(|| a.asset.symbol.cmp(&b.asset.symbol))
+        });
+
+        Ok(AnnualReport {
+            year,
+            currency: currency.to_string(),
+            from,
+            to,
+            opening_value,
+            closing_value,
+            events,
+            total_bought,
+            total_sold,
+            realized_gain,
+            fees: None,
+            income: (!income.holdings.is_empty()).then_some(income),
+            missing_prices,
+        })
+    }
+
+    /// Sales in `year` matched against the buy lots they sold, valued in
+    /// `currency`. A sale covering several lots yields one `TaxLot` per lot,
+    /// picked by `method`; a lot held longer than `long_term_days` is
+    /// long-term.
+    ///
+    /// Unlike the annual report, a price that cannot be found is an error:
+    /// a tax report must not be partial. So is a sale of more units than
+    /// were bought before it. Only buys of assets sold during `year` are
+    /// valued.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_tax_lots(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        year: i32,
+        method: CostBasisMethod,
+        currency: &str,
+        long_term_days: u32,
+    ) -> Result<Vec<TaxLot>, CoreError> {
+        let (from, to) = match (
+            NaiveDate::from_ymd_opt(year, 1, 1),
+            NaiveDate::from_ymd_opt(year, 12, 31),
+        ) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Err(CoreError::ValidationError(format!("Invalid year {year}"))),
+        };
+        let valued = portfolio.valued_events();
+        let sold_in_year: HashSet<&Asset> = valued
+            .actual()
+            .filter(|e| e.event_type == EventType::Sell && e.date >= from && e.date <= to)
+            .map(|e| &e.asset)
+            .collect();
+
+        // Open lots per asset: (acquired date, units left, unit cost), in buy order
+        let mut open_lots: HashMap<&Asset, Vec<(NaiveDate, f64, f64)>> = HashMap::new();
+        let mut tax_lots = Vec::new();
+        for event in valued.actual().take_while(|e| e.date <= to) {
+            if !sold_in_year.contains(&event.asset) {
+                continue;
+            }
+            let lots = open_lots.entry(&event.asset).or_default();
+            if event.event_type.increases_holding() {
+                let value = self
+                    .event_value(price_service, price_cache, event, currency)
+                    .await?;
+                lots.push((event.date, event.amount, value / event.amount));
+                continue;
+            }
+
+            // A withdrawal uses up lots without being a taxable sale
+            let taxable = event.date >= from && event.event_type == EventType::Sell;
+            let unit_proceeds = if taxable {
+                self.event_value(price_service, price_cache, event, currency)
+                    .await?
+                    / event.amount
+            } else {
+                0.0
+            };
+            let mut remaining = event.amount;
+            while remaining > event.amount * 1e-9 {
+                let index = Self::next_lot(lots, method);
+                let Some(lot) = lots.get_mut(index) else {
+                    return Err(CoreError::ValidationError(format!(
+                        "Sell of {} {} on {} exceeds the units bought before it",
+                        event.amount, event.asset.symbol, event.date
+                    )));
+                };
+                let quantity = remaining.min(lot.1);
+                let (acquired_date, unit_cost) = (lot.0, lot.2);
+                lot.1 -= quantity;
+                if lot.1 <= quantity * 1e-9 {
+                    lots.remove(index);
+                }
+                remaining -= quantity;
+
+                if taxable {
+                    let proceeds = round_cents(quantity * unit_proceeds);
+                    let cost_basis = round_cents(quantity * unit_cost);
+                    let held_days = (event.date - acquired_date).num_days();
+                    tax_lots.push(TaxLot {
+                        asset: event.asset.clone(),
+                        acquired_date,
+                        sold_date: event.date,
+                        quantity,
+                        proceeds,
+                        cost_basis,
+                        gain: round_cents(proceeds - cost_basis),
+                        term: if held_days > i64::from(long_term_days) {
+                            HoldingTerm::Long
+                        } else {
+                            HoldingTerm::Short
+                        },
+                    });
+                }
+            }
+        }
+        Ok(tax_lots)
+    }
+
+    /// Index in `lots` ((acquired date, units, unit cost), in acquisition
+    /// order) of the lot a sale is matched against next under `method`.
+    fn next_lot(lots: &[(NaiveDate, f64, f64)], method: CostBasisMethod) -> usize {
+        match method {
+            CostBasisMethod::Fifo => 0,
+            CostBasisMethod::Lifo => lots.len().saturating_sub(1),
+            CostBasisMethod::Hifo => (0..lots.len())
+                .rev()
+                .max_by(|&a, &b| lots[a].2.total_cmp(&lots[b].2))
+                .unwrap_or(0),
+        }
+    }
+
+    /// Remove `amount` units from `lots`, matched under `method`. Units
+    /// beyond the lots are ignored.
+    fn consume_lots(lots: &mut Vec<(NaiveDate, f64, f64)>, amount: f64, method: CostBasisMethod) {
+        let mut remaining = amount;
+        while remaining > amount * 1e-9 && !lots.is_empty() {
+            let index = Self::next_lot(lots, method);
+            let quantity = remaining.min(lots[index].1);
+            lots[index].1 -= quantity;
+            if lots[index].1 <= quantity * 1e-9 {
+                lots.remove(index);
+            }
+            remaining -= quantity;
+        }
+    }
+
+    /// Event counts, traded amounts and date ranges of the actual events,
+    /// overall and per asset. Needs no prices; one pass over the events.
+    pub fn get_statistics(&self, portfolio: &Portfolio) -> Statistics {
+        let mut by_asset: HashMap<&Asset, AssetStatistics> = HashMap::new();
+        let (mut total_events, mut buys, mut sells) = (0, 0, 0);
+        let (mut deposits, mut withdrawals, mut interest) = (0, 0, 0);
+
+        // Events are in date order, so the first one seen is the earliest.
+        // Traded amounts are in today's units, like the holdings.
+        let valued = portfolio.valued_events();
+        for event in valued.actual() {
+            total_events += 1;
+            let stats = by_asset.entry(&event.asset).or_insert_with(|| AssetStatistics {
+                asset: event.asset.clone(),
+                event_count: 0,
+                buy_count: 0,
+                sell_count: 0,
+                total_bought: 0.0,
+                total_sold: 0.0,
+                first_event_date: event.date,
+                last_event_date: event.date,
+            });
+            stats.event_count += 1;
+            stats.last_event_date = event.date;
+            match event.event_type {
+                EventType::Buy => {
+                    buys += 1;
+                    stats.buy_count += 1;
+                    stats.total_bought += event.amount;
+                }
+                EventType::Sell => {
+                    sells += 1;
+                    stats.sell_count += 1;
+                    stats.total_sold += event.amount;
+                }
+                EventType::Deposit => deposits += 1,
+                EventType::Withdrawal => withdrawals += 1,
+                EventType::Interest => interest += 1,
+            }
+        }
+
+        let mut assets: Vec<AssetStatistics> = by_asset.into_values().collect();
+        assets.sort_by(|a, b| {
+            a.asset.symbol.cmp(&b.asset.symbol).then_with(|| {
+                a.asset.asset_type.to_string().cmp(&b.asset.asset_type.to_string())
+            })
+        });
+        let by_asset_type = [AssetType::Crypto, AssetType::Fiat, AssetType::Metal, AssetType::Stock]
+            .into_iter()
+            .filter_map(|asset_type| {
+                let of_type = assets.iter().filter(|a| a.asset.asset_type == asset_type);
+                let (asset_count, event_count) = of_type
+                    .fold((0, 0), |(assets, events), a| (assets + 1, events + a.event_count));
+                (asset_count > 0).then_some(AssetTypeCount { asset_type, event_count, asset_count })
+            })
+            .collect();
+
+        let mut by_event_type = vec![
+            EventTypeCount { event_type: EventType::Buy, count: buys },
+            EventTypeCount { event_type: EventType::Sell, count: sells },
+        ];
+        for (event_type, count) in [
+            (EventType::Deposit, deposits),
+            (EventType::Withdrawal, withdrawals),
+            (EventType::Interest, interest),
+        ] {
+            if count > 0 {
+                by_event_type.push(EventTypeCount { event_type, count });
+            }
+        }
+
+        Statistics {
+            total_events,
+            by_event_type,
+            by_asset_type,
+            first_event_date: portfolio.actual_events().next().map(|e| e.date),
+            last_event_date: portfolio.actual_events().next_back().map(|e| e.date),
+            assets,
+        }
+    }
+}
+
+impl AnalyticsService {
+    /// Value of every holding on `date`. Holdings that cannot be priced are
+    /// added to `missing` and make the total `None`; the values that could
+    /// be computed are still returned.
+    async fn holdings_values(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        date: NaiveDate,
+        currency: &str,
+        missing: &mut Vec<MissingPrice>,
+    ) -> Result<(Option<f64>, Vec<(Asset, f64)>), CoreError> {
+        let holdings = self.sorted_holdings(portfolio, date);
+
+        let mut total = Some(0.0);
+        let mut values = Vec::with_capacity(holdings.len());
+        for (asset, amount) in holdings {
+            match self
+                .currency_service
+                .convert_asset_to_currency(
+                    price_service,
+                    price_cache,
+                    &asset,
+                    amount,
+                    currency,
+                    date,
+                )
+                .await
+            {
+                Ok(value) => {
+                    total = total.map(|total| total + value);
+                    values.push((asset, value));
+                }
+                Err(CoreError::Cancelled) => return Err(CoreError::Cancelled),
+                Err(e) => {
+                    missing.push(MissingPrice { asset, date, reason: e.to_string() });
+                    total = None;
+                }
+            }
+        }
+        Ok((total, values))
+    }
+
+    fn annual_yield_pct(portfolio: &Portfolio, asset: &Asset) -> Option<f64> {
+        portfolio
+            .asset_metadata
+            .get(&(asset.symbol.to_uppercase(), asset.asset_type.clone()))
+            .and_then(|m| m.annual_yield_pct)
+    }
+
+    /// Manual unit price of `asset` from the portfolio's asset metadata, if set.
+    pub fn manual_price(portfolio: &Portfolio, asset: &Asset) -> Option<f64> {
+        portfolio
+            .asset_metadata
+            .get(&(asset.symbol.to_uppercase(), asset.asset_type.clone()))
+            .and_then(|m| m.manual_price)
+    }
+
+    /// Income projection from (asset, value) pairs; assets without a yield are skipped.
+    fn build_income_projection(
+        portfolio: &Portfolio,
+        values: impl IntoIterator<Item = (Asset, f64)>,
+        date: NaiveDate,
+        currency: &str,
+    ) -> IncomeProjection {
+        let mut holdings: Vec<HoldingIncome> = values
+            .into_iter()
+            .filter_map(|(asset, value)| {
+                let annual_yield_pct = Self::annual_yield_pct(portfolio, &asset)?;
+                Some(HoldingIncome {
+                    asset,
+                    value,
+                    annual_yield_pct,
+                    annual_income: value * annual_yield_pct / 100.0,
+                })
+            })
+            .collect();
+
+        // Largest income first, then by symbol for a deterministic order
+        holdings.sort_by(|a, b| {
+            b.annual_income
+                .partial_cmp(&a.annual_income)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.asset.symbol.cmp(&b.asset.symbol))
+        });
+
+        IncomeProjection {
+            as_of_date: date,
+            currency: currency.to_string(),
+            total_annual_income: holdings.iter().map(|h| h.annual_income).sum(),
+            holdings,
+        }
+    }
+
+    /// Summary row of an asset sold out by the summary date, before the
+    /// per-asset details are filled in.
+    fn closed_position(portfolio: &Portfolio, asset: &Asset) -> HoldingSummary {
+        HoldingSummary {
+            asset: asset.clone(),
+            amount: 0.0,
+            current_value: 0.0,
+            total_invested: 0.0,
+            cost_basis_per_unit: 0.0,
+            avg_buy_price: 0.0,
+            break_even_price: 0.0,
+            break_even_recouped: false,
+            current_price: 0.0,
+            gain_loss: 0.0,
+            asset_gain: 0.0,
+            fx_gain: 0.0,
+            income: 0.0,
+            return_pct: 0.0,
+            allocation_pct: 0.0,
+            color: ChartService::asset_color(asset, &portfolio.asset_metadata),
+            price_status: PriceStatus::Fresh,
+        }
+    }
+
+    /// Totals per asset type. Gain covers every asset with events up to the
+    /// summary date (sold-out assets contribute their realized gain), so the
+    /// per-type gains add up to the portfolio's total gain.
+    fn asset_type_breakdown(
+        holdings: &[HoldingSummary],
+        asset_invested: &HashMap<Asset, f64>,
+        asset_returned: &HashMap<Asset, f64>,
+        asset_cash_flow: &HashMap<Asset, f64>,
+        total_value: f64,
+    ) -> Vec<AssetTypeAllocation> {
+        let mut totals: HashMap<AssetType, (f64, f64)> = HashMap::new();
+
+        for holding in holdings {
+            totals.entry(holding.asset.asset_type.clone()).or_default().0 += holding.current_value;
+        }
+        for (asset, invested) in asset_invested {
+            totals.entry(asset.asset_type.clone()).or_default().1 -= invested;
+        }
+        for (asset, returned) in asset_returned {
+            totals.entry(asset.asset_type.clone()).or_default().1 += returned;
+        }
+        for (asset, cash_flow) in asset_cash_flow {
+            totals.entry(asset.asset_type.clone()).or_default().1 -= cash_flow;
+        }
+
+        let mut breakdown: Vec<AssetTypeAllocation> = totals
+            .into_iter()
+            .map(|(asset_type, (value, flows))| AssetTypeAllocation {
+                asset_type,
+                value,
+                allocation_pct: percent_of(value, total_value),
+                gain_loss: value + flows,
+            })
+            .collect();
+
+        // Largest value first; ties (e.g., sold-out types) in a stable order
+        breakdown.sort_by(|a, b| {
+            b.value
+                .partial_cmp(&a.value)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.asset_type.to_string().cmp(&b.asset_type.to_string()))
+        });
+        breakdown
+    }
+
+    /// Value of an event in `currency` and in the asset's native quote currency.
+    /// Uses the event's recorded unit prices where they match, otherwise looks up
+    /// prices on the event date.
+    async fn event_values(
+        &self,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        event: &Event,
+        currency: &str,
+    ) -> Result<(f64, f64), CoreError> {
+        let quote = CurrencyService::quote_currency(&event.asset);
+        let recorded = event.recorded_value.as_ref();
+        let value = self
+            .event_value(price_service, price_cache, event, currency)
+            .await?;
+
+        let value_native = match recorded {
+            Some(rv) if rv.quote_currency.eq_ignore_ascii_case(&quote) => {
+                event.amount * rv.quote_unit_price
+            }
+            _ => {
+                self.currency_service
+                    .convert_asset_to_currency(
+                        price_service,
+                        price_cache,
+                        &event.asset,
+                        event.amount,
+                        &quote,
+                        event.date,
+                    )
+                    .await?
+            }
+        };
+
+        Ok((value, value_native))
+    }
+
+    /// Value of an event in `currency`: its recorded unit price if it was
+    /// recorded in `currency`, otherwise the price on the event date.
+    pub(crate) async fn event_value(
+        &self,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        event: &Event,
+        currency: &str,
+    ) -> Result<f64, CoreError> {
+        match &event.recorded_value {
+            Some(rv) if rv.currency.eq_ignore_ascii_case(currency) => {
+                Ok(event.amount * rv.unit_price)
+            }
+            _ => {
+                self.currency_service
+                    .convert_asset_to_currency(
+                        price_service,
+                        price_cache,
+                        &event.asset,
+                        event.amount,
+                        currency,
+                        event.date,
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+/// `part` as a percentage of `whole`: 0 when `whole` is not positive (e.g.,
+/// nothing invested) or either value is NaN or infinite.
+fn percent_of(part: f64, whole: f64) -> f64 {
+    let pct = part / whole * 100.0;
+    if whole > 0.0 && pct.is_finite() {
+        pct
+    } else {
+        0.0
+    }
+}
+
+/// Round allocation percentages to `PERCENT_DECIMALS` places. When they
+/// make up a `whole` portfolio, the sum is kept at exactly 100 by the largest
+/// remainder method: each is rounded down and the units left over go to
+/// those that lost the most. Percentages that don't sum to 100 to begin with
+/// (e.g., with negative values) are rounded one by one.
+fn round_allocations(mut pcts: Vec<&mut f64>, whole: bool) {
+    let scale = 10f64.powi(PERCENT_DECIMALS as i32);
+    let units: Vec<f64> = pcts.iter().map(|p| **p * scale).collect();
+    let floors: Vec<f64> = units.iter().map(|u| u.floor()).collect();
+    let left = (100.0 * scale - floors.iter().sum::<f64>()).round();
+    if !whole || pcts.is_empty() || !(0.0..=pcts.len() as f64).contains(&left) {
+        for pct in pcts {
+            *pct = round_to(*pct, PERCENT_DECIMALS);
+        }
+        return;
+    }
+
+    let mut order: Vec<usize> = (0..pcts.len()).collect();
+    order.sort_by(|&a, &b| (units[b] - floors[b]).total_cmp(&(units[a] - floors[a])));
+    for (rank, &i) in order.iter().enumerate() {
+        let bump = if (rank as f64) < left { 1.0 } else { 0.0 };
+        *pcts[i] = (floors[i] + bump) / scale;
+    }
+
+    // Summed in order, the rounded values can still miss 100 by an ulp;
+    // the largest absorbs it
+    let largest = (0..pcts.len()).max_by(|&a, &b| pcts[a].total_cmp(pcts[b])).unwrap_or(0);
+    for _ in 0..4 {
+        let sum: f64 = pcts.iter().map(|p| **p).sum();
+        if sum == 100.0 {
+            break;
+        }
+        *pcts[largest] += 100.0 - sum;
+    }
+}
+
+/// Round a currency amount to 2 decimals.
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
 }
 
 impl Default for AnalyticsService {
@@ -0,0 +1,70 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::models::usage::ProviderUsage;
+
+/// Usage of each provider, keyed by lowercase provider name.
+type Usage = HashMap<String, ProviderUsage>;
+
+/// Counts the requests `PriceService` sends to each provider.
+///
+/// Shared (like the response memo) by every service built for a tracker,
+/// so the counts survive registry rebuilds. Uses interior mutability, since
+/// the service only holds a shared reference.
+#[derive(Debug, Default)]
+pub struct ApiUsageTracker {
+    usage: Mutex<Usage>,
+}
+
+impl ApiUsageTracker {
+    /// A tracker with no requests counted yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tracker continuing from previously recorded usage (e.g., the
+    /// `Portfolio::api_usage` of a loaded file).
+    pub fn with_usage(usage: HashMap<String, ProviderUsage>) -> Self {
+        Self {
+            usage: Mutex::new(usage),
+        }
+    }
+
+    /// Count a request to `provider` sent at `now`, unless `daily_limit`
+    /// requests were already sent to it that UTC day.
+    /// Returns whether the request was counted (and may be sent).
+    pub fn try_record(
+        &self,
+        provider: &str,
+        daily_limit: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let mut usage = self.lock();
+        let entry = usage
+            .entry(provider.to_lowercase())
+            .or_insert_with(|| ProviderUsage::new(provider));
+        if daily_limit.is_some_and(|limit| entry.calls_on(now.date_naive()) >= limit) {
+            return false;
+        }
+        entry.record_call(now);
+        true
+    }
+
+    /// Usage of `provider` (any case) as of `today`, if it was ever called.
+    pub fn get(&self, provider: &str, today: NaiveDate) -> Option<ProviderUsage> {
+        self.lock()
+            .get(&provider.to_lowercase())
+            .map(|usage| usage.as_of(today))
+    }
+
+    /// Usage of every provider called so far, as recorded (daily counts are
+    /// not reset), keyed by lowercase provider name.
+    pub fn snapshot(&self) -> HashMap<String, ProviderUsage> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Usage> {
+        self.usage.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -1,20 +1,42 @@
-use chrono::NaiveDate;
+use chrono::{Days, NaiveDate};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use crate::errors::CoreError;
-use crate::models::asset::Asset;
-use crate::models::chart::{ChartDataPoint, ChartEvent};
+use crate::models::accrual::AccrualRule;
+use crate::models::asset::{Asset, AssetMetadata, AssetMetadataKey, AssetType};
+use crate::models::analytics::PriceStatus;
+use crate::models::chart::{
+    AssetChart, AssetChartOptions, ChartDataPoint, ChartEvent, ChartResolution, GroupChart,
+    PortfolioChartOptions,
+};
 use crate::models::event::Event;
+use crate::models::holdings::HoldingsOptions;
 use crate::models::portfolio::Portfolio;
 use crate::models::price::PriceCache;
+use crate::models::progress::{CancellationToken, Progress};
+use crate::models::settings::UnpriceableAssetPolicy;
+use crate::models::snapshot::ValueSnapshot;
 use crate::services::currency_service::CurrencyService;
+use crate::services::inflation_service::InflationService;
 use crate::services::portfolio_service::PortfolioService;
-use crate::services::price_service::PriceService;
+use crate::services::price_service::{PriceRequest, PriceService, DEFAULT_FETCH_CONCURRENCY};
+
+/// Points of chart prices fetched together before they are computed (a
+/// month of daily points).
+const PREFETCH_WINDOW_POINTS: u64 = 31;
+
+/// Colors assigned to assets without a user-chosen color.
+const ASSET_PALETTE: [&str; 12] = [
+    "#4E79A7", "#F28E2B", "#E15759", "#76B7B2", "#59A14F", "#EDC948",
+    "#B07AA1", "#FF9DA7", "#9C755F", "#BAB0AC", "#1F77B4", "#17BECF",
+];
 
 /// Generates chart-ready data sets from portfolio data.
 ///
 /// The core computes all the numbers — the frontend only renders.
 /// Chart data includes:
-/// - Daily portfolio value in the default currency
+/// - Portfolio value in the default currency per day (per week or month
+///   over long ranges, see `ChartResolution`)
 /// - Buy/sell event annotations (what was bought/sold and its value)
 pub struct ChartService {
     portfolio_service: PortfolioService,
@@ -29,14 +51,28 @@ impl ChartService {
         }
     }
 
+    /// Use a configured `CurrencyService` (e.g., custom pivot currencies) for conversions.
+    pub fn with_currency_service(currency_service: CurrencyService) -> Self {
+        Self {
+            portfolio_service: PortfolioService::new(),
+            currency_service,
+        }
+    }
+
     /// Generate a full portfolio chart over a date range.
     ///
-    /// For each day from `from` to `to`:
+    /// For each day from `from` to `to` (or each week or month over long
+    /// ranges, see `ChartResolution`):
     /// 1. Maintain incremental holdings (apply events as we advance)
     /// 2. Get prices for all held assets
     /// 3. Convert everything to `currency` and sum up
     /// 4. Annotate any buy/sell events that happened on that date
     ///
+    /// Prices missing from the cache are fetched concurrently, a window of
+    /// days at a time, before the points of that window are computed from
+    /// the cache. Days whose prices can't be fetched are gap-filled like
+    /// weekends rather than failing the chart.
+    ///
     /// Uses incremental computation: O(days + events) instead of O(days × events).
     /// Returns Vec<ChartDataPoint> ready for frontend rendering.
     pub async fn generate_portfolio_chart(
@@ -48,123 +84,368 @@ impl ChartService {
         to: NaiveDate,
         currency: &str,
     ) -> Result<Vec<ChartDataPoint>, CoreError> {
-        let mut chart_data = Vec::new();
-        let mut current_date = from;
-        let mut last_known_value = 0.0;
+        self.generate_portfolio_chart_with_progress(
+            portfolio,
+            price_service,
+            price_cache,
+            from,
+            to,
+            currency,
+            |_| {},
+            None,
+        )
+        .await
+    }
 
-        // Pre-compute holdings at the start date (includes all events before `from`)
-        let mut holdings: std::collections::HashMap<Asset, f64> =
-            self.portfolio_service.get_holdings(portfolio, from);
+    /// Same as `generate_portfolio_chart`, reporting progress after every asset
+    /// lookup and every completed day.
+    ///
+    /// `cancel` is checked before each day; when set, returns `CoreError::Cancelled`.
+    /// Prices fetched before cancellation remain in `price_cache`.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(%from, %to, currency, points = tracing::field::Empty)
+        )
+    )]
+    pub async fn generate_portfolio_chart_with_progress(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &mut PriceService,
+        price_cache: &mut PriceCache,
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: &str,
+        on_progress: impl FnMut(Progress),
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        let iter = self.portfolio_chart_iter(portfolio, from, to, currency);
+        self.collect_chart(iter, price_service, price_cache, on_progress, cancel)
+            .await
+    }
 
-        // Index events by date for O(1) lookup per day
-        let mut events_by_date: std::collections::HashMap<NaiveDate, Vec<&Event>> =
-            std::collections::HashMap::new();
-        for event in &portfolio.events {
-            if event.date >= from && event.date <= to {
-                events_by_date.entry(event.date).or_default().push(event);
+    /// Chart of only the assets with the given symbols (case-insensitive),
+    /// computed like `generate_portfolio_chart` from their events alone.
+    ///
+    /// Symbols without events are ignored and listed in the result; with
+    /// none left, every point is zero. Stored snapshots cover the whole
+    /// portfolio, so they are not used.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn generate_group_chart(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &mut PriceService,
+        price_cache: &mut PriceCache,
+        symbols: &[String],
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: &str,
+    ) -> Result<GroupChart, CoreError> {
+        let symbols: BTreeSet<String> = symbols.iter().map(|s| s.trim().to_uppercase()).collect();
+        let present: HashSet<&str> = portfolio
+            .actual_events()
+            .map(|e| e.asset.symbol.as_str())
+            .filter(|symbol| symbols.contains(*symbol))
+            .collect();
+        let ignored_symbols = symbols
+            .iter()
+            .filter(|symbol| !present.contains(symbol.as_str()))
+            .cloned()
+            .collect();
+
+        let mut group = portfolio.clone_without_prices();
+        group.events.retain(|e| symbols.contains(&e.asset.symbol));
+        group.snapshots.clear();
+        group.mark_events_changed();
+
+        let points = self
+            .generate_portfolio_chart(&group, price_service, price_cache, from, to, currency)
+            .await?;
+        Ok(GroupChart {
+            points,
+            ignored_symbols,
+        })
+    }
+
+    /// Run `iter` to completion, prefetching each window of days concurrently
+    /// before its points are computed from the cache.
+    ///
+    /// Takes the iterator rather than the portfolio so that `price_cache` can
+    /// be the portfolio's own cache. `cancel` (or else `price_service`'s
+    /// cancellation token) is checked before each day.
+    pub async fn collect_chart(
+        &self,
+        mut iter: PortfolioChartIter,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        mut on_progress: impl FnMut(Progress),
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        let mut chart_data = Vec::with_capacity(iter.total_days());
+        let cancel = cancel.or(price_service.cancellation());
+        let cached_prices = price_service.cache_only();
+        let mut prefetched_until = None;
+
+        loop {
+            if cancel.is_some_and(CancellationToken::is_cancelled) {
+                return Err(CoreError::Cancelled);
+            }
+            let day = iter.current_date;
+            if day <= iter.to && prefetched_until.is_none_or(|until| day > until) {
+                let window_days = PREFETCH_WINDOW_POINTS * iter.resolution.step_days();
+                let until = day
+                    .checked_add_days(Days::new(window_days - 1))
+                    .map_or(iter.to, |end| end.min(iter.to));
+                self.prefetch_prices(&iter, until, price_service, price_cache)
+                    .await;
+                prefetched_until = Some(until);
+            }
+            match iter
+                .next_point_with_progress(&cached_prices, price_cache, &mut on_progress)
+                .await
+            {
+                Some(point) => chart_data.push(point?),
+                None => break,
             }
         }
 
-        // We already have holdings for `from`, but events ON `from` are already included
-        // via get_holdings. For subsequent days, we apply events incrementally.
-        let mut is_first_day = true;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("points", chart_data.len());
 
-        while current_date <= to {
-            // Apply events for this date (skip on first day — already in initial holdings)
-            if !is_first_day {
-                if let Some(day_events) = events_by_date.get(&current_date) {
-                    for event in day_events {
-                        let amount = holdings.entry(event.asset.clone()).or_insert(0.0);
-                        match event.event_type {
-                            crate::models::event::EventType::Buy => *amount += event.amount,
-                            crate::models::event::EventType::Sell => *amount -= event.amount,
-                        }
-                    }
-                    // Clean up zero/negative holdings
-                    holdings.retain(|_, amount| *amount > f64::EPSILON);
-                }
-            }
-            is_first_day = false;
+        Ok(chart_data)
+    }
 
-            // Calculate total portfolio value
-            let mut portfolio_value = 0.0;
-            let mut any_price_found = false;
+    /// Fetch the prices the points of `iter` up to `until` need, concurrently.
+    /// Past fiat rates are fetched with one range request per pair first.
+    ///
+    /// Fiat pairs without a direct quote are triangulated like
+    /// `CurrencyService` does: the legs through each pivot are fetched in
+    /// turn until the pair resolves, and the cross rate is cached as derived.
+    /// Past days already derived are neither asked for again nor re-derived.
+    /// Failed lookups are simply left out of the cache.
+    async fn prefetch_prices(
+        &self,
+        iter: &PortfolioChartIter,
+        until: NaiveDate,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+    ) {
+        let today = chrono::Utc::now().date_naive();
+        let mut seen = HashSet::new();
+        let requests: Vec<PriceRequest> = iter
+            .valuations(until)
+            .into_iter()
+            .flat_map(|(asset, date)| {
+                self.currency_service
+                    .price_requests(&asset, &iter.currency, date)
+            })
+            .filter(|request| seen.insert(request.clone()))
+            .filter(|request| {
+                request.date >= today
+                    || !price_cache.is_derived(
+                        &request.symbol,
+                        &request.currency,
+                        request.date,
+                        &request.asset_type,
+                    )
+            })
+            .collect();
 
-            for (asset, amount) in &holdings {
-                match self
-                    .currency_service
-                    .convert_asset_to_currency(
-                        price_service,
+        // Past FX rates come in one range request per pair, not one per day
+        let mut fx_spans: Vec<(&PriceRequest, NaiveDate, NaiveDate)> = Vec::new();
+        for request in &requests {
+            if request.asset_type != AssetType::Fiat || request.date >= today {
+                continue;
+            }
+            let span = fx_spans.iter_mut().find(|(r, ..)| {
+                (&r.symbol, &r.currency) == (&request.symbol, &request.currency)
+            });
+            match span {
+                Some((_, from, to)) => {
+                    *from = (*from).min(request.date);
+                    *to = (*to).max(request.date);
+                }
+                None => fx_spans.push((request, request.date, request.date)),
+            }
+        }
+        for (request, from, to) in fx_spans {
+            if from < to {
+                let _ = price_service
+                    .get_price_range(
                         price_cache,
-                        asset,
-                        *amount,
-                        currency,
-                        current_date,
+                        &request.symbol,
+                        &request.currency,
+                        from,
+                        to,
+                        &AssetType::Fiat,
                     )
-                    .await
-                {
-                    Ok(value) => {
-                        portfolio_value += value;
-                        any_price_found = true;
-                    }
-                    Err(_) => {
-                        continue;
-                    }
-                }
+                    .await;
             }
+        }
 
-            // Carry forward last known value on days with no price data (weekends/holidays)
-            if !holdings.is_empty() && !any_price_found {
-                portfolio_value = last_known_value;
-            } else {
-                last_known_value = portfolio_value;
-            }
+        let results = price_service
+            .get_prices(price_cache, &requests, DEFAULT_FETCH_CONCURRENCY)
+            .await;
 
-            // Collect events that happened on this date and compute their values
-            let mut chart_events = Vec::new();
-            if let Some(day_events) = events_by_date.get(&current_date) {
-                for event in day_events {
-                    let value = self
-                        .currency_service
-                        .convert_asset_to_currency(
-                            price_service,
-                            price_cache,
-                            &event.asset,
-                            event.amount,
-                            currency,
-                            current_date,
-                        )
-                        .await
-                        .unwrap_or(0.0);
+        // Reused derived rates need no legs (see CurrencyService::cross_rate)
+        let mut unresolved: Vec<PriceRequest> = requests
+            .into_iter()
+            .zip(results)
+            .filter(|(request, result)| {
+                result.is_err()
+                    && request.asset_type == AssetType::Fiat
+                    && !price_cache.is_derived(
+                        &request.symbol,
+                        &request.currency,
+                        request.date,
+                        &request.asset_type,
+                    )
+            })
+            .map(|(request, _)| request)
+            .collect();
 
-                    chart_events.push(ChartEvent {
-                        event_type: event.event_type.clone(),
-                        asset_symbol: event.asset.symbol.clone(),
-                        amount: event.amount,
-                        value_in_default_currency: value,
-                    });
+        for pivot in self.currency_service.pivot_currencies() {
+            if unresolved.is_empty() {
+                break;
+            }
+            let legs: Vec<Vec<PriceRequest>> = unresolved
+                .iter()
+                .map(|r| {
+                    CurrencyService::cross_rate_requests(&r.symbol, &r.currency, pivot, r.date)
+                })
+                .collect();
+            let flat: Vec<PriceRequest> = legs.iter().flatten().cloned().collect();
+            let mut results = price_service
+                .get_prices(price_cache, &flat, DEFAULT_FETCH_CONCURRENCY)
+                .await
+                .into_iter();
+
+            let mut still_unresolved = Vec::new();
+            for (request, legs) in unresolved.into_iter().zip(&legs) {
+                let rates: Vec<f64> =
+                    results.by_ref().take(legs.len()).filter_map(Result::ok).collect();
+                if legs.is_empty() || rates.len() < legs.len() {
+                    still_unresolved.push(request);
+                    continue;
                 }
+                price_cache.set_derived_price(
+                    &request.symbol,
+                    &request.currency,
+                    request.date,
+                    rates.iter().product(),
+                    &AssetType::Fiat,
+                );
             }
+            unresolved = still_unresolved;
+        }
+    }
 
-            chart_data.push(ChartDataPoint {
-                date: current_date,
-                portfolio_value,
-                events: chart_events,
-            });
+    /// Start an incremental portfolio chart computation over `from..=to`.
+    ///
+    /// The returned iterator owns everything it needs from `portfolio`, so the
+    /// caller can hand it the portfolio's own price cache on every step and emit
+    /// points as they are computed. Each price lookup writes straight into that
+    /// cache, so stopping early (or dropping the future) leaves it consistent.
+    /// Planned events are left out.
+    pub fn portfolio_chart_iter(
+        &self,
+        portfolio: &Portfolio,
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: &str,
+    ) -> PortfolioChartIter {
+        self.portfolio_chart_iter_with(
+            portfolio,
+            from,
+            to,
+            currency,
+            &PortfolioChartOptions::default(),
+        )
+    }
 
-            // Move to next day
-            current_date = match current_date.succ_opt() {
-                Some(next) => next,
-                None => break,
-            };
+    /// `portfolio_chart_iter` with `options`, e.g. applying planned events as
+    /// if they happened. Days after today are then valued at today's prices,
+    /// so the line extends into the future.
+    pub fn portfolio_chart_iter_with(
+        &self,
+        portfolio: &Portfolio,
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: &str,
+        options: &PortfolioChartOptions,
+    ) -> PortfolioChartIter {
+        let include_planned = options.include_planned;
+        // Index events by date for O(1) lookup per day, with corporate
+        // actions applied like the holdings
+        let mut events_by_date: HashMap<NaiveDate, Vec<Event>> = HashMap::new();
+        for event in portfolio.valued_events().active() {
+            if event.date >= from && event.date <= to && (include_planned || !event.is_planned) {
+                events_by_date.entry(event.date).or_default().push(event.clone());
+            }
         }
 
-        Ok(chart_data)
+        // Stored snapshots in the requested currency replace recomputation,
+        // unless planned events make the holdings differ from the snapshot's
+        let first_planned = portfolio
+            .planned_events()
+            .next()
+            .map(|e| e.date)
+            .filter(|_| include_planned);
+        let snapshots = portfolio
+            .snapshots
+            .iter()
+            .filter(|s| s.date >= from && s.date <= to)
+            .filter(|s| s.currency.eq_ignore_ascii_case(currency))
+            .filter(|s| first_planned.is_none_or(|planned| s.date < planned))
+            .map(|s| (s.date, s.clone()))
+            .collect();
+
+        // Pre-compute holdings at the start date (includes all events before `from`)
+        let holdings = self.portfolio_service.get_holdings_with(
+            portfolio,
+            from,
+            &HoldingsOptions { include_planned },
+        );
+
+        let manual_prices = portfolio
+            .asset_metadata
+            .iter()
+            .filter_map(|(key, metadata)| Some((key.clone(), metadata.manual_price?)))
+            .collect();
+
+        PortfolioChartIter {
+            currency_service: self.currency_service.clone(),
+            currency: currency.to_string(),
+            unpriceable_asset_policy: portfolio.settings.unpriceable_asset_policy,
+            manual_prices,
+            from,
+            resolution: ChartResolution::for_range(from, to),
+            current_date: from,
+            last_point: from,
+            to,
+            holdings,
+            events_by_date,
+            accruals: portfolio.accrual_rules.clone(),
+            snapshots,
+            latest_price_date: include_planned.then(|| chrono::Utc::now().date_naive()),
+            last_known_value: 0.0,
+            is_first_day: true,
+            completed_days: 0,
+            total_days: ChartResolution::for_range(from, to).point_count(from, to),
+        }
     }
 
     /// Generate a chart for a single asset's price history with events overlaid.
     ///
+    /// The asset is looked up in the events, not the holdings: an asset sold
+    /// out since (or not yet bought by `from`) charts its value on every day,
+    /// zero while none is held. Fails only if no event has the asset.
+    ///
     /// Uses incremental holdings computation (O(days + events)) like `generate_portfolio_chart`.
+    /// Planned events are left out.
     #[allow(clippy::too_many_arguments)]
     pub async fn generate_asset_chart(
         &self,
@@ -176,15 +457,55 @@ impl ChartService {
         to: NaiveDate,
         currency: &str,
     ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        let chart = self
+            .generate_asset_chart_with(
+                portfolio,
+                price_service,
+                price_cache,
+                asset_symbol,
+                from,
+                to,
+                currency,
+                &AssetChartOptions::default(),
+            )
+            .await?;
+        Ok(chart.points)
+    }
+
+    /// `generate_asset_chart` with `options`, e.g. to also return the
+    /// asset's price per unit on every date.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(asset_symbol, %from, %to, currency, points = tracing::field::Empty)
+        )
+    )]
+    pub async fn generate_asset_chart_with(
+        &self,
+        portfolio: &Portfolio,
+        price_service: &mut PriceService,
+        price_cache: &mut PriceCache,
+        asset_symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: &str,
+        options: &AssetChartOptions,
+    ) -> Result<AssetChart, CoreError> {
         let mut chart_data = Vec::new();
+        let mut unit_prices = Vec::new();
         let mut current_date = from;
         let mut last_known_value = 0.0;
-        let upper_symbol = asset_symbol.to_uppercase();
+        let mut last_unit_price = None;
+        // A renamed symbol is charted under its current name
+        let upper_symbol = portfolio.current_symbol(asset_symbol);
+        let valued = portfolio.valued_events();
 
         // Find the asset in portfolio events
-        let asset = portfolio
-            .events
-            .iter()
+        let asset = valued
+            .active()
             .find(|e| e.asset.symbol == upper_symbol)
             .map(|e| e.asset.clone())
             .ok_or_else(|| CoreError::ValidationError(
@@ -198,32 +519,40 @@ impl ChartService {
         // Index events for this asset by date for O(1) lookup
         let mut events_by_date: std::collections::HashMap<NaiveDate, Vec<&Event>> =
             std::collections::HashMap::new();
-        for event in &portfolio.events {
+        for event in valued.actual() {
             if event.asset.symbol == upper_symbol && event.date >= from && event.date <= to {
                 events_by_date.entry(event.date).or_default().push(event);
             }
         }
 
         let mut is_first_day = true;
+        let resolution = ChartResolution::for_range(from, to);
+        let mut last_point = from;
 
         while current_date <= to {
-            // Apply events for this date incrementally (skip first day — already in initial holdings)
+            // Apply accrual and events day by day since the previous point
+            // (skip first day — already in initial holdings)
+            let days: Vec<NaiveDate> =
+                PortfolioChartIter::point_days(is_first_day, last_point, current_date).collect();
             if !is_first_day {
-                if let Some(day_events) = events_by_date.get(&current_date) {
-                    for event in day_events {
-                        match event.event_type {
-                            crate::models::event::EventType::Buy => amount_held += event.amount,
-                            crate::models::event::EventType::Sell => amount_held -= event.amount,
-                        }
+                for &day in &days {
+                    if let Some(previous) = day.pred_opt() {
+                        amount_held *= portfolio.accrual_growth(&asset, previous, day);
                     }
-                    if amount_held < f64::EPSILON {
-                        amount_held = 0.0;
+                    if let Some(day_events) = events_by_date.get(&day) {
+                        for event in day_events {
+                            amount_held += event.holding_delta();
+                        }
+                        if amount_held < f64::EPSILON {
+                            amount_held = 0.0;
+                        }
                     }
                 }
             }
             is_first_day = false;
 
             // Calculate value, carry forward on weekends/holidays
+            let mut provisional = false;
             let portfolio_value = if amount_held > 0.0 {
                 match self.currency_service
                     .convert_asset_to_currency(
@@ -237,6 +566,12 @@ impl ChartService {
                     .await
                 {
                     Ok(value) => {
+                        provisional = self.currency_service.is_provisional(
+                            price_cache,
+                            &asset,
+                            currency,
+                            current_date,
+                        );
                         last_known_value = value;
                         value
                     }
@@ -247,9 +582,29 @@ impl ChartService {
                 0.0
             };
 
-            // Events for this asset on this date — calculate values
+            if options.include_unit_prices {
+                let unit_price = self
+                    .currency_service
+                    .convert_asset_to_currency(
+                        price_service,
+                        price_cache,
+                        &asset,
+                        1.0,
+                        currency,
+                        current_date,
+                    )
+                    .await;
+                if let Ok(price) = unit_price {
+                    last_unit_price = Some(price);
+                }
+                if let Some(price) = last_unit_price {
+                    unit_prices.push((current_date, price));
+                }
+            }
+
+            // Events for this asset since the previous point — calculate values
             let mut events_with_values = Vec::new();
-            if let Some(day_events) = events_by_date.get(&current_date) {
+            for day_events in days.iter().filter_map(|day| events_by_date.get(day)) {
                 for event in day_events {
                     let value = self
                         .currency_service
@@ -259,16 +614,16 @@ impl ChartService {
                             &event.asset,
                             event.amount,
                             currency,
-                            current_date,
+                            event.date,
                         )
-                        .await
-                        .unwrap_or(0.0);
+                        .await;
 
                     events_with_values.push(ChartEvent {
                         event_type: event.event_type.clone(),
                         asset_symbol: event.asset.symbol.clone(),
                         amount: event.amount,
-                        value_in_default_currency: value,
+                        unit_price: value.as_ref().ok().map(|value| value / event.amount),
+                        value_in_default_currency: value.unwrap_or(0.0),
                     });
                 }
             }
@@ -276,16 +631,380 @@ impl ChartService {
             chart_data.push(ChartDataPoint {
                 date: current_date,
                 portfolio_value,
+                currency: currency.to_string(),
                 events: events_with_values,
+                provisional,
             });
 
-            current_date = match current_date.succ_opt() {
+            last_point = current_date;
+            current_date = match resolution.next_date(from, current_date, to) {
                 Some(next) => next,
                 None => break,
             };
         }
 
-        Ok(chart_data)
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("points", chart_data.len());
+
+        Ok(AssetChart {
+            points: chart_data,
+            unit_prices,
+        })
+    }
+
+    /// Convert nominal chart values into real values expressed in `base_date` money,
+    /// using `country`'s CPI: value × CPI(base_date) / CPI(point date).
+    ///
+    /// Event values are deflated the same way. Fails with
+    /// `CoreError::InflationDataUnavailable` if any date has no CPI data.
+    pub fn deflate_chart(
+        &self,
+        points: Vec<ChartDataPoint>,
+        inflation: &InflationService,
+        country: &str,
+        base_date: NaiveDate,
+    ) -> Result<Vec<ChartDataPoint>, CoreError> {
+        points
+            .into_iter()
+            .map(|mut point| {
+                let factor = inflation.deflator(country, point.date, base_date)?;
+                point.portfolio_value *= factor;
+                for event in &mut point.events {
+                    event.value_in_default_currency *= factor;
+                    if let Some(unit_price) = &mut event.unit_price {
+                        *unit_price *= factor;
+                    }
+                }
+                Ok(point)
+            })
+            .collect()
+    }
+
+    /// Display color of each asset, in the order given: the `color_hex` in
+    /// the asset's `metadata` if set, otherwise a palette color picked by
+    /// hashing its symbol and type, so it is the same on every load.
+    pub fn assign_colors(
+        assets: &[Asset],
+        metadata: &HashMap<AssetMetadataKey, AssetMetadata>,
+    ) -> Vec<(Asset, String)> {
+        assets
+            .iter()
+            .map(|asset| (asset.clone(), Self::asset_color(asset, metadata)))
+            .collect()
+    }
+
+    /// Display color of one asset (see `assign_colors`).
+    pub fn asset_color(
+        asset: &Asset,
+        metadata: &HashMap<AssetMetadataKey, AssetMetadata>,
+    ) -> String {
+        let key = (asset.symbol.to_uppercase(), asset.asset_type.clone());
+        if let Some(color) = metadata.get(&key).and_then(|m| m.color_hex.as_ref()) {
+            return color.clone();
+        }
+        // FNV-1a: stable across runs, platforms and Rust versions (unlike `DefaultHasher`)
+        let key = format!("{}:{}", asset.symbol, asset.asset_type);
+        let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        ASSET_PALETTE[(hash % ASSET_PALETTE.len() as u64) as usize].to_string()
+    }
+}
+
+/// Point-by-point portfolio chart computation created by `ChartService::portfolio_chart_iter`.
+///
+/// Uses incremental holdings (O(days + events)): each call to `next_point`
+/// applies the events since the previous point, values the holdings and
+/// returns one point, in date order. Points are daily except over long
+/// ranges (see `ChartResolution`).
+pub struct PortfolioChartIter {
+    currency_service: CurrencyService,
+    currency: String,
+    /// How holdings that can't be priced on a day are valued
+    unpriceable_asset_policy: UnpriceableAssetPolicy,
+    /// `AssetMetadata::manual_price` by uppercase symbol
+    manual_prices: HashMap<AssetMetadataKey, f64>,
+    from: NaiveDate,
+    resolution: ChartResolution,
+    /// Date of the next point
+    current_date: NaiveDate,
+    /// Date of the last point computed (`from` before the first one)
+    last_point: NaiveDate,
+    to: NaiveDate,
+    holdings: HashMap<Asset, f64>,
+    events_by_date: HashMap<NaiveDate, Vec<Event>>,
+    /// The portfolio's accrual rules, growing `holdings` day by day
+    accruals: Vec<AccrualRule>,
+    snapshots: HashMap<NaiveDate, ValueSnapshot>,
+    /// Later days are valued at this date's prices (projections)
+    latest_price_date: Option<NaiveDate>,
+    last_known_value: f64,
+    is_first_day: bool,
+    completed_days: usize,
+    total_days: usize,
+}
+
+impl PortfolioChartIter {
+    /// Number of points the full range produces (one per day unless the
+    /// range is long, see `resolution`).
+    pub fn total_days(&self) -> usize {
+        self.total_days
+    }
+
+    /// Number of points produced so far.
+    pub fn completed_days(&self) -> usize {
+        self.completed_days
+    }
+
+    /// Spacing of the points.
+    pub fn resolution(&self) -> ChartResolution {
+        self.resolution
+    }
+
+    /// Days whose events the point of `date` lists: those after the
+    /// previous point on `last_point`, or just `date` for the first point.
+    fn point_days(
+        is_first: bool,
+        last_point: NaiveDate,
+        date: NaiveDate,
+    ) -> impl Iterator<Item = NaiveDate> {
+        let start = if is_first { Some(date) } else { last_point.succ_opt() };
+        start
+            .into_iter()
+            .flat_map(|start| start.iter_days())
+            .take_while(move |day| *day <= date)
+    }
+
+    /// Date whose prices value the point of `date`.
+    fn price_date(&self, date: NaiveDate) -> NaiveDate {
+        self.latest_price_date.map_or(date, |latest| date.min(latest))
+    }
+
+    /// Assets valued by the points from the current one up to `until`, with
+    /// their dates: every holding on points without a stored snapshot, plus
+    /// the assets of the events since the previous point that their day's
+    /// snapshot doesn't price.
+    fn valuations(&self, until: NaiveDate) -> Vec<(Asset, NaiveDate)> {
+        let mut holdings = self.holdings.clone();
+        let mut is_first_day = self.is_first_day;
+        let mut last_point = self.last_point;
+        let mut date = self.current_date;
+        let mut valuations = Vec::new();
+
+        while date <= until.min(self.to) {
+            let days: Vec<NaiveDate> = Self::point_days(is_first_day, last_point, date).collect();
+            if !is_first_day {
+                for events in days.iter().filter_map(|day| self.events_by_date.get(day)) {
+                    Self::apply_events(&mut holdings, events);
+                }
+            }
+            is_first_day = false;
+
+            if !self.snapshots.contains_key(&date) {
+                let price_date = self.price_date(date);
+                valuations.extend(holdings.keys().map(|asset| (asset.clone(), price_date)));
+            }
+            for event in days.iter().filter_map(|day| self.events_by_date.get(day)).flatten() {
+                let snapshot = self.snapshots.get(&event.date);
+                if snapshot.and_then(|s| s.unit_value(&event.asset)).is_none() {
+                    valuations.push((event.asset.clone(), self.price_date(event.date)));
+                }
+            }
+
+            last_point = date;
+            date = match self.resolution.next_date(self.from, date, self.to) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        valuations
+    }
+
+    /// Grow `holdings` by the accrual of `date` under `accruals`.
+    fn accrue(holdings: &mut HashMap<Asset, f64>, accruals: &[AccrualRule], date: NaiveDate) {
+        let Some(previous) = date.pred_opt() else {
+            return;
+        };
+        for rule in accruals {
+            if let Some(amount) = holdings.get_mut(&rule.asset) {
+                *amount *= rule.growth(previous, date);
+            }
+        }
+    }
+
+    /// Apply one day's events to `holdings`, dropping emptied positions.
+    fn apply_events(holdings: &mut HashMap<Asset, f64>, events: &[Event]) {
+        for event in events {
+            *holdings.entry(event.asset.clone()).or_insert(0.0) += event.holding_delta();
+        }
+        // Clean up zero/negative holdings
+        holdings.retain(|_, amount| *amount > f64::EPSILON);
+    }
+
+    /// Compute the next day's point, or `None` once the range is exhausted.
+    pub async fn next_point(
+        &mut self,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+    ) -> Option<Result<ChartDataPoint, CoreError>> {
+        self.next_point_with_progress(price_service, price_cache, &mut |_| {})
+            .await
+    }
+
+    /// Same as `next_point`, calling `on_progress` before each asset lookup
+    /// and once the day is complete.
+    pub async fn next_point_with_progress(
+        &mut self,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        on_progress: &mut impl FnMut(Progress),
+    ) -> Option<Result<ChartDataPoint, CoreError>> {
+        if self.current_date > self.to {
+            return None;
+        }
+        let current_date = self.current_date;
+        let price_date = self.price_date(current_date);
+        let currency = self.currency.as_str();
+
+        // Accrue and apply events day by day since the previous point (skip
+        // on first day — already in initial holdings)
+        let days: Vec<NaiveDate> =
+            Self::point_days(self.is_first_day, self.last_point, current_date).collect();
+        if !self.is_first_day {
+            for day in &days {
+                Self::accrue(&mut self.holdings, &self.accruals, *day);
+                if let Some(day_events) = self.events_by_date.get(day) {
+                    Self::apply_events(&mut self.holdings, day_events);
+                }
+            }
+        }
+        self.is_first_day = false;
+
+        // Prefer a stored snapshot in the requested currency over recomputation
+        let snapshot = self.snapshots.get(&current_date);
+
+        // Calculate total portfolio value
+        let mut portfolio_value = 0.0;
+        let mut any_price_found = false;
+        // Whether a current quote stands in for a day's close
+        let mut provisional = false;
+
+        if let Some(snapshot) = snapshot {
+            portfolio_value = snapshot.total_value;
+            any_price_found = true;
+        } else {
+            for (asset, amount) in &self.holdings {
+                on_progress(Progress {
+                    completed: self.completed_days,
+                    total: self.total_days,
+                    current_symbol: Some(asset.symbol.clone()),
+                });
+                match self
+                    .currency_service
+                    .value_holding(
+                        price_service,
+                        price_cache,
+                        asset,
+                        *amount,
+                        currency,
+                        price_date,
+                        self.unpriceable_asset_policy,
+                        self.manual_prices
+                            .get(&(asset.symbol.to_uppercase(), asset.asset_type.clone()))
+                            .copied(),
+                    )
+                    .await
+                {
+                    Ok((value, status)) => {
+                        portfolio_value += value;
+                        any_price_found |= status != PriceStatus::Unavailable;
+                        provisional |= status == PriceStatus::Fresh
+                            && self.currency_service.is_provisional(
+                                price_cache,
+                                asset,
+                                currency,
+                                price_date,
+                            );
+                    }
+                    Err(CoreError::Cancelled) => return Some(Err(CoreError::Cancelled)),
+                    Err(_) => {
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // Carry forward last known value on days with no price data (weekends/holidays)
+        if !self.holdings.is_empty() && !any_price_found {
+            portfolio_value = self.last_known_value;
+        } else {
+            self.last_known_value = portfolio_value;
+        }
+
+        // Collect events that happened since the previous point and compute
+        // their values on their own dates
+        let mut chart_events = Vec::new();
+        for day in &days {
+            let Some(day_events) = self.events_by_date.get(day) else {
+                continue;
+            };
+            let snapshot = self.snapshots.get(day);
+            let price_date = self.price_date(*day);
+            for event in day_events {
+                let snapshot_value = snapshot
+                    .and_then(|s| s.unit_value(&event.asset))
+                    .map(|unit| unit * event.amount);
+                let value = match snapshot_value {
+                    Some(value) => Ok(value),
+                    None => self
+                        .currency_service
+                        .convert_asset_to_currency(
+                            price_service,
+                            price_cache,
+                            &event.asset,
+                            event.amount,
+                            currency,
+                            price_date,
+                        )
+                        .await,
+                };
+
+                chart_events.push(ChartEvent {
+                    event_type: event.event_type.clone(),
+                    asset_symbol: event.asset.symbol.clone(),
+                    amount: event.amount,
+                    unit_price: value.as_ref().ok().map(|value| value / event.amount),
+                    value_in_default_currency: value.unwrap_or(0.0),
+                });
+            }
+        }
+
+        self.completed_days += 1;
+        on_progress(Progress {
+            completed: self.completed_days,
+            total: self.total_days,
+            current_symbol: None,
+        });
+
+        // Move to the next point (past `to` after the last one, or `to`
+        // moves back when the calendar ends)
+        self.last_point = current_date;
+        let next = self.resolution.next_date(self.from, current_date, self.to);
+        self.current_date = match next.or_else(|| current_date.succ_opt()) {
+            Some(next) => next,
+            None => {
+                self.to = current_date.pred_opt().unwrap_or(current_date);
+                current_date
+            }
+        };
+
+        Some(Ok(ChartDataPoint {
+            date: current_date,
+            portfolio_value,
+            currency: currency.to_string(),
+            events: chart_events,
+            provisional,
+        }))
     }
 }
 
@@ -1,9 +1,11 @@
 use chrono::NaiveDate;
 
 use crate::errors::CoreError;
+use crate::models::analytics::PriceStatus;
 use crate::models::asset::{Asset, AssetType};
 use crate::models::price::PriceCache;
-use super::price_service::PriceService;
+use crate::models::settings::UnpriceableAssetPolicy;
+use super::price_service::{PriceRequest, PriceService};
 
 /// Handles currency conversion between any two currencies or asset → currency.
 ///
@@ -15,11 +17,102 @@ use super::price_service::PriceService;
 ///
 /// Most API providers return prices in USD. If the target currency
 /// is not USD, we do a two-step conversion: Asset → USD → target currency.
-pub struct CurrencyService;
+///
+/// When a provider has no direct quote for a fiat pair, the rate is
+/// triangulated through the pivot currencies (USD, then EUR by default).
+#[derive(Debug, Clone)]
+pub struct CurrencyService {
+    pivot_currencies: Vec<String>,
+}
 
 impl CurrencyService {
     pub fn new() -> Self {
-        Self
+        Self::with_pivot_currencies(vec!["USD".to_string(), "EUR".to_string()])
+    }
+
+    /// Create a service that triangulates missing fiat pairs through `pivots`, in order.
+    pub fn with_pivot_currencies(pivots: Vec<String>) -> Self {
+        Self {
+            pivot_currencies: pivots.iter().map(|p| p.to_uppercase()).collect(),
+        }
+    }
+
+    /// The currency providers quote an asset in: the currency itself for fiat,
+    /// USD for crypto, metals and stocks.
+    pub fn quote_currency(asset: &Asset) -> String {
+        match asset.asset_type {
+            AssetType::Fiat => asset.symbol.to_uppercase(),
+            AssetType::Crypto | AssetType::Metal | AssetType::Stock => "USD".to_string(),
+        }
+    }
+
+    /// The pivot currencies used for cross-rate triangulation, in order.
+    pub fn pivot_currencies(&self) -> &[String] {
+        &self.pivot_currencies
+    }
+
+    /// The price lookups `convert_asset_to_currency` makes for `asset` on
+    /// `date` when every direct quote is available (no triangulation).
+    pub fn price_requests(
+        &self,
+        asset: &Asset,
+        target_currency: &str,
+        date: NaiveDate,
+    ) -> Vec<PriceRequest> {
+        let target = target_currency.to_uppercase();
+        match asset.asset_type {
+            AssetType::Fiat => {
+                if asset.symbol.eq_ignore_ascii_case(&target) {
+                    return Vec::new();
+                }
+                vec![PriceRequest::new(&asset.symbol, &target, date, &AssetType::Fiat)]
+            }
+            AssetType::Crypto | AssetType::Metal | AssetType::Stock => {
+                let mut requests =
+                    vec![PriceRequest::new(&asset.symbol, "USD", date, &asset.asset_type)];
+                if target != "USD" {
+                    requests.push(PriceRequest::new("USD", &target, date, &AssetType::Fiat));
+                }
+                requests
+            }
+        }
+    }
+
+    /// Whether valuing `asset` in `target_currency` on `date` uses a
+    /// provisional cached price (see `PriceCache::is_provisional`), e.g. a
+    /// current quote standing in for the day's close.
+    pub fn is_provisional(
+        &self,
+        cache: &PriceCache,
+        asset: &Asset,
+        target_currency: &str,
+        date: NaiveDate,
+    ) -> bool {
+        self.price_requests(asset, target_currency, date).iter().any(|request| {
+            cache.is_provisional(
+                &request.symbol,
+                &request.currency,
+                request.date,
+                &request.asset_type,
+            )
+        })
+    }
+
+    /// The two legs (from → pivot, pivot → to) that triangulate a fiat pair
+    /// through `pivot`, or none if the pivot is one of the pair's currencies.
+    pub fn cross_rate_requests(
+        from: &str,
+        to: &str,
+        pivot: &str,
+        date: NaiveDate,
+    ) -> Vec<PriceRequest> {
+        if pivot.eq_ignore_ascii_case(from) || pivot.eq_ignore_ascii_case(to) {
+            return Vec::new();
+        }
+        vec![
+            PriceRequest::new(from, pivot, date, &AssetType::Fiat),
+            PriceRequest::new(pivot, to, date, &AssetType::Fiat),
+        ]
     }
 
     /// Convert an amount of a fiat currency to another fiat currency.
@@ -41,11 +134,61 @@ impl CurrencyService {
         }
 
         // Get the exchange rate from → to using Frankfurter
-        let rate = price_service
+        match price_service
             .get_price(cache, &from, &to, date, &AssetType::Fiat)
-            .await?;
+            .await
+        {
+            Ok(rate) => Ok(amount * rate),
+            Err(e) => match self
+                .cross_rate(price_service, cache, &from, &to, date)
+                .await
+            {
+                Some(rate) => Ok(amount * rate),
+                None => Err(e),
+            },
+        }
+    }
 
-        Ok(amount * rate)
+    /// Derive a from → to rate through a pivot currency when no direct quote exists.
+    ///
+    /// Reuses a previously derived rate from the cache; otherwise tries each pivot
+    /// in order (from → pivot × pivot → to, cached or fetched) and caches the result
+    /// flagged as derived so a later direct quote replaces it.
+    async fn cross_rate(
+        &self,
+        price_service: &PriceService,
+        cache: &mut PriceCache,
+        from: &str,
+        to: &str,
+        date: NaiveDate,
+    ) -> Option<f64> {
+        if cache.is_derived(from, to, date, &AssetType::Fiat) {
+            return cache.get_price(from, to, date, &AssetType::Fiat);
+        }
+
+        for pivot in &self.pivot_currencies {
+            if pivot == from || pivot == to {
+                continue;
+            }
+            let Ok(first_leg) = price_service
+                .get_price(cache, from, pivot, date, &AssetType::Fiat)
+                .await
+            else {
+                continue;
+            };
+            let Ok(second_leg) = price_service
+                .get_price(cache, pivot, to, date, &AssetType::Fiat)
+                .await
+            else {
+                continue;
+            };
+
+            let rate = first_leg * second_leg;
+            cache.set_derived_price(from, to, date, rate, &AssetType::Fiat);
+            return Some(rate);
+        }
+
+        None
     }
 
     /// Convert an asset holding to a target fiat currency.
@@ -91,6 +234,86 @@ impl CurrencyService {
             }
         }
     }
+
+    /// `convert_asset_to_currency` for a holding, valued as `policy` allows
+    /// when its price can't be found: at the latest cached price before
+    /// `date`, at `manual_price` (in the asset's quote currency) or at zero.
+    /// Returns the value and how it was priced. Fails under
+    /// `UnpriceableAssetPolicy::Fail`, and under any policy when the lookup
+    /// was cancelled or failed otherwise (e.g., a network error or a
+    /// missing exchange rate).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn value_holding(
+        &self,
+        price_service: &PriceService,
+        cache: &mut PriceCache,
+        asset: &Asset,
+        amount: f64,
+        target_currency: &str,
+        date: NaiveDate,
+        policy: UnpriceableAssetPolicy,
+        manual_price: Option<f64>,
+    ) -> Result<(f64, PriceStatus), CoreError> {
+        let error = match self
+            .convert_asset_to_currency(price_service, cache, asset, amount, target_currency, date)
+            .await
+        {
+            Ok(value) => return Ok((value, PriceStatus::Fresh)),
+            Err(e) if Self::is_unpriceable(asset, &e) => e,
+            Err(e) => return Err(e),
+        };
+
+        // Fiat is cached as its rate to the target currency, the rest in their quote currency
+        let cached_currency = match asset.asset_type {
+            AssetType::Fiat => target_currency.to_uppercase(),
+            AssetType::Crypto | AssetType::Metal | AssetType::Stock => Self::quote_currency(asset),
+        };
+        let fallback = match policy {
+            UnpriceableAssetPolicy::Fail => return Err(error),
+            UnpriceableAssetPolicy::LastCachedPrice => cache
+                .get_latest_price(&asset.symbol, &cached_currency, date, &asset.asset_type)
+                .map(|point| {
+                    (point.price, cached_currency, PriceStatus::Stale { as_of: point.date })
+                }),
+            UnpriceableAssetPolicy::ManualPrice => manual_price
+                .map(|price| (price, Self::quote_currency(asset), PriceStatus::Manual)),
+            UnpriceableAssetPolicy::Zero => None,
+        };
+        let Some((unit_price, price_currency, status)) = fallback else {
+            return Ok((0.0, PriceStatus::Unavailable));
+        };
+
+        let value = amount * unit_price;
+        match self
+            .convert_fiat(price_service, cache, value, &price_currency, target_currency, date)
+            .await
+        {
+            Ok(value) => Ok((value, status)),
+            Err(CoreError::PriceNotAvailable { .. } | CoreError::NoProvider(_)) => {
+                Ok((0.0, PriceStatus::Unavailable))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether `error` of valuing `asset` means no price of the asset
+    /// itself can be found, rather than a failed or cancelled lookup or a
+    /// missing exchange rate. For fiat, the exchange rate is the price.
+    fn is_unpriceable(asset: &Asset, error: &CoreError) -> bool {
+        let is_fiat = asset.asset_type == AssetType::Fiat;
+        match error {
+            CoreError::PriceNotAvailable { symbol, .. } => {
+                is_fiat || symbol.eq_ignore_ascii_case(&asset.symbol)
+            }
+            CoreError::NoProvider(asset_type) => {
+                is_fiat || *asset_type == asset.asset_type.to_string()
+            }
+            CoreError::SymbolNotMapped { symbol, .. } => {
+                symbol.eq_ignore_ascii_case(&asset.symbol)
+            }
+            _ => false,
+        }
+    }
 }
 
 impl Default for CurrencyService {
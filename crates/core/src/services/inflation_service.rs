@@ -0,0 +1,133 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::errors::CoreError;
+use crate::inflation::embedded::EmbeddedCpiProvider;
+use crate::inflation::traits::InflationProvider;
+use crate::models::inflation::CpiPoint;
+
+/// Looks up consumer price indices and turns them into deflators
+/// for real (inflation-adjusted) values.
+///
+/// - Data comes from an `InflationProvider` (the embedded table by default).
+/// - Manual overrides take precedence over the provider's values.
+/// - Missing months between two known months are interpolated linearly;
+///   months after the last known one reuse the latest value.
+pub struct InflationService {
+    provider: Box<dyn InflationProvider>,
+    overrides: EmbeddedCpiProvider,
+}
+
+impl InflationService {
+    /// Create a service backed by the embedded CPI table.
+    pub fn new() -> Self {
+        Self::with_provider(Box::new(EmbeddedCpiProvider::new()))
+    }
+
+    /// Create a service backed by a custom provider.
+    pub fn with_provider(provider: Box<dyn InflationProvider>) -> Self {
+        Self {
+            provider,
+            overrides: EmbeddedCpiProvider::empty(),
+        }
+    }
+
+    /// Replace the CPI provider. Manual overrides are kept.
+    pub fn set_provider(&mut self, provider: Box<dyn InflationProvider>) {
+        self.provider = provider;
+    }
+
+    /// Name of the current CPI provider.
+    pub fn provider_name(&self) -> &str {
+        self.provider.name()
+    }
+
+    /// Set the CPI value for one month of a country, taking precedence over the provider.
+    pub fn set_override(
+        &mut self,
+        country: &str,
+        year: i32,
+        month: u32,
+        index: f64,
+    ) -> Result<(), CoreError> {
+        self.overrides.set_override(country, year, month, index)
+    }
+
+    /// Country whose CPI is used to deflate values in `currency`, if known.
+    pub fn country_for_currency(currency: &str) -> Option<&'static str> {
+        match currency.to_uppercase().as_str() {
+            "USD" => Some("US"),
+            "EUR" => Some("EU"),
+            "PLN" => Some("PL"),
+            _ => None,
+        }
+    }
+
+    /// Known monthly CPI values for a country (provider merged with overrides), sorted.
+    pub fn monthly_cpi(&self, country: &str) -> Vec<CpiPoint> {
+        let mut points = self.provider.monthly_cpi(country);
+        for point in self.overrides.monthly_cpi(country) {
+            match points.binary_search_by_key(&(point.year, point.month), |p| (p.year, p.month)) {
+                Ok(idx) => points[idx] = point,
+                Err(idx) => points.insert(idx, point),
+            }
+        }
+        points
+    }
+
+    /// CPI for the month containing `date`.
+    ///
+    /// Returns `None` if the country is unknown or `date` is before the first known month.
+    pub fn cpi_at(&self, country: &str, date: NaiveDate) -> Option<f64> {
+        let points = self.monthly_cpi(country);
+        let target = Self::month_number(date.year(), date.month());
+
+        let next_idx = points.partition_point(|p| Self::month_number(p.year, p.month) < target);
+        let next = points.get(next_idx);
+        if let Some(next) = next {
+            if Self::month_number(next.year, next.month) == target {
+                return Some(next.index);
+            }
+        }
+
+        let prev = next_idx.checked_sub(1).map(|idx| &points[idx])?;
+        match next {
+            Some(next) => {
+                let prev_month = Self::month_number(prev.year, prev.month);
+                let next_month = Self::month_number(next.year, next.month);
+                let t = (target - prev_month) as f64 / (next_month - prev_month) as f64;
+                Some(prev.index + (next.index - prev.index) * t)
+            }
+            // After the last published month: carry the latest value forward
+            None => Some(prev.index),
+        }
+    }
+
+    /// Factor that converts a value from `date` money into `base_date` money:
+    /// CPI(base_date) / CPI(date).
+    pub fn deflator(
+        &self,
+        country: &str,
+        date: NaiveDate,
+        base_date: NaiveDate,
+    ) -> Result<f64, CoreError> {
+        let unavailable = |d: NaiveDate| CoreError::InflationDataUnavailable {
+            country: country.to_uppercase(),
+            date: d.to_string(),
+        };
+        let cpi = self.cpi_at(country, date).ok_or_else(|| unavailable(date))?;
+        let base = self
+            .cpi_at(country, base_date)
+            .ok_or_else(|| unavailable(base_date))?;
+        Ok(base / cpi)
+    }
+
+    fn month_number(year: i32, month: u32) -> i64 {
+        i64::from(year) * 12 + i64::from(month) - 1
+    }
+}
+
+impl Default for InflationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
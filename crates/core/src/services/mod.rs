@@ -1,5 +1,12 @@
+pub mod alert_service;
 pub mod analytics_service;
+pub mod api_usage;
 pub mod chart_service;
 pub mod currency_service;
+pub mod inflation_service;
 pub mod portfolio_service;
+pub mod price_diagnostics;
 pub mod price_service;
+pub mod price_warnings;
+pub mod response_cache;
+pub mod simulation_service;
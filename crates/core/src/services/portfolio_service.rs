@@ -1,11 +1,20 @@
-use chrono::{NaiveDate, Utc};
-use std::collections::HashMap;
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 use crate::errors::CoreError;
-use crate::models::asset::Asset;
-use crate::models::event::{Event, EventType};
+use crate::models::accrual::AccrualRule;
+use crate::models::alert::{Alert, AlertCondition};
+use crate::models::asset::{Asset, AssetMetadata, AssetType, MAX_DISPLAY_PRECISION};
+use crate::models::corporate_action::{CorporateAction, CorporateActionType};
+use crate::models::event::{newest_first, Event, EventChanges, EventFilter, EventType};
+use crate::models::holdings::HoldingsOptions;
+use crate::models::integrity::{IntegrityIssue, IntegrityReport, RepairOptions};
 use crate::models::portfolio::Portfolio;
+use crate::models::preview::{EventPreview, HoldingChange};
+use crate::models::reminder::Reminder;
+use crate::models::settings::ValidationPolicy;
+use crate::models::snapshot::ValueSnapshot;
 
 /// Manages portfolio events (buy/sell) and calculates current holdings.
 ///
@@ -18,19 +27,68 @@ impl PortfolioService {
     }
 
     /// Add a new event to the portfolio.
-    /// Validates the event before adding (e.g., can't sell more than you own).
-    pub fn add_event(&self, portfolio: &mut Portfolio, event: Event) -> Result<(), CoreError> {
-        self.validate_event(portfolio, &event)?;
+    /// Validates the event before adding (e.g., can't sell more than you own,
+    /// then or for later sells) and normalizes its notes (see `normalize_notes`).
+    pub fn add_event(&self, portfolio: &mut Portfolio, mut event: Event) -> Result<(), CoreError> {
+        let policy = &portfolio.settings.validation_policy;
+        if let Some(max_events) = policy.max_events {
+            if portfolio.active_events().count() >= max_events {
+                return Err(CoreError::ValidationError(format!(
+                    "The portfolio already has the maximum of {max_events} events"
+                )));
+            }
+        }
+        event.notes = Self::normalize_notes(policy, event.notes.take())?;
+        self.validate_event(portfolio, &event, None)?;
+        let (date, reduces_holding) = (event.date, !event.event_type.increases_holding());
         // Binary insert: find the correct position to maintain date-sorted order (O(log n))
         let pos = portfolio
             .events
             .binary_search_by_key(&event.date, |e| e.date)
             .unwrap_or_else(|pos| pos);
         portfolio.events.insert(pos, event);
+        portfolio.mark_events_changed();
+
+        // A sell can leave too little for later sells of the asset
+        if reduces_holding {
+            if let Err(e) = self.validate_portfolio_consistency(portfolio, date) {
+                portfolio.events.remove(pos);
+                portfolio.mark_events_changed();
+                return Err(e);
+            }
+        }
         Ok(())
     }
 
-    /// Remove an event by its UUID.
+    /// Add `event` to a copy of `portfolio` (without its price cache) and
+    /// compare the holdings on the event date. Validated like `add_event`,
+    /// with the same errors. Returns the copy with the event added.
+    pub fn preview_event(
+        &self,
+        portfolio: &Portfolio,
+        event: Event,
+    ) -> Result<(Portfolio, EventPreview), CoreError> {
+        let mut preview = portfolio.clone_without_prices();
+        let date = event.date;
+        self.add_event(&mut preview, event.clone())?;
+
+        let before = self.get_holdings(portfolio, date);
+        let after = self.get_holdings(&preview, date);
+        let mut holdings: Vec<HoldingChange> = before
+            .keys()
+            .chain(after.keys().filter(|asset| !before.contains_key(*asset)))
+            .map(|asset| HoldingChange {
+                asset: asset.clone(),
+                before: before.get(asset).copied().unwrap_or(0.0),
+                after: after.get(asset).copied().unwrap_or(0.0),
+            })
+            .collect();
+        holdings.sort_by(|a, b| a.asset.symbol.cmp(&b.asset.symbol));
+
+        Ok((preview, EventPreview { event, holdings }))
+    }
+
+    /// Remove an event by its UUID for good (deleted events too).
     /// Revalidates all subsequent sell events to ensure portfolio consistency.
     pub fn remove_event(&self, portfolio: &mut Portfolio, event_id: Uuid) -> Result<(), CoreError> {
         let idx = portfolio
@@ -40,21 +98,134 @@ impl PortfolioService {
             .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))?;
 
         let removed = portfolio.events.remove(idx);
+        portfolio.mark_events_changed();
 
         // Revalidate: check all sell events at or after the removed event's date
         // to ensure none would cause negative holdings.
-        if removed.event_type == EventType::Buy {
+        if removed.event_type.increases_holding() && !removed.is_deleted() {
             if let Err(e) = self.validate_portfolio_consistency(portfolio, removed.date) {
-                // Rollback: re-insert at correct position
-                Self::binary_insert(&mut portfolio.events, removed);
+                // Rollback: re-insert where it was, ahead of same-day events
+                // added after it
+                portfolio.events.insert(idx, removed);
+                portfolio.mark_events_changed();
                 return Err(e);
             }
         }
+        portfolio.reminders.retain(|r| r.event_id != event_id);
 
         Ok(())
     }
 
+    /// Mark an event deleted at `at`, keeping it in the history.
+    /// Revalidates subsequent sells like `remove_event`. Returns the event
+    /// as it was before.
+    pub fn delete_event(
+        &self,
+        portfolio: &mut Portfolio,
+        event_id: Uuid,
+        at: DateTime<Utc>,
+    ) -> Result<Event, CoreError> {
+        let idx = Self::active_index(portfolio, event_id)?;
+        let original = portfolio.events[idx].clone();
+        portfolio.events[idx].deleted_at = Some(at);
+        portfolio.mark_events_changed();
+
+        if original.event_type.increases_holding() {
+            if let Err(e) = self.validate_portfolio_consistency(portfolio, original.date) {
+                portfolio.events[idx].deleted_at = None;
+                portfolio.mark_events_changed();
+                return Err(e);
+            }
+        }
+        Ok(original)
+    }
+
+    /// Replace an event with `replacements` in one step: it is marked deleted
+    /// at `at` and the replacements are added. Each replacement is validated
+    /// like an added event, against the history without the replaced one;
+    /// later sells are revalidated once all are added, so sells they cover
+    /// stay valid. On failure nothing changes. Returns the replacement ids.
+    pub fn replace_with_events(
+        &self,
+        portfolio: &mut Portfolio,
+        event_id: Uuid,
+        replacements: Vec<Event>,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, CoreError> {
+        let idx = Self::active_index(portfolio, event_id)?;
+        if replacements.is_empty() {
+            return Err(CoreError::ValidationError(
+                "Replacing an event needs at least one replacement (delete it instead)".into(),
+            ));
+        }
+        if replacements.iter().any(|e| e.id == event_id) {
+            return Err(CoreError::ValidationError(
+                "Replacements need ids of their own, not the replaced event's".into(),
+            ));
+        }
+
+        let original = portfolio.events.clone();
+        let mut from = portfolio.events[idx].date;
+        portfolio.events[idx].deleted_at = Some(at);
+        portfolio.mark_events_changed();
+
+        let mut ids = Vec::with_capacity(replacements.len());
+        let result = replacements
+            .into_iter()
+            .try_for_each(|event| {
+                from = from.min(event.date);
+                ids.push(event.id);
+                self.add_event(portfolio, event)
+            })
+            .and_then(|()| self.validate_portfolio_consistency(portfolio, from));
+        if let Err(e) = result {
+            portfolio.events = original;
+            portfolio.mark_events_changed();
+            return Err(e);
+        }
+        Ok(ids)
+    }
+
+    /// Undelete a deleted event. It is validated like a newly added event,
+    /// and later sells are revalidated; on failure it stays deleted.
+    /// Returns the restored event.
+    pub fn restore_event(
+        &self,
+        portfolio: &mut Portfolio,
+        event_id: Uuid,
+    ) -> Result<Event, CoreError> {
+        let idx = portfolio
+            .events
+            .iter()
+            .position(|e| e.id == event_id && e.is_deleted())
+            .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))?;
+        let restored = Event {
+            deleted_at: None,
+            ..portfolio.events[idx].clone()
+        };
+        self.validate_event(portfolio, &restored, None)?;
+
+        let deleted_at = std::mem::take(&mut portfolio.events[idx].deleted_at);
+        portfolio.mark_events_changed();
+        if let Err(e) = self.validate_portfolio_consistency(portfolio, restored.date) {
+            portfolio.events[idx].deleted_at = deleted_at;
+            portfolio.mark_events_changed();
+            return Err(e);
+        }
+        Ok(restored)
+    }
+
+    /// Index of the non-deleted event with id `event_id`.
+    fn active_index(portfolio: &Portfolio, event_id: Uuid) -> Result<usize, CoreError> {
+        portfolio
+            .events
+            .iter()
+            .position(|e| e.id == event_id && !e.is_deleted())
+            .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))
+    }
+
     /// Update an existing event. Validates the new state before committing.
+    /// A planned event stays planned.
     pub fn update_event(
         &self,
         portfolio: &mut Portfolio,
@@ -64,32 +235,217 @@ impl PortfolioService {
         amount: f64,
         date: NaiveDate,
     ) -> Result<(), CoreError> {
-        let idx = portfolio
+        self.replace_event(portfolio, event_id, |old_event| {
+            // A recorded unit price only stays valid for the same asset and date
+            let recorded_value = if asset == old_event.asset && date == old_event.date {
+                old_event.recorded_value.clone()
+            } else {
+                None
+            };
+            Event {
+                id: old_event.id,
+                event_type,
+                asset,
+                amount,
+                date,
+                notes: old_event.notes.clone(),
+                recorded_value,
+                is_planned: old_event.is_planned,
+                deleted_at: None,
+            }
+        })
+    }
+
+    /// Turn a planned event into an actual one that happened on `date` with
+    /// `amount`, validated like any actual event (e.g., no future dates).
+    pub fn convert_planned_to_actual(
+        &self,
+        portfolio: &mut Portfolio,
+        event_id: Uuid,
+        date: NaiveDate,
+        amount: f64,
+    ) -> Result<(), CoreError> {
+        let idx = Self::active_index(portfolio, event_id)?;
+        if !portfolio.events[idx].is_planned {
+            return Err(CoreError::ValidationError(format!(
+                "Event {event_id} is not a planned event"
+            )));
+        }
+        self.replace_event(portfolio, event_id, |old_event| Event {
+            amount,
+            date,
+            // The planned date's price says nothing about the actual one
+            recorded_value: None,
+            is_planned: false,
+            ..old_event.clone()
+        })
+    }
+
+    /// Apply `changes` to every event matching `filter`, all-or-nothing.
+    ///
+    /// The changed events are validated against the original portfolio,
+    /// then every sell is revalidated against the changed history. On any
+    /// failure the events are left unchanged. Returns the number of events
+    /// changed.
+    pub fn bulk_update_events(
+        &self,
+        portfolio: &mut Portfolio,
+        filter: &EventFilter,
+        changes: &EventChanges,
+    ) -> Result<usize, CoreError> {
+        if changes.is_empty() {
+            return Err(CoreError::ValidationError("No changes to apply".into()));
+        }
+        if let Some(factor) = changes.scale_amount {
+            if !factor.is_finite() || factor <= 0.0 {
+                return Err(CoreError::ValidationError(format!(
+                    "Invalid amount scale {factor}: must be a positive number"
+                )));
+            }
+        }
+
+        let policy = &portfolio.settings.validation_policy;
+        let changes = &EventChanges {
+            set_notes: match &changes.set_notes {
+                Some(notes) => Some(Self::normalize_notes(policy, notes.clone())?),
+                None => None,
+            },
+            ..changes.clone()
+        };
+
+        let mut updated: HashMap<Uuid, Event> = HashMap::new();
+        for event in portfolio.events.iter().filter(|e| filter.matches(e)) {
+            let changed = Self::apply_changes(event, changes)?;
+            Self::validate_event_fields(portfolio, &changed, Some(event))?;
+            updated.insert(event.id, changed);
+        }
+        let count = updated.len();
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let mut events: Vec<Event> = portfolio
             .events
             .iter()
-            .position(|e| e.id == event_id)
-            .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))?;
+            .map(|e| updated.remove(&e.id).unwrap_or_else(|| e.clone()))
+            .collect();
+        // Stable: events of the same date keep their order
+        events.sort_by_key(|e| e.date);
+        let original = std::mem::replace(&mut portfolio.events, events);
+        portfolio.mark_events_changed();
+
+        let from = portfolio.events.first().map_or(NaiveDate::MIN, |e| e.date);
+        if let Err(e) = self.validate_portfolio_consistency(portfolio, from) {
+            portfolio.events = original;
+            portfolio.mark_events_changed();
+            return Err(e);
+        }
+        Ok(count)
+    }
+
+    /// `event` with `changes` applied. A recorded unit price is dropped when
+    /// the asset or date changes, like `update_event` does.
+    fn apply_changes(event: &Event, changes: &EventChanges) -> Result<Event, CoreError> {
+        let mut changed = event.clone();
+        if let Some(asset) = &changes.new_asset {
+            changed.asset = asset.clone();
+        }
+        if let Some(days) = changes.shift_date_days {
+            changed.date = chrono::Duration::try_days(days)
+                .and_then(|shift| event.date.checked_add_signed(shift))
+                .ok_or_else(|| {
+                    CoreError::ValidationError(format!(
+                        "Shifting {} by {days} days is out of range",
+                        event.date
+                    ))
+                })?;
+        }
+        if let Some(factor) = changes.scale_amount {
+            changed.amount *= factor;
+        }
+        if let Some(notes) = &changes.set_notes {
+            changed.notes = notes.clone();
+        }
+        if changed.asset != event.asset || changed.date != event.date {
+            changed.recorded_value = None;
+        }
+        Ok(changed)
+    }
+
+    /// Replace all events (e.g., with the result of a sync merge) and
+    /// revalidate every sell. Events are sorted by date; on failure the
+    /// events are left unchanged.
+    pub fn replace_events(
+        &self,
+        portfolio: &mut Portfolio,
+        mut events: Vec<Event>,
+    ) -> Result<(), CoreError> {
+        events.sort_by_key(|e| e.date);
+        let original = std::mem::replace(&mut portfolio.events, events);
+        portfolio.mark_events_changed();
+
+        if let Err(e) = self.validate_portfolio_consistency(portfolio, NaiveDate::MIN) {
+            portfolio.events = original;
+            portfolio.mark_events_changed();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Make `version` the event with id `event_id`: replace the current one,
+    /// add it if there is none, or remove the current one if `version` is
+    /// `None`. The new version is validated like an update.
+    pub fn set_event_version(
+        &self,
+        portfolio: &mut Portfolio,
+        event_id: Uuid,
+        version: Option<Event>,
+    ) -> Result<(), CoreError> {
+        let current = portfolio.events.iter().find(|e| e.id == event_id);
+        if let Some(event) = &version {
+            if event.id != event_id {
+                return Err(CoreError::ValidationError(format!(
+                    "Event id {} does not match {event_id}",
+                    event.id
+                )));
+            }
+            Self::validate_event_fields(portfolio, event, current)?;
+        }
+        let events = portfolio
+            .events
+            .iter()
+            .filter(|e| e.id != event_id)
+            .cloned()
+            .chain(version)
+            .collect();
+        self.replace_events(portfolio, events)
+    }
+
+    /// Replace an event with the one `update` builds from it, validating the
+    /// new state and rolling back on failure.
+    fn replace_event(
+        &self,
+        portfolio: &mut Portfolio,
+        event_id: Uuid,
+        update: impl FnOnce(&Event) -> Event,
+    ) -> Result<(), CoreError> {
+        let idx = Self::active_index(portfolio, event_id)?;
 
         // Take the old event out, apply changes, validate, then commit
         let old_event = portfolio.events.remove(idx);
-
-        let updated = Event {
-            id: old_event.id,
-            event_type,
-            asset,
-            amount,
-            date,
-            notes: old_event.notes.clone(),
-        };
+        portfolio.mark_events_changed();
+        let updated = update(&old_event);
+        let date = updated.date;
 
         // Validate the updated event against the portfolio (without the old event)
-        if let Err(e) = self.validate_event(portfolio, &updated) {
-            // Rollback: put the old event back
-            Self::binary_insert(&mut portfolio.events, old_event);
+        if let Err(e) = self.validate_event(portfolio, &updated, Some(&old_event)) {
+            // Rollback: put the old event back where it was
+            portfolio.events.insert(idx, old_event);
+            portfolio.mark_events_changed();
             return Err(e);
         }
 
-        Self::binary_insert(&mut portfolio.events, updated);
+        Self::binary_insert(portfolio, updated);
 
         // Revalidate consistency of all subsequent events
         let check_date = portfolio.events.iter().map(|e| e.date).min().unwrap_or(date);
@@ -98,135 +454,912 @@ impl PortfolioService {
             if let Some(new_idx) = portfolio.events.iter().position(|e| e.id == old_event.id) {
                 portfolio.events.remove(new_idx);
             }
-            Self::binary_insert(&mut portfolio.events, old_event);
+            portfolio.events.insert(idx, old_event);
+            portfolio.mark_events_changed();
             return Err(e);
         }
 
         Ok(())
     }
 
-    /// Get all events sorted by date (newest first for display).
+    /// Get all actual events sorted by date (newest first for display).
+    /// Planned events are left out (see `get_planned_events`).
     pub fn get_events<'a>(&self, portfolio: &'a Portfolio) -> Vec<&'a Event> {
-        let mut events: Vec<&Event> = portfolio.events.iter().collect();
-        events.sort_by(|a, b| b.date.cmp(&a.date)); // newest first
-        events
+        newest_first(portfolio.actual_events())
+    }
+
+    /// Get planned events, newest first like `get_events`.
+    pub fn get_planned_events<'a>(&self, portfolio: &'a Portfolio) -> Vec<&'a Event> {
+        newest_first(portfolio.planned_events())
     }
 
     /// Calculate how much of each asset is held on a specific date.
     ///
-    /// Iterates through all events up to `date`, summing buys and subtracting sells.
+    /// Sums buys and subtracts sells of all events up to `date`, using the
+    /// portfolio's holdings index (built once per change to the events), so
+    /// a lookup is O(assets × log events).
     /// Returns only assets with positive holdings (amount > 0).
+    /// Planned events are not counted.
     pub fn get_holdings(&self, portfolio: &Portfolio, date: NaiveDate) -> HashMap<Asset, f64> {
-        let mut holdings: HashMap<Asset, f64> = HashMap::new();
+        portfolio.holdings_index().holdings_at(date)
+    }
 
-        for event in &portfolio.events {
-            if event.date > date {
-                continue; // skip future events
-            }
+    /// Every asset transacted on or before `date` with the amount held then,
+    /// including assets since sold out or withdrawn (amount 0). Unlike
+    /// `get_holdings`, this tells a fully sold asset (present, 0) from one
+    /// never held (absent).
+    pub fn get_all_positions(
+        &self,
+        portfolio: &Portfolio,
+        date: NaiveDate,
+    ) -> HashMap<Asset, f64> {
+        portfolio.holdings_index().positions_at(date)
+    }
 
-            let amount = holdings.entry(event.asset.clone()).or_insert(0.0);
-            match event.event_type {
-                EventType::Buy => *amount += event.amount,
-                EventType::Sell => *amount -= event.amount,
+    /// Amount of `symbol` (case-insensitive, summed over asset types) held
+    /// from `from` to `to`, from its actual events alone, adjusted for
+    /// corporate actions and accrual like `get_holdings`: a point at `from`
+    /// with the amount held then, one per date in the range whose events
+    /// change it, and one at `to`.
+    pub fn asset_holdings_series(
+        &self,
+        portfolio: &Portfolio,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, f64)>, CoreError> {
+        if from > to {
+            return Err(CoreError::ValidationError(format!(
+                "'from' date ({from}) must not be after 'to' date ({to})"
+            )));
+        }
+        let symbol = portfolio.current_symbol(symbol.trim());
+        let valued = portfolio.valued_events();
+        let events: Vec<&Event> = valued.actual().filter(|e| e.asset.symbol == symbol).collect();
+        if events.is_empty() {
+            return Err(CoreError::ValidationError(format!("Asset {symbol} has no events")));
+        }
+
+        // Amounts from the holdings index, so accrual between events counts
+        let assets: HashSet<&Asset> = events.iter().map(|e| &e.asset).collect();
+        let index = portfolio.holdings_index();
+        let held_on = |date| assets.iter().map(|asset| index.amount_at(asset, date)).sum::<f64>();
+
+        // Held at the end of `from`, that day's events included
+        let mut series = vec![(from, held_on(from))];
+        for event in events.iter().filter(|e| e.date > from && e.date <= to) {
+            if series.last().is_some_and(|&(date, _)| date < event.date) {
+                series.push((event.date, held_on(event.date)));
             }
         }
+        if series.last().is_some_and(|&(date, _)| date < to) {
+            series.push((to, held_on(to)));
+        }
+        Ok(series)
+    }
 
-        // Remove assets with zero or negative holdings
-        holdings.retain(|_, amount| *amount > f64::EPSILON);
-        holdings
+    /// `get_holdings` with `options`, e.g. counting planned events as if
+    /// they had happened.
+    pub fn get_holdings_with(
+        &self,
+        portfolio: &Portfolio,
+        date: NaiveDate,
+        options: &HoldingsOptions,
+    ) -> HashMap<Asset, f64> {
+        if options.include_planned {
+            portfolio.planned_holdings_index().holdings_at(date)
+        } else {
+            self.get_holdings(portfolio, date)
+        }
     }
 
     /// Validate an event before adding it to the portfolio.
     ///
-    /// Rules:
-    /// - Amount must be positive
-    /// - Can't sell more than you currently own at that date
-    fn validate_event(&self, portfolio: &Portfolio, event: &Event) -> Result<(), CoreError> {
-        if event.amount <= 0.0 {
-            return Err(CoreError::ValidationError(
-                "Event amount must be positive".into(),
-            ));
+    /// Rules (tuned by the portfolio's `ValidationPolicy`):
+    /// - Amount must be positive (and at most `max_amount`, if set)
+    /// - Date at most `allow_future_days` after today (planned events may be
+    ///   dated any time)
+    /// - Can't sell more than you currently own at that date (unless
+    ///   `allow_oversell`); planned sells count planned events as owned
+    /// - Asset already known to the portfolio (if `require_known_asset`);
+    ///   the asset of `replaced`, the event being updated, counts as known
+    fn validate_event(
+        &self,
+        portfolio: &Portfolio,
+        event: &Event,
+        replaced: Option<&Event>,
+    ) -> Result<(), CoreError> {
+        Self::validate_event_fields(portfolio, event, replaced)?;
+
+        // For sells and withdrawals, check you have enough of the asset,
+        // in the units the (split-adjusted) holdings count
+        if !event.event_type.increases_holding()
+            && !portfolio.settings.validation_policy.allow_oversell
+        {
+            let event = portfolio.valued_event(event);
+            let options = HoldingsOptions { include_planned: event.is_planned };
+            let holdings = self.get_holdings_with(portfolio, event.date, &options);
+            let current_amount = holdings.get(&event.asset).copied().unwrap_or(0.0);
+
+            if current_amount < event.amount {
+                let message = if event.event_type == EventType::Withdrawal {
+                    format!(
+                        "Cannot withdraw {} {} — the cash balance is only {} on {}",
+                        event.amount, event.asset.symbol, current_amount, event.date
+                    )
+                } else {
+                    format!(
+                        "Cannot sell {} {} — you only hold {} on {}",
+                        event.amount, event.asset.symbol, current_amount, event.date
+                    )
+                };
+                return Err(CoreError::ValidationError(message));
+            }
         }
 
-        // Warn about future dates (allow +1 day tolerance for timezone differences)
+        Ok(())
+    }
+
+    /// The rules of `validate_event` that don't depend on other events'
+    /// amounts: everything but the sell check.
+    fn validate_event_fields(
+        portfolio: &Portfolio,
+        event: &Event,
+        replaced: Option<&Event>,
+    ) -> Result<(), CoreError> {
+        let policy = &portfolio.settings.validation_policy;
+        event.validate()?;
+        if let Some(max_amount) = policy.max_amount {
+            if event.amount > max_amount {
+                return Err(CoreError::ValidationError(format!(
+                    "Event amount {} exceeds the maximum of {}",
+                    event.amount, max_amount
+                )));
+            }
+        }
+
+        // Reject future dates beyond the allowed window
+        // (the default +1 day is tolerance for timezone differences)
         let today = Utc::now().date_naive();
-        if let Some(tomorrow) = today.succ_opt() {
-            if event.date > tomorrow {
+        let latest = today.checked_add_days(Days::new(policy.allow_future_days.into()));
+        if let (false, Some(latest)) = (event.is_planned, latest) {
+            if event.date > latest {
                 return Err(CoreError::ValidationError(
                     format!("Event date {} is in the future — prices won't be available", event.date),
                 ));
             }
         }
 
-        // For sell events, check you have enough of the asset
-        if event.event_type == EventType::Sell {
-            let holdings = self.get_holdings(portfolio, event.date);
-            let current_amount = holdings.get(&event.asset).copied().unwrap_or(0.0);
+        if event.event_type.is_cash_flow() && event.asset.asset_type != AssetType::Fiat {
+            return Err(CoreError::ValidationError(format!(
+                "{} of {} rejected: deposits and withdrawals are only for fiat currencies",
+                event.event_type, event.asset.symbol
+            )));
+        }
 
-            if current_amount < event.amount {
-                return Err(CoreError::ValidationError(format!(
-                    "Cannot sell {} {} — you only hold {} on {}",
-                    event.amount, event.asset.symbol, current_amount, event.date
-                )));
-            }
+        if policy.require_known_asset
+            && replaced.is_none_or(|old| old.asset != event.asset)
+            && !Self::is_known_asset(portfolio, &event.asset)
+        {
+            return Err(CoreError::ValidationError(format!(
+                "Unknown asset {}: it has no other events, metadata or cached prices",
+                event.asset.symbol
+            )));
         }
 
         Ok(())
     }
 
+    /// Whether the portfolio has seen `asset`: in an event (including
+    /// deleted ones), in stored metadata or in the price cache.
+    fn is_known_asset(portfolio: &Portfolio, asset: &Asset) -> bool {
+        let symbol = asset.symbol.to_uppercase();
+        portfolio.events.iter().any(|e| e.asset.symbol == symbol)
+            || portfolio.asset_metadata.contains_key(&(symbol.clone(), asset.asset_type.clone()))
+            || portfolio.price_cache.entries.keys().any(|(cached, _, _)| *cached == symbol)
+    }
+
     /// Validate that no sell event in the portfolio causes negative holdings
-    /// from `from_date` onwards. Used after event removal or update.
+    /// from `from_date` onwards, with corporate actions applied. Used after
+    /// event removal or update and after changes to corporate actions.
+    /// Actual sells are checked against actual holdings, planned sells
+    /// against holdings including planned events.
+    /// Always passes when the policy allows overselling.
     fn validate_portfolio_consistency(
         &self,
         portfolio: &Portfolio,
         from_date: NaiveDate,
     ) -> Result<(), CoreError> {
-        // Re-simulate holdings day by day for sell events from from_date onwards
-        let mut holdings: HashMap<Asset, f64> = HashMap::new();
-
-        for event in &portfolio.events {
-            let amount = holdings.entry(event.asset.clone()).or_insert(0.0);
-            match event.event_type {
-                EventType::Buy => *amount += event.amount,
-                EventType::Sell => {
-                    if event.date >= from_date && *amount < event.amount {
-                        return Err(CoreError::ValidationError(format!(
-                            "Removing/updating this event would make sell of {} {} on {} invalid \
-                             (only {:.8} would be held)",
-                            event.amount,
-                            event.asset.symbol,
-                            event.date,
-                            *amount,
-                        )));
-                    }
-                    *amount -= event.amount;
-                }
+        if portfolio.settings.validation_policy.allow_oversell {
+            return Ok(());
+        }
+
+        // Re-simulate holdings day by day for sell events from from_date onwards:
+        // the amount held and the date of its last event, per asset
+        let mut actual: HashMap<Asset, (f64, NaiveDate)> = HashMap::new();
+        let mut with_planned: HashMap<Asset, (f64, NaiveDate)> = HashMap::new();
+        // Held on the event date, grown under the accrual rules since
+        let held_on = |held: &HashMap<Asset, (f64, NaiveDate)>, event: &Event| {
+            held.get(&event.asset).map_or(0.0, |&(amount, since)| {
+                amount * portfolio.accrual_growth(&event.asset, since, event.date)
+            })
+        };
+
+        let valued = portfolio.valued_events();
+        let active: Vec<&Event> = valued.active().collect();
+        // Sells are checked against everything held on their date, like
+        // `validate_event` does, whatever the order of same-day events
+        let by_day = active.chunk_by(|a, b| a.date == b.date).flat_map(|day| {
+            let (increases, decreases): (Vec<&Event>, Vec<&Event>) =
+                day.iter().partition(|e| e.event_type.increases_holding());
+            increases.into_iter().chain(decreases)
+        });
+        for event in by_day {
+            let delta = event.holding_delta();
+            let amount = held_on(if event.is_planned { &with_planned } else { &actual }, event);
+            if !event.event_type.increases_holding()
+                && event.date >= from_date
+                && amount < event.amount
+            {
+                return Err(CoreError::ValidationError(format!(
+                    "This change would make the {} of {} {} on {} invalid \
+                     (only {:.8} would be held)",
+                    event.event_type.to_string().to_lowercase(),
+                    event.amount, event.asset.symbol, event.date, amount,
+                )));
+            }
+            if !event.is_planned {
+                let held = held_on(&actual, event) + delta;
+                actual.insert(event.asset.clone(), (held, event.date));
             }
+            let held = held_on(&with_planned, event) + delta;
+            with_planned.insert(event.asset.clone(), (held, event.date));
         }
         Ok(())
     }
 
-    /// Binary insert into a date-sorted Vec<Event> in O(log n).
-    fn binary_insert(events: &mut Vec<Event>, event: Event) {
-        let pos = events
+    /// Look for problems event validation normally prevents, e.g. in a file
+    /// edited by hand: duplicate ids, deleted events reusing ids in use,
+    /// invalid amounts and (unless oversells are allowed) holdings going
+    /// negative. `include_prices` also scans the price cache for non-finite
+    /// prices.
+    pub fn check_integrity(&self, portfolio: &Portfolio, include_prices: bool) -> IntegrityReport {
+        let mut issues = Vec::new();
+
+        let mut active_ids: HashMap<Uuid, usize> = HashMap::new();
+        for event in portfolio.active_events() {
+            *active_ids.entry(event.id).or_default() += 1;
+        }
+        let mut reported: HashSet<Uuid> = HashSet::new();
+        for event in portfolio.active_events() {
+            let count = active_ids[&event.id];
+            if count > 1 && reported.insert(event.id) {
+                issues.push(IntegrityIssue::DuplicateEventId { event_id: event.id, count });
+            }
+        }
+        for event in portfolio.deleted_events() {
+            if active_ids.contains_key(&event.id) {
+                issues.push(IntegrityIssue::DeletedIdCollision { event_id: event.id });
+            }
+        }
+
+        for event in portfolio.active_events() {
+            if !Self::is_valid_amount(event.amount) {
+                issues.push(IntegrityIssue::InvalidAmount {
+                    event_id: event.id,
+                    amount: event.amount,
+                });
+            }
+        }
+
+        if !portfolio.settings.validation_policy.allow_oversell {
+            for (idx, held) in Self::overdraws(portfolio) {
+                let event = &portfolio.events[idx];
+                issues.push(IntegrityIssue::NegativeHolding {
+                    event_id: event.id,
+                    asset: event.asset.clone(),
+                    date: event.date,
+                    held,
+                });
+            }
+        }
+
+        if include_prices {
+            let prices = portfolio.price_cache.non_finite_prices();
+            for ((symbol, currency, asset_type), date) in prices {
+                issues.push(IntegrityIssue::NonFinitePrice { symbol, currency, asset_type, date });
+            }
+        }
+
+        IntegrityReport { issues }
+    }
+
+    /// Fix what `check_integrity` (with prices) finds: duplicate and
+    /// colliding ids get new ids (the first event in use keeps its id),
+    /// events with invalid amounts are deleted at `now`, sells and
+    /// withdrawals that overdraw are deleted or, with
+    /// `options.clamp_overdraws`, reduced to the amount held, and
+    /// non-finite cached prices are removed. Returns the issues found.
+    pub fn repair_integrity(
+        &self,
+        portfolio: &mut Portfolio,
+        options: RepairOptions,
+        now: DateTime<Utc>,
+    ) -> IntegrityReport {
+        let report = self.check_integrity(portfolio, true);
+        if report.is_clean() {
+            return report;
+        }
+
+        let mut kept: HashSet<Uuid> = HashSet::new();
+        for event in portfolio.events.iter_mut().filter(|e| !e.is_deleted()) {
+            if !kept.insert(event.id) {
+                event.id = Uuid::new_v4();
+            }
+        }
+        for event in portfolio.events.iter_mut().filter(|e| e.is_deleted()) {
+            if kept.contains(&event.id) {
+                event.id = Uuid::new_v4();
+            }
+        }
+
+        for event in portfolio.events.iter_mut() {
+            if !event.is_deleted() && !Self::is_valid_amount(event.amount) {
+                event.deleted_at = Some(now);
+            }
+        }
+        portfolio.mark_events_changed();
+
+        // One at a time: fixing an overdraw changes the holdings after it
+        if !portfolio.settings.validation_policy.allow_oversell {
+            while let Some(&(idx, held)) = Self::overdraws(portfolio).first() {
+                let event = &mut portfolio.events[idx];
+                let available = event.amount + held;
+                if options.clamp_overdraws && available > f64::EPSILON {
+                    event.amount = available;
+                } else {
+                    event.deleted_at = Some(now);
+                }
+                portfolio.mark_events_changed();
+            }
+        }
+
+        portfolio.price_cache.remove_non_finite();
+        report
+    }
+
+    fn is_valid_amount(amount: f64) -> bool {
+        amount.is_finite() && amount > 0.0
+    }
+
+    /// Actual events that take more of an asset than is held, replaying
+    /// valid events in date order, each day's increases before its
+    /// decreases (as `validate_portfolio_consistency` does): (index in
+    /// `portfolio.events`, amount held after the event).
+    fn overdraws(portfolio: &Portfolio) -> Vec<(usize, f64)> {
+        let events = &portfolio.events;
+        let mut replay: Vec<usize> = (0..events.len())
+            .filter(|&idx| {
+                let event = &events[idx];
+                !event.is_deleted() && !event.is_planned && Self::is_valid_amount(event.amount)
+            })
+            .collect();
+        replay.sort_by_key(|&idx| (events[idx].date, !events[idx].event_type.increases_holding()));
+
+        let mut held: HashMap<&Asset, f64> = HashMap::new();
+        let mut overdraws = Vec::new();
+        for idx in replay {
+            let event = &events[idx];
+            let amount = held.entry(&event.asset).or_insert(0.0);
+            *amount += event.holding_delta();
+            if *amount < -f64::EPSILON {
+                overdraws.push((idx, *amount));
+                // Report each shortfall once, at the event that caused it
+                *amount = 0.0;
+            }
+        }
+        overdraws
+    }
+
+    /// Get the stored snapshot for a date, if any.
+    pub fn get_snapshot<'a>(
+        &self,
+        portfolio: &'a Portfolio,
+        date: NaiveDate,
+    ) -> Option<&'a ValueSnapshot> {
+        portfolio
+            .snapshots
+            .binary_search_by_key(&date, |s| s.date)
+            .ok()
+            .map(|idx| &portfolio.snapshots[idx])
+    }
+
+    /// Store a snapshot, replacing any existing snapshot for the same date.
+    pub fn upsert_snapshot(&self, portfolio: &mut Portfolio, snapshot: ValueSnapshot) {
+        match portfolio
+            .snapshots
+            .binary_search_by_key(&snapshot.date, |s| s.date)
+        {
+            Ok(idx) => portfolio.snapshots[idx] = snapshot,
+            Err(idx) => portfolio.snapshots.insert(idx, snapshot),
+        }
+    }
+
+    /// Thin out snapshots older than one year before `today`, keeping only the
+    /// latest snapshot of each ISO week. Returns the number of snapshots removed.
+    pub fn prune_snapshots(&self, portfolio: &mut Portfolio, today: NaiveDate) -> usize {
+        let cutoff = today - chrono::Duration::days(365);
+        let old_len = portfolio.snapshots.len();
+
+        // Snapshots are sorted by date: keep an old one only if the next
+        // snapshot falls in a different week (or is recent).
+        let weeks: Vec<Option<chrono::IsoWeek>> = portfolio
+            .snapshots
+            .iter()
+            .map(|s| (s.date < cutoff).then(|| s.date.iso_week()))
+            .collect();
+        let mut idx = 0;
+        portfolio.snapshots.retain(|_| {
+            let keep = match weeks[idx] {
+                Some(week) => weeks.get(idx + 1).copied().flatten() != Some(week),
+                None => true,
+            };
+            idx += 1;
+            keep
+        });
+
+        old_len - portfolio.snapshots.len()
+    }
+
+    /// Binary insert into the date-sorted portfolio events in O(log n).
+    fn binary_insert(portfolio: &mut Portfolio, event: Event) {
+        let pos = portfolio
+            .events
             .binary_search_by_key(&event.date, |e| e.date)
             .unwrap_or_else(|pos| pos);
-        events.insert(pos, event);
+        portfolio.events.insert(pos, event);
+        portfolio.mark_events_changed();
     }
 
-    /// Set or clear the notes on an existing event.
+    /// Set or clear the notes on an existing event, normalized like
+    /// `add_event` does.
     pub fn set_notes(
         &self,
         portfolio: &mut Portfolio,
         event_id: Uuid,
         notes: Option<String>,
     ) -> Result<(), CoreError> {
-        let event = portfolio
-            .events
+        let idx = Self::active_index(portfolio, event_id)?;
+        let notes = Self::normalize_notes(&portfolio.settings.validation_policy, notes)?;
+        portfolio.events[idx].notes = notes;
+        portfolio.mark_events_changed();
+        Ok(())
+    }
+
+    /// `notes` with trailing whitespace trimmed (`None` if nothing is left),
+    /// rejecting NUL characters and notes longer than `max_notes_len`.
+    fn normalize_notes(
+        policy: &ValidationPolicy,
+        notes: Option<String>,
+    ) -> Result<Option<String>, CoreError> {
+        let Some(mut notes) = notes else {
+            return Ok(None);
+        };
+        notes.truncate(notes.trim_end().len());
+        if notes.is_empty() {
+            return Ok(None);
+        }
+        if notes.contains('\0') {
+            return Err(CoreError::ValidationError(
+                "Notes must not contain NUL characters".into(),
+            ));
+        }
+        if let Some(max_len) = policy.max_notes_len {
+            let len = notes.chars().count();
+            if len > max_len {
+                return Err(CoreError::ValidationError(format!(
+                    "Notes are {len} characters long, more than the maximum of {max_len}"
+                )));
+            }
+        }
+        Ok(Some(notes))
+    }
+
+    /// Add a price alert after validating its threshold.
+    pub fn add_alert(&self, portfolio: &mut Portfolio, alert: Alert) -> Result<(), CoreError> {
+        Self::validate_alert_threshold(alert.threshold)?;
+        portfolio.alerts.push(alert);
+        Ok(())
+    }
+
+    /// Remove an alert by its UUID.
+    pub fn remove_alert(&self, portfolio: &mut Portfolio, alert_id: Uuid) -> Result<(), CoreError> {
+        let idx = portfolio
+            .alerts
+            .iter()
+            .position(|a| a.id == alert_id)
+            .ok_or_else(|| CoreError::AlertNotFound(alert_id.to_string()))?;
+        portfolio.alerts.remove(idx);
+        Ok(())
+    }
+
+    /// Change an alert's rule. `last_triggered` is reset so the new rule can fire today.
+    pub fn update_alert(
+        &self,
+        portfolio: &mut Portfolio,
+        alert_id: Uuid,
+        condition: AlertCondition,
+        threshold: f64,
+        currency: String,
+    ) -> Result<(), CoreError> {
+        Self::validate_alert_threshold(threshold)?;
+        let alert = Self::find_alert_mut(portfolio, alert_id)?;
+        alert.condition = condition;
+        alert.threshold = threshold;
+        alert.currency = currency;
+        alert.last_triggered = None;
+        Ok(())
+    }
+
+    /// Enable or disable an alert without removing it.
+    pub fn set_alert_enabled(
+        &self,
+        portfolio: &mut Portfolio,
+        alert_id: Uuid,
+        enabled: bool,
+    ) -> Result<(), CoreError> {
+        Self::find_alert_mut(portfolio, alert_id)?.enabled = enabled;
+        Ok(())
+    }
+
+    fn find_alert_mut(portfolio: &mut Portfolio, alert_id: Uuid) -> Result<&mut Alert, CoreError> {
+        portfolio
+            .alerts
             .iter_mut()
-            .find(|e| e.id == event_id)
-            .ok_or_else(|| CoreError::EventNotFound(event_id.to_string()))?;
-        event.notes = notes;
+            .find(|a| a.id == alert_id)
+            .ok_or_else(|| CoreError::AlertNotFound(alert_id.to_string()))
+    }
+
+    /// Record a stock split or symbol change. Every sell is revalidated
+    /// with the action applied; on failure nothing changes.
+    pub fn add_corporate_action(
+        &self,
+        portfolio: &mut Portfolio,
+        action: CorporateAction,
+    ) -> Result<(), CoreError> {
+        action.validate()?;
+        if portfolio.corporate_actions.iter().any(|a| a.id == action.id) {
+            return Err(CoreError::ValidationError(format!(
+                "Corporate action {} is already recorded",
+                action.id
+            )));
+        }
+        let original = portfolio.corporate_actions.clone();
+        Self::insert_corporate_action(portfolio, action);
+        self.revalidate_corporate_actions(portfolio, original)
+    }
+
+    /// Change what a corporate action does and when it takes effect.
+    /// Revalidated like `add_corporate_action`.
+    pub fn update_corporate_action(
+        &self,
+        portfolio: &mut Portfolio,
+        action_id: Uuid,
+        action: CorporateActionType,
+        effective_date: NaiveDate,
+    ) -> Result<(), CoreError> {
+        let idx = Self::corporate_action_index(portfolio, action_id)?;
+        let mut updated =
+            CorporateAction::new(&portfolio.corporate_actions[idx].symbol, action, effective_date);
+        updated.id = action_id;
+        updated.validate()?;
+        let original = portfolio.corporate_actions.clone();
+        portfolio.corporate_actions.remove(idx);
+        Self::insert_corporate_action(portfolio, updated);
+        self.revalidate_corporate_actions(portfolio, original)
+    }
+
+    /// Remove a corporate action; the events count as entered again.
+    /// Revalidated like `add_corporate_action`. Returns the removed action.
+    pub fn remove_corporate_action(
+        &self,
+        portfolio: &mut Portfolio,
+        action_id: Uuid,
+    ) -> Result<CorporateAction, CoreError> {
+        let idx = Self::corporate_action_index(portfolio, action_id)?;
+        let original = portfolio.corporate_actions.clone();
+        let removed = portfolio.corporate_actions.remove(idx);
+        self.revalidate_corporate_actions(portfolio, original)?;
+        Ok(removed)
+    }
+
+    fn corporate_action_index(portfolio: &Portfolio, action_id: Uuid) -> Result<usize, CoreError> {
+        portfolio
+            .corporate_actions
+            .iter()
+            .position(|a| a.id == action_id)
+            .ok_or_else(|| CoreError::CorporateActionNotFound(action_id.to_string()))
+    }
+
+    /// Insert after the actions effective on the same date or earlier, so
+    /// actions on one date apply in the order they were recorded.
+    fn insert_corporate_action(portfolio: &mut Portfolio, action: CorporateAction) {
+        let pos = portfolio
+            .corporate_actions
+            .partition_point(|a| a.effective_date <= action.effective_date);
+        portfolio.corporate_actions.insert(pos, action);
+    }
+
+    /// Add an accrual rule. Rules of one asset must not overlap.
+    pub fn add_accrual_rule(
+        &self,
+        portfolio: &mut Portfolio,
+        rule: AccrualRule,
+    ) -> Result<(), CoreError> {
+        rule.validate()?;
+        if let Some(other) = portfolio.accrual_rules.iter().find(|other| other.overlaps(&rule)) {
+            return Err(CoreError::ValidationError(format!(
+                "Accrual of {} from {} overlaps the rule starting {}",
+                rule.asset.symbol, rule.start_date, other.start_date
+            )));
+        }
+        portfolio.accrual_rules.push(rule);
+        portfolio.mark_events_changed();
+        Ok(())
+    }
+
+    /// Remove an accrual rule; `Interest` events it was materialized into
+    /// stay. Every sell is revalidated without its growth; on failure
+    /// nothing changes. Returns the removed rule.
+    pub fn remove_accrual_rule(
+        &self,
+        portfolio: &mut Portfolio,
+        rule_id: Uuid,
+    ) -> Result<AccrualRule, CoreError> {
+        let idx = portfolio
+            .accrual_rules
+            .iter()
+            .position(|rule| rule.id == rule_id)
+            .ok_or_else(|| CoreError::AccrualRuleNotFound(rule_id.to_string()))?;
+        let removed = portfolio.accrual_rules.remove(idx);
+        portfolio.mark_events_changed();
+        if let Err(e) = self.validate_portfolio_consistency(portfolio, removed.start_date) {
+            portfolio.accrual_rules.insert(idx, removed);
+            portfolio.mark_events_changed();
+            return Err(e);
+        }
+        Ok(removed)
+    }
+
+    /// Turn what the accrual rules accrued in the periods ended by `up_to`
+    /// (month ends, and a rule's end date) into one `Interest` event per
+    /// rule and period, dated on the period's last day. Those periods then
+    /// stop accruing, so holdings don't change. On failure (e.g., a period
+    /// ending in the future) nothing changes. Returns the new event ids.
+    pub fn materialize_accruals(
+        &self,
+        portfolio: &mut Portfolio,
+        up_to: NaiveDate,
+    ) -> Result<Vec<Uuid>, CoreError> {
+        let index = portfolio.holdings_index();
+        let valued = portfolio.valued_events();
+        let mut interest = Vec::new();
+        let mut materialized = Vec::new();
+        for (idx, rule) in portfolio.accrual_rules.iter().enumerate() {
+            let ends = rule.period_ends(up_to);
+            let Some(&last) = ends.last() else {
+                continue;
+            };
+            let mut previous = rule.accrues_from().pred_opt().unwrap_or(NaiveDate::MIN);
+            for end in ends {
+                // Growth in the period: the change in holdings not due to events
+                let traded: f64 = valued
+                    .actual()
+                    .filter(|e| e.asset == rule.asset && e.date > previous && e.date <= end)
+                    .map(Event::holding_delta)
+                    .sum();
+                let accrued = index.amount_at(&rule.asset, end)
+                    - index.amount_at(&rule.asset, previous)
+                    - traded;
+                if accrued > f64::EPSILON {
+                    let notes = format!(
+                        "Accrued at {}% a year, compounded {}",
+                        rule.annual_rate_pct, rule.compounding
+                    );
+                    let asset = rule.asset.clone();
+                    let event = Event::with_notes(EventType::Interest, asset, accrued, end, notes);
+                    interest.push(event);
+                }
+                previous = end;
+            }
+            materialized.push((idx, last));
+        }
+        drop(valued);
+
+        let original = (portfolio.events.clone(), portfolio.accrual_rules.clone());
+        for (idx, last) in materialized {
+            portfolio.accrual_rules[idx].materialized_until = Some(last);
+        }
+        portfolio.mark_events_changed();
+        let ids = interest.iter().map(|e| e.id).collect();
+        if let Err(e) = interest.into_iter().try_for_each(|e| self.add_event(portfolio, e)) {
+            (portfolio.events, portfolio.accrual_rules) = original;
+            portfolio.mark_events_changed();
+            return Err(e);
+        }
+        Ok(ids)
+    }
+
+    /// Set the reminder of an active event to `date`, unacknowledged, or
+    /// remove it with `None`.
+    pub fn set_event_reminder(
+        &self,
+        portfolio: &mut Portfolio,
+        event_id: Uuid,
+        date: Option<NaiveDate>,
+    ) -> Result<(), CoreError> {
+        if !portfolio.active_events().any(|e| e.id == event_id) {
+            return Err(CoreError::EventNotFound(event_id.to_string()));
+        }
+        portfolio.reminders.retain(|r| r.event_id != event_id);
+        if let Some(date) = date {
+            portfolio.reminders.push(Reminder::new(event_id, date));
+        }
+        Ok(())
+    }
+
+    /// Mark the reminder of an event as acknowledged, so it is no longer due.
+    pub fn acknowledge_reminder(
+        &self,
+        portfolio: &mut Portfolio,
+        event_id: Uuid,
+    ) -> Result<(), CoreError> {
+        let reminder = portfolio
+            .reminders
+            .iter_mut()
+            .find(|r| r.event_id == event_id)
+            .ok_or_else(|| CoreError::ReminderNotFound(event_id.to_string()))?;
+        reminder.acknowledged = true;
+        Ok(())
+    }
+
+    /// After a change to the corporate actions: invalidate the adjusted
+    /// events and check every sell, restoring `original` on failure.
+    fn revalidate_corporate_actions(
+        &self,
+        portfolio: &mut Portfolio,
+        original: Vec<CorporateAction>,
+    ) -> Result<(), CoreError> {
+        portfolio.mark_events_changed();
+        if let Err(e) = self.validate_portfolio_consistency(portfolio, NaiveDate::MIN) {
+            portfolio.corporate_actions = original;
+            portfolio.mark_events_changed();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Set or clear the annual yield (percent) used for income projections
+    /// of `symbol` of `asset_type`.
+    pub fn set_asset_yield(
+        &self,
+        portfolio: &mut Portfolio,
+        symbol: &str,
+        asset_type: &AssetType,
+        annual_yield_pct: Option<f64>,
+    ) -> Result<(), CoreError> {
+        let mut metadata = self
+            .get_asset_metadata(portfolio, symbol, asset_type)
+            .cloned()
+            .unwrap_or_default();
+        metadata.annual_yield_pct = annual_yield_pct;
+        self.set_asset_metadata(portfolio, symbol, asset_type, metadata)
+    }
+
+    /// Replace the metadata stored for `symbol` of `asset_type` after
+    /// validating it. Empty metadata removes the entry.
+    pub fn set_asset_metadata(
+        &self,
+        portfolio: &mut Portfolio,
+        symbol: &str,
+        asset_type: &AssetType,
+        metadata: AssetMetadata,
+    ) -> Result<(), CoreError> {
+        if let Some(pct) = metadata.annual_yield_pct {
+            if !pct.is_finite() || pct < 0.0 {
+                return Err(CoreError::ValidationError(format!(
+                    "Invalid annual yield {pct}%: must be a non-negative number"
+                )));
+            }
+        }
+        if let Some(color) = &metadata.color_hex {
+            let digits = color.strip_prefix('#').unwrap_or_default();
+            if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(CoreError::ValidationError(format!(
+                    "Invalid color '{color}': must be #RRGGBB (e.g., #F7931A)"
+                )));
+            }
+        }
+        if let Some(price) = metadata.manual_price {
+            if !price.is_finite() || price < 0.0 {
+                return Err(CoreError::ValidationError(format!(
+                    "Invalid manual price {price}: must be a non-negative number"
+                )));
+            }
+        }
+        if let Some(precision) = metadata.display_precision {
+            if precision > MAX_DISPLAY_PRECISION {
+                return Err(CoreError::ValidationError(format!(
+                    "Invalid display precision {precision}: must be at most \
+                     {MAX_DISPLAY_PRECISION} decimal places"
+                )));
+            }
+        }
+        if metadata.custom_fields.keys().any(|name| name.trim().is_empty()) {
+            return Err(CoreError::ValidationError("Custom field names must not be empty".into()));
+        }
+        let symbol = symbol.trim().to_uppercase();
+        if symbol.is_empty() {
+            return Err(CoreError::ValidationError("Asset symbol must not be empty".into()));
+        }
+
+        let key = (symbol, asset_type.clone());
+        if metadata.is_empty() {
+            portfolio.asset_metadata.remove(&key);
+        } else {
+            portfolio.asset_metadata.insert(key, metadata);
+        }
+        Ok(())
+    }
+
+    /// Metadata stored for `symbol` of `asset_type`, if any.
+    pub fn get_asset_metadata<'a>(
+        &self,
+        portfolio: &'a Portfolio,
+        symbol: &str,
+        asset_type: &AssetType,
+    ) -> Option<&'a AssetMetadata> {
+        portfolio.asset_metadata.get(&(symbol.trim().to_uppercase(), asset_type.clone()))
+    }
+
+    /// Decimal places `asset`'s amounts are shown with: its metadata's
+    /// `display_precision`, or the default of its type.
+    pub fn display_precision(&self, portfolio: &Portfolio, asset: &Asset) -> u32 {
+        self.get_asset_metadata(portfolio, &asset.symbol, &asset.asset_type)
+            .and_then(|metadata| metadata.display_precision)
+            .unwrap_or_else(|| asset.asset_type.default_precision())
+    }
+
+    /// Add `asset` to the watchlist. Returns `false` if it is already on it.
+    pub fn add_to_watchlist(
+        &self,
+        portfolio: &mut Portfolio,
+        asset: Asset,
+    ) -> Result<bool, CoreError> {
+        if asset.symbol.trim().is_empty() {
+            return Err(CoreError::ValidationError("Asset symbol must not be empty".into()));
+        }
+        if portfolio.watchlist.contains(&asset) {
+            return Ok(false);
+        }
+        portfolio.watchlist.push(asset);
+        Ok(true)
+    }
+
+    /// Remove `asset` from the watchlist. Returns `false` if it was not on it.
+    pub fn remove_from_watchlist(&self, portfolio: &mut Portfolio, asset: &Asset) -> bool {
+        let before = portfolio.watchlist.len();
+        portfolio.watchlist.retain(|a| a != asset);
+        portfolio.watchlist.len() != before
+    }
+
+    /// Thresholds are prices or percentages: both must be positive numbers.
+    fn validate_alert_threshold(threshold: f64) -> Result<(), CoreError> {
+        if !threshold.is_finite() || threshold <= 0.0 {
+            return Err(CoreError::ValidationError(format!(
+                "Invalid alert threshold {threshold}: must be a positive number"
+            )));
+        }
         Ok(())
     }
 }
@@ -0,0 +1,66 @@
+use chrono::Utc;
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::models::diagnostics::{PriceDiagnostic, PriceServiceEvent};
+
+/// Default number of events a `PriceDiagnosticLog` keeps.
+pub const DEFAULT_MAX_DIAGNOSTICS: usize = 200;
+
+/// The most recent `PriceServiceEvent`s, oldest first, each with the time
+/// it was recorded.
+///
+/// Bounded like `PriceWarningLog`: once full, each new event drops the
+/// oldest one. Never persisted.
+#[derive(Debug)]
+pub struct PriceDiagnosticLog {
+    max_events: usize,
+    events: Mutex<VecDeque<PriceDiagnostic>>,
+}
+
+impl PriceDiagnosticLog {
+    /// A log keeping the last `DEFAULT_MAX_DIAGNOSTICS` events.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_MAX_DIAGNOSTICS)
+    }
+
+    /// A log keeping the last `max_events` events.
+    pub fn with_capacity(max_events: usize) -> Self {
+        Self {
+            max_events,
+            events: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record an event, dropping the oldest one if the log is full.
+    pub fn push(&self, event: PriceServiceEvent) {
+        if self.max_events == 0 {
+            return;
+        }
+        let mut events = self.lock();
+        if events.len() >= self.max_events {
+            events.pop_front();
+        }
+        events.push_back(PriceDiagnostic { at: Utc::now(), event });
+    }
+
+    /// The recorded events, oldest first.
+    pub fn recent(&self) -> Vec<PriceDiagnostic> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Drop every recorded event.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<PriceDiagnostic>> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for PriceDiagnosticLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,27 +1,352 @@
-use chrono::NaiveDate;
+use chrono::{Datelike, Days, Months, NaiveDate, Utc};
+use futures_util::future::{self, Either};
+use futures_util::stream::{self, StreamExt};
+use futures_util::FutureExt;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
 
 use crate::errors::CoreError;
-use crate::models::asset::AssetType;
-use crate::models::price::{PriceCache, PricePoint};
-use crate::providers::registry::PriceProviderRegistry;
+use crate::models::asset::{AssetType, MetalUnit};
+use crate::models::diagnostics::{PriceLookup, PriceServiceEvent};
+use crate::models::price::{PriceCache, PricePoint, PriceSource};
+use crate::models::progress::CancellationToken;
+use crate::models::usage::ProviderUsage;
+use crate::models::warning::{PriceWarning, PriceWarningReason};
+use crate::providers::registry::{PriceProviderRegistry, ProviderInfo};
+use crate::providers::traits::PriceProvider;
+use crate::services::api_usage::ApiUsageTracker;
+use crate::services::price_warnings::PriceWarningLog;
+use crate::services::response_cache::{
+    CachedResponse, ResponseCache, ResponseKey, ResponseRequest,
+};
+
+/// Run `lookups` to completion without an executor. Only for futures whose
+/// price lookups go through a `PriceService::cache_only` service, which
+/// never waits on a provider; one that would wait fails with
+/// `CoreError::NoProvider` instead.
+pub(crate) fn resolve_cached<T>(
+    lookups: impl Future<Output = Result<T, CoreError>>,
+) -> Result<T, CoreError> {
+    lookups
+        .now_or_never()
+        .unwrap_or_else(|| Err(CoreError::NoProvider("cached prices only".to_string())))
+}
+
+/// Default number of provider requests `get_prices` keeps in flight.
+pub const DEFAULT_FETCH_CONCURRENCY: usize = 8;
+
+/// Default factor a quote may differ by from a nearby cached price before
+/// it needs another provider's confirmation.
+pub const DEFAULT_MAX_PRICE_CHANGE: f64 = 10.0;
+
+/// How many days from the requested date a cached price may be to serve as
+/// the reference a new quote is checked against.
+const REFERENCE_MAX_DAYS: u32 = 7;
+
+/// Relative difference within which two providers' quotes agree.
+const CONFIRMATION_TOLERANCE: f64 = 0.1;
+
+/// Receiver of the `PriceServiceEvent`s of a service (see
+/// `PriceService::set_event_sink`).
+pub type PriceEventSink = Arc<dyn Fn(PriceServiceEvent) + Send + Sync>;
+
+/// One price lookup of a batch (see `PriceService::get_prices`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PriceRequest {
+    pub symbol: String,
+    pub currency: String,
+    pub date: NaiveDate,
+    pub asset_type: AssetType,
+}
+
+impl PriceRequest {
+    pub fn new(symbol: &str, currency: &str, date: NaiveDate, asset_type: &AssetType) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            currency: currency.to_uppercase(),
+            date,
+            asset_type: asset_type.clone(),
+        }
+    }
+
+    fn lookup(&self) -> PriceLookup {
+        PriceLookup::new(&self.symbol, &self.currency, &self.asset_type, self.date, self.date)
+    }
+}
+
+/// Opt-in estimate for historical lookups no provider can answer
+/// (see `PriceService::set_fallback`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriceFallback {
+    /// Answer with the nearest cached price instead of failing (off by default)
+    pub allow_nearest_price: bool,
+
+    /// How many days from the requested date the nearest price may be
+    pub max_distance_days: u32,
+}
+
+impl PriceFallback {
+    /// Allow the nearest cached price at most `max_distance_days` away.
+    pub fn nearest(max_distance_days: u32) -> Self {
+        Self { allow_nearest_price: true, max_distance_days }
+    }
+}
 
 /// Fetches asset prices from API providers with intelligent caching.
 ///
 /// Cache strategy:
 /// - **Historical dates (< today)**: fetch once, cache forever. Past prices don't change.
 /// - **Today's date**: fetch once per session/day, refresh on explicit `refresh_prices()`.
+///   Today's quote is provisional (`PriceCache::is_provisional`): once the day is
+///   over, its close is fetched in its place, keeping the quote only if that fails.
 /// - All cached prices are stored in `PriceCache` → saved in the encrypted file → offline access.
 ///
 /// **Note on precision**: All prices are stored as `f64`, which has ~15-17 significant
 /// decimal digits. For most financial use cases this is sufficient, but repeated
 /// arithmetic operations may accumulate small floating-point errors.
+///
+/// **Fiat ranges**: days without a quote (weekends, holidays) are filled with
+/// the previous day's rate and flagged in the cache (`PriceCache::is_carried`),
+/// so later lookups for those days don't go back to the provider.
+///
+/// **Metals**: providers quote per troy ounce; prices are converted to the
+/// configured `MetalUnit` before they are cached, and cached series recorded
+/// in another unit are converted before use.
+///
+/// **Historical dates**: a past date is only ever answered with a price for
+/// that date. When no provider has one, the lookup fails with
+/// `CoreError::PriceNotAvailable` (transient errors such as network failures
+/// are returned as-is), unless a `PriceFallback` allows the nearest cached
+/// price. Current prices are never substituted.
+///
+/// **Response memo**: with a `ResponseCache` installed, successful provider
+/// results are memoized for a short time, so identical requests made in a
+/// row (e.g. a chart, a summary and a refresh) only hit the network once.
+/// It is checked after `PriceCache`, per provider, and can be bypassed.
+///
+/// **Cancellation**: with a `CancellationToken` set, no provider request
+/// starts once it is cancelled, and requests in flight are dropped (aborting
+/// the HTTP request) as soon as it fires; lookups fail with
+/// `CoreError::Cancelled`. Cached prices are still returned.
+///
+/// **Sanity checks**: quotes that are not positive are never used. A quote
+/// more than `DEFAULT_MAX_PRICE_CHANGE` times above or below a cached price
+/// of the same or a nearby day (see `set_max_price_change`) is only used
+/// once another provider confirms it; otherwise the next provider's quote
+/// is used, or the lookup fails like one no provider could answer. Fetched
+/// ranges lose their non-positive points and isolated spikes. Rejected
+/// quotes are never cached and are recorded in the `PriceWarningLog`.
+///
+/// **Old history**: with `set_monthly_price_age`, dates further back are
+/// priced with the close of their month (`PriceCache::get_month_price`)
+/// when they have no daily price. Missing closes are fetched in one range
+/// request per series, of which only the closes are cached.
+///
+/// **Diagnostics**: with `set_event_sink`, every cache hit, provider attempt
+/// and its outcome is reported as a `PriceServiceEvent`, e.g. to show which
+/// provider was used after another failed. Without a sink no event is built.
+#[derive(Clone)]
 pub struct PriceService {
     registry: PriceProviderRegistry,
+    metal_unit: MetalUnit,
+    response_cache: Option<Arc<dyn ResponseCache>>,
+    bypass_response_cache: bool,
+    fallback: PriceFallback,
+    later_prices_only: bool,
+    cancellation: Option<CancellationToken>,
+    usage: Arc<ApiUsageTracker>,
+    daily_limits: HashMap<String, u32>,
+    max_price_change: Option<f64>,
+    warnings: Arc<PriceWarningLog>,
+    monthly_price_age: Option<u32>,
+    event_sink: Option<PriceEventSink>,
 }
 
 impl PriceService {
     pub fn new(registry: PriceProviderRegistry) -> Self {
-        Self { registry }
+        Self::with_metal_unit(registry, MetalUnit::default())
+    }
+
+    /// Create a service that prices metals per `metal_unit`.
+    pub fn with_metal_unit(registry: PriceProviderRegistry, metal_unit: MetalUnit) -> Self {
+        Self {
+            registry,
+            metal_unit,
+            response_cache: None,
+            bypass_response_cache: false,
+            fallback: PriceFallback::default(),
+            later_prices_only: false,
+            cancellation: None,
+            usage: Arc::new(ApiUsageTracker::new()),
+            daily_limits: HashMap::new(),
+            max_price_change: Some(DEFAULT_MAX_PRICE_CHANGE),
+            warnings: Arc::new(PriceWarningLog::new()),
+            monthly_price_age: None,
+            event_sink: None,
+        }
+    }
+
+    /// Memoize provider responses in `cache` (`None` disables memoization).
+    pub fn set_response_cache(&mut self, cache: Option<Arc<dyn ResponseCache>>) {
+        self.response_cache = cache;
+    }
+
+
+    /// Estimate historical prices no provider can answer as `fallback` allows
+    /// (no estimates by default). Estimates are returned, never cached.
+    pub fn set_fallback(&mut self, fallback: PriceFallback) {
+        self.fallback = fallback;
+        self.later_prices_only = false;
+    }
+
+    /// Cancel provider requests when `token` is cancelled (`None`: never).
+    /// Returns the previous token, e.g. to restore it after an operation.
+    pub fn set_cancellation(
+        &mut self,
+        token: Option<CancellationToken>,
+    ) -> Option<CancellationToken> {
+        std::mem::replace(&mut self.cancellation, token)
+    }
+
+    /// Count provider requests in `usage` (each service starts with its own
+    /// tracker; share one to keep the counts across rebuilt services).
+    pub fn set_usage_tracker(&mut self, usage: Arc<ApiUsageTracker>) {
+        self.usage = usage;
+    }
+
+    /// Stop calling a provider once it was sent its daily limit of requests
+    /// (keys: lowercase provider id, e.g., "alphavantage"). Requests over
+    /// the limit fail with `CoreError::rate_limited`, so the next provider
+    /// is tried. The limits apply per UTC day.
+    pub fn set_daily_limits(&mut self, limits: HashMap<String, u32>) {
+        self.daily_limits = limits;
+    }
+
+    /// Require another provider's confirmation for quotes more than `factor`
+    /// times above or below a nearby cached price (`None`: never).
+    pub fn set_max_price_change(&mut self, factor: Option<f64>) {
+        self.max_price_change = factor;
+    }
+
+    /// Record rejected quotes in `warnings` (each service starts with its own
+    /// log; share one to keep the warnings across rebuilt services).
+    pub fn set_warning_log(&mut self, warnings: Arc<PriceWarningLog>) {
+        self.warnings = warnings;
+    }
+
+    /// Price dates more than `days` days in the past with the close of their
+    /// month unless a daily price is cached (`None`: always daily prices).
+    pub fn set_monthly_price_age(&mut self, days: Option<u32>) {
+        self.monthly_price_age = days;
+    }
+
+    /// Report the steps of every lookup to `sink` (see `PriceServiceEvent`).
+    /// The sink is called synchronously, so it should return quickly.
+    pub fn set_event_sink(&mut self, sink: impl Fn(PriceServiceEvent) + Send + Sync + 'static) {
+        self.event_sink = Some(Arc::new(sink));
+    }
+
+    /// Stop reporting lookup steps.
+    pub fn clear_event_sink(&mut self) {
+        self.event_sink = None;
+    }
+
+    /// First day priced daily on `today`, if older days are priced with
+    /// month closes (see `set_monthly_price_age`).
+    pub fn monthly_before(&self, today: NaiveDate) -> Option<NaiveDate> {
+        today.checked_sub_days(Days::new(self.monthly_price_age?.into()))
+    }
+
+    /// The most recent rejected quotes, oldest first.
+    pub fn recent_warnings(&self) -> Vec<PriceWarning> {
+        self.warnings.recent()
+    }
+
+    /// Requests sent to every registered provider (and to earlier ones still
+    /// in the tracker), sorted by name, with today's counts.
+    pub fn api_usage(&self) -> Vec<ProviderUsage> {
+        let today = Utc::now().date_naive();
+        let mut usage: HashMap<String, ProviderUsage> = self
+            .usage
+            .snapshot()
+            .into_iter()
+            .map(|(key, usage)| (key, usage.as_of(today)))
+            .collect();
+        for provider in self.registry.list_providers() {
+            usage
+                .entry(provider.name.to_lowercase())
+                .or_insert_with(|| ProviderUsage::new(&provider.name));
+        }
+        let mut usage: Vec<ProviderUsage> = usage.into_values().collect();
+        usage.sort_by_key(|u| u.name.to_lowercase());
+        usage
+    }
+
+    /// The token provider requests are cancelled with, if any.
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    /// How historical lookups no provider can answer are estimated.
+    pub fn fallback(&self) -> PriceFallback {
+        self.fallback
+    }
+
+    /// A service with the same settings but no providers: lookups are answered
+    /// from the cache only, and misses fail with `CoreError::NoProvider`
+    /// (unless the fallback finds a nearby price).
+    pub fn cache_only(&self) -> Self {
+        let mut service = Self::with_metal_unit(PriceProviderRegistry::new(), self.metal_unit);
+        service.fallback = self.fallback;
+        service.later_prices_only = self.later_prices_only;
+        service.monthly_price_age = self.monthly_price_age;
+        service
+    }
+
+    /// A service sharing this one's providers, caches and usage counters, for
+    /// lookups that must be fresh: memoized responses are ignored and every
+    /// request goes to the providers (fresh results are still memoized).
+    pub fn bypassing_response_cache(&self) -> Self {
+        Self {
+            bypass_response_cache: true,
+            ..self.clone()
+        }
+    }
+
+    /// A service sharing this one's providers, caches and usage counters,
+    /// whose requests are cancelled by `token` instead.
+    pub fn with_cancellation(&self, token: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(token),
+            ..self.clone()
+        }
+    }
+
+    /// A service sharing this one's providers, caches and usage counters,
+    /// but estimating missing historical prices with `fallback`.
+    pub fn with_fallback(&self, fallback: PriceFallback) -> Self {
+        Self {
+            fallback,
+            later_prices_only: false,
+            ..self.clone()
+        }
+    }
+
+    /// Same as `with_fallback`, estimating missing historical prices only
+    /// with the first cached price at most `max_distance_days` after the
+    /// requested date, never an earlier one.
+    pub(crate) fn with_next_price_fallback(&self, max_distance_days: u32) -> Self {
+        Self {
+            fallback: PriceFallback::nearest(max_distance_days),
+            later_prices_only: true,
+            ..self.clone()
+        }
+    }
+
+    /// Unit metal prices are returned in.
+    pub fn metal_unit(&self) -> MetalUnit {
+        self.metal_unit
     }
 
     /// Check if at least one provider is available for a given asset type.
@@ -29,6 +354,17 @@ impl PriceService {
         self.registry.get_provider_for(asset_type).is_some()
     }
 
+    /// Name and supported asset types of every registered provider, in priority order.
+    pub fn list_providers(&self) -> Vec<ProviderInfo> {
+        self.registry.list_providers()
+    }
+
+    /// Symbol → id mappings learned by providers at runtime, keyed by
+    /// lowercase provider name (see `PriceProviderRegistry::learned_symbol_ids`).
+    pub fn learned_symbol_ids(&self) -> HashMap<String, HashMap<String, String>> {
+        self.registry.learned_symbol_ids()
+    }
+
     /// Get the names of all providers available for a given asset type.
     pub fn get_provider_names(&self, asset_type: &AssetType) -> Vec<String> {
         self.registry
@@ -43,6 +379,14 @@ impl PriceService {
     /// 1. Check cache → return if found (for historical dates, always use cache).
     /// 2. If not cached: fetch from API → store in cache → return.
     /// 3. For today's date: re-fetch if not already fetched today.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self, cache),
+            fields(provider = tracing::field::Empty, cache_hit = tracing::field::Empty)
+        )
+    )]
     pub async fn get_price(
         &self,
         cache: &mut PriceCache,
@@ -52,33 +396,164 @@ impl PriceService {
         asset_type: &AssetType,
     ) -> Result<f64, CoreError> {
         let today = chrono::Utc::now().date_naive();
+        if let Some(price) = self.cached_price(cache, symbol, currency, date, asset_type, today) {
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("cache_hit", true);
+            self.emit(|| PriceServiceEvent::CacheHit {
+                lookup: PriceLookup::new(symbol, currency, asset_type, date, date),
+            });
+            return Ok(price);
+        }
 
-        // Check cache first
-        if let Some(price) = cache.get_price(symbol, currency, date) {
-            // For historical dates, always trust cache
-            if date < today {
-                return Ok(price);
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("cache_hit", false);
+
+        if self.is_old(date, today) {
+            let months = self
+                .get_monthly_prices(cache, symbol, currency, date, date, asset_type)
+                .await;
+            if let Err(CoreError::Cancelled) = months {
+                return Err(CoreError::Cancelled);
             }
-            // For today, check if already refreshed today
-            if cache.is_today_fresh(symbol, currency, today) {
+            // Without a close for the month, the day itself is fetched
+            if let Some(price) = self.month_close(cache, symbol, currency, date, asset_type, today)
+            {
                 return Ok(price);
             }
         }
 
         // Cache miss — fetch from API
-        let price = self.fetch_price(symbol, currency, date, asset_type).await?;
+        let request = PriceRequest::new(symbol, currency, date, asset_type);
+        let reference = Self::reference_price(cache, &request);
+        match self.fetch_price(&request, reference).await {
+            Ok((price, source)) => {
+                Self::store_price(cache, &request, price, source, today);
+                Ok(price)
+            }
+            Err(CoreError::Cancelled) => Err(CoreError::Cancelled),
+            Err(e) => Self::provisional_price(cache, &request, today)
+                .or_else(|| self.nearest_price(cache, &request, today))
+                .ok_or(e),
+        }
+    }
 
-        // Store in cache
-        cache.set_price(symbol, currency, date, price);
-        if date == today {
-            cache.mark_updated_today(symbol, currency, today);
+    /// Look up many prices at once, like calling `get_price` for each request.
+    ///
+    /// Lookups missing from the cache are fetched concurrently, with at most
+    /// `concurrency` provider requests in flight; duplicate requests are
+    /// fetched once. Results are returned in request order and every
+    /// successful fetch is stored in `cache` as soon as it arrives.
+    pub async fn get_prices(
+        &self,
+        cache: &mut PriceCache,
+        requests: &[PriceRequest],
+        concurrency: usize,
+    ) -> Vec<Result<f64, CoreError>> {
+        let today = chrono::Utc::now().date_naive();
+        let mut missing = Vec::new();
+        let mut seen = HashSet::new();
+        // Date span of the old days missing from each series
+        let mut old: Vec<(&PriceRequest, NaiveDate, NaiveDate)> = Vec::new();
+        for request in requests {
+            let cached = self.cached_price(
+                cache,
+                &request.symbol,
+                &request.currency,
+                request.date,
+                &request.asset_type,
+                today,
+            );
+            if cached.is_some() {
+                self.emit(|| PriceServiceEvent::CacheHit { lookup: request.lookup() });
+            } else if seen.insert(request) {
+                missing.push((request, Self::reference_price(cache, request)));
+                if self.is_old(request.date, today) {
+                    let series = old.iter_mut().find(|(r, ..)| {
+                        (&r.symbol, &r.currency, &r.asset_type)
+                            == (&request.symbol, &request.currency, &request.asset_type)
+                    });
+                    match series {
+                        Some((_, from, to)) => {
+                            *from = (*from).min(request.date);
+                            *to = (*to).max(request.date);
+                        }
+                        None => old.push((request, request.date, request.date)),
+                    }
+                }
+            }
         }
 
-        Ok(price)
+        // Old days take their month's close; months without one are fetched
+        // daily below
+        for (request, from, to) in &old {
+            let PriceRequest { symbol, currency, asset_type, .. } = request;
+            let _ = self
+                .get_monthly_prices(cache, symbol, currency, *from, *to, asset_type)
+                .await;
+        }
+        if !old.is_empty() {
+            missing.retain(|(r, _)| {
+                self.month_close(cache, &r.symbol, &r.currency, r.date, &r.asset_type, today)
+                    .is_none()
+            });
+        }
+
+        let mut fetched = stream::iter(missing)
+            .map(|(request, reference)| async move {
+                (request, self.fetch_price(request, reference).await)
+            })
+            .buffer_unordered(concurrency.max(1));
+
+        // Store each price as it arrives, so dropping the future keeps them
+        let mut errors = HashMap::new();
+        while let Some((request, result)) = fetched.next().await {
+            match result {
+                Ok((price, source)) => {
+                    Self::store_price(cache, request, price, source, today);
+                }
+                Err(e) => {
+                    errors.insert(request, e);
+                }
+            }
+        }
+
+        requests
+            .iter()
+            .map(|request| match errors.get(request) {
+                Some(CoreError::Cancelled) => Err(CoreError::Cancelled),
+                Some(e) => Self::provisional_price(cache, request, today)
+                    .or_else(|| self.nearest_price(cache, request, today))
+                    .ok_or_else(|| e.clone()),
+                None => cache
+                    .get_price(
+                        &request.symbol,
+                        &request.currency,
+                        request.date,
+                        &request.asset_type,
+                    )
+                    .or_else(|| {
+                        let PriceRequest { symbol, currency, date, asset_type } = request;
+                        self.month_close(cache, symbol, currency, *date, asset_type, today)
+                    })
+                    .ok_or_else(|| CoreError::PriceNotAvailable {
+                        symbol: request.symbol.clone(),
+                        currency: request.currency.clone(),
+                        date: request.date.to_string(),
+                    }),
+            })
+            .collect()
     }
 
     /// Fetch a range of prices (for chart generation).
     /// Uses cache for dates that are already cached, fetches missing ones from API.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self, cache),
+            fields(provider = tracing::field::Empty, cache_hit = tracing::field::Empty)
+        )
+    )]
     pub async fn get_price_range(
         &self,
         cache: &mut PriceCache,
@@ -88,8 +563,10 @@ impl PriceService {
         to: NaiveDate,
         asset_type: &AssetType,
     ) -> Result<Vec<PricePoint>, CoreError> {
+        self.align_metal_unit(cache, symbol, currency, asset_type);
+
         // Check what we already have cached
-        let cached = cache.get_price_range(symbol, currency, from, to);
+        let cached = cache.get_price_range(symbol, currency, from, to, asset_type);
 
         // Use cache if we have data spanning the requested range boundaries
         // (checking first/last dates is more reliable than counting points,
@@ -100,26 +577,141 @@ impl PriceService {
             // If cached data covers the range boundaries (within 3 days tolerance for
             // weekends/holidays at both ends), trust the cache
             if (first - from).num_days().abs() <= 3 && (to - last).num_days().abs() <= 3 {
+                #[cfg(feature = "tracing")]
+                tracing::Span::current().record("cache_hit", true);
+                self.emit(|| PriceServiceEvent::CacheHit {
+                    lookup: PriceLookup::new(symbol, currency, asset_type, from, to),
+                });
                 return Ok(cached);
             }
         }
 
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("cache_hit", false);
+
         // Fetch the full range from API (with fallback)
+        let (mut points, provider) =
+            self.fetch_range(cache, symbol, currency, from, to, asset_type).await?;
+        cache.set_prices(symbol, currency, &points, asset_type);
+        let source = PriceSource::now(provider);
+        for point in &points {
+            cache.set_source(symbol, currency, point.date, source.clone(), asset_type);
+        }
+        if *asset_type == AssetType::Fiat {
+            Self::carry_forward(cache, symbol, currency, &mut points, to);
+        }
+        Ok(points)
+    }
+
+    /// Month closes (the last price of each month) of the months from `from`
+    /// to `to`, to price old history coarsely (see `set_monthly_price_age`).
+    ///
+    /// Months whose close is cached are not fetched again; the others are
+    /// fetched in a single range request, of which only the closes are
+    /// cached. Months not over yet have no close and are left out.
+    pub async fn get_monthly_prices(
+        &self,
+        cache: &mut PriceCache,
+        symbol: &str,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        self.align_metal_unit(cache, symbol, currency, asset_type);
+        let this_month = month_start(Utc::now().date_naive());
+        let months: Vec<NaiveDate> =
+            std::iter::successors(Some(month_start(from)), |m| m.checked_add_months(Months::new(1)))
+                .take_while(|m| *m <= to && *m < this_month)
+                .collect();
+        let missing: HashSet<NaiveDate> = months
+            .iter()
+            .copied()
+            .filter(|m| cache.get_month_price(symbol, currency, *m, asset_type).is_none())
+            .collect();
+
+        if let (Some(&first), Some(&last)) = (missing.iter().min(), missing.iter().max()) {
+            let (mut points, provider) = self
+                .fetch_range(cache, symbol, currency, first, month_end(last), asset_type)
+                .await?;
+            points.sort_by_key(|p| p.date);
+            let source = PriceSource::now(provider);
+            let closes = points.iter().enumerate().filter(|(i, point)| {
+                points
+                    .get(i + 1)
+                    .is_none_or(|next| month_start(next.date) != month_start(point.date))
+            });
+            for (_, close) in closes {
+                if missing.contains(&month_start(close.date)) {
+                    let PricePoint { date, price } = *close;
+                    cache.set_monthly_price(symbol, currency, date, price, asset_type);
+                    cache.set_source(symbol, currency, date, source.clone(), asset_type);
+                }
+            }
+        }
+
+        Ok(months
+            .iter()
+            .filter_map(|m| cache.get_month_price(symbol, currency, *m, asset_type))
+            .collect())
+    }
+
+    /// Internal: fetch the prices from `from` to `to` from the first provider
+    /// that has them (falling back like `fetch_price`), in the unit prices
+    /// are cached in and screened (see `screen_range`). Returns them with
+    /// the name of the provider.
+    async fn fetch_range(
+        &self,
+        cache: &PriceCache,
+        symbol: &str,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        asset_type: &AssetType,
+    ) -> Result<(Vec<PricePoint>, String), CoreError> {
         let providers = self.registry.get_providers_for(asset_type);
         if providers.is_empty() {
             return Err(CoreError::NoProvider(asset_type.to_string()));
         }
 
+        let lookup = || PriceLookup::new(symbol, currency, asset_type, from, to);
         let mut last_error = None;
         for provider in &providers {
-            match provider.get_price_range(symbol, currency, from, to).await {
-                Ok(points) => {
-                    cache.set_prices(symbol, currency, &points);
-                    return Ok(points);
+            let name = || provider.name().to_string();
+            self.emit(|| PriceServiceEvent::ProviderAttempt { provider: name(), lookup: lookup() });
+            match self
+                .fetch_range_memoized(*provider, asset_type, symbol, currency, from, to)
+                .await
+            {
+                Ok(mut points) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::Span::current().record("provider", provider.name());
+                    self.emit(|| PriceServiceEvent::ProviderSucceeded {
+                        provider: name(),
+                        lookup: lookup(),
+                    });
+                    let factor = self.unit_factor(asset_type);
+                    if factor != 1.0 {
+                        for point in &mut points {
+                            point.price *= factor;
+                        }
+                    }
+                    let request = PriceRequest::new(symbol, currency, from, asset_type);
+                    self.screen_range(cache, provider.name(), &request, &mut points);
+                    return Ok((points, provider.name().to_string()));
                 }
                 Err(e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(provider = provider.name(), error = %e, "provider request failed");
+                    self.emit(|| PriceServiceEvent::ProviderFailed {
+                        provider: name(),
+                        lookup: lookup(),
+                        error: e.clone(),
+                    });
+                    if !Self::should_try_next_provider(&e) {
+                        return Err(e);
+                    }
                     last_error = Some(e);
-                    // Try next provider
                 }
             }
         }
@@ -130,51 +722,567 @@ impl PriceService {
     /// Internal: fetch a single price from API providers with automatic fallback.
     ///
     /// Tries providers in registration order. If the primary fails (API down,
-    /// rate limited, etc.), automatically falls back to the next provider.
-    /// Validates that returned prices are finite and non-negative.
+    /// rate limited, symbol unsupported), automatically falls back to the next
+    /// provider. Permanent failures such as an invalid API key stop immediately.
+    /// Quotes that are not positive, or too far from `reference` without a
+    /// second provider agreeing, are rejected with a warning (see the type
+    /// docs). Returns the price, in the unit prices are cached in, and the
+    /// name of the provider that quoted it.
     async fn fetch_price(
         &self,
-        symbol: &str,
-        currency: &str,
-        date: NaiveDate,
-        asset_type: &AssetType,
-    ) -> Result<f64, CoreError> {
+        request: &PriceRequest,
+        reference: Option<f64>,
+    ) -> Result<(f64, String), CoreError> {
+        let PriceRequest { symbol, currency, date, asset_type } = request;
         let providers = self.registry.get_providers_for(asset_type);
         if providers.is_empty() {
             return Err(CoreError::NoProvider(asset_type.to_string()));
         }
 
         let today = chrono::Utc::now().date_naive();
+        let factor = self.unit_factor(asset_type);
         let mut last_error = None;
+        // First quote too far from `reference` (with its provider and the
+        // reference), until another provider confirms or contradicts it
+        let mut suspicious: Option<(f64, &str, f64)> = None;
 
         for provider in &providers {
-            let result = if date >= today {
-                provider.get_current_price(symbol, currency).await
+            self.emit(|| PriceServiceEvent::ProviderAttempt {
+                provider: provider.name().to_string(),
+                lookup: request.lookup(),
+            });
+            let response = if *date >= today {
+                ResponseRequest::Current
             } else {
-                provider.get_historical_price(symbol, currency, date).await
+                ResponseRequest::Historical(*date)
             };
+            let result = self
+                .fetch_price_memoized(*provider, asset_type, symbol, currency, response)
+                .await;
 
             match result {
                 Ok(price) => {
-                    // R4: Validate price is finite and non-negative
-                    if !price.is_finite() || price < 0.0 {
-                        last_error = Some(CoreError::Api {
+                    let price = price * factor;
+                    if !price.is_finite() || price <= 0.0 {
+                        self.warn(provider.name(), request, price, PriceWarningReason::NotPositive);
+                        let error = CoreError::Api {
+                            provider: provider.name().to_string(),
+                            status: None,
+                            message: format!(
+                                "Invalid price returned for {symbol}: {price} (must be finite and positive)"
+                            ),
+                        };
+                        self.emit_failed(provider.name(), request, &error);
+                        last_error = Some(error);
+                        continue;
+                    }
+                    let jump = reference.filter(|r| self.is_jump(price, *r));
+                    let confirmed = suspicious.is_some_and(|(first, ..)| Self::agree(first, price));
+                    if let (Some(reference), false) = (jump, confirmed) {
+                        if suspicious.is_none() {
+                            suspicious = Some((price, provider.name(), reference));
+                        } else {
+                            self.warn_suspicious(provider.name(), request, price, reference);
+                        }
+                        let error = CoreError::Api {
                             provider: provider.name().to_string(),
+                            status: None,
                             message: format!(
-                                "Invalid price returned for {symbol}: {price} (must be finite and non-negative)"
+                                "Suspicious price for {symbol}: {price} (cached: {reference})"
                             ),
-                        });
+                        };
+                        self.emit_failed(provider.name(), request, &error);
+                        last_error = Some(error);
                         continue;
                     }
-                    return Ok(price);
+                    // A confirmed quote stands; otherwise this one replaces it
+                    let (price, name) = match suspicious {
+                        Some((first, name, _)) if confirmed => (first, name),
+                        Some((first, name, reference)) => {
+                            self.warn_suspicious(name, request, first, reference);
+                            (price, provider.name())
+                        }
+                        None => (price, provider.name()),
+                    };
+                    #[cfg(feature = "tracing")]
+                    tracing::Span::current().record("provider", name);
+                    self.emit(|| PriceServiceEvent::ProviderSucceeded {
+                        provider: name.to_string(),
+                        lookup: request.lookup(),
+                    });
+                    return Ok((price, name.to_string()));
                 }
                 Err(e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(provider = provider.name(), error = %e, "provider request failed");
+                    self.emit_failed(provider.name(), request, &e);
+                    if !Self::should_try_next_provider(&e) {
+                        return Err(e);
+                    }
                     last_error = Some(e);
-                    // Try next provider
                 }
             }
         }
 
-        Err(last_error.unwrap_or_else(|| CoreError::NoProvider(asset_type.to_string())))
+        if let Some((first, name, reference)) = suspicious {
+            self.warn_suspicious(name, request, first, reference);
+        }
+        let error = last_error.unwrap_or_else(|| CoreError::NoProvider(asset_type.to_string()));
+        if *date < today && !error.is_transient() {
+            return Err(CoreError::PriceNotAvailable {
+                symbol: symbol.clone(),
+                currency: currency.clone(),
+                date: date.to_string(),
+            });
+        }
+        Err(error)
+    }
+
+    /// Ask one provider for a single price, answering from (and filling)
+    /// the response memo. Invalid prices are not memoized.
+    async fn fetch_price_memoized(
+        &self,
+        provider: &dyn PriceProvider,
+        asset_type: &AssetType,
+        symbol: &str,
+        currency: &str,
+        request: ResponseRequest,
+    ) -> Result<f64, CoreError> {
+        let key = ResponseKey::new(provider.name(), asset_type, symbol, currency, request);
+        if let Some(CachedResponse::Price(price)) = self.memoized(&key) {
+            return Ok(price);
+        }
+        self.count_call(provider)?;
+        let price = match key.request {
+            ResponseRequest::Historical(date) => {
+                self.cancellable(provider.get_historical_price_for_asset_type(
+                    asset_type, symbol, currency, date,
+                ))
+                .await?
+            }
+            _ => {
+                self.cancellable(
+                    provider.get_current_price_for_asset_type(asset_type, symbol, currency),
+                )
+                .await?
+            }
+        };
+        if price.is_finite() && price > 0.0 {
+            self.memoize(key, CachedResponse::Price(price));
+        }
+        Ok(price)
+    }
+
+    /// Ask one provider for a price range, answering from (and filling) the
+    /// response memo.
+    async fn fetch_range_memoized(
+        &self,
+        provider: &dyn PriceProvider,
+        asset_type: &AssetType,
+        symbol: &str,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        let request = ResponseRequest::Range { from, to };
+        let key = ResponseKey::new(provider.name(), asset_type, symbol, currency, request);
+        if let Some(CachedResponse::Range(points)) = self.memoized(&key) {
+            return Ok(points);
+        }
+        self.count_call(provider)?;
+        let points = self
+            .cancellable(
+                provider.get_price_range_for_asset_type(asset_type, symbol, currency, from, to),
+            )
+            .await?;
+        if self.response_cache.is_some() {
+            self.memoize(key, CachedResponse::Range(points.clone()));
+        }
+        Ok(points)
+    }
+
+    /// Run a provider request unless the cancellation token fired, dropping
+    /// it (and so aborting it) if the token fires while it is in flight.
+    async fn cancellable<T>(
+        &self,
+        request: impl Future<Output = Result<T, CoreError>>,
+    ) -> Result<T, CoreError> {
+        let Some(token) = &self.cancellation else {
+            return request.await;
+        };
+        if token.is_cancelled() {
+            return Err(CoreError::Cancelled);
+        }
+        match future::select(std::pin::pin!(request), token.cancelled()).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(CoreError::Cancelled),
+        }
+    }
+
+    /// Count a request about to be sent to `provider`, or refuse it once the
+    /// daily limit of its id is used up.
+    fn count_call(&self, provider: &dyn PriceProvider) -> Result<(), CoreError> {
+        let name = provider.name();
+        let limit = self.daily_limits.get(&provider.id().to_lowercase()).copied();
+        if self.usage.try_record(name, limit, Utc::now()) {
+            Ok(())
+        } else {
+            Err(CoreError::rate_limited(name))
+        }
+    }
+
+    /// The memoized response for `key`, unless memoization is off or bypassed.
+    fn memoized(&self, key: &ResponseKey) -> Option<CachedResponse> {
+        if self.bypass_response_cache {
+            return None;
+        }
+        self.response_cache.as_ref()?.get(key)
+    }
+
+    /// Store a successful provider response in the memo, if one is installed.
+    fn memoize(&self, key: ResponseKey, response: CachedResponse) {
+        if let Some(cache) = &self.response_cache {
+            cache.insert(key, response);
+        }
+    }
+
+    /// The cached price `get_price` would return without asking a provider.
+    ///
+    /// Historical prices are always trusted, except provisional ones until
+    /// their close was asked for today; today's only once refreshed today.
+    /// Derived cross rates are not authoritative, so they count as misses.
+    fn cached_price(
+        &self,
+        cache: &mut PriceCache,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+        today: NaiveDate,
+    ) -> Option<f64> {
+        self.align_metal_unit(cache, symbol, currency, asset_type);
+        let Some(price) = cache.get_price(symbol, currency, date, asset_type) else {
+            return self.month_close(cache, symbol, currency, date, asset_type, today);
+        };
+        let fresh = if date < today {
+            !cache.is_provisional(symbol, currency, date, asset_type)
+                || cache.is_close_missing(symbol, currency, date, asset_type, today)
+        } else {
+            cache.is_today_fresh(symbol, currency, today, asset_type)
+        };
+        (fresh && !cache.is_derived(symbol, currency, date, asset_type)).then_some(price)
+    }
+
+    /// Whether `date` is priced with its month's close when it has no daily
+    /// price (see `set_monthly_price_age`).
+    fn is_old(&self, date: NaiveDate, today: NaiveDate) -> bool {
+        self.monthly_before(today).is_some_and(|first_daily| date < first_daily)
+    }
+
+    /// The cached close of `date`'s month, if `date` is old enough for it.
+    fn month_close(
+        &self,
+        cache: &PriceCache,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        asset_type: &AssetType,
+        today: NaiveDate,
+    ) -> Option<f64> {
+        if !self.is_old(date, today) {
+            return None;
+        }
+        cache
+            .get_month_price(symbol, currency, date, asset_type)
+            .map(|point| point.price)
+    }
+
+    /// The nearest cached price for a past `request` that could not be
+    /// fetched, if the fallback allows one.
+    fn nearest_price(
+        &self,
+        cache: &PriceCache,
+        request: &PriceRequest,
+        today: NaiveDate,
+    ) -> Option<f64> {
+        if !self.fallback.allow_nearest_price || request.date >= today {
+            return None;
+        }
+        let PriceRequest { symbol, currency, date, asset_type } = request;
+        let max_days = self.fallback.max_distance_days;
+        if self.later_prices_only {
+            let last = date.checked_add_days(Days::new(max_days.into())).unwrap_or(NaiveDate::MAX);
+            return cache
+                .get_price_range(symbol, currency, *date, last, asset_type)
+                .first()
+                .map(|point| point.price);
+        }
+        cache
+            .get_nearest_price(symbol, currency, *date, max_days, asset_type)
+            .map(|point| point.price)
+    }
+
+    /// The current quote cached for `request`'s day, for when its close
+    /// could not be fetched (see `PriceCache::is_provisional`). A missing
+    /// close of a past day is recorded, so it is asked for once a day only.
+    fn provisional_price(
+        cache: &mut PriceCache,
+        request: &PriceRequest,
+        today: NaiveDate,
+    ) -> Option<f64> {
+        let PriceRequest { symbol, currency, date, asset_type } = request;
+        if !cache.is_provisional(symbol, currency, *date, asset_type) {
+            return None;
+        }
+        if *date < today {
+            cache.record_missing_close(symbol, currency, *date, asset_type, today);
+        }
+        cache.get_price(symbol, currency, *date, asset_type)
+    }
+
+    /// Report an event to the sink, if any; `event` is only built then.
+    fn emit(&self, event: impl FnOnce() -> PriceServiceEvent) {
+        if let Some(sink) = &self.event_sink {
+            sink(event());
+        }
+    }
+
+    fn emit_failed(&self, provider: &str, request: &PriceRequest, error: &CoreError) {
+        self.emit(|| PriceServiceEvent::ProviderFailed {
+            provider: provider.to_string(),
+            lookup: request.lookup(),
+            error: error.clone(),
+        });
+    }
+
+    /// Store a price fetched from the provider `source`, marking today's
+    /// price as refreshed and, as the current quote, provisional.
+    fn store_price(
+        cache: &mut PriceCache,
+        request: &PriceRequest,
+        price: f64,
+        source: String,
+        today: NaiveDate,
+    ) {
+        let PriceRequest { symbol, currency, date, asset_type } = request;
+        cache.set_price(symbol, currency, *date, price, asset_type);
+        cache.set_source(symbol, currency, *date, PriceSource::now(source), asset_type);
+        if *date >= today {
+            cache.mark_provisional(symbol, currency, *date, asset_type);
+        }
+        if *date == today {
+            cache.mark_updated_today(symbol, currency, today, asset_type);
+        }
+    }
+
+    /// Fill calendar days between the quotes in `points` (and after the last
+    /// one, up to `to` but not past yesterday) with the preceding rate.
+    /// Filled days are cached as carried prices and added to `points`.
+    fn carry_forward(
+        cache: &mut PriceCache,
+        symbol: &str,
+        currency: &str,
+        points: &mut Vec<PricePoint>,
+        to: NaiveDate,
+    ) {
+        let yesterday = chrono::Utc::now().date_naive() - chrono::Duration::days(1);
+        let end = to.min(yesterday);
+        let mut filled = Vec::new();
+        for (i, point) in points.iter().enumerate() {
+            let next_quote = points
+                .get(i + 1)
+                .map_or(end + chrono::Duration::days(1), |p| p.date);
+            let mut day = point.date + chrono::Duration::days(1);
+            while day < next_quote && day <= end {
+                if cache.set_carried_price(symbol, currency, day, point.price, &AssetType::Fiat) {
+                    filled.push(PricePoint { date: day, price: point.price });
+                }
+                day += chrono::Duration::days(1);
+            }
+        }
+        if !filled.is_empty() {
+            points.extend(filled);
+            points.sort_by_key(|p| p.date);
+        }
+    }
+
+    /// Convert a cached metal series to the configured unit (no-op for other assets).
+    fn align_metal_unit(
+        &self,
+        cache: &mut PriceCache,
+        symbol: &str,
+        currency: &str,
+        asset_type: &AssetType,
+    ) {
+        if *asset_type == AssetType::Metal {
+            cache.convert_metal_unit(symbol, currency, self.metal_unit);
+        }
+    }
+
+    /// Factor from a provider quote to the price this service returns.
+    fn unit_factor(&self, asset_type: &AssetType) -> f64 {
+        match asset_type {
+            AssetType::Metal => self.metal_unit.troy_ounces(),
+            _ => 1.0,
+        }
+    }
+
+    /// The cached price a new quote for `request` is checked against: the
+    /// one for the same day, else the nearest within `REFERENCE_MAX_DAYS`.
+    fn reference_price(cache: &PriceCache, request: &PriceRequest) -> Option<f64> {
+        let PriceRequest { symbol, currency, date, asset_type } = request;
+        cache
+            .get_nearest_price(symbol, currency, *date, REFERENCE_MAX_DAYS, asset_type)
+            .map(|point| point.price)
+            .filter(|price| price.is_finite() && *price > 0.0)
+    }
+
+    /// Whether `price` is more than the allowed factor above or below `reference`.
+    fn is_jump(&self, price: f64, reference: f64) -> bool {
+        match self.max_price_change {
+            Some(max) if reference > 0.0 => {
+                let ratio = price / reference;
+                ratio > max || ratio < 1.0 / max
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether two providers' quotes agree (within `CONFIRMATION_TOLERANCE`).
+    fn agree(a: f64, b: f64) -> bool {
+        (a - b).abs() <= CONFIRMATION_TOLERANCE * a.max(b)
+    }
+
+    /// Drop the points of a range `provider` returned (for `request`'s
+    /// symbol, from its date) that must not be cached: non-positive ones,
+    /// isolated spikes (too far from both neighbours) and ones too far from
+    /// the price already cached for their day. Warns about each.
+    fn screen_range(
+        &self,
+        cache: &PriceCache,
+        provider: &str,
+        request: &PriceRequest,
+        points: &mut Vec<PricePoint>,
+    ) {
+        let PriceRequest { symbol, currency, asset_type, .. } = request;
+        let mut positive = Vec::with_capacity(points.len());
+        for point in points.drain(..) {
+            if point.price.is_finite() && point.price > 0.0 {
+                positive.push(point);
+            } else {
+                let request = PriceRequest { date: point.date, ..request.clone() };
+                self.warn(provider, &request, point.price, PriceWarningReason::NotPositive);
+            }
+        }
+        for (i, point) in positive.iter().enumerate() {
+            let request = PriceRequest { date: point.date, ..request.clone() };
+            let cached = cache.get_price(symbol, currency, point.date, asset_type);
+            let neighbours = (i.checked_sub(1).map(|i| &positive[i]), positive.get(i + 1));
+            let reference = match (cached, neighbours) {
+                (Some(cached), _) if self.is_jump(point.price, cached) => Some(cached),
+                (_, (Some(before), Some(after)))
+                    if self.is_jump(point.price, before.price)
+                        && self.is_jump(point.price, after.price) =>
+                {
+                    Some(before.price)
+                }
+                _ => None,
+            };
+            match reference {
+                Some(reference) => self.warn_suspicious(provider, &request, point.price, reference),
+                None => points.push(point.clone()),
+            }
+        }
+    }
+
+    /// Record that `provider`'s quote `price` for `request` was rejected.
+    fn warn(
+        &self,
+        provider: &str,
+        request: &PriceRequest,
+        price: f64,
+        reason: PriceWarningReason,
+    ) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(provider, symbol = %request.symbol, price, ?reason, "rejected price");
+        self.warnings.push(PriceWarning {
+            at: Utc::now(),
+            provider: provider.to_string(),
+            symbol: request.symbol.clone(),
+            currency: request.currency.clone(),
+            date: request.date,
+            price,
+            reason,
+        });
+    }
+
+    /// `warn` about a quote too far from `reference` and not confirmed.
+    fn warn_suspicious(&self, provider: &str, request: &PriceRequest, price: f64, reference: f64) {
+        self.warn(provider, request, price, PriceWarningReason::SuspiciousChange { reference });
+    }
+
+    /// Whether a provider error should fall through to the next provider.
+    ///
+    /// Transient errors (network, 429, 5xx) and "this provider can't serve
+    /// that symbol" errors fall through; permanent ones (invalid API key,
+    /// validation) are surfaced right away so the caller can act on them.
+    fn should_try_next_provider(error: &CoreError) -> bool {
+        error.is_transient()
+            || matches!(
+                error,
+                CoreError::Api { .. }
+                    | CoreError::PriceNotAvailable { .. }
+                    | CoreError::NoProvider(_)
+                    | CoreError::SymbolNotMapped { .. }
+            )
+    }
+}
+
+/// First day of `date`'s month.
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// Last day of the month starting on `start`.
+fn month_end(start: NaiveDate) -> NaiveDate {
+    start
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(NaiveDate::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future;
+
+    #[test]
+    fn cached_lookup_that_would_wait_fails() {
+        let result = resolve_cached(future::pending::<Result<f64, CoreError>>());
+        assert!(matches!(result, Err(CoreError::NoProvider(_))));
+        assert_eq!(resolve_cached(future::ready(Ok(1.0))).unwrap(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn next_price_fallback_skips_earlier_prices() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 1, day).unwrap();
+        let btc = |cache: &mut PriceCache, day, price| {
+            cache.set_price("BTC", "USD", date(day), price, &AssetType::Crypto);
+        };
+        let svc = PriceService::new(PriceProviderRegistry::new()).with_next_price_fallback(3);
+        let mut cache = PriceCache::default();
+        btc(&mut cache, 14, 41000.0);
+
+        let missing = svc.get_price(&mut cache, "BTC", "USD", date(15), &AssetType::Crypto).await;
+        assert!(matches!(missing, Err(CoreError::NoProvider(_))));
+
+        btc(&mut cache, 19, 43000.0);
+        btc(&mut cache, 18, 42000.0);
+        let estimate = svc.get_price(&mut cache, "BTC", "USD", date(15), &AssetType::Crypto).await;
+        assert_eq!(estimate.unwrap(), 42000.0);
+        // A plain fallback set later takes earlier prices again
+        let nearest = svc.with_fallback(PriceFallback::nearest(3));
+        let estimate = nearest.get_price(&mut cache, "BTC", "USD", date(15), &AssetType::Crypto);
+        assert_eq!(estimate.await.unwrap(), 41000.0);
     }
 }
@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::models::warning::PriceWarning;
+
+/// Default number of warnings a `WarningLog` keeps.
+pub const DEFAULT_MAX_WARNINGS: usize = 100;
+
+/// The most recent quotes `PriceService` rejected, oldest first.
+///
+/// Shared (like the response memo) by every service built for it, so
+/// warnings survive registry rebuilds.
+pub type PriceWarningLog = WarningLog<PriceWarning>;
+
+/// The most recent warnings of one kind, oldest first.
+///
+/// Bounded: once full, each new warning drops the oldest one. Can be shared
+/// with background work (e.g., autosave) that has no caller to return errors
+/// to. Never persisted.
+#[derive(Debug)]
+pub struct WarningLog<T> {
+    max_warnings: usize,
+    warnings: Mutex<VecDeque<T>>,
+}
+
+impl<T: Clone> WarningLog<T> {
+    /// A log keeping the last `DEFAULT_MAX_WARNINGS` warnings.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_MAX_WARNINGS)
+    }
+
+    /// A log keeping the last `max_warnings` warnings.
+    pub fn with_capacity(max_warnings: usize) -> Self {
+        Self {
+            max_warnings,
+            warnings: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record a warning, dropping the oldest one if the log is full.
+    pub fn push(&self, warning: T) {
+        if self.max_warnings == 0 {
+            return;
+        }
+        let mut warnings = self.lock();
+        if warnings.len() >= self.max_warnings {
+            warnings.pop_front();
+        }
+        warnings.push_back(warning);
+    }
+
+    /// The recorded warnings, oldest first.
+    pub fn recent(&self) -> Vec<T> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Drop every recorded warning.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<T>> {
+        self.warnings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Clone> Default for WarningLog<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,202 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::models::asset::AssetType;
+use crate::models::price::PricePoint;
+
+/// Default time a memoized provider response stays valid.
+pub const DEFAULT_RESPONSE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Default maximum number of memoized responses.
+pub const DEFAULT_MAX_RESPONSES: usize = 1000;
+
+/// What a provider was asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResponseRequest {
+    Current,
+    Historical(NaiveDate),
+    Range { from: NaiveDate, to: NaiveDate },
+}
+
+/// Identifies one provider call: provider, asset, quote currency and request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResponseKey {
+    pub provider: String,
+    pub asset_type: AssetType,
+    pub symbol: String,
+    pub currency: String,
+    pub request: ResponseRequest,
+}
+
+impl ResponseKey {
+    pub fn new(
+        provider: &str,
+        asset_type: &AssetType,
+        symbol: &str,
+        currency: &str,
+        request: ResponseRequest,
+    ) -> Self {
+        Self {
+            provider: provider.to_string(),
+            asset_type: asset_type.clone(),
+            symbol: symbol.to_uppercase(),
+            currency: currency.to_uppercase(),
+            request,
+        }
+    }
+}
+
+/// A successful provider result, exactly as the provider returned it
+/// (before unit conversion).
+#[derive(Debug, Clone, PartialEq)]
+pub enum CachedResponse {
+    Price(f64),
+    Range(Vec<PricePoint>),
+}
+
+/// Short-lived memo of provider results, consulted by `PriceService` before
+/// it calls a provider.
+///
+/// Unlike `PriceCache` it is never persisted; it only saves repeated
+/// identical requests within a session (e.g. a chart, a summary and a
+/// refresh in a row). Implementations use interior mutability, since the
+/// service only holds a shared reference.
+pub trait ResponseCache: Send + Sync {
+    /// The memoized response for `key`, if present and not expired.
+    fn get(&self, key: &ResponseKey) -> Option<CachedResponse>;
+
+    /// Memoize a successful response.
+    fn insert(&self, key: ResponseKey, response: CachedResponse);
+
+    /// Drop every memoized response.
+    fn clear(&self);
+}
+
+/// Hit/miss counters of a `MemoryResponseCache`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResponseCacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+/// A memoized response with when (and in which order) it was stored.
+struct Entry {
+    stored: DateTime<Utc>,
+    seq: u64,
+    response: CachedResponse,
+}
+
+type Entries = HashMap<ResponseKey, Entry>;
+
+/// In-memory `ResponseCache` with a TTL and a bounded number of entries.
+///
+/// When full, inserting evicts expired entries first, then the oldest one.
+/// A zero TTL or zero capacity disables memoization.
+pub struct MemoryResponseCache {
+    ttl: chrono::Duration,
+    max_entries: usize,
+    entries: Mutex<Entries>,
+    next_seq: AtomicU64,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl MemoryResponseCache {
+    /// Create a cache with the default TTL (5 minutes) and capacity.
+    pub fn new() -> Self {
+        Self::with_ttl(DEFAULT_RESPONSE_TTL)
+    }
+
+    /// Create a cache whose entries expire after `ttl`.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self::with_limits(ttl, DEFAULT_MAX_RESPONSES)
+    }
+
+    /// Create a cache with the given TTL and maximum number of entries.
+    pub fn with_limits(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl: chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX),
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+            next_seq: AtomicU64::new(0),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// How long entries stay valid.
+    pub fn ttl(&self) -> Duration {
+        self.ttl.to_std().unwrap_or_default()
+    }
+
+    /// Hit/miss counts since creation and the current number of entries.
+    pub fn stats(&self) -> ResponseCacheStats {
+        ResponseCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.lock().len(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.max_entries > 0 && self.ttl > chrono::Duration::zero()
+    }
+}
+
+impl Default for MemoryResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponseCache for MemoryResponseCache {
+    fn get(&self, key: &ResponseKey) -> Option<CachedResponse> {
+        let now = Utc::now();
+        let mut entries = self.lock();
+        let response = match entries.get(key) {
+            Some(entry) if now - entry.stored < self.ttl => Some(entry.response.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if response.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        response
+    }
+
+    fn insert(&self, key: ResponseKey, response: CachedResponse) {
+        if !self.is_enabled() {
+            return;
+        }
+        let now = Utc::now();
+        let mut entries = self.lock();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, entry| now - entry.stored < self.ttl);
+            if entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.seq)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        entries.insert(key, Entry { stored: now, seq, response });
+    }
+
+    fn clear(&self) {
+        self.lock().clear();
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::{Datelike, Months, NaiveDate};
+
+use crate::errors::CoreError;
+use crate::models::asset::Asset;
+use crate::models::event::{Event, EventType, RecordedValue};
+use crate::models::portfolio::Portfolio;
+use crate::models::price::PriceCache;
+use crate::models::settings::Settings;
+use crate::models::simulation::SimulationResult;
+use crate::services::analytics_service::AnalyticsService;
+use crate::services::chart_service::ChartService;
+use crate::services::currency_service::CurrencyService;
+use crate::services::price_service::PriceService;
+
+/// Runs "what if I had invested" scenarios: hypothetical buys of one asset
+/// valued with real prices, on a portfolio that exists only for the
+/// simulation.
+pub struct SimulationService {
+    currency_service: CurrencyService,
+    chart_service: ChartService,
+    analytics_service: AnalyticsService,
+}
+
+impl SimulationService {
+    pub fn new() -> Self {
+        Self::with_currency_service(CurrencyService::new())
+    }
+
+    /// Use a configured `CurrencyService` (e.g., custom pivot currencies) for conversions.
+    pub fn with_currency_service(currency_service: CurrencyService) -> Self {
+        Self {
+            chart_service: ChartService::with_currency_service(currency_service.clone()),
+            analytics_service: AnalyticsService::with_currency_service(currency_service.clone()),
+            currency_service,
+        }
+    }
+
+    /// The 1st of every month from `from` to `to` (inclusive).
+    pub fn monthly_dates(from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let mut date = from.with_day(1).and_then(|first| {
+            if first == from {
+                Some(first)
+            } else {
+                first.checked_add_months(Months::new(1))
+            }
+        });
+        let mut dates = Vec::new();
+        while let Some(day) = date.filter(|day| *day <= to) {
+            dates.push(day);
+            date = day.checked_add_months(Months::new(1));
+        }
+        dates
+    }
+
+    /// Buy `asset` for each `(date, amount in currency)` contribution and
+    /// value the result from `from` to `to` in `currency`.
+    ///
+    /// A contribution date without a price is bought at the last known
+    /// price (listed in `gap_filled_dates`), or skipped when no earlier
+    /// price is known. Prices fetched along the way end up in `price_cache`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn simulate(
+        &self,
+        settings: &Settings,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        asset: &Asset,
+        contributions: &[(NaiveDate, f64)],
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<SimulationResult, CoreError> {
+        let mut portfolio = Portfolio::default();
+        portfolio.settings = settings.clone();
+        self.run(
+            &mut portfolio,
+            price_service,
+            price_cache,
+            asset,
+            contributions,
+            currency,
+            from,
+            to,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn run(
+        &self,
+        portfolio: &mut Portfolio,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        asset: &Asset,
+        contributions: &[(NaiveDate, f64)],
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<SimulationResult, CoreError> {
+        let quote_currency = CurrencyService::quote_currency(asset);
+        let (mut gap_filled_dates, mut skipped_dates) = (Vec::new(), Vec::new());
+        let mut last_prices: Option<(f64, f64)> = None;
+        let mut total_contributed = 0.0;
+
+        for &(date, amount) in contributions {
+            let prices = match self
+                .unit_prices(price_service, price_cache, asset, &quote_currency, currency, date)
+                .await
+            {
+                Ok(prices) => prices,
+                Err(CoreError::Cancelled) => return Err(CoreError::Cancelled),
+                Err(_) => match last_prices {
+                    Some(prices) => {
+                        gap_filled_dates.push(date);
+                        prices
+                    }
+                    None => {
+                        skipped_dates.push(date);
+                        continue;
+                    }
+                },
+            };
+            last_prices = Some(prices);
+            let (quote_unit_price, unit_price) = prices;
+
+            let mut event = Event::new(EventType::Buy, asset.clone(), amount / unit_price, date);
+            event.recorded_value = Some(RecordedValue {
+                quote_currency: quote_currency.clone(),
+                quote_unit_price,
+                currency: currency.to_string(),
+                unit_price,
+            });
+            portfolio.events.push(event);
+            total_contributed += amount;
+        }
+        portfolio.mark_events_changed();
+
+        let iter = self.chart_service.portfolio_chart_iter(portfolio, from, to, currency);
+        let value_series = self
+            .chart_service
+            .collect_chart(iter, price_service, price_cache, |_| {}, None)
+            .await?;
+        let summary = self
+            .analytics_service
+            .get_portfolio_summary(portfolio, price_service, price_cache, to, currency)
+            .await?;
+
+        Ok(SimulationResult {
+            asset: asset.clone(),
+            currency: currency.to_string(),
+            events: portfolio.events.clone(),
+            gap_filled_dates,
+            skipped_dates,
+            total_contributed,
+            final_value: summary.total_value,
+            value_series,
+            summary,
+        })
+    }
+
+    /// Unit price of `asset` on `date` in its quote currency and in `currency`.
+    async fn unit_prices(
+        &self,
+        price_service: &PriceService,
+        price_cache: &mut PriceCache,
+        asset: &Asset,
+        quote_currency: &str,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<(f64, f64), CoreError> {
+        let quote_unit_price = self
+            .currency_service
+            .convert_asset_to_currency(price_service, price_cache, asset, 1.0, quote_currency, date)
+            .await?;
+        let unit_price = self
+            .currency_service
+            .convert_asset_to_currency(price_service, price_cache, asset, 1.0, currency, date)
+            .await?;
+        Ok((quote_unit_price, unit_price))
+    }
+}
+
+impl Default for SimulationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,132 @@
+//! Unencrypted side-state of a desktop app: recently opened portfolio files
+//! and display preferences (native only).
+//!
+//! Kept in a small JSON file at a path the caller picks (e.g., the app's
+//! config directory), apart from any portfolio. It never holds passwords,
+//! keys or portfolio contents, only what is safe to leave in plain text.
+
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::CoreError;
+use crate::formatting::Locale;
+use crate::storage::manager::StorageManager;
+
+/// How many recent files are remembered; the oldest are dropped beyond it.
+pub const MAX_RECENT_FILES: usize = 20;
+
+/// Recent files and preferences, loaded from and saved to a JSON file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// Opened portfolio files, most recently opened first
+    pub recent: Vec<RecentFile>,
+
+    /// Date range last shown in a chart, `(from, to)`
+    pub last_chart_range: Option<(NaiveDate, NaiveDate)>,
+}
+
+/// A portfolio file opened before.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    /// Path as passed to `SessionState::add_recent`
+    pub path: String,
+
+    pub last_opened: DateTime<Utc>,
+
+    #[serde(default)]
+    pub preferences: FilePreferences,
+}
+
+/// How one portfolio file is displayed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilePreferences {
+    /// Currency to show values in; `None` for the portfolio's default currency
+    pub display_currency: Option<String>,
+
+    /// Number and date conventions; `None` for the app's locale
+    pub locale: Option<Locale>,
+
+    /// Mask amounts and values on screen (e.g., when presenting)
+    pub hide_values: bool,
+}
+
+impl SessionState {
+    /// Load the session from `path`. A missing file gives an empty session,
+    /// so the first run needs no special case.
+    pub fn load(path: &str) -> Result<Self, CoreError> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save the session to `path` as JSON, creating its directory if needed.
+    /// The file is replaced atomically (see `StorageManager::write_atomic`).
+    pub fn save(&self, path: &str) -> Result<(), CoreError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| CoreError::Serialization(format!("Failed to serialize session: {e}")))?;
+        if let Some(dir) = Path::new(path).parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        StorageManager::write_atomic(path, json.as_bytes())
+    }
+
+    /// Record that the file at `path` was opened now, moving it to the front.
+    /// Its preferences are kept. Beyond `MAX_RECENT_FILES`, the oldest
+    /// entries are dropped.
+    pub fn add_recent(&mut self, path: &str) {
+        let preferences = match self.recent.iter().position(|f| f.path == path) {
+            Some(idx) => self.recent.remove(idx).preferences,
+            None => FilePreferences::default(),
+        };
+        self.recent.insert(
+            0,
+            RecentFile { path: path.to_string(), last_opened: Utc::now(), preferences },
+        );
+        self.recent.truncate(MAX_RECENT_FILES);
+    }
+
+    /// The `limit` most recently opened files, most recent first.
+    #[must_use]
+    pub fn get_recent(&self, limit: usize) -> Vec<&RecentFile> {
+        self.recent.iter().take(limit).collect()
+    }
+
+    /// Forget files that no longer exist (e.g., moved or deleted).
+    /// Returns how many were removed.
+    pub fn remove_missing(&mut self) -> usize {
+        let count = self.recent.len();
+        self.recent.retain(|f| Path::new(&f.path).exists());
+        count - self.recent.len()
+    }
+
+    /// Forget the file at `path`. Returns `false` if it wasn't recent.
+    pub fn remove_recent(&mut self, path: &str) -> bool {
+        let count = self.recent.len();
+        self.recent.retain(|f| f.path != path);
+        self.recent.len() != count
+    }
+
+    /// Display preferences of the file at `path`, if it is a recent file.
+    #[must_use]
+    pub fn preferences(&self, path: &str) -> Option<&FilePreferences> {
+        self.recent.iter().find(|f| f.path == path).map(|f| &f.preferences)
+    }
+
+    /// Set the display preferences of a recent file. Returns `false` if
+    /// `path` isn't a recent file (add it with `add_recent` first).
+    pub fn set_preferences(&mut self, path: &str, preferences: FilePreferences) -> bool {
+        match self.recent.iter_mut().find(|f| f.path == path) {
+            Some(file) => {
+                file.preferences = preferences;
+                true
+            }
+            None => false,
+        }
+    }
+}
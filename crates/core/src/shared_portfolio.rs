@@ -0,0 +1,170 @@
+//! The portfolio held by `SavingsTracker`, shared copy-on-write with the
+//! autosave worker so scheduling a save doesn't copy it.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+use crate::models::alert::Alert;
+use crate::models::portfolio::Portfolio;
+use crate::models::price::PriceCache;
+
+/// What is lent to the autosave worker; it takes the portfolio when its
+/// debounce window passes.
+#[derive(Default)]
+pub(crate) struct Lent {
+    /// The portfolio, with the tracker revision it is as of
+    pub(crate) portfolio: Option<(Arc<Portfolio>, u64)>,
+    /// The portfolio was taken back to be changed in place and is to be lent
+    /// again, so the worker waits for it instead of skipping the save
+    pub(crate) taken_back: bool,
+}
+
+pub(crate) type Published = Arc<Mutex<Lent>>;
+
+/// A `Portfolio` behind an `Arc`, read like the portfolio itself.
+///
+/// `publish` lends the worker the current portfolio without copying it.
+/// Writes go through `edit`, which takes back a portfolio the worker hasn't
+/// started saving so it is changed in place: a burst of edits copies
+/// nothing. Dropping the edit lends the portfolio again at the revision it
+/// was lent at, also when the edit failed, and `mark_dirty` then lends it at
+/// the new one; the worker waits while an edit is open. Price cache and
+/// alert check writes (`price_cache_mut`, `lend_price_cache`,
+/// `alerts_and_price_cache_mut`) leave it lent and copy the portfolio once
+/// instead, so a valuation or alert check right after an edit doesn't delay
+/// its save.
+pub(crate) struct SharedPortfolio {
+    portfolio: Arc<Portfolio>,
+    published: Published,
+    /// Revision of the last lend
+    lent_revision: u64,
+    /// The lent portfolio was taken back and is to be lent again
+    relend: bool,
+}
+
+impl SharedPortfolio {
+    pub(crate) fn new(portfolio: Portfolio) -> Self {
+        Self {
+            portfolio: Arc::new(portfolio),
+            published: Published::default(),
+            lent_revision: 0,
+            relend: false,
+        }
+    }
+
+    /// The slot `publish` lends the portfolio to.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn published(&self) -> Published {
+        Arc::clone(&self.published)
+    }
+
+    /// Lend the worker the portfolio as of tracker `revision`.
+    pub(crate) fn publish(&mut self, revision: u64) {
+        self.relend = false;
+        self.lent_revision = revision;
+        *self.published.lock().unwrap_or_else(|e| e.into_inner()) = Lent {
+            portfolio: Some((Arc::clone(&self.portfolio), revision)),
+            taken_back: false,
+        };
+    }
+
+    /// The portfolio, for writes. Takes it back from the worker until the
+    /// returned edit is dropped.
+    pub(crate) fn edit(&mut self) -> PortfolioEdit<'_> {
+        self.take_back();
+        PortfolioEdit { shared: self }
+    }
+
+    /// The price cache, for writes that don't mark unsaved changes.
+    pub(crate) fn price_cache_mut(&mut self) -> &mut PriceCache {
+        &mut Arc::make_mut(&mut self.portfolio).price_cache
+    }
+
+    /// The alerts and the price cache, for alert checks that mark unsaved
+    /// changes only when one triggers.
+    pub(crate) fn alerts_and_price_cache_mut(&mut self) -> (&mut Vec<Alert>, &mut PriceCache) {
+        let portfolio = Arc::make_mut(&mut self.portfolio);
+        (&mut portfolio.alerts, &mut portfolio.price_cache)
+    }
+
+    /// Lend the price cache to a valuation that also reads the portfolio.
+    pub(crate) fn lend_price_cache(&mut self) -> PriceCacheLoan<'_> {
+        let cache = std::mem::take(self.price_cache_mut());
+        PriceCacheLoan { shared: self, cache }
+    }
+
+    /// Stop lending the portfolio if the worker hasn't taken it yet.
+    fn take_back(&mut self) {
+        if Arc::strong_count(&self.portfolio) == 1 {
+            return;
+        }
+        let mut published = self.published.lock().unwrap_or_else(|e| e.into_inner());
+        let lent = published.portfolio.as_ref();
+        if lent.is_some_and(|(lent, _)| Arc::ptr_eq(lent, &self.portfolio)) {
+            *published = Lent { portfolio: None, taken_back: true };
+            self.relend = true;
+        }
+    }
+
+    /// Lend the portfolio again after `take_back`, at the revision it was lent at.
+    fn relend(&mut self) {
+        if self.relend {
+            self.publish(self.lent_revision);
+        }
+    }
+}
+
+/// The portfolio taken back from the autosave worker to be changed in
+/// place. Dropping the edit lends it again.
+pub(crate) struct PortfolioEdit<'a> {
+    shared: &'a mut SharedPortfolio,
+}
+
+impl Deref for PortfolioEdit<'_> {
+    type Target = Portfolio;
+
+    fn deref(&self) -> &Portfolio {
+        &self.shared.portfolio
+    }
+}
+
+impl DerefMut for PortfolioEdit<'_> {
+    fn deref_mut(&mut self) -> &mut Portfolio {
+        Arc::make_mut(&mut self.shared.portfolio)
+    }
+}
+
+impl Drop for PortfolioEdit<'_> {
+    fn drop(&mut self) {
+        self.shared.relend();
+    }
+}
+
+/// The price cache taken out of the portfolio, so a valuation can read the
+/// portfolio and write the cache at once. Dropping the loan puts the cache
+/// back, also when the valuation's future is dropped before it completes.
+pub(crate) struct PriceCacheLoan<'a> {
+    shared: &'a mut SharedPortfolio,
+    cache: PriceCache,
+}
+
+impl PriceCacheLoan<'_> {
+    /// The portfolio and the lent price cache.
+    pub(crate) fn split(&mut self) -> (&Portfolio, &mut PriceCache) {
+        (&*self.shared, &mut self.cache)
+    }
+}
+
+impl Drop for PriceCacheLoan<'_> {
+    fn drop(&mut self) {
+        *self.shared.price_cache_mut() = std::mem::take(&mut self.cache);
+    }
+}
+
+impl Deref for SharedPortfolio {
+    type Target = Portfolio;
+
+    fn deref(&self) -> &Portfolio {
+        &self.portfolio
+    }
+}
@@ -0,0 +1,391 @@
+//! Events as CSV (RFC 4180): fields containing the delimiter, quotes or line
+//! breaks are quoted, quotes doubled, and records end with CRLF.
+//!
+//! Text cells starting with `=`, `+`, `-`, `@`, tab or carriage return are
+//! formulas to spreadsheet applications; with `CsvOptions::defuse_formulas`
+//! they are written with a leading `'` (and read back without it). Cells
+//! that already start with `'` before such a character get one more, so
+//! they read back unchanged.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::CoreError;
+use crate::models::asset::{Asset, AssetType};
+use crate::models::event::{Event, EventType, RecordedValue};
+
+/// Field separator of a CSV file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CsvDelimiter {
+    #[default]
+    Comma,
+    /// For spreadsheet locales that use a decimal comma (e.g., Polish, German)
+    Semicolon,
+}
+
+impl CsvDelimiter {
+    pub fn as_char(&self) -> char {
+        match self {
+            CsvDelimiter::Comma => ',',
+            CsvDelimiter::Semicolon => ';',
+        }
+    }
+}
+
+/// A column of the events CSV, named in the header row by `header()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CsvColumn {
+    Id,
+    EventType,
+    Symbol,
+    Name,
+    AssetType,
+    Amount,
+    Date,
+    Notes,
+    /// `Event::is_planned`; not exported by default, read as false when empty
+    Planned,
+    /// `RecordedValue::quote_currency`; empty without a recorded value
+    QuoteCurrency,
+    /// `RecordedValue::quote_unit_price`
+    QuoteUnitPrice,
+    /// `RecordedValue::currency`
+    Currency,
+    /// `RecordedValue::unit_price`
+    UnitPrice,
+}
+
+impl CsvColumn {
+    /// Columns exported by default.
+    pub const DEFAULT: [CsvColumn; 8] = [
+        CsvColumn::Id,
+        CsvColumn::EventType,
+        CsvColumn::Symbol,
+        CsvColumn::Name,
+        CsvColumn::AssetType,
+        CsvColumn::Amount,
+        CsvColumn::Date,
+        CsvColumn::Notes,
+    ];
+
+    /// The default columns followed by the planned flag and the recorded value
+    /// columns.
+    pub const ALL: [CsvColumn; 13] = [
+        CsvColumn::Id,
+        CsvColumn::EventType,
+        CsvColumn::Symbol,
+        CsvColumn::Name,
+        CsvColumn::AssetType,
+        CsvColumn::Amount,
+        CsvColumn::Date,
+        CsvColumn::Notes,
+        CsvColumn::Planned,
+        CsvColumn::QuoteCurrency,
+        CsvColumn::QuoteUnitPrice,
+        CsvColumn::Currency,
+        CsvColumn::UnitPrice,
+    ];
+
+    /// Name of the column in the header row.
+    pub fn header(&self) -> &'static str {
+        match self {
+            CsvColumn::Id => "id",
+            CsvColumn::EventType => "event_type",
+            CsvColumn::Symbol => "symbol",
+            CsvColumn::Name => "name",
+            CsvColumn::AssetType => "asset_type",
+            CsvColumn::Amount => "amount",
+            CsvColumn::Date => "date",
+            CsvColumn::Notes => "notes",
+            CsvColumn::Planned => "planned",
+            CsvColumn::QuoteCurrency => "quote_currency",
+            CsvColumn::QuoteUnitPrice => "quote_unit_price",
+            CsvColumn::Currency => "currency",
+            CsvColumn::UnitPrice => "unit_price",
+        }
+    }
+
+    /// Column named `header` (case-insensitive), if any.
+    pub fn from_header(header: &str) -> Option<CsvColumn> {
+        let header = header.trim();
+        Self::ALL.into_iter().find(|column| column.header().eq_ignore_ascii_case(header))
+    }
+
+    /// Whether the column holds free text, which may be taken for a formula.
+    fn is_text(&self) -> bool {
+        matches!(
+            self,
+            CsvColumn::Symbol
+                | CsvColumn::Name
+                | CsvColumn::Notes
+                | CsvColumn::QuoteCurrency
+                | CsvColumn::Currency
+        )
+    }
+}
+
+/// How events are written to and read from CSV.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvOptions {
+    pub delimiter: CsvDelimiter,
+
+    /// Prefix text cells that spreadsheets would run as formulas with `'`
+    /// (default true)
+    pub defuse_formulas: bool,
+
+    /// Columns to export, in order (default `CsvColumn::DEFAULT`). Imports
+    /// read the columns named in the header row instead.
+    pub columns: Vec<CsvColumn>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: CsvDelimiter::Comma,
+            defuse_formulas: true,
+            columns: CsvColumn::DEFAULT.to_vec(),
+        }
+    }
+}
+
+/// Characters that make a spreadsheet read a cell as a formula.
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Whether a text cell gets a `'` in front when defusing formulas.
+fn needs_defusing(field: &str) -> bool {
+    field.trim_start_matches('\'').starts_with(FORMULA_PREFIXES)
+}
+
+/// Write `events` as CSV with a header row. `precision` gives the decimal
+/// places amounts of an asset are rounded to.
+pub fn events_to_csv<'a>(
+    events: impl IntoIterator<Item = &'a Event>,
+    precision: impl Fn(&Asset) -> u32,
+    options: &CsvOptions,
+) -> String {
+    let delimiter = options.delimiter.as_char();
+    let mut csv = String::new();
+    let header: Vec<String> = options.columns.iter().map(|c| c.header().to_string()).collect();
+    push_record(&mut csv, &header, delimiter);
+    for event in events {
+        let recorded = event.recorded_value.as_ref();
+        let fields: Vec<String> = options
+            .columns
+            .iter()
+            .map(|column| {
+                let field = match column {
+                    CsvColumn::Id => event.id.to_string(),
+                    CsvColumn::EventType => event.event_type.to_string(),
+                    CsvColumn::Symbol => event.asset.symbol.clone(),
+                    CsvColumn::Name => event.asset.name.clone(),
+                    CsvColumn::AssetType => event.asset.asset_type.to_string(),
+                    CsvColumn::Amount => {
+                        crate::formatting::round_to(event.amount, precision(&event.asset))
+                            .to_string()
+                    }
+                    CsvColumn::Date => event.date.to_string(),
+                    CsvColumn::Notes => event.notes.clone().unwrap_or_default(),
+                    CsvColumn::Planned => event.is_planned.to_string(),
+                    CsvColumn::QuoteCurrency => {
+                        recorded.map(|r| r.quote_currency.clone()).unwrap_or_default()
+                    }
+                    CsvColumn::QuoteUnitPrice => {
+                        recorded.map(|r| r.quote_unit_price.to_string()).unwrap_or_default()
+                    }
+                    CsvColumn::Currency => {
+                        recorded.map(|r| r.currency.clone()).unwrap_or_default()
+                    }
+                    CsvColumn::UnitPrice => {
+                        recorded.map(|r| r.unit_price.to_string()).unwrap_or_default()
+                    }
+                };
+                if options.defuse_formulas && column.is_text() && needs_defusing(&field) {
+                    format!("'{field}")
+                } else {
+                    field
+                }
+            })
+            .collect();
+        push_record(&mut csv, &fields, delimiter);
+    }
+    csv
+}
+
+/// Read events from CSV with a header row naming the columns (any order;
+/// unknown columns are ignored). `event_type`, `symbol`, `asset_type`,
+/// `amount` and `date` are required. Events without an `id` get a new one;
+/// a recorded value is read when all four of its columns are filled.
+pub fn events_from_csv(csv: &str, options: &CsvOptions) -> Result<Vec<Event>, CoreError> {
+    let csv = csv.strip_prefix('\u{feff}').unwrap_or(csv);
+    let mut records = parse_records(csv, options.delimiter.as_char())?
+        .into_iter()
+        .enumerate()
+        .filter(|(_, record)| !(record.len() == 1 && record[0].trim().is_empty()));
+    let Some((_, header)) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let columns: Vec<Option<CsvColumn>> = header.iter().map(|h| CsvColumn::from_header(h)).collect();
+    for required in [
+        CsvColumn::EventType,
+        CsvColumn::Symbol,
+        CsvColumn::AssetType,
+        CsvColumn::Amount,
+        CsvColumn::Date,
+    ] {
+        if !columns.contains(&Some(required)) {
+            return Err(CoreError::Deserialization(format!(
+                "CSV has no \"{}\" column",
+                required.header()
+            )));
+        }
+    }
+
+    let mut events = Vec::new();
+    for (index, record) in records {
+        let row = index + 1;
+        let cell = |wanted: CsvColumn| -> Option<&str> {
+            let position = columns.iter().position(|c| *c == Some(wanted))?;
+            let field = record.get(position)?.as_str();
+            let field = match field.strip_prefix('\'') {
+                Some(rest) if options.defuse_formulas && wanted.is_text() && needs_defusing(rest) => {
+                    rest
+                }
+                _ => field,
+            };
+            Some(field).filter(|f| !f.trim().is_empty())
+        };
+        let invalid = |column: CsvColumn| {
+            CoreError::Deserialization(format!(
+                "Invalid {} {:?} in CSV row {row}",
+                column.header(),
+                cell(column).unwrap_or("")
+            ))
+        };
+        let number = |column: CsvColumn| -> Result<Option<f64>, CoreError> {
+            cell(column)
+                .map(|value| value.trim().parse::<f64>().map_err(|_| invalid(column)))
+                .transpose()
+        };
+
+        let event_type = cell(CsvColumn::EventType)
+            .and_then(parse_event_type)
+            .ok_or_else(|| invalid(CsvColumn::EventType))?;
+        let asset_type = cell(CsvColumn::AssetType)
+            .and_then(parse_asset_type)
+            .ok_or_else(|| invalid(CsvColumn::AssetType))?;
+        let symbol = cell(CsvColumn::Symbol).ok_or_else(|| invalid(CsvColumn::Symbol))?.trim();
+        let name = cell(CsvColumn::Name).unwrap_or(symbol);
+        let amount = number(CsvColumn::Amount)?.ok_or_else(|| invalid(CsvColumn::Amount))?;
+        let date = cell(CsvColumn::Date)
+            .and_then(|date| NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok())
+            .ok_or_else(|| invalid(CsvColumn::Date))?;
+
+        let mut event = Event::new(event_type, Asset::new(symbol, name, asset_type), amount, date);
+        if let Some(id) = cell(CsvColumn::Id) {
+            event.id = Uuid::parse_str(id.trim()).map_err(|_| invalid(CsvColumn::Id))?;
+        }
+        event.notes = cell(CsvColumn::Notes).map(str::to_string);
+        event.is_planned = match cell(CsvColumn::Planned).map(str::trim) {
+            None => false,
+            Some(planned) if planned.eq_ignore_ascii_case("true") => true,
+            Some(planned) if planned.eq_ignore_ascii_case("false") => false,
+            Some(_) => return Err(invalid(CsvColumn::Planned)),
+        };
+        if let (Some(quote_currency), Some(quote_unit_price), Some(currency), Some(unit_price)) = (
+            cell(CsvColumn::QuoteCurrency),
+            number(CsvColumn::QuoteUnitPrice)?,
+            cell(CsvColumn::Currency),
+            number(CsvColumn::UnitPrice)?,
+        ) {
+            event.recorded_value = Some(RecordedValue {
+                quote_currency: quote_currency.trim().to_uppercase(),
+                quote_unit_price,
+                currency: currency.trim().to_uppercase(),
+                unit_price,
+            });
+        }
+        events.push(event);
+    }
+    Ok(events)
+}
+
+fn parse_event_type(name: &str) -> Option<EventType> {
+    [
+        EventType::Buy,
+        EventType::Sell,
+        EventType::Deposit,
+        EventType::Withdrawal,
+        EventType::Interest,
+    ]
+    .into_iter()
+    .find(|event_type| event_type.to_string().eq_ignore_ascii_case(name.trim()))
+}
+
+fn parse_asset_type(name: &str) -> Option<AssetType> {
+    [AssetType::Crypto, AssetType::Fiat, AssetType::Metal, AssetType::Stock]
+        .into_iter()
+        .find(|asset_type| asset_type.to_string().eq_ignore_ascii_case(name.trim()))
+}
+
+/// Append one record, quoting fields that need it, terminated by CRLF.
+fn push_record(out: &mut String, fields: &[String], delimiter: char) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(delimiter);
+        }
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+/// Split CSV text into records of fields. Accepts CRLF and LF line ends
+/// and a missing final line end; quoted fields may span lines.
+pub fn parse_records(csv: &str, delimiter: char) -> Result<Vec<Vec<String>>, CoreError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' || (c == '\r' && chars.peek() == Some(&'\n')) {
+            if c == '\r' {
+                chars.next();
+            }
+            record.push(std::mem::take(&mut field));
+            records.push(std::mem::take(&mut record));
+        } else {
+            field.push(c);
+        }
+    }
+    if in_quotes {
+        return Err(CoreError::Deserialization(
+            "Invalid CSV: a quoted field is not closed".into(),
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
@@ -5,12 +5,38 @@ use super::encryption::KdfParams;
 pub const MAGIC: &[u8; 4] = b"SVTK";
 
 /// Current file format version.
-pub const CURRENT_VERSION: u16 = 1;
+///
+/// - 1: the first release; the encrypted payload is the portfolio
+/// - 2: the payload starts with its schema version (2 bytes LE), so the
+///   portfolio schema can change without a new file version
+///
+/// Files of older versions are migrated on load.
+pub const CURRENT_VERSION: u16 = 2;
+
+/// Current schema version of the portfolio in the encrypted payload.
+///
+/// - 1: the first release, in bincode (migrated on load)
+/// - 2: MessagePack with named fields, so fields with a default can be
+///   added without a legacy reader
+/// - 3: asset metadata keyed by symbol and asset type (the symbol-keyed
+///   metadata of version 2 is migrated on load)
+///
+/// Newer payloads (saved by a newer version of the library, possibly with
+/// fields this one would drop) are refused. Bump it once per release that
+/// adds fields, even with a default, so older libraries refuse files they
+/// would lose data of.
+pub const PAYLOAD_VERSION: u16 = 3;
+
+/// First file version whose payload starts with its schema version.
+const VERSIONED_PAYLOAD: u16 = 2;
 
 /// Minimum header size in bytes:
 /// magic(4) + version(2) + kdf_params(12) + salt(16) + nonce(12) + ciphertext_len(8) = 54
 pub const MIN_HEADER_SIZE: usize = 54;
 
+/// Largest file read by default (512 MiB); see `read_file_with_limit`.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 512 * 1024 * 1024;
+
 /// File header read from an encrypted .svtk file.
 #[derive(Debug)]
 pub struct FileHeader {
@@ -60,9 +86,43 @@ pub fn write_file(
     buf
 }
 
-/// Parse the header from raw file bytes.
+/// Split the decrypted payload of a file of `file_version` into its schema
+/// version and the serialized portfolio. Payloads of files before version 2
+/// have the file's version.
+pub fn read_payload(file_version: u16, plaintext: &[u8]) -> Result<(u16, &[u8]), CoreError> {
+    if file_version < VERSIONED_PAYLOAD {
+        return Ok((file_version, plaintext));
+    }
+    let Some((version, portfolio)) = plaintext.split_first_chunk::<2>() else {
+        return Err(CoreError::InvalidFileFormat("Payload too small to hold its version".into()));
+    };
+    let version = u16::from_le_bytes(*version);
+    if version == 0 || version > PAYLOAD_VERSION {
+        return Err(CoreError::UnsupportedVersion { found: version, supported: PAYLOAD_VERSION });
+    }
+    Ok((version, portfolio))
+}
+
+/// Parse the header from raw file bytes, refusing files larger than
+/// `DEFAULT_MAX_FILE_SIZE`.
 /// Returns the header and the ciphertext slice.
 pub fn read_file(data: &[u8]) -> Result<(FileHeader, &[u8]), CoreError> {
+    read_file_with_limit(data, DEFAULT_MAX_FILE_SIZE)
+}
+
+/// Parse the header from raw file bytes of at most `max_file_size` bytes.
+/// Returns the header and the ciphertext slice.
+///
+/// The bytes may come from anywhere (e.g., a crafted file): every length
+/// in the header is checked against the data before it is used.
+pub fn read_file_with_limit(
+    data: &[u8],
+    max_file_size: u64,
+) -> Result<(FileHeader, &[u8]), CoreError> {
+    let size = data.len() as u64;
+    if size > max_file_size {
+        return Err(CoreError::FileTooLarge { size, limit: max_file_size });
+    }
     if data.len() < MIN_HEADER_SIZE {
         return Err(CoreError::InvalidFileFormat(
             "File too small to be a valid SVTK file".into(),
@@ -83,7 +143,7 @@ pub fn read_file(data: &[u8]) -> Result<(FileHeader, &[u8]), CoreError> {
     offset += 2;
 
     if version == 0 || version > CURRENT_VERSION {
-        return Err(CoreError::UnsupportedVersion(version));
+        return Err(CoreError::UnsupportedVersion { found: version, supported: CURRENT_VERSION });
     }
 
     // KDF params
@@ -144,16 +204,15 @@ pub fn read_file(data: &[u8]) -> Result<(FileHeader, &[u8]), CoreError> {
     );
     offset += 8;
 
-    let expected_end = offset + ciphertext_len as usize;
-    if data.len() < expected_end {
+    // Compared before any conversion, so no length can overflow the offset
+    let available = data.len() - offset;
+    if ciphertext_len > available as u64 {
         return Err(CoreError::InvalidFileFormat(format!(
-            "File truncated: expected {} bytes of ciphertext, got {}",
-            ciphertext_len,
-            data.len() - offset
+            "File truncated: expected {ciphertext_len} bytes of ciphertext, got {available}"
         )));
     }
 
-    let ciphertext = &data[offset..expected_end];
+    let ciphertext = &data[offset..offset + ciphertext_len as usize];
 
     let header = FileHeader {
         version,
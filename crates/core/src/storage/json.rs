@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::errors::CoreError;
+use crate::models::event::Event;
+use crate::models::portfolio::Portfolio;
+use crate::models::reminder::Reminder;
+
+/// Version of the unencrypted JSON exports (`export_events_to_json`,
+/// `to_json`), written as their top-level `schema_version` field.
+///
+/// - 1: events wrapped in an object with `schema_version` (a bare array of
+///   events before); optionally with their `reminders`
+pub const JSON_SCHEMA_VERSION: u16 = 1;
+
+/// Events exported as JSON, with the reminders of those events (left out
+/// when there are none).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventsExport<E, R = Reminder> {
+    pub schema_version: u16,
+    pub events: Vec<E>,
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub reminders: Vec<R>,
+}
+
+/// The whole portfolio exported as JSON: its fields next to `schema_version`.
+#[derive(Debug, Serialize)]
+pub struct PortfolioExport<'a> {
+    pub schema_version: u16,
+    #[serde(flatten)]
+    pub portfolio: &'a Portfolio,
+}
+
+/// Serialize `events` and their `reminders` as a versioned export (see
+/// `JSON_SCHEMA_VERSION`).
+pub fn events_to_json<'a>(
+    events: impl IntoIterator<Item = &'a Event>,
+    reminders: impl IntoIterator<Item = &'a Reminder>,
+) -> Result<String, CoreError> {
+    let export = EventsExport {
+        schema_version: JSON_SCHEMA_VERSION,
+        events: events.into_iter().collect(),
+        reminders: reminders.into_iter().collect(),
+    };
+    serde_json::to_string_pretty(&export)
+        .map_err(|e| CoreError::Serialization(format!("Failed to serialize events to JSON: {e}")))
+}
+
+/// Read events (and reminders) exported by `events_to_json`, or a bare
+/// array of events as exported before exports were versioned.
+pub fn events_from_json(json: &str) -> Result<EventsExport<Event>, CoreError> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    if value.is_array() {
+        return Ok(EventsExport {
+            schema_version: JSON_SCHEMA_VERSION,
+            events: serde_json::from_value(value)?,
+            reminders: Vec::new(),
+        });
+    }
+    let export: EventsExport<Event> = serde_json::from_value(value)?;
+    if export.schema_version > JSON_SCHEMA_VERSION {
+        return Err(CoreError::UnsupportedVersion {
+            found: export.schema_version,
+            supported: JSON_SCHEMA_VERSION,
+        });
+    }
+    Ok(export)
+}
+
+/// Serialize the whole portfolio as a versioned export. Maps are written in
+/// sorted order, so the same portfolio always gives the same string.
+pub fn portfolio_to_json(portfolio: &Portfolio) -> Result<String, CoreError> {
+    let export = PortfolioExport {
+        schema_version: JSON_SCHEMA_VERSION,
+        portfolio,
+    };
+    serde_json::to_string_pretty(&export)
+        .map_err(|e| CoreError::Serialization(format!("Failed to serialize portfolio: {e}")))
+}
@@ -1,5 +1,8 @@
 use crate::errors::CoreError;
-use crate::models::portfolio::Portfolio;
+use bincode::Options;
+use serde::de::DeserializeOwned;
+
+use crate::models::portfolio::{Portfolio, PortfolioV1};
 
 use super::encryption::{self, KdfParams};
 use super::format;
@@ -7,13 +10,46 @@ use super::format;
 /// High-level storage operations: save/load portfolio to/from encrypted bytes or files.
 pub struct StorageManager;
 
+/// Limits applied when loading a file that may come from anywhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Largest file accepted, in bytes (default
+    /// `format::DEFAULT_MAX_FILE_SIZE`, 512 MiB). Larger files fail with
+    /// `CoreError::FileTooLarge` before being read or decrypted.
+    pub max_file_size: u64,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            max_file_size: format::DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+}
+
 impl StorageManager {
     /// Encrypt and serialize a portfolio to raw bytes (portable, platform-independent).
     ///
-    /// Flow: Portfolio → bincode → AES-256-GCM(Argon2id(password)) → SVTK format bytes
+    /// Flow: Portfolio → payload version + MessagePack → AES-256-GCM(Argon2id(password)) → SVTK format bytes
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                plaintext_bytes = tracing::field::Empty,
+                file_bytes = tracing::field::Empty,
+                duration_ms = tracing::field::Empty
+            )
+        )
+    )]
     pub fn save_to_bytes(portfolio: &Portfolio, password: &str) -> Result<Vec<u8>, CoreError> {
-        // 1. Serialize portfolio to binary
-        let plaintext = bincode::serialize(portfolio)
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+
+        // 1. Serialize portfolio with field names, after its schema version
+        let mut plaintext = format::PAYLOAD_VERSION.to_le_bytes().to_vec();
+        rmp_serde::encode::write_named(&mut plaintext, portfolio)
             .map_err(|e| CoreError::Serialization(format!("Failed to serialize portfolio: {e}")))?;
 
         // 2. Generate fresh salt and nonce
@@ -36,15 +72,50 @@ impl StorageManager {
             &ciphertext,
         );
 
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            span.record("plaintext_bytes", plaintext.len());
+            span.record("file_bytes", file_bytes.len());
+            span.record("duration_ms", started.elapsed().as_millis() as u64);
+        }
+
         Ok(file_bytes)
     }
 
-    /// Decrypt and deserialize a portfolio from raw bytes.
-    ///
-    /// Flow: SVTK bytes → parse header → Argon2id(password, salt) → AES-256-GCM decrypt → bincode → Portfolio
+    /// Decrypt and deserialize a portfolio from raw bytes, with the default
+    /// `LoadOptions`.
     pub fn load_from_bytes(data: &[u8], password: &str) -> Result<Portfolio, CoreError> {
+        Self::load_from_bytes_with(data, password, &LoadOptions::default())
+    }
+
+    /// Decrypt and deserialize a portfolio from raw bytes within the limits
+    /// of `options`.
+    ///
+    /// Flow: SVTK bytes → parse header → Argon2id(password, salt) → AES-256-GCM decrypt → MessagePack → Portfolio
+    /// (bincode for files of the first release; legacy trash migrated into deleted events)
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                file_bytes = data.len(),
+                plaintext_bytes = tracing::field::Empty,
+                duration_ms = tracing::field::Empty
+            )
+        )
+    )]
+    pub fn load_from_bytes_with(
+        data: &[u8],
+        password: &str,
+        options: &LoadOptions,
+    ) -> Result<Portfolio, CoreError> {
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+
         // 1. Parse file header
-        let (header, ciphertext) = format::read_file(data)?;
+        let (header, ciphertext) = format::read_file_with_limit(data, options.max_file_size)?;
 
         // 2. Re-derive key from password + stored salt + stored params
         let key = encryption::derive_key(password, &header.salt, &header.kdf_params)?;
@@ -52,14 +123,70 @@ impl StorageManager {
         // 3. Decrypt
         let plaintext = encryption::decrypt(ciphertext, &key, &header.nonce)?;
 
-        // 4. Deserialize
-        let portfolio: Portfolio = bincode::deserialize(&plaintext)
-            .map_err(|e| CoreError::Deserialization(format!("Failed to deserialize portfolio: {e}")))?;
+        // 4. Deserialize, migrating files of the first release and refusing newer schemas
+        let (payload_version, payload) = format::read_payload(header.version, &plaintext)?;
+        fn deserialization_error(e: impl std::fmt::Display) -> CoreError {
+            CoreError::Deserialization(format!("Failed to deserialize portfolio: {e}"))
+        }
+        let mut portfolio: Portfolio = match payload_version {
+            1 => {
+                let legacy = decode_bincode::<PortfolioV1>(payload).map_err(deserialization_error)?;
+                let (portfolio, _dropped) = legacy.migrate();
+                #[cfg(feature = "tracing")]
+                if _dropped > 0 {
+                    tracing::warn!(
+                        dropped = _dropped,
+                        "dropped cached price series with an ambiguous asset type"
+                    );
+                }
+                portfolio
+            }
+            _ => rmp_serde::from_slice(payload).map_err(deserialization_error)?,
+        };
+
+        // 5. Keep the trash of older files as deleted events, and key their
+        //    asset metadata by asset type
+        portfolio.migrate_legacy_trash();
+        let _dropped = portfolio.migrate_legacy_asset_metadata();
+        #[cfg(feature = "tracing")]
+        if _dropped > 0 {
+            tracing::warn!(dropped = _dropped, "dropped asset metadata of unknown symbols");
+        }
+
+        // 6. Reset settings no setter would have accepted
+        let _reset = portfolio.settings.reset_invalid();
+        #[cfg(feature = "tracing")]
+        if _reset {
+            tracing::warn!("reset invalid settings to their defaults");
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            span.record("plaintext_bytes", plaintext.len());
+            span.record("duration_ms", started.elapsed().as_millis() as u64);
+        }
 
         Ok(portfolio)
     }
 
-    /// Save portfolio to an encrypted file on disk (native only).
+    /// Re-save encrypted `data` of an older format (e.g., a bincode payload)
+    /// in the current one, with the same password. The portfolio is
+    /// migrated as on load; current files are just encrypted afresh.
+    pub fn migrate(data: &[u8], password: &str) -> Result<Vec<u8>, CoreError> {
+        let portfolio = Self::load_from_bytes(data, password)?;
+        Self::save_to_bytes(&portfolio, password)
+    }
+
+    /// `Portfolio::content_hash` of the portfolio in encrypted `data` (e.g.,
+    /// the file on disk), to tell whether it differs from the open one
+    /// without comparing the whole portfolio.
+    pub fn file_content_hash(data: &[u8], password: &str) -> Result<u64, CoreError> {
+        Ok(Self::load_from_bytes(data, password)?.content_hash())
+    }
+
+    /// Save portfolio to an encrypted file on disk (native only), replacing
+    /// it atomically (see `write_atomic`).
     #[cfg(not(target_arch = "wasm32"))]
     pub fn save_to_file(
         portfolio: &Portfolio,
@@ -67,14 +194,65 @@ impl StorageManager {
         password: &str,
     ) -> Result<(), CoreError> {
         let bytes = Self::save_to_bytes(portfolio, password)?;
-        std::fs::write(path, bytes)?;
-        Ok(())
+        Self::write_atomic(path, &bytes)
+    }
+
+    /// Write `bytes` to `path` through a temporary file next to it, renamed
+    /// over `path` once complete, so a crash or full disk mid-write never
+    /// leaves a truncated file behind (native only). Each write has its own
+    /// temporary file, so concurrent writers (e.g., autosave and a manual
+    /// save) never mix their bytes; the last rename wins.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn write_atomic(path: &str, bytes: &[u8]) -> Result<(), CoreError> {
+        use std::io::Write;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+        let write = WRITES.fetch_add(1, Ordering::Relaxed);
+        let tmp_path = format!("{path}.{}-{write}.tmp", std::process::id());
+        let result = std::fs::File::create(&tmp_path).and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        });
+        match result.and_then(|()| std::fs::rename(&tmp_path, path)) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                Err(e.into())
+            }
+        }
     }
 
-    /// Load portfolio from an encrypted file on disk (native only).
+    /// Load portfolio from an encrypted file on disk (native only), with the
+    /// default `LoadOptions`.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn load_from_file(path: &str, password: &str) -> Result<Portfolio, CoreError> {
+        Self::load_from_file_with(path, password, &LoadOptions::default())
+    }
+
+    /// Load portfolio from an encrypted file on disk within the limits of
+    /// `options` (native only). The size is checked before the file is read.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_from_file_with(
+        path: &str,
+        password: &str,
+        options: &LoadOptions,
+    ) -> Result<Portfolio, CoreError> {
+        let size = std::fs::metadata(path)?.len();
+        if size > options.max_file_size {
+            return Err(CoreError::FileTooLarge { size, limit: options.max_file_size });
+        }
         let bytes = std::fs::read(path)?;
-        Self::load_from_bytes(&bytes, password)
+        Self::load_from_bytes_with(&bytes, password, options)
     }
 }
+
+/// Decode a bincode payload as exactly `T`. Trailing bytes are an error:
+/// bincode has no field names, so a payload read with the layout of another
+/// version would otherwise decode as garbage or stop short of its end.
+fn decode_bincode<T: DeserializeOwned>(payload: &[u8]) -> Result<T, bincode::Error> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(payload)
+}
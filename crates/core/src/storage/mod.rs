@@ -1,3 +1,6 @@
+pub mod csv;
 pub mod encryption;
 pub mod format;
+pub mod json;
 pub mod manager;
+pub mod secret;
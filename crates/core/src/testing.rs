@@ -0,0 +1,312 @@
+//! Test utilities for downstream crates (Tauri commands, WASM bindings).
+//!
+//! Enabled with the `test-utils` feature. Provides a deterministic price
+//! provider, a fluent portfolio builder and a stub HTTP client for the
+//! built-in providers, so apps don't have to re-implement mocks to exercise
+//! the core without network access.
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::errors::CoreError;
+use crate::models::asset::{Asset, AssetType};
+use crate::models::event::{Event, EventType};
+use crate::models::portfolio::Portfolio;
+use crate::models::price::PricePoint;
+use crate::providers::http::{HttpClient, HttpResponse};
+use crate::providers::registry::PriceProviderRegistry;
+use crate::providers::traits::PriceProvider;
+use crate::services::portfolio_service::PortfolioService;
+use crate::SavingsTracker;
+
+type PriceFn = dyn Fn(&str, &str, NaiveDate) -> Option<f64> + Send + Sync;
+
+/// Where a `StaticPriceProvider` gets its numbers from.
+enum PriceSource {
+    /// (SYMBOL, CURRENCY, date) → price
+    Map(HashMap<(String, String, NaiveDate), f64>),
+    Fn(Box<PriceFn>),
+}
+
+/// Deterministic `PriceProvider` backed by a fixed price table or a closure.
+///
+/// Supports all asset types unless restricted with `with_asset_types`.
+/// The "current" price is the price on today's date; for map-backed providers
+/// it falls back to the most recent entry for the pair.
+pub struct StaticPriceProvider {
+    name: String,
+    asset_types: Vec<AssetType>,
+    source: PriceSource,
+}
+
+impl StaticPriceProvider {
+    /// Create an empty map-backed provider. Add prices with `with_price`.
+    pub fn new() -> Self {
+        Self::from_map(HashMap::new())
+    }
+
+    /// Create a provider from a `(symbol, currency, date) → price` map.
+    pub fn from_map(prices: HashMap<(String, String, NaiveDate), f64>) -> Self {
+        let prices = prices
+            .into_iter()
+            .map(|((s, c, d), p)| ((s.to_uppercase(), c.to_uppercase(), d), p))
+            .collect();
+        Self {
+            name: "Static".to_string(),
+            asset_types: vec![
+                AssetType::Crypto,
+                AssetType::Fiat,
+                AssetType::Metal,
+                AssetType::Stock,
+            ],
+            source: PriceSource::Map(prices),
+        }
+    }
+
+    /// Create a provider that computes prices with a closure.
+    /// Returning `None` yields `CoreError::PriceNotAvailable`.
+    pub fn from_fn(
+        f: impl Fn(&str, &str, NaiveDate) -> Option<f64> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            source: PriceSource::Fn(Box::new(f)),
+            ..Self::new()
+        }
+    }
+
+    /// Add a price point (map-backed providers only; ignored for closures).
+    pub fn with_price(mut self, symbol: &str, currency: &str, date: NaiveDate, price: f64) -> Self {
+        if let PriceSource::Map(prices) = &mut self.source {
+            prices.insert((symbol.to_uppercase(), currency.to_uppercase(), date), price);
+        }
+        self
+    }
+
+    /// Override the provider name reported by `PriceProvider::name`.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Restrict the asset types this provider claims to support.
+    pub fn with_asset_types(mut self, asset_types: Vec<AssetType>) -> Self {
+        self.asset_types = asset_types;
+        self
+    }
+
+    /// Wrap this provider in a registry, ready for `SavingsTracker::create_with_registry`.
+    pub fn into_registry(self) -> PriceProviderRegistry {
+        let mut registry = PriceProviderRegistry::new();
+        registry.register(Box::new(self));
+        registry
+    }
+
+    fn lookup(&self, symbol: &str, currency: &str, date: NaiveDate) -> Option<f64> {
+        let (symbol, currency) = (symbol.to_uppercase(), currency.to_uppercase());
+        match &self.source {
+            PriceSource::Map(prices) => prices.get(&(symbol, currency, date)).copied(),
+            PriceSource::Fn(f) => f(&symbol, &currency, date),
+        }
+    }
+
+    fn not_available(symbol: &str, currency: &str, date: impl ToString) -> CoreError {
+        CoreError::PriceNotAvailable {
+            symbol: symbol.to_string(),
+            currency: currency.to_string(),
+            date: date.to_string(),
+        }
+    }
+}
+
+impl Default for StaticPriceProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl PriceProvider for StaticPriceProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supported_asset_types(&self) -> Vec<AssetType> {
+        self.asset_types.clone()
+    }
+
+    async fn get_current_price(&self, symbol: &str, currency: &str) -> Result<f64, CoreError> {
+        let today = chrono::Utc::now().date_naive();
+        if let Some(price) = self.lookup(symbol, currency, today) {
+            return Ok(price);
+        }
+        if let PriceSource::Map(prices) = &self.source {
+            let (s, c) = (symbol.to_uppercase(), currency.to_uppercase());
+            if let Some((_, price)) = prices
+                .iter()
+                .filter(|((ps, pc, _), _)| *ps == s && *pc == c)
+                .max_by_key(|((_, _, d), _)| *d)
+            {
+                return Ok(*price);
+            }
+        }
+        Err(Self::not_available(symbol, currency, "current"))
+    }
+
+    async fn get_historical_price(
+        &self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<f64, CoreError> {
+        self.lookup(symbol, currency, date)
+            .ok_or_else(|| Self::not_available(symbol, currency, date))
+    }
+
+    async fn get_price_range(
+        &self,
+        symbol: &str,
+        currency: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        Ok(from
+            .iter_days()
+            .take_while(|d| *d <= to)
+            .filter_map(|date| {
+                self.lookup(symbol, currency, date)
+                    .map(|price| PricePoint { date, price })
+            })
+            .collect())
+    }
+}
+
+/// Fluent builder for test portfolios.
+///
+/// Events are added through `PortfolioService`, so they are validated
+/// exactly like real events; invalid fixtures panic in `build`.
+#[derive(Default)]
+pub struct PortfolioBuilder {
+    portfolio: Portfolio,
+    events: Vec<Event>,
+}
+
+impl PortfolioBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a buy event.
+    pub fn with_buy(mut self, asset: Asset, amount: f64, date: NaiveDate) -> Self {
+        self.events.push(Event::new(EventType::Buy, asset, amount, date));
+        self
+    }
+
+    /// Add a sell event.
+    pub fn with_sell(mut self, asset: Asset, amount: f64, date: NaiveDate) -> Self {
+        self.events.push(Event::new(EventType::Sell, asset, amount, date));
+        self
+    }
+
+    /// Add an arbitrary, pre-built event.
+    pub fn with_event(mut self, event: Event) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Seed the price cache.
+    pub fn with_cached_price(
+        mut self,
+        symbol: &str,
+        currency: &str,
+        date: NaiveDate,
+        price: f64,
+        asset_type: &AssetType,
+    ) -> Self {
+        self.portfolio.price_cache.set_price(symbol, currency, date, price, asset_type);
+        self
+    }
+
+    /// Set the default display currency.
+    pub fn with_default_currency(mut self, currency: &str) -> Self {
+        self.portfolio.settings.default_currency = currency.to_uppercase();
+        self
+    }
+
+    /// Build the portfolio.
+    ///
+    /// # Panics
+    /// If any event fails validation (e.g., selling more than held).
+    pub fn build(self) -> Portfolio {
+        let service = PortfolioService::new();
+        let mut portfolio = self.portfolio;
+        // Add in date order so sells are validated against earlier buys
+        let mut events = self.events;
+        events.sort_by_key(|e| e.date);
+        for event in events {
+            if let Err(e) = service.add_event(&mut portfolio, event) {
+                panic!("PortfolioBuilder: invalid fixture event: {e}");
+            }
+        }
+        portfolio
+    }
+
+    /// Build a `SavingsTracker` over this portfolio using the given registry.
+    pub fn build_tracker(self, registry: PriceProviderRegistry) -> SavingsTracker {
+        SavingsTracker::build_with_registry(self.build(), registry)
+    }
+}
+
+/// `HttpClient` answering with canned responses, for testing the built-in
+/// providers (`with_http_client`) against recorded API output.
+///
+/// Each request is answered by the first route whose pattern its URL
+/// contains; a request no route matches fails with `CoreError::Network`.
+/// Requested URLs are recorded in order.
+#[derive(Default)]
+pub struct StubHttpClient {
+    routes: Vec<(String, HttpResponse)>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl StubHttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer requests whose URL contains `pattern` with `status` and `body`.
+    pub fn with_route(self, pattern: &str, status: u16, body: &str) -> Self {
+        self.with_response(pattern, HttpResponse::new(status, body))
+    }
+
+    /// Answer requests whose URL contains `pattern` with `response`.
+    pub fn with_response(mut self, pattern: &str, response: HttpResponse) -> Self {
+        self.routes.push((pattern.to_string(), response));
+        self
+    }
+
+    /// URLs requested so far, oldest first.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpClient for StubHttpClient {
+    async fn get(
+        &self,
+        _provider: &str,
+        url: &str,
+        _headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, CoreError> {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner()).push(url.to_string());
+        self.routes
+            .iter()
+            .find(|(pattern, _)| url.contains(pattern.as_str()))
+            .map(|(_, response)| response.clone())
+            .ok_or_else(|| CoreError::Network(format!("No stubbed response for {url}")))
+    }
+}
@@ -0,0 +1,77 @@
+// ═══════════════════════════════════════════════════════════════════
+// Allocation Tests — event listings that must not collect all events
+// ═══════════════════════════════════════════════════════════════════
+//
+// A counting global allocator, per thread so tests running in parallel
+// don't see each other's allocations.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use chrono::NaiveDate;
+use savings_tracker_core::models::asset::Asset;
+use savings_tracker_core::models::event::{Event, EventSortOrder, EventType};
+use savings_tracker_core::SavingsTracker;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|bytes| bytes.set(bytes.get() + layout.size()));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Bytes allocated on this thread by `f`.
+fn allocated_by<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+/// A tracker with `count` events, a few per day.
+fn tracker_with_events(count: usize) -> SavingsTracker {
+    let mut tracker = SavingsTracker::create_new();
+    let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+    let events: Vec<Event> = (0..count)
+        .map(|i| {
+            let date = start + chrono::Duration::days((i / 3) as i64);
+            Event::new(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, date)
+        })
+        .collect();
+    tracker.add_events(events).unwrap();
+    tracker
+}
+
+#[test]
+fn test_date_ordered_slice_collects_only_the_slice() {
+    let tracker = tracker_with_events(10_000);
+    let full_list = 10_000 * std::mem::size_of::<&Event>();
+
+    for order in [EventSortOrder::DateDesc, EventSortOrder::DateAsc] {
+        let (slice, bytes) = allocated_by(|| tracker.get_events_slice(5_000..5_050, &order));
+        assert_eq!(slice.len(), 50);
+        // The slice itself and a day's buffer, nowhere near the full list
+        assert!(bytes < full_list / 10, "{order:?} slice allocated {bytes} bytes");
+
+        let (count, bytes) = allocated_by(|| tracker.event_count());
+        assert_eq!(count, 10_000);
+        assert_eq!(bytes, 0);
+    }
+
+    // For comparison: the full listing collects every event
+    let (events, bytes) = allocated_by(|| tracker.get_events());
+    assert_eq!(events.len(), 10_000);
+    assert!(bytes >= full_list);
+}
@@ -0,0 +1,57 @@
+// ═══════════════════════════════════════════════════════════════════
+// Blocking Tests — sync wrappers enabled with the "blocking" feature
+// ═══════════════════════════════════════════════════════════════════
+
+#![cfg(feature = "blocking")]
+
+use chrono::NaiveDate;
+use savings_tracker_core::models::asset::Asset;
+use savings_tracker_core::testing::{PortfolioBuilder, StaticPriceProvider};
+use savings_tracker_core::SavingsTracker;
+
+fn d(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+fn tracker() -> SavingsTracker {
+    let provider = StaticPriceProvider::new()
+        .with_price("BTC", "USD", d(15), 42000.0)
+        .with_price("BTC", "USD", d(16), 43500.0);
+    PortfolioBuilder::new()
+        .with_buy(Asset::crypto("BTC", "Bitcoin"), 0.5, d(15))
+        .build_tracker(provider.into_registry())
+}
+
+#[test]
+fn test_blocking_wrappers_without_a_runtime() {
+    let mut tracker = tracker();
+
+    let value = tracker.get_portfolio_value_blocking(d(15)).unwrap();
+    assert_eq!((value.amount, value.currency.as_str()), (21000.0, "USD"));
+
+    let price = tracker.get_asset_price_blocking(&Asset::crypto("BTC", "Bitcoin"), d(16)).unwrap();
+    assert_eq!(price, 43500.0);
+
+    let summary = tracker.get_portfolio_summary_blocking(d(16)).unwrap();
+    assert!((summary.total_value - 21750.0).abs() < 1e-9);
+
+    let chart = tracker.generate_portfolio_chart_blocking(d(15), d(16)).unwrap();
+    assert_eq!(chart.len(), 2);
+}
+
+#[test]
+fn test_blocking_wrappers_from_several_threads() {
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            std::thread::spawn(|| {
+                let mut tracker = tracker();
+                // The per-thread runtime is reused across calls
+                tracker.get_portfolio_value_blocking(d(15)).unwrap();
+                tracker.get_portfolio_value_blocking(d(16)).unwrap().amount
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 21750.0);
+    }
+}
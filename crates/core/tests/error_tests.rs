@@ -23,25 +23,31 @@ mod display {
 
     #[test]
     fn unsupported_version() {
-        let err = CoreError::UnsupportedVersion(99);
-        assert_eq!(err.to_string(), "Unsupported file version: 99");
+        let err = CoreError::UnsupportedVersion { found: 99, supported: 8 };
+        assert_eq!(err.to_string(), "Unsupported file version: 99 (supported up to 8)");
     }
 
     #[test]
     fn unsupported_version_zero() {
-        let err = CoreError::UnsupportedVersion(0);
-        assert_eq!(err.to_string(), "Unsupported file version: 0");
+        let err = CoreError::UnsupportedVersion { found: 0, supported: 8 };
+        assert_eq!(err.to_string(), "Unsupported file version: 0 (supported up to 8)");
     }
 
     #[test]
     fn unsupported_version_max() {
-        let err = CoreError::UnsupportedVersion(u16::MAX);
+        let err = CoreError::UnsupportedVersion { found: u16::MAX, supported: 8 };
         assert_eq!(
             err.to_string(),
-            format!("Unsupported file version: {}", u16::MAX)
+            format!("Unsupported file version: {} (supported up to 8)", u16::MAX)
         );
     }
 
+    #[test]
+    fn file_too_large() {
+        let err = CoreError::FileTooLarge { size: 2048, limit: 1024 };
+        assert_eq!(err.to_string(), "File too large: 2048 bytes (limit 1024 bytes)");
+    }
+
     #[test]
     fn encryption() {
         let err = CoreError::Encryption("AES key size invalid".into());
@@ -79,6 +85,7 @@ mod display {
     fn api_error() {
         let err = CoreError::Api {
             provider: "CoinCap".into(),
+            status: None,
             message: "rate limited".into(),
         };
         assert_eq!(err.to_string(), "API error (CoinCap): rate limited");
@@ -88,11 +95,34 @@ mod display {
     fn api_error_empty_provider() {
         let err = CoreError::Api {
             provider: String::new(),
+            status: None,
             message: "unknown".into(),
         };
         assert_eq!(err.to_string(), "API error (): unknown");
     }
 
+    #[test]
+    fn inflation_data_unavailable() {
+        let err = CoreError::InflationDataUnavailable {
+            country: "PL".into(),
+            date: "2019-05-01".into(),
+        };
+        assert_eq!(err.to_string(), "Inflation data not available for PL on 2019-05-01");
+    }
+
+    #[test]
+    fn cancelled() {
+        assert_eq!(CoreError::Cancelled.to_string(), "Operation cancelled");
+    }
+
+    #[test]
+    fn invalid_api_key() {
+        let err = CoreError::InvalidApiKey {
+            provider: "metals.dev".into(),
+        };
+        assert_eq!(err.to_string(), "Invalid or missing API key for metals.dev");
+    }
+
     #[test]
     fn network() {
         let err = CoreError::Network("connection refused".into());
@@ -123,6 +153,43 @@ mod display {
         assert_eq!(err.to_string(), "Event not found: abc-123");
     }
 
+    #[test]
+    fn alert_not_found() {
+        let err = CoreError::AlertNotFound("abc-123".into());
+        assert_eq!(err.to_string(), "Alert not found: abc-123");
+    }
+
+    #[test]
+    fn corporate_action_not_found() {
+        let err = CoreError::CorporateActionNotFound("abc-123".into());
+        assert_eq!(err.to_string(), "Corporate action not found: abc-123");
+    }
+
+    #[test]
+    fn accrual_rule_not_found() {
+        let err = CoreError::AccrualRuleNotFound("abc-123".into());
+        assert_eq!(err.to_string(), "Accrual rule not found: abc-123");
+    }
+
+    #[test]
+    fn reminder_not_found() {
+        let err = CoreError::ReminderNotFound("abc-123".into());
+        assert_eq!(err.to_string(), "Reminder not found for event: abc-123");
+    }
+
+    #[test]
+    fn symbol_not_mapped() {
+        let err = CoreError::SymbolNotMapped {
+            provider: "CoinCap".into(),
+            symbol: "PEPE".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Could not map symbol PEPE to a CoinCap asset id (set one with set_symbol_override)"
+        );
+        assert!(!err.is_transient());
+    }
+
     #[test]
     fn price_not_available() {
         let err = CoreError::PriceNotAvailable {
@@ -157,7 +224,8 @@ mod debug_trait {
         // Ensure Debug is derived and doesn't panic
         let variants: Vec<CoreError> = vec![
             CoreError::InvalidFileFormat("test".into()),
-            CoreError::UnsupportedVersion(1),
+            CoreError::UnsupportedVersion { found: 2, supported: 1 },
+            CoreError::FileTooLarge { size: 2, limit: 1 },
             CoreError::Encryption("test".into()),
             CoreError::Decryption,
             CoreError::Serialization("test".into()),
@@ -165,6 +233,7 @@ mod debug_trait {
             CoreError::FileIO("test".into()),
             CoreError::Api {
                 provider: "p".into(),
+                status: None,
                 message: "m".into(),
             },
             CoreError::Network("test".into()),
@@ -300,6 +369,71 @@ mod from_impls {
     }
 }
 
+// ── Transient classification ────────────────────────────────────────
+
+mod transient {
+    use super::*;
+
+    fn api(status: Option<u16>) -> CoreError {
+        CoreError::Api {
+            provider: "p".into(),
+            status,
+            message: "m".into(),
+        }
+    }
+
+    #[test]
+    fn network_is_transient() {
+        assert!(CoreError::Network("timed out".into()).is_transient());
+    }
+
+    #[test]
+    fn rate_limit_is_transient() {
+        assert!(api(Some(429)).is_transient());
+    }
+
+    #[test]
+    fn server_errors_are_transient() {
+        assert!(api(Some(500)).is_transient());
+        assert!(api(Some(503)).is_transient());
+        assert!(api(Some(599)).is_transient());
+    }
+
+    #[test]
+    fn timeouts_are_transient() {
+        let err = CoreError::timed_out("CoinCap");
+        assert!(err.is_timeout());
+        assert!(err.is_transient());
+        assert!(err.to_string().contains("timed out"));
+        assert!(!api(Some(504)).is_timeout());
+    }
+
+    #[test]
+    fn client_errors_are_permanent() {
+        assert!(!api(Some(400)).is_transient());
+        assert!(!api(Some(404)).is_transient());
+    }
+
+    #[test]
+    fn api_without_status_is_permanent() {
+        assert!(!api(None).is_transient());
+    }
+
+    #[test]
+    fn business_and_storage_errors_are_permanent() {
+        assert!(!CoreError::ValidationError("x".into()).is_transient());
+        assert!(!CoreError::Decryption.is_transient());
+        assert!(!CoreError::EventNotFound("x".into()).is_transient());
+        assert!(!CoreError::InvalidApiKey { provider: "p".into() }.is_transient());
+        assert!(!CoreError::PriceNotAvailable {
+            symbol: "BTC".into(),
+            currency: "USD".into(),
+            date: "2025-01-15".into(),
+        }
+        .is_transient());
+    }
+}
+
 // ── Error is std::error::Error ──────────────────────────────────────
 
 mod std_error {
@@ -342,6 +476,7 @@ mod edge_cases {
     fn unicode_in_error_message() {
         let err = CoreError::Api {
             provider: "日本API".into(),
+            status: None,
             message: "接続エラー".into(),
         };
         assert_eq!(err.to_string(), "API error (日本API): 接続エラー");
@@ -0,0 +1,274 @@
+// ═══════════════════════════════════════════════════════════════════
+// Golden File Tests — files saved by earlier versions must keep loading
+// ═══════════════════════════════════════════════════════════════════
+//
+// tests/fixtures/golden_v<N>.svtk is `golden_portfolio()` saved with
+// payload version N (password `PASSWORD`). Every golden file is loaded and
+// compared with `golden_portfolio()` field by field, so a file saved today
+// is guaranteed to load in any later version.
+//
+// When the format legitimately changes (a `format::PAYLOAD_VERSION` bump):
+//
+// 1. Run `cargo test --test golden_file_tests -- --ignored write_golden_file`
+//    to save golden_v<new version>.svtk next to the existing files.
+// 2. Commit it. Never regenerate or delete an existing golden file: each
+//    stands for the files users saved with that version.
+// 3. If the new version adds data, extend `golden_portfolio()` only with
+//    values older files load as (e.g., a field's default), or check the
+//    new data in a test of the new file alone.
+//
+// `current_version_has_a_golden_file` fails until step 1 is done.
+//
+// tests/fixtures/legacy_bincode_v1.svtk was saved by the first release
+// (payload version 1, before golden files) and is checked on its own.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use uuid::Uuid;
+
+use savings_tracker_core::models::asset::{Asset, AssetMetadata, AssetType, MetalUnit};
+use savings_tracker_core::models::event::{Event, EventType, RecordedValue};
+use savings_tracker_core::models::portfolio::Portfolio;
+use savings_tracker_core::models::price::{PriceResolution, PriceSource};
+use savings_tracker_core::models::reminder::Reminder;
+use savings_tracker_core::storage::encryption::{decrypt, derive_key};
+use savings_tracker_core::storage::format::{self, PAYLOAD_VERSION};
+use savings_tracker_core::storage::manager::StorageManager;
+use savings_tracker_core::SavingsTracker;
+
+const PASSWORD: &str = "golden-password";
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn event(id: u128, event_type: EventType, asset: Asset, amount: f64, date: NaiveDate) -> Event {
+    Event {
+        id: Uuid::from_u128(id),
+        ..Event::new(event_type, asset, amount, date)
+    }
+}
+
+/// The portfolio every golden file holds: events with notes, a recorded
+/// price, a planned and a deleted event, cached prices with a source and a
+/// month close, asset metadata, the watchlist and a reminder.
+fn golden_portfolio() -> Portfolio {
+    let btc = Asset::crypto("BTC", "Bitcoin");
+    let gold = Asset::metal("XAU", "Gold");
+    let usd = Asset::fiat("USD", "US Dollar");
+
+    let mut portfolio = Portfolio::default();
+    portfolio.settings.default_currency = "EUR".into();
+    portfolio.settings.metal_unit = MetalUnit::Gram;
+    portfolio.settings.long_term_holding_days = 730;
+
+    let mut first_buy = event(1, EventType::Buy, btc.clone(), 0.5, date(2024, 1, 15));
+    first_buy.notes = Some("First buy on Kraken".into());
+    first_buy.recorded_value = Some(RecordedValue {
+        quote_currency: "USD".into(),
+        quote_unit_price: 42_000.0,
+        currency: "EUR".into(),
+        unit_price: 38_500.0,
+    });
+    let deposit = event(2, EventType::Deposit, usd.clone(), 1_000.0, date(2024, 2, 1));
+    let coins = event(3, EventType::Buy, gold.clone(), 2.0, date(2024, 3, 10));
+    let mut deleted = event(4, EventType::Buy, btc.clone(), 0.1, date(2024, 4, 1));
+    deleted.deleted_at = Some(Utc.with_ymd_and_hms(2024, 4, 2, 18, 30, 0).unwrap());
+    let mut sell = event(5, EventType::Sell, btc.clone(), 0.2, date(2024, 9, 20));
+    sell.notes = Some("Took some profit".into());
+    let mut planned = event(6, EventType::Buy, gold.clone(), 1.0, date(2030, 1, 1));
+    planned.is_planned = true;
+    portfolio.events = vec![first_buy, deposit, coins, deleted, sell, planned];
+
+    let fetched_at = Utc.with_ymd_and_hms(2024, 1, 16, 8, 0, 0).unwrap();
+    let cache = &mut portfolio.price_cache;
+    cache.set_price("BTC", "USD", date(2024, 1, 15), 42_000.0, &AssetType::Crypto);
+    let source = PriceSource { source: "CoinGecko".into(), fetched_at };
+    cache.set_source("BTC", "USD", date(2024, 1, 15), source, &AssetType::Crypto);
+    cache.set_price("USD", "EUR", date(2024, 1, 15), 0.9167, &AssetType::Fiat);
+    cache.set_monthly_price("XAU", "USD", date(2024, 2, 29), 2_030.5, &AssetType::Metal);
+
+    portfolio.asset_metadata.insert(
+        ("XAU".into(), AssetType::Metal),
+        AssetMetadata {
+            notes: Some("Coins in the safe".into()),
+            color_hex: Some("#D4AF37".into()),
+            ..Default::default()
+        },
+    );
+    portfolio.watchlist.push(Asset::crypto("ETH", "Ethereum"));
+    portfolio.reminders.push(Reminder::new(Uuid::from_u128(3), date(2025, 3, 10)));
+    portfolio
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Golden files in the fixtures directory, with the payload version in
+/// their name.
+fn golden_files() -> Vec<(u16, PathBuf)> {
+    let mut files: Vec<(u16, PathBuf)> = std::fs::read_dir(fixtures_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            let version = name.strip_prefix("golden_v")?.strip_suffix(".svtk")?.parse().ok()?;
+            Some((version, path))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Payload version of encrypted `data`.
+fn payload_version(data: &[u8]) -> u16 {
+    let (header, ciphertext) = format::read_file(data).unwrap();
+    let key = derive_key(PASSWORD, &header.salt, &header.kdf_params).unwrap();
+    let plaintext = decrypt(ciphertext, &key, &header.nonce).unwrap();
+    format::read_payload(header.version, &plaintext).unwrap().0
+}
+
+fn assert_golden(loaded: &Portfolio, name: &str) {
+    let expected = golden_portfolio();
+
+    assert_eq!(loaded.settings.default_currency, "EUR", "{name}");
+    assert_eq!(loaded.settings.metal_unit, MetalUnit::Gram, "{name}");
+    assert_eq!(loaded.settings.long_term_holding_days, 730, "{name}");
+
+    assert_eq!(loaded.events.len(), expected.events.len(), "{name}");
+    for (loaded, expected) in loaded.events.iter().zip(&expected.events) {
+        assert_eq!(loaded.id, expected.id, "{name}");
+        assert_eq!(loaded.event_type, expected.event_type, "{name}: {}", expected.id);
+        assert_eq!(loaded.asset, expected.asset, "{name}: {}", expected.id);
+        assert_eq!(loaded.amount, expected.amount, "{name}: {}", expected.id);
+        assert_eq!(loaded.date, expected.date, "{name}: {}", expected.id);
+        assert_eq!(loaded.notes, expected.notes, "{name}: {}", expected.id);
+        assert_eq!(loaded.recorded_value, expected.recorded_value, "{name}: {}", expected.id);
+        assert_eq!(loaded.is_planned, expected.is_planned, "{name}: {}", expected.id);
+        assert_eq!(loaded.deleted_at, expected.deleted_at, "{name}: {}", expected.id);
+    }
+    // The deleted event is in the trash, not in a separate list
+    assert!(loaded.trash.is_empty(), "{name}");
+
+    let cache = &loaded.price_cache;
+    assert_eq!(cache.total_entries(), expected.price_cache.total_entries(), "{name}");
+    let btc_day = date(2024, 1, 15);
+    assert_eq!(cache.get_price("BTC", "USD", btc_day, &AssetType::Crypto), Some(42_000.0));
+    let source = cache.source("BTC", "USD", btc_day, &AssetType::Crypto).unwrap();
+    assert_eq!(source.source, "CoinGecko", "{name}");
+    assert_eq!(source.fetched_at, Utc.with_ymd_and_hms(2024, 1, 16, 8, 0, 0).unwrap());
+    assert_eq!(cache.get_price("USD", "EUR", btc_day, &AssetType::Fiat), Some(0.9167));
+    let close = date(2024, 2, 29);
+    assert_eq!(cache.get_price("XAU", "USD", close, &AssetType::Metal), Some(2_030.5));
+    assert_eq!(
+        cache.resolution("XAU", "USD", close, &AssetType::Metal),
+        PriceResolution::Monthly,
+        "{name}"
+    );
+
+    assert_eq!(loaded.asset_metadata, expected.asset_metadata, "{name}");
+    assert_eq!(loaded.watchlist, expected.watchlist, "{name}");
+    assert_eq!(loaded.reminders, expected.reminders, "{name}");
+    assert!(loaded.snapshots.is_empty() && loaded.alerts.is_empty(), "{name}");
+    assert_eq!(loaded.content_hash(), expected.content_hash(), "{name}");
+}
+
+#[test]
+fn golden_files_load_field_by_field() {
+    let files = golden_files();
+    assert!(!files.is_empty());
+    for (version, path) in files {
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(payload_version(&data), version, "{name}");
+
+        let loaded = StorageManager::load_from_bytes(&data, PASSWORD).unwrap();
+        assert_golden(&loaded, name);
+    }
+}
+
+#[test]
+fn golden_files_open_in_the_tracker() {
+    for (_, path) in golden_files() {
+        let tracker = SavingsTracker::load_from_file(path.to_str().unwrap(), PASSWORD).unwrap();
+        assert!(tracker.load_integrity_report().is_clean(), "{}", path.display());
+        let btc = Asset::crypto("BTC", "Bitcoin");
+        assert_eq!(tracker.get_holdings(date(2024, 12, 31))[&btc], 0.3);
+    }
+}
+
+#[test]
+fn current_version_has_a_golden_file() {
+    assert!(
+        golden_files().iter().any(|(version, _)| *version == PAYLOAD_VERSION),
+        "no golden file for payload version {PAYLOAD_VERSION}; see the top of this file"
+    );
+}
+
+/// `legacy_bincode_v1.svtk`: four events and a trashed one, settings with
+/// an API key, and three cached series, saved by the first release.
+#[test]
+fn first_release_file_loads_field_by_field() {
+    let data = std::fs::read(fixtures_dir().join("legacy_bincode_v1.svtk")).unwrap();
+    assert_eq!(payload_version(&data), 1);
+    let loaded = StorageManager::load_from_bytes(&data, PASSWORD).unwrap();
+
+    assert_eq!(loaded.settings.default_currency, "PLN");
+    assert_eq!(loaded.settings.api_keys.len(), 1);
+    assert_eq!(loaded.settings.api_keys["metals_dev"], "key-123");
+    // Settings the release didn't have take their default
+    assert_eq!(loaded.settings.pivot_currencies, ["USD", "EUR"]);
+    assert_eq!(loaded.settings.long_term_holding_days, 365);
+
+    // In date order; the trashed event (5) is kept as a deleted one
+    let btc = Asset::crypto("BTC", "Bitcoin");
+    let eur = Asset::fiat("EUR", "Euro");
+    let expected = [
+        (1, EventType::Buy, btc.clone(), 0.5, date(2023, 1, 15), Some("first buy")),
+        (2, EventType::Buy, Asset::metal("XAU", "Gold"), 2.0, date(2023, 2, 1), None),
+        (3, EventType::Buy, Asset::stock("AAPL", "Apple Inc."), 3.0, date(2023, 3, 10), None),
+        (5, EventType::Buy, eur, 100.0, date(2023, 4, 1), Some("typo")),
+        (4, EventType::Sell, btc, 0.1, date(2023, 6, 30), Some("rebalance")),
+    ];
+    assert!(loaded.trash.is_empty());
+    assert_eq!(loaded.events.len(), expected.len());
+    for (loaded, (n, event_type, asset, amount, date, notes)) in loaded.events.iter().zip(expected) {
+        // The release's test data used ids 00000000-0000-4000-8000-00000000000n
+        let id = Uuid::from_u128(0x4000_8000_0000_0000_0000 + n);
+        assert_eq!(loaded.id, id);
+        assert_eq!(loaded.event_type, event_type, "{id}");
+        assert_eq!(loaded.asset, asset, "{id}");
+        assert_eq!(loaded.amount, amount, "{id}");
+        assert_eq!(loaded.date, date, "{id}");
+        assert_eq!(loaded.notes.as_deref(), notes, "{id}");
+        assert_eq!(loaded.recorded_value, None, "{id}");
+        assert!(!loaded.is_planned, "{id}");
+        assert_eq!(loaded.is_deleted(), n == 5, "{id}");
+    }
+
+    // Each series is keyed by the asset type of its symbol
+    let cache = &loaded.price_cache;
+    assert_eq!(cache.asset_count(), 3);
+    assert_eq!(cache.total_entries(), 4);
+    assert_eq!(cache.get_price("BTC", "USD", date(2023, 1, 15), &AssetType::Crypto), Some(21_000.0));
+    assert_eq!(cache.get_price("BTC", "USD", date(2023, 6, 30), &AssetType::Crypto), Some(30_500.5));
+    assert_eq!(cache.get_price("XAU", "PLN", date(2023, 2, 1), &AssetType::Metal), Some(8_100.25));
+    assert_eq!(cache.get_price("USD", "PLN", date(2023, 6, 30), &AssetType::Fiat), Some(4.1));
+    assert!(cache.is_today_fresh("BTC", "USD", date(2023, 6, 30), &AssetType::Crypto));
+    assert_eq!(cache.last_updated.len(), 1);
+    assert!(cache.derived.is_empty() && cache.sources.is_empty());
+}
+
+/// Save `golden_portfolio()` as the golden file of the current payload
+/// version. Refuses to overwrite one: see the top of this file.
+#[test]
+#[ignore = "writes tests/fixtures; run when the format changes"]
+fn write_golden_file() {
+    let path = fixtures_dir().join(format!("golden_v{PAYLOAD_VERSION}.svtk"));
+    assert!(!path.exists(), "{} exists; golden files are never regenerated", path.display());
+
+    let bytes = StorageManager::save_to_bytes(&golden_portfolio(), PASSWORD).unwrap();
+    std::fs::write(&path, bytes).unwrap();
+}
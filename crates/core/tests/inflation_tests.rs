@@ -0,0 +1,324 @@
+// ═══════════════════════════════════════════════════════════════════
+// Inflation Tests — EmbeddedCpiProvider, InflationService, real values
+// ═══════════════════════════════════════════════════════════════════
+
+use chrono::NaiveDate;
+
+use savings_tracker_core::errors::CoreError;
+use savings_tracker_core::inflation::embedded::EmbeddedCpiProvider;
+use savings_tracker_core::inflation::traits::InflationProvider;
+use savings_tracker_core::models::analytics::SummaryOptions;
+use savings_tracker_core::models::asset::{Asset, AssetType};
+use savings_tracker_core::models::event::EventType;
+use savings_tracker_core::models::inflation::CpiPoint;
+use savings_tracker_core::providers::registry::PriceProviderRegistry;
+use savings_tracker_core::services::inflation_service::InflationService;
+use savings_tracker_core::SavingsTracker;
+
+fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, day).unwrap()
+}
+
+/// A provider with a fixed series for country "XX".
+struct FixedCpiProvider;
+
+impl InflationProvider for FixedCpiProvider {
+    fn name(&self) -> &str {
+        "Fixed"
+    }
+
+    fn countries(&self) -> Vec<String> {
+        vec!["XX".into()]
+    }
+
+    fn monthly_cpi(&self, country: &str) -> Vec<CpiPoint> {
+        if country != "XX" {
+            return Vec::new();
+        }
+        vec![
+            CpiPoint { year: 2024, month: 1, index: 100.0 },
+            CpiPoint { year: 2024, month: 5, index: 104.0 },
+        ]
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// EmbeddedCpiProvider
+// ═══════════════════════════════════════════════════════════════════
+
+mod embedded_provider {
+    use super::*;
+
+    #[test]
+    fn embedded_table_has_us_eu_pl() {
+        let provider = EmbeddedCpiProvider::new();
+        assert_eq!(provider.countries(), vec!["EU", "PL", "US"]);
+        for country in ["US", "EU", "PL"] {
+            let series = provider.monthly_cpi(country);
+            assert!(!series.is_empty(), "{country} has no data");
+            assert!(series.windows(2).all(|w| (w[0].year, w[0].month) < (w[1].year, w[1].month)));
+        }
+    }
+
+    #[test]
+    fn country_lookup_is_case_insensitive() {
+        let provider = EmbeddedCpiProvider::new();
+        assert_eq!(provider.monthly_cpi("us"), provider.monthly_cpi("US"));
+        assert!(provider.monthly_cpi("XX").is_empty());
+    }
+
+    #[test]
+    fn from_csv_skips_comments_and_blank_lines() {
+        let provider = EmbeddedCpiProvider::from_csv("# header\n\nUS,2024-01,100\nUS,2024-02, 101.5\n").unwrap();
+        assert_eq!(
+            provider.monthly_cpi("US"),
+            vec![
+                CpiPoint { year: 2024, month: 1, index: 100.0 },
+                CpiPoint { year: 2024, month: 2, index: 101.5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn from_csv_rejects_malformed_lines() {
+        for bad in ["US,2024-01", "US,2024/01,100", "US,2024-13,100", "US,2024-01,-5", "US,2024-01,100,1"] {
+            let result = EmbeddedCpiProvider::from_csv(bad);
+            assert!(matches!(result, Err(CoreError::ValidationError(_))), "accepted '{bad}'");
+        }
+    }
+
+    #[test]
+    fn load_csv_replaces_existing_months() {
+        let mut provider = EmbeddedCpiProvider::from_csv("PL,2024-01,100").unwrap();
+        provider.load_csv("PL,2024-01,110\nPL,2024-02,111").unwrap();
+        let series = provider.monthly_cpi("PL");
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].index, 110.0);
+    }
+
+    #[test]
+    fn set_override_validates_input() {
+        let mut provider = EmbeddedCpiProvider::empty();
+        assert!(provider.set_override("US", 2024, 0, 100.0).is_err());
+        assert!(provider.set_override("US", 2024, 1, f64::NAN).is_err());
+        assert!(provider.set_override("US", 2024, 1, 0.0).is_err());
+        provider.set_override("us", 2024, 1, 100.0).unwrap();
+        assert_eq!(provider.monthly_cpi("US").len(), 1);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// InflationService
+// ═══════════════════════════════════════════════════════════════════
+
+mod inflation_service {
+    use super::*;
+
+    fn fixed() -> InflationService {
+        InflationService::with_provider(Box::new(FixedCpiProvider))
+    }
+
+    #[test]
+    fn known_month_is_exact() {
+        let service = fixed();
+        assert_eq!(service.cpi_at("XX", d(2024, 1, 31)), Some(100.0));
+        assert_eq!(service.cpi_at("XX", d(2024, 5, 1)), Some(104.0));
+    }
+
+    #[test]
+    fn missing_months_interpolate_linearly() {
+        let service = fixed();
+        assert_eq!(service.cpi_at("XX", d(2024, 2, 10)), Some(101.0));
+        assert_eq!(service.cpi_at("XX", d(2024, 3, 10)), Some(102.0));
+        assert_eq!(service.cpi_at("XX", d(2024, 4, 10)), Some(103.0));
+    }
+
+    #[test]
+    fn after_last_month_carries_forward() {
+        assert_eq!(fixed().cpi_at("XX", d(2026, 1, 1)), Some(104.0));
+    }
+
+    #[test]
+    fn before_first_month_or_unknown_country_is_none() {
+        let service = fixed();
+        assert_eq!(service.cpi_at("XX", d(2023, 12, 31)), None);
+        assert_eq!(service.cpi_at("YY", d(2024, 3, 1)), None);
+    }
+
+    #[test]
+    fn overrides_take_precedence_and_fill_gaps() {
+        let mut service = fixed();
+        service.set_override("XX", 2024, 3, 110.0).unwrap();
+        assert_eq!(service.cpi_at("XX", d(2024, 3, 1)), Some(110.0));
+        assert_eq!(service.cpi_at("XX", d(2024, 2, 1)), Some(105.0));
+
+        service.set_override("XX", 2024, 1, 90.0).unwrap();
+        assert_eq!(service.cpi_at("XX", d(2024, 1, 1)), Some(90.0));
+    }
+
+    #[test]
+    fn overrides_survive_provider_change() {
+        let mut service = InflationService::new();
+        service.set_override("XX", 2030, 1, 200.0).unwrap();
+        service.set_provider(Box::new(FixedCpiProvider));
+        assert_eq!(service.provider_name(), "Fixed");
+        assert_eq!(service.cpi_at("XX", d(2030, 1, 1)), Some(200.0));
+    }
+
+    #[test]
+    fn deflator_is_cpi_ratio() {
+        let service = fixed();
+        let factor = service.deflator("XX", d(2024, 1, 15), d(2024, 5, 15)).unwrap();
+        assert!((factor - 1.04).abs() < 1e-12);
+    }
+
+    #[test]
+    fn deflator_without_data_errors() {
+        let result = fixed().deflator("XX", d(2023, 1, 1), d(2024, 5, 1));
+        assert!(matches!(
+            result,
+            Err(CoreError::InflationDataUnavailable { ref country, ref date })
+                if country == "XX" && date == "2023-01-01"
+        ));
+    }
+
+    #[test]
+    fn currency_to_country() {
+        assert_eq!(InflationService::country_for_currency("usd"), Some("US"));
+        assert_eq!(InflationService::country_for_currency("EUR"), Some("EU"));
+        assert_eq!(InflationService::country_for_currency("PLN"), Some("PL"));
+        assert_eq!(InflationService::country_for_currency("GBP"), None);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Real values through the facade
+// ═══════════════════════════════════════════════════════════════════
+
+mod real_values {
+    use super::*;
+
+    /// USD tracker holding 1 BTC bought 2024-01-15 at 40k, worth 50k on 2025-01-15.
+    fn tracker() -> SavingsTracker {
+        let mut tracker = SavingsTracker::create_with_registry(PriceProviderRegistry::new());
+        tracker
+            .add_event(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, d(2024, 1, 15))
+            .unwrap();
+        tracker.set_cached_price("BTC", "USD", d(2024, 1, 15), 40000.0, &AssetType::Crypto);
+        tracker.set_cached_price("BTC", "USD", d(2025, 1, 15), 50000.0, &AssetType::Crypto);
+        tracker
+    }
+
+    #[tokio::test]
+    async fn summary_reports_real_return() {
+        let mut tracker = tracker();
+        let options = SummaryOptions { raw: true, ..Default::default() };
+        let summary = tracker.get_portfolio_summary_with(d(2025, 1, 15), &options).await.unwrap();
+
+        let cpi = EmbeddedCpiProvider::new().monthly_cpi("US");
+        let index = |y: i32| cpi.iter().find(|p| p.year == y && p.month == 1).unwrap().index;
+        let real_invested = 40000.0 * index(2025) / index(2024);
+        let expected = (50000.0 - real_invested) / real_invested * 100.0;
+
+        assert!((summary.total_return_pct - 25.0).abs() < 1e-9);
+        let real = summary.real_return_pct.unwrap();
+        assert!((real - expected).abs() < 1e-9);
+        assert!(real < summary.total_return_pct);
+    }
+
+    #[tokio::test]
+    async fn cpi_changes_reach_a_memoized_summary() {
+        let mut tracker = tracker();
+        let date = d(2025, 1, 15);
+        let summary = tracker.get_portfolio_summary(date).await.unwrap();
+        assert!(summary.real_return_pct.unwrap() < summary.total_return_pct);
+
+        // No inflation since the buy: the real return is the nominal one
+        let cpi = EmbeddedCpiProvider::new().monthly_cpi("US");
+        let jan = cpi.iter().find(|p| p.year == 2024 && p.month == 1).unwrap().index;
+        tracker.set_cpi_override("US", 2025, 1, jan).unwrap();
+        let summary = tracker.get_portfolio_summary(date).await.unwrap();
+        assert_eq!(summary.real_return_pct, Some(summary.total_return_pct));
+
+        // No US data before the override
+        tracker.set_inflation_provider(Box::new(FixedCpiProvider));
+        let summary = tracker.get_portfolio_summary(date).await.unwrap();
+        assert_eq!(summary.real_return_pct, None);
+    }
+
+    #[tokio::test]
+    async fn summary_without_cpi_country_has_no_real_return() {
+        let mut tracker = tracker();
+        tracker.set_cached_price("USD", "GBP", d(2024, 1, 15), 0.8, &AssetType::Fiat);
+        tracker.set_cached_price("USD", "GBP", d(2025, 1, 15), 0.8, &AssetType::Fiat);
+
+        let summary = tracker
+            .get_portfolio_summary_in_currency(d(2025, 1, 15), "GBP")
+            .await
+            .unwrap();
+        assert_eq!(summary.real_return_pct, None);
+    }
+
+    #[tokio::test]
+    async fn real_chart_is_in_end_date_money() {
+        let mut tracker = tracker();
+        tracker.set_inflation_provider(Box::new(FixedCpiProvider));
+        tracker.set_cached_price("BTC", "USD", d(2024, 3, 15), 40000.0, &AssetType::Crypto);
+        tracker.set_cpi_override("XX", 2024, 3, 102.0).unwrap();
+
+        let from = d(2024, 3, 15);
+        let nominal = tracker.generate_portfolio_chart(from, from).await.unwrap();
+        let real = tracker.generate_portfolio_chart_real(from, from, "XX", None).await.unwrap();
+        assert_eq!(real[0].portfolio_value, nominal[0].portfolio_value);
+
+        tracker.set_cpi_override("XX", 2024, 1, 102.0 / 1.02).unwrap();
+        let mut day = d(2024, 1, 15);
+        while day <= from {
+            tracker.set_cached_price("BTC", "USD", day, 40000.0, &AssetType::Crypto);
+            day = day.succ_opt().unwrap();
+        }
+        let real = tracker
+            .generate_portfolio_chart_real(d(2024, 1, 15), d(2024, 3, 15), "XX", None)
+            .await
+            .unwrap();
+        // 2024-01 is 1.02× cheaper than 2024-03 → the Jan point is scaled up
+        let jan = real.iter().find(|p| p.date == d(2024, 1, 15)).unwrap();
+        assert!((jan.portfolio_value - 40000.0 * 1.02).abs() < 1e-6);
+        assert!((jan.events[0].value_in_default_currency - 40000.0 * 1.02).abs() < 1e-6);
+        assert_eq!(real.last().unwrap().portfolio_value, 40000.0);
+    }
+
+    #[tokio::test]
+    async fn real_chart_in_chosen_base_date_money() {
+        let mut tracker = tracker();
+        tracker.set_inflation_provider(Box::new(FixedCpiProvider));
+        tracker.set_cpi_override("XX", 2024, 3, 102.0).unwrap();
+        let (jan, mar) = (d(2024, 1, 15), d(2024, 3, 15));
+        tracker.set_cached_price("BTC", "USD", mar, 40000.0, &AssetType::Crypto);
+
+        let real = tracker
+            .generate_portfolio_chart_real(mar, mar, "XX", Some(jan))
+            .await
+            .unwrap();
+        // March money expressed in (cheaper) January money
+        assert!((real[0].portfolio_value - 40000.0 / 1.02).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn real_chart_unknown_country_errors_before_fetching() {
+        let mut tracker = tracker();
+        let result = tracker
+            .generate_portfolio_chart_real(d(2024, 1, 15), d(2024, 1, 20), "ZZ", None)
+            .await;
+        assert!(matches!(result, Err(CoreError::InflationDataUnavailable { .. })));
+    }
+
+    #[test]
+    fn cpi_override_is_validated() {
+        let mut tracker = SavingsTracker::create_new();
+        assert!(matches!(
+            tracker.set_cpi_override("US", 2025, 13, 320.0),
+            Err(CoreError::ValidationError(_))
+        ));
+    }
+}
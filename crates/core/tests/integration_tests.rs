@@ -1,4 +1,3 @@
-use async_trait::async_trait;
 use chrono::NaiveDate;
 use savings_tracker_core::errors::CoreError;
 use savings_tracker_core::models::asset::{Asset, AssetType};
@@ -8,119 +7,9 @@ use savings_tracker_core::models::price::{PriceCache, PricePoint};
 use savings_tracker_core::providers::traits::PriceProvider;
 use savings_tracker_core::services::portfolio_service::PortfolioService;
 use savings_tracker_core::storage::manager::StorageManager;
-
-// ═══════════════════════════════════════════════════════════════════
-// Mock Price Provider (for testing without real API calls)
-// ═══════════════════════════════════════════════════════════════════
-
-#[allow(dead_code)]
-struct MockPriceProvider {
-    prices: std::collections::HashMap<(String, String, String), f64>,
-}
-
-impl MockPriceProvider {
-    #[allow(dead_code)]
-    fn new() -> Self {
-        let mut prices = std::collections::HashMap::new();
-        // BTC prices in USD
-        prices.insert(
-            ("BTC".into(), "USD".into(), "2025-01-15".into()),
-            42000.0,
-        );
-        prices.insert(
-            ("BTC".into(), "USD".into(), "2025-01-16".into()),
-            43500.0,
-        );
-        prices.insert(
-            ("BTC".into(), "USD".into(), "2025-01-17".into()),
-            41000.0,
-        );
-        // ETH prices in USD
-        prices.insert(
-            ("ETH".into(), "USD".into(), "2025-01-15".into()),
-            2500.0,
-        );
-        // USD/PLN rates
-        prices.insert(
-            ("USD".into(), "PLN".into(), "2025-01-15".into()),
-            4.05,
-        );
-
-        Self { prices }
-    }
-}
-
-#[async_trait]
-impl PriceProvider for MockPriceProvider {
-    fn name(&self) -> &str {
-        "MockProvider"
-    }
-
-    fn supported_asset_types(&self) -> Vec<AssetType> {
-        vec![
-            AssetType::Crypto,
-            AssetType::Fiat,
-            AssetType::Metal,
-            AssetType::Stock,
-        ]
-    }
-
-    async fn get_current_price(&self, symbol: &str, currency: &str) -> Result<f64, CoreError> {
-        // Return any matching price
-        for ((s, c, _), price) in &self.prices {
-            if s == symbol && c == currency {
-                return Ok(*price);
-            }
-        }
-        Err(CoreError::PriceNotAvailable {
-            symbol: symbol.into(),
-            currency: currency.into(),
-            date: "current".into(),
-        })
-    }
-
-    async fn get_historical_price(
-        &self,
-        symbol: &str,
-        currency: &str,
-        date: NaiveDate,
-    ) -> Result<f64, CoreError> {
-        let key = (
-            symbol.to_string(),
-            currency.to_string(),
-            date.format("%Y-%m-%d").to_string(),
-        );
-        self.prices.get(&key).copied().ok_or(CoreError::PriceNotAvailable {
-            symbol: symbol.into(),
-            currency: currency.into(),
-            date: date.to_string(),
-        })
-    }
-
-    async fn get_price_range(
-        &self,
-        symbol: &str,
-        currency: &str,
-        from: NaiveDate,
-        to: NaiveDate,
-    ) -> Result<Vec<PricePoint>, CoreError> {
-        let mut points = Vec::new();
-        for ((s, c, d), price) in &self.prices {
-            if s == symbol && c == currency {
-                if let Ok(date) = NaiveDate::parse_from_str(d, "%Y-%m-%d") {
-                    if date >= from && date <= to {
-                        points.push(PricePoint {
-                            date,
-                            price: *price,
-                        });
-                    }
-                }
-            }
-        }
-        points.sort_by_key(|p| p.date);
-        Ok(points)
-    }
-}
+use savings_tracker_core::storage::secret::SecretString;
+use savings_tracker_core::testing::{PortfolioBuilder, StaticPriceProvider};
+use savings_tracker_core::SavingsTracker;
 
 // ═══════════════════════════════════════════════════════════════════
 // Storage Tests — encrypt/decrypt round-trip
@@ -177,12 +66,14 @@ fn test_storage_round_trip_with_price_cache() {
         "USD",
         NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
         42000.0,
+        &AssetType::Crypto,
     );
     portfolio.price_cache.set_price(
         "ETH",
         "USD",
         NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
         2500.0,
+        &AssetType::Crypto,
     );
 
     let password = "cache-test-pass";
@@ -193,7 +84,8 @@ fn test_storage_round_trip_with_price_cache() {
         decrypted.price_cache.get_price(
             "BTC",
             "USD",
-            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
+            &AssetType::Crypto,
         ),
         Some(42000.0)
     );
@@ -201,7 +93,8 @@ fn test_storage_round_trip_with_price_cache() {
         decrypted.price_cache.get_price(
             "ETH",
             "USD",
-            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
+            &AssetType::Crypto,
         ),
         Some(2500.0)
     );
@@ -246,6 +139,36 @@ fn test_storage_invalid_magic() {
     }
 }
 
+#[test]
+fn test_content_hash_tracks_the_saved_file() {
+    let mut tracker = SavingsTracker::create_new();
+    tracker
+        .add_event(
+            EventType::Buy,
+            Asset::crypto("BTC", "Bitcoin"),
+            0.25,
+            NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
+        )
+        .unwrap();
+    let saved = tracker.save_to_bytes("pw").unwrap();
+    let hash = tracker.content_hash();
+    assert_eq!(StorageManager::file_content_hash(&saved, "pw").unwrap(), hash);
+
+    // Loading gives the same content back
+    let loaded = SavingsTracker::load_from_bytes(&saved, "pw").unwrap();
+    assert_eq!(loaded.content_hash(), hash);
+
+    tracker
+        .add_event(
+            EventType::Sell,
+            Asset::crypto("BTC", "Bitcoin"),
+            0.1,
+            NaiveDate::from_ymd_opt(2025, 2, 10).unwrap(),
+        )
+        .unwrap();
+    assert_ne!(tracker.content_hash(), hash);
+}
+
 #[test]
 fn test_storage_file_too_small() {
     let data = b"SVT"; // less than 4 magic bytes
@@ -268,7 +191,13 @@ fn test_storage_file_round_trip() {
     ));
     portfolio
         .price_cache
-        .set_price("BTC", "USD", NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(), 50000.0);
+        .set_price(
+            "BTC",
+            "USD",
+            NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+            50000.0,
+            &AssetType::Crypto,
+        );
 
     let dir = tempfile::tempdir().unwrap();
     let path = dir.path().join("test.svtk");
@@ -282,11 +211,192 @@ fn test_storage_file_round_trip() {
     assert_eq!(loaded.events[0].asset.symbol, "BTC");
     assert_eq!(loaded.events[0].amount, 1.5);
     assert_eq!(
-        loaded.price_cache.get_price("BTC", "USD", NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()),
+        loaded.price_cache.get_price(
+            "BTC",
+            "USD",
+            NaiveDate::from_ymd_opt(2025, 6, 1).unwrap(),
+            &AssetType::Crypto,
+        ),
         Some(50000.0)
     );
 }
 
+// ═══════════════════════════════════════════════════════════════════
+// Autosave Tests (native only)
+// ═══════════════════════════════════════════════════════════════════
+
+fn add_btc_buy(tracker: &mut SavingsTracker, day: u32) {
+    let date = NaiveDate::from_ymd_opt(2025, 1, day).unwrap();
+    tracker.add_event(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, date).unwrap();
+}
+
+/// Autosave to `path` with a one-second window, returning a receiver
+/// notified after each autosave.
+fn autosave_with_hook(tracker: &mut SavingsTracker, path: &str) -> std::sync::mpsc::Receiver<()> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    tracker.set_autosave_hook(move || {
+        let _ = sender.send(());
+    });
+    tracker.set_autosave(path, SecretString::from("auto-pass"), 1);
+    receiver
+}
+
+fn wait_for_autosave(autosaved: &std::sync::mpsc::Receiver<()>) {
+    autosaved
+        .recv_timeout(std::time::Duration::from_secs(120))
+        .expect("autosave never ran");
+}
+
+#[test]
+fn test_autosave_saves_a_burst_of_edits_after_the_window() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("auto.svtk");
+    let path_str = path.to_str().unwrap();
+
+    let mut tracker = SavingsTracker::create_new();
+    let autosaved = autosave_with_hook(&mut tracker, path_str);
+    assert!(tracker.is_autosave_enabled());
+    for day in 1..=3 {
+        add_btc_buy(&mut tracker, day);
+    }
+    assert!(tracker.has_unsaved_changes());
+
+    wait_for_autosave(&autosaved);
+    assert!(!tracker.has_unsaved_changes());
+    let loaded = StorageManager::load_from_file(path_str, "auto-pass").unwrap();
+    assert_eq!(loaded.events.len(), 3);
+    assert!(tracker.last_autosave_time().is_some());
+    // No temporary file is left behind
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // Another edit is unsaved again until the next autosave
+    add_btc_buy(&mut tracker, 4);
+    assert!(tracker.has_unsaved_changes());
+}
+
+#[test]
+fn test_disable_autosave_writes_pending_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("auto.svtk");
+    let path_str = path.to_str().unwrap();
+
+    let mut tracker = SavingsTracker::create_new();
+    tracker.set_autosave(path_str, SecretString::from("auto-pass"), 3600);
+    add_btc_buy(&mut tracker, 1);
+    tracker.disable_autosave();
+
+    assert!(!tracker.is_autosave_enabled());
+    assert!(tracker.last_autosave_time().is_some());
+    assert!(!tracker.has_unsaved_changes());
+    let loaded = StorageManager::load_from_file(path_str, "auto-pass").unwrap();
+    assert_eq!(loaded.events.len(), 1);
+
+    // Off: edits stay unsaved
+    add_btc_buy(&mut tracker, 2);
+    drop(tracker);
+    let loaded = StorageManager::load_from_file(path_str, "auto-pass").unwrap();
+    assert_eq!(loaded.events.len(), 1);
+}
+
+#[test]
+fn test_autosave_saves_an_edit_followed_by_a_price_lookup() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("auto.svtk");
+    let path_str = path.to_str().unwrap();
+
+    let mut tracker = SavingsTracker::create_new();
+    let autosaved = autosave_with_hook(&mut tracker, path_str);
+    add_btc_buy(&mut tracker, 1);
+    // Writes the price cache, which marks no unsaved changes
+    tracker.get_portfolio_value_cached(NaiveDate::from_ymd_opt(2025, 1, 2).unwrap());
+
+    wait_for_autosave(&autosaved);
+    let loaded = StorageManager::load_from_file(path_str, "auto-pass").unwrap();
+    assert_eq!(loaded.events.len(), 1);
+}
+
+#[tokio::test]
+async fn test_autosave_saves_an_edit_followed_by_an_alert_check() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("auto.svtk");
+    let path_str = path.to_str().unwrap();
+
+    let mut tracker = SavingsTracker::create_new();
+    let autosaved = autosave_with_hook(&mut tracker, path_str);
+    add_btc_buy(&mut tracker, 1);
+    // Within the window; writes the alerts without marking unsaved changes
+    assert!(tracker.check_alerts().await.unwrap().is_empty());
+
+    wait_for_autosave(&autosaved);
+    let loaded = StorageManager::load_from_file(path_str, "auto-pass").unwrap();
+    assert_eq!(loaded.events.len(), 1);
+}
+
+#[test]
+fn test_autosave_saves_an_edit_followed_by_a_failed_edit() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("auto.svtk");
+    let path_str = path.to_str().unwrap();
+
+    let mut tracker = SavingsTracker::create_new();
+    let autosaved = autosave_with_hook(&mut tracker, path_str);
+    add_btc_buy(&mut tracker, 1);
+    // Takes the portfolio back from autosave, then fails without marking
+    // unsaved changes; the failed edit lends it again
+    let date = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+    let oversell = tracker.add_event(EventType::Sell, Asset::crypto("BTC", "Bitcoin"), 5.0, date);
+    assert!(oversell.is_err());
+
+    wait_for_autosave(&autosaved);
+    let loaded = StorageManager::load_from_file(path_str, "auto-pass").unwrap();
+    assert_eq!(loaded.events.len(), 1);
+}
+
+#[tokio::test]
+async fn test_disable_autosave_writes_an_edit_followed_by_other_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("auto.svtk");
+    let path_str = path.to_str().unwrap();
+
+    let mut tracker = SavingsTracker::create_new();
+    tracker.set_autosave(path_str, SecretString::from("auto-pass"), 3600);
+    add_btc_buy(&mut tracker, 1);
+    // Writes the (empty) alerts without marking unsaved changes
+    assert!(tracker.check_alerts().await.unwrap().is_empty());
+    tracker.disable_autosave();
+
+    let loaded = StorageManager::load_from_file(path_str, "auto-pass").unwrap();
+    assert_eq!(loaded.events.len(), 1);
+}
+
+#[test]
+fn test_failed_autosave_is_reported_as_a_warning() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing-dir").join("auto.svtk");
+
+    let mut tracker = SavingsTracker::create_new();
+    add_btc_buy(&mut tracker, 1);
+    // Unsaved changes are saved without waiting for another edit
+    tracker.set_autosave(path.to_str().unwrap(), SecretString::from("auto-pass"), 3600);
+    tracker.disable_autosave();
+
+    let warnings = tracker.get_autosave_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(warnings[0].error, CoreError::FileIO(_)));
+    assert!(tracker.last_autosave_time().is_none());
+    assert!(tracker.has_unsaved_changes());
+
+    tracker.clear_autosave_warnings();
+    assert!(tracker.get_autosave_warnings().is_empty());
+}
+
+#[test]
+fn test_secret_string_is_not_shown() {
+    let secret = SecretString::from("hunter2");
+    assert_eq!(secret.expose(), "hunter2");
+    assert!(!format!("{secret:?}").contains("hunter2"));
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // Portfolio Service Tests
 // ═══════════════════════════════════════════════════════════════════
@@ -555,9 +665,12 @@ fn test_price_cache_set_and_get() {
     let mut cache = PriceCache::new();
     let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
 
-    cache.set_price("BTC", "USD", date, 42000.0);
-    assert_eq!(cache.get_price("BTC", "USD", date), Some(42000.0));
-    assert_eq!(cache.get_price("btc", "usd", date), Some(42000.0)); // case insensitive
+    cache.set_price("BTC", "USD", date, 42000.0, &AssetType::Crypto);
+    assert_eq!(cache.get_price("BTC", "USD", date, &AssetType::Crypto), Some(42000.0));
+    assert_eq!(
+        cache.get_price("btc", "usd", date, &AssetType::Crypto),
+        Some(42000.0),
+    ); // case insensitive
 }
 
 #[test]
@@ -565,7 +678,7 @@ fn test_price_cache_miss() {
     let cache = PriceCache::new();
     let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
 
-    assert_eq!(cache.get_price("BTC", "USD", date), None);
+    assert_eq!(cache.get_price("BTC", "USD", date, &AssetType::Crypto), None);
 }
 
 #[test]
@@ -573,10 +686,10 @@ fn test_price_cache_update_existing() {
     let mut cache = PriceCache::new();
     let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
 
-    cache.set_price("BTC", "USD", date, 42000.0);
-    cache.set_price("BTC", "USD", date, 43000.0); // update
+    cache.set_price("BTC", "USD", date, 42000.0, &AssetType::Crypto);
+    cache.set_price("BTC", "USD", date, 43000.0, &AssetType::Crypto); // update
 
-    assert_eq!(cache.get_price("BTC", "USD", date), Some(43000.0));
+    assert_eq!(cache.get_price("BTC", "USD", date, &AssetType::Crypto), Some(43000.0));
 }
 
 #[test]
@@ -584,13 +697,13 @@ fn test_price_cache_today_freshness() {
     let mut cache = PriceCache::new();
     let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
 
-    assert!(!cache.is_today_fresh("BTC", "USD", today));
-    cache.mark_updated_today("BTC", "USD", today);
-    assert!(cache.is_today_fresh("BTC", "USD", today));
+    assert!(!cache.is_today_fresh("BTC", "USD", today, &AssetType::Crypto));
+    cache.mark_updated_today("BTC", "USD", today, &AssetType::Crypto);
+    assert!(cache.is_today_fresh("BTC", "USD", today, &AssetType::Crypto));
 
     // Different day should not be fresh
     let tomorrow = NaiveDate::from_ymd_opt(2025, 1, 16).unwrap();
-    assert!(!cache.is_today_fresh("BTC", "USD", tomorrow));
+    assert!(!cache.is_today_fresh("BTC", "USD", tomorrow, &AssetType::Crypto));
 }
 
 #[test]
@@ -600,11 +713,11 @@ fn test_price_cache_range() {
     let d2 = NaiveDate::from_ymd_opt(2025, 1, 16).unwrap();
     let d3 = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
 
-    cache.set_price("BTC", "USD", d1, 42000.0);
-    cache.set_price("BTC", "USD", d2, 43000.0);
-    cache.set_price("BTC", "USD", d3, 41000.0);
+    cache.set_price("BTC", "USD", d1, 42000.0, &AssetType::Crypto);
+    cache.set_price("BTC", "USD", d2, 43000.0, &AssetType::Crypto);
+    cache.set_price("BTC", "USD", d3, 41000.0, &AssetType::Crypto);
 
-    let range = cache.get_price_range("BTC", "USD", d1, d3);
+    let range = cache.get_price_range("BTC", "USD", d1, d3, &AssetType::Crypto);
     assert_eq!(range.len(), 3);
     assert_eq!(range[0].price, 42000.0);
     assert_eq!(range[1].price, 43000.0);
@@ -625,14 +738,24 @@ fn test_price_cache_set_prices_bulk() {
         },
     ];
 
-    cache.set_prices("BTC", "USD", &points);
+    cache.set_prices("BTC", "USD", &points, &AssetType::Crypto);
 
     assert_eq!(
-        cache.get_price("BTC", "USD", NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()),
+        cache.get_price(
+            "BTC",
+            "USD",
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
+            &AssetType::Crypto,
+        ),
         Some(42000.0)
     );
     assert_eq!(
-        cache.get_price("BTC", "USD", NaiveDate::from_ymd_opt(2025, 1, 16).unwrap()),
+        cache.get_price(
+            "BTC",
+            "USD",
+            NaiveDate::from_ymd_opt(2025, 1, 16).unwrap(),
+            &AssetType::Crypto,
+        ),
         Some(43000.0)
     );
 }
@@ -817,46 +940,34 @@ fn test_file_format_magic_validation() {
 #[test]
 fn test_full_flow_create_save_load() {
     let service = PortfolioService::new();
-    let mut portfolio = Portfolio::default();
-    portfolio.settings.default_currency = "PLN".to_string();
-
-    // Add events
-    service
-        .add_event(
-            &mut portfolio,
-            Event::new(
-                EventType::Buy,
-                Asset::crypto("BTC", "Bitcoin"),
-                0.1,
-                NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
-            ),
+    let portfolio = PortfolioBuilder::new()
+        .with_default_currency("PLN")
+        .with_buy(
+            Asset::crypto("BTC", "Bitcoin"),
+            0.1,
+            NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
         )
-        .unwrap();
-    service
-        .add_event(
-            &mut portfolio,
-            Event::new(
-                EventType::Buy,
-                Asset::fiat("USD", "US Dollar"),
-                5000.0,
-                NaiveDate::from_ymd_opt(2025, 1, 12).unwrap(),
-            ),
+        .with_buy(
+            Asset::fiat("USD", "US Dollar"),
+            5000.0,
+            NaiveDate::from_ymd_opt(2025, 1, 12).unwrap(),
         )
-        .unwrap();
-
-    // Populate price cache (simulating what would happen after API calls)
-    portfolio.price_cache.set_price(
-        "BTC",
-        "USD",
-        NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
-        42000.0,
-    );
-    portfolio.price_cache.set_price(
-        "USD",
-        "PLN",
-        NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
-        4.05,
-    );
+        // Populate price cache (simulating what would happen after API calls)
+        .with_cached_price(
+            "BTC",
+            "USD",
+            NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
+            42000.0,
+            &AssetType::Crypto,
+        )
+        .with_cached_price(
+            "USD",
+            "PLN",
+            NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
+            4.05,
+            &AssetType::Fiat,
+        )
+        .build();
 
     // Save and reload
     let password = "integration-test!";
@@ -871,13 +982,23 @@ fn test_full_flow_create_save_load() {
     assert_eq!(
         loaded
             .price_cache
-            .get_price("BTC", "USD", NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()),
+            .get_price(
+                "BTC",
+                "USD",
+                NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
+                &AssetType::Crypto,
+            ),
         Some(42000.0)
     );
     assert_eq!(
         loaded
             .price_cache
-            .get_price("USD", "PLN", NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()),
+            .get_price(
+                "USD",
+                "PLN",
+                NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
+                &AssetType::Fiat,
+            ),
         Some(4.05)
     );
 
@@ -888,3 +1009,128 @@ fn test_full_flow_create_save_load() {
     );
     assert_eq!(holdings.len(), 2);
 }
+
+// ═══════════════════════════════════════════════════════════════════
+// Full Integration Test (no network — injected static provider)
+// ═══════════════════════════════════════════════════════════════════
+
+fn static_provider() -> StaticPriceProvider {
+    let d = |day| NaiveDate::from_ymd_opt(2025, 1, day).unwrap();
+    StaticPriceProvider::new()
+        .with_price("BTC", "USD", d(15), 42000.0)
+        .with_price("BTC", "USD", d(16), 43500.0)
+        .with_price("ETH", "USD", d(15), 2500.0)
+        .with_price("USD", "PLN", d(15), 4.05)
+        .with_price("USD", "PLN", d(16), 4.10)
+}
+
+#[tokio::test]
+async fn test_full_flow_tracker_with_static_provider() {
+    let d = |day| NaiveDate::from_ymd_opt(2025, 1, day).unwrap();
+    let mut tracker = PortfolioBuilder::new()
+        .with_default_currency("PLN")
+        .with_buy(Asset::crypto("BTC", "Bitcoin"), 1.0, d(15))
+        .with_buy(Asset::crypto("ETH", "Ethereum"), 2.0, d(15))
+        .with_sell(Asset::crypto("ETH", "Ethereum"), 2.0, d(16))
+        .build_tracker(static_provider().into_registry());
+
+    // 1 BTC × $42,000 × 4.05 + 2 ETH × $2,500 × 4.05
+    let value = tracker.get_portfolio_value(d(15)).await.unwrap().amount;
+    assert!((value - 190_350.0).abs() < 0.01);
+
+    // ETH sold on the 16th: 1 BTC × $43,500 × 4.10
+    let value = tracker.get_portfolio_value(d(16)).await.unwrap().amount;
+    assert!((value - 178_350.0).abs() < 0.01);
+}
+
+#[tokio::test]
+async fn test_create_with_registry_uses_injected_provider() {
+    let d = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+    let mut tracker = SavingsTracker::create_with_registry(static_provider().into_registry());
+
+    let price = tracker
+        .get_asset_price(&Asset::crypto("BTC", "Bitcoin"), d)
+        .await
+        .unwrap();
+    assert_eq!(price, 42000.0);
+    assert_eq!(tracker.get_provider_names(&AssetType::Crypto), vec!["Static"]);
+}
+
+mod testing_utils {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+    }
+
+    #[tokio::test]
+    async fn static_provider_from_map_is_case_insensitive() {
+        let mut prices = std::collections::HashMap::new();
+        prices.insert(("btc".to_string(), "usd".to_string(), d(15)), 42000.0);
+        let provider = StaticPriceProvider::from_map(prices);
+
+        assert_eq!(provider.get_historical_price("BTC", "USD", d(15)).await.unwrap(), 42000.0);
+    }
+
+    #[tokio::test]
+    async fn static_provider_missing_price_errors() {
+        let provider = StaticPriceProvider::new();
+        let result = provider.get_historical_price("BTC", "USD", d(15)).await;
+        assert!(matches!(result, Err(CoreError::PriceNotAvailable { .. })));
+    }
+
+    #[tokio::test]
+    async fn static_provider_current_falls_back_to_latest() {
+        let provider = static_provider();
+        assert_eq!(provider.get_current_price("BTC", "USD").await.unwrap(), 43500.0);
+    }
+
+    #[tokio::test]
+    async fn static_provider_from_fn_range() {
+        let provider = StaticPriceProvider::from_fn(|symbol, _currency, date| {
+            (symbol == "BTC").then(|| f64::from(chrono::Datelike::day(&date)))
+        });
+
+        let points = provider.get_price_range("BTC", "USD", d(1), d(3)).await.unwrap();
+        assert_eq!(
+            points,
+            vec![
+                PricePoint { date: d(1), price: 1.0 },
+                PricePoint { date: d(2), price: 2.0 },
+                PricePoint { date: d(3), price: 3.0 },
+            ]
+        );
+        assert!(provider.get_price_range("ETH", "USD", d(1), d(3)).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn static_provider_name_and_types() {
+        let provider = StaticPriceProvider::new()
+            .with_name("Broker")
+            .with_asset_types(vec![AssetType::Stock]);
+        assert_eq!(provider.name(), "Broker");
+        assert_eq!(provider.supported_asset_types(), vec![AssetType::Stock]);
+    }
+
+    #[test]
+    fn builder_orders_events_by_date() {
+        let btc = Asset::crypto("BTC", "Bitcoin");
+        // Sell listed before the buy it depends on
+        let portfolio = PortfolioBuilder::new()
+            .with_sell(btc.clone(), 0.5, d(20))
+            .with_buy(btc.clone(), 1.0, d(10))
+            .build();
+
+        assert_eq!(portfolio.events.len(), 2);
+        assert_eq!(portfolio.events[0].event_type, EventType::Buy);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid fixture event")]
+    fn builder_panics_on_invalid_sell() {
+        PortfolioBuilder::new()
+            .with_sell(Asset::crypto("BTC", "Bitcoin"), 1.0, d(10))
+            .build();
+    }
+}
+
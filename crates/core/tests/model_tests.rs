@@ -1,11 +1,15 @@
 use chrono::NaiveDate;
-use savings_tracker_core::models::asset::{Asset, AssetType};
-use savings_tracker_core::models::chart::{ChartDataPoint, ChartEvent};
+use savings_tracker_core::models::alert::{Alert, AlertCondition};
+use savings_tracker_core::models::asset::{Asset, AssetMetadata, AssetType, MetalUnit};
+use savings_tracker_core::models::chart::{ChartDataPoint, ChartEvent, ChartResolution};
 use savings_tracker_core::models::event::{Event, EventType};
 use savings_tracker_core::models::portfolio::Portfolio;
-use savings_tracker_core::models::price::{PriceCache, PricePoint};
-use savings_tracker_core::models::settings::Settings;
+use savings_tracker_core::models::price::{PriceCache, PricePoint, PriceSource};
+use savings_tracker_core::models::settings::{
+    Settings, TrashPolicy, ValidationPolicy, WeekStart,
+};
 use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 fn d(y: i32, m: u32, day: u32) -> NaiveDate {
     NaiveDate::from_ymd_opt(y, m, day).unwrap()
@@ -242,6 +246,26 @@ mod asset {
         let a = Asset::fiat("PLN", "Złoty");
         assert_eq!(a.name, "Złoty");
     }
+
+    // ── Metadata ──────────────────────────────────────────────────
+
+    #[test]
+    fn metadata_defaults_missing_fields() {
+        let metadata: AssetMetadata = serde_json::from_str(r#"{"annual_yield_pct":2.0}"#).unwrap();
+        assert_eq!(metadata.annual_yield_pct, Some(2.0));
+        assert_eq!((metadata.notes, metadata.color_hex, metadata.icon), (None, None, None));
+        assert!(metadata.custom_fields.is_empty());
+    }
+
+    #[test]
+    fn metadata_is_empty_checks_every_field() {
+        assert!(AssetMetadata::default().is_empty());
+        let mut metadata = AssetMetadata::default();
+        metadata.custom_fields.insert("wallet".into(), "ledger".into());
+        assert!(!metadata.is_empty());
+        let metadata = AssetMetadata { icon: Some("coin".into()), ..Default::default() };
+        assert!(!metadata.is_empty());
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -296,6 +320,64 @@ mod event {
         NaiveDate::from_ymd_opt(2025, 6, 15).unwrap()
     }
 
+    #[test]
+    fn new_has_no_recorded_value() {
+        let e = Event::new(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, sample_date());
+        assert!(e.recorded_value.is_none());
+    }
+
+    #[test]
+    fn missing_recorded_value_defaults_in_json() {
+        let e = Event::new(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, sample_date());
+        let mut json: serde_json::Value = serde_json::to_value(&e).unwrap();
+        json.as_object_mut().unwrap().remove("recorded_value");
+        let back: Event = serde_json::from_value(json).unwrap();
+        assert!(back.recorded_value.is_none());
+    }
+
+    #[test]
+    fn planned_constructor_sets_flag() {
+        let e = Event::planned(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, sample_date());
+        assert!(e.is_planned);
+        let actual = Event::new(EventType::Buy, Asset::crypto("BTC", "B"), 1.0, sample_date());
+        assert!(!actual.is_planned);
+    }
+
+    #[test]
+    fn missing_is_planned_defaults_to_actual_in_json() {
+        let e = Event::planned(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, sample_date());
+        let mut json: serde_json::Value = serde_json::to_value(&e).unwrap();
+        json.as_object_mut().unwrap().remove("is_planned");
+        let back: Event = serde_json::from_value(json).unwrap();
+        assert!(!back.is_planned);
+    }
+
+    #[test]
+    fn filter_matches_every_set_criterion() {
+        use savings_tracker_core::models::event::EventFilter;
+        let btc = Asset::crypto("BTC", "Bitcoin");
+        let buy = Event::new(EventType::Buy, btc.clone(), 1.0, sample_date());
+        let planned = Event::planned(EventType::Buy, btc, 1.0, sample_date());
+
+        assert!(EventFilter::default().matches(&buy));
+        assert!(!EventFilter::default().matches(&planned));
+        assert!(EventFilter::for_symbol("btc").matches(&buy));
+        assert!(!EventFilter::for_symbol("ETH").matches(&buy));
+        let sells_in_june = EventFilter {
+            event_type: Some(EventType::Sell),
+            from: Some(NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()),
+            ..Default::default()
+        };
+        assert!(!sells_in_june.matches(&buy));
+        let with_planned = EventFilter {
+            asset_type: Some(AssetType::Crypto),
+            to: Some(sample_date()),
+            include_planned: true,
+            ..Default::default()
+        };
+        assert!(with_planned.matches(&planned));
+    }
+
     #[test]
     fn new_generates_unique_ids() {
         let e1 = Event::new(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, sample_date());
@@ -393,6 +475,42 @@ mod event {
         let e = Event::new(EventType::Buy, Asset::fiat("USD", "Dollar"), 1_000_000_000.0, sample_date());
         assert_eq!(e.amount, 1_000_000_000.0);
     }
+
+    #[test]
+    fn validate_rejects_non_finite_and_non_positive_amounts() {
+        let btc = Asset::crypto("BTC", "Bitcoin");
+        assert!(Event::new(EventType::Buy, btc.clone(), 0.5, sample_date()).validate().is_ok());
+        for amount in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 0.0, -1.0] {
+            let e = Event::new(EventType::Sell, btc.clone(), amount, sample_date());
+            assert!(e.validate().is_err(), "{amount} accepted");
+        }
+    }
+
+    #[test]
+    fn split_keeps_everything_but_id_and_amount() {
+        let mut e = Event::new(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, sample_date());
+        e.notes = Some("cold wallet".into());
+        let parts = e.split(&[0.4, 0.6]).unwrap();
+
+        let amounts: Vec<f64> = parts.iter().map(|p| p.amount).collect();
+        assert_eq!(amounts, vec![0.4, 0.6]);
+        assert!(parts.iter().all(|p| p.id != e.id && p.date == e.date));
+        assert!(parts.iter().all(|p| p.notes.as_deref() == Some("cold wallet")));
+        assert_ne!(parts[0].id, parts[1].id);
+    }
+
+    #[test]
+    fn split_parts_must_add_up() {
+        let e = Event::new(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 0.3, sample_date());
+        // 0.1 + 0.2 is not exactly 0.3: the last part takes the difference
+        let parts = e.split(&[0.1, 0.2]).unwrap();
+        assert_eq!(parts[0].amount + parts[1].amount, 0.3);
+
+        assert!(e.split(&[0.1, 0.1]).is_err());
+        assert!(e.split(&[0.3]).is_err());
+        assert!(e.split(&[0.4, -0.1]).is_err());
+        assert!(e.split(&[0.3, f64::NAN]).is_err());
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -466,61 +584,137 @@ mod price_cache {
     #[test]
     fn set_and_get_price() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), Some(42000.0));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
     }
 
     #[test]
     fn get_nonexistent_symbol() {
         let cache = PriceCache::new();
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), None);
+        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto), None);
     }
 
     #[test]
     fn get_nonexistent_date() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 16)), None);
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 16), &AssetType::Crypto), None);
     }
 
     #[test]
     fn get_wrong_currency() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        assert_eq!(cache.get_price("BTC", "EUR", d(2025, 1, 15)), None);
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        assert_eq!(cache.get_price("BTC", "EUR", d(2025, 1, 15), &AssetType::Crypto), None);
     }
 
     #[test]
     fn case_insensitive_get() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        assert_eq!(cache.get_price("btc", "usd", d(2025, 1, 15)), Some(42000.0));
-        assert_eq!(cache.get_price("Btc", "Usd", d(2025, 1, 15)), Some(42000.0));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        assert_eq!(
+            cache.get_price("btc", "usd", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
+        assert_eq!(
+            cache.get_price("Btc", "Usd", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
     }
 
     #[test]
     fn case_insensitive_set_stores_uppercase() {
         let mut cache = PriceCache::new();
-        cache.set_price("btc", "usd", d(2025, 1, 15), 42000.0);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), Some(42000.0));
+        cache.set_price("btc", "usd", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
     }
 
     #[test]
     fn update_existing_price() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 43000.0);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), Some(43000.0));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 43000.0, &AssetType::Crypto);
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(43000.0),
+        );
+    }
+
+    #[test]
+    fn same_symbol_of_different_asset_types_kept_apart() {
+        let mut cache = PriceCache::new();
+        cache.set_price("GLD", "USD", d(2025, 1, 15), 190.0, &AssetType::Stock);
+        cache.set_price("GLD", "USD", d(2025, 1, 15), 0.02, &AssetType::Crypto);
+        assert_eq!(cache.get_price("GLD", "USD", d(2025, 1, 15), &AssetType::Stock), Some(190.0));
+        assert_eq!(cache.get_price("GLD", "USD", d(2025, 1, 15), &AssetType::Crypto), Some(0.02));
+        assert_eq!(cache.get_price("GLD", "USD", d(2025, 1, 15), &AssetType::Metal), None);
+        assert_eq!(cache.asset_count(), 2);
+    }
+
+    #[test]
+    fn sources_follow_their_price() {
+        let mut cache = PriceCache::new();
+        let crypto = &AssetType::Crypto;
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, crypto);
+        cache.set_source("BTC", "USD", d(2025, 1, 15), PriceSource::now("CoinGecko"), crypto);
+        let entry = cache.get_entry("btc", "usd", d(2025, 1, 15), crypto).unwrap();
+        assert_eq!(entry.source.as_deref(), Some("CoinGecko"));
+
+        // A replaced price loses the source of the old one
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 43000.0, crypto);
+        let entry = cache.get_entry("BTC", "USD", d(2025, 1, 15), crypto).unwrap();
+        assert_eq!((entry.price, entry.source, entry.fetched_at), (43000.0, None, None));
+
+        cache.set_source("BTC", "USD", d(2025, 1, 15), PriceSource::now("CoinCap"), crypto);
+        cache.prune_before(d(2025, 2, 1));
+        assert!(cache.sources.is_empty());
+    }
+
+    #[test]
+    fn nearest_price_within_distance() {
+        let mut cache = PriceCache::new();
+        let crypto = &AssetType::Crypto;
+        cache.set_price("BTC", "USD", d(2025, 1, 10), 40000.0, crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 14), 44000.0, crypto);
+        let nearest = |date, days| {
+            cache.get_nearest_price("BTC", "USD", date, days, crypto).map(|p| p.price)
+        };
+        assert_eq!(nearest(d(2025, 1, 10), 0), Some(40000.0));
+        assert_eq!(nearest(d(2025, 1, 13), 1), Some(44000.0));
+        // Ties go to the earlier price
+        assert_eq!(nearest(d(2025, 1, 12), 2), Some(40000.0));
+        assert_eq!(nearest(d(2025, 1, 12), 1), None);
+        assert_eq!(nearest(d(2025, 1, 20), 7), Some(44000.0));
+        assert_eq!(nearest(d(2025, 1, 1), 7), None);
+    }
+
+    #[test]
+    fn latest_price_on_or_before() {
+        let mut cache = PriceCache::new();
+        let crypto = &AssetType::Crypto;
+        cache.set_price("BTC", "USD", d(2024, 1, 10), 40000.0, crypto);
+        cache.set_price("BTC", "USD", d(2024, 3, 1), 60000.0, crypto);
+        let latest = |date| cache.get_latest_price("BTC", "USD", date, crypto).map(|p| p.date);
+        assert_eq!(latest(d(2024, 3, 1)), Some(d(2024, 3, 1)));
+        assert_eq!(latest(d(2024, 2, 29)), Some(d(2024, 1, 10)));
+        assert_eq!(latest(d(2030, 1, 1)), Some(d(2024, 3, 1)));
+        assert_eq!(latest(d(2024, 1, 9)), None);
     }
 
     #[test]
     fn maintains_sort_order() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 17), 41000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 16), 43000.0);
+        cache.set_price("BTC", "USD", d(2025, 1, 17), 41000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 16), 43000.0, &AssetType::Crypto);
 
-        let key = ("BTC".to_string(), "USD".to_string());
+        let key = ("BTC".to_string(), "USD".to_string(), AssetType::Crypto);
         let entries = cache.entries.get(&key).unwrap();
         assert_eq!(entries[0].date, d(2025, 1, 15));
         assert_eq!(entries[1].date, d(2025, 1, 16));
@@ -530,19 +724,28 @@ mod price_cache {
     #[test]
     fn multiple_symbols() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        cache.set_price("ETH", "USD", d(2025, 1, 15), 2500.0);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), Some(42000.0));
-        assert_eq!(cache.get_price("ETH", "USD", d(2025, 1, 15)), Some(2500.0));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.set_price("ETH", "USD", d(2025, 1, 15), 2500.0, &AssetType::Crypto);
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
+        assert_eq!(cache.get_price("ETH", "USD", d(2025, 1, 15), &AssetType::Crypto), Some(2500.0));
     }
 
     #[test]
     fn multiple_currencies() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        cache.set_price("BTC", "EUR", d(2025, 1, 15), 38000.0);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), Some(42000.0));
-        assert_eq!(cache.get_price("BTC", "EUR", d(2025, 1, 15)), Some(38000.0));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "EUR", d(2025, 1, 15), 38000.0, &AssetType::Crypto);
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
+        assert_eq!(
+            cache.get_price("BTC", "EUR", d(2025, 1, 15), &AssetType::Crypto),
+            Some(38000.0),
+        );
     }
 
     // ── set_prices (bulk) ─────────────────────────────────────────
@@ -555,26 +758,38 @@ mod price_cache {
             PricePoint { date: d(2025, 1, 16), price: 43000.0 },
             PricePoint { date: d(2025, 1, 17), price: 41000.0 },
         ];
-        cache.set_prices("BTC", "USD", &points);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), Some(42000.0));
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 16)), Some(43000.0));
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 17)), Some(41000.0));
+        cache.set_prices("BTC", "USD", &points, &AssetType::Crypto);
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 16), &AssetType::Crypto),
+            Some(43000.0),
+        );
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 17), &AssetType::Crypto),
+            Some(41000.0),
+        );
     }
 
     #[test]
     fn set_prices_empty_slice() {
         let mut cache = PriceCache::new();
-        cache.set_prices("BTC", "USD", &[]);
+        cache.set_prices("BTC", "USD", &[], &AssetType::Crypto);
         assert!(cache.entries.is_empty());
     }
 
     #[test]
     fn set_prices_overwrites_existing() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
         let points = vec![PricePoint { date: d(2025, 1, 15), price: 99000.0 }];
-        cache.set_prices("BTC", "USD", &points);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), Some(99000.0));
+        cache.set_prices("BTC", "USD", &points, &AssetType::Crypto);
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(99000.0),
+        );
     }
 
     // ── is_today_fresh / mark_updated_today ───────────────────────
@@ -582,45 +797,58 @@ mod price_cache {
     #[test]
     fn is_today_fresh_not_marked() {
         let cache = PriceCache::new();
-        assert!(!cache.is_today_fresh("BTC", "USD", d(2025, 1, 15)));
+        assert!(!cache.is_today_fresh("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto));
     }
 
     #[test]
     fn is_today_fresh_after_marking() {
         let mut cache = PriceCache::new();
         let today = d(2025, 1, 15);
-        cache.mark_updated_today("BTC", "USD", today);
-        assert!(cache.is_today_fresh("BTC", "USD", today));
+        cache.mark_updated_today("BTC", "USD", today, &AssetType::Crypto);
+        assert!(cache.is_today_fresh("BTC", "USD", today, &AssetType::Crypto));
     }
 
     #[test]
     fn is_today_fresh_wrong_day() {
         let mut cache = PriceCache::new();
-        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15));
-        assert!(!cache.is_today_fresh("BTC", "USD", d(2025, 1, 16)));
+        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto);
+        assert!(!cache.is_today_fresh("BTC", "USD", d(2025, 1, 16), &AssetType::Crypto));
     }
 
     #[test]
     fn is_today_fresh_case_insensitive() {
         let mut cache = PriceCache::new();
-        cache.mark_updated_today("btc", "usd", d(2025, 1, 15));
-        assert!(cache.is_today_fresh("BTC", "USD", d(2025, 1, 15)));
+        cache.mark_updated_today("btc", "usd", d(2025, 1, 15), &AssetType::Crypto);
+        assert!(cache.is_today_fresh("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto));
     }
 
     #[test]
     fn is_today_fresh_different_symbol() {
         let mut cache = PriceCache::new();
-        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15));
-        assert!(!cache.is_today_fresh("ETH", "USD", d(2025, 1, 15)));
+        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto);
+        assert!(!cache.is_today_fresh("ETH", "USD", d(2025, 1, 15), &AssetType::Crypto));
     }
 
     #[test]
     fn mark_updated_today_overwrites() {
         let mut cache = PriceCache::new();
-        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15));
-        cache.mark_updated_today("BTC", "USD", d(2025, 1, 16));
-        assert!(!cache.is_today_fresh("BTC", "USD", d(2025, 1, 15)));
-        assert!(cache.is_today_fresh("BTC", "USD", d(2025, 1, 16)));
+        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto);
+        cache.mark_updated_today("BTC", "USD", d(2025, 1, 16), &AssetType::Crypto);
+        assert!(!cache.is_today_fresh("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto));
+        assert!(cache.is_today_fresh("BTC", "USD", d(2025, 1, 16), &AssetType::Crypto));
+    }
+
+    #[test]
+    fn mark_stale_keeps_price() {
+        let mut cache = PriceCache::new();
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto);
+        cache.mark_stale("btc", "usd", &AssetType::Crypto);
+        assert!(!cache.is_today_fresh("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto));
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
     }
 
     // ── get_price_range ───────────────────────────────────────────
@@ -628,10 +856,16 @@ mod price_cache {
     #[test]
     fn get_price_range_normal() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 16), 43000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 17), 41000.0);
-        let range = cache.get_price_range("BTC", "USD", d(2025, 1, 15), d(2025, 1, 17));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 16), 43000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 17), 41000.0, &AssetType::Crypto);
+        let range = cache.get_price_range(
+            "BTC",
+            "USD",
+            d(2025, 1, 15),
+            d(2025, 1, 17),
+            &AssetType::Crypto,
+        );
         assert_eq!(range.len(), 3);
         assert_eq!(range[0].price, 42000.0);
         assert_eq!(range[2].price, 41000.0);
@@ -640,34 +874,58 @@ mod price_cache {
     #[test]
     fn get_price_range_empty_cache() {
         let cache = PriceCache::new();
-        let range = cache.get_price_range("BTC", "USD", d(2025, 1, 15), d(2025, 1, 17));
+        let range = cache.get_price_range(
+            "BTC",
+            "USD",
+            d(2025, 1, 15),
+            d(2025, 1, 17),
+            &AssetType::Crypto,
+        );
         assert!(range.is_empty());
     }
 
     #[test]
     fn get_price_range_no_overlap() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 10), 40000.0);
-        let range = cache.get_price_range("BTC", "USD", d(2025, 1, 15), d(2025, 1, 17));
+        cache.set_price("BTC", "USD", d(2025, 1, 10), 40000.0, &AssetType::Crypto);
+        let range = cache.get_price_range(
+            "BTC",
+            "USD",
+            d(2025, 1, 15),
+            d(2025, 1, 17),
+            &AssetType::Crypto,
+        );
         assert!(range.is_empty());
     }
 
     #[test]
     fn get_price_range_partial_overlap() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 14), 40000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 16), 43000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 18), 44000.0);
-        let range = cache.get_price_range("BTC", "USD", d(2025, 1, 15), d(2025, 1, 17));
+        cache.set_price("BTC", "USD", d(2025, 1, 14), 40000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 16), 43000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 18), 44000.0, &AssetType::Crypto);
+        let range = cache.get_price_range(
+            "BTC",
+            "USD",
+            d(2025, 1, 15),
+            d(2025, 1, 17),
+            &AssetType::Crypto,
+        );
         assert_eq!(range.len(), 2);
     }
 
     #[test]
     fn get_price_range_single_day() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        let range = cache.get_price_range("BTC", "USD", d(2025, 1, 15), d(2025, 1, 15));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        let range = cache.get_price_range(
+            "BTC",
+            "USD",
+            d(2025, 1, 15),
+            d(2025, 1, 15),
+            &AssetType::Crypto,
+        );
         assert_eq!(range.len(), 1);
         assert_eq!(range[0].price, 42000.0);
     }
@@ -675,8 +933,14 @@ mod price_cache {
     #[test]
     fn get_price_range_case_insensitive() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        let range = cache.get_price_range("btc", "usd", d(2025, 1, 15), d(2025, 1, 15));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        let range = cache.get_price_range(
+            "btc",
+            "usd",
+            d(2025, 1, 15),
+            d(2025, 1, 15),
+            &AssetType::Crypto,
+        );
         assert_eq!(range.len(), 1);
     }
 
@@ -685,16 +949,145 @@ mod price_cache {
     #[test]
     fn serde_roundtrip() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        cache.set_price("ETH", "USD", d(2025, 1, 15), 2500.0);
-        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15));
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.set_price("ETH", "USD", d(2025, 1, 15), 2500.0, &AssetType::Crypto);
+        cache.mark_updated_today("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto);
 
         let bytes = bincode::serialize(&cache).unwrap();
         let back: PriceCache = bincode::deserialize(&bytes).unwrap();
 
-        assert_eq!(back.get_price("BTC", "USD", d(2025, 1, 15)), Some(42000.0));
-        assert_eq!(back.get_price("ETH", "USD", d(2025, 1, 15)), Some(2500.0));
-        assert!(back.is_today_fresh("BTC", "USD", d(2025, 1, 15)));
+        assert_eq!(back.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto), Some(42000.0));
+        assert_eq!(back.get_price("ETH", "USD", d(2025, 1, 15), &AssetType::Crypto), Some(2500.0));
+        assert!(back.is_today_fresh("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto));
+    }
+
+    #[test]
+    fn derived_price_is_flagged() {
+        let mut cache = PriceCache::new();
+        cache.set_derived_price("eur", "pln", d(2025, 1, 15), 4.37, &AssetType::Fiat);
+        assert_eq!(cache.get_price("EUR", "PLN", d(2025, 1, 15), &AssetType::Fiat), Some(4.37));
+        assert!(cache.is_derived("EUR", "PLN", d(2025, 1, 15), &AssetType::Fiat));
+        assert!(!cache.is_derived("EUR", "PLN", d(2025, 1, 16), &AssetType::Fiat));
+    }
+
+    #[test]
+    fn direct_price_clears_derived_flag() {
+        let mut cache = PriceCache::new();
+        cache.set_derived_price("EUR", "PLN", d(2025, 1, 15), 4.37, &AssetType::Fiat);
+        cache.set_price("EUR", "PLN", d(2025, 1, 15), 4.35, &AssetType::Fiat);
+        assert_eq!(cache.get_price("EUR", "PLN", d(2025, 1, 15), &AssetType::Fiat), Some(4.35));
+        assert!(!cache.is_derived("EUR", "PLN", d(2025, 1, 15), &AssetType::Fiat));
+    }
+
+    #[test]
+    fn prune_and_clear_drop_derived_flags() {
+        let mut cache = PriceCache::new();
+        cache.set_derived_price("EUR", "PLN", d(2025, 1, 10), 4.30, &AssetType::Fiat);
+        cache.set_derived_price("EUR", "PLN", d(2025, 1, 20), 4.40, &AssetType::Fiat);
+        cache.prune_before(d(2025, 1, 15));
+        assert!(!cache.is_derived("EUR", "PLN", d(2025, 1, 10), &AssetType::Fiat));
+        assert!(cache.is_derived("EUR", "PLN", d(2025, 1, 20), &AssetType::Fiat));
+
+        cache.clear();
+        assert!(cache.derived.is_empty());
+    }
+
+    #[test]
+    fn carried_price_never_replaces_a_quote() {
+        let mut cache = PriceCache::new();
+        cache.set_price("EUR", "PLN", d(2025, 1, 17), 4.27, &AssetType::Fiat);
+        assert!(!cache.set_carried_price("EUR", "PLN", d(2025, 1, 17), 4.20, &AssetType::Fiat));
+        assert_eq!(cache.get_price("EUR", "PLN", d(2025, 1, 17), &AssetType::Fiat), Some(4.27));
+
+        assert!(cache.set_carried_price("eur", "pln", d(2025, 1, 18), 4.27, &AssetType::Fiat));
+        assert!(cache.is_carried("EUR", "PLN", d(2025, 1, 18), &AssetType::Fiat));
+        // Carried values can be re-carried, quotes clear the flag
+        assert!(cache.set_carried_price("EUR", "PLN", d(2025, 1, 18), 4.26, &AssetType::Fiat));
+        cache.set_price("EUR", "PLN", d(2025, 1, 18), 4.25, &AssetType::Fiat);
+        assert!(!cache.is_carried("EUR", "PLN", d(2025, 1, 18), &AssetType::Fiat));
+    }
+
+    #[test]
+    fn prune_and_clear_drop_carried_flags() {
+        let mut cache = PriceCache::new();
+        cache.set_carried_price("EUR", "PLN", d(2025, 1, 11), 4.30, &AssetType::Fiat);
+        cache.set_carried_price("EUR", "PLN", d(2025, 1, 18), 4.40, &AssetType::Fiat);
+        cache.prune_before(d(2025, 1, 15));
+        assert!(!cache.is_carried("EUR", "PLN", d(2025, 1, 11), &AssetType::Fiat));
+        assert!(cache.is_carried("EUR", "PLN", d(2025, 1, 18), &AssetType::Fiat));
+
+        cache.clear();
+        assert!(cache.carried.is_empty());
+    }
+
+    #[test]
+    fn merge_prefers_quotes_then_newer_series() {
+        let mut own = PriceCache::new();
+        own.set_price("BTC", "USD", d(2025, 1, 1), 40000.0, &AssetType::Crypto);
+        own.set_derived_price("BTC", "USD", d(2025, 1, 2), 41000.0, &AssetType::Crypto);
+        own.set_price("ETH", "USD", d(2025, 1, 1), 3000.0, &AssetType::Crypto);
+        own.mark_updated_today("ETH", "USD", d(2025, 1, 5), &AssetType::Crypto);
+
+        let mut other = PriceCache::new();
+        other.set_price("BTC", "USD", d(2025, 1, 1), 40500.0, &AssetType::Crypto);
+        other.set_price("BTC", "USD", d(2025, 1, 2), 41500.0, &AssetType::Crypto);
+        other.set_price("BTC", "USD", d(2025, 1, 3), 42000.0, &AssetType::Crypto);
+        other.mark_updated_today("BTC", "USD", d(2025, 1, 3), &AssetType::Crypto);
+        other.set_price("ETH", "USD", d(2025, 1, 1), 3100.0, &AssetType::Crypto);
+        other.set_carried_price("SOL", "USD", d(2025, 1, 4), 150.0, &AssetType::Crypto);
+
+        own.merge(other);
+        // Newer BTC series wins; the quote replaces the derived value
+        assert_eq!(own.get_price("BTC", "USD", d(2025, 1, 1), &AssetType::Crypto), Some(40500.0));
+        assert_eq!(own.get_price("BTC", "USD", d(2025, 1, 2), &AssetType::Crypto), Some(41500.0));
+        assert!(!own.is_derived("BTC", "USD", d(2025, 1, 2), &AssetType::Crypto));
+        assert_eq!(own.get_price("BTC", "USD", d(2025, 1, 3), &AssetType::Crypto), Some(42000.0));
+        assert!(own.is_today_fresh("BTC", "USD", d(2025, 1, 3), &AssetType::Crypto));
+        // Own ETH series is the newer one
+        assert_eq!(own.get_price("ETH", "USD", d(2025, 1, 1), &AssetType::Crypto), Some(3000.0));
+        assert!(own.is_today_fresh("ETH", "USD", d(2025, 1, 5), &AssetType::Crypto));
+        assert!(own.is_carried("SOL", "USD", d(2025, 1, 4), &AssetType::Crypto));
+    }
+
+    #[test]
+    fn merge_converts_metal_series_to_own_unit() {
+        let mut own = PriceCache::new();
+        own.set_price("XAU", "USD", d(2025, 1, 1), 2000.0, &AssetType::Metal);
+
+        let mut other = PriceCache::new();
+        other.set_price("XAU", "USD", d(2025, 1, 2), 2100.0, &AssetType::Metal);
+        other.convert_metal_unit("XAU", "USD", MetalUnit::Gram);
+        other.convert_metal_unit("XAG", "USD", MetalUnit::Gram);
+        other.set_price("XAG", "USD", d(2025, 1, 2), 1.0, &AssetType::Metal);
+
+        own.merge(other);
+        let xau = own.get_price("XAU", "USD", d(2025, 1, 2), &AssetType::Metal).unwrap();
+        assert!((xau - 2100.0).abs() < 1e-6);
+        assert_eq!(own.metal_unit("XAU", "USD"), MetalUnit::TroyOunce);
+        // A new series keeps its unit
+        assert_eq!(own.metal_unit("XAG", "USD"), MetalUnit::Gram);
+        assert_eq!(own.get_price("XAG", "USD", d(2025, 1, 2), &AssetType::Metal), Some(1.0));
+    }
+
+    #[test]
+    fn non_finite_prices_are_ignored() {
+        let mut cache = PriceCache::new();
+        cache.set_price("BTC", "USD", d(2025, 1, 1), 50000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 1), f64::NAN, &AssetType::Crypto);
+        cache.set_derived_price("BTC", "USD", d(2025, 1, 2), f64::INFINITY, &AssetType::Crypto);
+        let carried = cache.set_carried_price("BTC", "USD", d(2025, 1, 3), f64::NAN, &AssetType::Crypto);
+        assert!(!carried);
+
+        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 1), &AssetType::Crypto), Some(50000.0));
+        assert!(!cache.is_derived("BTC", "USD", d(2025, 1, 2), &AssetType::Crypto));
+        assert_eq!(cache.total_entries(), 1);
+
+        // Nor taken over from a cache that got them some other way
+        let mut other = PriceCache::new();
+        let key = ("ETH".to_string(), "USD".to_string(), AssetType::Crypto);
+        other.entries.insert(key, vec![PricePoint { date: d(2025, 1, 1), price: f64::NAN }]);
+        cache.merge(other);
+        assert_eq!(cache.get_price("ETH", "USD", d(2025, 1, 1), &AssetType::Crypto), None);
     }
 }
 
@@ -712,6 +1105,15 @@ mod settings {
         assert_eq!(s.default_currency, "USD");
     }
 
+    #[test]
+    fn chart_range_defaults_to_ten_years() {
+        assert_eq!(Settings::default().max_chart_range_days, Some(3650));
+        let s: Settings = serde_json::from_str(r#"{"default_currency":"EUR"}"#).unwrap();
+        assert_eq!(s.max_chart_range_days, Some(3650));
+        let s: Settings = serde_json::from_str(r#"{"max_chart_range_days":null}"#).unwrap();
+        assert_eq!(s.max_chart_range_days, None);
+    }
+
     #[test]
     fn default_api_keys_empty() {
         let s = Settings::default();
@@ -760,6 +1162,110 @@ mod settings {
         assert_eq!(back.default_currency, "GBP");
     }
 
+    #[test]
+    fn default_pivot_currencies() {
+        let s = Settings::default();
+        assert_eq!(s.pivot_currencies, vec!["USD", "EUR"]);
+    }
+
+    #[test]
+    fn missing_pivot_currencies_default_in_json() {
+        let json = r#"{"default_currency":"PLN","api_keys":{}}"#;
+        let s: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(s.pivot_currencies, vec!["USD", "EUR"]);
+    }
+
+    #[test]
+    fn metal_unit_defaults_to_troy_ounce() {
+        let json = r#"{"default_currency":"PLN","api_keys":{}}"#;
+        let s: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(s.metal_unit, MetalUnit::TroyOunce);
+    }
+
+    #[test]
+    fn validation_policy_defaults_match_built_in_rules() {
+        let policy = ValidationPolicy::default();
+        assert_eq!(policy.allow_future_days, 1);
+        assert!(!policy.allow_oversell);
+        assert_eq!(policy.max_amount, None);
+        assert!(!policy.require_known_asset);
+    }
+
+    #[test]
+    fn validation_policy_defaults_in_json() {
+        let json = r#"{"default_currency":"PLN","api_keys":{}}"#;
+        let s: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(s.validation_policy, ValidationPolicy::default());
+
+        let json = r#"{"default_currency":"PLN","api_keys":{},
+                       "validation_policy":{"allow_oversell":true}}"#;
+        let s: Settings = serde_json::from_str(json).unwrap();
+        assert!(s.validation_policy.allow_oversell);
+        assert_eq!(s.validation_policy.allow_future_days, 1);
+    }
+
+    #[test]
+    fn long_term_holding_days_defaults_to_a_year() {
+        assert_eq!(Settings::default().long_term_holding_days, 365);
+        let json = r#"{"default_currency":"PLN","api_keys":{}}"#;
+        let s: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(s.long_term_holding_days, 365);
+    }
+
+    #[test]
+    fn fiscal_year_and_week_follow_settings() {
+        let json = r#"{"default_currency":"PLN","api_keys":{}}"#;
+        let s: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!((s.fiscal_year_start_month, s.week_start), (1, WeekStart::Monday));
+        assert_eq!(s.fiscal_year(d(2025, 4, 1)), 2025);
+
+        let s = Settings { fiscal_year_start_month: 4, week_start: WeekStart::Sunday, ..s };
+        assert_eq!(s.fiscal_year(d(2025, 3, 31)), 2024);
+        assert_eq!(s.fiscal_year(d(2025, 4, 1)), 2025);
+        assert_eq!(s.fiscal_year_start(2025), Some(d(2025, 4, 1)));
+        assert_eq!(s.fiscal_year_end(2025), Some(d(2026, 3, 31)));
+        // 2025-01-08 is a Wednesday
+        assert_eq!(s.week_start_date(d(2025, 1, 8)), d(2025, 1, 5));
+        let monday = Settings { week_start: WeekStart::Monday, ..s };
+        assert_eq!(monday.week_start_date(d(2025, 1, 8)), d(2025, 1, 6));
+        assert_eq!(monday.week_start_date(d(2025, 1, 5)), d(2024, 12, 30));
+    }
+
+    #[test]
+    fn provider_timeout_falls_back_to_global_default() {
+        let json = r#"{"default_currency":"PLN","api_keys":{}}"#;
+        let mut s: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(s.provider_timeout_secs, 10);
+        assert!(s.provider_timeouts.is_empty());
+        assert_eq!(s.provider_timeout("coincap"), Duration::from_secs(10));
+
+        s.provider_timeout_secs = 20;
+        s.provider_timeouts.insert("alphavantage".into(), 45);
+        s.provider_timeouts.insert("yahoo".into(), 0);
+        assert_eq!(s.provider_timeout("coincap"), Duration::from_secs(20));
+        assert_eq!(s.provider_timeout("alphavantage"), Duration::from_secs(45));
+        assert_eq!(s.provider_timeout("yahoo"), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn proxy_is_unset_by_default() {
+        let json = r#"{"default_currency":"PLN","api_keys":{}}"#;
+        let s: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(s.proxy_url, None);
+        assert!(s.no_proxy.is_empty());
+        assert_eq!(Settings::default().proxy_url, None);
+    }
+
+    #[test]
+    fn trash_policy_defaults_to_unlimited() {
+        let json = r#"{"default_currency":"PLN","api_keys":{},
+                       "trash_policy":{"max_entries":50}}"#;
+        let s: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(s.trash_policy.max_entries, Some(50));
+        assert_eq!(s.trash_policy.retention_days, None);
+        assert_eq!(Settings::default().trash_policy, TrashPolicy::default());
+    }
+
     #[test]
     fn clone_preserves_fields() {
         let mut s = Settings::default();
@@ -781,7 +1287,9 @@ mod chart {
         let point = ChartDataPoint {
             date: d(2025, 1, 15),
             portfolio_value: 10000.0,
+            currency: "USD".to_string(),
             events: vec![],
+            provisional: false,
         };
         assert_eq!(point.date, d(2025, 1, 15));
         assert_eq!(point.portfolio_value, 10000.0);
@@ -795,11 +1303,14 @@ mod chart {
             asset_symbol: "BTC".to_string(),
             amount: 0.5,
             value_in_default_currency: 21000.0,
+            unit_price: Some(42000.0),
         };
         let point = ChartDataPoint {
             date: d(2025, 1, 15),
             portfolio_value: 21000.0,
+            currency: "USD".to_string(),
             events: vec![event],
+            provisional: false,
         };
         assert_eq!(point.events.len(), 1);
         assert_eq!(point.events[0].asset_symbol, "BTC");
@@ -812,6 +1323,7 @@ mod chart {
             asset_symbol: "ETH".to_string(),
             amount: 10.0,
             value_in_default_currency: 25000.0,
+            unit_price: Some(2500.0),
         };
         assert_eq!(e.event_type, EventType::Buy);
         assert_eq!(e.amount, 10.0);
@@ -824,6 +1336,7 @@ mod chart {
             asset_symbol: "BTC".to_string(),
             amount: 0.1,
             value_in_default_currency: 4200.0,
+            unit_price: None,
         };
         assert_eq!(e.event_type, EventType::Sell);
     }
@@ -833,12 +1346,15 @@ mod chart {
         let point = ChartDataPoint {
             date: d(2025, 1, 15),
             portfolio_value: 5000.0,
+            currency: "USD".to_string(),
             events: vec![ChartEvent {
                 event_type: EventType::Buy,
                 asset_symbol: "BTC".to_string(),
                 amount: 0.1,
                 value_in_default_currency: 4200.0,
+                unit_price: Some(42000.0),
             }],
+            provisional: false,
         };
         let c = point.clone();
         assert_eq!(c.portfolio_value, point.portfolio_value);
@@ -850,17 +1366,58 @@ mod chart {
         let point = ChartDataPoint {
             date: d(2025, 1, 15),
             portfolio_value: 10000.0,
+            currency: "USD".to_string(),
             events: vec![ChartEvent {
                 event_type: EventType::Buy,
                 asset_symbol: "BTC".to_string(),
                 amount: 0.5,
                 value_in_default_currency: 21000.0,
+                unit_price: Some(42000.0),
             }],
+            provisional: false,
         };
         let json = serde_json::to_string(&point).unwrap();
         let back: ChartDataPoint = serde_json::from_str(&json).unwrap();
         assert_eq!(back.portfolio_value, 10000.0);
         assert_eq!(back.events[0].asset_symbol, "BTC");
+        assert_eq!(back.events[0].unit_price, Some(42000.0));
+    }
+
+    #[test]
+    fn chart_event_without_unit_price_deserializes() {
+        let json = r#"{"event_type":"Buy","asset_symbol":"BTC","amount":0.5,
+            "value_in_default_currency":21000.0}"#;
+        let event: ChartEvent = serde_json::from_str(json).unwrap();
+        assert_eq!(event.unit_price, None);
+    }
+
+    #[test]
+    fn resolution_follows_the_range_length() {
+        let from = d(2000, 1, 1);
+        let days = |n: u64| from.checked_add_days(chrono::Days::new(n)).unwrap();
+        assert_eq!(ChartResolution::for_range(from, days(3650)), ChartResolution::Daily);
+        assert_eq!(ChartResolution::for_range(from, days(3651)), ChartResolution::Weekly);
+        assert_eq!(ChartResolution::for_range(from, days(7300)), ChartResolution::Weekly);
+        assert_eq!(ChartResolution::for_range(from, days(7301)), ChartResolution::Monthly);
+    }
+
+    #[test]
+    fn points_step_from_the_start_and_end_on_the_last_day() {
+        let weekly = ChartResolution::Weekly;
+        let (from, to) = (d(2025, 1, 1), d(2025, 1, 20));
+        assert_eq!(weekly.next_date(from, from, to), Some(d(2025, 1, 8)));
+        assert_eq!(weekly.next_date(from, d(2025, 1, 15), to), Some(to));
+        assert_eq!(weekly.next_date(from, to, to), None);
+        assert_eq!(weekly.point_count(from, to), 4);
+
+        // Month ends clamp without shifting the later points
+        let monthly = ChartResolution::Monthly;
+        let (from, to) = (d(2024, 1, 31), d(2024, 12, 31));
+        assert_eq!(monthly.next_date(from, from, to), Some(d(2024, 2, 29)));
+        assert_eq!(monthly.next_date(from, d(2024, 2, 29), to), Some(d(2024, 3, 31)));
+        assert_eq!(monthly.point_count(from, to), 12);
+        assert_eq!(monthly.point_count(to, from), 0);
+        assert_eq!(ChartResolution::Daily.point_count(from, from), 1);
     }
 }
 
@@ -889,6 +1446,23 @@ mod portfolio {
         assert!(p.price_cache.entries.is_empty());
     }
 
+    #[test]
+    fn default_has_no_alerts() {
+        assert!(Portfolio::default().alerts.is_empty());
+    }
+
+    #[test]
+    fn alerts_serde_roundtrip() {
+        let mut p = Portfolio::default();
+        let mut alert = Alert::new(Asset::crypto("BTC", "Bitcoin"), AlertCondition::Below, 30000.0, "USD");
+        alert.last_triggered = Some(d(2025, 3, 1));
+        p.alerts.push(alert.clone());
+
+        let bytes = bincode::serialize(&p).unwrap();
+        let back: Portfolio = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back.alerts, vec![alert]);
+    }
+
     #[test]
     fn clone_preserves_events() {
         let mut p = Portfolio::default();
@@ -914,9 +1488,11 @@ mod portfolio {
             NaiveDate::from_ymd_opt(2025, 2, 1).unwrap(),
         ));
         p.price_cache.set_price(
-            "ETH", "USD",
+            "ETH",
+            "USD",
             NaiveDate::from_ymd_opt(2025, 2, 1).unwrap(),
             2500.0,
+            &AssetType::Crypto,
         );
 
         let bytes = bincode::serialize(&p).unwrap();
@@ -924,17 +1500,434 @@ mod portfolio {
         assert_eq!(back.events.len(), 1);
         assert_eq!(back.settings.default_currency, "PLN");
         assert_eq!(
-            back.price_cache.get_price("ETH", "USD", NaiveDate::from_ymd_opt(2025, 2, 1).unwrap()),
+            back.price_cache.get_price(
+                "ETH",
+                "USD",
+                NaiveDate::from_ymd_opt(2025, 2, 1).unwrap(),
+                &AssetType::Crypto,
+            ),
             Some(2500.0),
         );
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════
+//  CorporateAction
+// ═══════════════════════════════════════════════════════════════════
+
+mod corporate_action {
+    use super::*;
+    use savings_tracker_core::models::corporate_action::{
+        adjust_event, current_symbol, CorporateAction, KNOWN_SPLITS,
+    };
+    use savings_tracker_core::models::event::RecordedValue;
+    use std::borrow::Cow;
+
+    #[test]
+    fn adjust_applies_later_splits_and_renames() {
+        let actions = vec![
+            CorporateAction::split("FB", 2.0, d(2020, 1, 1)),
+            CorporateAction::rename("fb", " meta ", d(2022, 6, 9)),
+            CorporateAction::split("META", 5.0, d(2023, 1, 1)),
+        ];
+        let fb = Asset::stock("FB", "Facebook");
+        let mut buy = Event::new(EventType::Buy, fb.clone(), 3.0, d(2019, 5, 1));
+        buy.recorded_value = Some(RecordedValue {
+            quote_currency: "USD".into(),
+            quote_unit_price: 100.0,
+            currency: "USD".into(),
+            unit_price: 100.0,
+        });
+
+        let adjusted = adjust_event(&actions, &buy);
+        assert_eq!((adjusted.asset.symbol.as_str(), adjusted.amount), ("META", 30.0));
+        assert_eq!(adjusted.recorded_value.as_ref().unwrap().unit_price, 10.0);
+        assert_eq!(adjusted.id, buy.id);
+
+        // After every split: renamed only; other assets are borrowed as is
+        let late = Event::new(EventType::Buy, fb, 3.0, d(2023, 1, 1));
+        assert_eq!(adjust_event(&actions, &late).amount, 3.0);
+        let other = Event::new(EventType::Buy, Asset::stock("AAPL", "Apple"), 1.0, d(2019, 5, 1));
+        assert!(matches!(adjust_event(&actions, &other), Cow::Borrowed(_)));
+        assert_eq!(current_symbol(&actions, "FB"), "META");
+    }
+
+    #[test]
+    fn validate_rejects_no_op_actions() {
+        let date = d(2024, 6, 10);
+        assert!(CorporateAction::split("NVDA", 10.0, date).validate().is_ok());
+        assert!(CorporateAction::split("NVDA", 0.1, date).validate().is_ok());
+        assert!(CorporateAction::split("NVDA", 1.0, date).validate().is_err());
+        assert!(CorporateAction::split("NVDA", -2.0, date).validate().is_err());
+        assert!(CorporateAction::split("NVDA", f64::NAN, date).validate().is_err());
+        assert!(CorporateAction::split(" ", 2.0, date).validate().is_err());
+        assert!(CorporateAction::rename("FB", "fb", date).validate().is_err());
+        assert!(CorporateAction::rename("FB", "", date).validate().is_err());
+        assert!(KNOWN_SPLITS.iter().all(|s| s.to_action().is_some_and(|a| a.validate().is_ok())));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  Reminder
+// ═══════════════════════════════════════════════════════════════════
+
+mod reminder {
+    use super::*;
+    use savings_tracker_core::models::reminder::Reminder;
+
+    #[test]
+    fn due_from_its_date_until_acknowledged() {
+        let mut reminder = Reminder::new(uuid::Uuid::new_v4(), d(2025, 6, 1));
+        assert!(!reminder.is_due(d(2025, 5, 31)));
+        assert!(reminder.is_due(d(2025, 6, 1)));
+        assert!(reminder.is_due(d(2026, 1, 1)));
+
+        reminder.acknowledged = true;
+        assert!(!reminder.is_due(d(2026, 1, 1)));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  AccrualRule
+// ═══════════════════════════════════════════════════════════════════
+
+mod accrual_rule {
+    use super::*;
+    use savings_tracker_core::models::accrual::{AccrualRule, Compounding};
+    use savings_tracker_core::models::holdings::HoldingsIndex;
+
+    fn eth() -> Asset {
+        Asset::crypto("ETH", "Ethereum")
+    }
+
+    #[test]
+    fn growth_compounds_inside_the_window() {
+        let rule = AccrualRule::new(eth(), 3.65, Compounding::Daily, d(2025, 1, 1))
+            .with_end_date(d(2025, 1, 10));
+        let daily = 1.0001_f64;
+        assert!((rule.growth(d(2024, 12, 1), d(2025, 1, 3)) - daily.powi(3)).abs() < 1e-12);
+        // Clipped to the window on both sides
+        assert!((rule.growth(d(2025, 1, 8), d(2025, 2, 1)) - daily.powi(2)).abs() < 1e-12);
+        assert_eq!(rule.growth(d(2025, 1, 10), d(2025, 3, 1)), 1.0);
+
+        let monthly = AccrualRule::new(eth(), 12.0, Compounding::Monthly, d(2025, 1, 15));
+        assert_eq!(monthly.growth(d(2025, 1, 1), d(2025, 1, 30)), 1.0);
+        let three = monthly.growth(d(2025, 1, 1), d(2025, 3, 31));
+        assert!((three - 1.01_f64.powi(3)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn period_ends_skip_materialized_months() {
+        let mut rule = AccrualRule::new(eth(), 5.0, Compounding::Daily, d(2025, 1, 20))
+            .with_end_date(d(2025, 3, 10));
+        assert_eq!(rule.period_ends(d(2025, 2, 27)), vec![d(2025, 1, 31)]);
+        assert_eq!(
+            rule.period_ends(d(2025, 6, 1)),
+            vec![d(2025, 1, 31), d(2025, 2, 28), d(2025, 3, 10)]
+        );
+        rule.materialized_until = Some(d(2025, 2, 28));
+        assert_eq!(rule.accrues_from(), d(2025, 3, 1));
+        assert_eq!(rule.period_ends(d(2025, 6, 1)), vec![d(2025, 3, 10)]);
+        assert_eq!(rule.growth(d(2025, 1, 1), d(2025, 2, 28)), 1.0);
+    }
+
+    #[test]
+    fn validate_and_overlap() {
+        let rule = AccrualRule::new(eth(), 4.0, Compounding::Monthly, d(2025, 1, 1))
+            .with_end_date(d(2025, 6, 30));
+        assert!(rule.validate().is_ok());
+        let zero = AccrualRule::new(eth(), 0.0, Compounding::Daily, d(2025, 1, 1));
+        assert!(zero.validate().is_err());
+        let backwards = AccrualRule::new(eth(), 4.0, Compounding::Daily, d(2025, 1, 1))
+            .with_end_date(d(2024, 12, 31));
+        assert!(backwards.validate().is_err());
+
+        let later = AccrualRule::new(eth(), 3.0, Compounding::Daily, d(2025, 6, 30));
+        assert!(rule.overlaps(&later) && later.overlaps(&rule));
+        let after = AccrualRule::new(eth(), 3.0, Compounding::Daily, d(2025, 7, 1));
+        assert!(!rule.overlaps(&after));
+        let btc = Asset::crypto("BTC", "Bitcoin");
+        let btc = AccrualRule::new(btc, 3.0, Compounding::Daily, d(2025, 1, 1));
+        assert!(!rule.overlaps(&btc));
+    }
+
+    #[test]
+    fn index_grows_balances_between_events() {
+        let rule = AccrualRule::new(eth(), 3.65, Compounding::Daily, d(2025, 1, 1));
+        let events = vec![
+            Event::new(EventType::Buy, eth(), 10.0, d(2025, 1, 1)),
+            Event::new(EventType::Sell, eth(), 5.0, d(2025, 1, 11)),
+        ];
+        let index = HoldingsIndex::build_accruing(&events, &[rule], 0, false);
+
+        // Bought on the first day: it accrues from the next one
+        let after_nine_days = 10.0 * 1.0001_f64.powi(9);
+        assert!((index.amount_at(&eth(), d(2025, 1, 10)) - after_nine_days).abs() < 1e-9);
+        let expected = (10.0 * 1.0001_f64.powi(10) - 5.0) * 1.0001_f64.powi(5);
+        assert!((index.amount_at(&eth(), d(2025, 1, 16)) - expected).abs() < 1e-9);
+        assert_eq!(index.amount_at(&eth(), d(2024, 12, 31)), 0.0);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  HoldingsIndex
+// ═══════════════════════════════════════════════════════════════════
+
+mod holdings_index {
+    use super::*;
+    use savings_tracker_core::models::holdings::HoldingsIndex;
+    use std::sync::Arc;
+
+    fn btc() -> Asset {
+        Asset::crypto("BTC", "Bitcoin")
+    }
+
+    fn events() -> Vec<Event> {
+        vec![
+            Event::new(EventType::Buy, btc(), 2.0, d(2025, 1, 1)),
+            Event::new(EventType::Buy, Asset::fiat("EUR", "Euro"), 100.0, d(2025, 1, 5)),
+            Event::new(EventType::Sell, btc(), 0.5, d(2025, 1, 10)),
+            Event::new(EventType::Buy, btc(), 1.0, d(2025, 1, 10)),
+            Event::new(EventType::Sell, Asset::fiat("EUR", "Euro"), 100.0, d(2025, 2, 1)),
+        ]
+    }
+
+    #[test]
+    fn amounts_are_prefix_sums_per_date() {
+        let index = HoldingsIndex::build(&events(), 0, false);
+        assert_eq!(index.amount_at(&btc(), d(2024, 12, 31)), 0.0);
+        assert_eq!(index.amount_at(&btc(), d(2025, 1, 9)), 2.0);
+        // Both events of Jan 10 are applied
+        assert_eq!(index.amount_at(&btc(), d(2025, 1, 10)), 2.5);
+        assert_eq!(index.amount_at(&Asset::crypto("ETH", "Ethereum"), d(2025, 3, 1)), 0.0);
+    }
+
+    #[test]
+    fn holdings_skip_emptied_positions() {
+        let index = HoldingsIndex::build(&events(), 0, false);
+        assert_eq!(index.holdings_at(d(2025, 1, 20)).len(), 2);
+        let later = index.holdings_at(d(2025, 2, 1));
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[&btc()], 2.5);
+    }
+
+    #[test]
+    fn positions_keep_emptied_assets_at_exactly_zero() {
+        let eur = Asset::fiat("EUR", "Euro");
+        let index = HoldingsIndex::build(&events(), 0, false);
+        assert!(index.positions_at(d(2024, 12, 31)).is_empty());
+        assert_eq!(index.positions_at(d(2025, 1, 1)), HashMap::from([(btc(), 2.0)]));
+        assert_eq!(index.positions_at(d(2025, 2, 1))[&eur], 0.0);
+
+        // Float dust left by a full sell is reported as 0
+        let dust = vec![
+            Event::new(EventType::Buy, btc(), 0.1, d(2025, 1, 1)),
+            Event::new(EventType::Buy, btc(), 0.2, d(2025, 1, 2)),
+            Event::new(EventType::Sell, btc(), 0.3, d(2025, 1, 3)),
+        ];
+        let index = HoldingsIndex::build(&dust, 0, false);
+        assert_ne!(index.amount_at(&btc(), d(2025, 1, 3)), 0.0);
+        assert_eq!(index.positions_at(d(2025, 1, 3)), HashMap::from([(btc(), 0.0)]));
+    }
+
+    #[test]
+    fn planned_events_only_count_when_included() {
+        let mut events = events();
+        events.push(Event::planned(EventType::Buy, btc(), 4.0, d(2025, 3, 1)));
+
+        let actual = HoldingsIndex::build(&events, 0, false);
+        assert_eq!(actual.amount_at(&btc(), d(2025, 3, 1)), 2.5);
+        let planned = HoldingsIndex::build(&events, 0, true);
+        assert_eq!(planned.amount_at(&btc(), d(2025, 3, 1)), 6.5);
+        assert_eq!(planned.amount_at(&btc(), d(2025, 2, 28)), 2.5);
+    }
+
+    #[test]
+    fn portfolio_reuses_index_until_events_change() {
+        let mut p = Portfolio::default();
+        p.events = events();
+        let first = p.holdings_index();
+        assert!(Arc::ptr_eq(&first, &p.holdings_index()));
+
+        p.events[0].amount = 3.0;
+        p.mark_events_changed();
+        let second = p.holdings_index();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(second.amount_at(&btc(), d(2025, 1, 1)), 3.0);
+
+        // Appending directly is caught by the event count
+        p.events.push(Event::new(EventType::Buy, btc(), 1.0, d(2025, 3, 1)));
+        assert_eq!(p.holdings_index().amount_at(&btc(), d(2025, 3, 1)), 4.5);
+    }
+
+    #[test]
+    fn index_is_not_serialized() {
+        let mut p = Portfolio::default();
+        p.events = events();
+        p.mark_events_changed();
+        p.holdings_index();
+
+        let bytes = bincode::serialize(&p).unwrap();
+        let back: Portfolio = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back.events_generation(), 0);
+        assert_eq!(back.holdings_index().amount_at(&btc(), d(2025, 1, 10)), 2.5);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  SearchIndex
+// ═══════════════════════════════════════════════════════════════════
+
+mod search_index {
+    use super::*;
+    use savings_tracker_core::models::search::{SearchIndex, SearchOptions};
+
+    fn events() -> Vec<Event> {
+        let btc = Asset::crypto("BTC", "Bitcoin");
+        let aapl = Asset::stock("AAPL", "Apple");
+        vec![
+            Event::with_notes(EventType::Buy, btc, 1.0, d(2025, 1, 1), "Kraken"),
+            Event::new(EventType::Buy, Asset::crypto("ETH", "Ethereum"), 2.0, d(2025, 1, 2)),
+            Event::with_notes(EventType::Buy, aapl, 3.0, d(2025, 1, 3), "DCA"),
+        ]
+    }
+
+    fn search(index: &SearchIndex, query: &str) -> Vec<usize> {
+        index.search(query, &SearchOptions::default())
+    }
+
+    #[test]
+    fn matches_substrings_case_insensitively() {
+        let index = SearchIndex::build(&events(), 0);
+        assert_eq!(search(&index, "KRAK"), vec![0]);
+        assert_eq!(search(&index, "eum"), vec![1]);
+        // Shorter than a trigram: scanned
+        assert_eq!(search(&index, "a"), vec![0, 2]);
+        assert_eq!(search(&index, ""), vec![0, 1, 2]);
+        assert!(search(&index, "dogecoin").is_empty());
+        // No match across the symbol and name
+        assert!(search(&index, "btcbit").is_empty());
+    }
+
+    #[test]
+    fn fuzzy_matches_characters_in_order_within_a_field() {
+        let index = SearchIndex::build(&events(), 0);
+        let fuzzy = SearchOptions { fuzzy: true };
+        assert_eq!(index.search("btcn", &fuzzy), vec![0]);
+        assert_eq!(index.search("ETRM", &fuzzy), vec![1]);
+        assert!(index.search("ncb", &fuzzy).is_empty());
+        // "aapl" then "dca" are different fields
+        assert!(index.search("apld", &fuzzy).is_empty());
+    }
+
+    #[test]
+    fn update_reindexes_changed_added_and_removed_events() {
+        let mut events = events();
+        let mut index = SearchIndex::build(&events, 0);
+
+        events[1].notes = Some("Moved to Ledger".into());
+        events.remove(0);
+        let sol = Asset::crypto("SOL", "Solana");
+        events.push(Event::with_notes(EventType::Buy, sol, 1.0, d(2025, 1, 4), "kraken"));
+        index.update(&events, 1);
+
+        assert!(index.is_current(1, 3));
+        assert_eq!(search(&index, "ledger"), vec![0]);
+        assert_eq!(search(&index, "kraken"), vec![2]);
+        assert!(search(&index, "bitcoin").is_empty());
+        assert_eq!(search(&index, "apple"), vec![1]);
+    }
+
+    #[test]
+    fn events_sharing_an_id_are_indexed_separately() {
+        let mut events = events();
+        events[2].id = events[0].id;
+        let index = SearchIndex::build(&events, 0);
+        assert_eq!(search(&index, "kraken"), vec![0]);
+        assert_eq!(search(&index, "apple"), vec![2]);
+    }
+
+    #[test]
+    fn portfolio_updates_index_after_events_change() {
+        let mut p = Portfolio::default();
+        p.events = events();
+        assert_eq!(p.search_index().search("dca", &SearchOptions::default()), vec![2]);
+
+        p.events[0].notes = Some("DCA".into());
+        p.mark_events_changed();
+        assert_eq!(p.search_index().search("dca", &SearchOptions::default()), vec![0, 2]);
+
+        // A clone shares the index until its events change
+        let mut copy = p.clone();
+        copy.events.remove(0);
+        copy.mark_events_changed();
+        assert_eq!(copy.search_index().search("dca", &SearchOptions::default()), vec![1]);
+        assert_eq!(p.search_index().search("dca", &SearchOptions::default()), vec![0, 2]);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  MetalUnit / PriceCache unit records
+// ═══════════════════════════════════════════════════════════════════
+
+mod metal_unit {
+    use super::*;
+
+    #[test]
+    fn price_factor_between_units() {
+        assert_eq!(MetalUnit::Gram.price_factor(MetalUnit::Gram), 1.0);
+        let per_gram = 62.5;
+        let per_ounce = per_gram * MetalUnit::Gram.price_factor(MetalUnit::TroyOunce);
+        assert!((per_ounce - 62.5 * 31.1034768).abs() < 1e-9);
+        let per_kg = per_gram * MetalUnit::Gram.price_factor(MetalUnit::Kilogram);
+        assert!((per_kg - 62_500.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cache_conversion_rescales_and_records_unit() {
+        let xau = |cache: &PriceCache, date| {
+            cache.get_price("XAU", "USD", date, &AssetType::Metal).unwrap()
+        };
+        let mut cache = PriceCache::new();
+        cache.set_price("XAU", "USD", d(2025, 1, 1), 3110.34768, &AssetType::Metal);
+        cache.set_price("XAU", "USD", d(2025, 1, 2), 311.034768, &AssetType::Metal);
+        assert_eq!(cache.metal_unit("xau", "usd"), MetalUnit::TroyOunce);
+
+        cache.convert_metal_unit("xau", "usd", MetalUnit::Gram);
+        assert_eq!(cache.metal_unit("XAU", "USD"), MetalUnit::Gram);
+        assert!((xau(&cache, d(2025, 1, 1)) - 100.0).abs() < 1e-9);
+        assert!((xau(&cache, d(2025, 1, 2)) - 10.0).abs() < 1e-9);
+
+        // Converting again to the same unit changes nothing
+        cache.convert_metal_unit("XAU", "USD", MetalUnit::Gram);
+        assert!((xau(&cache, d(2025, 1, 1)) - 100.0).abs() < 1e-9);
+
+        cache.convert_metal_unit("XAU", "USD", MetalUnit::TroyOunce);
+        assert!(cache.metal_units.is_empty());
+        assert!((xau(&cache, d(2025, 1, 1)) - 3110.34768).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unit_records_follow_prune_and_clear() {
+        let mut cache = PriceCache::new();
+        cache.set_price("XAU", "USD", d(2025, 1, 1), 100.0, &AssetType::Metal);
+        cache.convert_metal_unit("XAU", "USD", MetalUnit::Gram);
+        cache.prune_before(d(2025, 2, 1));
+        assert!(cache.metal_units.is_empty());
+
+        cache.set_price("XAG", "USD", d(2025, 1, 1), 1.0, &AssetType::Metal);
+        cache.convert_metal_unit("XAG", "USD", MetalUnit::Kilogram);
+        cache.clear();
+        assert_eq!(cache.metal_unit("XAG", "USD"), MetalUnit::TroyOunce);
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // PriceCache prune edge cases (T9)
 // ═══════════════════════════════════════════════════════════════════
 
 mod price_cache_prune_edge_cases {
+    use savings_tracker_core::models::asset::AssetType;
     use savings_tracker_core::models::price::PriceCache;
     use chrono::NaiveDate;
 
@@ -945,22 +1938,25 @@ mod price_cache_prune_edge_cases {
     #[test]
     fn prune_exact_date_match() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 10), 40000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 20), 44000.0);
+        cache.set_price("BTC", "USD", d(2025, 1, 10), 40000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 15), 42000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 20), 44000.0, &AssetType::Crypto);
 
         // Prune before 2025-01-15 → removes Jan 10, keeps Jan 15 and Jan 20
         let removed = cache.prune_before(d(2025, 1, 15));
         assert_eq!(removed, 1);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 10)), None);
-        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 15)), Some(42000.0));
+        assert_eq!(cache.get_price("BTC", "USD", d(2025, 1, 10), &AssetType::Crypto), None);
+        assert_eq!(
+            cache.get_price("BTC", "USD", d(2025, 1, 15), &AssetType::Crypto),
+            Some(42000.0),
+        );
     }
 
     #[test]
     fn prune_all_entries() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 1), 40000.0);
-        cache.set_price("BTC", "USD", d(2025, 1, 2), 41000.0);
+        cache.set_price("BTC", "USD", d(2025, 1, 1), 40000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 1, 2), 41000.0, &AssetType::Crypto);
 
         let removed = cache.prune_before(d(2026, 1, 1));
         assert_eq!(removed, 2);
@@ -971,7 +1967,7 @@ mod price_cache_prune_edge_cases {
     #[test]
     fn prune_with_nothing_to_remove() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 6, 1), 50000.0);
+        cache.set_price("BTC", "USD", d(2025, 6, 1), 50000.0, &AssetType::Crypto);
 
         let removed = cache.prune_before(d(2025, 1, 1));
         assert_eq!(removed, 0);
@@ -981,10 +1977,10 @@ mod price_cache_prune_edge_cases {
     #[test]
     fn prune_multiple_pairs() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 1), 40000.0);
-        cache.set_price("BTC", "USD", d(2025, 6, 1), 50000.0);
-        cache.set_price("ETH", "USD", d(2025, 1, 1), 2500.0);
-        cache.set_price("ETH", "USD", d(2025, 6, 1), 3000.0);
+        cache.set_price("BTC", "USD", d(2025, 1, 1), 40000.0, &AssetType::Crypto);
+        cache.set_price("BTC", "USD", d(2025, 6, 1), 50000.0, &AssetType::Crypto);
+        cache.set_price("ETH", "USD", d(2025, 1, 1), 2500.0, &AssetType::Crypto);
+        cache.set_price("ETH", "USD", d(2025, 6, 1), 3000.0, &AssetType::Crypto);
 
         let removed = cache.prune_before(d(2025, 3, 1));
         assert_eq!(removed, 2); // one from each pair
@@ -994,8 +1990,8 @@ mod price_cache_prune_edge_cases {
     #[test]
     fn prune_cleans_stale_last_updated() {
         let mut cache = PriceCache::new();
-        cache.set_price("BTC", "USD", d(2025, 1, 1), 40000.0);
-        cache.mark_updated_today("BTC", "USD", d(2025, 1, 1));
+        cache.set_price("BTC", "USD", d(2025, 1, 1), 40000.0, &AssetType::Crypto);
+        cache.mark_updated_today("BTC", "USD", d(2025, 1, 1), &AssetType::Crypto);
 
         // Prune all → should also clean last_updated
         cache.prune_before(d(2026, 1, 1));
@@ -1046,4 +2042,457 @@ mod portfolio_trash_serde {
         let back: Portfolio = bincode::deserialize(&bytes).unwrap();
         assert!(back.trash.is_empty());
     }
+
+    #[test]
+    fn legacy_trash_migrates_to_deleted_events_in_trash_order() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 1, day).unwrap();
+        let btc = Asset::crypto("BTC", "Bitcoin");
+        let mut p = Portfolio::default();
+        p.events.push(Event::new(EventType::Buy, btc.clone(), 1.0, date(2)));
+        p.trash.push(Event::new(EventType::Buy, btc.clone(), 2.0, date(3)));
+        p.trash.push(Event::new(EventType::Buy, btc, 3.0, date(1)));
+
+        assert_eq!(p.migrate_legacy_trash(), 2);
+        assert!(p.trash.is_empty());
+        let amounts: Vec<f64> = p.events.iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, vec![3.0, 1.0, 2.0]);
+        assert_eq!(p.active_events().count(), 1);
+        let deleted: Vec<&Event> = p.deleted_events().collect();
+        assert!(deleted[0].deleted_at > deleted[1].deleted_at);
+        assert_eq!(p.migrate_legacy_trash(), 0);
+    }
+
+    #[test]
+    fn trashed_at_of_earlier_json_reads_as_deleted_at() {
+        let event = Event::new(
+            EventType::Buy,
+            Asset::crypto("BTC", "Bitcoin"),
+            1.0,
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        );
+        let mut json = serde_json::to_value(&event).unwrap();
+        let object = json.as_object_mut().unwrap();
+        object.remove("deleted_at");
+        object.insert("trashed_at".into(), "2025-02-01T10:00:00Z".into());
+        let back: Event = serde_json::from_value(json).unwrap();
+        assert!(back.is_deleted());
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  PortfolioDiff
+// ═══════════════════════════════════════════════════════════════════
+
+mod portfolio_diff {
+    use super::*;
+    use savings_tracker_core::models::diff::{FieldChange, PortfolioDiff};
+
+    fn btc(amount: f64, date: NaiveDate) -> Event {
+        Event::new(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), amount, date)
+    }
+
+    #[test]
+    fn matches_events_by_id() {
+        let mut old = Portfolio::default();
+        let kept = btc(1.0, d(2025, 1, 1));
+        let removed = btc(2.0, d(2025, 1, 2));
+        old.events = vec![kept.clone(), removed.clone()];
+
+        let mut new = old.clone();
+        new.events.retain(|e| e.id != removed.id);
+        new.events[0].amount = 1.5;
+        new.events[0].notes = Some("corrected".into());
+        let added = btc(3.0, d(2025, 1, 3));
+        new.events.push(added.clone());
+        new.price_cache.set_price("BTC", "USD", d(2025, 1, 3), 42000.0, &AssetType::Crypto);
+
+        let diff = PortfolioDiff::between(&old, &new);
+        assert_eq!(diff.added_events, vec![added]);
+        assert_eq!(diff.removed_events, vec![removed]);
+        assert_eq!(diff.modified_events.len(), 1);
+        assert_eq!(diff.modified_events[0].id, kept.id);
+        assert_eq!(
+            diff.modified_events[0].changes,
+            vec![
+                FieldChange {
+                    field: "amount".into(),
+                    old: Some("1".into()),
+                    new: Some("1.5".into()),
+                },
+                FieldChange {
+                    field: "notes".into(),
+                    old: None,
+                    new: Some("corrected".into()),
+                },
+            ]
+        );
+        assert_eq!(diff.cache_entry_delta, 1);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn settings_changes_hide_api_keys() {
+        let old = Portfolio::default();
+        let mut new = old.clone();
+        new.settings.default_currency = "PLN".into();
+        new.settings.api_keys.insert("finnhub".into(), "secret".into());
+        new.settings.validation_policy.allow_oversell = true;
+
+        let diff = PortfolioDiff::between(&old, &new);
+        let fields: Vec<_> = diff.settings_changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["api_keys.finnhub", "default_currency", "validation_policy.allow_oversell"]
+        );
+        assert_eq!(diff.settings_changes[0].old, None);
+        assert_eq!(diff.settings_changes[0].new.as_deref(), Some("***"));
+        assert!(!serde_json::to_string(&diff).unwrap().contains("secret"));
+    }
+
+    #[test]
+    fn identical_portfolios_have_empty_diff() {
+        let mut portfolio = Portfolio::default();
+        portfolio.events.push(btc(1.0, d(2025, 1, 1)));
+
+        let diff = PortfolioDiff::between(&portfolio, &portfolio.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff, PortfolioDiff::default());
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(serde_json::from_str::<PortfolioDiff>(&json).unwrap(), diff);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  ThreeWayMerge
+// ═══════════════════════════════════════════════════════════════════
+
+mod three_way_merge {
+    use super::*;
+    use savings_tracker_core::models::sync::ThreeWayMerge;
+
+    #[test]
+    fn merges_settings_maps_per_key() {
+        let mut base = Portfolio::default();
+        base.settings.api_keys.insert("finnhub".into(), "old".into());
+        base.settings.api_keys.insert("metals_dev".into(), "metals".into());
+
+        let mut mine = base.clone();
+        mine.settings.api_keys.insert("finnhub".into(), "mine".into());
+        mine.settings
+            .symbol_overrides
+            .entry("coincap".into())
+            .or_default()
+            .insert("PEPE".into(), "pepe".into());
+
+        let mut theirs = base.clone();
+        theirs.settings.api_keys.insert("finnhub".into(), "theirs".into());
+        theirs.settings.api_keys.remove("metals_dev");
+        theirs.settings.metal_unit = MetalUnit::Gram;
+
+        let merge = ThreeWayMerge::compute(&base, &mine, &theirs);
+        let settings = &merge.settings;
+        assert_eq!(settings.api_keys.get("finnhub").map(String::as_str), Some("mine"));
+        assert!(!settings.api_keys.contains_key("metals_dev"));
+        assert_eq!(settings.symbol_overrides["coincap"]["PEPE"], "pepe");
+        assert_eq!(settings.metal_unit, MetalUnit::Gram);
+        assert_eq!(merge.report.settings_conflicts, vec!["api_keys.finnhub".to_string()]);
+        assert!(merge.events.is_empty());
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  Formatting
+// ═══════════════════════════════════════════════════════════════════
+
+mod formatting {
+    use super::*;
+    use savings_tracker_core::formatting::{
+        format_amount, format_date, format_money, format_number, format_pct, round_to, Locale,
+    };
+
+    #[test]
+    fn english_money() {
+        assert_eq!(format_money(1234567.891, "USD", Locale::En), "$1,234,567.89");
+        assert_eq!(format_money(-1234.5, "usd", Locale::En), "-$1,234.50");
+        assert_eq!(format_money(0.0, "EUR", Locale::En), "€0.00");
+        assert_eq!(format_money(-0.001, "USD", Locale::En), "$0.00");
+        assert_eq!(format_money(-1234.5, "PLN", Locale::En), "-PLN\u{a0}1,234.50");
+    }
+
+    #[test]
+    fn polish_money() {
+        assert_eq!(format_money(1234567.891, "PLN", Locale::Pl), "1\u{a0}234\u{a0}567,89\u{a0}zł");
+        // Four-digit numbers are not grouped in Polish
+        assert_eq!(format_money(1234.5, "PLN", Locale::Pl), "1234,50\u{a0}zł");
+        assert_eq!(format_money(-12345.0, "USD", Locale::Pl), "-12\u{a0}345,00\u{a0}USD");
+        assert_eq!(format_money(0.0, "PLN", Locale::Pl), "0,00\u{a0}zł");
+    }
+
+    #[test]
+    fn german_money() {
+        assert_eq!(format_money(1234567.891, "EUR", Locale::De), "1.234.567,89\u{a0}€");
+        assert_eq!(format_money(-1234.5, "EUR", Locale::De), "-1.234,50\u{a0}€");
+        assert_eq!(format_money(0.0, "USD", Locale::De), "0,00\u{a0}$");
+        assert_eq!(format_money(f64::NAN, "EUR", Locale::De), "n/a");
+    }
+
+    #[test]
+    fn decimals_follow_the_currency() {
+        assert_eq!(format_money(1234.6, "JPY", Locale::En), "¥1,235");
+        assert_eq!(format_money(-98765.4, "JPY", Locale::De), "-98.765\u{a0}¥");
+        assert_eq!(format_money(0.123456789, "BTC", Locale::En), "BTC\u{a0}0.12345679");
+        assert_eq!(format_money(1.5, "BTC", Locale::Pl), "1,50\u{a0}BTC");
+        assert_eq!(format_number(1234.5, 0, 3, Locale::De), "1.234,5");
+    }
+
+    #[test]
+    fn percentages() {
+        assert_eq!(format_pct(12.5, Locale::En), "12.50%");
+        assert_eq!(format_pct(-3.254, Locale::Pl), "-3,25%");
+        assert_eq!(format_pct(0.0, Locale::De), "0,00\u{a0}%");
+        assert_eq!(format_pct(123456.0, Locale::De), "123.456,00\u{a0}%");
+    }
+
+    #[test]
+    fn dates() {
+        let date = d(2025, 1, 15);
+        assert_eq!(format_date(date, Locale::En), "01/15/2025");
+        assert_eq!(format_date(date, Locale::Pl), "15.01.2025");
+        assert_eq!(format_date(date, Locale::De), "15.01.2025");
+    }
+
+    #[test]
+    fn asset_amounts_use_the_type_precision() {
+        assert_eq!(AssetType::Crypto.default_precision(), 8);
+        assert_eq!(AssetType::Stock.default_precision(), 4);
+        assert_eq!(AssetType::Metal.default_precision(), 4);
+        assert_eq!(AssetType::Fiat.default_precision(), 2);
+
+        assert_eq!(format_amount(0.123456789, 8, Locale::En), "0.12345679");
+        assert_eq!(format_amount(0.5, 8, Locale::En), "0.5");
+        assert_eq!(format_amount(1234.56789, 4, Locale::De), "1.234,5679");
+        assert_eq!(format_amount(10.0, 2, Locale::Pl), "10");
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(round_to(0.123456789, 8), 0.12345679);
+        assert_eq!(round_to(2.5, 0), 3.0);
+        assert_eq!(round_to(-1.005001, 2), -1.01);
+        assert_eq!(round_to(0.1 + 0.2, 2), 0.3);
+        assert_eq!(round_to(1e300, 12), 1e300);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  Provider Usage
+// ═══════════════════════════════════════════════════════════════════
+
+mod provider_usage {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use savings_tracker_core::models::usage::ProviderUsage;
+    use savings_tracker_core::services::api_usage::ApiUsageTracker;
+
+    #[test]
+    fn daily_count_restarts_at_utc_midnight() {
+        let mut usage = ProviderUsage::new("Alpha Vantage");
+        usage.record_call(Utc.with_ymd_and_hms(2025, 3, 1, 22, 0, 0).unwrap());
+        usage.record_call(Utc.with_ymd_and_hms(2025, 3, 1, 23, 59, 59).unwrap());
+        assert_eq!((usage.calls_today, usage.calls_total), (2, 2));
+        assert_eq!(usage.calls_on(d(2025, 3, 1)), 2);
+        assert_eq!(usage.calls_on(d(2025, 3, 2)), 0);
+        assert_eq!(usage.as_of(d(2025, 3, 2)).calls_today, 0);
+
+        let midnight = Utc.with_ymd_and_hms(2025, 3, 2, 0, 0, 0).unwrap();
+        usage.record_call(midnight);
+        assert_eq!((usage.calls_today, usage.calls_total), (1, 3));
+        assert_eq!(usage.last_call, Some(midnight));
+    }
+
+    #[test]
+    fn tracker_refuses_requests_over_the_daily_limit() {
+        let tracker = ApiUsageTracker::new();
+        let morning = Utc.with_ymd_and_hms(2025, 3, 1, 8, 0, 0).unwrap();
+        assert!(tracker.try_record("Alpha Vantage", Some(2), morning));
+        assert!(tracker.try_record("alpha vantage", Some(2), morning));
+        assert!(!tracker.try_record("Alpha Vantage", Some(2), morning));
+        assert!(tracker.try_record("Alpha Vantage", None, morning));
+
+        let usage = tracker.get("ALPHA VANTAGE", d(2025, 3, 1)).unwrap();
+        assert_eq!((usage.name.as_str(), usage.calls_today), ("Alpha Vantage", 3));
+
+        // A new day, a new allowance
+        let next_day = Utc.with_ymd_and_hms(2025, 3, 2, 8, 0, 0).unwrap();
+        assert!(tracker.try_record("Alpha Vantage", Some(2), next_day));
+        assert_eq!(tracker.snapshot()["alpha vantage"].calls_total, 4);
+        assert!(tracker.get("CoinCap", d(2025, 3, 2)).is_none());
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  Price Warnings
+// ═══════════════════════════════════════════════════════════════════
+
+mod price_warnings {
+    use super::*;
+    use chrono::Utc;
+    use savings_tracker_core::models::warning::{PriceWarning, PriceWarningReason};
+    use savings_tracker_core::services::price_warnings::PriceWarningLog;
+
+    fn warning(price: f64) -> PriceWarning {
+        PriceWarning {
+            at: Utc::now(),
+            provider: "CoinCap".into(),
+            symbol: "BTC".into(),
+            currency: "USD".into(),
+            date: d(2025, 3, 1),
+            price,
+            reason: PriceWarningReason::NotPositive,
+        }
+    }
+
+    #[test]
+    fn log_keeps_the_latest_warnings() {
+        let log = PriceWarningLog::with_capacity(2);
+        for price in [0.0, -1.0, -2.0] {
+            log.push(warning(price));
+        }
+        let prices: Vec<f64> = log.recent().iter().map(|w| w.price).collect();
+        assert_eq!(prices, vec![-1.0, -2.0]);
+        assert_eq!(
+            log.recent()[0].to_string(),
+            "CoinCap quoted BTC/USD at -1 on 2025-03-01: not a positive price"
+        );
+
+        log.clear();
+        assert!(log.recent().is_empty());
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  Cache Coverage
+// ═══════════════════════════════════════════════════════════════════
+
+mod cache_coverage {
+    use super::*;
+    use savings_tracker_core::models::coverage::DateSpan;
+
+    fn span(from: NaiveDate, to: NaiveDate) -> DateSpan {
+        DateSpan::new(from, to)
+    }
+
+    #[test]
+    fn crypto_gaps_between_cached_runs() {
+        let mut cache = PriceCache::new();
+        for day in [2, 3, 5] {
+            cache.set_price("btc", "usd", d(2025, 1, day), 40000.0, &AssetType::Crypto);
+        }
+
+        let coverage =
+            cache.coverage("BTC", "USD", d(2025, 1, 1), d(2025, 1, 6), &AssetType::Crypto);
+        assert_eq!(
+            coverage.covered,
+            vec![span(d(2025, 1, 2), d(2025, 1, 3)), span(d(2025, 1, 5), d(2025, 1, 5))]
+        );
+        assert_eq!(
+            coverage.gaps,
+            vec![
+                span(d(2025, 1, 1), d(2025, 1, 1)),
+                span(d(2025, 1, 4), d(2025, 1, 4)),
+                span(d(2025, 1, 6), d(2025, 1, 6)),
+            ]
+        );
+        assert_eq!(coverage.missing_days, 3);
+        assert!(!coverage.is_complete());
+    }
+
+    #[test]
+    fn uncached_series_is_one_gap() {
+        let cache = PriceCache::new();
+        let coverage =
+            cache.coverage("ETH", "USD", d(2025, 1, 1), d(2025, 1, 31), &AssetType::Crypto);
+        assert!(coverage.covered.is_empty());
+        assert_eq!(coverage.gaps, vec![span(d(2025, 1, 1), d(2025, 1, 31))]);
+        assert_eq!(coverage.missing_days, 31);
+    }
+
+    #[test]
+    fn stocks_skip_weekends_and_market_holidays() {
+        let mut cache = PriceCache::new();
+        // Mon 6th to Fri 10th, Mon 13th, then Wed 15th (Tue 14th: a holiday)
+        for day in [6, 7, 8, 9, 10, 13, 15] {
+            cache.set_price("AAPL", "USD", d(2025, 1, day), 230.0, &AssetType::Stock);
+        }
+
+        // Sat 4th to Sun 19th
+        let coverage =
+            cache.coverage("AAPL", "USD", d(2025, 1, 4), d(2025, 1, 19), &AssetType::Stock);
+        assert_eq!(coverage.covered, vec![span(d(2025, 1, 6), d(2025, 1, 15))]);
+        assert_eq!(coverage.gaps, vec![span(d(2025, 1, 16), d(2025, 1, 17))]);
+        assert_eq!(coverage.missing_days, 2);
+    }
+
+    #[test]
+    fn stock_gap_longer_than_a_holiday() {
+        let mut cache = PriceCache::new();
+        cache.set_price("AAPL", "USD", d(2025, 1, 6), 230.0, &AssetType::Stock);
+        cache.set_price("AAPL", "USD", d(2025, 1, 10), 235.0, &AssetType::Stock);
+
+        let coverage =
+            cache.coverage("AAPL", "USD", d(2025, 1, 6), d(2025, 1, 10), &AssetType::Stock);
+        assert_eq!(coverage.gaps, vec![span(d(2025, 1, 7), d(2025, 1, 9))]);
+        assert_eq!(coverage.missing_days, 3);
+        assert_eq!(coverage.covered.len(), 2);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+//  Month Closes
+// ═══════════════════════════════════════════════════════════════════
+
+mod month_closes {
+    use super::*;
+    use savings_tracker_core::models::price::PriceResolution;
+
+    #[test]
+    fn close_stands_in_for_its_month_only() {
+        let mut cache = PriceCache::new();
+        let crypto = AssetType::Crypto;
+        cache.set_price("BTC", "USD", d(2015, 3, 2), 260.0, &crypto);
+        cache.set_monthly_price("BTC", "USD", d(2015, 3, 31), 244.0, &crypto);
+
+        let close = cache.get_month_price("btc", "usd", d(2015, 3, 15), &crypto).unwrap();
+        assert_eq!((close.date, close.price), (d(2015, 3, 31), 244.0));
+        assert_eq!(cache.get_month_price("BTC", "USD", d(2015, 4, 1), &crypto), None);
+        assert_eq!(cache.get_price("BTC", "USD", d(2015, 3, 15), &crypto), None);
+
+        let entry = cache.get_entry("BTC", "USD", d(2015, 3, 31), &crypto).unwrap();
+        assert_eq!(entry.resolution, PriceResolution::Monthly);
+        let entry = cache.get_entry("BTC", "USD", d(2015, 3, 2), &crypto).unwrap();
+        assert_eq!(entry.resolution, PriceResolution::Daily);
+    }
+
+    #[test]
+    fn daily_quote_replaces_a_close() {
+        let mut cache = PriceCache::new();
+        let crypto = AssetType::Crypto;
+        cache.set_monthly_price("BTC", "USD", d(2015, 3, 31), 244.0, &crypto);
+        cache.set_price("BTC", "USD", d(2015, 3, 31), 245.0, &crypto);
+
+        assert_eq!(cache.get_month_price("BTC", "USD", d(2015, 3, 15), &crypto), None);
+        assert_eq!(cache.resolution("BTC", "USD", d(2015, 3, 31), &crypto), PriceResolution::Daily);
+    }
+
+    #[test]
+    fn old_caches_deserialize_as_daily() {
+        let mut cache = PriceCache::new();
+        cache.set_price("BTC", "USD", d(2015, 3, 31), 244.0, &AssetType::Crypto);
+        let mut json: serde_json::Value = serde_json::to_value(&cache).unwrap();
+        json.as_object_mut().unwrap().remove("monthly");
+
+        let loaded: PriceCache = serde_json::from_value(json).unwrap();
+        let resolution = loaded.resolution("BTC", "USD", d(2015, 3, 31), &AssetType::Crypto);
+        assert_eq!(resolution, PriceResolution::Daily);
+    }
 }
@@ -0,0 +1,266 @@
+// ═══════════════════════════════════════════════════════════════════
+// Property Tests — invariants of random event operation sequences
+// ═══════════════════════════════════════════════════════════════════
+//
+// Random sequences of add / update / remove / bulk update operations
+// over a few assets and dates, applied through `PortfolioService`.
+// Amounts are multiples of 1/4 (and bulk scaling keeps them dyadic), so
+// every sum is exact and the balances can be compared without tolerance.
+//
+// The case count is kept low so CI stays fast; run more locally with e.g.
+// `PROPTEST_CASES=5000 cargo test --test property_tests`.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use uuid::Uuid;
+
+use savings_tracker_core::models::asset::{Asset, AssetType};
+use savings_tracker_core::models::event::{Event, EventChanges, EventFilter, EventType};
+use savings_tracker_core::models::portfolio::Portfolio;
+use savings_tracker_core::services::portfolio_service::PortfolioService;
+
+const CASES: u32 = 256;
+
+fn config() -> ProptestConfig {
+    let cases = std::env::var("PROPTEST_CASES")
+        .ok()
+        .and_then(|cases| cases.parse().ok())
+        .unwrap_or(CASES);
+    ProptestConfig::with_cases(cases)
+}
+
+fn assets() -> Vec<Asset> {
+    vec![
+        Asset::crypto("BTC", "Bitcoin"),
+        Asset::crypto("ETH", "Ethereum"),
+        Asset::metal("XAU", "Gold"),
+        Asset::fiat("USD", "US Dollar"),
+    ]
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    Add { event_type: EventType, asset: usize, amount: f64, day: u32 },
+    Update { target: Index, event_type: EventType, asset: usize, amount: f64, day: u32 },
+    Remove { target: Index },
+    BulkUpdate { asset: usize, change: BulkChange },
+}
+
+#[derive(Debug, Clone)]
+enum BulkChange {
+    Rename(usize),
+    ShiftDays(i64),
+    Scale(f64),
+}
+
+fn event_type() -> impl Strategy<Value = EventType> {
+    prop_oneof![
+        3 => Just(EventType::Buy),
+        3 => Just(EventType::Sell),
+        1 => Just(EventType::Deposit),
+        1 => Just(EventType::Withdrawal),
+        1 => Just(EventType::Interest),
+    ]
+}
+
+/// 0.25 to 25 in steps of 0.25.
+fn amount() -> impl Strategy<Value = f64> {
+    (1u32..=100).prop_map(|quarters| f64::from(quarters) / 4.0)
+}
+
+/// Days after 2024-01-01, all in the past.
+fn day() -> impl Strategy<Value = u32> {
+    0u32..120
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let asset = 0..assets().len();
+    prop_oneof![
+        4 => (event_type(), asset.clone(), amount(), day()).prop_map(
+            |(event_type, asset, amount, day)| Op::Add { event_type, asset, amount, day }
+        ),
+        2 => (any::<Index>(), event_type(), asset.clone(), amount(), day()).prop_map(
+            |(target, event_type, asset, amount, day)| Op::Update {
+                target, event_type, asset, amount, day
+            }
+        ),
+        2 => any::<Index>().prop_map(|target| Op::Remove { target }),
+        1 => (
+            asset.clone(),
+            prop_oneof![
+                asset.prop_map(BulkChange::Rename),
+                (-30i64..=30).prop_map(BulkChange::ShiftDays),
+                prop::sample::select(vec![0.5, 1.5, 2.0]).prop_map(BulkChange::Scale),
+            ],
+        )
+            .prop_map(|(asset, change)| Op::BulkUpdate { asset, change }),
+    ]
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Days::new(day.into())
+}
+
+/// The id of the `target`th event, or a random id (which must be
+/// rejected) if there are none.
+fn target_id(portfolio: &Portfolio, target: &Index) -> Uuid {
+    if portfolio.events.is_empty() {
+        Uuid::new_v4()
+    } else {
+        portfolio.events[target.index(portfolio.events.len())].id
+    }
+}
+
+fn apply(service: &PortfolioService, portfolio: &mut Portfolio, op: &Op) -> bool {
+    let assets = assets();
+    let result = match op {
+        Op::Add { event_type, asset, amount, day } => service.add_event(
+            portfolio,
+            Event::new(event_type.clone(), assets[*asset].clone(), *amount, date(*day)),
+        ),
+        Op::Update { target, event_type, asset, amount, day } => service.update_event(
+            portfolio,
+            target_id(portfolio, target),
+            event_type.clone(),
+            assets[*asset].clone(),
+            *amount,
+            date(*day),
+        ),
+        Op::Remove { target } => service.remove_event(portfolio, target_id(portfolio, target)),
+        Op::BulkUpdate { asset, change } => {
+            let changes = match change {
+                BulkChange::Rename(to) => EventChanges {
+                    new_asset: Some(assets[*to].clone()),
+                    ..Default::default()
+                },
+                BulkChange::ShiftDays(days) => EventChanges {
+                    shift_date_days: Some(*days),
+                    ..Default::default()
+                },
+                BulkChange::Scale(factor) => EventChanges {
+                    scale_amount: Some(*factor),
+                    ..Default::default()
+                },
+            };
+            let filter = EventFilter::for_symbol(&assets[*asset].symbol);
+            service.bulk_update_events(portfolio, &filter, &changes).map(|_| ())
+        }
+    };
+    result.is_ok()
+}
+
+/// Balance of every asset after each date with events, summed from the
+/// events directly rather than through the holdings index. Same-day
+/// events count together, as they do when a sell is validated.
+fn balances_by_date(portfolio: &Portfolio) -> Vec<(NaiveDate, HashMap<Asset, f64>)> {
+    let mut balances: HashMap<Asset, f64> = HashMap::new();
+    let mut by_date = Vec::new();
+    let mut current = None;
+    for event in portfolio.actual_events() {
+        if let Some(date) = current.filter(|date| *date != event.date) {
+            by_date.push((date, balances.clone()));
+        }
+        current = Some(event.date);
+        let delta = if event.event_type.increases_holding() { event.amount } else { -event.amount };
+        *balances.entry(event.asset.clone()).or_default() += delta;
+    }
+    if let Some(date) = current {
+        by_date.push((date, balances));
+    }
+    by_date
+}
+
+fn check_holdings(service: &PortfolioService, portfolio: &Portfolio) -> Result<(), TestCaseError> {
+    for (date, balances) in balances_by_date(portfolio) {
+        for (asset, amount) in &balances {
+            prop_assert!(*amount >= 0.0, "{} {} held on {}", amount, asset.symbol, date);
+        }
+        let expected: HashMap<Asset, f64> =
+            balances.into_iter().filter(|(_, amount)| *amount > 0.0).collect();
+        prop_assert_eq!(service.get_holdings(portfolio, date), expected, "holdings on {}", date);
+    }
+    Ok(())
+}
+
+/// Whether adding `event` would leave a negative balance on some date.
+fn overdraws(portfolio: &Portfolio, event: &Event) -> bool {
+    let mut with_event = portfolio.clone();
+    with_event.events.push(event.clone());
+    with_event.events.sort_by_key(|e| e.date);
+    with_event.mark_events_changed();
+    balances_by_date(&with_event)
+        .iter()
+        .any(|(_, balances)| balances.values().any(|amount| *amount < 0.0))
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn accepted_operations_never_leave_negative_holdings(ops in prop::collection::vec(op(), 1..40)) {
+        let service = PortfolioService::new();
+        let mut portfolio = Portfolio::default();
+        for op in &ops {
+            if apply(&service, &mut portfolio, op) {
+                check_holdings(&service, &portfolio)?;
+            }
+        }
+    }
+
+    #[test]
+    fn adds_are_rejected_only_when_they_would_overdraw(ops in prop::collection::vec(op(), 1..40)) {
+        let service = PortfolioService::new();
+        let mut portfolio = Portfolio::default();
+        let assets = assets();
+        for op in &ops {
+            let expected = match op {
+                Op::Add { event_type, asset, amount, day } => {
+                    let asset = &assets[*asset];
+                    let event = Event::new(event_type.clone(), asset.clone(), *amount, date(*day));
+                    let cash_flow_of_non_fiat =
+                        event_type.is_cash_flow() && asset.asset_type != AssetType::Fiat;
+                    Some(!(cash_flow_of_non_fiat || overdraws(&portfolio, &event)))
+                }
+                _ => None,
+            };
+            let accepted = apply(&service, &mut portfolio, op);
+            if let Some(expected) = expected {
+                prop_assert_eq!(accepted, expected, "{:?}", op);
+            }
+        }
+    }
+
+    #[test]
+    fn rejected_operations_leave_the_portfolio_unchanged(ops in prop::collection::vec(op(), 1..40)) {
+        let service = PortfolioService::new();
+        let mut portfolio = Portfolio::default();
+        for op in &ops {
+            let events = portfolio.events.clone();
+            let hash = portfolio.content_hash();
+            if !apply(&service, &mut portfolio, op) {
+                prop_assert_eq!(&portfolio.events, &events, "after rejected {:?}", op);
+                prop_assert_eq!(portfolio.content_hash(), hash);
+            }
+        }
+    }
+
+    #[test]
+    fn serialization_round_trip_preserves_the_events(ops in prop::collection::vec(op(), 1..40)) {
+        let service = PortfolioService::new();
+        let mut portfolio = Portfolio::default();
+        for op in &ops {
+            apply(&service, &mut portfolio, op);
+        }
+
+        // The payload encoding `StorageManager::save_to_bytes` encrypts;
+        // the encryption itself is covered by the storage tests.
+        let payload = rmp_serde::to_vec_named(&portfolio).unwrap();
+        let loaded: Portfolio = rmp_serde::from_slice(&payload).unwrap();
+        prop_assert_eq!(&loaded.events, &portfolio.events);
+        prop_assert_eq!(loaded.content_hash(), portfolio.content_hash());
+        check_holdings(&service, &loaded)?;
+    }
+}
@@ -9,11 +9,16 @@ use std::collections::HashMap;
 use savings_tracker_core::errors::CoreError;
 use savings_tracker_core::models::asset::AssetType;
 use savings_tracker_core::models::price::PricePoint;
+use savings_tracker_core::models::settings::Settings;
+use savings_tracker_core::providers::alphavantage::AlphaVantageProvider;
 use savings_tracker_core::providers::coincap::CoinCapProvider;
-use savings_tracker_core::providers::frankfurter::FrankfurterProvider;
-use savings_tracker_core::providers::metals_dev::MetalsDevProvider;
+use savings_tracker_core::providers::frankfurter::{
+    FrankfurterProvider, MAX_TIMESERIES_DAYS as FRANKFURTER_MAX_DAYS,
+};
+use savings_tracker_core::providers::metals_dev::{MetalsDevProvider, MAX_TIMESERIES_DAYS};
 use savings_tracker_core::providers::registry::PriceProviderRegistry;
 use savings_tracker_core::providers::traits::PriceProvider;
+use savings_tracker_core::providers::yahoo_chart::YahooChartProvider;
 use savings_tracker_core::providers::yahoo_finance::YahooFinanceProvider;
 
 // ═══════════════════════════════════════════════════════════════════
@@ -99,6 +104,7 @@ impl PriceProvider for FailingProvider {
     async fn get_current_price(&self, sym: &str, cur: &str) -> Result<f64, CoreError> {
         Err(CoreError::Api {
             provider: self.name.clone(),
+            status: None,
             message: format!("Failed for {sym}/{cur}"),
         })
     }
@@ -111,6 +117,7 @@ impl PriceProvider for FailingProvider {
     ) -> Result<f64, CoreError> {
         Err(CoreError::Api {
             provider: self.name.clone(),
+            status: None,
             message: format!("Failed historical for {sym}/{cur}"),
         })
     }
@@ -124,6 +131,7 @@ impl PriceProvider for FailingProvider {
     ) -> Result<Vec<PricePoint>, CoreError> {
         Err(CoreError::Api {
             provider: self.name.clone(),
+            status: None,
             message: "Failed range".into(),
         })
     }
@@ -199,6 +207,74 @@ mod registry_construction {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════
+// PriceProviderRegistry — listing, cloning, extending
+// ═══════════════════════════════════════════════════════════════════
+
+mod registry_listing {
+    use super::*;
+    use savings_tracker_core::providers::registry::ProviderInfo;
+
+    #[test]
+    fn list_providers_in_registration_order() {
+        let mut registry = PriceProviderRegistry::new();
+        registry.register(Box::new(MockProvider::new("A", vec![AssetType::Crypto])));
+        registry.register(Box::new(MockProvider::new(
+            "B",
+            vec![AssetType::Fiat, AssetType::Stock],
+        )));
+
+        assert_eq!(
+            registry.list_providers(),
+            vec![
+                ProviderInfo {
+                    name: "A".into(),
+                    supported_asset_types: vec![AssetType::Crypto],
+                },
+                ProviderInfo {
+                    name: "B".into(),
+                    supported_asset_types: vec![AssetType::Fiat, AssetType::Stock],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn len_and_is_empty() {
+        let mut registry = PriceProviderRegistry::new();
+        assert!(registry.is_empty());
+        registry.register(Box::new(MockProvider::new("A", vec![AssetType::Crypto])));
+        assert_eq!(registry.len(), 1);
+        assert!(!registry.is_empty());
+    }
+
+    #[test]
+    fn clone_shares_providers() {
+        let mut registry = PriceProviderRegistry::new();
+        registry.register(Box::new(MockProvider::new("A", vec![AssetType::Crypto])));
+        let cloned = registry.clone();
+        assert_eq!(cloned.list_providers(), registry.list_providers());
+    }
+
+    #[test]
+    fn extend_appends_after_existing() {
+        let mut first = PriceProviderRegistry::new();
+        first.register(Box::new(MockProvider::new("First", vec![AssetType::Crypto])));
+        let mut second = PriceProviderRegistry::new();
+        second.register(Box::new(MockProvider::new("Second", vec![AssetType::Crypto])));
+
+        first.extend(&second);
+
+        let names: Vec<&str> = first
+            .get_providers_for(&AssetType::Crypto)
+            .iter()
+            .map(|p| p.name())
+            .collect();
+        assert_eq!(names, vec!["First", "Second"]);
+        assert_eq!(second.len(), 1);
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // PriceProviderRegistry — get_provider_for
 // ═══════════════════════════════════════════════════════════════════
@@ -351,6 +427,25 @@ mod registry_defaults {
         assert_eq!(stock_providers.len(), 2);
     }
 
+    #[test]
+    fn with_alphavantage_key_has_fiat_and_crypto_fallback() {
+        let mut keys = HashMap::new();
+        keys.insert("alphavantage".to_string(), "av-key".to_string());
+        let registry = PriceProviderRegistry::new_with_defaults(&keys);
+
+        for (
+            asset_type,
+            primary,
+        ) in [(AssetType::Crypto, "CoinCap"), (AssetType::Fiat, "Frankfurter")] {
+            let names: Vec<&str> = registry
+                .get_providers_for(&asset_type)
+                .iter()
+                .map(|p| p.name())
+                .collect();
+            assert_eq!(names, vec![primary, "Alpha Vantage"]);
+        }
+    }
+
     #[test]
     fn without_alphavantage_key_only_yahoo() {
         let keys = HashMap::new();
@@ -458,6 +553,43 @@ mod coincap {
         assert_eq!(provider.resolve_id("UNKNOWN"), "unknown");
     }
 
+    #[test]
+    fn symbol_overrides_take_precedence() {
+        let overrides = HashMap::from([
+            ("pepe".to_string(), "pepe-token".to_string()),
+            ("BTC".to_string(), "wrapped-bitcoin".to_string()),
+        ]);
+        let provider = CoinCapProvider::with_symbol_overrides(&overrides);
+        assert_eq!(provider.resolve_id("PEPE"), "pepe-token");
+        assert_eq!(provider.resolve_id("btc"), "wrapped-bitcoin");
+        assert_eq!(provider.resolve_id("ETH"), "ethereum");
+        assert!(provider.learned_symbol_ids().is_empty());
+    }
+
+    #[test]
+    fn search_picks_best_ranked_exact_symbol() {
+        let body = r#"{"data": [
+            {"id": "pepecoin-network", "symbol": "PEPECOIN", "name": "PepeCoin", "rank": "900"},
+            {"id": "pepe-fork", "symbol": "PEPE", "name": "Pepe Fork", "rank": "1500"},
+            {"id": "pepe", "symbol": "pepe", "name": "Pepe", "rank": "40"}
+        ]}"#;
+        assert_eq!(CoinCapProvider::parse_search_response(body, "Pepe").unwrap(), "pepe");
+    }
+
+    #[test]
+    fn search_without_exact_symbol_is_not_mapped() {
+        let body = r#"{"data": [{"id": "pepecoin-network", "symbol": "PEPECOIN", "rank": "900"}]}"#;
+        let err = CoinCapProvider::parse_search_response(body, "pepe").unwrap_err();
+        assert!(matches!(
+            err,
+            CoreError::SymbolNotMapped { ref provider, ref symbol }
+                if provider == "CoinCap" && symbol == "PEPE"
+        ));
+
+        let err = CoinCapProvider::parse_search_response("{}", "PEPE").unwrap_err();
+        assert!(matches!(err, CoreError::Api { .. }));
+    }
+
     #[test]
     fn resolve_id_all_common_symbols() {
         let provider = CoinCapProvider::new();
@@ -519,6 +651,41 @@ mod frankfurter {
         let provider = FrankfurterProvider::default();
         assert_eq!(provider.name(), "Frankfurter");
     }
+
+    #[test]
+    fn parse_timeseries_nested_rates() {
+        let body = r#"{"amount": 1.0, "base": "EUR", "start_date": "2025-01-02",
+            "end_date": "2025-01-06", "rates": {
+                "2025-01-06": {"PLN": 4.2635, "USD": 1.0412},
+                "2025-01-02": {"PLN": 4.2718},
+                "2025-01-03": {"USD": 1.0309}
+            }}"#;
+        let points = FrankfurterProvider::parse_timeseries(body, "EUR", "pln").unwrap();
+        let d = |day| NaiveDate::from_ymd_opt(2025, 1, day).unwrap();
+        assert_eq!(
+            points,
+            vec![
+                PricePoint { date: d(2), price: 4.2718 },
+                PricePoint { date: d(6), price: 4.2635 },
+            ]
+        );
+        assert!(matches!(
+            FrankfurterProvider::parse_timeseries(r#"{"message": "not found"}"#, "EUR", "PLN"),
+            Err(CoreError::Api { .. })
+        ));
+    }
+
+    #[test]
+    fn timeseries_windows_split_long_ranges() {
+        let from = NaiveDate::from_ymd_opt(2023, 12, 25).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let windows = FrankfurterProvider::timeseries_windows(from, to);
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].0, from);
+        assert_eq!((windows[0].1 - windows[0].0).num_days(), FRANKFURTER_MAX_DAYS - 1);
+        assert_eq!(windows[0].1.succ_opt().unwrap(), windows[1].0);
+        assert_eq!(windows[1].1, to);
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -545,6 +712,63 @@ mod metals_dev {
         assert_eq!(types, vec![AssetType::Metal]);
     }
 
+    fn day(y: i32, m: u32, dd: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, dd).unwrap()
+    }
+
+    #[test]
+    fn timeseries_windows_cover_range_without_overlap() {
+        let from = day(2025, 1, 1);
+        let to = day(2025, 3, 6); // 65 days
+        let windows = MetalsDevProvider::timeseries_windows(from, to);
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0], (from, day(2025, 1, 30)));
+        assert_eq!(windows[2].1, to);
+        for pair in windows.windows(2) {
+            assert_eq!(pair[0].1.succ_opt().unwrap(), pair[1].0);
+        }
+        for (start, end) in &windows {
+            assert!((*end - *start).num_days() < MAX_TIMESERIES_DAYS);
+        }
+
+        assert_eq!(MetalsDevProvider::timeseries_windows(from, from), vec![(from, from)]);
+        assert!(MetalsDevProvider::timeseries_windows(to, from).is_empty());
+    }
+
+    #[test]
+    fn parse_timeseries_rates_shape() {
+        let body = r#"{"status": "success", "currency": "USD", "unit": "toz", "rates": {
+            "2025-01-03": {"date": "2025-01-03", "metals": {"gold": 2640.5, "silver": 29.7}},
+            "2025-01-02": {"date": "2025-01-02", "metals": {"gold": 2658.1, "silver": 29.9}}
+        }}"#;
+        let points = MetalsDevProvider::parse_timeseries(body, "gold").unwrap();
+        assert_eq!(
+            points,
+            vec![
+                PricePoint { date: day(2025, 1, 2), price: 2658.1 },
+                PricePoint { date: day(2025, 1, 3), price: 2640.5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_timeseries_legacy_shape() {
+        let body = r#"{"gold": [{"date": "2025-01-02", "price": 2658.1}]}"#;
+        let points = MetalsDevProvider::parse_timeseries(body, "gold").unwrap();
+        assert_eq!(points, vec![PricePoint { date: day(2025, 1, 2), price: 2658.1 }]);
+    }
+
+    #[test]
+    fn parse_timeseries_failure_is_api_error() {
+        let body = r#"{"status": "failure", "error_code": 1203, "error_message": "Date range too long"}"#;
+        let err = MetalsDevProvider::parse_timeseries(body, "gold").unwrap_err();
+        assert!(matches!(
+            err,
+            CoreError::Api { ref message, .. } if message == "Date range too long"
+        ));
+        assert!(MetalsDevProvider::parse_timeseries("not json", "gold").is_err());
+    }
+
     #[test]
     fn resolve_xau() {
         let provider = make_provider();
@@ -587,7 +811,7 @@ mod metals_dev {
         let result = provider.resolve_metal_name("XYZ");
         assert!(result.is_err());
         match result.unwrap_err() {
-            CoreError::Api { provider, message } => {
+            CoreError::Api { provider, message, .. } => {
                 assert_eq!(provider, "metals.dev");
                 assert!(message.contains("Unknown metal symbol"));
                 assert!(message.contains("XYZ"));
@@ -603,6 +827,230 @@ mod metals_dev {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════
+// AlphaVantageProvider — response parsing (canned JSON)
+// ═══════════════════════════════════════════════════════════════════
+
+mod alphavantage {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    #[test]
+    fn supported_types() {
+        let provider = AlphaVantageProvider::new("key".into());
+        assert_eq!(provider.name(), "Alpha Vantage");
+        assert_eq!(
+            provider.supported_asset_types(),
+            vec![AssetType::Stock, AssetType::Fiat, AssetType::Crypto]
+        );
+    }
+
+    #[test]
+    fn parse_stock_daily_series() {
+        let body = r#"{
+            "Meta Data": {"1. Information": "Daily Prices", "2. Symbol": "IBM"},
+            "Time Series (Daily)": {
+                "2025-01-16": {"1. open": "220.0", "4. close": "221.50", "5. volume": "100"},
+                "2025-01-15": {"1. open": "218.0", "4. close": "219.25", "5. volume": "120"}
+            }
+        }"#;
+        let points = AlphaVantageProvider::parse_daily_series(body, "IBM", "USD").unwrap();
+        assert_eq!(
+            points,
+            vec![
+                PricePoint { date: d(2025, 1, 15), price: 219.25 },
+                PricePoint { date: d(2025, 1, 16), price: 221.5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_fx_daily_series() {
+        let body = r#"{
+            "Meta Data": {"1. Information": "Forex Daily Prices", "2. From Symbol": "EUR", "3. To Symbol": "PLN"},
+            "Time Series FX (Daily)": {
+                "2025-01-15": {"1. open": "4.2601", "2. high": "4.2700", "3. low": "4.2500", "4. close": "4.2655"}
+            }
+        }"#;
+        let points = AlphaVantageProvider::parse_daily_series(body, "EUR", "PLN").unwrap();
+        assert_eq!(points, vec![PricePoint { date: d(2025, 1, 15), price: 4.2655 }]);
+    }
+
+    #[test]
+    fn parse_digital_currency_daily_series() {
+        let body = r#"{
+            "Meta Data": {"2. Digital Currency Code": "BTC", "4. Market Code": "USD"},
+            "Time Series (Digital Currency Daily)": {
+                "2025-01-15": {"1. open": "96000.00", "4. close": "100504.49", "5. volume": "12.3"}
+            }
+        }"#;
+        let points = AlphaVantageProvider::parse_daily_series(body, "BTC", "USD").unwrap();
+        assert_eq!(points, vec![PricePoint { date: d(2025, 1, 15), price: 100504.49 }]);
+    }
+
+    #[test]
+    fn parse_legacy_digital_currency_market_close() {
+        let body = r#"{
+            "Time Series (Digital Currency Daily)": {
+                "2023-05-01": {"4a. close (EUR)": "25000.5", "4b. close (USD)": "27500.1"}
+            }
+        }"#;
+        let points = AlphaVantageProvider::parse_daily_series(body, "BTC", "eur").unwrap();
+        assert_eq!(points, vec![PricePoint { date: d(2023, 5, 1), price: 25000.5 }]);
+    }
+
+    #[test]
+    fn parse_series_skips_malformed_days() {
+        let body = r#"{
+            "Time Series (Daily)": {
+                "2025-01-15": {"4. close": "not-a-number"},
+                "bad-date": {"4. close": "1.0"},
+                "2025-01-16": {"4. close": "10.0"}
+            }
+        }"#;
+        let points = AlphaVantageProvider::parse_daily_series(body, "X", "USD").unwrap();
+        assert_eq!(points, vec![PricePoint { date: d(2025, 1, 16), price: 10.0 }]);
+    }
+
+    #[test]
+    fn parse_series_without_data_is_api_error() {
+        let result = AlphaVantageProvider::parse_daily_series(r#"{"Meta Data": {}}"#, "X", "USD");
+        assert!(matches!(result, Err(CoreError::Api { status: None, .. })));
+    }
+
+    #[test]
+    fn parse_global_quote() {
+        let body = r#"{"Global Quote": {"01. symbol": "IBM", "05. price": "221.5000"}}"#;
+        assert_eq!(AlphaVantageProvider::parse_global_quote(body, "IBM").unwrap(), 221.5);
+    }
+
+    #[test]
+    fn parse_exchange_rate() {
+        let body = r#"{
+            "Realtime Currency Exchange Rate": {
+                "1. From_Currency Code": "BTC",
+                "3. To_Currency Code": "USD",
+                "5. Exchange Rate": "100504.49000000"
+            }
+        }"#;
+        assert_eq!(AlphaVantageProvider::parse_exchange_rate(body, "BTC").unwrap(), 100504.49);
+    }
+
+    #[test]
+    fn rate_limit_notice_is_transient() {
+        let notice = r#"{"Information": "Thank you for using Alpha Vantage! Our standard API rate limit is 25 requests per day."}"#;
+        let note = r#"{"Note": "API call frequency is 5 calls per minute."}"#;
+        for body in [notice, note] {
+            for err in [
+                AlphaVantageProvider::parse_daily_series(body, "X", "USD").unwrap_err(),
+                AlphaVantageProvider::parse_global_quote(body, "X").unwrap_err(),
+                AlphaVantageProvider::parse_exchange_rate(body, "X").unwrap_err(),
+            ] {
+                assert!(err.is_transient(), "{err:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_key_notice_is_invalid_api_key() {
+        let body = r#"{"Information": "the parameter apikey is invalid or missing."}"#;
+        assert!(matches!(
+            AlphaVantageProvider::parse_exchange_rate(body, "EUR"),
+            Err(CoreError::InvalidApiKey { .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_json_is_api_error() {
+        assert!(matches!(
+            AlphaVantageProvider::parse_global_quote("<html>", "IBM"),
+            Err(CoreError::Api { .. })
+        ));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// YahooChartProvider — chart API parsing (canned JSON)
+// ═══════════════════════════════════════════════════════════════════
+
+mod yahoo_chart {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    // 2025-01-14, 2025-01-15, 2025-01-16 (14:30 UTC market open)
+    const CHART: &str = r#"{"chart": {"result": [{
+        "meta": {"currency": "USD", "symbol": "AAPL", "regularMarketPrice": 229.98},
+        "timestamp": [1736865000, 1736951400, 1737037800],
+        "indicators": {"quote": [{"open": [1, 2, 3], "close": [233.28, 237.87, null]}]}
+    }], "error": null}}"#;
+
+    #[test]
+    fn supported_types() {
+        let provider = YahooChartProvider::new();
+        assert_eq!(provider.name(), "Yahoo Finance");
+        assert_eq!(provider.supported_asset_types(), vec![AssetType::Stock]);
+        assert_eq!(provider.base_url(), "https://query1.finance.yahoo.com");
+    }
+
+    #[test]
+    fn proxy_url_trailing_slash_is_trimmed() {
+        let provider = YahooChartProvider::with_proxy(" https://proxy.example/yahoo/ ");
+        assert_eq!(provider.base_url(), "https://proxy.example/yahoo");
+    }
+
+    #[test]
+    fn parse_chart_skips_missing_closes() {
+        let points = YahooChartProvider::parse_chart(CHART, "AAPL").unwrap();
+        assert_eq!(
+            points,
+            vec![
+                PricePoint { date: d(2025, 1, 14), price: 233.28 },
+                PricePoint { date: d(2025, 1, 15), price: 237.87 },
+            ]
+        );
+    }
+
+    #[test]
+    fn latest_price_prefers_regular_market_price() {
+        assert_eq!(YahooChartProvider::parse_latest_price(CHART, "AAPL").unwrap(), 229.98);
+
+        let without_meta = CHART.replace(r#""regularMarketPrice": 229.98"#, r#""x": 0"#);
+        let price = YahooChartProvider::parse_latest_price(&without_meta, "AAPL").unwrap();
+        assert_eq!(price, 237.87);
+    }
+
+    #[test]
+    fn chart_error_is_api_error() {
+        let body = r#"{"chart": {"result": null, "error": {
+            "code": "Not Found", "description": "No data found, symbol may be delisted"
+        }}}"#;
+        let err = YahooChartProvider::parse_chart(body, "NOPE").unwrap_err();
+        assert!(matches!(
+            err,
+            CoreError::Api { ref provider, ref message, .. }
+                if provider == "Yahoo Finance" && message.contains("delisted")
+        ));
+    }
+
+    #[test]
+    fn malformed_body_is_api_error() {
+        assert!(matches!(
+            YahooChartProvider::parse_chart("<html>", "AAPL"),
+            Err(CoreError::Api { .. })
+        ));
+        assert!(matches!(
+            YahooChartProvider::parse_latest_price(r#"{"chart": {"result": []}}"#, "AAPL"),
+            Err(CoreError::Api { .. })
+        ));
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // YahooFinanceProvider
 // ═══════════════════════════════════════════════════════════════════
@@ -622,6 +1070,537 @@ mod yahoo_finance {
         let types = provider.supported_asset_types();
         assert_eq!(types, vec![AssetType::Stock]);
     }
+
+    #[test]
+    fn fetch_errors_keep_the_http_status() {
+        use yahoo_finance_api::YahooError;
+        let error = |e| YahooFinanceProvider::fetch_error(e, "Failed".into());
+
+        let limited = error(YahooError::TooManyRequests("chart".into()));
+        assert!(matches!(limited, CoreError::Api { status: Some(429), .. }));
+        assert!(limited.is_transient());
+        let down = error(YahooError::FetchFailed("HTTP error: 503 Service Unavailable".into()));
+        assert!(matches!(down, CoreError::Api { status: Some(503), .. }));
+        assert!(down.is_transient());
+
+        let no_data = error(YahooError::NoQuotes);
+        assert!(matches!(no_data, CoreError::Api { status: None, .. }));
+        assert!(!no_data.is_transient());
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Request timeouts
+// ═══════════════════════════════════════════════════════════════════
+
+mod request_timeouts {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Accept connections but never answer them.
+    async fn silent_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn unanswered_request_times_out() {
+        let url = silent_server().await;
+        let provider =
+            YahooChartProvider::with_proxy(&url).with_timeout(Duration::from_millis(200));
+
+        let started = Instant::now();
+        let err = provider.get_current_price("AAPL", "USD").await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(err.is_timeout(), "unexpected error: {err}");
+        assert!(err.is_transient());
+        assert!(matches!(
+            err,
+            CoreError::Api { ref provider, status: Some(408), .. } if provider == "Yahoo Finance"
+        ));
+    }
+
+    /// Proxy that records the first line of each request and closes the connection.
+    async fn recording_proxy() -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0; 1024];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let _ = tx.send(request.lines().next().unwrap_or_default().to_string());
+            }
+        });
+        (format!("http://{addr}"), rx)
+    }
+
+    #[tokio::test]
+    async fn registry_providers_connect_through_the_proxy() {
+        let (url, mut requests) = recording_proxy().await;
+        let settings = Settings { proxy_url: Some(url), ..Settings::default() };
+        let registry = PriceProviderRegistry::new_with_settings(&settings);
+        let coincap = registry.get_provider_for(&AssetType::Crypto).unwrap();
+
+        assert!(coincap.get_current_price("BTC", "USD").await.is_err());
+        let request = requests.recv().await.unwrap();
+        assert!(request.starts_with("CONNECT "), "unexpected request: {request}");
+        assert!(request.contains("coincap"), "unexpected request: {request}");
+    }
+
+    #[test]
+    fn every_provider_accepts_a_timeout() {
+        let timeout = Duration::from_secs(3);
+        let mut registry = PriceProviderRegistry::new();
+        registry.register(Box::new(CoinCapProvider::new().with_timeout(timeout)));
+        registry.register(Box::new(FrankfurterProvider::new().with_timeout(timeout)));
+        registry.register(Box::new(MetalsDevProvider::new("k".into()).with_timeout(timeout)));
+        registry.register(Box::new(AlphaVantageProvider::new("k".into()).with_timeout(timeout)));
+        registry.register(Box::new(YahooChartProvider::new().with_timeout(timeout)));
+        registry.register(Box::new(YahooFinanceProvider::with_timeout(timeout).unwrap()));
+        assert_eq!(registry.len(), 6);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Request headers and the Yahoo session
+// ═══════════════════════════════════════════════════════════════════
+
+mod request_headers {
+    use super::*;
+    use savings_tracker_core::providers::http;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    type Requests = Arc<Mutex<Vec<String>>>;
+
+    /// HTTP server recording each request head (lowercased) and answering
+    /// with `respond(head)`: status line, extra headers and body.
+    async fn mock_server(
+        respond: fn(&str) -> (&'static str, &'static str, &'static str),
+    ) -> (String, Requests) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Requests::default();
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut head = Vec::new();
+                let mut buf = [0; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&head).to_lowercase();
+                let (status, headers, body) = respond(&head);
+                recorded.lock().unwrap().push(head);
+                let response = format!(
+                    "HTTP/1.1 {status}\r\n{headers}content-length: {}\r\n\
+                     connection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{addr}"), requests)
+    }
+
+    const CHART: &str = r#"{"chart": {"result": [{
+        "meta": {"regularMarketPrice": 229.98},
+        "timestamp": [1736951400],
+        "indicators": {"quote": [{"close": [237.87]}]}
+    }], "error": null}}"#;
+
+    #[tokio::test]
+    async fn shared_client_identifies_itself() {
+        let (url, requests) = mock_server(|_| ("200 OK", "", "{}")).await;
+        let settings =
+            Settings { contact_email: Some("me@example.com".into()), ..Settings::default() };
+        let client = http::shared_client(&settings).unwrap();
+        client.get(&url).send().await.unwrap();
+
+        let head = requests.lock().unwrap()[0].clone();
+        let user_agent = http::user_agent(&settings).to_lowercase();
+        assert!(user_agent.starts_with("savings-tracker/"));
+        assert!(head.contains(&format!("user-agent: {user_agent}\r\n")), "{head}");
+        assert!(head.contains("(+mailto:me@example.com)"), "{head}");
+        assert!(head.contains("accept: application/json\r\n"), "{head}");
+    }
+
+    #[test]
+    fn invalid_contact_email_is_rejected() {
+        let email = Some("not an email".into());
+        let settings = Settings { contact_email: email, ..Settings::default() };
+        let result = http::shared_client(&settings);
+        assert!(matches!(result, Err(CoreError::ValidationError(_))));
+        assert_eq!(http::user_agent(&Settings::default()).matches('(').count(), 0);
+    }
+
+    #[tokio::test]
+    async fn yahoo_starts_a_session_when_rejected() {
+        let (url, requests) = mock_server(|head| {
+            if head.starts_with("get /v1/test/getcrumb") {
+                ("200 OK", "content-type: text/plain\r\n", "abc.crumb")
+            } else if head.starts_with("get /v8/") && head.contains("crumb=abc.crumb") {
+                ("200 OK", "", CHART)
+            } else if head.starts_with("get /v8/") {
+                ("401 Unauthorized", "", r#"{"finance":{"error":"Invalid Crumb"}}"#)
+            } else {
+                ("404 Not Found", "set-cookie: A3=d=AQ; Domain=.yahoo.com; Secure\r\n", "")
+            }
+        })
+        .await;
+        let provider = YahooChartProvider::with_proxy(&url);
+
+        let price = provider.get_current_price("AAPL", "USD").await.unwrap();
+        assert_eq!(price, 229.98);
+        let heads = requests.lock().unwrap().clone();
+        let lines: Vec<&str> = heads.iter().map(|h| h.split(' ').nth(1).unwrap()).collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("/v8/finance/chart/aapl"));
+        assert_eq!(lines[1..3], ["/", "/v1/test/getcrumb"]);
+        assert!(lines[3].contains("crumb=abc.crumb"));
+        assert!(heads[2].contains("cookie: a3=d=aq\r\n"));
+        assert!(heads[3].contains("cookie: a3=d=aq\r\n"));
+        assert!(heads.iter().all(|h| h.contains("user-agent: mozilla/5.0")));
+
+        // The session is kept for later requests
+        provider.get_current_price("AAPL", "USD").await.unwrap();
+        let heads = requests.lock().unwrap().clone();
+        assert_eq!(heads.len(), 5);
+        assert!(heads[4].contains("crumb=abc.crumb"));
+    }
+
+    #[tokio::test]
+    async fn yahoo_without_crumb_fails_with_api_error() {
+        let (url, _) = mock_server(|head| {
+            if head.starts_with("get /v1/test/getcrumb") {
+                ("200 OK", "", "")
+            } else {
+                ("403 Forbidden", "", "")
+            }
+        })
+        .await;
+        let provider = YahooChartProvider::with_proxy(&url);
+
+        let err = provider.get_current_price("AAPL", "USD").await.unwrap_err();
+        assert!(
+            matches!(err, CoreError::Api { ref provider, .. } if provider == "Yahoo Finance"),
+            "unexpected error: {err}"
+        );
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// Providers over a stubbed HTTP client (recorded responses)
+// ═══════════════════════════════════════════════════════════════════
+
+mod stubbed_http {
+    use super::*;
+    use savings_tracker_core::providers::http::{HttpClient, HttpResponse};
+    use savings_tracker_core::testing::StubHttpClient;
+    use std::sync::Arc;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    fn assert_api_error(err: CoreError, expected: &str) {
+        assert!(
+            matches!(err, CoreError::Api { ref provider, .. } if provider == expected),
+            "expected an API error of {expected}, got {err:?}"
+        );
+    }
+
+    // 2025-01-15 and 2025-01-16, 00:00 UTC
+    const COINCAP_HISTORY: &str = r#"{"data": [
+        {"priceUsd": "99850.1234", "time": 1736899200000, "date": "2025-01-15T00:00:00.000Z"},
+        {"priceUsd": "101200.5", "time": 1736985600000, "date": "2025-01-16T00:00:00.000Z"}
+    ], "timestamp": 1737000000000}"#;
+
+    #[tokio::test]
+    async fn coincap_current_historical_and_range() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("/assets/bitcoin/history", 200, COINCAP_HISTORY)
+                .with_route("/assets/bitcoin", 200, r#"{"data": {"id": "bitcoin",
+                    "symbol": "BTC", "priceUsd": "100123.45"}, "timestamp": 1737000000000}"#),
+        );
+        let provider = CoinCapProvider::new().with_http_client(stub.clone());
+
+        assert_eq!(provider.get_current_price("BTC", "USD").await.unwrap(), 100123.45);
+        let price = provider.get_historical_price("btc", "USD", d(2025, 1, 15)).await.unwrap();
+        assert_eq!(price, 99850.1234);
+        let points =
+            provider.get_price_range("BTC", "USD", d(2025, 1, 15), d(2025, 1, 16)).await.unwrap();
+        assert_eq!(
+            points,
+            vec![
+                PricePoint { date: d(2025, 1, 15), price: 99850.1234 },
+                PricePoint { date: d(2025, 1, 16), price: 101200.5 },
+            ]
+        );
+        let requests = stub.requests();
+        assert_eq!(requests[0], "https://api.coincap.io/v2/assets/bitcoin");
+        assert!(requests[1].contains("interval=d1&start=1736899200000"));
+    }
+
+    #[tokio::test]
+    async fn coincap_searches_unknown_symbols() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("/assets?search=PEPE", 200, r#"{"data": [
+                    {"id": "pepe", "symbol": "PEPE", "rank": "30"}]}"#)
+                .with_route("/assets/pepe", 200, r#"{"data": {"priceUsd": "0.0000171"}}"#),
+        );
+        let provider = CoinCapProvider::new().with_http_client(stub.clone());
+
+        assert_eq!(provider.get_current_price("PEPE", "USD").await.unwrap(), 0.0000171);
+        assert_eq!(provider.learned_symbol_ids().get("PEPE").map(String::as_str), Some("pepe"));
+        assert!(stub.requests()[0].ends_with("/assets?search=PEPE&limit=10"));
+    }
+
+    #[tokio::test]
+    async fn coincap_malformed_responses_are_api_errors() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("/history", 200, r#"{"data": "oops"}"#)
+                .with_route("/assets/bitcoin", 200, "<html>Bad gateway</html>"),
+        );
+        let provider = CoinCapProvider::new().with_http_client(stub);
+
+        let err = provider.get_current_price("BTC", "USD").await.unwrap_err();
+        assert_api_error(err, "CoinCap");
+        let range = provider.get_price_range("BTC", "USD", d(2025, 1, 1), d(2025, 1, 2)).await;
+        assert_api_error(range.unwrap_err(), "CoinCap");
+    }
+
+    #[tokio::test]
+    async fn frankfurter_current_historical_and_range() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("/latest?base=EUR&symbols=PLN", 200, r#"{"amount": 1.0,
+                    "base": "EUR", "date": "2025-01-17", "rates": {"PLN": 4.2203}}"#)
+                .with_route("/2025-01-15?base=EUR", 200, r#"{"amount": 1.0, "base": "EUR",
+                    "date": "2025-01-15", "rates": {"PLN": 4.2271}}"#)
+                .with_route("..2025-01-14?base=EUR", 200, r#"{"amount": 1.0, "base": "EUR",
+                    "start_date": "2025-01-09", "end_date": "2025-01-14", "rates": {
+                        "2025-01-10": {"PLN": 4.2632},
+                        "2025-01-13": {"PLN": 4.2657},
+                        "2025-01-14": {"PLN": 4.2491}
+                    }}"#),
+        );
+        let provider = FrankfurterProvider::new().with_http_client(stub.clone());
+
+        assert_eq!(provider.get_current_price("eur", "pln").await.unwrap(), 4.2203);
+        let rate = provider.get_historical_price("EUR", "PLN", d(2025, 1, 15)).await.unwrap();
+        assert_eq!(rate, 4.2271);
+        // Saturday's rate is Friday's
+        let points =
+            provider.get_price_range("EUR", "PLN", d(2025, 1, 11), d(2025, 1, 14)).await.unwrap();
+        let dates: Vec<NaiveDate> = points.iter().map(|p| p.date).collect();
+        assert_eq!(dates, vec![d(2025, 1, 10), d(2025, 1, 13), d(2025, 1, 14)]);
+        assert!(stub.requests()[2].ends_with("/2025-01-04..2025-01-14?base=EUR&symbols=PLN"));
+    }
+
+    #[tokio::test]
+    async fn frankfurter_malformed_responses_are_api_errors() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("/latest", 200, r#"{"rates": {"PLN": "4.22"}}"#)
+                .with_route("..", 200, "not json")
+                .with_route("/2025-01-15", 200, r#"{"message": "not found"}"#),
+        );
+        let provider = FrankfurterProvider::new().with_http_client(stub);
+
+        let err = provider.get_current_price("EUR", "PLN").await.unwrap_err();
+        assert_api_error(err, "Frankfurter");
+        let historical = provider.get_historical_price("EUR", "PLN", d(2025, 1, 15)).await;
+        assert_api_error(historical.unwrap_err(), "Frankfurter");
+        let range = provider.get_price_range("EUR", "PLN", d(2025, 1, 1), d(2025, 1, 2)).await;
+        assert_api_error(range.unwrap_err(), "Frankfurter");
+    }
+
+    const METALS_TIMESERIES: &str = r#"{"status": "success", "currency": "USD", "unit": "toz",
+        "rates": {
+            "2025-01-03": {"date": "2025-01-03", "metals": {"gold": 2640.5, "silver": 29.7}},
+            "2025-01-02": {"date": "2025-01-02", "metals": {"gold": 2658.1, "silver": 29.9}}
+        }}"#;
+
+    #[tokio::test]
+    async fn metals_dev_current_historical_and_range() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("/latest", 200, r#"{"status": "success", "currency": "USD",
+                    "unit": "toz", "metals": {"gold": 2701.25, "silver": 30.5}}"#)
+                .with_route("/timeseries", 200, METALS_TIMESERIES),
+        );
+        let provider = MetalsDevProvider::new("secret".into()).with_http_client(stub.clone());
+
+        assert_eq!(provider.get_current_price("XAG", "USD").await.unwrap(), 30.5);
+        let price = provider.get_historical_price("xau", "USD", d(2025, 1, 3)).await.unwrap();
+        assert_eq!(price, 2640.5);
+        let points =
+            provider.get_price_range("XAU", "USD", d(2025, 1, 2), d(2025, 1, 3)).await.unwrap();
+        assert_eq!(
+            points,
+            vec![
+                PricePoint { date: d(2025, 1, 2), price: 2658.1 },
+                PricePoint { date: d(2025, 1, 3), price: 2640.5 },
+            ]
+        );
+        let requests = stub.requests();
+        assert!(requests[0].contains("api_key=secret&currency=USD&unit=toz"));
+        assert!(requests[1].contains("unit=toz&metal=gold&start_date=2025-01-03&end_date=2025-01-03"));
+    }
+
+    #[tokio::test]
+    async fn metals_dev_malformed_responses_are_api_errors() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("/latest", 200, r#"{"status": "success"}"#)
+                .with_route("/timeseries", 200, r#"{"status": "failure",
+                    "error_code": 1101, "error_message": "Invalid API key"}"#),
+        );
+        let provider = MetalsDevProvider::new("k".into()).with_http_client(stub);
+
+        assert_api_error(provider.get_current_price("XAU", "USD").await.unwrap_err(), "metals.dev");
+        let range = provider.get_price_range("XAU", "USD", d(2025, 1, 1), d(2025, 1, 2)).await;
+        assert_api_error(range.unwrap_err(), "metals.dev");
+    }
+
+    const AV_DAILY: &str = r#"{
+        "Meta Data": {"1. Information": "Daily Prices", "2. Symbol": "IBM"},
+        "Time Series (Daily)": {
+            "2025-01-16": {"1. open": "220.0", "4. close": "221.50", "5. volume": "100"},
+            "2025-01-15": {"1. open": "218.0", "4. close": "219.25", "5. volume": "120"}
+        }
+    }"#;
+
+    #[tokio::test]
+    async fn alphavantage_current_historical_and_range() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("function=GLOBAL_QUOTE", 200, r#"{"Global Quote": {
+                    "01. symbol": "IBM", "05. price": "222.1000"}}"#)
+                .with_route("function=TIME_SERIES_DAILY", 200, AV_DAILY),
+        );
+        let provider = AlphaVantageProvider::new("secret".into()).with_http_client(stub.clone());
+
+        assert_eq!(provider.get_current_price("ibm", "USD").await.unwrap(), 222.1);
+        let price = provider.get_historical_price("IBM", "USD", d(2025, 1, 15)).await.unwrap();
+        assert_eq!(price, 219.25);
+        let points =
+            provider.get_price_range("IBM", "USD", d(2025, 1, 16), d(2025, 1, 31)).await.unwrap();
+        assert_eq!(points, vec![PricePoint { date: d(2025, 1, 16), price: 221.5 }]);
+        assert!(stub.requests().iter().all(|url| url.ends_with("&apikey=secret")));
+    }
+
+    #[tokio::test]
+    async fn alphavantage_malformed_responses_are_api_errors() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("function=GLOBAL_QUOTE", 200, "{")
+                .with_route("function=TIME_SERIES_DAILY", 200, r#"{"Note": "5 calls per minute"}"#),
+        );
+        let provider = AlphaVantageProvider::new("k".into()).with_http_client(stub);
+
+        let err = provider.get_current_price("IBM", "USD").await.unwrap_err();
+        assert_api_error(err, "Alpha Vantage");
+        let range = provider.get_price_range("IBM", "USD", d(2025, 1, 1), d(2025, 1, 2)).await;
+        assert_api_error(range.unwrap_err(), "Alpha Vantage");
+    }
+
+    // 2025-01-14, 2025-01-15, 2025-01-16 (14:30 UTC market open)
+    const YAHOO_CHART: &str = r#"{"chart": {"result": [{
+        "meta": {"currency": "USD", "symbol": "AAPL", "regularMarketPrice": 229.98},
+        "timestamp": [1736865000, 1736951400, 1737037800],
+        "indicators": {"quote": [{"close": [233.28, 237.87, 228.26]}]}
+    }], "error": null}}"#;
+
+    #[tokio::test]
+    async fn yahoo_chart_current_historical_and_range() {
+        let stub =
+            Arc::new(StubHttpClient::new().with_route("/v8/finance/chart/AAPL", 200, YAHOO_CHART));
+        let provider = YahooChartProvider::new().with_http_client(stub.clone());
+
+        assert_eq!(provider.get_current_price("aapl", "USD").await.unwrap(), 229.98);
+        let price = provider.get_historical_price("AAPL", "USD", d(2025, 1, 15)).await.unwrap();
+        assert_eq!(price, 237.87);
+        let points =
+            provider.get_price_range("AAPL", "USD", d(2025, 1, 15), d(2025, 1, 16)).await.unwrap();
+        assert_eq!(points.len(), 2);
+        assert!(stub.requests()[0].ends_with("/AAPL?range=1d&interval=1d"));
+    }
+
+    #[tokio::test]
+    async fn yahoo_chart_malformed_responses_are_api_errors() {
+        let stub = Arc::new(
+            StubHttpClient::new()
+                .with_route("/v8/finance/chart/AAPL", 200, r#"{"chart": {"result": null}}"#)
+                .with_route("/v8/finance/chart/MSFT", 200, "[]"),
+        );
+        let provider = YahooChartProvider::new().with_http_client(stub);
+
+        let err = provider.get_current_price("AAPL", "USD").await.unwrap_err();
+        assert_api_error(err, "Yahoo Finance");
+        let range = provider.get_price_range("MSFT", "USD", d(2025, 1, 1), d(2025, 1, 2)).await;
+        assert_api_error(range.unwrap_err(), "Yahoo Finance");
+    }
+
+    #[tokio::test]
+    async fn keyless_provider_rejecting_a_request_falls_back_to_the_next() {
+        use savings_tracker_core::models::price::PriceCache;
+        use savings_tracker_core::services::price_service::PriceService;
+        use savings_tracker_core::testing::StaticPriceProvider;
+
+        let stub = Arc::new(StubHttpClient::new().with_route("/assets/bitcoin", 403, ""));
+        let mut registry = PriceProviderRegistry::new();
+        registry.register(Box::new(CoinCapProvider::new().with_http_client(stub)));
+        let date = d(2025, 1, 15);
+        registry.register(Box::new(StaticPriceProvider::new().with_price("BTC", "USD", date, 1.0)));
+        let service = PriceService::new(registry);
+
+        let mut cache = PriceCache::default();
+        let price = service.get_price(&mut cache, "BTC", "USD", date, &AssetType::Crypto).await;
+        assert_eq!(price.unwrap(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn statuses_map_to_core_errors() {
+        let stub = StubHttpClient::new()
+            .with_route("/ok", 200, r#"{"a": 1}"#)
+            .with_route("/limited", 429, "")
+            .with_route("/forbidden", 403, "")
+            .with_response("/cookie", HttpResponse::new(404, "").with_header("Set-Cookie", "A=1"));
+
+        let json = stub.get_json("P", "https://x.test/ok", &[]).await.unwrap();
+        assert_eq!(json["a"], 1);
+        let limited = stub.get_text("P", "https://x.test/limited", &[]).await.unwrap_err();
+        assert!(limited.is_transient());
+        // A bad key only if the request sent one
+        let forbidden = stub.get_text("P", "https://x.test/forbidden", &[]).await.unwrap_err();
+        assert!(matches!(forbidden, CoreError::Api { status: Some(403), .. }));
+        let forbidden =
+            stub.get_text_with_key("P", "https://x.test/forbidden", &[]).await.unwrap_err();
+        assert!(matches!(forbidden, CoreError::InvalidApiKey { .. }));
+        let cookie = stub.get("P", "https://x.test/cookie", &[]).await.unwrap();
+        assert_eq!(cookie.header_values("set-cookie").collect::<Vec<_>>(), vec!["A=1"]);
+        let missing = stub.get("P", "https://x.test/other", &[]).await.unwrap_err();
+        assert!(matches!(missing, CoreError::Network(_)));
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -636,9 +1615,11 @@ mod trait_compliance {
     fn providers_are_send_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
 
+        assert_send_sync::<AlphaVantageProvider>();
         assert_send_sync::<CoinCapProvider>();
         assert_send_sync::<FrankfurterProvider>();
         assert_send_sync::<MetalsDevProvider>();
+        assert_send_sync::<YahooChartProvider>();
         assert_send_sync::<YahooFinanceProvider>();
     }
 
@@ -0,0 +1,109 @@
+// ═══════════════════════════════════════════════════════════════════
+// Session Tests — recent files and preferences in a side JSON file
+// ═══════════════════════════════════════════════════════════════════
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use chrono::NaiveDate;
+use savings_tracker_core::errors::CoreError;
+use savings_tracker_core::formatting::Locale;
+use savings_tracker_core::session::{FilePreferences, SessionState, MAX_RECENT_FILES};
+
+fn path_str(path: &std::path::Path) -> String {
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_add_recent_moves_files_to_the_front() {
+    let mut session = SessionState::default();
+    session.add_recent("a.svtk");
+    session.add_recent("b.svtk");
+    session.add_recent("c.svtk");
+    let prefs = FilePreferences { hide_values: true, ..Default::default() };
+    assert!(session.set_preferences("a.svtk", prefs.clone()));
+
+    session.add_recent("a.svtk");
+    let paths: Vec<&str> = session.get_recent(10).iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["a.svtk", "c.svtk", "b.svtk"]);
+    assert_eq!(session.get_recent(1).len(), 1);
+    // Reopening keeps the preferences
+    assert_eq!(session.preferences("a.svtk"), Some(&prefs));
+    assert!(session.recent[0].last_opened >= session.recent[1].last_opened);
+
+    assert!(session.remove_recent("c.svtk"));
+    assert!(!session.remove_recent("c.svtk"));
+    assert!(!session.set_preferences("c.svtk", FilePreferences::default()));
+    assert_eq!(session.preferences("c.svtk"), None);
+}
+
+#[test]
+fn test_add_recent_keeps_the_newest_files() {
+    let mut session = SessionState::default();
+    for i in 0..MAX_RECENT_FILES + 5 {
+        session.add_recent(&format!("{i}.svtk"));
+    }
+    assert_eq!(session.recent.len(), MAX_RECENT_FILES);
+    assert_eq!(session.recent[0].path, format!("{}.svtk", MAX_RECENT_FILES + 4));
+    assert_eq!(session.recent.last().unwrap().path, "5.svtk");
+}
+
+#[test]
+fn test_remove_missing_prunes_nonexistent_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let kept = dir.path().join("kept.svtk");
+    let gone = dir.path().join("gone.svtk");
+    std::fs::write(&kept, b"").unwrap();
+    std::fs::write(&gone, b"").unwrap();
+
+    let mut session = SessionState::default();
+    session.add_recent(&path_str(&kept));
+    session.add_recent(&path_str(&gone));
+    std::fs::remove_file(&gone).unwrap();
+
+    assert_eq!(session.remove_missing(), 1);
+    assert_eq!(session.remove_missing(), 0);
+    assert_eq!(session.get_recent(10)[0].path, path_str(&kept));
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = path_str(&dir.path().join("config").join("session.json"));
+
+    // No file yet: an empty session
+    assert_eq!(SessionState::load(&path).unwrap(), SessionState::default());
+
+    let mut session = SessionState::default();
+    session.add_recent("/home/me/savings.svtk");
+    session.set_preferences(
+        "/home/me/savings.svtk",
+        FilePreferences {
+            display_currency: Some("EUR".into()),
+            locale: Some(Locale::Pl),
+            hide_values: false,
+        },
+    );
+    session.last_chart_range = Some((
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2025, 6, 30).unwrap(),
+    ));
+    session.save(&path).unwrap();
+
+    assert_eq!(SessionState::load(&path).unwrap(), session);
+
+    // Saving again replaces the file through a temporary one, which is gone
+    session.remove_recent("/home/me/savings.svtk");
+    session.save(&path).unwrap();
+    assert_eq!(SessionState::load(&path).unwrap(), session);
+    assert_eq!(std::fs::read_dir(dir.path().join("config")).unwrap().count(), 1);
+}
+
+#[test]
+fn test_load_rejects_invalid_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = path_str(&dir.path().join("session.json"));
+    std::fs::write(&path, "not json").unwrap();
+
+    let result = SessionState::load(&path);
+    assert!(matches!(result, Err(CoreError::Deserialization(_))));
+}
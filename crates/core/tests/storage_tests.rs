@@ -3,15 +3,22 @@
 // ═══════════════════════════════════════════════════════════════════
 
 use chrono::NaiveDate;
+use std::collections::HashMap;
 use savings_tracker_core::errors::CoreError;
-use savings_tracker_core::models::asset::Asset;
+use savings_tracker_core::models::accrual::{AccrualRule, Compounding};
+use savings_tracker_core::models::asset::{Asset, AssetType};
+use savings_tracker_core::models::corporate_action::CorporateAction;
 use savings_tracker_core::models::event::{Event, EventType};
 use savings_tracker_core::models::portfolio::Portfolio;
+use savings_tracker_core::models::price::{PricePoint, PriceResolution, PriceSource};
+use savings_tracker_core::models::reminder::Reminder;
 use savings_tracker_core::storage::encryption::{
     derive_key, decrypt, encrypt, generate_nonce, generate_salt, KdfParams,
 };
-use savings_tracker_core::storage::format::{self, CURRENT_VERSION, MAGIC, MIN_HEADER_SIZE};
-use savings_tracker_core::storage::manager::StorageManager;
+use savings_tracker_core::storage::format::{
+    self, CURRENT_VERSION, MAGIC, MIN_HEADER_SIZE, PAYLOAD_VERSION,
+};
+use savings_tracker_core::storage::manager::{LoadOptions, StorageManager};
 
 // ═══════════════════════════════════════════════════════════════════
 // KdfParams
@@ -492,7 +499,9 @@ mod file_format {
         let result = format::read_file(&file_bytes);
         assert!(result.is_err());
         match result.unwrap_err() {
-            CoreError::UnsupportedVersion(v) => assert_eq!(v, CURRENT_VERSION + 1),
+            CoreError::UnsupportedVersion { found, supported } => {
+                assert_eq!((found, supported), (CURRENT_VERSION + 1, CURRENT_VERSION));
+            }
             other => panic!("Expected UnsupportedVersion, got {:?}", other),
         }
     }
@@ -522,6 +531,60 @@ mod file_format {
         assert_eq!(header.ciphertext_len, 6);
     }
 
+    #[test]
+    fn huge_ciphertext_len_is_rejected_without_overflow() {
+        for len in [u64::MAX, usize::MAX as u64 - (MIN_HEADER_SIZE as u64 - 1), 1 << 40] {
+            let mut file_bytes = make_test_file(b"cipher");
+            file_bytes[MIN_HEADER_SIZE - 8..MIN_HEADER_SIZE].copy_from_slice(&len.to_le_bytes());
+
+            match format::read_file(&file_bytes) {
+                Err(CoreError::InvalidFileFormat(msg)) => assert!(msg.contains("truncated")),
+                other => panic!("Expected InvalidFileFormat, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn every_prefix_of_a_file_is_rejected_without_panicking() {
+        let file_bytes = make_test_file(b"some ciphertext");
+        for end in 0..file_bytes.len() {
+            assert!(format::read_file(&file_bytes[..end]).is_err(), "prefix of {end} bytes");
+        }
+        assert!(format::read_file(&file_bytes).is_ok());
+    }
+
+    #[test]
+    fn fuzz_seed_corpus_parses_as_named() {
+        // Files named valid_* parse (valid_file_* have an invalid payload);
+        // the rest are rejected
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/read_file");
+        let mut seeds = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            let parses = name.starts_with("valid_");
+            let data = std::fs::read(&path).unwrap();
+            assert_eq!(format::read_file(&data).is_ok(), parses, "{name}");
+            seeds += 1;
+        }
+        assert!(seeds > 10);
+    }
+
+    #[test]
+    fn file_over_the_size_limit_is_refused() {
+        let file_bytes = make_test_file(b"cipher");
+        let size = file_bytes.len() as u64;
+
+        assert!(format::read_file_with_limit(&file_bytes, size).is_ok());
+        match format::read_file_with_limit(&file_bytes, size - 1) {
+            Err(CoreError::FileTooLarge { size: found, limit }) => {
+                assert_eq!((found, limit), (size, size - 1));
+            }
+            other => panic!("Expected FileTooLarge, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(format::DEFAULT_MAX_FILE_SIZE, 512 * 1024 * 1024);
+    }
+
     #[test]
     fn file_header_is_debug() {
         let file_bytes = make_test_file(b"test");
@@ -537,8 +600,9 @@ mod file_format {
     }
 
     #[test]
-    fn current_version_is_one() {
-        assert_eq!(CURRENT_VERSION, 1);
+    fn current_version_is_two() {
+        assert_eq!(CURRENT_VERSION, 2);
+        assert_eq!(PAYLOAD_VERSION, 3);
     }
 
     #[test]
@@ -608,17 +672,356 @@ mod storage_manager {
     fn save_load_portfolio_with_price_cache() {
         let mut portfolio = Portfolio::default();
         let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
-        portfolio.price_cache.set_price("ETH", "USD", date, 3000.0);
+        portfolio.price_cache.set_price("ETH", "USD", date, 3000.0, &AssetType::Crypto);
         portfolio
             .price_cache
-            .set_price("XAU", "USD", date, 2050.0);
+            .set_price("XAU", "USD", date, 2050.0, &AssetType::Metal);
 
         let password = "cache-pw";
         let bytes = StorageManager::save_to_bytes(&portfolio, password).unwrap();
         let loaded = StorageManager::load_from_bytes(&bytes, password).unwrap();
 
-        assert_eq!(loaded.price_cache.get_price("ETH", "USD", date), Some(3000.0));
-        assert_eq!(loaded.price_cache.get_price("XAU", "USD", date), Some(2050.0));
+        assert_eq!(
+            loaded.price_cache.get_price("ETH", "USD", date, &AssetType::Crypto),
+            Some(3000.0),
+        );
+        assert_eq!(
+            loaded.price_cache.get_price("XAU", "USD", date, &AssetType::Metal),
+            Some(2050.0),
+        );
+    }
+
+    /// The first release's portfolio, mirrored here (rather than taken from
+    /// the library's reader) to write files of version 1.
+    #[derive(serde::Serialize)]
+    struct FirstReleasePortfolio {
+        events: Vec<FirstReleaseEvent>,
+        settings: FirstReleaseSettings,
+        price_cache: FirstReleasePriceCache,
+        trash: Vec<FirstReleaseEvent>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct FirstReleaseEvent {
+        id: uuid::Uuid,
+        event_type: EventType,
+        asset: Asset,
+        amount: f64,
+        date: NaiveDate,
+        notes: Option<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct FirstReleaseSettings {
+        default_currency: String,
+        api_keys: HashMap<String, String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct FirstReleasePriceCache {
+        entries: HashMap<(String, String), Vec<PricePoint>>,
+        last_updated: HashMap<(String, String), NaiveDate>,
+    }
+
+    /// A first-release portfolio buying `assets` (the last one trashed, if
+    /// `trash_last`), with a price and a refresh on `date` of every
+    /// (symbol, currency) in `series`.
+    fn first_release(
+        assets: &[Asset],
+        trash_last: bool,
+        series: &[(&str, &str)],
+        date: NaiveDate,
+    ) -> FirstReleasePortfolio {
+        let mut events: Vec<FirstReleaseEvent> = assets
+            .iter()
+            .map(|asset| FirstReleaseEvent {
+                id: uuid::Uuid::new_v4(),
+                event_type: EventType::Buy,
+                asset: asset.clone(),
+                amount: 1.0,
+                date,
+                notes: None,
+            })
+            .collect();
+        let trash = if trash_last { events.pop().into_iter().collect() } else { vec![] };
+        let mut price_cache =
+            FirstReleasePriceCache { entries: HashMap::new(), last_updated: HashMap::new() };
+        for (i, &(symbol, currency)) in series.iter().enumerate() {
+            let key = (symbol.to_string(), currency.to_string());
+            let point = PricePoint { date, price: 1.0 + i as f64 };
+            price_cache.entries.insert(key.clone(), vec![point]);
+            price_cache.last_updated.insert(key, date);
+        }
+        FirstReleasePortfolio {
+            events,
+            settings: FirstReleaseSettings {
+                default_currency: "EUR".into(),
+                api_keys: HashMap::new(),
+            },
+            price_cache,
+            trash,
+        }
+    }
+
+    /// Encrypted file of version 1 holding `portfolio`.
+    fn first_release_file(portfolio: &FirstReleasePortfolio, password: &str) -> Vec<u8> {
+        let plaintext = bincode::serialize(portfolio).unwrap();
+        let (salt, nonce) = (generate_salt().unwrap(), generate_nonce().unwrap());
+        let kdf = KdfParams::default();
+        let key = derive_key(password, &salt, &kdf).unwrap();
+        let ciphertext = encrypt(&plaintext, &key, &nonce).unwrap();
+        format::write_file(1, &kdf, &salt, &nonce, &ciphertext)
+    }
+
+    /// Encrypted file of the current format holding `payload`, as MessagePack
+    /// with field names, with schema version `payload_version`.
+    fn payload_file(payload: &impl serde::Serialize, payload_version: u16, password: &str) -> Vec<u8> {
+        let mut plaintext = payload_version.to_le_bytes().to_vec();
+        plaintext.extend(rmp_serde::to_vec_named(payload).unwrap());
+        let (salt, nonce) = (generate_salt().unwrap(), generate_nonce().unwrap());
+        let kdf = KdfParams::default();
+        let key = derive_key(password, &salt, &kdf).unwrap();
+        let ciphertext = encrypt(&plaintext, &key, &nonce).unwrap();
+        format::write_file(CURRENT_VERSION, &kdf, &salt, &nonce, &ciphertext)
+    }
+
+    /// A portfolio as a newer library might save it: a field added.
+    #[derive(serde::Serialize)]
+    struct FuturePortfolio {
+        #[serde(flatten)]
+        portfolio: Portfolio,
+        tags: Vec<String>,
+    }
+
+    fn future_portfolio() -> FuturePortfolio {
+        let mut portfolio = Portfolio::default();
+        portfolio.events.push(Event::new(
+            EventType::Buy,
+            Asset::crypto("BTC", "Bitcoin"),
+            1.0,
+            NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+        ));
+        FuturePortfolio { portfolio, tags: vec!["long-term".into()] }
+    }
+
+    #[test]
+    fn newer_payload_is_refused_with_both_versions() {
+        let bytes = payload_file(&future_portfolio(), PAYLOAD_VERSION + 1, "future-pw");
+
+        let result = StorageManager::load_from_bytes(&bytes, "future-pw");
+        match result {
+            Err(CoreError::UnsupportedVersion { found, supported }) => {
+                assert_eq!((found, supported), (PAYLOAD_VERSION + 1, PAYLOAD_VERSION));
+            }
+            other => panic!("Expected UnsupportedVersion, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn unknown_fields_of_a_current_payload_are_skipped() {
+        let future = future_portfolio();
+        let bytes = payload_file(&future, PAYLOAD_VERSION, "future-pw");
+
+        let loaded = StorageManager::load_from_bytes(&bytes, "future-pw").unwrap();
+        assert_eq!(loaded.events.len(), 1);
+        assert_eq!(loaded.events[0].id, future.portfolio.events[0].id);
+    }
+
+    #[test]
+    fn missing_fields_take_their_defaults() {
+        #[derive(serde::Serialize)]
+        struct EventsOnly {
+            events: Vec<Event>,
+        }
+        let events = future_portfolio().portfolio.events;
+        let bytes = payload_file(&EventsOnly { events: events.clone() }, PAYLOAD_VERSION, "pw");
+
+        let loaded = StorageManager::load_from_bytes(&bytes, "pw").unwrap();
+        assert_eq!(loaded.events, events);
+        assert_eq!(loaded.settings.default_currency, Portfolio::default().settings.default_currency);
+        assert!(loaded.reminders.is_empty());
+    }
+
+    #[test]
+    fn payload_is_messagepack_with_field_names() {
+        let bytes = StorageManager::save_to_bytes(&Portfolio::default(), "pw").unwrap();
+        let (header, ciphertext) = format::read_file(&bytes).unwrap();
+        let key = derive_key("pw", &header.salt, &header.kdf_params).unwrap();
+        let plaintext = decrypt(ciphertext, &key, &header.nonce).unwrap();
+
+        let (version, payload) = format::read_payload(header.version, &plaintext).unwrap();
+        assert_eq!(version, PAYLOAD_VERSION);
+        assert!(payload.windows(16).any(|w| w == b"default_currency"));
+    }
+
+    #[test]
+    fn save_load_keeps_price_sources() {
+        let mut portfolio = Portfolio::default();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let source = PriceSource::now("CoinGecko");
+        portfolio.price_cache.set_price("ETH", "USD", date, 3000.0, &AssetType::Crypto);
+        portfolio.price_cache.set_source("ETH", "USD", date, source.clone(), &AssetType::Crypto);
+
+        let bytes = StorageManager::save_to_bytes(&portfolio, "sources-pw").unwrap();
+        let loaded = StorageManager::load_from_bytes(&bytes, "sources-pw").unwrap();
+        let cache = &loaded.price_cache;
+        assert_eq!(cache.source("ETH", "USD", date, &AssetType::Crypto), Some(&source));
+    }
+
+    #[test]
+    fn save_load_keeps_month_closes() {
+        let mut portfolio = Portfolio::default();
+        let date = NaiveDate::from_ymd_opt(2015, 3, 31).unwrap();
+        portfolio.price_cache.set_monthly_price("ETH", "USD", date, 1.5, &AssetType::Crypto);
+
+        let bytes = StorageManager::save_to_bytes(&portfolio, "monthly-pw").unwrap();
+        let loaded = StorageManager::load_from_bytes(&bytes, "monthly-pw").unwrap();
+        let cache = &loaded.price_cache;
+        let resolution = cache.resolution("ETH", "USD", date, &AssetType::Crypto);
+        assert_eq!(resolution, PriceResolution::Monthly);
+    }
+
+    #[test]
+    fn save_load_keeps_corporate_actions() {
+        let mut portfolio = Portfolio::default();
+        let date = NaiveDate::from_ymd_opt(2020, 8, 31).unwrap();
+        portfolio.corporate_actions.push(CorporateAction::split("AAPL", 4.0, date));
+        portfolio.corporate_actions.push(CorporateAction::rename("FB", "META", date));
+
+        let bytes = StorageManager::save_to_bytes(&portfolio, "actions-pw").unwrap();
+        let loaded = StorageManager::load_from_bytes(&bytes, "actions-pw").unwrap();
+        assert_eq!(loaded.corporate_actions, portfolio.corporate_actions);
+    }
+
+    #[test]
+    fn save_load_keeps_accrual_rules() {
+        let mut portfolio = Portfolio::default();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        let eth = Asset::crypto("ETH", "Ethereum");
+        let mut rule = AccrualRule::new(eth, 3.5, Compounding::Daily, start).with_end_date(end);
+        rule.materialized_until = NaiveDate::from_ymd_opt(2025, 6, 30);
+        portfolio.accrual_rules.push(rule);
+
+        let bytes = StorageManager::save_to_bytes(&portfolio, "accrual-pw").unwrap();
+        let loaded = StorageManager::load_from_bytes(&bytes, "accrual-pw").unwrap();
+        assert_eq!(loaded.accrual_rules, portfolio.accrual_rules);
+    }
+
+    #[test]
+    fn save_load_keeps_reminders() {
+        let mut portfolio = Portfolio::default();
+        let date = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        let buy = Event::new(EventType::Buy, Asset::crypto("BTC", "Bitcoin"), 1.0, date);
+        let mut reminder = Reminder::new(buy.id, NaiveDate::from_ymd_opt(2025, 9, 30).unwrap());
+        reminder.acknowledged = true;
+        portfolio.events.push(buy);
+        portfolio.reminders.push(reminder);
+
+        let bytes = StorageManager::save_to_bytes(&portfolio, "reminder-pw").unwrap();
+        let loaded = StorageManager::load_from_bytes(&bytes, "reminder-pw").unwrap();
+        assert_eq!(loaded.reminders, portfolio.reminders);
+    }
+
+    #[test]
+    fn load_version_1_file_keys_cache_by_asset_type() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let assets = [Asset::crypto("BTC", "Bitcoin"), Asset::metal("XAU", "Gold")];
+        let series = [("BTC", "USD"), ("XAU", "USD"), ("USD", "EUR"), ("GLD", "USD")];
+        let bytes = first_release_file(&first_release(&assets, true, &series, date), "legacy-pw");
+
+        let loaded = StorageManager::load_from_bytes(&bytes, "legacy-pw").unwrap();
+        let cache = &loaded.price_cache;
+        assert_eq!(cache.get_price("BTC", "USD", date, &AssetType::Crypto), Some(1.0));
+        // XAU is only bought by the trashed event
+        assert_eq!(cache.get_price("XAU", "USD", date, &AssetType::Metal), Some(2.0));
+        assert_eq!(cache.get_price("USD", "EUR", date, &AssetType::Fiat), Some(3.0));
+        assert!(cache.is_today_fresh("BTC", "USD", date, &AssetType::Crypto));
+        // No asset in the portfolio tells what GLD is
+        assert_eq!(cache.asset_count(), 3);
+        assert_eq!(cache.last_updated.len(), 3);
+    }
+
+    #[test]
+    fn load_version_1_file_drops_series_of_ambiguous_symbols() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let assets = [Asset::stock("GLD", "SPDR Gold"), Asset::crypto("GLD", "Goldcoin")];
+        let series = [("GLD", "USD"), ("DOGE", "USD")];
+        let bytes = first_release_file(&first_release(&assets, false, &series, date), "legacy-pw");
+
+        let loaded = StorageManager::load_from_bytes(&bytes, "legacy-pw").unwrap();
+        assert_eq!(loaded.price_cache.asset_count(), 0);
+        assert!(loaded.price_cache.last_updated.is_empty());
+        assert_eq!(loaded.events.len(), 2);
+    }
+
+    /// Saved by the first release (file version 1), password
+    /// "golden-password".
+    const FIRST_RELEASE_FILE: &[u8] = include_bytes!("fixtures/legacy_bincode_v1.svtk");
+
+    #[test]
+    fn load_file_of_the_first_release() {
+        let loaded = StorageManager::load_from_bytes(FIRST_RELEASE_FILE, "golden-password").unwrap();
+
+        assert_eq!(loaded.settings.default_currency, "PLN");
+        assert_eq!(loaded.settings.api_keys["metals_dev"], "key-123");
+        // Settings added since take their default
+        assert_eq!(loaded.settings.pivot_currencies, ["USD", "EUR"]);
+        assert_eq!(loaded.settings.max_chart_range_days, Some(3650));
+        assert_eq!(loaded.events.len(), 5);
+        assert_eq!(loaded.events[0].notes.as_deref(), Some("first buy"));
+        // The trashed event is kept as a deleted one
+        assert!(loaded.trash.is_empty());
+        assert_eq!(loaded.events.iter().filter(|e| e.is_deleted()).count(), 1);
+        let date = NaiveDate::from_ymd_opt(2023, 1, 15).unwrap();
+        let cache = &loaded.price_cache;
+        assert_eq!(cache.get_price("BTC", "USD", date, &AssetType::Crypto), Some(21_000.0));
+        assert!(cache.derived.is_empty());
+    }
+
+    #[test]
+    fn load_file_of_the_first_release_resaves_in_the_current_version() {
+        let loaded = StorageManager::load_from_bytes(FIRST_RELEASE_FILE, "golden-password").unwrap();
+        let bytes = StorageManager::save_to_bytes(&loaded, "golden-password").unwrap();
+        assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]), CURRENT_VERSION);
+
+        let reloaded = StorageManager::load_from_bytes(&bytes, "golden-password").unwrap();
+        assert_eq!(reloaded.events, loaded.events);
+        assert_eq!(reloaded.trash, loaded.trash);
+    }
+
+    /// Payload version of encrypted `data`.
+    fn payload_version(data: &[u8], password: &str) -> u16 {
+        let (header, ciphertext) = format::read_file(data).unwrap();
+        let key = derive_key(password, &header.salt, &header.kdf_params).unwrap();
+        let plaintext = decrypt(ciphertext, &key, &header.nonce).unwrap();
+        format::read_payload(header.version, &plaintext).unwrap().0
+    }
+
+    #[test]
+    fn migrate_resaves_a_file_of_the_first_release_as_messagepack() {
+        assert_eq!(payload_version(FIRST_RELEASE_FILE, "golden-password"), 1);
+        let migrated = StorageManager::migrate(FIRST_RELEASE_FILE, "golden-password").unwrap();
+        assert_eq!(payload_version(&migrated, "golden-password"), PAYLOAD_VERSION);
+
+        let before = StorageManager::load_from_bytes(FIRST_RELEASE_FILE, "golden-password").unwrap();
+        let after = StorageManager::load_from_bytes(&migrated, "golden-password").unwrap();
+        // The trashed event is deleted when loaded, so compare all but that time
+        let events = |p: &Portfolio| -> Vec<_> {
+            p.events.iter().map(|e| (e.id, e.amount, e.notes.clone(), e.is_deleted())).collect()
+        };
+        assert_eq!(events(&after), events(&before));
+        assert_eq!(after.settings.api_keys, before.settings.api_keys);
+        let date = NaiveDate::from_ymd_opt(2023, 1, 15).unwrap();
+        assert_eq!(
+            after.price_cache.get_price("BTC", "USD", date, &AssetType::Crypto),
+            Some(21_000.0)
+        );
+
+        assert!(matches!(
+            StorageManager::migrate(FIRST_RELEASE_FILE, "wrong"),
+            Err(CoreError::Decryption)
+        ));
     }
 
     #[test]
@@ -789,6 +1192,57 @@ mod storage_manager {
 
         assert_eq!(loaded.events.len(), 100);
     }
+
+    #[test]
+    fn content_hash_ignores_map_order_and_price_cache() {
+        let event = Event::new(
+            EventType::Buy,
+            Asset::crypto("BTC", "Bitcoin"),
+            0.5,
+            NaiveDate::from_ymd_opt(2025, 2, 1).unwrap(),
+        );
+        let mut a = Portfolio::default();
+        let mut b = Portfolio::default();
+        a.events.push(event.clone());
+        b.events.push(event);
+        for (provider, key) in [("coingecko", "cg-key"), ("metals", "m-key"), ("yahoo", "y-key")] {
+            a.settings.api_keys.insert(provider.into(), key.into());
+        }
+        for (provider, key) in [("yahoo", "y-key"), ("coingecko", "cg-key"), ("metals", "m-key")] {
+            b.settings.api_keys.insert(provider.into(), key.into());
+        }
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        // Prices are not content
+        let date = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        b.price_cache.set_price("BTC", "USD", date, 100_000.0, &AssetType::Crypto);
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        b.events[0].amount = 0.6;
+        assert_ne!(a.content_hash(), b.content_hash());
+    }
+
+    #[test]
+    fn file_content_hash_matches_the_saved_portfolio() {
+        let mut portfolio = Portfolio::default();
+        portfolio.settings.default_currency = "EUR".into();
+        portfolio.events.push(Event::new(
+            EventType::Buy,
+            Asset::stock("AAPL", "Apple Inc."),
+            3.0,
+            NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+        ));
+
+        let bytes = StorageManager::save_to_bytes(&portfolio, "hash-pw").unwrap();
+        assert_eq!(
+            StorageManager::file_content_hash(&bytes, "hash-pw").unwrap(),
+            portfolio.content_hash()
+        );
+        assert!(matches!(
+            StorageManager::file_content_hash(&bytes, "wrong"),
+            Err(CoreError::Decryption)
+        ));
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -861,6 +1315,47 @@ mod file_io {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn oversized_file_is_refused_before_reading() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("huge.svtk");
+        // Sparse: takes no space, but would take 512 MiB of memory to read
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_len(format::DEFAULT_MAX_FILE_SIZE + 1).unwrap();
+
+        match StorageManager::load_from_file(path.to_str().unwrap(), "pw") {
+            Err(CoreError::FileTooLarge { size, limit }) => {
+                assert_eq!(
+                    (size, limit),
+                    (format::DEFAULT_MAX_FILE_SIZE + 1, format::DEFAULT_MAX_FILE_SIZE)
+                );
+            }
+            other => panic!("Expected FileTooLarge, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn size_limit_is_configurable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("limit.svtk");
+        let path_str = path.to_str().unwrap();
+        StorageManager::save_to_file(&Portfolio::default(), path_str, "pw").unwrap();
+        let size = std::fs::metadata(&path).unwrap().len();
+
+        let tight = LoadOptions { max_file_size: size - 1 };
+        assert!(matches!(
+            StorageManager::load_from_file_with(path_str, "pw", &tight),
+            Err(CoreError::FileTooLarge { .. })
+        ));
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(matches!(
+            StorageManager::load_from_bytes_with(&bytes, "pw", &tight),
+            Err(CoreError::FileTooLarge { .. })
+        ));
+        let exact = LoadOptions { max_file_size: size };
+        assert!(StorageManager::load_from_file_with(path_str, "pw", &exact).is_ok());
+    }
+
     #[test]
     fn file_has_svtk_content() {
         let dir = tempfile::tempdir().unwrap();
@@ -873,6 +1368,25 @@ mod file_io {
         let bytes = std::fs::read(&path).unwrap();
         assert_eq!(&bytes[0..4], b"SVTK");
     }
+
+    #[test]
+    fn concurrent_atomic_writes_never_mix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.svtk");
+        let path_str = path.to_str().unwrap();
+        let contents: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 256 * 1024]).collect();
+
+        std::thread::scope(|scope| {
+            for bytes in &contents {
+                scope.spawn(|| StorageManager::write_atomic(path_str, bytes).unwrap());
+            }
+        });
+
+        // One write wins whole, and no temporary file is left behind
+        let written = std::fs::read(&path).unwrap();
+        assert!(contents.contains(&written));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -1055,3 +1569,120 @@ mod kdf_bounds_validation {
     }
 }
 
+
+// ═══════════════════════════════════════════════════════════════════
+// CSV — RFC 4180 records and formula defusing
+// ═══════════════════════════════════════════════════════════════════
+
+mod csv_format {
+    use super::*;
+    use savings_tracker_core::storage::csv::{
+        events_from_csv, events_to_csv, parse_records, CsvColumn, CsvDelimiter, CsvOptions,
+    };
+
+    fn event(notes: &str) -> Event {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let asset = Asset::new("ACME", "Acme; \"Widgets\", Inc.", AssetType::Stock);
+        Event::with_notes(EventType::Buy, asset, 2.5, date, notes)
+    }
+
+    #[test]
+    fn parse_handles_quotes_line_breaks_and_line_ends() {
+        let csv = "a,\"b,1\",\"say \"\"hi\"\"\"\r\n\"multi\nline\",,x\nlast,row,";
+        let records = parse_records(csv, ',').unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["a", "b,1", "say \"hi\""],
+                vec!["multi\nline", "", "x"],
+                vec!["last", "row", ""],
+            ]
+        );
+        assert!(parse_records("a,\"open", ',').is_err());
+    }
+
+    #[test]
+    fn fields_are_quoted_for_the_chosen_delimiter() {
+        let events = [event("line one\nline two")];
+        let semicolon = CsvOptions { delimiter: CsvDelimiter::Semicolon, ..Default::default() };
+
+        let csv = events_to_csv(&events, |_| 4, &semicolon);
+        let mut lines = csv.split("\r\n");
+        let header = "id;event_type;symbol;name;asset_type;amount;date;notes";
+        assert_eq!(lines.next().unwrap(), header);
+        let row = lines.next().unwrap();
+        assert!(row.contains(";\"Acme; \"\"Widgets\"\", Inc.\";Stock;2.5;2025-01-15;"));
+        assert!(row.ends_with(";\"line one\nline two\""));
+        assert!(csv.ends_with("\r\n"));
+
+        // Commas need no quoting with semicolons, but do with commas
+        let comma = events_to_csv(&events, |_| 4, &CsvOptions::default());
+        assert!(comma.contains(",\"Acme; \"\"Widgets\"\", Inc.\",Stock,"));
+    }
+
+    #[test]
+    fn formula_cells_are_defused_and_restored() {
+        let events = [
+            event("=HYPERLINK(\"http://evil\")"),
+            event("+1 bonus"),
+            event("@SUM(A1)"),
+            event("-5% fee"),
+            event("plain 'quoted' note"),
+            event("'=already defused"),
+        ];
+        let csv = events_to_csv(&events, |_| 4, &CsvOptions::default());
+        assert!(csv.contains(",\"'=HYPERLINK(\"\"http://evil\"\")\"\r\n"));
+        assert!(csv.contains(",'+1 bonus\r\n"));
+        assert!(csv.contains(",'@SUM(A1)\r\n"));
+        assert!(csv.contains(",'-5% fee\r\n"));
+        assert!(csv.contains(",plain 'quoted' note\r\n"));
+        assert!(csv.contains(",''=already defused\r\n"));
+
+        let read = events_from_csv(&csv, &CsvOptions::default()).unwrap();
+        assert_eq!(read, events);
+
+        let raw = CsvOptions { defuse_formulas: false, ..Default::default() };
+        let csv = events_to_csv(&events, |_| 4, &raw);
+        assert!(csv.contains(",+1 bonus\r\n"));
+        assert_eq!(events_from_csv(&csv, &raw).unwrap(), events);
+    }
+
+    #[test]
+    fn import_finds_columns_by_header() {
+        let csv = "\u{feff}Date;Amount;Symbol;Asset_Type;Event_Type;extra\n\
+                   2025-01-15;0.5;btc;crypto;sell;ignored\n\n";
+        let options = CsvOptions { delimiter: CsvDelimiter::Semicolon, ..Default::default() };
+        let events = events_from_csv(csv, &options).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, EventType::Sell);
+        assert_eq!(events[0].asset, Asset::crypto("BTC", "BTC"));
+        assert_eq!(events[0].amount, 0.5);
+        assert!(events[0].notes.is_none());
+
+        let missing = events_from_csv("symbol,amount,date\nBTC,1,2025-01-01", &options);
+        assert!(matches!(missing, Err(CoreError::Deserialization(_))));
+        let bad_type = "event_type,symbol,asset_type,amount,date\nSwap,BTC,Crypto,1,2025-01-01";
+        match events_from_csv(bad_type, &CsvOptions::default()) {
+            Err(CoreError::Deserialization(msg)) => assert!(msg.contains("row 2"), "{msg}"),
+            other => panic!("Expected Deserialization error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn recorded_values_round_trip_with_all_columns() {
+        let mut event = event("");
+        event.notes = None;
+        event.recorded_value = Some(savings_tracker_core::models::event::RecordedValue {
+            quote_currency: "USD".into(),
+            quote_unit_price: 101.25,
+            currency: "PLN".into(),
+            unit_price: 405.5,
+        });
+        let options = CsvOptions { columns: CsvColumn::ALL.to_vec(), ..Default::default() };
+
+        let csv = events_to_csv([&event], |_| 4, &options);
+        let header = csv.lines().next().unwrap();
+        assert!(header.ends_with(",quote_currency,quote_unit_price,currency,unit_price"));
+        assert_eq!(events_from_csv(&csv, &options).unwrap(), vec![event]);
+    }
+}
@@ -0,0 +1,218 @@
+// ═══════════════════════════════════════════════════════════════════
+// Tracing Tests — span fields emitted with the "tracing" feature
+// ═══════════════════════════════════════════════════════════════════
+
+#![cfg(feature = "tracing")]
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use savings_tracker_core::errors::CoreError;
+use savings_tracker_core::models::asset::AssetType;
+use savings_tracker_core::models::portfolio::Portfolio;
+use savings_tracker_core::models::price::{PriceCache, PricePoint};
+use savings_tracker_core::providers::registry::PriceProviderRegistry;
+use savings_tracker_core::providers::traits::PriceProvider;
+use savings_tracker_core::services::price_service::PriceService;
+use savings_tracker_core::storage::manager::StorageManager;
+
+// ═══════════════════════════════════════════════════════════════════
+// Test Helpers — field-capturing layer and a fixed-price provider
+// ═══════════════════════════════════════════════════════════════════
+
+/// (span name, field name, value) triples recorded on spans.
+type Captured = Arc<Mutex<Vec<(String, String, String)>>>;
+
+struct CaptureLayer {
+    captured: Captured,
+}
+
+struct FieldVisitor<'a> {
+    span: &'a str,
+    captured: &'a Captured,
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.captured.lock().unwrap().push((
+            self.span.to_string(),
+            field.name().to_string(),
+            format!("{value:?}"),
+        ));
+    }
+}
+
+impl<S> Layer<S> for CaptureLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let span = attrs.metadata().name();
+        attrs.record(&mut FieldVisitor {
+            span,
+            captured: &self.captured,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).map(|s| s.name()).unwrap_or_default();
+        values.record(&mut FieldVisitor {
+            span,
+            captured: &self.captured,
+        });
+    }
+}
+
+fn capture() -> (impl Subscriber, Captured) {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::registry().with(CaptureLayer {
+        captured: captured.clone(),
+    });
+    (subscriber, captured)
+}
+
+fn field_values(captured: &Captured, span: &str, field: &str) -> Vec<String> {
+    captured
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(s, f, _)| s == span && f == field)
+        .map(|(_, _, v)| v.clone())
+        .collect()
+}
+
+struct FixedPriceProvider;
+
+#[async_trait]
+impl PriceProvider for FixedPriceProvider {
+    fn name(&self) -> &str {
+        "Fixed"
+    }
+
+    fn supported_asset_types(&self) -> Vec<AssetType> {
+        vec![AssetType::Crypto]
+    }
+
+    async fn get_current_price(&self, _symbol: &str, _currency: &str) -> Result<f64, CoreError> {
+        Ok(100.0)
+    }
+
+    async fn get_historical_price(
+        &self,
+        _symbol: &str,
+        _currency: &str,
+        _date: NaiveDate,
+    ) -> Result<f64, CoreError> {
+        Ok(100.0)
+    }
+
+    async fn get_price_range(
+        &self,
+        _symbol: &str,
+        _currency: &str,
+        from: NaiveDate,
+        _to: NaiveDate,
+    ) -> Result<Vec<PricePoint>, CoreError> {
+        Ok(vec![PricePoint {
+            date: from,
+            price: 100.0,
+        }])
+    }
+}
+
+fn price_service() -> PriceService {
+    let mut registry = PriceProviderRegistry::new();
+    registry.register(Box::new(FixedPriceProvider));
+    PriceService::new(registry)
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// PriceService spans
+// ═══════════════════════════════════════════════════════════════════
+
+mod price_service_spans {
+    use super::*;
+
+    #[tokio::test]
+    async fn get_price_records_cache_hit_and_provider() {
+        let (subscriber, captured) = capture();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let svc = price_service();
+        let mut cache = PriceCache::new();
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        // First call misses the cache, second is served from it
+        svc.get_price(&mut cache, "BTC", "USD", date, &AssetType::Crypto)
+            .await
+            .unwrap();
+        svc.get_price(&mut cache, "BTC", "USD", date, &AssetType::Crypto)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            field_values(&captured, "get_price", "cache_hit"),
+            vec!["false".to_string(), "true".to_string()]
+        );
+        assert_eq!(
+            field_values(&captured, "get_price", "provider"),
+            vec!["\"Fixed\"".to_string()]
+        );
+        assert!(field_values(&captured, "get_price", "symbol")
+            .iter()
+            .any(|v| v.contains("BTC")));
+    }
+
+    #[tokio::test]
+    async fn get_price_range_records_cache_miss() {
+        let (subscriber, captured) = capture();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let svc = price_service();
+        let mut cache = PriceCache::new();
+        let from = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 1, 20).unwrap();
+
+        svc.get_price_range(&mut cache, "BTC", "USD", from, to, &AssetType::Crypto)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            field_values(&captured, "get_price_range", "cache_hit"),
+            vec!["false".to_string()]
+        );
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════
+// StorageManager spans
+// ═══════════════════════════════════════════════════════════════════
+
+mod storage_spans {
+    use super::*;
+
+    #[test]
+    fn save_records_sizes_but_never_the_password() {
+        let (subscriber, captured) = capture();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let password = "super-secret-password";
+        let bytes = StorageManager::save_to_bytes(&Portfolio::default(), password).unwrap();
+
+        assert_eq!(
+            field_values(&captured, "save_to_bytes", "file_bytes"),
+            vec![bytes.len().to_string()]
+        );
+        assert!(captured
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|(_, _, v)| !v.contains(password)));
+    }
+}